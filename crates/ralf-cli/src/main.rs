@@ -1,12 +1,28 @@
 //! ralf CLI: Command-line interface for multi-model autonomous loops
 
 use clap::{Parser, Subcommand};
+use ralf_engine::safety::violation_summary;
+use ralf_engine::secrets::secrets_warning;
+use ralf_engine::telemetry;
+use ralf_engine::thread::{Thread, ThreadBranch, ThreadPhase};
 use ralf_engine::{
-    check_promise, discover_models, get_git_info, hash_prompt, invoke_model, probe_model,
-    run_verifier, select_model, write_changelog_entry, ChangelogEntry, Config, Cooldowns,
-    IterationStatus, RunState, RunStatus,
+    discover_models, events_path, export_run, find_leftover_processes, find_template, format_start,
+    format_wait, gc, generate_commit_message, generate_token, hash_prompt, import_run,
+    kill_all_process_trees, latest_run, list_logs, models_ready_at, no_op_warning, parse_criteria,
+    parse_event_line, parse_log, parse_start, probe_model, read_log, refine_commit_message,
+    report_path, run_health_checks, run_preflight_with_probes, save_warning, target_root,
+    user_templates_dir, Analytics, ChangelogStore, ChangelogSummary, CheckStatus, Config,
+    Cooldowns, CronSchedule, EventRecord, EventSink, EventTail, GitSafety, Isolation,
+    IterationStatus, LayeredConfig, LogFile, MultiSink, PendingStart, PersistenceSink, Queue,
+    QueueStatus, RalfDirs, RemoteAction, RemoteClient, RemoteServer, ReportFormat, ReviewChecklist,
+    RunEvent, RunLock, RunReport, RunState, RunStatus, Runner, ServeInfo, ThreadStore, UsageLedger,
+    RALF_DIR,
 };
-use std::path::Path;
+use ralf_tui::notify::{notify, NotificationConfig, NotifyEvent};
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 /// Multi-model autonomous loop engine with TUI
@@ -16,11 +32,36 @@ use std::time::{Duration, Instant};
 struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
+
+    /// Log debug detail to .ralf/logs/engine.log
+    #[arg(long, short, global = true)]
+    verbose: bool,
 }
 
 #[derive(Subcommand)]
 enum Commands {
-    /// Detect models and print diagnostics
+    /// Open the shell TUI (the default without a command)
+    Shell {
+        /// Print a single frame of the shell and exit, for debugging layouts
+        /// and golden-file comparisons
+        #[arg(long)]
+        render_once: bool,
+
+        /// Frame width in columns
+        #[arg(long, default_value = "120", requires = "render_once")]
+        width: u16,
+
+        /// Frame height in rows
+        #[arg(long, default_value = "40", requires = "render_once")]
+        height: u16,
+
+        /// JSON fixture describing the state to render (default: an empty
+        /// shell)
+        #[arg(long, value_name = "FIXTURE", requires = "render_once")]
+        state: Option<PathBuf>,
+    },
+
+    /// Detect models and check repository and environment health
     Doctor {
         /// Output as JSON
         #[arg(long)]
@@ -28,7 +69,12 @@ enum Commands {
     },
 
     /// Initialize .ralf/ directory and config
-    Init,
+    Init {
+        /// Scaffold PROMPT.md from a template (bugfix, feature, refactor,
+        /// test-coverage, or one in `~/.config/ralf/templates/`)
+        #[arg(long)]
+        template: Option<String>,
+    },
 
     /// Probe models with timeout to detect auth prompts/hangs
     Probe {
@@ -55,13 +101,87 @@ enum Commands {
         #[arg(long)]
         max_seconds: Option<u64>,
 
-        /// Run on a specific branch
+        /// Run on a specific branch, creating it from the current one if
+        /// needed (not with worktree isolation)
         #[arg(long)]
         branch: Option<String>,
 
+        /// Directory to work in, relative to the repository or absolute
+        /// (e.g. services/api in a monorepo, or another repository)
+        #[arg(long, value_name = "DIR")]
+        target: Option<PathBuf>,
+
         /// Models to use (comma-separated, e.g. claude,codex,gemini)
         #[arg(long, value_delimiter = ',')]
         models: Option<Vec<String>>,
+
+        /// Resume an interrupted run from its last completed iteration
+        #[arg(long, value_name = "RUN_ID")]
+        resume: Option<String>,
+
+        /// Resume even if PROMPT.md changed since the run started, and take
+        /// over a run lock whose holder stopped responding
+        #[arg(long)]
+        force: bool,
+
+        /// Stop once the run's reported cost reaches this many US dollars
+        #[arg(long, value_name = "USD")]
+        max_cost: Option<f64>,
+
+        /// Wall-clock cap per iteration in seconds, including verifiers
+        #[arg(long, value_name = "SECONDS")]
+        iteration_timeout: Option<u64>,
+
+        /// Wait until this time to start, e.g. 02:00, 2am or
+        /// "2026-01-13 02:00"
+        #[arg(long, value_name = "TIME", conflicts_with = "cron")]
+        at: Option<String>,
+
+        /// Start a run each time this cron expression matches, e.g.
+        /// "0 2 * * *", until interrupted
+        #[arg(long, value_name = "EXPR", conflicts_with = "resume")]
+        cron: Option<String>,
+
+        /// If every model is in cooldown, wait and start the run as soon
+        /// as the first comes back, instead of failing
+        #[arg(long)]
+        wait_for_models: bool,
+
+        /// Serve Prometheus metrics at http://<ADDR>/metrics while running
+        /// (needs ralf built with the `metrics` feature)
+        #[arg(long, value_name = "ADDR")]
+        metrics_addr: Option<SocketAddr>,
+    },
+
+    /// Run the loop headless and serve its status, events and controls
+    /// (cancel, pause, resume, approve, reject) over a local HTTP API, so
+    /// other processes can attach to it
+    Serve {
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:7878")]
+        addr: SocketAddr,
+
+        /// Token clients must send (default: a random one); written with
+        /// the address to .ralf/serve.json
+        #[arg(long)]
+        token: Option<String>,
+
+        /// Maximum number of iterations
+        #[arg(long)]
+        max_iterations: Option<u64>,
+
+        /// Maximum runtime in seconds
+        #[arg(long)]
+        max_seconds: Option<u64>,
+
+        /// Resume an interrupted run from its last completed iteration
+        #[arg(long, value_name = "RUN_ID")]
+        resume: Option<String>,
+
+        /// Resume even if PROMPT.md changed since the run started, and take
+        /// over a run lock whose holder stopped responding
+        #[arg(long)]
+        force: bool,
     },
 
     /// Print current state and cooldowns
@@ -73,26 +193,233 @@ enum Commands {
 
     /// Cancel the current run
     Cancel,
+
+    /// Put models in cooldown or end their cooldowns; a running loop picks
+    /// changes up at its next iteration
+    Cooldown {
+        #[command(subcommand)]
+        action: CooldownAction,
+    },
+
+    /// Disable or re-enable models for selection; a running loop picks
+    /// changes up at its next iteration
+    Model {
+        #[command(subcommand)]
+        action: ModelAction,
+    },
+
+    /// Queue finalized threads and run them one after another
+    Queue {
+        #[command(subcommand)]
+        action: QueueAction,
+    },
+
+    /// Print a run's event log (`.ralf/runs/<run_id>/events.jsonl`)
+    Events {
+        /// Run ID
+        run_id: String,
+
+        /// Keep printing new events until the run finishes
+        #[arg(long, short)]
+        follow: bool,
+
+        /// Print raw JSONL records
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Print a run's model and verifier logs
+    Logs {
+        /// Run ID (default: the most recent run)
+        run_id: Option<String>,
+
+        /// Only show logs from this iteration
+        #[arg(long, short)]
+        iteration: Option<u64>,
+
+        /// Only show logs from this model or verifier
+        #[arg(long, short)]
+        model: Option<String>,
+
+        /// Keep printing new logs until the run finishes
+        #[arg(long, short)]
+        follow: bool,
+    },
+
+    /// Write a shareable report of a run (iterations, verifiers, criteria,
+    /// diff stats)
+    Report {
+        /// Run ID (default: the most recent run)
+        run_id: Option<String>,
+
+        /// Report format: md or html
+        #[arg(long, short, default_value = "md")]
+        format: ReportFormat,
+
+        /// Output file (default: `.ralf/reports/<run_id>.<md|html>`)
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+
+    /// Summarize iteration outcomes from `.ralf/changelog/`
+    Changelog {
+        /// Only include this run
+        #[arg(long, value_name = "RUN_ID")]
+        run: Option<String>,
+
+        /// Only include iterations with this status (success, `rate_limited`,
+        /// timeout, error, `verifier_failed`, cancelled, `needs_attention`,
+        /// `no_op`)
+        #[arg(long)]
+        status: Option<IterationStatus>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Print a commit message for a run, written from its history
+    CommitMsg {
+        /// Run ID (default: the most recent run)
+        run_id: Option<String>,
+
+        /// Have this model polish the message
+        #[arg(long)]
+        model: Option<String>,
+    },
+
+    /// Print per-model analytics across all past runs
+    Stats {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Bundle a run's logs, events, changelog, spec and config into one
+    /// file, or import such a bundle
+    Archive {
+        /// Run ID (default: the most recent run)
+        #[arg(conflicts_with = "import")]
+        run_id: Option<String>,
+
+        /// Output file (default: `<run_id>.tar.zst`)
+        #[arg(long, short, conflicts_with = "import")]
+        output: Option<PathBuf>,
+
+        /// Import the run in this archive into `.ralf/`
+        #[arg(long, value_name = "FILE")]
+        import: Option<PathBuf>,
+    },
+
+    /// Prune old runs, changelog entries and draft snapshots, as the
+    /// config's `retention` policy allows
+    Gc {
+        /// Only list what would be removed
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum CooldownAction {
+    /// Put a model in cooldown
+    Set {
+        /// Model name
+        model: String,
+
+        /// Cooldown length in seconds
+        seconds: u64,
+    },
+
+    /// End a model's cooldown, or every model's
+    Clear {
+        /// Model name (default: all models)
+        model: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ModelAction {
+    /// Stop using a model to implement or verify
+    Disable {
+        /// Model name
+        model: String,
+    },
+
+    /// Use a disabled model again
+    Enable {
+        /// Model name
+        model: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum QueueAction {
+    /// Queue a finalized thread
+    Add {
+        /// Thread ID (or a unique prefix of it)
+        thread_id: String,
+    },
+
+    /// Take a thread out of the queue
+    Remove {
+        /// Thread ID (or a unique prefix of it)
+        thread_id: String,
+    },
+
+    /// List queued threads and how finished ones went
+    List,
+
+    /// Run the queued threads one after another until the queue is empty
+    Start,
 }
 
-const RALF_DIR: &str = ".ralf";
+/// How often a scheduled run waiting to start rechecks the clock and
+/// cooldowns. Short enough to start on time after the machine sleeps.
+const SCHEDULE_POLL: Duration = Duration::from_secs(30);
+
+/// How often, in seconds, a run waiting to start reports the time left.
+const WAIT_PROGRESS_SECS: u64 = 300;
 
+/// How long each model gets to answer a queued thread's preflight probe.
+const QUEUE_PROBE_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[allow(clippy::too_many_lines)]
 fn main() {
     let cli = Cli::parse();
 
+    // Only log inside a ralf project, so commands run elsewhere leave no trace
+    let ralf_dir = Path::new(RALF_DIR);
+    if ralf_dir.is_dir() {
+        if let Err(e) = telemetry::init(ralf_dir, cli.verbose) {
+            eprintln!("Warning: engine log unavailable: {e}");
+        }
+    }
+
     match cli.command {
-        None => {
+        None
+        | Some(Commands::Shell {
+            render_once: false, ..
+        }) => {
             // Default: open the shell TUI
             if let Err(e) = ralf_tui::run_shell_tui() {
                 eprintln!("Error: {e}");
                 std::process::exit(1);
             }
         }
+        Some(Commands::Shell {
+            render_once: true,
+            width,
+            height,
+            state,
+        }) => {
+            cmd_render_once(width, height, state.as_deref());
+        }
         Some(Commands::Doctor { json }) => {
             cmd_doctor(json);
         }
-        Some(Commands::Init) => {
-            cmd_init();
+        Some(Commands::Init { template }) => {
+            cmd_init(template.as_deref());
         }
         Some(Commands::Probe {
             json,
@@ -105,9 +432,42 @@ fn main() {
             max_iterations,
             max_seconds,
             branch,
+            target,
             models,
+            resume,
+            force,
+            max_cost,
+            iteration_timeout,
+            at,
+            cron,
+            wait_for_models,
+            metrics_addr,
+        }) => {
+            cmd_run(
+                max_iterations,
+                max_seconds,
+                branch.as_deref(),
+                target.as_deref(),
+                models,
+                resume,
+                force,
+                max_cost,
+                iteration_timeout,
+                at.as_deref(),
+                cron.as_deref(),
+                wait_for_models,
+                metrics_addr,
+            );
+        }
+        Some(Commands::Serve {
+            addr,
+            token,
+            max_iterations,
+            max_seconds,
+            resume,
+            force,
         }) => {
-            cmd_run(max_iterations, max_seconds, branch, models);
+            cmd_serve(addr, token, max_iterations, max_seconds, resume, force);
         }
         Some(Commands::Status { json }) => {
             cmd_status(json);
@@ -115,17 +475,89 @@ fn main() {
         Some(Commands::Cancel) => {
             cmd_cancel();
         }
+        Some(Commands::Cooldown { action }) => {
+            cmd_cooldown(action);
+        }
+        Some(Commands::Model { action }) => {
+            cmd_model(action);
+        }
+        Some(Commands::Queue { action }) => {
+            cmd_queue(action);
+        }
+        Some(Commands::Events {
+            run_id,
+            follow,
+            json,
+        }) => {
+            cmd_events(&run_id, follow, json);
+        }
+        Some(Commands::Logs {
+            run_id,
+            iteration,
+            model,
+            follow,
+        }) => {
+            cmd_logs(run_id, iteration, model.as_deref(), follow);
+        }
+        Some(Commands::Report {
+            run_id,
+            format,
+            output,
+        }) => {
+            cmd_report(run_id, format, output);
+        }
+        Some(Commands::Changelog { run, status, json }) => {
+            cmd_changelog(run.as_deref(), status, json);
+        }
+        Some(Commands::CommitMsg { run_id, model }) => {
+            cmd_commit_msg(run_id, model.as_deref());
+        }
+        Some(Commands::Stats { json }) => {
+            cmd_stats(json);
+        }
+        Some(Commands::Archive {
+            run_id,
+            output,
+            import,
+        }) => {
+            cmd_archive(run_id, output, import.as_deref());
+        }
+        Some(Commands::Gc { dry_run }) => {
+            cmd_gc(dry_run);
+        }
+    }
+}
+
+fn cmd_render_once(width: u16, height: u16, state: Option<&Path>) {
+    match ralf_tui::render_shell_once(width, height, state) {
+        Ok(frame) => println!("{frame}"),
+        Err(e) => {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        }
     }
 }
 
 fn cmd_doctor(json: bool) {
     let result = discover_models();
+    let layered = Config::load_layered(Path::new("."));
+    let health = run_health_checks(Path::new("."));
 
     if json {
+        let mut report = serde_json::to_value(&result).expect("failed to serialize");
+        report["config"] = match &layered {
+            Ok(layered) => serde_json::to_value(layered).expect("failed to serialize"),
+            Err(e) => serde_json::json!({ "error": e.to_string() }),
+        };
+        report["status"] = serde_json::to_value(health.status()).expect("failed to serialize");
+        report["checks"] = serde_json::to_value(&health.checks).expect("failed to serialize");
         println!(
             "{}",
-            serde_json::to_string_pretty(&result).expect("failed to serialize")
+            serde_json::to_string_pretty(&report).expect("failed to serialize")
         );
+        if health.status() == CheckStatus::Fail {
+            std::process::exit(1);
+        }
         return;
     }
 
@@ -156,11 +588,71 @@ fn cmd_doctor(json: bool) {
 
     let ready_count = result.models.iter().filter(|m| m.callable).count();
     println!("{ready_count} model(s) ready");
+
+    println!("\nConfiguration\n");
+    match layered {
+        Ok(layered) => print_config_sources(&layered),
+        Err(e) => println!("  Error: {e}"),
+    }
+
+    println!("\nHealth Checks\n");
+    let width = health
+        .checks
+        .iter()
+        .map(|c| c.label.len())
+        .max()
+        .unwrap_or(0);
+    for check in &health.checks {
+        let marker = match check.status {
+            CheckStatus::Pass => "ok  ",
+            CheckStatus::Warn => "WARN",
+            CheckStatus::Fail => "FAIL",
+        };
+        println!("  [{marker}] {:<width$}  {}", check.label, check.message);
+        if let Some(fix) = &check.remediation {
+            println!("         {:<width$}  -> {fix}", "");
+        }
+    }
+    println!("\n{}", health.summary());
+
+    if health.status() == CheckStatus::Fail {
+        std::process::exit(1);
+    }
+}
+
+/// Print config file locations and where each setting came from.
+fn print_config_sources(layered: &LayeredConfig) {
+    let describe = |path: &Path| {
+        if path.exists() {
+            path.display().to_string()
+        } else {
+            format!("{} (not found)", path.display())
+        }
+    };
+    match &layered.global_path {
+        Some(path) => println!("  Global: {}", describe(path)),
+        None => println!("  Global: (no home directory)"),
+    }
+    println!("  Repo:   {}", describe(&layered.repo_path));
+    println!();
+
+    let width = layered.sources.keys().map(String::len).max().unwrap_or(0);
+    for (key, source) in &layered.sources {
+        println!("  {key:<width$}  {source}");
+    }
 }
 
-fn cmd_init() {
+fn cmd_init(template: Option<&str>) {
     let ralf_dir = Path::new(RALF_DIR);
 
+    // Resolve the template first, so a typo doesn't leave a half-done init
+    let template = template.map(|name| {
+        find_template(name, user_templates_dir().as_deref()).unwrap_or_else(|e| {
+            eprintln!("{e}");
+            std::process::exit(1);
+        })
+    });
+
     // Create directory structure
     let dirs = ["runs", "changelog"];
     for dir in dirs {
@@ -204,7 +696,15 @@ fn cmd_init() {
 
     // Check for prompt file (at repo root, not in .ralf/)
     let prompt_path = Path::new("PROMPT.md");
-    if !prompt_path.exists() {
+    if prompt_path.exists() {
+        if let Some(template) = &template {
+            println!(
+                "{} already exists; not applying the {} template",
+                prompt_path.display(),
+                template.name
+            );
+        }
+    } else {
         let default_prompt = r"# Task Description
 
 Describe the task for the autonomous loop here.
@@ -219,12 +719,26 @@ Describe the task for the autonomous loop here.
 When the task is complete, output:
 
 <promise>COMPLETE</promise>
+
+If something outside your control stops you, output
+`<promise>BLOCKED: reason</promise>`; if you need a decision from the user,
+output `<promise>NEEDS_INPUT: question</promise>`; the next prompt has the answer.
 ";
-        if let Err(e) = std::fs::write(prompt_path, default_prompt) {
+        let prompt = template
+            .as_ref()
+            .map_or(default_prompt, |t| t.content.as_str());
+        if let Err(e) = std::fs::write(prompt_path, prompt) {
             eprintln!("Failed to write prompt file: {e}");
             std::process::exit(1);
         }
-        println!("Created {}", prompt_path.display());
+        match &template {
+            Some(template) => println!(
+                "Created {} from the {} template",
+                prompt_path.display(),
+                template.name
+            ),
+            None => println!("Created {}", prompt_path.display()),
+        }
     }
 
     println!("\nInitialization complete!");
@@ -237,9 +751,9 @@ fn cmd_probe(json: bool, model_filter: Option<String>, timeout_secs: u64) {
     let models_to_probe = if let Some(name) = model_filter {
         vec![name]
     } else {
-        ralf_engine::discovery::KNOWN_MODELS
-            .iter()
-            .map(|s| (*s).to_string())
+        ralf_engine::discovery::known_models()
+            .into_iter()
+            .map(str::to_string)
             .collect()
     };
 
@@ -285,11 +799,21 @@ fn cmd_probe(json: bool, model_filter: Option<String>, timeout_secs: u64) {
     println!("{ready_count} model(s) responding");
 }
 
+#[allow(clippy::too_many_arguments, clippy::too_many_lines)]
 fn cmd_run(
     max_iterations: Option<u64>,
     max_seconds: Option<u64>,
-    _branch: Option<String>,
+    branch: Option<&str>,
+    target: Option<&Path>,
     _models: Option<Vec<String>>,
+    resume: Option<String>,
+    force: bool,
+    max_cost: Option<f64>,
+    iteration_timeout: Option<u64>,
+    at: Option<&str>,
+    cron: Option<&str>,
+    wait_for_models: bool,
+    metrics_addr: Option<SocketAddr>,
 ) {
     let ralf_dir = Path::new(RALF_DIR);
 
@@ -312,38 +836,239 @@ fn cmd_run(
         std::process::exit(1);
     }
 
-    // Load config
-    let config = match Config::load(&config_path) {
-        Ok(c) => c,
+    // Load config (global settings layered under the repo's)
+    let mut config = match Config::load_layered(Path::new(".")) {
+        Ok(layered) => layered.config,
         Err(e) => {
             eprintln!("Error loading config: {e}");
             std::process::exit(1);
         }
     };
 
-    // Run the loop
+    // CLI budget overrides config
+    if max_cost.is_some() {
+        config.max_cost_usd = max_cost;
+    }
+    if iteration_timeout.is_some() {
+        config.iteration_timeout_seconds = iteration_timeout;
+    }
+
+    let target = target_root(Path::new("."), target);
+    if !target.is_dir() {
+        eprintln!("Error: target directory {} not found", target.display());
+        std::process::exit(1);
+    }
+
+    let start_at = at.map(|at| {
+        parse_start(at).unwrap_or_else(|e| {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        })
+    });
+    let schedule = cron.map(|cron| {
+        CronSchedule::parse(cron).unwrap_or_else(|e| {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        })
+    });
+
+    // Switch to the requested branch; worktree runs get their own
+    if let Some(branch) = branch {
+        if config.isolation == Isolation::Worktree {
+            eprintln!(
+                "Error: --branch doesn't apply with worktree isolation (each run gets its own ralf/run-<id> branch)"
+            );
+            std::process::exit(1);
+        }
+        match GitSafety::new(&target).switch_or_create_branch(branch) {
+            Ok(true) => println!("Created branch {branch}"),
+            Ok(false) => println!("Switched to branch {branch}"),
+            Err(e) => {
+                eprintln!("Error switching to branch {branch}: {e}");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // Scheduled runs wait for models anyway
+    if start_at.is_none() && schedule.is_none() && !wait_for_models {
+        require_available_model(&config, ralf_dir);
+    }
+
+    // Counted across every run of a schedule
+    let observer = metrics_addr.map(serve_metrics);
+
+    // Run the loop, once or on each trigger
     let rt = tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
-    rt.block_on(run_loop(
-        config,
-        ralf_dir,
-        prompt_path,
-        max_iterations,
-        max_seconds,
-    ));
+    rt.block_on(async {
+        let (mut start_at, mut resume) = (start_at, resume);
+        loop {
+            if let Some(schedule) = &schedule {
+                let Some(next) = schedule.next() else {
+                    eprintln!("Error: --cron {:?} never matches", cron.unwrap_or_default());
+                    std::process::exit(1);
+                };
+                start_at = Some(next);
+            }
+            let wait_until =
+                start_at.or_else(|| wait_for_models.then(ralf_engine::state::current_timestamp));
+            if let Some(at) = wait_until {
+                if !wait_for_start(&config, ralf_dir, at).await {
+                    return;
+                }
+            }
+            let interrupted = run_once(
+                &config,
+                ralf_dir,
+                max_iterations,
+                max_seconds,
+                resume.take(),
+                force,
+                &target,
+                observer.clone(),
+            )
+            .await;
+            if schedule.is_none() || interrupted {
+                return;
+            }
+        }
+    });
+    // A question or review left unanswered on the terminal mustn't keep
+    // ralf from exiting
+    rt.shutdown_background();
+}
+
+/// Serve run metrics on `addr`, returning the sink that records them.
+#[cfg(feature = "metrics")]
+fn serve_metrics(addr: SocketAddr) -> Arc<dyn EventSink> {
+    let metrics = Arc::new(ralf_engine::Metrics::new());
+    match ralf_engine::metrics::serve(addr, Arc::clone(&metrics)) {
+        Ok(bound) => println!("Serving metrics at http://{bound}/metrics"),
+        Err(e) => {
+            eprintln!("Error: can't serve metrics on {addr}: {e}");
+            std::process::exit(1);
+        }
+    }
+    metrics
+}
+
+#[cfg(not(feature = "metrics"))]
+fn serve_metrics(_addr: SocketAddr) -> Arc<dyn EventSink> {
+    eprintln!("Error: --metrics-addr needs ralf built with the `metrics` feature");
+    std::process::exit(1);
+}
+
+/// Exit if every model is in cooldown, pointing at `--wait-for-models`.
+fn require_available_model(config: &Config, ralf_dir: &Path) {
+    let now = ralf_engine::state::current_timestamp();
+    if let Some(start) = models_start(config, ralf_dir, now).filter(|start| *start > now) {
+        eprintln!(
+            "Error: every model is in cooldown until {}; add --wait-for-models to start then",
+            format_start(start)
+        );
+        std::process::exit(1);
+    }
+}
+
+/// When the first model not disabled is out of cooldown, no earlier than
+/// `at`, re-reading cooldowns and disabled models so `ralf cooldown` and
+/// `ralf model` changes count. `None` if every model is disabled.
+fn models_start(config: &Config, ralf_dir: &Path, at: u64) -> Option<u64> {
+    let cooldowns_path = RalfDirs::new(ralf_dir).configured(config).cooldowns();
+    let cooldowns = Cooldowns::load(&cooldowns_path).unwrap_or_default();
+    let disabled = Config::load_layered(Path::new(".")).map_or_else(
+        |_| config.disabled_models.clone(),
+        |l| l.config.disabled_models,
+    );
+    let models = config
+        .models
+        .iter()
+        .map(|m| m.name.as_str())
+        .filter(|name| !disabled.iter().any(|d| d == name));
+    models_ready_at(models, &cooldowns, at)
+}
+
+/// Wait until `at`, and then until a model is out of cooldown, recording
+/// the wait in `.ralf/pending_start.json` and reporting the time left now
+/// and then. Returns false if interrupted, or called off with `ralf
+/// cancel`, first.
+async fn wait_for_start(config: &Config, ralf_dir: &Path, at: u64) -> bool {
+    let mut signal = std::pin::pin!(shutdown_signal());
+    let mut announced = None;
+    let mut last_progress = Instant::now();
+    let started = loop {
+        // With no models to wait for, let the run report it
+        let Some(start) = models_start(config, ralf_dir, at) else {
+            break true;
+        };
+        let now = ralf_engine::state::current_timestamp();
+        if start <= now {
+            break true;
+        }
+        if announced.is_some() && !PendingStart::path(ralf_dir).exists() {
+            println!("Run called off by `ralf cancel` before it started");
+            return false;
+        }
+        let left = format_wait(start - now);
+        if announced != Some(start) {
+            if start > at {
+                println!(
+                    "Every model is in cooldown; starting at {}, in {left} (Ctrl+C or `ralf cancel` to call it off)",
+                    format_start(start)
+                );
+            } else {
+                println!(
+                    "Starting the run at {}, in {left} (Ctrl+C or `ralf cancel` to call it off)",
+                    format_start(start)
+                );
+            }
+            if let Err(e) = PendingStart::new(start, start > at).save(ralf_dir) {
+                eprintln!("Warning: {}", save_warning("pending start", &e.to_string()));
+            }
+            announced = Some(start);
+            last_progress = Instant::now();
+        } else if last_progress.elapsed().as_secs() >= WAIT_PROGRESS_SECS {
+            println!("  {left} until the run starts");
+            last_progress = Instant::now();
+        }
+        let wait = Duration::from_secs(start - now).min(SCHEDULE_POLL);
+        tokio::select! {
+            () = tokio::time::sleep(wait) => {}
+            () = &mut signal => {
+                println!("\nInterrupted before the run started");
+                break false;
+            }
+        }
+    };
+    PendingStart::remove(ralf_dir);
+    started
 }
 
 fn cmd_status(json: bool) {
     let ralf_dir = Path::new(RALF_DIR);
-    let state_path = ralf_dir.join("state.json");
-    let cooldowns_path = ralf_dir.join("cooldowns.json");
-
-    let state = RunState::load(&state_path).ok();
-    let cooldowns = Cooldowns::load(&cooldowns_path).ok();
+    let dirs = ralf_dirs();
+
+    let state = RunState::load(&dirs.state()).ok();
+    let cooldowns = Cooldowns::load(&dirs.cooldowns()).ok();
+    let ledger = UsageLedger::load(&dirs.usage()).ok();
+    let disabled_models = Config::load_layered(Path::new("."))
+        .map(|layered| layered.config.disabled_models)
+        .unwrap_or_default();
+    let run_usage = ledger.as_ref().and_then(|l| {
+        let run_id = state.as_ref()?.run_id.as_deref()?;
+        l.run(run_id).cloned()
+    });
 
     if json {
         let output = serde_json::json!({
             "state": state,
+            "pending_start": PendingStart::load(ralf_dir).ok().flatten(),
             "cooldowns": cooldowns,
+            "disabled_models": disabled_models,
+            "usage": {
+                "run": run_usage,
+                "total": ledger.as_ref().map(UsageLedger::total),
+            },
         });
         println!(
             "{}",
@@ -354,16 +1079,45 @@ fn cmd_status(json: bool) {
 
     println!("Ralf Status\n");
 
+    print_pending_start(ralf_dir);
+
     match state {
         Some(s) => {
             if let Some(run_id) = &s.run_id {
                 println!("Run: {run_id}");
             }
             println!("Status: {}", s.status);
+            if s.degraded {
+                println!("Warning: some run files failed to save; history may be incomplete");
+            }
             println!("Iteration: {}", s.iteration);
+            if let Some(pending) = &s.pending_question {
+                println!(
+                    "Question from iteration {}: {}",
+                    pending.iteration, pending.question
+                );
+            }
             if let Some(started) = s.started_at {
                 println!("Started: {started} (Unix timestamp)");
             }
+            if let Some(usage) = &run_usage {
+                println!("Usage: {}", usage.total.summary());
+                for (model, model_usage) in &usage.models {
+                    println!("  {model}: {}", model_usage.summary());
+                }
+            }
+            if let Some(report) = &s.completion_report {
+                if let Some(summary) = &report.summary {
+                    println!("Report: {summary}");
+                }
+                if !report.criteria.is_empty() {
+                    let (met, total) = report.criteria_met();
+                    println!("Self-assessed criteria: {met}/{total} met");
+                }
+                for item in &report.follow_up {
+                    println!("  Follow-up: {item}");
+                }
+            }
         }
         None => {
             println!("No active run");
@@ -380,7 +1134,11 @@ fn cmd_status(json: bool) {
             } else {
                 println!("Models in cooldown:");
                 for name in cooling {
-                    println!("  - {name}");
+                    let remaining = c.remaining_seconds(name).unwrap_or(0);
+                    match c.entries.get(name).and_then(|e| e.reset_hint.as_deref()) {
+                        Some(hint) => println!("  - {name}: {remaining}s remaining ({hint})"),
+                        None => println!("  - {name}: {remaining}s remaining"),
+                    }
                 }
             }
         }
@@ -388,16 +1146,60 @@ fn cmd_status(json: bool) {
             println!("No cooldown data");
         }
     }
+
+    if !disabled_models.is_empty() {
+        println!("Disabled models: {}", disabled_models.join(", "));
+    }
+}
+
+/// Report a run waiting to start, if there is one.
+fn print_pending_start(ralf_dir: &Path) {
+    let Ok(Some(pending)) = PendingStart::load(ralf_dir) else {
+        return;
+    };
+    let reason = if pending.waiting_for_models {
+        "waiting for models to come out of cooldown"
+    } else {
+        "scheduled"
+    };
+    println!(
+        "Pending run: starts at {} ({reason}, pid {})\n",
+        format_start(pending.start_at),
+        pending.pid
+    );
 }
 
 fn cmd_cancel() {
     let ralf_dir = Path::new(RALF_DIR);
-    let state_path = ralf_dir.join("state.json");
+    let state_path = RalfDirs::new(ralf_dir).state();
+
+    // A served run is cancelled through its server
+    if let Some(client) = RemoteClient::discover(&RalfDirs::new(ralf_dir)) {
+        match client.send(RemoteAction::Cancel) {
+            Ok(status) => {
+                let run_id = status.run_id.as_deref().unwrap_or("unknown");
+                println!("Cancelling run {run_id}");
+                return;
+            }
+            Err(e) => eprintln!("Warning: can't reach `ralf serve` ({e}); cancelling via state"),
+        }
+    }
 
-    let Ok(mut state) = RunState::load(&state_path) else {
-        eprintln!("No active run to cancel");
-        std::process::exit(1);
-    };
+    // A run waiting to start sees its pending start go and gives up
+    if let Ok(Some(pending)) = PendingStart::load(ralf_dir) {
+        PendingStart::remove(ralf_dir);
+        println!(
+            "Called off the run waiting to start at {} (pid {})",
+            format_start(pending.start_at),
+            pending.pid
+        );
+        return;
+    }
+
+    let Ok(mut state) = RunState::load(&state_path) else {
+        eprintln!("No active run to cancel");
+        std::process::exit(1);
+    };
 
     if state.status != RunStatus::Running {
         eprintln!("Run is not active (status: {})", state.status);
@@ -415,277 +1217,1404 @@ fn cmd_cancel() {
     println!("Cancelled run {run_id}");
 }
 
-/// Run the main autonomous loop.
-#[allow(clippy::too_many_lines, clippy::similar_names)]
-async fn run_loop(
-    config: Config,
-    ralf_dir: &Path,
-    prompt_path: &Path,
+/// Run the loop through the engine, serving it until it finishes.
+fn cmd_serve(
+    addr: SocketAddr,
+    token: Option<String>,
     max_iterations: Option<u64>,
     max_seconds: Option<u64>,
+    resume: Option<String>,
+    force: bool,
 ) {
-    let state_path = ralf_dir.join("state.json");
-    let cooldowns_path = ralf_dir.join("cooldowns.json");
-    let runs_dir = ralf_dir.join("runs");
-    let changelog_dir = ralf_dir.join("changelog");
-
-    // Load or create state
-    let mut state = RunState::load(&state_path).unwrap_or_default();
-    let mut cooldowns = Cooldowns::load(&cooldowns_path).unwrap_or_default();
-
-    // Start a new run
-    let run_id = state.start_run();
-    println!("Starting run {run_id}");
-
-    // Create run directory
-    let run_dir = runs_dir.join(&run_id);
-    if let Err(e) = std::fs::create_dir_all(&run_dir) {
-        eprintln!("Failed to create run directory: {e}");
-        state.fail();
-        let _ = state.save(&state_path);
+    let config = load_config();
+    let dirs = RalfDirs::new(RALF_DIR).configured(&config);
+    let token = token.unwrap_or_else(generate_token);
+    let server = match RemoteServer::bind(addr, token.clone()) {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("Error: can't listen on {addr}: {e}");
+            std::process::exit(1);
+        }
+    };
+    let addr = server.local_addr().unwrap_or(addr);
+    let info_path = dirs.serve();
+    if let Err(e) = std::fs::create_dir_all(dirs.root())
+        .and_then(|()| ServeInfo::new(addr, &token).write(&info_path))
+    {
+        eprintln!("Error: can't write {}: {e}", info_path.display());
         std::process::exit(1);
     }
+    println!(
+        "Serving the run at http://{addr} (token in {})",
+        info_path.display()
+    );
+
+    let max_iterations = usize::try_from(max_iterations.unwrap_or(100)).unwrap_or(usize::MAX);
+    let rt = tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
+    let outcome = rt.block_on(async {
+        let (event_tx, mut event_rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut builder = Runner::builder(config, ".")
+            .dirs(dirs)
+            .max_iterations(max_iterations)
+            .max_runtime_secs(max_seconds.unwrap_or(0))
+            .force(force)
+            .events(MultiSink::new().with(server.events()).with(event_tx));
+        if let Some(run_id) = resume {
+            builder = builder.resume(run_id);
+        }
+        let handle = Arc::new(builder.build().start());
+        if let Err(e) = server.spawn(handle.clone()) {
+            eprintln!("Error: can't serve the run: {e}");
+            handle.try_cancel();
+        }
+
+        let mut signal = std::pin::pin!(shutdown_signal());
+        let mut interrupted = false;
+        let mut outcome = None;
+        loop {
+            let event = tokio::select! {
+                event = event_rx.recv() => match event {
+                    Some(event) => event,
+                    None => break,
+                },
+                () = &mut signal, if !interrupted => {
+                    println!("Cancelling the run...");
+                    interrupted = true;
+                    handle.try_cancel();
+                    continue;
+                }
+            };
+            let completed = match &event {
+                RunEvent::Completed { .. } => Some(true),
+                RunEvent::Failed { .. } | RunEvent::Stuck { .. } | RunEvent::Cancelled { .. } => {
+                    Some(false)
+                }
+                _ => None,
+            };
+            outcome = completed.or(outcome);
+            println!("{}", format_event(&EventRecord::now(event)));
+        }
+        outcome
+    });
+
+    let _ = std::fs::remove_file(&info_path);
+    if outcome != Some(true) {
+        std::process::exit(1);
+    }
+}
 
-    // Read the prompt
-    let prompt = match std::fs::read_to_string(prompt_path) {
-        Ok(p) => p,
+fn cmd_cooldown(action: CooldownAction) {
+    let cooldowns_path = ralf_dirs().cooldowns();
+    let mut cooldowns = match Cooldowns::load(&cooldowns_path) {
+        Ok(c) => c,
         Err(e) => {
-            eprintln!("Failed to read prompt: {e}");
-            state.fail();
-            let _ = state.save(&state_path);
+            eprintln!("Error loading cooldowns: {e}");
             std::process::exit(1);
         }
     };
-    let prompt_hash = hash_prompt(&prompt);
 
-    // Save initial state
-    let _ = state.save(&state_path);
+    match action {
+        CooldownAction::Set { model, seconds } => {
+            require_known_model(&load_config(), &model);
+            cooldowns.set_cooldown(&model, seconds, "manual");
+            println!("{model} in cooldown for {seconds}s");
+        }
+        CooldownAction::Clear { model: Some(model) } => {
+            if !cooldowns.is_cooling(&model) {
+                println!("{model} isn't in cooldown");
+                return;
+            }
+            cooldowns.entries.remove(&model);
+            println!("Cleared {model}'s cooldown");
+        }
+        CooldownAction::Clear { model: None } => {
+            let count = cooldowns.cooling_models().len();
+            cooldowns.entries.clear();
+            println!("Cleared {count} cooldown(s)");
+        }
+    }
 
-    let start_time = Instant::now();
-    let max_iterations = max_iterations.unwrap_or(100);
-    let max_duration = max_seconds.map(Duration::from_secs);
+    if let Err(e) = cooldowns.save(&cooldowns_path) {
+        eprintln!("Failed to save cooldowns: {e}");
+        std::process::exit(1);
+    }
+}
 
-    println!("Prompt hash: {}", &prompt_hash[..8]);
-    println!("Max iterations: {max_iterations}");
-    if let Some(d) = max_duration {
-        println!("Max duration: {}s", d.as_secs());
+fn cmd_model(action: ModelAction) {
+    let layered = match Config::load_layered(Path::new(".")) {
+        Ok(layered) => layered,
+        Err(e) => {
+            eprintln!("Error loading config: {e}");
+            std::process::exit(1);
+        }
+    };
+    let (model, disable) = match action {
+        ModelAction::Disable { model } => (model, true),
+        ModelAction::Enable { model } => (model, false),
+    };
+    require_known_model(&layered.config, &model);
+
+    if layered.config.is_disabled(&model) == disable {
+        let state = if disable { "disabled" } else { "enabled" };
+        println!("{model} is already {state}");
+        return;
+    }
+    let mut disabled = layered.config.disabled_models.clone();
+    if disable {
+        disabled.push(model.clone());
+    } else {
+        disabled.retain(|name| *name != model);
     }
-    println!();
 
-    // Main loop
-    loop {
-        // Check iteration limit
-        if state.iteration >= max_iterations {
-            println!("\nMax iterations ({max_iterations}) reached");
-            state.fail();
-            break;
+    // The repo config, so other repos' runs aren't affected
+    if let Err(e) = layered.set_repo("disabled_models", serde_json::json!(disabled)) {
+        eprintln!("Failed to save config: {e}");
+        std::process::exit(1);
+    }
+    if disable {
+        println!("Disabled {model}");
+    } else {
+        println!("Enabled {model}");
+    }
+}
+
+fn cmd_queue(action: QueueAction) {
+    let ralf_dir = Path::new(RALF_DIR);
+    let store = match ThreadStore::new(ralf_dir) {
+        Ok(store) => store,
+        Err(e) => {
+            eprintln!("Error opening threads: {e}");
+            std::process::exit(1);
+        }
+    };
+    let queue_path = Queue::path(ralf_dir);
+    let mut queue = match Queue::load(&queue_path) {
+        Ok(queue) => queue,
+        Err(e) => {
+            eprintln!("Error loading queue: {e}");
+            std::process::exit(1);
         }
+    };
 
-        // Check time limit
-        if let Some(max_dur) = max_duration {
-            if start_time.elapsed() > max_dur {
-                println!("\nMax duration reached");
-                state.fail();
-                break;
+    match action {
+        QueueAction::Add { thread_id } => {
+            let thread = load_thread(&store, &thread_id);
+            if thread.phase != ThreadPhase::Finalized {
+                eprintln!(
+                    "Error: {} is {}; only finalized threads can be queued",
+                    thread.title,
+                    thread.phase_display_name()
+                );
+                std::process::exit(1);
+            }
+            if !queue.add(&thread.id) {
+                println!("{} is already queued", thread.title);
+                return;
+            }
+            let position = queue.position(&thread.id).unwrap_or_default();
+            println!("Queued {} (position {position})", thread.title);
+        }
+        QueueAction::Remove { thread_id } => {
+            let thread = load_thread(&store, &thread_id);
+            if !queue.remove(&thread.id) {
+                println!("{} isn't queued", thread.title);
+                return;
             }
+            println!("Removed {} from the queue", thread.title);
+        }
+        QueueAction::List => {
+            print_queue(&queue, &store);
+            return;
         }
+        QueueAction::Start => {
+            cmd_queue_start(&store, queue, &queue_path);
+            return;
+        }
+    }
+    save_queue(&queue, &queue_path);
+}
 
-        // Clear expired cooldowns
-        cooldowns.clear_expired();
+fn print_queue(queue: &Queue, store: &ThreadStore) {
+    if queue.entries.is_empty() {
+        println!("The queue is empty; add threads with `ralf queue add <thread-id>`");
+        return;
+    }
+    for entry in &queue.entries {
+        let title = store
+            .load(&entry.thread_id)
+            .map_or_else(|_| "(deleted thread)".to_string(), |t| t.title);
+        let place = queue
+            .position(&entry.thread_id)
+            .map_or_else(String::new, |p| format!("{p}."));
+        let short_id = entry.thread_id.get(..8).unwrap_or(&entry.thread_id);
+        print!("{place:>4} {title} ({short_id}) {}", entry.status.label());
+        match &entry.outcome {
+            Some(outcome) => println!(": {outcome}"),
+            None => println!(),
+        }
+    }
+}
 
-        // Select a model
-        let Some(model) = select_model(&config, &cooldowns, &mut state) else {
-            // All models in cooldown - wait for earliest expiry
-            if let Some(expiry) = cooldowns.earliest_expiry() {
-                let now = ralf_engine::state::current_timestamp();
-                let wait_secs = expiry.saturating_sub(now);
-                println!("All models in cooldown, waiting {wait_secs}s...");
-                tokio::time::sleep(Duration::from_secs(wait_secs + 1)).await;
-                continue;
+/// Run queued threads until none are left, or the user interrupts.
+fn cmd_queue_start(store: &ThreadStore, mut queue: Queue, queue_path: &Path) {
+    let config = load_config();
+    let requeued = queue.requeue_interrupted();
+    if requeued > 0 {
+        println!("Picking up {requeued} thread(s) an earlier `ralf queue start` left unfinished");
+    }
+    if queue.next_pending().is_none() {
+        println!("Nothing queued; add threads with `ralf queue add <thread-id>`");
+        return;
+    }
+
+    let notifications = NotificationConfig::from_env();
+    let rt = tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
+    let (mut completed, mut failed) = (0, 0);
+    while let Some(thread_id) = queue.next_pending().map(|e| e.thread_id.clone()) {
+        queue.set_status(&thread_id, QueueStatus::Running, None);
+        save_queue(&queue, queue_path);
+
+        let outcome = rt.block_on(run_queued_thread(store, &thread_id, &config));
+        let title = store
+            .load(&thread_id)
+            .map_or_else(|_| thread_id.clone(), |t| t.title);
+        match outcome {
+            QueueOutcome::Completed(message) => {
+                println!("Completed {title}: {message}");
+                notify(
+                    &notifications,
+                    NotifyEvent::RunCompleted,
+                    format!("{title}: {message}"),
+                );
+                queue.set_status(&thread_id, QueueStatus::Completed, Some(message));
+                completed += 1;
             }
-            eprintln!("No models available");
-            state.fail();
-            break;
-        };
+            QueueOutcome::Failed(message) => {
+                println!("Failed {title}: {message}");
+                notify(
+                    &notifications,
+                    NotifyEvent::RunFailed,
+                    format!("{title}: {message}"),
+                );
+                queue.set_status(&thread_id, QueueStatus::Failed, Some(message));
+                failed += 1;
+            }
+            QueueOutcome::Interrupted => {
+                queue.set_status(&thread_id, QueueStatus::Pending, None);
+                save_queue(&queue, queue_path);
+                println!("Queue stopped; `ralf queue start` picks up again at {title}");
+                return;
+            }
+        }
+        save_queue(&queue, queue_path);
 
-        state.next_iteration();
-        println!(
-            "=== Iteration {} - Model: {} ===",
-            state.iteration, model.name
-        );
+        if let Err(message) = return_to_base(store, &thread_id, &config) {
+            println!("Queue stopped: {message}");
+            return;
+        }
+    }
+    println!("Queue finished: {completed} completed, {failed} failed");
+}
 
-        // Save state
-        let _ = state.save(&state_path);
-
-        // Invoke the model
-        let invocation = match invoke_model(model, &prompt, &run_dir).await {
-            Ok(mut inv) => {
-                inv.has_promise = check_promise(&inv.stdout, &config.completion_promise);
-                inv
-            }
-            Err(ralf_engine::RunnerError::Timeout(name)) => {
-                println!("  Model {name} timed out");
-                let entry = ChangelogEntry {
-                    changelog_dir: &changelog_dir,
-                    run_id: &run_id,
-                    iteration: state.iteration,
-                    invocation: &ralf_engine::InvocationResult {
-                        model: model.name.clone(),
-                        exit_code: None,
-                        stdout: String::new(),
-                        stderr: String::new(),
-                        rate_limited: false,
-                        duration_ms: model.timeout_seconds * 1000,
-                        has_promise: false,
-                    },
-                    verifier_results: &[],
-                    prompt_hash: &prompt_hash,
-                    git_info: &get_git_info(),
-                    status: IterationStatus::Timeout,
-                    reason: "Model timed out",
-                    log_path: run_dir.join(format!("{}.log", model.name)),
-                };
-                let _ = write_changelog_entry(&entry);
-                cooldowns.set_cooldown(&model.name, model.default_cooldown_seconds, "timeout");
-                let _ = cooldowns.save(&cooldowns_path);
-                continue;
+/// How a queued thread's turn ended.
+enum QueueOutcome {
+    /// The run completed; the message says how.
+    Completed(String),
+    /// Preflight or the run failed, for the reason given.
+    Failed(String),
+    /// The user stopped the queue.
+    Interrupted,
+}
+
+/// Take a finalized thread through preflight and a run, as `/preflight`
+/// and `/run` would in the TUI, leaving it pending review if it completes.
+#[allow(clippy::too_many_lines)]
+async fn run_queued_thread(store: &ThreadStore, thread_id: &str, config: &Config) -> QueueOutcome {
+    let repo_path = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let thread = match store.load(thread_id) {
+        Ok(thread) => thread,
+        Err(e) => return QueueOutcome::Failed(format!("can't load the thread: {e}")),
+    };
+    println!("\n{} ({})", thread.title, thread.id);
+    if thread.phase != ThreadPhase::Finalized {
+        return QueueOutcome::Failed(format!(
+            "the thread is {}, not finalized",
+            thread.phase_display_name()
+        ));
+    }
+    let Ok(Some(spec)) = store.load_latest_spec(&thread.id) else {
+        return QueueOutcome::Failed("the thread has no spec".to_string());
+    };
+    let prompt_path = repo_path.join("PROMPT.md");
+    if let Err(e) = std::fs::write(&prompt_path, &spec) {
+        return QueueOutcome::Failed(format!("can't write PROMPT.md: {e}"));
+    }
+
+    let mut thread = match store.transition(&thread.id, ThreadPhase::Preflight) {
+        Ok(thread) => thread,
+        Err(e) => return QueueOutcome::Failed(e.to_string()),
+    };
+    let preflight =
+        run_preflight_with_probes(&thread, &repo_path, store, config, QUEUE_PROBE_TIMEOUT).await;
+    if !preflight.passed {
+        let reason = preflight
+            .checks
+            .iter()
+            .filter(|c| !c.passed)
+            .map(|c| format!("{}: {}", c.label, c.message))
+            .collect::<Vec<_>>()
+            .join("; ");
+        if let Err(e) = store.transition(
+            &thread.id,
+            ThreadPhase::PreflightFailed {
+                reason: reason.clone(),
+            },
+        ) {
+            eprintln!("Warning: {}", save_warning("thread", &e.to_string()));
+        }
+        return QueueOutcome::Failed(format!("preflight failed: {reason}"));
+    }
+
+    // Work on the thread's own branch, recording where it started
+    let settings = thread.run_config.clone().unwrap_or_default();
+    let work_dir = settings.target_root(&repo_path);
+    let git = GitSafety::new(&work_dir);
+    if config.isolation != Isolation::Worktree && git.is_repo() {
+        let switched = git.current_branch().and_then(|base| {
+            if let Some(branch) = &thread.branch {
+                if branch.name != base {
+                    git.checkout(&branch.name)?;
+                }
+                return Ok(None);
+            }
+            let name = git.switch_to_thread_branch(&thread.branch_slug())?;
+            Ok(Some(ThreadBranch { name, base }))
+        });
+        match switched {
+            Ok(Some(branch)) => {
+                println!("Working on branch {} (from {})", branch.name, branch.base);
+                thread.branch = Some(branch);
             }
+            Ok(None) => {}
             Err(e) => {
-                eprintln!("  Model error: {e}");
-                let entry = ChangelogEntry {
-                    changelog_dir: &changelog_dir,
-                    run_id: &run_id,
-                    iteration: state.iteration,
-                    invocation: &ralf_engine::InvocationResult {
-                        model: model.name.clone(),
-                        exit_code: None,
-                        stdout: String::new(),
-                        stderr: e.to_string(),
-                        rate_limited: false,
-                        duration_ms: 0,
-                        has_promise: false,
-                    },
-                    verifier_results: &[],
-                    prompt_hash: &prompt_hash,
-                    git_info: &get_git_info(),
-                    status: IterationStatus::Error,
-                    reason: "Model invocation failed",
-                    log_path: run_dir.join(format!("{}.log", model.name)),
-                };
-                let _ = write_changelog_entry(&entry);
+                return QueueOutcome::Failed(format!("can't switch to the thread's branch: {e}"))
+            }
+        }
+    }
+    if thread.baseline.is_none() && git.is_repo() {
+        thread.baseline = git.capture_baseline().ok();
+    }
+    let started = thread
+        .transition_to(ThreadPhase::Configuring)
+        .and_then(|()| thread.transition_to(ThreadPhase::Running { iteration: 1 }));
+    if let Err(e) = started {
+        return QueueOutcome::Failed(e.to_string());
+    }
+    // Don't start a run the thread won't show
+    if let Err(e) = store.save(&thread) {
+        return QueueOutcome::Failed(save_warning("thread", &e.to_string()));
+    }
+
+    let mut run_settings = config.clone();
+    if let Some(promise) = settings.completion_promise.clone() {
+        run_settings.completion_promise = promise;
+    }
+    let run_config = ralf_engine::RunConfig {
+        max_iterations: settings.max_iterations as usize,
+        max_runtime_secs: 0,
+        prompt_path,
+        repo_path,
+        target: settings.target,
+        criteria: parse_criteria(&spec),
+        resume: None,
+        force: false,
+        max_cost_usd: None,
+        max_tokens: None,
+        no_verify_cache: false,
+        iteration_timeout_seconds: None,
+        scheduler: None,
+        dirs: None,
+        clock: None,
+        spawner: None,
+    };
+    let (event_tx, mut event_rx) = tokio::sync::mpsc::unbounded_channel();
+    let handle = ralf_engine::start_run(run_settings, run_config, event_tx);
+
+    let mut signal = std::pin::pin!(shutdown_signal());
+    let mut interrupted = false;
+    let mut held_for_review = None;
+    let mut question = None;
+    let mut outcome = QueueOutcome::Failed("the run ended without a result".to_string());
+    let mut end_phase = None;
+    loop {
+        let event = tokio::select! {
+            event = event_rx.recv() => match event {
+                Some(event) => event,
+                None => break,
+            },
+            () = &mut signal, if !interrupted => {
+                println!("Cancelling the run...");
+                interrupted = true;
+                handle.try_cancel();
                 continue;
             }
         };
+        let paused = |iteration: usize| ThreadPhase::Paused {
+            iteration: u32::try_from(iteration).unwrap_or(u32::MAX),
+        };
+        match event {
+            RunEvent::Started { run_id, .. } => {
+                thread.current_run_id = Some(run_id);
+                if let Err(e) = store.save(&thread) {
+                    eprintln!("Warning: {}", save_warning("thread", &e.to_string()));
+                }
+            }
+            RunEvent::IterationStarted { iteration, model } => {
+                println!("  Iteration {iteration}: {model}");
+            }
+            RunEvent::Status { message } => println!("  {message}"),
+            // Nobody is there to review, so the run stops and leaves the
+            // changes for the user
+            RunEvent::ReviewRequired { reasons, .. } => {
+                held_for_review = Some(reasons.join("; "));
+                handle.reject_review();
+            }
+            // Nor to answer, so the run stops with the question recorded
+            RunEvent::QuestionAsked {
+                question: asked, ..
+            } => {
+                println!("  The model asks: {asked}");
+                question = Some(asked);
+                handle.try_cancel();
+            }
+            RunEvent::Completed { iteration, reason } => {
+                outcome = QueueOutcome::Completed(format!(
+                    "completed after iteration {iteration} ({reason}); ready for review"
+                ));
+                end_phase = Some(vec![
+                    ThreadPhase::Verifying {
+                        iteration: u32::try_from(iteration).unwrap_or(u32::MAX),
+                    },
+                    ThreadPhase::Implemented,
+                    ThreadPhase::PendingReview,
+                ]);
+            }
+            RunEvent::Failed { iteration, error } => {
+                outcome = QueueOutcome::Failed(error);
+                end_phase = Some(vec![paused(iteration)]);
+            }
+            RunEvent::Stuck {
+                iteration,
+                diagnosis,
+            } => {
+                outcome = QueueOutcome::Failed(format!("stuck at iteration {iteration}"));
+                end_phase = Some(vec![ThreadPhase::Stuck { diagnosis }]);
+            }
+            RunEvent::Cancelled { iteration } => {
+                outcome = QueueOutcome::Failed("the run was cancelled".to_string());
+                end_phase = Some(vec![paused(iteration)]);
+            }
+            _ => {}
+        }
+    }
 
-        // Check for rate limiting
-        if invocation.rate_limited {
-            println!(
-                "  Rate limited ({}ms), cooling down for {}s",
-                invocation.duration_ms, model.default_cooldown_seconds
-            );
-            let entry = ChangelogEntry {
-                changelog_dir: &changelog_dir,
-                run_id: &run_id,
-                iteration: state.iteration,
-                invocation: &invocation,
-                verifier_results: &[],
-                prompt_hash: &prompt_hash,
-                git_info: &get_git_info(),
-                status: IterationStatus::RateLimited,
-                reason: "Rate limited",
-                log_path: run_dir.join(format!("{}.log", model.name)),
-            };
-            let _ = write_changelog_entry(&entry);
-            cooldowns.set_cooldown(&model.name, model.default_cooldown_seconds, "rate_limit");
-            let _ = cooldowns.save(&cooldowns_path);
-            continue;
+    for phase in end_phase.unwrap_or_default() {
+        if thread.transition_to(phase).is_err() {
+            break;
+        }
+    }
+    if thread.phase == ThreadPhase::PendingReview {
+        if let Some(baseline) = &thread.baseline {
+            thread.review_checklist = ReviewChecklist::for_baseline(
+                &work_dir,
+                baseline,
+                &spec,
+                &config.review.protected_paths,
+            )
+            .ok();
+        }
+    }
+    let saved = store.save(&thread);
+    if interrupted {
+        if let Err(e) = saved {
+            eprintln!("Warning: {}", save_warning("thread", &e.to_string()));
+        }
+        QueueOutcome::Interrupted
+    } else if let Err(e) = saved {
+        // The thread would be left running on disk
+        QueueOutcome::Failed(save_warning("thread", &e.to_string()))
+    } else if let Some(reasons) = held_for_review {
+        QueueOutcome::Failed(format!("changes need review: {reasons}"))
+    } else if let Some(question) = question {
+        QueueOutcome::Failed(format!("the model asked: {question}"))
+    } else {
+        outcome
+    }
+}
+
+/// Put the repository back on the branch a queued thread started from, so
+/// the next thread branches from the same place.
+fn return_to_base(store: &ThreadStore, thread_id: &str, config: &Config) -> Result<(), String> {
+    let Ok(thread) = store.load(thread_id) else {
+        return Ok(());
+    };
+    let Some(branch) = thread.branch.as_ref() else {
+        return Ok(());
+    };
+    if config.isolation == Isolation::Worktree {
+        return Ok(());
+    }
+    let target = thread
+        .run_config
+        .as_ref()
+        .map_or_else(|| PathBuf::from("."), |c| c.target_root(Path::new(".")));
+    let git = GitSafety::new(target);
+    if !git.is_clean().unwrap_or(false) {
+        return Err(format!(
+            "{} left uncommitted changes on {}; commit them (or use worktree isolation) and run `ralf queue start` again",
+            thread.title, branch.name
+        ));
+    }
+    git.checkout(&branch.base)
+        .map_err(|e| format!("can't switch back to {}: {e}", branch.base))
+}
+
+/// Load a thread by ID or unique ID prefix, exiting if there's no such
+/// thread.
+fn load_thread(store: &ThreadStore, id: &str) -> Thread {
+    let id = if store.exists(id) {
+        id.to_string()
+    } else {
+        let matches: Vec<String> = store
+            .list()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|summary| summary.id)
+            .filter(|thread_id| thread_id.starts_with(id))
+            .collect();
+        match matches.as_slice() {
+            [only] => only.clone(),
+            [] => {
+                eprintln!("Error: no thread {id}");
+                std::process::exit(1);
+            }
+            _ => {
+                eprintln!(
+                    "Error: {id} matches {} threads; give more of the ID",
+                    matches.len()
+                );
+                std::process::exit(1);
+            }
+        }
+    };
+    match store.load(&id) {
+        Ok(thread) => thread,
+        Err(e) => {
+            eprintln!("Error loading thread {id}: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Save the queue, exiting on error.
+fn save_queue(queue: &Queue, path: &Path) {
+    if let Err(e) = queue.save(path) {
+        eprintln!("Failed to save the queue: {e}");
+        std::process::exit(1);
+    }
+}
+
+/// Where `.ralf` files are kept, as the config lays them out. A config
+/// that can't be read leaves everything in `.ralf/`.
+fn ralf_dirs() -> RalfDirs {
+    let config = Config::load_layered(Path::new("."))
+        .map(|layered| layered.config)
+        .unwrap_or_default();
+    RalfDirs::new(RALF_DIR).configured(&config)
+}
+
+/// Load the merged config, exiting on error.
+fn load_config() -> Config {
+    match Config::load_layered(Path::new(".")) {
+        Ok(layered) => layered.config,
+        Err(e) => {
+            eprintln!("Error loading config: {e}");
+            std::process::exit(1);
         }
+    }
+}
 
-        println!("  Model completed in {}ms", invocation.duration_ms);
-        println!("  Has promise: {}", invocation.has_promise);
+/// Exit with an error unless `name` is a configured model.
+fn require_known_model(config: &Config, name: &str) {
+    if config.get_model(name).is_none() {
+        let known: Vec<&str> = config.models.iter().map(|m| m.name.as_str()).collect();
+        eprintln!(
+            "Error: unknown model {name} (configured: {})",
+            known.join(", ")
+        );
+        std::process::exit(1);
+    }
+}
 
-        // Run verifiers
-        let mut verifier_results = Vec::new();
-        let mut all_passed = true;
+/// Run the loop once through the engine, printing its progress and asking
+/// on the terminal when it needs an answer. Returns whether it was
+/// interrupted (Ctrl+C or SIGTERM).
+#[allow(clippy::too_many_arguments)]
+async fn run_once(
+    config: &Config,
+    ralf_dir: &Path,
+    max_iterations: Option<u64>,
+    max_seconds: Option<u64>,
+    resume: Option<String>,
+    force: bool,
+    target: &Path,
+    observer: Option<Arc<dyn EventSink>>,
+) -> bool {
+    let dirs = RalfDirs::new(ralf_dir).configured(config);
+    let max_iterations = max_iterations.unwrap_or(100);
 
-        for verifier in &config.verifiers {
-            print!("  Running verifier '{}'... ", verifier.name);
-            match run_verifier(verifier, &run_dir).await {
-                Ok(result) => {
-                    if result.passed {
-                        println!("PASS ({}ms)", result.duration_ms);
+    if let Ok(prompt) = std::fs::read_to_string("PROMPT.md") {
+        println!("Prompt hash: {}", &hash_prompt(&prompt)[..8]);
+    }
+    println!("Max iterations: {max_iterations}");
+    if let Some(secs) = max_seconds {
+        println!("Max duration: {secs}s");
+    }
+    if let Some(max) = config.max_cost_usd {
+        println!("Max cost: ${max:.2}");
+    }
+    if let Some(max) = config.max_tokens {
+        println!("Max tokens: {max}");
+    }
+    if let Some(secs) = config.iteration_timeout_seconds {
+        println!("Iteration timeout: {secs}s");
+    }
+    let leftovers = find_leftover_processes();
+    if !leftovers.is_empty() {
+        println!(
+            "Warning: {} process(es) left running by earlier runs:",
+            leftovers.len()
+        );
+        for process in &leftovers {
+            println!("  {} ({})", process.pid, process.name);
+        }
+    }
+    println!();
+
+    let (event_tx, mut event_rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut sinks = MultiSink::new().with(ConsoleSink).with(event_tx);
+    if let Some(observer) = observer {
+        sinks.push(observer);
+    }
+    let mut builder = Runner::builder(config.clone(), ".")
+        .dirs(dirs.clone())
+        .target(target)
+        .max_iterations(usize::try_from(max_iterations).unwrap_or(usize::MAX))
+        .max_runtime_secs(max_seconds.unwrap_or(0))
+        .force(force)
+        .events(sinks);
+    if let Some(run_id) = resume {
+        builder = builder.resume(run_id);
+    }
+    let handle = Arc::new(builder.build().start());
+
+    // The first Ctrl+C or SIGTERM cancels the run at the next chance, so
+    // state is saved. Model and verifier processes run in their own process
+    // group, out of reach of the terminal's Ctrl+C; a second signal stops
+    // them and exits at once.
+    let mut signal = std::pin::pin!(shutdown_signal());
+    let mut interrupted = false;
+    let mut run_id = None;
+    let mut saves = PersistenceSink::new();
+    loop {
+        let event = tokio::select! {
+            event = event_rx.recv() => match event {
+                Some(event) => event,
+                None => break,
+            },
+            () = &mut signal => {
+                if interrupted {
+                    kill_all_process_trees();
+                    release_run_lock(dirs.root());
+                    std::process::exit(130);
+                }
+                println!("\nInterrupted, cancelling the run (press Ctrl+C again to quit now)");
+                interrupted = true;
+                handle.try_cancel();
+                signal.set(shutdown_signal());
+                continue;
+            }
+        };
+        match event {
+            RunEvent::Started { run_id: id, .. } => run_id = Some(id),
+            // Asked off the runtime, so a signal still cancels meanwhile
+            RunEvent::QuestionAsked { question, .. } => {
+                let handle = Arc::clone(&handle);
+                tokio::task::spawn_blocking(move || match ask_question(&question) {
+                    Some(answer) => handle.answer(answer),
+                    None => handle.try_cancel(),
+                });
+            }
+            RunEvent::ReviewRequired { .. } => {
+                let handle = Arc::clone(&handle);
+                tokio::task::spawn_blocking(move || {
+                    if confirm_review() {
+                        handle.approve_review()
                     } else {
-                        println!("FAIL ({}ms)", result.duration_ms);
-                        all_passed = false;
+                        handle.reject_review()
+                    }
+                });
+            }
+            RunEvent::SaveFailed { what, error } => {
+                saves.record(&what, Err(error));
+            }
+            _ => {}
+        }
+    }
+
+    // The engine has said why the run couldn't start
+    let Some(run_id) = run_id else {
+        std::process::exit(1);
+    };
+    match RunState::load(&dirs.state()) {
+        Ok(state) => println!("\nRun {run_id} finished with status: {}", state.status),
+        Err(e) => eprintln!("\nRun {run_id} finished, but its state can't be read: {e}"),
+    }
+    if let Some(summary) = saves.summary() {
+        eprintln!("Warning: {summary}");
+    }
+    interrupted
+}
+
+/// Remove the run lock if this process holds it, for exiting without
+/// unwinding the run that took it.
+fn release_run_lock(ralf_dir: &Path) {
+    let ours = RunLock::read(ralf_dir)
+        .ok()
+        .flatten()
+        .is_some_and(|lock| lock.pid == std::process::id());
+    if ours {
+        let _ = std::fs::remove_file(RunLock::path(ralf_dir));
+    }
+}
+
+/// Prints a run's events as `ralf run` reports its progress.
+#[derive(Debug)]
+struct ConsoleSink;
+
+impl EventSink for ConsoleSink {
+    fn emit(&self, event: RunEvent) {
+        match event {
+            RunEvent::Started { run_id, .. } => println!("Run {run_id} started"),
+            RunEvent::IterationStarted { iteration, model } => {
+                println!("=== Iteration {iteration} - Model: {model} ===");
+            }
+            RunEvent::ModelCompleted {
+                duration_ms,
+                has_promise,
+                rate_limited,
+                ..
+            } => {
+                if rate_limited {
+                    println!("  Rate limited ({duration_ms}ms)");
+                } else {
+                    println!("  Model completed in {duration_ms}ms");
+                    println!("  Has promise: {has_promise}");
+                }
+            }
+            RunEvent::UsageUpdated {
+                usage, run_total, ..
+            } => println!(
+                "  Usage: {} (run total: {})",
+                usage.summary(),
+                run_total.summary()
+            ),
+            RunEvent::CooldownStarted {
+                model,
+                duration_secs,
+            } => println!("  {model} cooling down for {duration_secs}s"),
+            RunEvent::VerifierCompleted {
+                name,
+                passed,
+                duration_ms,
+                skipped,
+                ..
+            } => {
+                if !skipped {
+                    let verdict = if passed { "PASS" } else { "FAIL" };
+                    println!("  Verifier '{name}': {verdict} ({duration_ms}ms)");
+                }
+            }
+            RunEvent::VerificationStarted {
+                model,
+                criteria_count,
+                ..
+            } => println!("  Verifying {criteria_count} criteria with {model}"),
+            RunEvent::CriterionVerified {
+                id,
+                passed,
+                reason,
+                cached,
+                ..
+            } => {
+                let verdict = if passed { "PASS" } else { "FAIL" };
+                let cached = if cached { " (cached)" } else { "" };
+                match reason {
+                    Some(reason) => println!("    {id}: {verdict}{cached} - {reason}"),
+                    None => println!("    {id}: {verdict}{cached}"),
+                }
+            }
+            RunEvent::IterationCommitted { commit_sha, .. } => {
+                println!("  Committed {}", commit_sha.get(..8).unwrap_or(&commit_sha));
+            }
+            RunEvent::CompletionReported { report, .. } => {
+                if let Some(summary) = &report.summary {
+                    println!("  Report: {summary}");
+                }
+            }
+            RunEvent::Completed { reason, .. } => println!("\n{reason}"),
+            RunEvent::Failed { error, .. } => eprintln!("  {error}"),
+            RunEvent::Stuck { diagnosis, .. } => {
+                println!("  Stuck: {}", diagnosis.signals.join("; "));
+                for (i, suggestion) in diagnosis.suggestions.iter().enumerate() {
+                    println!(
+                        "  {}. {}: {}",
+                        i + 1,
+                        suggestion.action.label(),
+                        suggestion.title
+                    );
+                    if !suggestion.details.is_empty() {
+                        println!("     {}", suggestion.details);
                     }
-                    verifier_results.push(result);
                 }
-                Err(e) => {
-                    println!("ERROR: {e}");
-                    all_passed = false;
-                    verifier_results.push(ralf_engine::VerifierResult {
-                        name: verifier.name.clone(),
-                        passed: false,
-                        exit_code: None,
-                        output: e.to_string(),
-                        duration_ms: 0,
-                    });
+            }
+            RunEvent::Paused { iteration } => println!("Paused after iteration {iteration}"),
+            RunEvent::Resumed { .. } => println!("Resumed"),
+            RunEvent::ReviewRequired {
+                reasons,
+                files_changed,
+                lines_changed,
+                ..
+            } => {
+                println!("  Review needed ({files_changed} files, {lines_changed} lines changed):");
+                for reason in &reasons {
+                    println!("    - {reason}");
                 }
             }
+            RunEvent::SafetyViolation {
+                reverted,
+                forbidden_commands,
+                ..
+            } => println!(
+                "  Safety: {}",
+                violation_summary(&reverted, &forbidden_commands)
+            ),
+            RunEvent::SecretsDetected {
+                iteration,
+                findings,
+            } => println!("  Warning: {}", secrets_warning(iteration, &findings)),
+            RunEvent::NoOp {
+                iteration,
+                model,
+                consecutive,
+            } => println!(
+                "  Warning: {}",
+                no_op_warning(iteration, &model, consecutive)
+            ),
+            RunEvent::SaveFailed { what, error } => {
+                eprintln!("  Warning: {}", save_warning(&what, &error));
+            }
+            RunEvent::Status { message } => println!("  {message}"),
+            // Shown by the terminal prompts and the final status
+            RunEvent::QuestionAsked { .. }
+            | RunEvent::QuestionAnswered { .. }
+            | RunEvent::ReviewResolved { .. }
+            | RunEvent::IterationCompleted { .. }
+            | RunEvent::Cancelled { .. } => {}
         }
+    }
+}
 
-        // Determine status and reason
-        let (status, reason) = if invocation.has_promise && all_passed {
-            (
-                IterationStatus::Success,
-                "All verifiers passed with promise",
-            )
-        } else if invocation.has_promise && !all_passed {
-            (
-                IterationStatus::VerifierFailed,
-                "Promise found but verifiers failed",
-            )
-        } else if !invocation.has_promise && all_passed {
-            (
-                IterationStatus::VerifierFailed,
-                "Verifiers passed but no promise",
-            )
-        } else {
-            (
-                IterationStatus::VerifierFailed,
-                "Verifiers failed, no promise",
-            )
-        };
+/// Wait for Ctrl+C or, on Unix, SIGTERM.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if tokio::signal::ctrl_c().await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
 
-        // Write changelog entry
-        let entry = ChangelogEntry {
-            changelog_dir: &changelog_dir,
-            run_id: &run_id,
-            iteration: state.iteration,
-            invocation: &invocation,
-            verifier_results: &verifier_results,
-            prompt_hash: &prompt_hash,
-            git_info: &get_git_info(),
-            status,
-            reason,
-            log_path: run_dir.join(format!("{}.log", model.name)),
-        };
-        let _ = write_changelog_entry(&entry);
+    tokio::select! {
+        () = ctrl_c => {}
+        () = terminate => {}
+    }
+}
+
+/// Ask whether to go on with changes held for review. Without a terminal
+/// to ask on, the changes are rejected.
+fn confirm_review() -> bool {
+    use std::io::{IsTerminal, Write};
+
+    if !std::io::stdin().is_terminal() {
+        println!("  No terminal to review on; stopping");
+        return false;
+    }
+    print!("  Continue with these changes? [y/N] ");
+    let _ = std::io::stdout().flush();
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer).is_ok()
+        && matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// Put the model's question to the user and read the answer. Without a
+/// terminal to ask on, or with an empty answer, there is none.
+fn ask_question(question: &str) -> Option<String> {
+    use std::io::{IsTerminal, Write};
+
+    println!("  The model asks: {question}");
+    if !std::io::stdin().is_terminal() {
+        println!("  No terminal to answer on; stopping");
+        return None;
+    }
+    print!("  Your answer: ");
+    let _ = std::io::stdout().flush();
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer).ok()?;
+    let answer = answer.trim();
+    if answer.is_empty() {
+        println!("  No answer; stopping");
+        return None;
+    }
+    Some(answer.to_string())
+}
+
+/// Print a run's event log, optionally following it until the run ends.
+fn cmd_events(run_id: &str, follow: bool, json: bool) {
+    let path = events_path(Path::new(RALF_DIR), run_id);
+    if !follow && !path.exists() {
+        eprintln!("No events found for run {run_id}");
+        std::process::exit(1);
+    }
+
+    let mut tail = EventTail::new(&path);
+    loop {
+        // Only complete lines come back; a partial line is finished on the next read
+        let mut finished = false;
+        for line in tail.read_lines() {
+            let Some(record) = parse_event_line(&line) else {
+                continue;
+            };
+            if json {
+                print!("{line}");
+            } else {
+                println!("{}", format_event(&record));
+            }
+            finished |= matches!(
+                record.event,
+                RunEvent::Completed { .. }
+                    | RunEvent::Failed { .. }
+                    | RunEvent::Stuck { .. }
+                    | RunEvent::Cancelled { .. }
+            );
+        }
+
+        if !follow || finished {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(250));
+    }
+}
+
+/// Format an event record as `HH:MM:SS type key=value ...`.
+fn cmd_logs(run_id: Option<String>, iteration: Option<u64>, model: Option<&str>, follow: bool) {
+    let ralf_dir = Path::new(RALF_DIR);
+    let Some(run_id) = run_id.or_else(|| latest_run(ralf_dir)) else {
+        eprintln!("No runs found");
+        std::process::exit(1);
+    };
+    let run_dir = RalfDirs::new(ralf_dir).run(&run_id);
+    if !run_dir.is_dir() {
+        eprintln!("Run {run_id} not found");
+        std::process::exit(1);
+    }
+
+    let matches = |log: &LogFile| {
+        iteration.is_none_or(|n| log.iteration == Some(n)) && model.is_none_or(|m| log.name == m)
+    };
 
-        // Check for completion
-        if invocation.has_promise && all_passed {
-            println!("\n=== RUN COMPLETE ===");
-            println!("Promise found and all verifiers passed!");
-            state.complete();
+    // Logs are written once the process exits, so following means printing
+    // each new log as it appears
+    let mut printed = HashSet::new();
+    loop {
+        // Check before listing, so logs written just before the run
+        // finished are still printed
+        let running =
+            follow && RunState::load(&run_dir.join("state.json")).is_ok_and(|s| s.is_running());
+
+        for log in list_logs(&run_dir).into_iter().filter(|log| matches(log)) {
+            if printed.insert((log.path.clone(), log.modified)) {
+                print_log(&log);
+            }
+        }
+
+        if !running {
             break;
         }
+        std::thread::sleep(Duration::from_secs(1));
+    }
+
+    if printed.is_empty() {
+        eprintln!("No matching logs for run {run_id}");
+        std::process::exit(1);
+    }
+}
+
+fn cmd_report(run_id: Option<String>, format: ReportFormat, output: Option<PathBuf>) {
+    let ralf_dir = Path::new(RALF_DIR);
+    let Some(run_id) = run_id.or_else(|| latest_run(ralf_dir)) else {
+        eprintln!("No runs found");
+        std::process::exit(1);
+    };
+
+    let report = match RunReport::load(ralf_dir, &run_id) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    };
+    // Criterion text comes from the current prompt; verdicts are by index
+    let criteria = std::fs::read_to_string("PROMPT.md")
+        .map(|prompt| parse_criteria(&prompt))
+        .unwrap_or_default();
+    let mut report = report.with_criteria(&criteria);
+    if let Err(e) = report.collect_diff_stat(&GitSafety::new(".")) {
+        eprintln!("Warning: no diff stats: {e}");
+    }
+
+    let path = output.unwrap_or_else(|| report_path(ralf_dir, &run_id, format));
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        if let Err(e) = std::fs::create_dir_all(parent) {
+            eprintln!("Failed to create {}: {e}", parent.display());
+            std::process::exit(1);
+        }
+    }
+    if let Err(e) = std::fs::write(&path, report.render(format)) {
+        eprintln!("Failed to write {}: {e}", path.display());
+        std::process::exit(1);
+    }
+    println!("Wrote report to {}", path.display());
+}
+
+fn cmd_commit_msg(run_id: Option<String>, model: Option<&str>) {
+    let ralf_dir = Path::new(RALF_DIR);
+    let Some(run_id) = run_id.or_else(|| latest_run(ralf_dir)) else {
+        eprintln!("No runs found");
+        std::process::exit(1);
+    };
+
+    let spec = std::fs::read_to_string("PROMPT.md").unwrap_or_default();
+    let message = match generate_commit_message(ralf_dir, &run_id, &spec) {
+        Ok(message) => message,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    };
+
+    let Some(name) = model else {
+        println!("{message}");
+        return;
+    };
+    let config = Config::load_layered(Path::new("."))
+        .map(|layered| layered.config)
+        .unwrap_or_default();
+    let Some(model) = config.get_model(name) else {
+        eprintln!("Unknown model: {name}");
+        std::process::exit(1);
+    };
+    let rt = tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
+    match rt.block_on(refine_commit_message(
+        model,
+        &message,
+        model.timeout_seconds,
+    )) {
+        Ok(refined) => println!("{refined}"),
+        Err(e) => {
+            eprintln!("Warning: {name} couldn't refine the message: {e}");
+            println!("{message}");
+        }
+    }
+}
+
+fn cmd_changelog(run_id: Option<&str>, status: Option<IterationStatus>, json: bool) {
+    let store = ChangelogStore::new(RalfDirs::new(RALF_DIR).changelog());
+    let records = match status {
+        Some(status) => store.list_with_status(run_id, status),
+        None => store.list(run_id),
+    };
+    let records = match records {
+        Ok(records) => records,
+        Err(e) => {
+            eprintln!("Failed to read changelog: {e}");
+            std::process::exit(1);
+        }
+    };
+    let summary = ChangelogSummary::from_records(&records);
+
+    if json {
+        let output = serde_json::json!({
+            "entries": records,
+            "summary": summary,
+        });
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&output).expect("failed to serialize")
+        );
+        return;
+    }
+
+    if records.is_empty() {
+        println!("No changelog entries");
+        return;
+    }
+
+    let mut last_run = None;
+    for record in &records {
+        if last_run != Some(&record.run_id) {
+            println!("Run {}", record.run_id);
+            last_run = Some(&record.run_id);
+        }
+        let duration = record
+            .duration_ms
+            .map(|ms| format!("{:.1}s", Duration::from_millis(ms).as_secs_f64()))
+            .unwrap_or_default();
+        println!(
+            "  #{:<3} {:<10} {:<16} {duration:>8}  {}",
+            record.iteration, record.model, record.status, record.reason
+        );
+    }
+
+    println!("\nBy model:");
+    for (model, totals) in &summary.models {
+        println!(
+            "  {model}: {} iterations, {} succeeded, {:.1}s",
+            totals.iterations,
+            totals.successes,
+            Duration::from_millis(totals.duration_ms).as_secs_f64()
+        );
+    }
+    let statuses: Vec<String> = summary
+        .statuses
+        .iter()
+        .map(|(status, count)| format!("{status} {count}"))
+        .collect();
+    println!("\nOutcomes: {}", statuses.join(", "));
+    println!(
+        "Total: {} iterations, {:.1}s",
+        summary.iterations,
+        Duration::from_millis(summary.total_duration_ms).as_secs_f64()
+    );
+}
+
+fn cmd_stats(json: bool) {
+    let analytics = Analytics::load(Path::new(RALF_DIR));
+
+    if json {
+        let models: serde_json::Map<String, serde_json::Value> = analytics
+            .models
+            .iter()
+            .map(|(model, stats)| {
+                let value = serde_json::json!({
+                    "invocations": stats.invocations,
+                    "successes": stats.successes,
+                    "success_rate": stats.success_rate(),
+                    "rate_limited": stats.rate_limited,
+                    "rate_limit_rate": stats.rate_limit_rate(),
+                    "median_duration_ms": stats.median_duration_ms(),
+                });
+                (model.clone(), value)
+            })
+            .collect();
+        let output = serde_json::json!({
+            "runs": analytics.runs,
+            "completed_runs": analytics.completed_runs(),
+            "models": models,
+            "iterations_to_complete": {
+                "median": analytics.median_iterations_to_complete(),
+                "distribution": analytics.iterations_to_complete_distribution(),
+            },
+        });
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&output).expect("failed to serialize")
+        );
+        return;
+    }
+
+    if analytics.runs == 0 {
+        println!("No runs found");
+        return;
+    }
+
+    println!(
+        "{:<12} {:>11} {:>8} {:>13} {:>9}",
+        "Model", "Invocations", "Success", "Rate limited", "Median"
+    );
+    for (model, stats) in &analytics.models {
+        let median = stats.median_duration_ms().map_or_else(
+            || "-".to_string(),
+            |ms| format!("{:.1}s", Duration::from_millis(ms).as_secs_f64()),
+        );
+        println!(
+            "{model:<12} {:>11} {:>7.0}% {:>12.0}% {median:>9}",
+            stats.invocations,
+            stats.success_rate() * 100.0,
+            stats.rate_limit_rate() * 100.0,
+        );
+    }
+
+    println!(
+        "\nRuns: {} ({} completed)",
+        analytics.runs,
+        analytics.completed_runs()
+    );
+    if let Some(median) = analytics.median_iterations_to_complete() {
+        println!("Iterations to complete (median {median}):");
+        for (iterations, runs) in analytics.iterations_to_complete_distribution() {
+            println!("  {iterations:>3}: {}", "#".repeat(runs));
+        }
+    }
+}
+
+fn cmd_archive(run_id: Option<String>, output: Option<PathBuf>, import: Option<&Path>) {
+    let config = load_config();
+    let dirs = RalfDirs::new(RALF_DIR).configured(&config);
+
+    if let Some(archive) = import {
+        match import_run(&dirs, archive) {
+            Ok(manifest) => println!(
+                "Imported run {} ({} files, written by ralf {})",
+                manifest.run_id,
+                manifest.files.len(),
+                manifest.ralf_version
+            ),
+            Err(e) => {
+                eprintln!("Error importing {}: {e}", archive.display());
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
 
-        println!("  Status: {status} - {reason}");
+    let Some(run_id) = run_id.or_else(|| latest_run(dirs.root())) else {
+        eprintln!("No runs found");
+        std::process::exit(1);
+    };
+    let output = output.unwrap_or_else(|| PathBuf::from(format!("{run_id}.tar.zst")));
+    match export_run(&dirs, &run_id, Path::new("PROMPT.md"), &config, &output) {
+        Ok(manifest) => {
+            println!(
+                "Archived run {run_id} to {} ({} files)",
+                output.display(),
+                manifest.files.len()
+            );
+            if !manifest.has_spec() {
+                println!("PROMPT.md changed since the run started, so it was left out");
+            }
+        }
+        Err(e) => {
+            eprintln!("Error archiving run {run_id}: {e}");
+            std::process::exit(1);
+        }
     }
+}
 
-    // Save final state
-    let _ = state.save(&state_path);
-    let _ = cooldowns.save(&cooldowns_path);
+fn cmd_gc(dry_run: bool) {
+    let config = load_config();
+    let dirs = RalfDirs::new(RALF_DIR).configured(&config);
+    let report = match gc(&dirs, &config.retention, dry_run) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Error pruning {RALF_DIR}: {e}");
+            std::process::exit(1);
+        }
+    };
 
-    println!("\nRun {} finished with status: {}", run_id, state.status);
+    let kept = if dry_run { "Would keep" } else { "Kept" };
+    for run_id in &report.kept_worktrees {
+        println!(
+            "{kept} {}: it has uncommitted changes",
+            dirs.worktrees().join(run_id).display()
+        );
+    }
+
+    if report.is_empty() {
+        println!("Nothing to remove");
+        return;
+    }
+    for run_id in &report.runs {
+        println!("  run {run_id}");
+    }
+    for draft in &report.drafts {
+        println!("  draft {draft}");
+    }
+    for run_id in &report.worktrees {
+        println!("  worktree {run_id}");
+    }
+    let verb = if dry_run { "Would remove" } else { "Removed" };
+    println!("{verb} {}", report.summary());
+}
+
+fn print_log(log: &LogFile) {
+    let content = match read_log(&log.path) {
+        Ok(content) => content,
+        Err(e) => {
+            eprintln!("Failed to read {}: {e}", log.path.display());
+            return;
+        }
+    };
+    let sections = parse_log(&content);
+
+    println!("=== {} ({}) ===", log.title(), log.path.display());
+    if sections.stdout.is_empty() && sections.stderr.is_empty() {
+        println!("(no output)");
+    }
+    if !sections.stdout.is_empty() {
+        println!("{}", sections.stdout);
+    }
+    if !sections.stderr.is_empty() {
+        println!("--- stderr ---");
+        println!("{}", sections.stderr);
+    }
+    println!();
+}
+
+fn format_event(record: &EventRecord) -> String {
+    use std::fmt::Write;
+
+    let mut line = record.timestamp.format("%H:%M:%S").to_string();
+    let Ok(serde_json::Value::Object(fields)) = serde_json::to_value(&record.event) else {
+        return line;
+    };
+    if let Some(serde_json::Value::String(kind)) = fields.get("type") {
+        let _ = write!(line, " {kind:<20}");
+    }
+    for (key, value) in &fields {
+        if key == "type" || value.is_null() {
+            continue;
+        }
+        let _ = write!(line, " {key}={value}");
+    }
+    line
 }
@@ -0,0 +1,114 @@
+//! Adapter for the Claude Code CLI.
+
+use super::{check_rate_limit, CommandSpec, ModelAdapter};
+use crate::config::ModelConfig;
+use crate::usage::{self, Usage};
+
+/// Adapter for `claude`.
+///
+/// Runs in print mode (`-p`) and reads the prompt from stdin. Asks for the
+/// JSON result, which is the only output that reports usage, and takes
+/// the response from its `result`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ClaudeAdapter;
+
+impl ModelAdapter for ClaudeAdapter {
+    fn name(&self) -> &'static str {
+        "claude"
+    }
+
+    fn command(&self, model: &ModelConfig, prompt: &str) -> CommandSpec {
+        let mut spec = CommandSpec::from_argv(&model.command_argv, &model.name, prompt);
+        // Configs written before JSON was the default ask for text
+        match spec.args.iter().position(|arg| arg == "--output-format") {
+            Some(i) => {
+                if let Some(format) = spec.args.get_mut(i + 1).filter(|f| *f == "text") {
+                    *format = "json".into();
+                }
+            }
+            None => spec.args.extend(["--output-format".into(), "json".into()]),
+        }
+        spec
+    }
+
+    fn probe_command(&self, prompt: &str) -> CommandSpec {
+        CommandSpec {
+            program: "claude".into(),
+            args: vec!["-p".into(), "--output-format".into(), "text".into()],
+            stdin: Some(format!("{prompt}\n")),
+        }
+    }
+
+    fn auth_command(&self) -> Option<Vec<String>> {
+        // The interactive session walks through login when signed out
+        Some(vec!["claude".into()])
+    }
+
+    fn is_rate_limited(&self, output: &str, patterns: &[String]) -> bool {
+        // Subscription limits are reported as "Claude AI usage limit reached"
+        check_rate_limit(output, patterns) || output.to_lowercase().contains("usage limit reached")
+    }
+
+    fn parse_usage(&self, stdout: &str, stderr: &str) -> Option<Usage> {
+        usage::parse_claude_usage(stdout, stderr)
+    }
+
+    fn parse_output(&self, stdout: &str, stderr: &str) -> String {
+        let response = usage::claude_result(stdout)
+            .and_then(|result| result["result"].as_str().map(str::to_string));
+        match response {
+            Some(response) => response,
+            None if stdout.trim().is_empty() => stderr.to_string(),
+            None => stdout.to_string(),
+        }
+    }
+
+    fn streams_output(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_claude_usage_limit_is_rate_limit() {
+        let adapter = ClaudeAdapter;
+        assert!(adapter.is_rate_limited("Claude AI usage limit reached|1736700000", &[]));
+        assert!(!adapter.is_rate_limited("All done", &[]));
+    }
+
+    #[test]
+    fn test_claude_asks_for_json() {
+        let mut model = ModelConfig::default_for("claude");
+        let spec = ClaudeAdapter.command(&model, "hello");
+        assert_eq!(spec.args[1..3], ["--output-format", "json"]);
+
+        model.command_argv = vec![
+            "claude".into(),
+            "-p".into(),
+            "--output-format".into(),
+            "text".into(),
+        ];
+        let spec = ClaudeAdapter.command(&model, "hello");
+        assert_eq!(spec.args, ["-p", "--output-format", "json"]);
+
+        model.command_argv = vec!["claude".into(), "-p".into()];
+        let spec = ClaudeAdapter.command(&model, "hello");
+        assert_eq!(spec.args, ["-p", "--output-format", "json"]);
+    }
+
+    #[test]
+    fn test_claude_response_from_json_result() {
+        let stdout = r#"{"type":"result","result":"Done. <promise>COMPLETE</promise>","total_cost_usd":0.5,"usage":{"input_tokens":10,"output_tokens":5}}"#;
+        let adapter = ClaudeAdapter;
+        assert_eq!(
+            adapter.parse_output(stdout, ""),
+            "Done. <promise>COMPLETE</promise>"
+        );
+        assert_eq!(adapter.parse_usage(stdout, "").unwrap().total_tokens, 15);
+        // Text output is taken as it is
+        assert_eq!(adapter.parse_output("Done.\n", ""), "Done.\n");
+    }
+}
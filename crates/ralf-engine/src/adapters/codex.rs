@@ -0,0 +1,49 @@
+//! Adapter for the `OpenAI` Codex CLI.
+
+use super::{check_rate_limit, CommandSpec, ModelAdapter};
+use crate::usage::{self, Usage};
+
+/// Adapter for `codex`.
+///
+/// Uses `codex exec -`, which reads the prompt from stdin.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CodexAdapter;
+
+impl ModelAdapter for CodexAdapter {
+    fn name(&self) -> &'static str {
+        "codex"
+    }
+
+    fn probe_command(&self, prompt: &str) -> CommandSpec {
+        CommandSpec {
+            program: "codex".into(),
+            args: vec!["exec".into(), "-".into()],
+            stdin: Some(format!("{prompt}\n")),
+        }
+    }
+
+    fn auth_command(&self) -> Option<Vec<String>> {
+        Some(vec!["codex".into(), "login".into()])
+    }
+
+    fn is_rate_limited(&self, output: &str, patterns: &[String]) -> bool {
+        // Plan limits are reported as "You've hit your usage limit"
+        check_rate_limit(output, patterns) || output.to_lowercase().contains("hit your usage limit")
+    }
+
+    fn parse_usage(&self, _stdout: &str, stderr: &str) -> Option<Usage> {
+        usage::parse_codex_usage(stderr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codex_usage_limit_is_rate_limit() {
+        let adapter = CodexAdapter;
+        assert!(adapter.is_rate_limited("ERROR: You've hit your usage limit.", &[]));
+        assert!(!adapter.is_rate_limited("Applied patch", &[]));
+    }
+}
@@ -0,0 +1,68 @@
+//! Adapter for the Google Gemini CLI.
+
+use super::{CommandSpec, ModelAdapter};
+use crate::config::ModelConfig;
+
+/// Longest prompt passed as an argument. Linux refuses arguments over
+/// 128 KiB, and gemini's context budget makes prompts far longer than that.
+const MAX_ARG_PROMPT_BYTES: usize = 64 * 1024;
+
+/// Adapter for `gemini`.
+///
+/// The Gemini CLI takes the prompt as an argument (`-p <prompt>`). Longer
+/// prompts go on stdin, which it puts before the (then empty) argument.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GeminiAdapter;
+
+impl ModelAdapter for GeminiAdapter {
+    fn name(&self) -> &'static str {
+        "gemini"
+    }
+
+    fn command(&self, model: &ModelConfig, prompt: &str) -> CommandSpec {
+        let mut spec = CommandSpec::from_argv(&model.command_argv, &model.name, prompt);
+        if prompt.len() > MAX_ARG_PROMPT_BYTES {
+            spec.args.push(String::new());
+        } else {
+            spec.args.push(prompt.to_string());
+            spec.stdin = None;
+        }
+        spec
+    }
+
+    fn probe_command(&self, prompt: &str) -> CommandSpec {
+        CommandSpec {
+            program: "gemini".into(),
+            args: vec!["-p".into(), prompt.into()],
+            stdin: None,
+        }
+    }
+
+    fn auth_command(&self) -> Option<Vec<String>> {
+        // The interactive session prompts for a login method when signed out
+        Some(vec!["gemini".into()])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gemini_passes_prompt_as_argument() {
+        let model = ModelConfig::default_for("gemini");
+        let spec = GeminiAdapter.command(&model, "hello");
+        assert_eq!(spec.program, "gemini");
+        assert_eq!(spec.args, vec!["-p".to_string(), "hello".to_string()]);
+        assert!(spec.stdin.is_none());
+    }
+
+    #[test]
+    fn test_gemini_sends_long_prompt_on_stdin() {
+        let model = ModelConfig::default_for("gemini");
+        let prompt = "x".repeat(200 * 1024);
+        let spec = GeminiAdapter.command(&model, &prompt);
+        assert_eq!(spec.args, vec!["-p".to_string(), String::new()]);
+        assert_eq!(spec.stdin.as_deref(), Some(prompt.as_str()));
+    }
+}
@@ -0,0 +1,32 @@
+//! Adapter for arbitrary model CLIs.
+
+use super::{CommandSpec, ModelAdapter};
+
+/// Adapter for any CLI that reads a prompt from stdin and prints a response.
+///
+/// Used for models without a dedicated adapter.
+#[derive(Debug, Clone)]
+pub struct GenericAdapter {
+    name: String,
+}
+
+impl GenericAdapter {
+    /// Create an adapter for the named CLI.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into() }
+    }
+}
+
+impl ModelAdapter for GenericAdapter {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn probe_command(&self, prompt: &str) -> CommandSpec {
+        CommandSpec {
+            program: self.name.clone(),
+            args: Vec::new(),
+            stdin: Some(format!("{prompt}\n")),
+        }
+    }
+}
@@ -0,0 +1,319 @@
+//! Scripted stand-in for a model CLI, for testing without real models.
+//!
+//! The `mock` model answers in-process from files in `.ralf/mock/`, so the
+//! runner and TUI can be exercised end to end with no model CLI installed.
+//! Iteration `N` answers with `.ralf/mock/<N>.md`, falling back to
+//! `default.md`; verifying its completion criteria answers with
+//! `<N>.verify.md`, falling back to `verify.md`. Prompts sent outside a run
+//! (chat, assessment, probes) answer with `chat.md`, falling back to
+//! `default.md`. Without a file, the mock gives a canned reply.
+//!
+//! A response file may start with a header that scripts how the mock
+//! behaves:
+//!
+//! ```text
+//! ---
+//! delay_ms: 1500
+//! rate_limit: true
+//! promise: COMPLETE
+//! exit_code: 1
+//! ---
+//! The response body.
+//! ```
+//!
+//! `promise` appends `<promise>COMPLETE</promise>` to the response, and
+//! `rate_limit` reports a 429 on stderr (exiting with 1 unless `exit_code`
+//! says otherwise).
+//!
+//! The mock is enabled by naming a `mock` model in the config, or by
+//! setting [`MOCK_MODELS_ENV`]`=1` so discovery offers it.
+
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::{CommandSpec, ModelAdapter};
+use crate::logs::{iteration_of, VERIFY_LOG_DIR};
+
+/// Name of the mock model.
+pub const MOCK_MODEL: &str = "mock";
+
+/// Environment variable that makes discovery offer the mock model.
+pub const MOCK_MODELS_ENV: &str = "RALF_MOCK_MODELS";
+
+/// Directory under `.ralf/` holding scripted responses.
+pub const MOCK_DIR: &str = "mock";
+
+/// Whether [`MOCK_MODELS_ENV`] is set to a truthy value.
+pub fn mock_models_enabled() -> bool {
+    std::env::var(MOCK_MODELS_ENV).is_ok_and(|value| is_truthy(&value))
+}
+
+fn is_truthy(value: &str) -> bool {
+    matches!(
+        value.trim().to_lowercase().as_str(),
+        "1" | "true" | "yes" | "on"
+    )
+}
+
+/// Output the mock produces in place of a model process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockResponse {
+    /// Text written to stdout.
+    pub stdout: String,
+    /// Text written to stderr.
+    pub stderr: String,
+    /// Exit code of the pretend process.
+    pub exit_code: i32,
+    /// How long to wait before answering.
+    pub delay: Duration,
+}
+
+impl MockResponse {
+    /// Parse a response file: an optional `---` header, then the body.
+    ///
+    /// Unknown header keys and bad values are reported on stderr rather
+    /// than failing, so a typo shows up in the log instead of aborting
+    /// the run.
+    pub fn parse(content: &str) -> Self {
+        let (header, body) = split_header(content);
+        let mut response = Self {
+            stdout: body.to_string(),
+            stderr: String::new(),
+            exit_code: 0,
+            delay: Duration::ZERO,
+        };
+        let mut exit_code = None;
+        let mut rate_limited = false;
+
+        for line in header.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let Some((key, value)) = line.split_once(':') else {
+                response.warn(&format!("ignoring header line '{line}'"));
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim());
+            match key {
+                "delay_ms" => match value.parse() {
+                    Ok(ms) => response.delay = Duration::from_millis(ms),
+                    Err(_) => response.warn(&format!("invalid delay_ms '{value}'")),
+                },
+                "rate_limit" => rate_limited = is_truthy(value),
+                "promise" => {
+                    if !response.stdout.is_empty() && !response.stdout.ends_with('\n') {
+                        response.stdout.push('\n');
+                    }
+                    let _ = writeln!(response.stdout, "<promise>{value}</promise>");
+                }
+                "exit_code" => match value.parse() {
+                    Ok(code) => exit_code = Some(code),
+                    Err(_) => response.warn(&format!("invalid exit_code '{value}'")),
+                },
+                other => response.warn(&format!("unknown header key '{other}'")),
+            }
+        }
+
+        if rate_limited {
+            response
+                .stderr
+                .push_str("Error: 429 Too Many Requests (mock rate limit)\n");
+        }
+        response.exit_code = exit_code.unwrap_or(i32::from(rate_limited));
+        response
+    }
+
+    /// The canned reply used when no response file exists.
+    fn canned(iteration: Option<u64>, verifying: bool) -> Self {
+        let stdout = match (iteration, verifying) {
+            (Some(n), true) => format!("Mock verification for iteration {n}.\n"),
+            (Some(n), false) => format!("Mock response for iteration {n}.\n"),
+            (None, _) => "ok\n".to_string(),
+        };
+        Self {
+            stdout,
+            stderr: String::new(),
+            exit_code: 0,
+            delay: Duration::ZERO,
+        }
+    }
+
+    fn warn(&mut self, message: &str) {
+        let _ = writeln!(self.stderr, "mock: {message}");
+    }
+}
+
+/// Split `content` into its `---` header and body. Content without a
+/// closed header is all body.
+fn split_header(content: &str) -> (&str, &str) {
+    let Some(rest) = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))
+    else {
+        return ("", content);
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return (&rest[..offset], &rest[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+    ("", content)
+}
+
+/// Adapter for the scripted `mock` model.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MockAdapter;
+
+impl MockAdapter {
+    /// The response for an invocation logging to `log_dir` (`None` outside
+    /// a run), read from the nearest `.ralf/mock/` at or above `work_dir`
+    /// so runs in a worktree find the repo's.
+    pub fn respond(work_dir: &Path, log_dir: Option<&Path>) -> MockResponse {
+        let iteration = log_dir.and_then(iteration_of);
+        let verifying = log_dir.is_some_and(|dir| dir.ends_with(VERIFY_LOG_DIR));
+        let Some(dir) = find_mock_dir(work_dir) else {
+            return MockResponse::canned(iteration, verifying);
+        };
+        response_files(iteration, verifying)
+            .iter()
+            .find_map(|name| std::fs::read_to_string(dir.join(name)).ok())
+            .map_or_else(
+                || MockResponse::canned(iteration, verifying),
+                |c| MockResponse::parse(&c),
+            )
+    }
+}
+
+/// Response files to try, in order.
+fn response_files(iteration: Option<u64>, verifying: bool) -> [String; 2] {
+    match (iteration, verifying) {
+        (Some(n), true) => [format!("{n}.verify.md"), "verify.md".into()],
+        (Some(n), false) => [format!("{n}.md"), "default.md".into()],
+        (None, _) => ["chat.md".into(), "default.md".into()],
+    }
+}
+
+fn find_mock_dir(work_dir: &Path) -> Option<PathBuf> {
+    work_dir
+        .ancestors()
+        .map(|dir| dir.join(".ralf").join(MOCK_DIR))
+        .find(|dir| dir.is_dir())
+}
+
+impl ModelAdapter for MockAdapter {
+    fn name(&self) -> &'static str {
+        MOCK_MODEL
+    }
+
+    fn probe_command(&self, prompt: &str) -> CommandSpec {
+        // Never run: probes answer through `mock_response`
+        CommandSpec {
+            program: MOCK_MODEL.into(),
+            args: Vec::new(),
+            stdin: Some(format!("{prompt}\n")),
+        }
+    }
+
+    fn mock_response(&self, work_dir: &Path, log_dir: Option<&Path>) -> Option<MockResponse> {
+        Some(Self::respond(work_dir, log_dir))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logs::iteration_dir;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_plain_body() {
+        let response = MockResponse::parse("Did the thing.\n");
+        assert_eq!(response.stdout, "Did the thing.\n");
+        assert_eq!(response.exit_code, 0);
+        assert_eq!(response.delay, Duration::ZERO);
+    }
+
+    #[test]
+    fn test_parse_header() {
+        let response =
+            MockResponse::parse("---\ndelay_ms: 250\npromise: COMPLETE\n---\nAll criteria met.");
+        assert_eq!(
+            response.stdout,
+            "All criteria met.\n<promise>COMPLETE</promise>\n"
+        );
+        assert_eq!(response.delay, Duration::from_millis(250));
+        assert!(response.stderr.is_empty());
+    }
+
+    #[test]
+    fn test_parse_rate_limit() {
+        let response = MockResponse::parse("---\nrate_limit: true\n---\n");
+        assert_eq!(response.exit_code, 1);
+        assert!(response.stderr.contains("429"));
+
+        let response = MockResponse::parse("---\nrate_limit: yes\nexit_code: 0\n---\n");
+        assert_eq!(response.exit_code, 0);
+    }
+
+    #[test]
+    fn test_parse_reports_bad_header() {
+        let response = MockResponse::parse("---\ndelay_ms: soon\ncolour: red\n---\nbody");
+        assert_eq!(response.stdout, "body");
+        assert!(response.stderr.contains("invalid delay_ms 'soon'"));
+        assert!(response.stderr.contains("unknown header key 'colour'"));
+
+        // An unclosed header is just body
+        let response = MockResponse::parse("---\ndelay_ms: 5\n");
+        assert_eq!(response.stdout, "---\ndelay_ms: 5\n");
+    }
+
+    #[test]
+    fn test_respond_reads_iteration_files() {
+        let temp = TempDir::new().unwrap();
+        let mock_dir = temp.path().join(".ralf").join(MOCK_DIR);
+        fs::create_dir_all(&mock_dir).unwrap();
+        fs::write(mock_dir.join("1.md"), "first").unwrap();
+        fs::write(mock_dir.join("default.md"), "fallback").unwrap();
+        fs::write(mock_dir.join("1.verify.md"), "CRITERION 1: PASS").unwrap();
+
+        let run_dir = Path::new("runs/run-1");
+        let respond =
+            |log_dir: Option<PathBuf>| MockAdapter::respond(temp.path(), log_dir.as_deref());
+        let first = iteration_dir(run_dir, 1);
+        assert_eq!(respond(Some(first.clone())).stdout, "first");
+        assert_eq!(respond(Some(iteration_dir(run_dir, 2))).stdout, "fallback");
+        assert_eq!(respond(None).stdout, "fallback");
+        assert_eq!(
+            respond(Some(first.join(VERIFY_LOG_DIR))).stdout,
+            "CRITERION 1: PASS"
+        );
+
+        // Worktrees below the repo find its responses
+        let worktree = temp.path().join(".ralf").join("worktrees").join("run-1");
+        fs::create_dir_all(&worktree).unwrap();
+        assert_eq!(
+            MockAdapter::respond(&worktree, Some(&first)).stdout,
+            "first"
+        );
+    }
+
+    #[test]
+    fn test_respond_without_files_is_canned() {
+        let temp = TempDir::new().unwrap();
+        let log_dir = iteration_dir(Path::new("runs/run-1"), 3);
+        let response = MockAdapter::respond(temp.path(), Some(&log_dir));
+        assert_eq!(response.stdout, "Mock response for iteration 3.\n");
+        let response = MockAdapter::respond(temp.path(), Some(&log_dir.join(VERIFY_LOG_DIR)));
+        assert_eq!(response.stdout, "Mock verification for iteration 3.\n");
+        assert_eq!(MockAdapter::respond(temp.path(), None).stdout, "ok\n");
+    }
+
+    #[test]
+    fn test_is_truthy() {
+        assert!(is_truthy("1"));
+        assert!(is_truthy(" TRUE "));
+        assert!(!is_truthy("0"));
+        assert!(!is_truthy(""));
+    }
+}
@@ -0,0 +1,291 @@
+//! Model adapters for ralf engine.
+//!
+//! Each model CLI has its own quirks: how the prompt is passed, what a
+//! rate limit or auth failure looks like, and where the useful output ends
+//! up. A [`ModelAdapter`] captures those quirks so the runner, chat, and
+//! discovery code can treat every model the same way.
+//!
+//! Adding support for a new model means implementing [`ModelAdapter`] and
+//! registering it in [`adapter_for`].
+
+mod claude;
+mod codex;
+mod gemini;
+mod generic;
+mod mock;
+
+pub use claude::ClaudeAdapter;
+pub use codex::CodexAdapter;
+pub use gemini::GeminiAdapter;
+pub use generic::GenericAdapter;
+pub use mock::{
+    mock_models_enabled, MockAdapter, MockResponse, MOCK_DIR, MOCK_MODEL, MOCK_MODELS_ENV,
+};
+
+use crate::config::ModelConfig;
+use crate::platform;
+use crate::usage::{self, Usage};
+use std::path::Path;
+use std::process::Stdio;
+
+/// A fully built model CLI command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandSpec {
+    /// Program to execute.
+    pub program: String,
+
+    /// Arguments passed to the program.
+    pub args: Vec<String>,
+
+    /// Input written to stdin, if the CLI reads the prompt from there.
+    pub stdin: Option<String>,
+}
+
+impl CommandSpec {
+    /// Build a spec from a command argv, sending `prompt` via stdin.
+    ///
+    /// Falls back to `fallback_program` if `argv` is empty.
+    pub fn from_argv(command_argv: &[String], fallback_program: &str, prompt: &str) -> Self {
+        let (program, args) = match command_argv.split_first() {
+            Some((program, args)) => (program.clone(), args.to_vec()),
+            None => (fallback_program.to_string(), Vec::new()),
+        };
+        Self {
+            program,
+            args,
+            stdin: Some(prompt.to_string()),
+        }
+    }
+
+    /// Create an async command with piped stdio.
+    pub fn to_tokio_command(&self) -> tokio::process::Command {
+        let mut cmd = crate::embed::command(&self.program, &self.args);
+        cmd.stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        cmd
+    }
+
+    /// Create a blocking command with piped stdio.
+    pub fn to_std_command(&self) -> std::process::Command {
+        let mut cmd = platform::std_command(&self.program, &self.args);
+        cmd.stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        cmd
+    }
+}
+
+/// Model-specific behavior for invoking and interpreting a model CLI.
+pub trait ModelAdapter: Send + Sync {
+    /// Model name this adapter handles.
+    fn name(&self) -> &str;
+
+    /// Build the command that sends `prompt` to the model.
+    ///
+    /// The default runs the configured argv and writes the prompt to stdin.
+    fn command(&self, model: &ModelConfig, prompt: &str) -> CommandSpec {
+        CommandSpec::from_argv(&model.command_argv, &model.name, prompt)
+    }
+
+    /// Build a minimal, non-agentic command used to probe the model.
+    fn probe_command(&self, prompt: &str) -> CommandSpec;
+
+    /// Check whether output indicates the model is rate limited.
+    fn is_rate_limited(&self, output: &str, patterns: &[String]) -> bool {
+        check_rate_limit(output, patterns)
+    }
+
+    /// Check whether output indicates the model needs authentication.
+    fn needs_auth(&self, output: &str) -> bool {
+        is_auth_error(&output.to_lowercase())
+    }
+
+    /// Interactive command that signs the user in, if the CLI has one.
+    ///
+    /// Run with the terminal attached, not captured.
+    fn auth_command(&self) -> Option<Vec<String>> {
+        None
+    }
+
+    /// Extract when a rate limit resets, if the output says so.
+    fn rate_limit_reset(&self, output: &str) -> Option<String> {
+        extract_reset_time(output)
+    }
+
+    /// Extract token and cost usage from raw process output, if reported.
+    fn parse_usage(&self, stdout: &str, stderr: &str) -> Option<Usage> {
+        usage::parse_usage(stdout, stderr)
+    }
+
+    /// Extract the model's response from raw process output.
+    ///
+    /// The default prefers stdout and falls back to stderr, since some
+    /// CLIs print their answer there.
+    fn parse_output(&self, stdout: &str, stderr: &str) -> String {
+        if stdout.trim().is_empty() {
+            stderr.to_string()
+        } else {
+            stdout.to_string()
+        }
+    }
+
+    /// Whether stdout is worth showing as it is written. CLIs that print
+    /// one JSON result at the end aren't.
+    fn streams_output(&self) -> bool {
+        true
+    }
+
+    /// Answer in-process instead of running a CLI, if this model is scripted.
+    ///
+    /// Only the mock model answers this way. `log_dir` is where a run logs
+    /// the invocation, and `None` for prompts sent outside a run.
+    fn mock_response(&self, _work_dir: &Path, _log_dir: Option<&Path>) -> Option<MockResponse> {
+        None
+    }
+}
+
+/// Get the adapter for a model by name.
+///
+/// Unknown models get a [`GenericAdapter`] that runs the configured argv.
+pub fn adapter_for(name: &str) -> Box<dyn ModelAdapter> {
+    match name {
+        "claude" => Box::new(ClaudeAdapter),
+        "codex" => Box::new(CodexAdapter),
+        "gemini" => Box::new(GeminiAdapter),
+        MOCK_MODEL => Box::new(MockAdapter),
+        other => Box::new(GenericAdapter::new(other)),
+    }
+}
+
+/// Check if output contains rate limit patterns.
+pub(crate) fn check_rate_limit(output: &str, patterns: &[String]) -> bool {
+    let lower = output.to_lowercase();
+    patterns.iter().any(|p| lower.contains(&p.to_lowercase()))
+}
+
+/// Check if output indicates an auth error (not success messages like "Loaded cached credentials").
+///
+/// Expects lowercased output.
+pub(crate) fn is_auth_error(output: &str) -> bool {
+    // Positive patterns indicating auth is needed
+    let needs_auth_patterns = [
+        "not authenticated",
+        "authentication required",
+        "unauthorized",
+        "please login",
+        "please sign in",
+        "api key required",
+        "api_key required",
+        "missing api key",
+        "invalid api key",
+        "no credentials",
+    ];
+
+    // Check for explicit auth error patterns first
+    for pattern in needs_auth_patterns {
+        if output.contains(pattern) {
+            return true;
+        }
+    }
+
+    // Check for "auth" keyword but exclude success messages
+    if output.contains("auth") && !output.contains("loaded") && !output.contains("success") {
+        return true;
+    }
+
+    // Check for "login" but exclude "please run ... login" success context
+    if output.contains("login required") || output.contains("must login") {
+        return true;
+    }
+
+    false
+}
+
+/// Extract rate limit reset time from error message.
+///
+/// Looks for patterns like:
+/// - "try again at Jan 12th, 2026 9:08 PM"
+/// - "resets at 2026-01-12T21:08:00"
+pub(crate) fn extract_reset_time(output: &str) -> Option<String> {
+    // Look for "try again at <datetime>"
+    if let Some(idx) = output.find("try again at ") {
+        let rest = &output[idx + 13..];
+        // Take until end of line or period
+        let end = rest.find(['.', '\n']).unwrap_or(rest.len());
+        let time_str = rest[..end].trim();
+        if !time_str.is_empty() {
+            return Some(time_str.to_string());
+        }
+    }
+
+    // Look for "resets at <datetime>"
+    if let Some(idx) = output.find("resets at ") {
+        let rest = &output[idx + 10..];
+        let end = rest.find(['.', '\n', '"']).unwrap_or(rest.len());
+        let time_str = rest[..end].trim();
+        if !time_str.is_empty() {
+            return Some(time_str.to_string());
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adapter_for_known_models() {
+        assert_eq!(adapter_for("claude").name(), "claude");
+        assert_eq!(adapter_for("codex").name(), "codex");
+        assert_eq!(adapter_for("gemini").name(), "gemini");
+        assert_eq!(adapter_for("mock").name(), "mock");
+        assert_eq!(adapter_for("llama").name(), "llama");
+    }
+
+    #[test]
+    fn test_default_command_uses_stdin() {
+        let model = ModelConfig::default_for("claude");
+        let spec = adapter_for("claude").command(&model, "do the thing");
+        assert_eq!(spec.program, "claude");
+        assert!(spec.args.contains(&"-p".to_string()));
+        assert_eq!(spec.stdin.as_deref(), Some("do the thing"));
+    }
+
+    #[test]
+    fn test_command_spec_empty_argv_falls_back_to_name() {
+        let spec = CommandSpec::from_argv(&[], "mymodel", "hi");
+        assert_eq!(spec.program, "mymodel");
+        assert!(spec.args.is_empty());
+    }
+
+    #[test]
+    fn test_is_auth_error() {
+        assert!(is_auth_error("error: not authenticated"));
+        assert!(is_auth_error("please login to continue"));
+        assert!(!is_auth_error("loaded cached credentials"));
+        assert!(!is_auth_error("ok"));
+    }
+
+    #[test]
+    fn test_extract_reset_time() {
+        assert_eq!(
+            extract_reset_time("Usage limit hit, try again at Jan 12th, 2026 9:08 PM."),
+            Some("Jan 12th, 2026 9:08 PM".into())
+        );
+        assert_eq!(
+            extract_reset_time("{\"msg\": \"quota resets at 2026-01-12T21:08:00\"}"),
+            Some("2026-01-12T21:08:00".into())
+        );
+        assert_eq!(extract_reset_time("nothing here"), None);
+    }
+
+    #[test]
+    fn test_parse_output_falls_back_to_stderr() {
+        let adapter = adapter_for("generic");
+        assert_eq!(adapter.parse_output("out", "err"), "out");
+        assert_eq!(adapter.parse_output("  \n", "err"), "err");
+    }
+}
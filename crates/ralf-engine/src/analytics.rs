@@ -0,0 +1,268 @@
+//! Per-model analytics over past runs.
+//!
+//! Unlike [`ModelStats`](crate::model_stats::ModelStats), which keeps
+//! recency-weighted averages for picking the next model, this aggregates
+//! the full history in `.ralf/runs/*/events.jsonl`: how often each model's
+//! iterations pass verification, how long it takes, how often it hits rate
+//! limits, and how many iterations runs need to complete.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::dirs::{RalfDirs, STATE_FILE};
+use crate::events::{events_path, read_events, EventRecord};
+use crate::runner::RunEvent;
+use crate::state::{RunState, RunStatus};
+
+/// Aggregated outcomes of one model's iterations.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModelAnalytics {
+    /// Model invocations.
+    pub invocations: usize,
+    /// Iterations whose verifiers all passed.
+    pub successes: usize,
+    /// Invocations that were rate limited.
+    pub rate_limited: usize,
+    /// Durations of invocations that weren't rate limited.
+    pub durations_ms: Vec<u64>,
+}
+
+impl ModelAnalytics {
+    /// Share of invocations whose iteration passed verification.
+    pub fn success_rate(&self) -> f64 {
+        ratio(self.successes, self.invocations)
+    }
+
+    /// Share of invocations that were rate limited.
+    pub fn rate_limit_rate(&self) -> f64 {
+        ratio(self.rate_limited, self.invocations)
+    }
+
+    /// Median duration of invocations that weren't rate limited.
+    pub fn median_duration_ms(&self) -> Option<u64> {
+        median(&self.durations_ms)
+    }
+}
+
+/// Analytics over a set of runs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Analytics {
+    /// Per-model outcomes, keyed by model name.
+    pub models: BTreeMap<String, ModelAnalytics>,
+    /// Runs included.
+    pub runs: usize,
+    /// Iterations each completed run needed, one entry per run.
+    pub iterations_to_complete: Vec<usize>,
+}
+
+impl Analytics {
+    /// Aggregate every run in `ralf_dir/runs`. Runs without a readable
+    /// event log are skipped.
+    pub fn load(ralf_dir: &Path) -> Self {
+        let mut analytics = Self::default();
+        let Ok(entries) = std::fs::read_dir(RalfDirs::new(ralf_dir).runs()) else {
+            return analytics;
+        };
+        for entry in entries.flatten() {
+            let Some(run_id) = entry.file_name().to_str().map(ToString::to_string) else {
+                continue;
+            };
+            let Ok(records) = read_events(&events_path(ralf_dir, &run_id)) else {
+                continue;
+            };
+            // Limits also end a run with `Completed`; only the run's own
+            // state says whether it actually finished its task
+            let completed = RunState::load(&entry.path().join(STATE_FILE))
+                .is_ok_and(|state| state.status == RunStatus::Completed);
+            analytics.add_run(&records, completed);
+        }
+        analytics
+    }
+
+    /// Fold one run's events into the totals. `completed` is whether the
+    /// run finished its task.
+    pub fn add_run(&mut self, records: &[EventRecord], completed: bool) {
+        self.runs += 1;
+        let mut iteration_models: BTreeMap<usize, &str> = BTreeMap::new();
+        let mut completed_at = None;
+
+        for record in records {
+            match &record.event {
+                RunEvent::IterationStarted { iteration, model } => {
+                    iteration_models.insert(*iteration, model);
+                }
+                RunEvent::ModelCompleted {
+                    model,
+                    duration_ms,
+                    rate_limited,
+                    ..
+                } => {
+                    let stats = self.models.entry(model.clone()).or_default();
+                    stats.invocations += 1;
+                    if *rate_limited {
+                        stats.rate_limited += 1;
+                    } else {
+                        stats.durations_ms.push(*duration_ms);
+                    }
+                }
+                RunEvent::IterationCompleted {
+                    iteration,
+                    all_verifiers_passed: true,
+                } => {
+                    if let Some(model) = iteration_models.get(iteration) {
+                        self.models
+                            .entry((*model).to_string())
+                            .or_default()
+                            .successes += 1;
+                    }
+                }
+                RunEvent::Completed { iteration, .. } => completed_at = Some(*iteration),
+                _ => {}
+            }
+        }
+
+        if let Some(iteration) = completed_at.filter(|_| completed) {
+            self.iterations_to_complete.push(iteration);
+        }
+    }
+
+    /// Number of runs that finished their task.
+    pub fn completed_runs(&self) -> usize {
+        self.iterations_to_complete.len()
+    }
+
+    /// Median iterations a completed run needed.
+    pub fn median_iterations_to_complete(&self) -> Option<usize> {
+        median(&self.iterations_to_complete)
+    }
+
+    /// How many completed runs needed each number of iterations.
+    pub fn iterations_to_complete_distribution(&self) -> BTreeMap<usize, usize> {
+        let mut distribution = BTreeMap::new();
+        for iterations in &self.iterations_to_complete {
+            *distribution.entry(*iterations).or_default() += 1;
+        }
+        distribution
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn ratio(count: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        count as f64 / total as f64
+    }
+}
+
+/// Median of `values`, the lower middle value for even counts.
+fn median<T: Copy + Ord>(values: &[T]) -> Option<T> {
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+    sorted.get(sorted.len().saturating_sub(1) / 2).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::EventLog;
+    use tempfile::TempDir;
+
+    fn model_completed(iteration: usize, model: &str, duration_ms: u64) -> RunEvent {
+        RunEvent::ModelCompleted {
+            iteration,
+            model: model.into(),
+            duration_ms,
+            has_promise: false,
+            rate_limited: duration_ms == 0,
+            output_preview: String::new(),
+        }
+    }
+
+    fn iteration(iteration: usize, model: &str, duration_ms: u64, passed: bool) -> Vec<RunEvent> {
+        vec![
+            RunEvent::IterationStarted {
+                iteration,
+                model: model.into(),
+            },
+            model_completed(iteration, model, duration_ms),
+            RunEvent::IterationCompleted {
+                iteration,
+                all_verifiers_passed: passed,
+            },
+        ]
+    }
+
+    fn write_run(ralf_dir: &Path, run_id: &str, events: &[RunEvent], status: Option<RunStatus>) {
+        let run_dir = ralf_dir.join("runs").join(run_id);
+        std::fs::create_dir_all(&run_dir).unwrap();
+        let mut log = EventLog::open(&run_dir).unwrap();
+        for event in events {
+            log.append(event).unwrap();
+        }
+        if let Some(status) = status {
+            let mut state = RunState::default();
+            state.start_run_with_id(run_id);
+            state.status = status;
+            state.save(&run_dir.join("state.json")).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_analytics_load() {
+        let temp = TempDir::new().unwrap();
+        let ralf_dir = temp.path();
+        assert_eq!(Analytics::load(ralf_dir), Analytics::default());
+
+        let mut completed = iteration(1, "claude", 4000, false);
+        completed.extend(iteration(2, "codex", 1000, true));
+        completed.push(RunEvent::Completed {
+            iteration: 2,
+            reason: "All criteria verified".into(),
+        });
+        write_run(ralf_dir, "done", &completed, Some(RunStatus::Completed));
+
+        let mut limited = iteration(1, "claude", 2000, false);
+        limited.push(model_completed(2, "codex", 0));
+        limited.push(RunEvent::Completed {
+            iteration: 2,
+            reason: "Max iterations reached".into(),
+        });
+        write_run(ralf_dir, "limited", &limited, Some(RunStatus::Failed));
+
+        std::fs::create_dir_all(ralf_dir.join("runs").join("empty")).unwrap();
+
+        let analytics = Analytics::load(ralf_dir);
+        assert_eq!(analytics.runs, 2);
+        assert_eq!(analytics.iterations_to_complete, vec![2]);
+        assert_eq!(analytics.median_iterations_to_complete(), Some(2));
+
+        let claude = &analytics.models["claude"];
+        assert_eq!((claude.invocations, claude.successes), (2, 0));
+        assert_eq!(claude.median_duration_ms(), Some(2000));
+
+        let codex = &analytics.models["codex"];
+        assert_eq!(
+            (codex.invocations, codex.successes, codex.rate_limited),
+            (2, 1, 1)
+        );
+        assert!((codex.success_rate() - 0.5).abs() < f64::EPSILON);
+        assert!((codex.rate_limit_rate() - 0.5).abs() < f64::EPSILON);
+        assert_eq!(codex.median_duration_ms(), Some(1000));
+    }
+
+    #[test]
+    fn test_iterations_to_complete_distribution() {
+        let analytics = Analytics {
+            iterations_to_complete: vec![3, 1, 3, 5],
+            ..Analytics::default()
+        };
+        assert_eq!(analytics.completed_runs(), 4);
+        assert_eq!(analytics.median_iterations_to_complete(), Some(3));
+        assert_eq!(
+            analytics.iterations_to_complete_distribution(),
+            BTreeMap::from([(1, 1), (3, 2), (5, 1)])
+        );
+        assert_eq!(median::<u64>(&[]), None);
+    }
+}
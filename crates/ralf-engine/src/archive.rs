@@ -0,0 +1,553 @@
+//! Run archives.
+//!
+//! `ralf archive` bundles everything about one run into a single
+//! zstd-compressed tar file (`.tar.zst`), to attach to a bug report or to move history to another
+//! machine: the run directory (logs, events and state), the run's
+//! changelog entries (as JSON records and as Markdown), the spec it ran
+//! against, and the config. A
+//! `manifest.json` at the start of the archive names its format version,
+//! the run and every file, and is checked before anything is imported.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+
+use crate::changelog::{ChangelogError, ChangelogRecord, ChangelogStore};
+use crate::config::Config;
+use crate::dirs::RalfDirs;
+use crate::runner::hash_prompt;
+use crate::snapshot::{CONFIG_SNAPSHOT_FILE, SPEC_SNAPSHOT_FILE};
+use crate::state::RunState;
+
+/// Version of the archive layout written by [`export_run`]. Version 2
+/// added the JSON changelog records.
+pub const ARCHIVE_VERSION: u32 = 2;
+
+/// The manifest's name in an archive.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Archived files of the run directory live under this directory.
+const RUN_PREFIX: &str = "run/";
+
+/// Archived changelog entries live under this directory, one file per model.
+const CHANGELOG_PREFIX: &str = "changelog/";
+
+/// Archived JSON changelog records live under this directory, one file per
+/// iteration.
+const RECORDS_PREFIX: &str = "changelog/entries/";
+
+/// The spec the run was started with.
+const SPEC_FILE: &str = SPEC_SNAPSHOT_FILE;
+
+/// The config at the time of archiving.
+const CONFIG_FILE: &str = CONFIG_SNAPSHOT_FILE;
+
+/// How hard zstd compresses archives (its default).
+const ZSTD_LEVEL: i32 = 3;
+
+/// First bytes of a gzip stream, as archives before zstd were written.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// What an archive holds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    /// Archive layout version.
+    pub version: u32,
+    /// The archived run.
+    pub run_id: String,
+    /// ralf version that wrote the archive.
+    pub ralf_version: String,
+    /// When the archive was written.
+    pub created_at: DateTime<Utc>,
+    /// Every other file in the archive.
+    pub files: Vec<String>,
+}
+
+impl ArchiveManifest {
+    /// Whether the archive includes the spec the run was started with.
+    pub fn has_spec(&self) -> bool {
+        self.files.iter().any(|file| file == SPEC_FILE)
+    }
+
+    /// Models with changelog entries in the archive.
+    pub fn changelog_models(&self) -> Vec<&str> {
+        self.files
+            .iter()
+            .filter_map(|file| file.strip_prefix(CHANGELOG_PREFIX)?.strip_suffix(".md"))
+            .filter(|model| !model.contains('/'))
+            .collect()
+    }
+}
+
+/// Error writing or importing an archive.
+#[derive(Debug, thiserror::Error)]
+pub enum ArchiveError {
+    /// I/O error.
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The run has no directory.
+    #[error("Run {0} not found")]
+    RunNotFound(String),
+
+    /// The archived run is already here.
+    #[error("Run {0} already exists")]
+    RunExists(String),
+
+    /// The archive was written by a newer ralf.
+    #[error("Archive format version {0} is not supported (newest supported: {ARCHIVE_VERSION})")]
+    UnsupportedVersion(u32),
+
+    /// The archive is damaged or wasn't written by ralf.
+    #[error("Invalid archive: {0}")]
+    Invalid(String),
+
+    /// The changelog couldn't be read or written.
+    #[error(transparent)]
+    Changelog(#[from] ChangelogError),
+
+    /// JSON error.
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Write run `run_id` to an archive at `output`. The spec in `prompt_path`
+/// is included if it's still the one the run started with.
+pub fn export_run(
+    dirs: &RalfDirs,
+    run_id: &str,
+    prompt_path: &Path,
+    config: &Config,
+    output: &Path,
+) -> Result<ArchiveManifest, ArchiveError> {
+    let run_dir = dirs.run(run_id);
+    if !is_valid_run_id(run_id) || !run_dir.is_dir() {
+        return Err(ArchiveError::RunNotFound(run_id.to_string()));
+    }
+
+    let mut files: BTreeMap<String, Vec<u8>> = BTreeMap::new();
+    for path in files_under(&run_dir)? {
+        let relative = path.strip_prefix(&run_dir).unwrap_or(&path);
+        let name = format!("{RUN_PREFIX}{}", archive_path(relative));
+        files.insert(name, std::fs::read(&path)?);
+    }
+    let changelog = ChangelogStore::new(dirs.changelog());
+    for (model, entries) in changelog.run_entries(run_id)? {
+        files.insert(
+            format!("{CHANGELOG_PREFIX}{model}.md"),
+            entries.into_bytes(),
+        );
+    }
+    for record in changelog.run_records(run_id)? {
+        files.insert(
+            format!("{RECORDS_PREFIX}{}.json", record.iteration),
+            serde_json::to_vec_pretty(&record)?,
+        );
+    }
+    let started_with = RunState::load(&run_dir.join(crate::dirs::STATE_FILE))
+        .ok()
+        .and_then(|state| state.prompt_hash);
+    if let Ok(spec) = std::fs::read_to_string(prompt_path) {
+        if started_with.is_none_or(|hash| hash == hash_prompt(&spec)) {
+            files.insert(SPEC_FILE.to_string(), spec.into_bytes());
+        }
+    }
+    files.insert(CONFIG_FILE.to_string(), serde_json::to_vec_pretty(config)?);
+
+    let manifest = ArchiveManifest {
+        version: ARCHIVE_VERSION,
+        run_id: run_id.to_string(),
+        ralf_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: Utc::now(),
+        files: files.keys().cloned().collect(),
+    };
+
+    let mtime = u64::try_from(manifest.created_at.timestamp()).unwrap_or_default();
+    let mut tar = tar::Builder::new(zstd::Encoder::new(Vec::new(), ZSTD_LEVEL)?);
+    let manifest_json = serde_json::to_vec_pretty(&manifest)?;
+    write_entry(&mut tar, MANIFEST_FILE, &manifest_json, mtime)?;
+    for (name, content) in &files {
+        write_entry(&mut tar, name, content, mtime)?;
+    }
+    let compressed = tar.into_inner()?.finish()?;
+    if let Some(parent) = output.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(output, compressed)?;
+    Ok(manifest)
+}
+
+/// Check the archive at `archive` and add its run to `dirs`: the run
+/// directory, plus its changelog entries. The spec and config are kept in
+/// the run directory as `spec.md` and `config.json`, leaving the
+/// repository's own alone; a snapshot the run took when it started wins
+/// over them.
+pub fn import_run(dirs: &RalfDirs, archive: &Path) -> Result<ArchiveManifest, ArchiveError> {
+    let compressed = std::fs::read(archive)?;
+    let mut bytes = Vec::new();
+    let decompressed = if compressed.starts_with(&GZIP_MAGIC) {
+        GzDecoder::new(compressed.as_slice()).read_to_end(&mut bytes)
+    } else {
+        zstd::Decoder::new(compressed.as_slice())?.read_to_end(&mut bytes)
+    };
+    decompressed.map_err(|e| ArchiveError::Invalid(e.to_string()))?;
+    let mut entries = read_entries(&bytes)?.into_iter();
+
+    let manifest: ArchiveManifest = match entries.next() {
+        Some((name, content)) if name == MANIFEST_FILE => serde_json::from_slice(&content)
+            .map_err(|e| ArchiveError::Invalid(format!("unreadable manifest: {e}")))?,
+        _ => return Err(ArchiveError::Invalid(format!("no {MANIFEST_FILE}"))),
+    };
+    if manifest.version > ARCHIVE_VERSION {
+        return Err(ArchiveError::UnsupportedVersion(manifest.version));
+    }
+    if !is_valid_run_id(&manifest.run_id) {
+        return Err(ArchiveError::Invalid(format!(
+            "bad run ID {:?}",
+            manifest.run_id
+        )));
+    }
+
+    let files: BTreeMap<String, Vec<u8>> = entries.collect();
+    let listed: BTreeSet<&String> = manifest.files.iter().collect();
+    if listed != files.keys().collect() {
+        return Err(ArchiveError::Invalid(
+            "files don't match the manifest".to_string(),
+        ));
+    }
+    let unsafe_path = |name: &&String| {
+        !is_safe_path(name)
+            || name
+                .strip_prefix(CHANGELOG_PREFIX)
+                .is_some_and(|model| model.contains('/') && !name.starts_with(RECORDS_PREFIX))
+            || name
+                .strip_prefix(RECORDS_PREFIX)
+                .is_some_and(|record| record.contains('/'))
+    };
+    if let Some(bad) = files.keys().find(unsafe_path) {
+        return Err(ArchiveError::Invalid(format!("unsafe path {bad}")));
+    }
+    let mut records = Vec::new();
+    for (name, content) in &files {
+        let Some(file) = name.strip_prefix(RECORDS_PREFIX) else {
+            continue;
+        };
+        let record: ChangelogRecord = serde_json::from_slice(content)
+            .map_err(|e| ArchiveError::Invalid(format!("unreadable {name}: {e}")))?;
+        if record.run_id != manifest.run_id || file != format!("{}.json", record.iteration) {
+            return Err(ArchiveError::Invalid(format!(
+                "{name} records run {} iteration {}",
+                record.run_id, record.iteration
+            )));
+        }
+        records.push(record);
+    }
+
+    let run_dir = dirs.run(&manifest.run_id);
+    if run_dir.exists() {
+        return Err(ArchiveError::RunExists(manifest.run_id));
+    }
+
+    let changelog = ChangelogStore::new(dirs.changelog());
+    for record in &records {
+        changelog.record(record)?;
+    }
+    for (name, content) in &files {
+        if name.starts_with(RECORDS_PREFIX) {
+            continue;
+        }
+        if let Some(model) = name.strip_prefix(CHANGELOG_PREFIX) {
+            let model = model.strip_suffix(".md").unwrap_or(model);
+            changelog.append_entries(model, &String::from_utf8_lossy(content))?;
+            continue;
+        }
+        if files.contains_key(&format!("{RUN_PREFIX}{name}")) {
+            continue;
+        }
+        let target = run_dir.join(name.strip_prefix(RUN_PREFIX).unwrap_or(name));
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(target, content)?;
+    }
+    std::fs::create_dir_all(&run_dir)?;
+    Ok(manifest)
+}
+
+/// Whether `run_id` names a directory directly under `runs/`.
+fn is_valid_run_id(run_id: &str) -> bool {
+    !run_id.is_empty() && is_safe_path(run_id) && !run_id.contains('/')
+}
+
+/// Whether an archived path stays inside the directory it's unpacked in.
+fn is_safe_path(path: &str) -> bool {
+    !path.contains('\\')
+        && Path::new(path)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+}
+
+/// `path` with `/` separators.
+fn archive_path(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Every file under `dir`, recursively.
+fn files_under(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let kind = entry.file_type()?;
+        if kind.is_dir() {
+            files.extend(files_under(&entry.path())?);
+        } else if kind.is_file() {
+            files.push(entry.path());
+        }
+    }
+    Ok(files)
+}
+
+/// Add a regular file, last modified at the Unix timestamp `mtime`, to
+/// `tar`.
+fn write_entry(
+    tar: &mut tar::Builder<impl Write>,
+    name: &str,
+    content: &[u8],
+    mtime: u64,
+) -> Result<(), ArchiveError> {
+    let mut header = tar::Header::new_gnu();
+    header.set_entry_type(tar::EntryType::Regular);
+    header.set_mode(0o644);
+    header.set_size(content.len() as u64);
+    header.set_mtime(mtime);
+    tar.append_data(&mut header, name, content)?;
+    Ok(())
+}
+
+/// The regular files of a tar stream, in order, with their names as
+/// written.
+fn read_entries(bytes: &[u8]) -> Result<Vec<(String, Vec<u8>)>, ArchiveError> {
+    let invalid = |e: std::io::Error| ArchiveError::Invalid(e.to_string());
+    let mut archive = tar::Archive::new(bytes);
+    let mut entries = Vec::new();
+    for entry in archive.entries().map_err(invalid)? {
+        let mut entry = entry.map_err(invalid)?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = String::from_utf8_lossy(&entry.path_bytes()).into_owned();
+        let mut content = Vec::new();
+        entry.read_to_end(&mut content).map_err(invalid)?;
+        entries.push((name, content));
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::changelog::{IterationStatus, VerifierRecord, VerifierStatus};
+    use tempfile::TempDir;
+
+    fn run_with_history(dirs: &RalfDirs, run_id: &str) {
+        let iteration = dirs.run(run_id).join("iteration-1");
+        std::fs::create_dir_all(&iteration).unwrap();
+        std::fs::write(iteration.join("claude.log"), "=== STDOUT ===\nhi\n").unwrap();
+        std::fs::write(dirs.run(run_id).join("events.jsonl"), "{}\n").unwrap();
+        ChangelogStore::new(dirs.changelog())
+            .append_entries(
+                "claude",
+                &format!("\n## Run {run_id} — Iteration 1\n\n- **Status**: success\n"),
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn test_export_and_import() {
+        let temp = TempDir::new().unwrap();
+        let here = RalfDirs::new(temp.path().join("here"));
+        run_with_history(&here, "run-1");
+        run_with_history(&here, "run-2");
+        // The config the run started with, as the runner snapshots it
+        std::fs::write(here.run("run-1").join(CONFIG_FILE), "{\"started\": true}").unwrap();
+        let prompt = temp.path().join("PROMPT.md");
+        std::fs::write(&prompt, "Do the thing").unwrap();
+        let archive = temp.path().join("run-1.tar.zst");
+
+        let manifest = export_run(&here, "run-1", &prompt, &Config::default(), &archive).unwrap();
+        assert!(manifest.has_spec());
+        assert_eq!(manifest.changelog_models(), ["claude"]);
+        assert!(manifest
+            .files
+            .contains(&"run/iteration-1/claude.log".to_string()));
+
+        let there = RalfDirs::new(temp.path().join("there"));
+        let imported = import_run(&there, &archive).unwrap();
+        assert_eq!(imported, manifest);
+        assert_eq!(
+            std::fs::read_to_string(there.run("run-1").join("iteration-1/claude.log")).unwrap(),
+            "=== STDOUT ===\nhi\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(there.run("run-1").join(SPEC_FILE)).unwrap(),
+            "Do the thing"
+        );
+        assert_eq!(
+            std::fs::read_to_string(there.run("run-1").join(CONFIG_FILE)).unwrap(),
+            "{\"started\": true}"
+        );
+        let records = ChangelogStore::new(there.changelog()).list(None).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].run_id, "run-1");
+
+        assert!(matches!(
+            import_run(&there, &archive),
+            Err(ArchiveError::RunExists(_))
+        ));
+    }
+
+    #[test]
+    fn test_archives_changelog_records() {
+        let temp = TempDir::new().unwrap();
+        let here = RalfDirs::new(temp.path().join("here"));
+        run_with_history(&here, "run-1");
+        let record = ChangelogRecord {
+            run_id: "run-1".into(),
+            iteration: 2,
+            model: "claude".into(),
+            status: IterationStatus::Success,
+            reason: String::new(),
+            prompt_hash: "abc".into(),
+            branch: "ralf/add-login".into(),
+            duration_ms: Some(1200),
+            recorded_at: None,
+            commit_sha: None,
+            log_path: None,
+            verifiers: vec![VerifierRecord {
+                name: "tests".into(),
+                status: VerifierStatus::Pass,
+            }],
+            changed_files: vec!["src/login.rs".into()],
+        };
+        ChangelogStore::new(here.changelog())
+            .record(&record)
+            .unwrap();
+        let prompt = temp.path().join("PROMPT.md");
+        let archive = temp.path().join("run-1.tar.zst");
+        let manifest = export_run(&here, "run-1", &prompt, &Config::default(), &archive).unwrap();
+        assert!(manifest
+            .files
+            .contains(&"changelog/entries/2.json".to_string()));
+        assert_eq!(manifest.changelog_models(), ["claude"]);
+
+        let there = RalfDirs::new(temp.path().join("there"));
+        import_run(&there, &archive).unwrap();
+        let imported = ChangelogStore::new(there.changelog())
+            .read_entry("run-1", 2)
+            .unwrap();
+        assert_eq!(imported.verifiers, record.verifiers);
+        assert_eq!(imported.changed_files, record.changed_files);
+        let records = ChangelogStore::new(there.changelog()).list(None).unwrap();
+        assert_eq!(records.len(), 2);
+    }
+
+    #[test]
+    fn test_long_paths() {
+        let mut tar = tar::Builder::new(Vec::new());
+        let name = format!("run/{}/{}.log", "d".repeat(120), "f".repeat(190));
+        write_entry(&mut tar, &name, b"content", 0).unwrap();
+        let entries = read_entries(&tar.into_inner().unwrap()).unwrap();
+        assert_eq!(entries, [(name, b"content".to_vec())]);
+    }
+
+    #[test]
+    fn test_imports_gzipped_archives() {
+        let temp = TempDir::new().unwrap();
+        let here = RalfDirs::new(temp.path().join("here"));
+        run_with_history(&here, "run-1");
+        let prompt = temp.path().join("PROMPT.md");
+        let archive = temp.path().join("run-1.tar.zst");
+        export_run(&here, "run-1", &prompt, &Config::default(), &archive).unwrap();
+
+        // The same archive as older versions wrote it
+        let mut tar = Vec::new();
+        zstd::Decoder::new(std::fs::File::open(&archive).unwrap())
+            .unwrap()
+            .read_to_end(&mut tar)
+            .unwrap();
+        let mut gzipped = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzipped.write_all(&tar).unwrap();
+        let old = temp.path().join("run-1.tar.gz");
+        std::fs::write(&old, gzipped.finish().unwrap()).unwrap();
+
+        let there = RalfDirs::new(temp.path().join("there"));
+        assert_eq!(import_run(&there, &old).unwrap().run_id, "run-1");
+        assert!(there.run("run-1").join("events.jsonl").exists());
+    }
+
+    #[test]
+    fn test_rejects_bad_archives() {
+        let temp = TempDir::new().unwrap();
+        let dirs = RalfDirs::new(temp.path().join(".ralf"));
+        let write_archive = |entries: &[(&str, &[u8])]| {
+            let path = temp.path().join("bad.tar.zst");
+            let mut tar = tar::Builder::new(zstd::Encoder::new(Vec::new(), ZSTD_LEVEL).unwrap());
+            for (name, content) in entries {
+                // Set the name as is: the builder refuses unsafe ones
+                let mut header = tar::Header::new_gnu();
+                header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+                header.set_entry_type(tar::EntryType::Regular);
+                header.set_size(content.len() as u64);
+                header.set_cksum();
+                tar.append(&header, *content).unwrap();
+            }
+            std::fs::write(&path, tar.into_inner().unwrap().finish().unwrap()).unwrap();
+            path
+        };
+        let manifest = |version: u32, files: &[&str]| {
+            serde_json::to_vec(&ArchiveManifest {
+                version,
+                run_id: "run-1".to_string(),
+                ralf_version: "0.0.0".to_string(),
+                created_at: Utc::now(),
+                files: files.iter().map(ToString::to_string).collect(),
+            })
+            .unwrap()
+        };
+
+        let newer = write_archive(&[(MANIFEST_FILE, &manifest(ARCHIVE_VERSION + 1, &[]))]);
+        assert!(matches!(
+            import_run(&dirs, &newer),
+            Err(ArchiveError::UnsupportedVersion(_))
+        ));
+
+        let unlisted = write_archive(&[(MANIFEST_FILE, &manifest(1, &[])), ("run/x.log", b"")]);
+        assert!(matches!(
+            import_run(&dirs, &unlisted),
+            Err(ArchiveError::Invalid(_))
+        ));
+
+        let escaping = write_archive(&[
+            (MANIFEST_FILE, &manifest(1, &["run/../../x"])),
+            ("run/../../x", b""),
+        ]);
+        assert!(matches!(
+            import_run(&dirs, &escaping),
+            Err(ArchiveError::Invalid(_))
+        ));
+
+        let no_manifest = write_archive(&[("run/x.log", b"")]);
+        assert!(matches!(
+            import_run(&dirs, &no_manifest),
+            Err(ArchiveError::Invalid(_))
+        ));
+        assert!(!dirs.runs().exists());
+    }
+}
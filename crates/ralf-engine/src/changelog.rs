@@ -1,10 +1,44 @@
 //! Changelog generation for ralf engine.
 //!
-//! This module handles writing per-iteration changelog entries.
+//! This module handles writing per-iteration changelog entries and reading
+//! them back for summaries. Each entry is a JSON file,
+//! `.ralf/changelog/entries/<run>/<iteration>.json`, listed in
+//! `.ralf/changelog/index.json`; both carry [`CHANGELOG_SCHEMA_VERSION`],
+//! and files from a newer version are refused. An iteration has at most one
+//! entry: writing it again (say, when a save is retried) changes nothing.
+//! Entries are also appended to `.ralf/changelog/<model>.md` for reading;
+//! entries found only there (written before the JSON entries, or imported
+//! with an archive) are read from the Markdown.
 
-use crate::runner::{GitInfo, InvocationResult, VerifierResult};
+use crate::chat::invoke_prompt;
+use crate::config::ModelConfig;
+use crate::criterion::Criterion;
+use crate::dirs::RalfDirs;
+use crate::git::GitSafety;
+use crate::parse_criteria;
+use crate::publish::{commit_subject, spec_summary, wrap, MAX_SUBJECT_LEN};
+use crate::report::{ReportError, RunReport};
+use crate::runner::{GitInfo, InvocationResult, RunnerError, VerifierResult};
+use crate::state::atomic_write;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
 use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Width a commit body is wrapped to.
+const BODY_WIDTH: usize = 72;
+
+/// Version of the JSON schema changelog entries and their index are
+/// written with.
+pub const CHANGELOG_SCHEMA_VERSION: u32 = 1;
+
+/// The index of JSON entries, in the changelog directory.
+const INDEX_FILE: &str = "index.json";
+
+/// Directory of JSON entries, one subdirectory per run.
+const ENTRIES_DIR: &str = "entries";
 
 /// Data for a changelog entry.
 pub struct ChangelogEntry<'a> {
@@ -18,6 +52,8 @@ pub struct ChangelogEntry<'a> {
     pub invocation: &'a InvocationResult,
     /// Verifier results.
     pub verifier_results: &'a [VerifierResult],
+    /// Completion criteria the iteration worked towards.
+    pub criteria: &'a [Criterion],
     /// Hash of the prompt.
     pub prompt_hash: &'a str,
     /// Git information.
@@ -28,12 +64,39 @@ pub struct ChangelogEntry<'a> {
     pub reason: &'a str,
     /// Path to the log file.
     pub log_path: PathBuf,
+    /// Commit created for this iteration (with `commit_per_iteration`).
+    pub commit_sha: Option<&'a str>,
 }
 
-/// Write a changelog entry for an iteration.
+/// Write a changelog entry for an iteration. Does nothing if the
+/// iteration already has one.
 pub fn write_changelog_entry(entry: &ChangelogEntry<'_>) -> Result<(), ChangelogError> {
-    // Ensure changelog directory exists
-    std::fs::create_dir_all(entry.changelog_dir).map_err(ChangelogError::Io)?;
+    let recorded_at = Utc::now();
+    let record = ChangelogRecord {
+        run_id: entry.run_id.to_string(),
+        iteration: entry.iteration,
+        model: entry.invocation.model.clone(),
+        status: entry.status,
+        reason: entry.reason.to_string(),
+        prompt_hash: entry.prompt_hash.to_string(),
+        branch: entry.git_info.branch.clone(),
+        duration_ms: Some(entry.invocation.duration_ms),
+        recorded_at: Some(recorded_at),
+        commit_sha: entry.commit_sha.map(ToString::to_string),
+        log_path: Some(entry.log_path.clone()),
+        verifiers: entry
+            .verifier_results
+            .iter()
+            .map(|v| VerifierRecord {
+                name: v.name.clone(),
+                status: VerifierStatus::of(v),
+            })
+            .collect(),
+        changed_files: entry.git_info.changed_files.clone(),
+    };
+    if !ChangelogStore::new(entry.changelog_dir).record(&record)? {
+        return Ok(());
+    }
 
     let changelog_path = entry
         .changelog_dir
@@ -50,9 +113,23 @@ pub fn write_changelog_entry(entry: &ChangelogEntry<'_>) -> Result<(), Changelog
     let verifier_lines: Vec<String> = entry
         .verifier_results
         .iter()
-        .map(|v| {
-            let status = if v.passed { "pass" } else { "fail" };
-            format!("  - {}: {status}", v.name)
+        .map(|v| format!("  - {}: {}", v.name, VerifierStatus::of(v)))
+        .collect();
+
+    // Format criteria with their annotations
+    let criteria_lines: Vec<String> = entry
+        .criteria
+        .iter()
+        .map(|c| {
+            let mut line = format!("  - {}: {}", c.id, c.text);
+            for tag in &c.tags {
+                line.push_str(" #");
+                line.push_str(tag);
+            }
+            if !c.required {
+                line.push_str(" (optional)");
+            }
+            line
         })
         .collect();
 
@@ -79,6 +156,9 @@ pub fn write_changelog_entry(entry: &ChangelogEntry<'_>) -> Result<(), Changelog
     writeln!(file, "- **Status**: {status}").map_err(ChangelogError::Io)?;
     writeln!(file, "- **Reason**: {reason}").map_err(ChangelogError::Io)?;
     writeln!(file, "- **Prompt hash**: {prompt_hash}").map_err(ChangelogError::Io)?;
+    writeln!(file, "- **Duration**: {}ms", entry.invocation.duration_ms)
+        .map_err(ChangelogError::Io)?;
+    writeln!(file, "- **Recorded**: {}", recorded_at.to_rfc3339()).map_err(ChangelogError::Io)?;
     writeln!(file, "- **Git branch**: {}", entry.git_info.branch).map_err(ChangelogError::Io)?;
     writeln!(file, "- **Git dirty**: {}", entry.git_info.dirty).map_err(ChangelogError::Io)?;
     writeln!(file, "- **Changed files**: {changed_files}").map_err(ChangelogError::Io)?;
@@ -86,13 +166,23 @@ pub fn write_changelog_entry(entry: &ChangelogEntry<'_>) -> Result<(), Changelog
     for line in &verifier_lines {
         writeln!(file, "{line}").map_err(ChangelogError::Io)?;
     }
+    if !criteria_lines.is_empty() {
+        writeln!(file, "- **Criteria**:").map_err(ChangelogError::Io)?;
+        for line in &criteria_lines {
+            writeln!(file, "{line}").map_err(ChangelogError::Io)?;
+        }
+    }
     writeln!(file, "- **Logs**: {}", entry.log_path.display()).map_err(ChangelogError::Io)?;
+    if let Some(sha) = entry.commit_sha {
+        writeln!(file, "- **Commit**: {sha}").map_err(ChangelogError::Io)?;
+    }
 
     Ok(())
 }
 
 /// Status of an iteration.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum IterationStatus {
     /// Iteration succeeded.
     Success,
@@ -104,6 +194,13 @@ pub enum IterationStatus {
     Error,
     /// Verifiers failed.
     VerifierFailed,
+    /// The run was cancelled mid-iteration.
+    Cancelled,
+    /// The iteration added something that looks like a secret, so it
+    /// wasn't committed.
+    NeedsAttention,
+    /// The model left the workspace exactly as it found it.
+    NoOp,
 }
 
 impl std::fmt::Display for IterationStatus {
@@ -114,8 +211,819 @@ impl std::fmt::Display for IterationStatus {
             Self::Timeout => write!(f, "timeout"),
             Self::Error => write!(f, "error"),
             Self::VerifierFailed => write!(f, "verifier_failed"),
+            Self::Cancelled => write!(f, "cancelled"),
+            Self::NeedsAttention => write!(f, "needs_attention"),
+            Self::NoOp => write!(f, "no_op"),
+        }
+    }
+}
+
+impl FromStr for IterationStatus {
+    type Err = ChangelogError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "success" => Ok(Self::Success),
+            "rate_limited" => Ok(Self::RateLimited),
+            "timeout" => Ok(Self::Timeout),
+            "error" => Ok(Self::Error),
+            "verifier_failed" => Ok(Self::VerifierFailed),
+            "cancelled" => Ok(Self::Cancelled),
+            "needs_attention" => Ok(Self::NeedsAttention),
+            "no_op" => Ok(Self::NoOp),
+            _ => Err(ChangelogError::UnknownStatus(s.to_string())),
+        }
+    }
+}
+
+/// How a verifier fared in an iteration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerifierStatus {
+    /// The verifier passed.
+    Pass,
+    /// The verifier failed.
+    Fail,
+    /// The verifier didn't run.
+    Skipped,
+}
+
+impl VerifierStatus {
+    /// The status of `result`.
+    pub fn of(result: &VerifierResult) -> Self {
+        if result.skipped {
+            Self::Skipped
+        } else if result.passed {
+            Self::Pass
+        } else {
+            Self::Fail
+        }
+    }
+}
+
+impl std::fmt::Display for VerifierStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Pass => write!(f, "pass"),
+            Self::Fail => write!(f, "fail"),
+            Self::Skipped => write!(f, "skipped"),
+        }
+    }
+}
+
+/// A verifier's outcome in a changelog entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifierRecord {
+    /// Verifier name.
+    pub name: String,
+    /// How it fared.
+    pub status: VerifierStatus,
+}
+
+/// A changelog entry, as written to and read back from disk.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangelogRecord {
+    /// Run identifier.
+    pub run_id: String,
+    /// Iteration number.
+    pub iteration: u64,
+    /// Model that ran the iteration.
+    pub model: String,
+    /// Status of the iteration.
+    pub status: IterationStatus,
+    /// Reason for the status.
+    pub reason: String,
+    /// Hash of the prompt.
+    pub prompt_hash: String,
+    /// Git branch the iteration ran on.
+    pub branch: String,
+    /// Model invocation time (`None` for entries written before durations
+    /// were recorded).
+    pub duration_ms: Option<u64>,
+    /// When the entry was written, if recorded.
+    pub recorded_at: Option<DateTime<Utc>>,
+    /// Commit created for this iteration.
+    pub commit_sha: Option<String>,
+    /// Path to the log file.
+    pub log_path: Option<PathBuf>,
+    /// Verifier outcomes, in the order they ran (empty for entries read
+    /// from the Markdown changelog).
+    #[serde(default)]
+    pub verifiers: Vec<VerifierRecord>,
+    /// Files the iteration changed (empty for entries read from the
+    /// Markdown changelog).
+    #[serde(default)]
+    pub changed_files: Vec<String>,
+}
+
+/// A JSON entry file: a record with the schema version it was written with.
+#[derive(Debug, Serialize, Deserialize)]
+struct EntryFile {
+    schema_version: u32,
+    #[serde(flatten)]
+    record: ChangelogRecord,
+}
+
+/// The index of JSON entries: every entry's run, iteration, model and
+/// status, in the order they were written.
+#[derive(Debug, Serialize, Deserialize)]
+struct ChangelogIndex {
+    schema_version: u32,
+    entries: Vec<IndexEntry>,
+}
+
+impl Default for ChangelogIndex {
+    fn default() -> Self {
+        Self {
+            schema_version: CHANGELOG_SCHEMA_VERSION,
+            entries: Vec::new(),
+        }
+    }
+}
+
+/// An entry in the [`ChangelogIndex`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct IndexEntry {
+    run_id: String,
+    iteration: u64,
+    model: String,
+    status: IterationStatus,
+}
+
+impl IndexEntry {
+    fn of(record: &ChangelogRecord) -> Self {
+        Self {
+            run_id: record.run_id.clone(),
+            iteration: record.iteration,
+            model: record.model.clone(),
+            status: record.status,
+        }
+    }
+}
+
+/// Parse a JSON changelog file, refusing ones from a newer schema.
+fn read_versioned<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T, ChangelogError> {
+    #[derive(Deserialize)]
+    struct Version {
+        schema_version: u32,
+    }
+
+    let invalid = |reason: String| ChangelogError::Invalid {
+        path: path.to_path_buf(),
+        reason,
+    };
+    let content = std::fs::read_to_string(path)?;
+    let version: Version = serde_json::from_str(&content).map_err(|e| invalid(e.to_string()))?;
+    if version.schema_version > CHANGELOG_SCHEMA_VERSION {
+        return Err(ChangelogError::UnsupportedVersion {
+            path: path.to_path_buf(),
+            version: version.schema_version,
+        });
+    }
+    serde_json::from_str(&content).map_err(|e| invalid(e.to_string()))
+}
+
+/// Whether `run_id` can name a directory of entries.
+fn is_valid_run_id(run_id: &str) -> bool {
+    !run_id.is_empty() && run_id != "." && run_id != ".." && !run_id.contains(['/', '\\'])
+}
+
+/// Reads and writes the changelog files in `.ralf/changelog/`.
+#[derive(Debug, Clone)]
+pub struct ChangelogStore {
+    dir: PathBuf,
+}
+
+impl ChangelogStore {
+    /// Create a store reading from `changelog_dir`.
+    pub fn new(changelog_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: changelog_dir.into(),
+        }
+    }
+
+    /// All entries, or only those of `run_id`, oldest first.
+    ///
+    /// A missing changelog directory has no entries; entries that can't be
+    /// parsed or don't match the schema are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the index is from a newer schema, or the
+    /// directory can't be read.
+    pub fn list(&self, run_id: Option<&str>) -> Result<Vec<ChangelogRecord>, ChangelogError> {
+        let mut records = Vec::new();
+        let mut recorded = HashSet::new();
+        let index = self.index()?;
+        for item in index
+            .entries
+            .iter()
+            .filter(|item| run_id.is_none_or(|id| item.run_id == id))
+        {
+            match self.read_entry(&item.run_id, item.iteration) {
+                Ok(record) => {
+                    recorded.insert((record.run_id.clone(), record.iteration));
+                    records.push(record);
+                }
+                Err(e) => tracing::warn!("Skipping changelog entry: {e}"),
+            }
+        }
+
+        // Entries from before the JSON changelog
+        for (model, path) in self.files()? {
+            let content = std::fs::read_to_string(&path)?;
+            records.extend(
+                parse_changelog(&content, &model)
+                    .into_iter()
+                    .filter(|record| {
+                        run_id.is_none_or(|id| record.run_id == id)
+                            && !recorded.contains(&(record.run_id.clone(), record.iteration))
+                    }),
+            );
+        }
+
+        records.sort_by(|a, b| {
+            (a.recorded_at, &a.run_id, a.iteration).cmp(&(b.recorded_at, &b.run_id, b.iteration))
+        });
+        Ok(records)
+    }
+
+    /// The JSON entries of `run_id`, in the order they were written.
+    /// Entries that can't be read are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the index is from a newer schema, or the
+    /// directory can't be read.
+    pub fn run_records(&self, run_id: &str) -> Result<Vec<ChangelogRecord>, ChangelogError> {
+        let index = self.index()?;
+        Ok(index
+            .entries
+            .iter()
+            .filter(|item| item.run_id == run_id)
+            .filter_map(|item| match self.read_entry(&item.run_id, item.iteration) {
+                Ok(record) => Some(record),
+                Err(e) => {
+                    tracing::warn!("Skipping changelog entry: {e}");
+                    None
+                }
+            })
+            .collect())
+    }
+
+    /// Entries of `run_id` (or all runs) with the given status.
+    pub fn list_with_status(
+        &self,
+        run_id: Option<&str>,
+        status: IterationStatus,
+    ) -> Result<Vec<ChangelogRecord>, ChangelogError> {
+        let mut records = self.list(run_id)?;
+        records.retain(|record| record.status == status);
+        Ok(records)
+    }
+
+    /// Record `record` as the entry for its run's iteration. Returns
+    /// false, writing nothing, if the iteration already has an entry, so
+    /// a retried write never duplicates it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the run id can't name a file, the index is from
+    /// a newer schema, or the files can't be written.
+    pub fn record(&self, record: &ChangelogRecord) -> Result<bool, ChangelogError> {
+        let path = self.entry_path(&record.run_id, record.iteration)?;
+        let mut index = self.index()?;
+        let indexed = index
+            .entries
+            .iter()
+            .any(|item| item.run_id == record.run_id && item.iteration == record.iteration);
+        let existing = match self.read_entry(&record.run_id, record.iteration) {
+            Ok(existing) => Some(existing),
+            // Written by a newer ralf, so not ours to replace
+            Err(ChangelogError::UnsupportedVersion { .. }) => return Ok(false),
+            Err(_) => None,
+        };
+        if let Some(existing) = existing {
+            // The entry was saved but the index wasn't
+            if !indexed {
+                index.entries.push(IndexEntry::of(&existing));
+                self.save_index(&index)?;
+            }
+            return Ok(false);
+        }
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = EntryFile {
+            schema_version: CHANGELOG_SCHEMA_VERSION,
+            record: record.clone(),
+        };
+        let content = serde_json::to_string_pretty(&file).map_err(|e| ChangelogError::Invalid {
+            path: path.clone(),
+            reason: e.to_string(),
+        })?;
+        atomic_write(&path, content.as_bytes())?;
+        if !indexed {
+            index.entries.push(IndexEntry::of(record));
+        }
+        self.save_index(&index)?;
+        Ok(true)
+    }
+
+    /// The JSON entry for `run_id`'s `iteration`, checked against the
+    /// schema.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no such entry, it's from a newer
+    /// schema, or it doesn't match the schema or the iteration.
+    pub fn read_entry(
+        &self,
+        run_id: &str,
+        iteration: u64,
+    ) -> Result<ChangelogRecord, ChangelogError> {
+        let path = self.entry_path(run_id, iteration)?;
+        let file: EntryFile = read_versioned(&path)?;
+        let record = file.record;
+        let reason = if record.run_id != run_id || record.iteration != iteration {
+            Some(format!(
+                "records run {} iteration {}",
+                record.run_id, record.iteration
+            ))
+        } else if record.model.is_empty() {
+            Some("has no model".to_string())
+        } else {
+            None
+        };
+        match reason {
+            Some(reason) => Err(ChangelogError::Invalid { path, reason }),
+            None => Ok(record),
+        }
+    }
+
+    /// Remove every entry of `run_ids`, returning how many there were.
+    /// With `dry_run`, only count them. Files left empty are deleted.
+    pub fn remove_runs(
+        &self,
+        run_ids: &HashSet<String>,
+        dry_run: bool,
+    ) -> Result<usize, ChangelogError> {
+        if run_ids.is_empty() {
+            return Ok(0);
+        }
+
+        let mut index = self.index()?;
+        let (gone, kept): (Vec<IndexEntry>, Vec<IndexEntry>) = index
+            .entries
+            .into_iter()
+            .partition(|item| run_ids.contains(&item.run_id));
+        index.entries = kept;
+        let mut removed = gone.len();
+        if !dry_run && !gone.is_empty() {
+            self.save_index(&index)?;
+            for run_id in run_ids.iter().filter(|id| is_valid_run_id(id)) {
+                match std::fs::remove_dir_all(self.dir.join(ENTRIES_DIR).join(run_id)) {
+                    Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                    _ => {}
+                }
+            }
+        }
+
+        // The Markdown copies of those entries aren't counted again
+        let json: HashSet<(&str, u64)> = gone
+            .iter()
+            .map(|item| (item.run_id.as_str(), item.iteration))
+            .collect();
+        for (_, path) in self.files()? {
+            let content = std::fs::read_to_string(&path)?;
+            let (preamble, entries) = split_entries(&content);
+            let mut kept = preamble.to_string();
+            let mut changed = false;
+            for (run_id, entry) in entries {
+                if run_ids.contains(run_id) {
+                    changed = true;
+                    if !entry_iteration(entry).is_some_and(|n| json.contains(&(run_id, n))) {
+                        removed += 1;
+                    }
+                } else {
+                    kept.push_str(entry);
+                }
+            }
+
+            if dry_run || !changed {
+                continue;
+            }
+            if kept.trim().is_empty() {
+                std::fs::remove_file(&path)?;
+            } else {
+                atomic_write(&path, kept.as_bytes())?;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// The entries of `run_id` as written, by model. Models without any
+    /// are left out.
+    pub fn run_entries(&self, run_id: &str) -> Result<BTreeMap<String, String>, ChangelogError> {
+        let mut by_model = BTreeMap::new();
+        for (model, path) in self.files()? {
+            let content = std::fs::read_to_string(&path)?;
+            let entries: String = split_entries(&content)
+                .1
+                .into_iter()
+                .filter(|(id, _)| *id == run_id)
+                .map(|(_, entry)| entry)
+                .collect();
+            if !entries.is_empty() {
+                by_model.insert(model, entries);
+            }
+        }
+        Ok(by_model)
+    }
+
+    /// Append entries taken from [`run_entries`](Self::run_entries) to
+    /// `model`'s changelog.
+    pub fn append_entries(&self, model: &str, entries: &str) -> Result<(), ChangelogError> {
+        std::fs::create_dir_all(&self.dir)?;
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.dir.join(format!("{model}.md")))?;
+        file.write_all(entries.as_bytes())?;
+        Ok(())
+    }
+
+    /// Where the JSON entry for `run_id`'s `iteration` is kept.
+    fn entry_path(&self, run_id: &str, iteration: u64) -> Result<PathBuf, ChangelogError> {
+        if !is_valid_run_id(run_id) {
+            return Err(ChangelogError::Invalid {
+                path: self.dir.join(ENTRIES_DIR),
+                reason: format!("run id {run_id:?} can't name a directory"),
+            });
+        }
+        Ok(self
+            .dir
+            .join(ENTRIES_DIR)
+            .join(run_id)
+            .join(format!("{iteration}.json")))
+    }
+
+    /// The index of JSON entries, rebuilt from the entries when it's
+    /// missing or unreadable.
+    fn index(&self) -> Result<ChangelogIndex, ChangelogError> {
+        let path = self.dir.join(INDEX_FILE);
+        if !path.exists() {
+            return self.rebuild_index();
+        }
+        match read_versioned(&path) {
+            Err(ChangelogError::Invalid { reason, .. }) => {
+                tracing::warn!("Rebuilding changelog index: {reason}");
+                self.rebuild_index()
+            }
+            result => result,
+        }
+    }
+
+    /// An index of every valid JSON entry, by run and iteration.
+    fn rebuild_index(&self) -> Result<ChangelogIndex, ChangelogError> {
+        let mut index = ChangelogIndex::default();
+        let runs = match std::fs::read_dir(self.dir.join(ENTRIES_DIR)) {
+            Ok(runs) => runs,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(index),
+            Err(e) => return Err(ChangelogError::Io(e)),
+        };
+        for run in runs {
+            let run = run?;
+            let Some(run_id) = run.file_name().to_str().map(ToString::to_string) else {
+                continue;
+            };
+            for file in std::fs::read_dir(run.path())? {
+                let path = file?.path();
+                let iteration = path
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .and_then(|stem| stem.parse().ok());
+                if let Some(Ok(record)) = iteration.map(|n| self.read_entry(&run_id, n)) {
+                    index.entries.push(IndexEntry::of(&record));
+                }
+            }
+        }
+        index
+            .entries
+            .sort_by(|a, b| (&a.run_id, a.iteration).cmp(&(&b.run_id, b.iteration)));
+        Ok(index)
+    }
+
+    fn save_index(&self, index: &ChangelogIndex) -> Result<(), ChangelogError> {
+        let path = self.dir.join(INDEX_FILE);
+        let content = serde_json::to_string_pretty(index).map_err(|e| ChangelogError::Invalid {
+            path: path.clone(),
+            reason: e.to_string(),
+        })?;
+        std::fs::create_dir_all(&self.dir)?;
+        atomic_write(&path, content.as_bytes())?;
+        Ok(())
+    }
+
+    /// Every model's changelog file. A missing directory has none.
+    fn files(&self) -> Result<Vec<(String, PathBuf)>, ChangelogError> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(ChangelogError::Io(e)),
+        };
+
+        let mut files = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("md") {
+                continue;
+            }
+            if let Some(model) = path.file_stem().and_then(|stem| stem.to_str()) {
+                files.push((model.to_string(), path.clone()));
+            }
+        }
+        Ok(files)
+    }
+}
+
+/// Split a changelog file into what comes before its first entry and its
+/// entries, each with its run. Joined back together they make the file.
+fn split_entries(content: &str) -> (&str, Vec<(&str, &str)>) {
+    // Every entry starts with its header, after a blank line
+    const HEADER: &str = "\n## Run ";
+    let mut starts: Vec<usize> = content.match_indices(HEADER).map(|(at, _)| at).collect();
+    let preamble = &content[..starts.first().copied().unwrap_or(content.len())];
+    starts.push(content.len());
+    let entries = starts
+        .windows(2)
+        .map(|bounds| {
+            let entry = &content[bounds[0]..bounds[1]];
+            let run_id = entry[HEADER.len()..]
+                .split(" — Iteration ")
+                .next()
+                .unwrap_or_default();
+            (run_id, entry)
+        })
+        .collect();
+    (preamble, entries)
+}
+
+/// The iteration in a Markdown entry's header.
+fn entry_iteration(entry: &str) -> Option<u64> {
+    let header = entry.trim_start().lines().next()?;
+    header.split_once(" — Iteration ")?.1.trim().parse().ok()
+}
+
+/// Parse the entries of one model's changelog file. `model` is used for
+/// entries missing their model line.
+fn parse_changelog(content: &str, model: &str) -> Vec<ChangelogRecord> {
+    let mut records = Vec::new();
+    let mut current: Option<(String, u64, BTreeMap<&str, &str>)> = None;
+
+    for line in content.lines() {
+        if let Some(header) = line.strip_prefix("## Run ") {
+            records.extend(current.take().and_then(|entry| build_record(entry, model)));
+            current = header
+                .split_once(" — Iteration ")
+                .and_then(|(run_id, iteration)| {
+                    Some((
+                        run_id.to_string(),
+                        iteration.trim().parse().ok()?,
+                        BTreeMap::new(),
+                    ))
+                });
+        } else if let Some((_, _, fields)) = current.as_mut() {
+            let field = line
+                .strip_prefix("- **")
+                .and_then(|rest| rest.split_once("**:"));
+            if let Some((key, value)) = field {
+                fields.insert(key, value.trim());
+            }
         }
     }
+    records.extend(current.and_then(|entry| build_record(entry, model)));
+    records
+}
+
+fn build_record(
+    (run_id, iteration, fields): (String, u64, BTreeMap<&str, &str>),
+    model: &str,
+) -> Option<ChangelogRecord> {
+    let text = |key: &str| fields.get(key).map(ToString::to_string);
+    Some(ChangelogRecord {
+        run_id,
+        iteration,
+        model: text("Model").unwrap_or_else(|| model.to_string()),
+        status: fields.get("Status")?.parse().ok()?,
+        reason: text("Reason").unwrap_or_default(),
+        prompt_hash: text("Prompt hash").unwrap_or_default(),
+        branch: text("Git branch").unwrap_or_default(),
+        duration_ms: fields
+            .get("Duration")
+            .and_then(|d| d.strip_suffix("ms")?.parse().ok()),
+        recorded_at: fields
+            .get("Recorded")
+            .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.with_timezone(&Utc)),
+        commit_sha: text("Commit"),
+        log_path: fields.get("Logs").map(PathBuf::from),
+        verifiers: Vec::new(),
+        changed_files: Vec::new(),
+    })
+}
+
+/// Per-model totals in a [`ChangelogSummary`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ModelSummary {
+    /// Iterations the model ran.
+    pub iterations: usize,
+    /// Iterations that succeeded.
+    pub successes: usize,
+    /// Total invocation time.
+    pub duration_ms: u64,
+}
+
+/// Iteration outcomes aggregated over changelog entries.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ChangelogSummary {
+    /// Number of entries.
+    pub iterations: usize,
+    /// Number of entries per status.
+    pub statuses: BTreeMap<IterationStatus, usize>,
+    /// Totals per model.
+    pub models: BTreeMap<String, ModelSummary>,
+    /// Total invocation time across all entries.
+    pub total_duration_ms: u64,
+}
+
+impl ChangelogSummary {
+    /// Summarize `records`.
+    pub fn from_records(records: &[ChangelogRecord]) -> Self {
+        let mut summary = Self::default();
+        for record in records {
+            let duration = record.duration_ms.unwrap_or(0);
+            summary.iterations += 1;
+            *summary.statuses.entry(record.status).or_default() += 1;
+            summary.total_duration_ms += duration;
+
+            let model = summary.models.entry(record.model.clone()).or_default();
+            model.iterations += 1;
+            model.duration_ms += duration;
+            if record.status == IterationStatus::Success {
+                model.successes += 1;
+            }
+        }
+        summary
+    }
+}
+
+/// A commit message for run `run_id`, written from its history: a
+/// conventional subject from the spec's title, the spec's opening
+/// paragraph, the criteria the run met, its diff stats and the models that
+/// worked on it. `spec` is the prompt the run worked from.
+///
+/// # Errors
+///
+/// Returns an error if the run has no event log or its changelog can't be
+/// read.
+pub fn generate_commit_message(
+    ralf_dir: &Path,
+    run_id: &str,
+    spec: &str,
+) -> Result<String, ChangelogError> {
+    let records = ChangelogStore::new(RalfDirs::new(ralf_dir).changelog()).list(Some(run_id))?;
+    let mut report = RunReport::load(ralf_dir, run_id)?.with_criteria(&parse_criteria(spec));
+    // Diff stats are a nicety; a message without them is still useful
+    let repo_path = ralf_dir.parent().unwrap_or_else(|| Path::new("."));
+    let _ = report.collect_diff_stat(&GitSafety::new(repo_path));
+
+    let title = spec_title(spec).unwrap_or_else(|| format!("Changes from run {run_id}"));
+    let met: Vec<&str> = report
+        .criteria
+        .iter()
+        .filter(|c| c.passed)
+        .filter_map(|c| c.text.as_deref())
+        .collect();
+    let diff_summary = report.diff_stat.as_deref().and_then(diff_stat_summary);
+    Ok(compose_commit_message(
+        &title,
+        spec,
+        &met,
+        diff_summary,
+        &records,
+    ))
+}
+
+/// Have `model` polish a generated commit message, keeping its
+/// conventional subject. Falls back to `message` if the model says
+/// nothing.
+///
+/// # Errors
+///
+/// Returns an error if the model can't be invoked.
+pub async fn refine_commit_message(
+    model: &ModelConfig,
+    message: &str,
+    timeout_secs: u64,
+) -> Result<String, RunnerError> {
+    let prompt = format!(
+        "Improve this commit message. Keep a conventional commit subject \
+         (`type: description`, at most {MAX_SUBJECT_LEN} characters), then a blank line \
+         and a concise body wrapped at {BODY_WIDTH} columns. Don't invent changes that \
+         aren't described. Reply with the commit message only.\n\n```\n{message}\n```\n"
+    );
+    let result = invoke_prompt(model, &prompt, timeout_secs, None).await?;
+    let refined = strip_code_fence(&result.content);
+    if refined.is_empty() {
+        Ok(message.to_string())
+    } else {
+        Ok(refined)
+    }
+}
+
+/// Lay out a commit message: subject, summary, met criteria, then the
+/// run's iterations and diff stats.
+fn compose_commit_message(
+    title: &str,
+    spec: &str,
+    criteria: &[&str],
+    diff_summary: Option<&str>,
+    records: &[ChangelogRecord],
+) -> String {
+    let mut message = commit_subject(title);
+
+    let summary = spec_summary(spec);
+    if !summary.is_empty() {
+        message.push_str("\n\n");
+        message.push_str(&wrap(&summary, BODY_WIDTH));
+    }
+
+    if !criteria.is_empty() {
+        message.push('\n');
+        for criterion in criteria {
+            message.push_str("\n- ");
+            message.push_str(criterion);
+        }
+    }
+
+    let mut trailer = Vec::new();
+    if !records.is_empty() {
+        let mut models: Vec<&str> = Vec::new();
+        for record in records {
+            if !models.contains(&record.model.as_str()) {
+                models.push(&record.model);
+            }
+        }
+        trailer.push(format!(
+            "Iterations: {} ({})",
+            records.len(),
+            models.join(", ")
+        ));
+    }
+    if let Some(diff) = diff_summary {
+        trailer.push(format!("Changes: {diff}"));
+    }
+    if !trailer.is_empty() {
+        message.push_str("\n\n");
+        message.push_str(&trailer.join("\n"));
+    }
+    message
+}
+
+/// The spec's first level-1 heading.
+fn spec_title(spec: &str) -> Option<String> {
+    spec.lines()
+        .find_map(|line| line.trim().strip_prefix("# "))
+        .map(|title| title.trim().to_string())
+        .filter(|title| !title.is_empty())
+}
+
+/// The totals line of `git diff --stat`, e.g. "3 files changed, 40
+/// insertions(+)".
+fn diff_stat_summary(diff_stat: &str) -> Option<&str> {
+    diff_stat
+        .lines()
+        .rev()
+        .map(str::trim)
+        .find(|line| line.contains("changed"))
+}
+
+/// A model's reply without the code fence it may have wrapped it in.
+fn strip_code_fence(reply: &str) -> String {
+    let reply = reply.trim();
+    let Some(inner) = reply.strip_prefix("```") else {
+        return reply.to_string();
+    };
+    // Drop the info string on the opening fence, if any
+    let inner = inner.split_once('\n').map_or("", |(_, rest)| rest);
+    inner
+        .trim_end()
+        .strip_suffix("```")
+        .unwrap_or(inner)
+        .trim()
+        .to_string()
 }
 
 /// Errors that can occur when writing changelogs.
@@ -124,6 +1032,32 @@ pub enum ChangelogError {
     /// I/O error.
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
+
+    /// Unrecognized iteration status.
+    #[error("Unknown iteration status: {0}")]
+    UnknownStatus(String),
+
+    /// A changelog file was written by a newer ralf.
+    #[error("{} uses changelog schema {version}; this ralf reads up to {CHANGELOG_SCHEMA_VERSION}", path.display())]
+    UnsupportedVersion {
+        /// The file.
+        path: PathBuf,
+        /// Its schema version.
+        version: u32,
+    },
+
+    /// A changelog file doesn't match the schema.
+    #[error("Invalid changelog file {}: {reason}", path.display())]
+    Invalid {
+        /// The file.
+        path: PathBuf,
+        /// What's wrong with it.
+        reason: String,
+    },
+
+    /// The run's history could not be loaded.
+    #[error(transparent)]
+    Report(#[from] ReportError),
 }
 
 #[cfg(test)]
@@ -144,6 +1078,7 @@ mod tests {
             rate_limited: false,
             duration_ms: 1000,
             has_promise: true,
+            usage: None,
         };
 
         let verifier_results = vec![VerifierResult {
@@ -152,6 +1087,7 @@ mod tests {
             exit_code: Some(0),
             output: String::new(),
             duration_ms: 500,
+            skipped: false,
         }];
 
         let git_info = GitInfo {
@@ -166,11 +1102,16 @@ mod tests {
             iteration: 1,
             invocation: &invocation,
             verifier_results: &verifier_results,
+            criteria: &[
+                Criterion::parse(0, "Tests pass #tests"),
+                Criterion::parse(1, "Docs updated (optional)"),
+            ],
             prompt_hash: "hash123",
             git_info: &git_info,
             status: IterationStatus::Success,
             reason: "All verifiers passed",
             log_path: PathBuf::from(".ralf/runs/abc123/claude.log"),
+            commit_sha: Some("0123456789abcdef"),
         };
 
         let result = write_changelog_entry(&entry);
@@ -185,5 +1126,341 @@ mod tests {
         assert!(content.contains("Run abc123"));
         assert!(content.contains("Iteration 1"));
         assert!(content.contains("claude"));
+        assert!(content.contains("- **Commit**: 0123456789abcdef"));
+        assert!(content.contains(
+            "- **Criteria**:\n  - c1: Tests pass #tests\n  - c2: Docs updated (optional)\n"
+        ));
+    }
+
+    #[test]
+    fn test_changelog_store_list() {
+        let temp_dir = TempDir::new().unwrap();
+        let changelog_dir = temp_dir.path().join("changelog");
+        assert!(ChangelogStore::new(&changelog_dir)
+            .list(None)
+            .unwrap()
+            .is_empty());
+
+        std::fs::create_dir_all(&changelog_dir).unwrap();
+        std::fs::write(
+            changelog_dir.join("claude.md"),
+            "\n## Run r1 — Iteration 1\n\n- **Model**: claude\n- **Status**: success\n\
+             - **Reason**: All verifiers passed\n- **Duration**: 1200ms\n\
+             - **Recorded**: 2026-01-01T00:00:00+00:00\n\
+             \n## Run r2 — Iteration 1\n\n- **Model**: claude\n- **Status**: success\n\
+             - **Recorded**: 2026-01-02T00:00:00+00:00\n",
+        )
+        .unwrap();
+        std::fs::write(
+            changelog_dir.join("codex.md"),
+            "\n## Run r1 — Iteration 2\n\n- **Model**: codex\n- **Status**: rate_limited\n\
+             - **Verifier results**:\n  - tests: skipped\n- **Duration**: 300ms\n\
+             - **Recorded**: 2026-01-01T00:01:00+00:00\n\
+             \n## Run r1 — Iteration 3\n\n- **Status**: bogus\n",
+        )
+        .unwrap();
+
+        let store = ChangelogStore::new(&changelog_dir);
+        let records = store.list(Some("r1")).unwrap();
+        let iterations: Vec<(u64, &str)> = records
+            .iter()
+            .map(|r| (r.iteration, r.model.as_str()))
+            .collect();
+        assert_eq!(iterations, vec![(1, "claude"), (2, "codex")]);
+        assert_eq!(records[0].reason, "All verifiers passed");
+        assert_eq!(records[0].duration_ms, Some(1200));
+
+        let limited = store
+            .list_with_status(None, IterationStatus::RateLimited)
+            .unwrap();
+        assert_eq!(limited.len(), 1);
+        assert_eq!(limited[0].model, "codex");
+
+        let summary = ChangelogSummary::from_records(&store.list(None).unwrap());
+        assert_eq!(summary.iterations, 3);
+        assert_eq!(summary.statuses[&IterationStatus::Success], 2);
+        assert_eq!(summary.models["claude"].successes, 2);
+        assert_eq!(summary.models["codex"].duration_ms, 300);
+        assert_eq!(summary.total_duration_ms, 1500);
+    }
+
+    #[test]
+    fn test_written_entry_reads_back() {
+        let temp_dir = TempDir::new().unwrap();
+        let invocation = InvocationResult {
+            model: "gemini".into(),
+            exit_code: Some(1),
+            stdout: String::new(),
+            stderr: String::new(),
+            rate_limited: false,
+            duration_ms: 4200,
+            has_promise: false,
+            usage: None,
+        };
+        let git_info = GitInfo {
+            branch: "main".into(),
+            dirty: true,
+            changed_files: Vec::new(),
+        };
+        write_changelog_entry(&ChangelogEntry {
+            changelog_dir: temp_dir.path(),
+            run_id: "run-9",
+            iteration: 4,
+            invocation: &invocation,
+            verifier_results: &[],
+            criteria: &[],
+            prompt_hash: "abc",
+            git_info: &git_info,
+            status: IterationStatus::Error,
+            reason: "exit code 1",
+            log_path: PathBuf::from("gemini.log"),
+            commit_sha: None,
+        })
+        .unwrap();
+
+        let records = ChangelogStore::new(temp_dir.path()).list(None).unwrap();
+        assert_eq!(records.len(), 1);
+        let record = &records[0];
+        assert_eq!(record.run_id, "run-9");
+        assert_eq!(record.iteration, 4);
+        assert_eq!(record.status, IterationStatus::Error);
+        assert_eq!(record.branch, "main");
+        assert_eq!(record.duration_ms, Some(4200));
+        assert!(record.recorded_at.is_some());
+        assert_eq!(record.commit_sha, None);
+        assert_eq!(record.log_path, Some(PathBuf::from("gemini.log")));
+
+        let status: IterationStatus = "needs_attention".parse().unwrap();
+        assert_eq!(status, IterationStatus::NeedsAttention);
+        assert_eq!(status.to_string(), "needs_attention");
+        let status: IterationStatus = "no_op".parse().unwrap();
+        assert_eq!(status, IterationStatus::NoOp);
+        assert_eq!(status.to_string(), "no_op");
+    }
+
+    #[test]
+    fn test_entry_written_once() {
+        let temp_dir = TempDir::new().unwrap();
+        let invocation = InvocationResult {
+            model: "claude".into(),
+            exit_code: Some(0),
+            stdout: String::new(),
+            stderr: String::new(),
+            rate_limited: false,
+            duration_ms: 800,
+            has_promise: false,
+            usage: None,
+        };
+        let verifier_results = vec![VerifierResult {
+            name: "tests".into(),
+            passed: false,
+            exit_code: Some(1),
+            output: String::new(),
+            duration_ms: 10,
+            skipped: false,
+        }];
+        let git_info = GitInfo {
+            branch: "main".into(),
+            dirty: true,
+            changed_files: vec!["src/lib.rs".into()],
+        };
+        let entry = ChangelogEntry {
+            changelog_dir: temp_dir.path(),
+            run_id: "run-3",
+            iteration: 2,
+            invocation: &invocation,
+            verifier_results: &verifier_results,
+            criteria: &[],
+            prompt_hash: "abc",
+            git_info: &git_info,
+            status: IterationStatus::VerifierFailed,
+            reason: "tests failed",
+            log_path: PathBuf::from("claude.log"),
+            commit_sha: None,
+        };
+
+        // A retried write leaves the first entry alone
+        write_changelog_entry(&entry).unwrap();
+        write_changelog_entry(&ChangelogEntry {
+            reason: "retried",
+            ..entry
+        })
+        .unwrap();
+        let store = ChangelogStore::new(temp_dir.path());
+        let records = store.list(None).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].reason, "tests failed");
+        assert_eq!(
+            records[0].verifiers,
+            [VerifierRecord {
+                name: "tests".into(),
+                status: VerifierStatus::Fail,
+            }]
+        );
+        assert_eq!(records[0].changed_files, ["src/lib.rs"]);
+        let markdown = std::fs::read_to_string(temp_dir.path().join("claude.md")).unwrap();
+        assert_eq!(markdown.matches("## Run run-3").count(), 1);
+
+        let json = std::fs::read_to_string(temp_dir.path().join("entries/run-3/2.json")).unwrap();
+        assert!(json.contains("\"schema_version\": 1"));
+        let index = std::fs::read_to_string(temp_dir.path().join(INDEX_FILE)).unwrap();
+        assert!(index.contains("\"run_id\": \"run-3\""));
+
+        // Its Markdown copy doesn't count as another entry
+        let run_ids = HashSet::from(["run-3".to_string()]);
+        assert_eq!(store.remove_runs(&run_ids, true).unwrap(), 1);
+
+        // A lost index is rebuilt from the entries
+        std::fs::remove_file(temp_dir.path().join(INDEX_FILE)).unwrap();
+        std::fs::remove_file(temp_dir.path().join("claude.md")).unwrap();
+        assert_eq!(store.list(Some("run-3")).unwrap(), records);
+        assert!(!store.record(&records[0]).unwrap());
+
+        assert_eq!(store.remove_runs(&run_ids, false).unwrap(), 1);
+        assert!(store.list(None).unwrap().is_empty());
+        assert!(!temp_dir.path().join("entries/run-3").exists());
+    }
+
+    #[test]
+    fn test_read_entry_validates() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = ChangelogStore::new(temp_dir.path());
+        let mut first = record(1, "claude");
+        assert!(store.record(&first).unwrap());
+        assert!(store.record(&record(2, "codex")).unwrap());
+        assert!(matches!(
+            store.record(&ChangelogRecord {
+                run_id: "../escape".into(),
+                ..record(1, "claude")
+            }),
+            Err(ChangelogError::Invalid { .. })
+        ));
+
+        // An entry from a newer ralf is refused, and never overwritten
+        let newer = temp_dir.path().join("entries/run-1/1.json");
+        let content = std::fs::read_to_string(&newer).unwrap();
+        std::fs::write(
+            &newer,
+            content.replace("\"schema_version\": 1", "\"schema_version\": 2"),
+        )
+        .unwrap();
+        assert!(matches!(
+            store.read_entry("run-1", 1),
+            Err(ChangelogError::UnsupportedVersion { version: 2, .. })
+        ));
+        first.reason = "again".into();
+        assert!(!store.record(&first).unwrap());
+
+        // An entry that doesn't match its place is skipped
+        let moved = temp_dir.path().join("entries/run-1/2.json");
+        let content = std::fs::read_to_string(&moved).unwrap();
+        std::fs::write(
+            &moved,
+            content.replace("\"iteration\": 2", "\"iteration\": 5"),
+        )
+        .unwrap();
+        assert!(matches!(
+            store.read_entry("run-1", 2),
+            Err(ChangelogError::Invalid { .. })
+        ));
+        assert!(store.list(None).unwrap().is_empty());
+
+        // So are entries that aren't JSON at all
+        std::fs::write(&moved, "not json").unwrap();
+        assert!(matches!(
+            store.read_entry("run-1", 2),
+            Err(ChangelogError::Invalid { .. })
+        ));
+
+        // A newer index is refused rather than rewritten
+        std::fs::write(
+            temp_dir.path().join(INDEX_FILE),
+            "{\"schema_version\": 9, \"entries\": []}",
+        )
+        .unwrap();
+        assert!(matches!(
+            store.list(None),
+            Err(ChangelogError::UnsupportedVersion { version: 9, .. })
+        ));
+    }
+
+    fn record(iteration: u64, model: &str) -> ChangelogRecord {
+        ChangelogRecord {
+            run_id: "run-1".into(),
+            iteration,
+            model: model.into(),
+            status: IterationStatus::Success,
+            reason: String::new(),
+            prompt_hash: "abc".into(),
+            branch: "ralf/add-login".into(),
+            duration_ms: None,
+            recorded_at: None,
+            commit_sha: None,
+            log_path: None,
+            verifiers: Vec::new(),
+            changed_files: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_compose_commit_message() {
+        let spec = "\
+# Add OAuth login
+
+Users sign in with GitHub or Google instead of a password, and their \
+sessions survive a server restart.
+
+## Requirements
+
+- Login with GitHub works
+- Login with Google works (optional)
+";
+        assert_eq!(spec_title(spec).as_deref(), Some("Add OAuth login"));
+        let diff = diff_stat_summary(
+            " src/auth.rs | 40 ++++\n src/main.rs |  2 +-\n 2 files changed, 41 insertions(+), 1 deletion(-)\n",
+        );
+        let message = compose_commit_message(
+            "Add OAuth login",
+            spec,
+            &["Login with GitHub works"],
+            diff,
+            &[record(1, "claude"), record(2, "codex"), record(3, "claude")],
+        );
+        assert_eq!(
+            message,
+            "feat: add OAuth login
+
+Users sign in with GitHub or Google instead of a password, and their
+sessions survive a server restart.
+
+- Login with GitHub works
+
+Iterations: 3 (claude, codex)
+Changes: 2 files changed, 41 insertions(+), 1 deletion(-)"
+        );
+        assert_eq!(
+            compose_commit_message("Add login", "", &[], None, &[]),
+            "feat: add login"
+        );
+    }
+
+    #[test]
+    fn test_generate_commit_message_needs_run() {
+        let temp_dir = TempDir::new().unwrap();
+        let err = generate_commit_message(temp_dir.path(), "missing", "# Spec\n").unwrap_err();
+        assert!(matches!(
+            err,
+            ChangelogError::Report(ReportError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_strip_code_fence() {
+        assert_eq!(
+            strip_code_fence("```text\nfix: handle empty config\n```\n"),
+            "fix: handle empty config"
+        );
+        assert_eq!(strip_code_fence("  feat: add login \n"), "feat: add login");
+        assert_eq!(strip_code_fence("```"), "");
     }
 }
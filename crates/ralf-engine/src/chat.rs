@@ -3,16 +3,18 @@
 //! This module provides types and functions for managing multi-turn
 //! conversations with AI models, including thread persistence.
 
+use crate::adapters::adapter_for;
 use crate::config::ModelConfig;
-use crate::runner::RunnerError;
+use crate::dirs::DRAFTS_DIR;
+use crate::runner::{spawn_process, wait_with_limit_streaming, RunnerError};
+use crate::state::atomic_write;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::io::Read;
 use std::path::Path;
-use std::process::Stdio;
 use std::time::Duration;
 use tokio::io::AsyncWriteExt;
-use tokio::process::Command;
-use tokio::time::timeout;
+use tokio::sync::mpsc;
 use uuid::Uuid;
 
 /// Role in a conversation.
@@ -73,6 +75,81 @@ impl ChatMessage {
     }
 }
 
+/// Most bytes of a file `/attach` includes; the rest is cut.
+pub const MAX_ATTACHMENT_BYTES: usize = 32 * 1024;
+
+/// A file attached to a chat, included in every prompt after it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attachment {
+    /// Path as the user gave it.
+    pub path: String,
+    /// The file's text, up to [`MAX_ATTACHMENT_BYTES`].
+    pub content: String,
+    /// Whether the file was longer than what was kept.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+}
+
+impl Attachment {
+    /// Read the file at `path`, relative to `base` unless absolute.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ChatError::Io`] if the file can't be read, or
+    /// [`ChatError::BinaryAttachment`] if it isn't text.
+    pub fn read(base: &Path, path: &str) -> Result<Self, ChatError> {
+        let file = std::fs::File::open(base.join(path))?;
+        let mut bytes = Vec::new();
+        file.take(MAX_ATTACHMENT_BYTES as u64 + 1)
+            .read_to_end(&mut bytes)?;
+        let truncated = bytes.len() > MAX_ATTACHMENT_BYTES;
+        bytes.truncate(MAX_ATTACHMENT_BYTES);
+
+        let content = match String::from_utf8(bytes) {
+            Ok(content) => content,
+            // Cut in the middle of a character
+            Err(e) if truncated && e.utf8_error().error_len().is_none() => {
+                let valid = e.utf8_error().valid_up_to();
+                let mut bytes = e.into_bytes();
+                bytes.truncate(valid);
+                String::from_utf8(bytes).unwrap_or_default()
+            }
+            Err(_) => return Err(ChatError::BinaryAttachment(path.to_string())),
+        };
+        if content.contains('\0') {
+            return Err(ChatError::BinaryAttachment(path.to_string()));
+        }
+
+        Ok(Self {
+            path: path.to_string(),
+            content,
+            truncated,
+        })
+    }
+
+    /// The attachment as a prompt section: a heading with the path, then
+    /// the content in a fence longer than any backtick run inside it.
+    pub fn to_prompt(&self) -> String {
+        let longest_run = self
+            .content
+            .split(|c| c != '`')
+            .map(str::len)
+            .max()
+            .unwrap_or(0);
+        let fence = "`".repeat(longest_run.max(2) + 1);
+        let note = if self.truncated {
+            format!(" (first {} KB)", MAX_ATTACHMENT_BYTES / 1024)
+        } else {
+            String::new()
+        };
+        format!(
+            "File: {}{note}\n{fence}\n{}\n{fence}\n",
+            self.path,
+            self.content.trim_end()
+        )
+    }
+}
+
 /// Context for a chat invocation.
 #[derive(Debug, Clone)]
 pub struct ChatContext {
@@ -80,6 +157,8 @@ pub struct ChatContext {
     pub messages: Vec<ChatMessage>,
     /// Current draft content.
     pub draft: String,
+    /// Files attached with `/attach`.
+    pub attachments: Vec<Attachment>,
 }
 
 impl ChatContext {
@@ -88,6 +167,7 @@ impl ChatContext {
         Self {
             messages: Vec::new(),
             draft: String::new(),
+            attachments: Vec::new(),
         }
     }
 
@@ -108,6 +188,15 @@ impl ChatContext {
             prompt.push_str("\n---\n\n");
         }
 
+        // Attached files
+        if !self.attachments.is_empty() {
+            prompt.push_str("Attached files:\n\n");
+            for attachment in &self.attachments {
+                prompt.push_str(&attachment.to_prompt());
+                prompt.push('\n');
+            }
+        }
+
         // Conversation history
         prompt.push_str("Conversation:\n");
         for msg in &self.messages {
@@ -165,8 +254,9 @@ Help the user define WHAT they want built, not HOW to build it. The coding agent
 4. **Finalize**: When ready, output a complete specification starting with a markdown heading
 
 ## Specification Format
-When you produce a draft specification, format it as:
-```
+When you produce a draft specification, put it in a fenced block tagged `spec`, with any questions or comments outside the block:
+````
+```spec
 # [Task Title]
 
 ## Goal
@@ -183,6 +273,9 @@ When you produce a draft specification, format it as:
 
 <promise>COMPLETE</promise>
 ```
+````
+
+Only the latest `spec` block becomes the draft, so always include the whole specification in it, not just the changes.
 
 The `<promise>COMPLETE</promise>` tag signals the spec is ready for the autonomous agent.
 
@@ -228,62 +321,99 @@ pub async fn invoke_chat(
     model: &ModelConfig,
     context: &ChatContext,
     timeout_secs: u64,
+) -> Result<ChatResult, RunnerError> {
+    invoke_prompt(model, &context.build_prompt(), timeout_secs, None).await
+}
+
+/// Invoke a model for a chat turn, sending its reply to `partial` piece by
+/// piece as the CLI writes it. The result still holds the full reply.
+///
+/// Dropping the future (e.g. aborting its task) cancels the turn and stops
+/// the model's process tree.
+pub async fn invoke_chat_streaming(
+    model: &ModelConfig,
+    context: &ChatContext,
+    timeout_secs: u64,
+    partial: &mpsc::UnboundedSender<String>,
+) -> Result<ChatResult, RunnerError> {
+    invoke_prompt(model, &context.build_prompt(), timeout_secs, Some(partial)).await
+}
+
+/// Ask a model to assess a draft spec.
+///
+/// Parse the response with [`parse_assessment`].
+pub async fn invoke_assessment(
+    model: &ModelConfig,
+    draft: &str,
+    timeout_secs: u64,
+) -> Result<ChatResult, RunnerError> {
+    invoke_prompt(model, &build_assessment_prompt(draft), timeout_secs, None).await
+}
+
+/// Send a single prompt to a model and collect its response, streaming
+/// stdout to `partial` if given.
+pub(crate) async fn invoke_prompt(
+    model: &ModelConfig,
+    prompt: &str,
+    timeout_secs: u64,
+    partial: Option<&mpsc::UnboundedSender<String>>,
 ) -> Result<ChatResult, RunnerError> {
     let start = std::time::Instant::now();
-    let prompt = context.build_prompt();
-
-    // Build command - handle model-specific invocation patterns
-    let mut cmd = Command::new(&model.command_argv[0]);
-
-    // Gemini CLI uses positional argument for prompt, not stdin
-    let uses_stdin = if model.name == "gemini" {
-        // For gemini: gemini "prompt text"
-        cmd.arg(&prompt);
-        false
-    } else {
-        // For other models: pass command args, then write to stdin
-        for arg in &model.command_argv[1..] {
-            cmd.arg(arg);
-        }
-        true
-    };
 
-    cmd.stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .kill_on_drop(true);
+    let adapter = adapter_for(&model.name);
+
+    // The mock model answers from `.ralf/mock/chat.md` in the current repo
+    if let Some(response) = adapter.mock_response(Path::new("."), None) {
+        let delay = response.delay.min(Duration::from_secs(timeout_secs));
+        tokio::time::sleep(delay).await;
+        if response.delay > delay {
+            return Err(RunnerError::Timeout(model.name.clone()));
+        }
+        if let Some(partial) = partial {
+            let _ = partial.send(response.stdout.clone());
+        }
+        #[allow(clippy::cast_possible_truncation)]
+        let duration_ms = delay.as_millis() as u64;
+        return Ok(ChatResult {
+            model: model.name.clone(),
+            content: adapter.parse_output(&response.stdout, &response.stderr),
+            duration_ms,
+            has_draft_update: false,
+        });
+    }
 
-    let mut child = cmd.spawn().map_err(RunnerError::Spawn)?;
+    // Build command - the adapter handles model-specific invocation patterns
+    let spec = adapter.command(model, prompt);
+    let mut cmd = spec.to_tokio_command();
+    let (mut child, guard) = spawn_process(&mut cmd)?;
 
     // Write prompt to stdin if needed
-    if uses_stdin {
-        if let Some(mut stdin) = child.stdin.take() {
+    if let Some(mut stdin) = child.stdin.take() {
+        if let Some(input) = &spec.stdin {
             stdin
-                .write_all(prompt.as_bytes())
+                .write_all(input.as_bytes())
                 .await
                 .map_err(RunnerError::Io)?;
-            drop(stdin);
         }
+        drop(stdin);
     }
 
     // Wait with timeout
     let timeout_duration = Duration::from_secs(timeout_secs);
-    let result = timeout(timeout_duration, child.wait_with_output()).await;
+    let partial = partial.filter(|_| adapter.streams_output());
+    let output = wait_with_limit_streaming(child, guard, timeout_duration, partial)
+        .await
+        .map_err(RunnerError::Io)?;
 
     #[allow(clippy::cast_possible_truncation)]
     let duration_ms = start.elapsed().as_millis() as u64;
 
-    match result {
-        Ok(Ok(output)) => {
+    match output.status {
+        Some(_) => {
             let stdout = String::from_utf8_lossy(&output.stdout).to_string();
             let stderr = String::from_utf8_lossy(&output.stderr).to_string();
 
-            // Use stdout if available, otherwise stderr (some CLIs output to stderr)
-            let response = if stdout.trim().is_empty() {
-                stderr
-            } else {
-                stdout
-            };
+            let response = adapter.parse_output(&stdout, &stderr);
 
             Ok(ChatResult {
                 model: model.name.clone(),
@@ -292,11 +422,71 @@ pub async fn invoke_chat(
                 has_draft_update: false, // Could be detected with heuristics later
             })
         }
-        Ok(Err(e)) => Err(RunnerError::Io(e)),
-        Err(_) => Err(RunnerError::Timeout(model.name.clone())),
+        None => Err(RunnerError::Timeout(model.name.clone())),
     }
 }
 
+/// Instructions for assessing a draft spec.
+const ASSESSMENT_PROMPT: &str = r"You are reviewing a task specification that an autonomous coding agent will implement. Assess the draft below for:
+
+- Clarity: is the goal unambiguous?
+- Testability: can each completion criterion be checked from artifacts (files, test results, command output)?
+- Missing criteria: are there requirements with no matching completion criterion?
+
+Report each finding on its own line, in exactly this format:
+
+PASS <aspect>: <short explanation>
+FAIL <aspect>: <what is wrong and how to fix it>
+
+Use one line per criterion you find untestable or per gap you find. Do not rewrite the spec.";
+
+/// Build the prompt sent to the assessor model.
+pub fn build_assessment_prompt(draft: &str) -> String {
+    format!("{ASSESSMENT_PROMPT}\n\nDraft:\n---\n{draft}\n---\n")
+}
+
+/// A single finding from a spec assessment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssessmentFinding {
+    /// What was assessed (e.g., "Clarity").
+    pub aspect: String,
+    /// Whether the spec passed this check.
+    pub passed: bool,
+    /// Explanation from the assessor.
+    pub details: String,
+}
+
+/// Parse `PASS <aspect>: <details>` / `FAIL <aspect>: <details>` lines from
+/// an assessment response. Other lines are ignored.
+pub fn parse_assessment(response: &str) -> Vec<AssessmentFinding> {
+    let re =
+        regex::Regex::new(r"(?i)^\s*(?:[-*]\s*)?\**(pass|fail)\**\s*[:\-]?\s*([^:]+?)\s*:\s*(.*)$")
+            .expect("Invalid regex pattern");
+    response
+        .lines()
+        .filter_map(|line| {
+            let caps = re.captures(line)?;
+            Some(AssessmentFinding {
+                aspect: caps[2].trim().to_string(),
+                passed: caps[1].eq_ignore_ascii_case("pass"),
+                details: caps[3].trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Check that a draft is ready to be finalized as `PROMPT.md`.
+///
+/// Returns a list of problems; empty means the draft can be finalized.
+/// These are the errors from [`crate::spec::lint`]; warnings don't block.
+pub fn validate_spec(draft: &str) -> Vec<String> {
+    crate::spec::lint(draft)
+        .into_iter()
+        .filter(crate::spec::SpecDiagnostic::is_error)
+        .map(|d| d.message)
+        .collect()
+}
+
 /// A conversation thread with persistence.
 #[derive(Debug, Clone)]
 pub struct Thread {
@@ -308,12 +498,34 @@ pub struct Thread {
     pub messages: Vec<ChatMessage>,
     /// Current draft content.
     pub draft: String,
+    /// Model chosen with `/model` for chat and runs (first ready model if unset).
+    pub model: Option<String>,
+    /// Earlier versions of the conversation, set aside when a message was
+    /// edited.
+    pub branches: Vec<ThreadBranch>,
+    /// Files attached with `/attach`.
+    pub attachments: Vec<Attachment>,
     /// Creation timestamp.
     pub created_at: DateTime<Utc>,
     /// Last updated timestamp.
     pub updated_at: DateTime<Utc>,
 }
 
+/// A version of a thread's conversation that was set aside, kept so the
+/// user can go back to it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadBranch {
+    /// The branch's messages, from the start of the thread.
+    pub messages: Vec<ChatMessage>,
+    /// The draft at the end of the branch.
+    pub draft: String,
+    /// Index of the first message that differs from the conversation it
+    /// was set aside for.
+    pub forked_at: usize,
+    /// When the branch was set aside.
+    pub set_aside_at: DateTime<Utc>,
+}
+
 impl Thread {
     /// Create a new thread.
     pub fn new() -> Self {
@@ -323,6 +535,9 @@ impl Thread {
             title: "New Specification".into(),
             messages: Vec::new(),
             draft: String::new(),
+            model: None,
+            branches: Vec::new(),
+            attachments: Vec::new(),
             created_at: now,
             updated_at: now,
         }
@@ -336,6 +551,9 @@ impl Thread {
             title: "New Specification".into(),
             messages: Vec::new(),
             draft: String::new(),
+            model: None,
+            branches: Vec::new(),
+            attachments: Vec::new(),
             created_at: now,
             updated_at: now,
         }
@@ -354,42 +572,114 @@ impl Thread {
         self.updated_at = Utc::now();
     }
 
+    /// Replace the user message at `index` with `content`, dropping the
+    /// messages after it so the model can reply afresh. The conversation as
+    /// it was is kept as a branch, and the draft goes back to the latest one
+    /// in the messages kept (empty if none has one).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ChatError::NotEditable`] if there's no user message at
+    /// `index`.
+    pub fn edit_message(
+        &mut self,
+        index: usize,
+        content: impl Into<String>,
+    ) -> Result<(), ChatError> {
+        if self
+            .messages
+            .get(index)
+            .is_none_or(|m| m.role != Role::User)
+        {
+            return Err(ChatError::NotEditable(index));
+        }
+        self.branches.push(ThreadBranch {
+            messages: self.messages.clone(),
+            draft: self.draft.clone(),
+            forked_at: index,
+            set_aside_at: Utc::now(),
+        });
+        self.messages.truncate(index);
+        self.draft = latest_draft(&self.messages);
+        self.add_message(ChatMessage::user(content));
+        Ok(())
+    }
+
+    /// Go back to the branch at `index`. The current conversation takes its
+    /// place, so switching again returns to it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ChatError::NoBranch`] if there's no branch at `index`.
+    pub fn switch_branch(&mut self, index: usize) -> Result<(), ChatError> {
+        let branch = self
+            .branches
+            .get_mut(index)
+            .ok_or(ChatError::NoBranch(index))?;
+        std::mem::swap(&mut branch.messages, &mut self.messages);
+        std::mem::swap(&mut branch.draft, &mut self.draft);
+        branch.set_aside_at = Utc::now();
+        self.updated_at = Utc::now();
+        Ok(())
+    }
+
+    /// Attach a file, replacing an earlier attachment of the same path.
+    pub fn attach(&mut self, attachment: Attachment) {
+        self.attachments.retain(|a| a.path != attachment.path);
+        self.attachments.push(attachment);
+        self.updated_at = Utc::now();
+    }
+
+    /// Indices of the user messages, oldest first.
+    pub fn user_message_indices(&self) -> Vec<usize> {
+        self.messages
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| m.role == Role::User)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
     /// Convert to chat context for model invocation.
     pub fn to_context(&self) -> ChatContext {
         ChatContext {
             messages: self.messages.clone(),
             draft: self.draft.clone(),
+            attachments: self.attachments.clone(),
         }
     }
 
     /// Save thread to a JSONL file.
     pub fn save(&self, spec_dir: &Path) -> Result<(), ChatError> {
-        use std::io::Write;
+        use std::fmt::Write;
 
         let threads_dir = spec_dir.join("threads");
         std::fs::create_dir_all(&threads_dir).map_err(ChatError::Io)?;
 
         let path = threads_dir.join(format!("{}.jsonl", self.id));
-        let mut file = std::fs::File::create(&path).map_err(ChatError::Io)?;
+        let mut content = String::new();
 
         // Write metadata as first line
         let metadata = ThreadMetadata {
             id: self.id.clone(),
             title: self.title.clone(),
             draft: self.draft.clone(),
+            model: self.model.clone(),
+            branches: self.branches.clone(),
+            attachments: self.attachments.clone(),
             created_at: self.created_at,
             updated_at: self.updated_at,
         };
         let meta_json = serde_json::to_string(&metadata).map_err(ChatError::Serialize)?;
-        writeln!(file, "{meta_json}").map_err(ChatError::Io)?;
+        let _ = writeln!(content, "{meta_json}");
 
         // Write each message
         for msg in &self.messages {
             let json = serde_json::to_string(msg).map_err(ChatError::Serialize)?;
-            writeln!(file, "{json}").map_err(ChatError::Io)?;
+            let _ = writeln!(content, "{json}");
         }
 
-        Ok(())
+        atomic_write(&path, content.as_bytes()).map_err(ChatError::Io)
     }
 
     /// Load thread from a JSONL file.
@@ -417,6 +707,9 @@ impl Thread {
             title: metadata.title,
             messages,
             draft: metadata.draft,
+            model: metadata.model,
+            branches: metadata.branches,
+            attachments: metadata.attachments,
             created_at: metadata.created_at,
             updated_at: metadata.updated_at,
         })
@@ -456,20 +749,36 @@ struct ThreadMetadata {
     id: String,
     title: String,
     draft: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    model: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    branches: Vec<ThreadBranch>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    attachments: Vec<Attachment>,
     created_at: DateTime<Utc>,
     updated_at: DateTime<Utc>,
 }
 
+/// The draft in the latest reply of `messages` that has one.
+fn latest_draft(messages: &[ChatMessage]) -> String {
+    messages
+        .iter()
+        .rev()
+        .filter(|m| m.role == Role::Assistant)
+        .find_map(|m| extract_spec_from_response(&m.content))
+        .unwrap_or_default()
+}
+
 /// Save a draft snapshot.
 pub fn save_draft_snapshot(spec_dir: &Path, draft: &str) -> Result<String, ChatError> {
-    let drafts_dir = spec_dir.join("drafts");
+    let drafts_dir = spec_dir.join(DRAFTS_DIR);
     std::fs::create_dir_all(&drafts_dir).map_err(ChatError::Io)?;
 
     let timestamp = Utc::now().format("%Y%m%d_%H%M%S").to_string();
     let filename = format!("{timestamp}.md");
     let path = drafts_dir.join(&filename);
 
-    std::fs::write(&path, draft).map_err(ChatError::Io)?;
+    atomic_write(&path, draft.as_bytes()).map_err(ChatError::Io)?;
     Ok(filename)
 }
 
@@ -488,9 +797,15 @@ pub fn extract_draft_promise(draft: &str) -> Option<String> {
 
 /// Extract the spec/draft portion from an assistant response.
 ///
-/// Looks for content between `---` markers that contains markdown spec structure,
-/// or falls back to extracting the markdown portion starting with a `#` header.
+/// Prefers the spec the system prompt asks for: the last ` ```spec ` fenced
+/// block or `<spec>...</spec>` section. Without one, looks for content
+/// between `---` markers that contains markdown spec structure, or falls
+/// back to extracting the markdown portion starting with a `#` header.
 pub fn extract_spec_from_response(response: &str) -> Option<String> {
+    if let Some(spec) = marked_spec(response) {
+        return Some(spec);
+    }
+
     // First, try to find content between --- markers
     let parts: Vec<&str> = response.split("---").collect();
 
@@ -545,6 +860,72 @@ pub fn extract_spec_from_response(response: &str) -> Option<String> {
     Some(spec_lines.join("\n"))
 }
 
+/// The last explicitly marked spec in `response`, whichever of a ` ```spec `
+/// block or `<spec>` tags comes later. Unclosed or empty markers don't count.
+fn marked_spec(response: &str) -> Option<String> {
+    let tagged = response.rfind("<spec>").and_then(|start| {
+        let body = &response[start + "<spec>".len()..];
+        let end = body.find("</spec>")?;
+        Some((start, body[..end].trim()))
+    });
+    let fenced = fenced_specs(response).pop();
+
+    let spec = match (tagged, fenced) {
+        (Some((tag_at, tag)), Some((fence_at, fence))) => {
+            if tag_at > fence_at {
+                tag.to_string()
+            } else {
+                fence
+            }
+        }
+        (Some((_, tag)), None) => tag.to_string(),
+        (None, Some((_, fence))) => fence,
+        (None, None) => return None,
+    };
+    (!spec.is_empty()).then_some(spec)
+}
+
+/// The closed ` ```spec ` blocks in `text`, with their byte offsets. Code
+/// blocks inside a spec (a `sh` example in its criteria, say) are kept.
+fn fenced_specs(text: &str) -> Vec<(usize, String)> {
+    let mut specs = Vec::new();
+    // Offset, fence length, lines so far and nested fences still open
+    let mut current: Option<(usize, usize, Vec<&str>, usize)> = None;
+    let mut offset = 0;
+
+    for line in text.split_inclusive('\n') {
+        let line_at = offset;
+        offset += line.len();
+        let line = line.trim_end_matches(['\n', '\r']);
+        let trimmed = line.trim();
+        let ticks = trimmed.len() - trimmed.trim_start_matches('`').len();
+        let info = trimmed[ticks..].trim();
+
+        match current.as_mut() {
+            None => {
+                if ticks >= 3 && info == "spec" {
+                    current = Some((line_at, ticks, Vec::new(), 0));
+                }
+            }
+            Some((at, fence, lines, nested)) => {
+                if ticks >= 3 && info.is_empty() {
+                    if *nested > 0 {
+                        *nested -= 1;
+                    } else if ticks >= *fence {
+                        specs.push((*at, lines.join("\n").trim().to_string()));
+                        current = None;
+                        continue;
+                    }
+                } else if ticks >= 3 {
+                    *nested += 1;
+                }
+                lines.push(line);
+            }
+        }
+    }
+    specs
+}
+
 /// Errors that can occur in chat operations.
 #[derive(Debug, thiserror::Error)]
 pub enum ChatError {
@@ -567,6 +948,18 @@ pub enum ChatError {
     /// Thread not found.
     #[error("Thread not found: {0}")]
     NotFound(String),
+
+    /// No user message to edit at the index.
+    #[error("Message {0} is not a user message")]
+    NotEditable(usize),
+
+    /// No branch at the index.
+    #[error("No branch {0}")]
+    NoBranch(usize),
+
+    /// The file to attach isn't text.
+    #[error("{0} is not a text file")]
+    BinaryAttachment(String),
 }
 
 #[cfg(test)]
@@ -597,6 +990,58 @@ mod tests {
         assert!(prompt.contains("User: I want to build a CLI tool"));
     }
 
+    #[test]
+    fn test_attachments() {
+        let temp = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp.path().join("error.log"), "panicked at main.rs:3\n").unwrap();
+        std::fs::write(temp.path().join("README.md"), "Run:\n```sh\nralf\n```\n").unwrap();
+        std::fs::write(
+            temp.path().join("big.txt"),
+            "é".repeat(MAX_ATTACHMENT_BYTES),
+        )
+        .unwrap();
+        std::fs::write(temp.path().join("blob.bin"), [0u8, 159, 146, 150]).unwrap();
+
+        let log = Attachment::read(temp.path(), "error.log").unwrap();
+        assert_eq!(
+            log.to_prompt(),
+            "File: error.log\n```\npanicked at main.rs:3\n```\n"
+        );
+        // Fenced past the file's own fences
+        let readme = Attachment::read(temp.path(), "README.md").unwrap();
+        assert!(readme
+            .to_prompt()
+            .starts_with("File: README.md\n````\nRun:"));
+
+        // Cut to the cap on a character boundary
+        let big = Attachment::read(temp.path(), "big.txt").unwrap();
+        assert!(big.truncated);
+        assert_eq!(big.content.len(), MAX_ATTACHMENT_BYTES);
+        assert!(big.to_prompt().starts_with("File: big.txt (first 32 KB)\n"));
+
+        assert!(matches!(
+            Attachment::read(temp.path(), "blob.bin"),
+            Err(ChatError::BinaryAttachment(_))
+        ));
+        assert!(matches!(
+            Attachment::read(temp.path(), "missing.txt"),
+            Err(ChatError::Io(_))
+        ));
+
+        // Kept with the thread and sent with every prompt
+        let mut thread = Thread::new();
+        thread.attach(log.clone());
+        thread.attach(readme);
+        thread.attach(log);
+        assert_eq!(thread.attachments[1].path, "error.log");
+        thread.save(temp.path()).unwrap();
+        let loaded = Thread::load(temp.path(), &thread.id).unwrap();
+        assert_eq!(loaded.attachments, thread.attachments);
+        let prompt = loaded.to_context().build_prompt();
+        assert!(prompt.contains("Attached files:\n\nFile: README.md\n"));
+        assert!(prompt.contains("File: error.log\n```\npanicked"));
+    }
+
     #[test]
     fn test_thread_title_from_first_message() {
         let mut thread = Thread::new();
@@ -605,6 +1050,61 @@ mod tests {
         assert!(thread.title.starts_with("Build a markdown"));
     }
 
+    #[test]
+    fn test_thread_save_load_model() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let mut thread = Thread::new();
+        thread.save(temp.path()).unwrap();
+        assert_eq!(Thread::load(temp.path(), &thread.id).unwrap().model, None);
+
+        thread.model = Some("codex".into());
+        thread.save(temp.path()).unwrap();
+        let loaded = Thread::load(temp.path(), &thread.id).unwrap();
+        assert_eq!(loaded.model.as_deref(), Some("codex"));
+    }
+
+    #[test]
+    fn test_edit_message_branches() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let mut thread = Thread::new();
+        thread.add_message(ChatMessage::user("Build a CLI"));
+        thread.add_message(ChatMessage::assistant("---\n# CLI v1\n---", "claude"));
+        thread.add_message(ChatMessage::user("Add colors"));
+        thread.add_message(ChatMessage::assistant("---\n# CLI v2\n---", "claude"));
+        thread.draft = "# CLI v2".into();
+        assert_eq!(thread.user_message_indices(), vec![0, 2]);
+
+        assert!(matches!(
+            thread.edit_message(1, "nope"),
+            Err(ChatError::NotEditable(1))
+        ));
+        thread.edit_message(2, "Add a --json flag").unwrap();
+        assert_eq!(thread.messages.len(), 3);
+        assert_eq!(thread.messages[2].content, "Add a --json flag");
+        assert_eq!(thread.draft, "# CLI v1");
+        assert_eq!(thread.branches.len(), 1);
+        assert_eq!(thread.branches[0].forked_at, 2);
+        assert_eq!(thread.branches[0].messages.len(), 4);
+
+        // Branches survive a save, and switching swaps them with the current one
+        thread.save(temp.path()).unwrap();
+        let mut loaded = Thread::load(temp.path(), &thread.id).unwrap();
+        loaded.switch_branch(0).unwrap();
+        assert_eq!(loaded.messages[2].content, "Add colors");
+        assert_eq!(loaded.draft, "# CLI v2");
+        assert_eq!(loaded.branches[0].messages[2].content, "Add a --json flag");
+        assert!(matches!(
+            loaded.switch_branch(1),
+            Err(ChatError::NoBranch(1))
+        ));
+
+        // Editing the first message starts over, retitling the thread
+        loaded.edit_message(0, "Build a TUI").unwrap();
+        assert_eq!(loaded.title, "Build a TUI");
+        assert_eq!(loaded.draft, "");
+        assert_eq!(loaded.branches.len(), 2);
+    }
+
     #[test]
     fn test_draft_has_promise() {
         assert!(draft_has_promise(
@@ -623,6 +1123,43 @@ mod tests {
         assert_eq!(extract_draft_promise("No promise"), None);
     }
 
+    #[test]
+    fn test_parse_assessment() {
+        let response = "Here is my review.\n\
+            PASS Clarity: The goal is clear.\n\
+            - FAIL Testability: \"works well\" cannot be checked.\n\
+            **FAIL** Missing criteria: No criterion covers error handling.\n\
+            Overall decent.";
+        let findings = parse_assessment(response);
+        assert_eq!(findings.len(), 3);
+        assert_eq!(findings[0].aspect, "Clarity");
+        assert!(findings[0].passed);
+        assert_eq!(findings[1].aspect, "Testability");
+        assert!(!findings[1].passed);
+        assert_eq!(findings[1].details, "\"works well\" cannot be checked.");
+        assert_eq!(findings[2].aspect, "Missing criteria");
+        assert!(parse_assessment("No structured findings").is_empty());
+    }
+
+    #[test]
+    fn test_build_assessment_prompt() {
+        let prompt = build_assessment_prompt("# My Spec");
+        assert!(prompt.contains("Testability"));
+        assert!(prompt.contains("---\n# My Spec\n---"));
+    }
+
+    #[test]
+    fn test_validate_spec() {
+        let ready =
+            "# Task\n\n## Completion Criteria\n- [ ] File exists\n\n<promise>COMPLETE</promise>";
+        assert!(validate_spec(ready).is_empty());
+
+        let problems = validate_spec("# Task\n\nJust prose.");
+        assert_eq!(problems.len(), 2);
+        assert!(problems[0].contains("promise"));
+        assert!(problems[1].contains("criteria"));
+    }
+
     #[test]
     fn test_extract_spec_from_response() {
         // Test with --- delimited spec
@@ -658,4 +1195,52 @@ What do you think?"#;
         let response3 = "Just a regular message without any spec.";
         assert!(extract_spec_from_response(response3).is_none());
     }
+
+    #[test]
+    fn test_extract_marked_spec() {
+        // A fenced spec wins over headings and rules in the chat around it
+        let response = r#"# Quick thoughts
+
+Your old draft had a gap:
+---
+# Old Tool
+---
+
+Here's the update:
+
+```spec
+# My Tool
+
+## Completion Criteria
+- [ ] Running this prints "ok":
+  ```sh
+  my-tool --check
+  ```
+```
+
+Does the check command look right?"#;
+        let spec = extract_spec_from_response(response).unwrap();
+        assert!(spec.starts_with("# My Tool"));
+        assert!(spec.ends_with("  ```"));
+        assert!(spec.contains("my-tool --check"));
+        assert!(!spec.contains("Does the check"));
+
+        // Tags work too, and the later marked spec wins
+        let response = "```spec\n# First\n```\nActually:\n<spec>\n# Second\n</spec>\nThoughts?";
+        assert_eq!(extract_spec_from_response(response).unwrap(), "# Second");
+        let response = "<spec># First</spec>\n````spec\n# Second\n````";
+        assert_eq!(extract_spec_from_response(response).unwrap(), "# Second");
+
+        // Unclosed or empty markers fall back to the heuristics
+        let response = "Draft:\n```spec\n# Cut Off\n\n## Goal\nStuff";
+        assert_eq!(
+            extract_spec_from_response(response).unwrap(),
+            "# Cut Off\n\n## Goal\nStuff"
+        );
+        let response = "<spec></spec>\n# Fallback\n\nBody";
+        assert_eq!(
+            extract_spec_from_response(response).unwrap(),
+            "# Fallback\n\nBody"
+        );
+    }
 }
@@ -0,0 +1,417 @@
+//! Review checklists for threads pending review.
+//!
+//! When a thread's implementation is ready for review,
+//! [`ReviewChecklist::generate`] turns what changed since the thread's
+//! baseline and its spec's criteria into items to tick off: the files to
+//! inspect, changes that are risky, code changed without any test to go
+//! with it, and each criterion. The checklist is kept with the thread, and
+//! approving the thread records which items the reviewer checked.
+
+use std::fmt;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::criterion::Criterion;
+use crate::git::GitError;
+use crate::review::{changes_since, glob_matches, FileChange};
+use crate::thread::GitBaseline;
+
+/// Changed files listed one by one before the rest are summed up.
+const MAX_INSPECT_FILES: usize = 20;
+
+/// Lines changed in one file that make the change risky.
+const LARGE_CHANGE_LINES: usize = 200;
+
+/// Paths whose changes are risky whatever their size, and why.
+const RISKY_PATHS: &[(&str, &str)] = &[
+    ("*.lock", "dependency lockfile"),
+    ("Cargo.toml", "dependency manifest"),
+    ("package.json", "dependency manifest"),
+    ("go.mod", "dependency manifest"),
+    ("pyproject.toml", "dependency manifest"),
+    ("requirements*.txt", "dependency manifest"),
+    (".github/**", "CI configuration"),
+    (".gitlab-ci.yml", "CI configuration"),
+    ("Dockerfile", "container build"),
+    ("build.rs", "build script"),
+    ("migrations/", "database migration"),
+    ("*.sql", "SQL"),
+];
+
+/// Extensions of source files that tests are expected to cover.
+const SOURCE_EXTENSIONS: &[&str] = &[
+    "rs", "py", "ts", "tsx", "js", "jsx", "go", "java", "kt", "rb", "c", "cc", "cpp", "h", "cs",
+    "swift",
+];
+
+/// What a checklist item is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChecklistSection {
+    /// A file the implementation changed.
+    Inspect,
+    /// A change that deserves a closer look.
+    Risky,
+    /// Code changed without a test changing with it.
+    Untested,
+    /// A completion criterion from the spec.
+    Criteria,
+}
+
+impl ChecklistSection {
+    /// Heading for the section's items.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Inspect => "Files to inspect",
+            Self::Risky => "Risky changes",
+            Self::Untested => "Untested paths",
+            Self::Criteria => "Criteria",
+        }
+    }
+}
+
+/// One thing for the reviewer to check.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChecklistItem {
+    /// What the item is about.
+    pub section: ChecklistSection,
+    /// What to check.
+    pub text: String,
+    /// Whether the reviewer checked it.
+    #[serde(default)]
+    pub checked: bool,
+}
+
+impl ChecklistItem {
+    fn new(section: ChecklistSection, text: impl Into<String>) -> Self {
+        Self {
+            section,
+            text: text.into(),
+            checked: false,
+        }
+    }
+}
+
+/// The checklist for a thread's review.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReviewChecklist {
+    /// When the checklist was generated.
+    pub generated_at: DateTime<Utc>,
+    /// Items in section order.
+    pub items: Vec<ChecklistItem>,
+    /// When the thread was approved with the checklist as it is.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approved_at: Option<DateTime<Utc>>,
+}
+
+impl ReviewChecklist {
+    /// The checklist for `changes`, implementing `criteria`.
+    /// `protected_paths` (see [`ReviewConfig`](crate::ReviewConfig)) are
+    /// risky on top of the paths ralf always treats as risky.
+    pub fn generate(
+        changes: &[FileChange],
+        criteria: &[Criterion],
+        protected_paths: &[String],
+    ) -> Self {
+        let mut items = Vec::new();
+
+        let mut by_size: Vec<&FileChange> = changes.iter().collect();
+        by_size.sort_by_key(|change| std::cmp::Reverse(change.added + change.removed));
+        for change in by_size.iter().take(MAX_INSPECT_FILES) {
+            items.push(ChecklistItem::new(
+                ChecklistSection::Inspect,
+                format!("{} (+{} -{})", change.path, change.added, change.removed),
+            ));
+        }
+        if by_size.len() > MAX_INSPECT_FILES {
+            items.push(ChecklistItem::new(
+                ChecklistSection::Inspect,
+                format!("{} more files", by_size.len() - MAX_INSPECT_FILES),
+            ));
+        }
+
+        for change in changes {
+            let lines = change.added + change.removed;
+            let mut why = Vec::new();
+            if protected_paths
+                .iter()
+                .any(|pattern| glob_matches(pattern, &change.path))
+            {
+                why.push("protected path".to_string());
+            }
+            if let Some((_, kind)) = RISKY_PATHS
+                .iter()
+                .find(|(pattern, _)| glob_matches(pattern, &change.path))
+            {
+                why.push((*kind).to_string());
+            }
+            if lines >= LARGE_CHANGE_LINES {
+                why.push(format!("{lines} lines changed"));
+            }
+            if change.added == 0 && change.removed > 0 {
+                why.push(format!("{} lines removed, none added", change.removed));
+            }
+            if !why.is_empty() {
+                items.push(ChecklistItem::new(
+                    ChecklistSection::Risky,
+                    format!("{}: {}", change.path, why.join(", ")),
+                ));
+            }
+        }
+
+        let tests: Vec<&str> = changes
+            .iter()
+            .map(|change| change.path.as_str())
+            .filter(|path| is_test_path(path))
+            .collect();
+        for change in changes {
+            if change.added == 0 || !is_source_path(&change.path) || is_test_path(&change.path) {
+                continue;
+            }
+            let stem = Path::new(&change.path)
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or_default();
+            if !tests.iter().any(|test| test.contains(stem)) {
+                items.push(ChecklistItem::new(
+                    ChecklistSection::Untested,
+                    format!("{}: no test changes", change.path),
+                ));
+            }
+        }
+
+        for criterion in criteria {
+            items.push(ChecklistItem::new(
+                ChecklistSection::Criteria,
+                criterion.text.clone(),
+            ));
+        }
+
+        Self {
+            generated_at: Utc::now(),
+            items,
+            approved_at: None,
+        }
+    }
+
+    /// The checklist for the changes in `work_dir` since `baseline`,
+    /// including commits, implementing `spec`'s criteria.
+    ///
+    /// # Errors
+    ///
+    /// Returns the git error if the workspace can't be diffed.
+    pub fn for_baseline(
+        work_dir: &Path,
+        baseline: &GitBaseline,
+        spec: &str,
+        protected_paths: &[String],
+    ) -> Result<Self, GitError> {
+        let changes = changes_since(work_dir, &baseline.commit_sha)?;
+        Ok(Self::generate(
+            &changes,
+            &crate::parse_criteria(spec),
+            protected_paths,
+        ))
+    }
+
+    /// Check or uncheck the item at `index`. Returns false if there is none.
+    pub fn toggle(&mut self, index: usize) -> bool {
+        let Some(item) = self.items.get_mut(index) else {
+            return false;
+        };
+        item.checked = !item.checked;
+        true
+    }
+
+    /// How many items are checked.
+    pub fn checked_count(&self) -> usize {
+        self.items.iter().filter(|item| item.checked).count()
+    }
+
+    /// Record that the thread was approved with the items checked so far.
+    pub fn approve(&mut self) {
+        self.approved_at = Some(Utc::now());
+    }
+}
+
+impl fmt::Display for ReviewChecklist {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} review items checked",
+            self.checked_count(),
+            self.items.len()
+        )
+    }
+}
+
+/// Whether `path` looks like a test.
+fn is_test_path(path: &str) -> bool {
+    path.split('/').any(|component| {
+        let name = component.split('.').next().unwrap_or_default();
+        matches!(component, "tests" | "test" | "spec" | "__tests__")
+            || name.starts_with("test_")
+            || name.ends_with("_test")
+            || name.ends_with("_spec")
+            || component.contains(".test.")
+            || component.contains(".spec.")
+    })
+}
+
+/// Whether `path` is a source file tests are expected to cover.
+fn is_source_path(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| SOURCE_EXTENSIONS.contains(&ext))
+        && !matches!(
+            Path::new(path).file_name().and_then(|name| name.to_str()),
+            Some("build.rs" | "setup.py")
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(path: &str, added: usize, removed: usize) -> FileChange {
+        FileChange {
+            path: path.into(),
+            added,
+            removed,
+        }
+    }
+
+    fn texts(checklist: &ReviewChecklist, section: ChecklistSection) -> Vec<&str> {
+        checklist
+            .items
+            .iter()
+            .filter(|item| item.section == section)
+            .map(|item| item.text.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_generate() {
+        let changes = vec![
+            change("src/auth.rs", 40, 2),
+            change("src/parser.rs", 250, 10),
+            change("tests/parser_test.rs", 30, 0),
+            change("Cargo.lock", 12, 4),
+            change("docs/old.md", 0, 20),
+            change("config/secrets.yml", 1, 1),
+        ];
+        let criteria = crate::parse_criteria("## Criteria\n- Logins are checked\n");
+        let checklist = ReviewChecklist::generate(&changes, &criteria, &["config/".to_string()]);
+
+        assert_eq!(
+            texts(&checklist, ChecklistSection::Inspect),
+            vec![
+                "src/parser.rs (+250 -10)",
+                "src/auth.rs (+40 -2)",
+                "tests/parser_test.rs (+30 -0)",
+                "docs/old.md (+0 -20)",
+                "Cargo.lock (+12 -4)",
+                "config/secrets.yml (+1 -1)",
+            ]
+        );
+        assert_eq!(
+            texts(&checklist, ChecklistSection::Risky),
+            vec![
+                "src/parser.rs: 260 lines changed",
+                "Cargo.lock: dependency lockfile",
+                "docs/old.md: 20 lines removed, none added",
+                "config/secrets.yml: protected path",
+            ]
+        );
+        // The parser's test changed with it
+        assert_eq!(
+            texts(&checklist, ChecklistSection::Untested),
+            vec!["src/auth.rs: no test changes"]
+        );
+        assert_eq!(
+            texts(&checklist, ChecklistSection::Criteria),
+            vec!["Logins are checked"]
+        );
+        assert!(checklist.items.iter().all(|item| !item.checked));
+    }
+
+    #[test]
+    fn test_generate_caps_files() {
+        let changes: Vec<FileChange> = (0..25)
+            .map(|i| change(&format!("docs/{i}.md"), 1, 1))
+            .collect();
+        let checklist = ReviewChecklist::generate(&changes, &[], &[]);
+        let inspect = texts(&checklist, ChecklistSection::Inspect);
+        assert_eq!(inspect.len(), MAX_INSPECT_FILES + 1);
+        assert_eq!(inspect.last(), Some(&"5 more files"));
+    }
+
+    #[test]
+    fn test_for_baseline() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let git = |args: &[&str]| {
+            assert!(std::process::Command::new("git")
+                .args(args)
+                .current_dir(dir)
+                .output()
+                .unwrap()
+                .status
+                .success());
+        };
+        git(&["init"]);
+        git(&["config", "user.email", "test@test.com"]);
+        git(&["config", "user.name", "Test"]);
+        std::fs::write(dir.join("lib.rs"), "fn a() {}\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "init"]);
+        let baseline = crate::GitSafety::new(dir).capture_baseline().unwrap();
+
+        // Committed and uncommitted changes both count
+        std::fs::write(dir.join("lib.rs"), "fn b() {}\n").unwrap();
+        git(&["commit", "-am", "change"]);
+        std::fs::write(dir.join("Cargo.lock"), "v1\n").unwrap();
+
+        let checklist =
+            ReviewChecklist::for_baseline(dir, &baseline, "## Criteria\n- Works\n", &[]).unwrap();
+        assert_eq!(
+            texts(&checklist, ChecklistSection::Inspect),
+            vec!["lib.rs (+1 -1)", "Cargo.lock (+1 -0)"]
+        );
+        assert_eq!(
+            texts(&checklist, ChecklistSection::Risky),
+            vec!["Cargo.lock: dependency lockfile"]
+        );
+    }
+
+    #[test]
+    fn test_toggle_and_approve() {
+        let criteria = crate::parse_criteria("## Criteria\n- One\n- Two\n");
+        let mut checklist = ReviewChecklist::generate(&[], &criteria, &[]);
+        assert!(checklist.toggle(1));
+        assert!(!checklist.toggle(2));
+        assert_eq!(checklist.to_string(), "1 of 2 review items checked");
+        checklist.approve();
+
+        let json = serde_json::to_string(&checklist).unwrap();
+        let loaded: ReviewChecklist = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, checklist);
+        assert!(loaded.items[1].checked);
+        assert!(loaded.approved_at.is_some());
+    }
+
+    #[test]
+    fn test_test_paths() {
+        assert!(is_test_path("tests/cli.rs"));
+        assert!(is_test_path("src/test_parser.py"));
+        assert!(is_test_path("pkg/auth_test.go"));
+        assert!(is_test_path("web/app.test.ts"));
+        assert!(!is_test_path("src/attest.rs"));
+        assert!(is_source_path("src/lib.rs"));
+        assert!(!is_source_path("build.rs"));
+        assert!(!is_source_path("README.md"));
+    }
+}
@@ -0,0 +1,95 @@
+//! Time sources.
+//!
+//! Cooldowns, a run's runtime limit and waits, and the TUI's toasts read
+//! the time from a [`Clock`] rather than the system directly, so tests can
+//! move time on with a [`ManualClock`] instead of sleeping.
+
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::state::current_timestamp;
+
+/// A source of the current time.
+pub trait Clock: Send + Sync + Debug {
+    /// The current time as a Unix timestamp.
+    fn now(&self) -> u64;
+
+    /// The current monotonic time, for timeouts and expiries.
+    fn instant(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// The system clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        current_timestamp()
+    }
+}
+
+/// A clock that only moves when told to.
+#[derive(Debug)]
+pub struct ManualClock {
+    start: u64,
+    start_instant: Instant,
+    elapsed_ms: AtomicU64,
+}
+
+impl ManualClock {
+    /// A clock stopped at the Unix timestamp `now`.
+    pub fn new(now: u64) -> Self {
+        Self {
+            start: now,
+            start_instant: Instant::now(),
+            elapsed_ms: AtomicU64::new(0),
+        }
+    }
+
+    /// Move the clock on by `by`.
+    pub fn advance(&self, by: Duration) {
+        let ms = u64::try_from(by.as_millis()).unwrap_or(u64::MAX);
+        self.elapsed_ms.fetch_add(ms, Ordering::SeqCst);
+    }
+
+    fn elapsed(&self) -> Duration {
+        Duration::from_millis(self.elapsed_ms.load(Ordering::SeqCst))
+    }
+}
+
+impl Default for ManualClock {
+    /// A clock stopped at the current time.
+    fn default() -> Self {
+        Self::new(current_timestamp())
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> u64 {
+        self.start + self.elapsed().as_secs()
+    }
+
+    fn instant(&self) -> Instant {
+        self.start_instant + self.elapsed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manual_clock() {
+        let clock = ManualClock::new(1000);
+        let instant = clock.instant();
+        assert_eq!(clock.now(), 1000);
+        assert_eq!(clock.instant(), instant);
+
+        clock.advance(Duration::from_millis(1500));
+        assert_eq!(clock.now(), 1001);
+        assert_eq!(clock.instant() - instant, Duration::from_millis(1500));
+    }
+}
@@ -0,0 +1,286 @@
+//! Completion promises and the reports that can come with them.
+//!
+//! A model signals it is done with `<promise>COMPLETE</promise>`. It may
+//! also follow the promise word with a JSON report inside the same tags:
+//!
+//! ```text
+//! <promise>COMPLETE
+//! {
+//!   "summary": "Added a login page",
+//!   "files_changed": ["src/login.rs"],
+//!   "criteria": [{"id": "c1", "met": true, "notes": "Covered by tests"}],
+//!   "follow_up": ["Rate-limit login attempts"]
+//! }
+//! </promise>
+//! ```
+//!
+//! The report is parsed into a [`CompletionReport`] and stored on the run.
+//! A report that isn't valid JSON doesn't void the promise.
+//!
+//! A model that can't finish says so with another promise word instead
+//! ([`PromiseSignal`]): `<promise>BLOCKED: why</promise>` stops the run as
+//! stuck, and `<promise>NEEDS_INPUT: question</promise>` waits for the
+//! user's answer, which the next iteration's prompt carries.
+
+use serde::{Deserialize, Serialize};
+
+/// Opening tag of a promise block.
+const OPEN_TAG: &str = "<promise>";
+
+/// Closing tag of a promise block.
+const CLOSE_TAG: &str = "</promise>";
+
+/// Promise word for a model that can't go on.
+pub const BLOCKED_PROMISE: &str = "BLOCKED";
+
+/// Promise word for a model with a question for the user.
+pub const NEEDS_INPUT_PROMISE: &str = "NEEDS_INPUT";
+
+/// A promise that stops an iteration short of completion.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PromiseSignal {
+    /// `<promise>BLOCKED</promise>`, optionally followed by why: the model
+    /// can't go on by itself.
+    Blocked { reason: Option<String> },
+    /// `<promise>NEEDS_INPUT: question</promise>`: the model needs the
+    /// user's answer before it goes on.
+    NeedsInput { question: String },
+}
+
+impl PromiseSignal {
+    /// The signal in the last `BLOCKED` or `NEEDS_INPUT` block of `output`.
+    /// A `NEEDS_INPUT` without a question isn't one.
+    pub fn from_output(output: &str) -> Option<Self> {
+        let mut signal = None;
+        for inner in blocks(output) {
+            if let Some(reason) = signal_text(inner, BLOCKED_PROMISE) {
+                signal = Some(Self::Blocked {
+                    reason: (!reason.is_empty()).then(|| reason.to_string()),
+                });
+            } else if let Some(question) = signal_text(inner, NEEDS_INPUT_PROMISE) {
+                if !question.is_empty() {
+                    signal = Some(Self::NeedsInput {
+                        question: question.to_string(),
+                    });
+                }
+            }
+        }
+        signal
+    }
+}
+
+/// The model's own account of a finished task.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompletionReport {
+    /// What was done, in a sentence or two.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    /// Files the model changed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files_changed: Vec<String>,
+    /// The model's assessment of each completion criterion.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub criteria: Vec<CriterionAssessment>,
+    /// Work left for later.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub follow_up: Vec<String>,
+}
+
+/// The model's assessment of one criterion.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CriterionAssessment {
+    /// Criterion ID ("c1", ...).
+    pub id: String,
+    /// Whether the model considers the criterion met.
+    pub met: bool,
+    /// How it was met, or why not.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+}
+
+impl CompletionReport {
+    /// The report in the last `promise` block of `output`, if it has one.
+    ///
+    /// # Errors
+    ///
+    /// Returns the parse error if the report isn't valid JSON.
+    pub fn from_output(output: &str, promise: &str) -> Result<Option<Self>, serde_json::Error> {
+        let Some(body) = promise_body(output, promise) else {
+            return Ok(None);
+        };
+        let json = strip_json_fence(body);
+        if json.is_empty() {
+            return Ok(None);
+        }
+        serde_json::from_str(json).map(Some)
+    }
+
+    /// Number of criteria the model considers met, out of those it assessed.
+    pub fn criteria_met(&self) -> (usize, usize) {
+        let met = self.criteria.iter().filter(|c| c.met).count();
+        (met, self.criteria.len())
+    }
+}
+
+/// Whether `output` contains the completion `promise`, alone or followed by
+/// a report.
+pub fn has_promise(output: &str, promise: &str) -> bool {
+    promise_body(output, promise).is_some()
+}
+
+/// What follows the promise word in the last block for `promise`: empty
+/// for a plain `<promise>COMPLETE</promise>`.
+fn promise_body<'a>(output: &'a str, promise: &str) -> Option<&'a str> {
+    let mut body = None;
+    for inner in blocks(output) {
+        if let Some(tail) = inner.trim_start().strip_prefix(promise) {
+            // The promise word must stand alone ("COMPLETED" isn't "COMPLETE")
+            if tail.is_empty() || tail.starts_with(char::is_whitespace) {
+                body = Some(tail.trim());
+            }
+        }
+    }
+    body
+}
+
+/// What is between the tags of each complete promise block in `output`.
+fn blocks(output: &str) -> impl Iterator<Item = &str> {
+    let mut rest = output;
+    std::iter::from_fn(move || {
+        let start = rest.find(OPEN_TAG)?;
+        let after = &rest[start + OPEN_TAG.len()..];
+        let end = after.find(CLOSE_TAG)?;
+        rest = &after[end + CLOSE_TAG.len()..];
+        Some(&after[..end])
+    })
+}
+
+/// The text after `word` in a block, which may be set off by a colon.
+fn signal_text<'a>(inner: &'a str, word: &str) -> Option<&'a str> {
+    let tail = inner.trim_start().strip_prefix(word)?;
+    let text = tail.strip_prefix(':').unwrap_or(tail);
+    (text.len() < tail.len() || text.is_empty() || text.starts_with(char::is_whitespace))
+        .then(|| text.trim())
+}
+
+/// `text` without a surrounding ```` ```json ```` fence.
+fn strip_json_fence(text: &str) -> &str {
+    let Some(fenced) = text.strip_prefix("```") else {
+        return text;
+    };
+    let fenced = fenced.strip_prefix("json").unwrap_or(fenced);
+    fenced.strip_suffix("```").unwrap_or(fenced).trim()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_has_promise() {
+        assert!(has_promise("Done <promise>COMPLETE</promise>", "COMPLETE"));
+        assert!(has_promise(
+            "<promise>COMPLETE\n{\"summary\": \"ok\"}\n</promise>",
+            "COMPLETE"
+        ));
+        assert!(!has_promise("<promise>COMPLETED</promise>", "COMPLETE"));
+        assert!(!has_promise("<promise>COMPLETE", "COMPLETE"));
+        assert!(!has_promise("No promise here", "COMPLETE"));
+    }
+
+    #[test]
+    fn test_report_from_output() {
+        let output = r#"All done.
+<promise>COMPLETE
+```json
+{
+  "summary": "Added Vec<Login> handling",
+  "files_changed": ["src/login.rs"],
+  "criteria": [
+    {"id": "c1", "met": true, "notes": "Tested"},
+    {"id": "c2", "met": false}
+  ],
+  "follow_up": ["Rate-limit logins"]
+}
+```
+</promise>"#;
+        let report = CompletionReport::from_output(output, "COMPLETE")
+            .unwrap()
+            .unwrap();
+        assert_eq!(report.summary.as_deref(), Some("Added Vec<Login> handling"));
+        assert_eq!(report.files_changed, vec!["src/login.rs"]);
+        assert_eq!(report.criteria_met(), (1, 2));
+        assert_eq!(report.criteria[0].notes.as_deref(), Some("Tested"));
+        assert_eq!(report.follow_up, vec!["Rate-limit logins"]);
+    }
+
+    #[test]
+    fn test_plain_or_invalid_report() {
+        let plain = "<promise>COMPLETE</promise>";
+        assert_eq!(
+            CompletionReport::from_output(plain, "COMPLETE").unwrap(),
+            None
+        );
+        assert_eq!(
+            CompletionReport::from_output("no promise", "COMPLETE").unwrap(),
+            None
+        );
+
+        // A broken report still counts as a promise
+        let broken = "<promise>COMPLETE {not json}</promise>";
+        assert!(has_promise(broken, "COMPLETE"));
+        assert!(CompletionReport::from_output(broken, "COMPLETE").is_err());
+
+        // The last block wins
+        let twice = "<promise>COMPLETE {\"summary\": \"first\"}</promise>\n\
+                     <promise>COMPLETE {\"summary\": \"second\"}</promise>";
+        let report = CompletionReport::from_output(twice, "COMPLETE")
+            .unwrap()
+            .unwrap();
+        assert_eq!(report.summary.as_deref(), Some("second"));
+    }
+
+    #[test]
+    fn test_promise_signal() {
+        assert_eq!(
+            PromiseSignal::from_output("<promise>BLOCKED</promise>"),
+            Some(PromiseSignal::Blocked { reason: None })
+        );
+        assert_eq!(
+            PromiseSignal::from_output("<promise>BLOCKED: the API key is missing</promise>"),
+            Some(PromiseSignal::Blocked {
+                reason: Some("the API key is missing".to_string())
+            })
+        );
+        assert_eq!(
+            PromiseSignal::from_output(
+                "Two options.\n<promise>NEEDS_INPUT: Postgres or SQLite?</promise>"
+            ),
+            Some(PromiseSignal::NeedsInput {
+                question: "Postgres or SQLite?".to_string()
+            })
+        );
+
+        // A question is needed, and the word must stand alone
+        assert_eq!(
+            PromiseSignal::from_output("<promise>NEEDS_INPUT</promise>"),
+            None
+        );
+        assert_eq!(
+            PromiseSignal::from_output("<promise>BLOCKEDX</promise>"),
+            None
+        );
+        assert_eq!(
+            PromiseSignal::from_output("<promise>COMPLETE</promise>"),
+            None
+        );
+
+        // The last signal wins
+        let twice = "<promise>BLOCKED</promise> <promise>NEEDS_INPUT: Which port?</promise>";
+        assert!(matches!(
+            PromiseSignal::from_output(twice),
+            Some(PromiseSignal::NeedsInput { .. })
+        ));
+    }
+}
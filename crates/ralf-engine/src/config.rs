@@ -2,13 +2,39 @@
 //!
 //! This module defines the configuration schema for ralf, including
 //! model definitions, verifiers, and runtime settings.
+//!
+//! The schema is versioned. [`Config::load`] migrates older files to
+//! [`CONFIG_VERSION`] (keeping a backup of the original) and validates the
+//! result.
+//!
+//! Settings can also come from a global config at
+//! `~/.config/ralf/config.json`. [`Config::load_layered`] merges it under the
+//! repo's `.ralf/config.json` and records where each setting came from.
 
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::fmt::{self, Write as _};
+use std::path::{Path, PathBuf};
+
+use crate::criterion::Criterion;
+use crate::gc::RetentionConfig;
+use crate::polish::PolishConfig;
+use crate::review::ReviewConfig;
+use crate::safety::SafetyConfig;
+use crate::scheduler::ConcurrencyConfig;
+use crate::state::atomic_write;
+
+/// Current config schema version.
+pub const CONFIG_VERSION: u32 = 2;
 
 /// Main configuration for ralf.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct Config {
+    /// Schema version (files without one are version 1).
+    #[serde(default = "legacy_version")]
+    pub version: u32,
+
     /// Whether initial setup has been completed.
     #[serde(default)]
     pub setup_completed: bool,
@@ -21,6 +47,23 @@ pub struct Config {
     #[serde(default = "default_model_selection")]
     pub model_selection: ModelSelection,
 
+    /// Preferred models for verifying completion criteria, in order. Models
+    /// not listed follow in `model_priority` order. The model that did the
+    /// work is only used to verify it when no other model is available.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub verifier_model_priority: Vec<String>,
+
+    /// Preferred models for each workflow role, e.g. a strong reasoning
+    /// model for drafting specs and a fast coder for implementing them.
+    #[serde(default, skip_serializing_if = "ModelRoles::is_empty")]
+    pub roles: ModelRoles,
+
+    /// Models left out of implementer and verifier selection, e.g. while
+    /// their CLI is misbehaving. A running loop picks changes up at its
+    /// next iteration.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disabled_models: Vec<String>,
+
     /// Required verifiers that must pass for completion.
     #[serde(default = "default_required_verifiers")]
     pub required_verifiers: Vec<String>,
@@ -29,9 +72,10 @@ pub struct Config {
     #[serde(default = "default_completion_promise")]
     pub completion_promise: String,
 
-    /// Whether to create checkpoint commits after each iteration.
-    #[serde(default)]
-    pub checkpoint_commits: bool,
+    /// Whether to commit the working tree after each iteration that passes
+    /// verification.
+    #[serde(default, alias = "checkpoint_commits")]
+    pub commit_per_iteration: bool,
 
     /// Model configurations.
     #[serde(default)]
@@ -40,6 +84,121 @@ pub struct Config {
     /// Verifier configurations.
     #[serde(default)]
     pub verifiers: Vec<VerifierConfig>,
+
+    /// How many previous iterations' failures are fed back into the prompt
+    /// (0 disables feedback).
+    #[serde(default = "default_feedback_window")]
+    pub feedback_window: usize,
+
+    /// Whether prompts include a map of the repository: its file tree,
+    /// recently changed files, and excerpts of key files.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub repo_context: bool,
+
+    /// Whether the model is asked to keep working notes in the run
+    /// directory, shown to the next iteration.
+    #[serde(default = "default_notes")]
+    pub notes: bool,
+
+    /// Text wrapped around `PROMPT.md` on every iteration.
+    #[serde(default, skip_serializing_if = "PromptConfig::is_empty")]
+    pub prompt: PromptConfig,
+
+    /// How many recent iterations stuck detection looks at (0 disables it).
+    #[serde(default = "default_stuck_window")]
+    pub stuck_window: usize,
+
+    /// How many iterations in a row may leave the workspace unchanged
+    /// before the run counts as stuck (0 disables no-op detection).
+    #[serde(default = "default_max_noop_iterations")]
+    pub max_noop_iterations: u32,
+
+    /// Stop a run once its reported cost reaches this many US dollars.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_cost_usd: Option<f64>,
+
+    /// Stop a run once its reported token usage reaches this total.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u64>,
+
+    /// Wall-clock cap in seconds on each iteration, model invocation and
+    /// verification together.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iteration_timeout_seconds: Option<u64>,
+
+    /// How runs are isolated from the user's working directory.
+    #[serde(default)]
+    pub isolation: Isolation,
+
+    /// When an iteration's changes must be reviewed before the run goes on.
+    #[serde(default, skip_serializing_if = "ReviewConfig::is_empty")]
+    pub review: ReviewConfig,
+
+    /// How `/polish` runs differ from implementation runs.
+    #[serde(default, skip_serializing_if = "PolishConfig::is_default")]
+    pub polish: PolishConfig,
+
+    /// Paths a run may not change and commands it may not run.
+    #[serde(default, skip_serializing_if = "SafetyConfig::is_empty")]
+    pub safety: SafetyConfig,
+
+    /// Whether each iteration's changes are scanned for secrets such as API
+    /// keys and private keys. An iteration that adds one isn't committed.
+    #[serde(default = "default_scan_secrets")]
+    pub scan_secrets: bool,
+
+    /// How many model invocations runs going side by side may make at once.
+    #[serde(default, skip_serializing_if = "ConcurrencyConfig::is_default")]
+    pub concurrency: ConcurrencyConfig,
+
+    /// Whether each iteration's model and verifier logs are gzipped once
+    /// the iteration is over.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub compress_logs: bool,
+
+    /// How many old runs and draft snapshots `ralf gc` keeps.
+    #[serde(default, skip_serializing_if = "RetentionConfig::is_default")]
+    pub retention: RetentionConfig,
+
+    /// Model used by `/assess` to review draft specs (defaults to the
+    /// first available model).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assessor_model: Option<String>,
+
+    /// TUI color theme (`mocha`, `latte`, `high_contrast`, or the name of
+    /// a user theme in `~/.config/ralf/themes/`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+
+    /// Keep cooldowns, usage and model stats in ralf's data directory
+    /// (`~/.local/share/ralf/`), shared by every repository, instead of in
+    /// `.ralf/`. A rate limit hit in one repository then holds in all.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub shared_state: bool,
+}
+
+fn legacy_version() -> u32 {
+    1
+}
+
+fn default_feedback_window() -> usize {
+    2
+}
+
+fn default_notes() -> bool {
+    true
+}
+
+fn default_scan_secrets() -> bool {
+    true
+}
+
+fn default_stuck_window() -> usize {
+    3
+}
+
+fn default_max_noop_iterations() -> u32 {
+    3
 }
 
 fn default_model_priority() -> Vec<String> {
@@ -67,6 +226,94 @@ pub enum ModelSelection {
     RoundRobin,
     /// Use first non-cooldown model from priority list.
     Priority,
+    /// Use the non-cooldown model with the best recent health score (see
+    /// [`ModelStats`](crate::model_stats::ModelStats)), breaking ties in
+    /// priority order.
+    Adaptive,
+}
+
+/// A step of the workflow that needs a model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelRole {
+    /// Chats with the user in Spec Studio to draft a spec.
+    Drafter,
+    /// Runs the loop's iterations.
+    Implementer,
+    /// Judges whether completion criteria are met.
+    Verifier,
+    /// Reviews draft specs with `/assess`.
+    Assessor,
+}
+
+impl ModelRole {
+    /// All roles, in workflow order.
+    pub const ALL: [Self; 4] = [
+        Self::Drafter,
+        Self::Implementer,
+        Self::Verifier,
+        Self::Assessor,
+    ];
+
+    /// The role's config key.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Drafter => "drafter",
+            Self::Implementer => "implementer",
+            Self::Verifier => "verifier",
+            Self::Assessor => "assessor",
+        }
+    }
+}
+
+/// Preferred models for each role, best first. An empty list leaves the
+/// choice to the role's usual selection.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelRoles {
+    /// Models for Spec Studio chat.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub drafter: Vec<String>,
+
+    /// Models for iterations. When any of them is available, selection
+    /// only considers these, in this order of priority.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub implementer: Vec<String>,
+
+    /// Models for verifying criteria, ranked ahead of `model_priority`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub verifier: Vec<String>,
+
+    /// Models for `/assess`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assessor: Vec<String>,
+}
+
+impl ModelRoles {
+    /// Whether no role has preferences.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The preferences for `role`.
+    pub fn get(&self, role: ModelRole) -> &[String] {
+        match role {
+            ModelRole::Drafter => &self.drafter,
+            ModelRole::Implementer => &self.implementer,
+            ModelRole::Verifier => &self.verifier,
+            ModelRole::Assessor => &self.assessor,
+        }
+    }
+}
+
+/// Run isolation mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Isolation {
+    /// Run models directly in the repository working tree.
+    #[default]
+    None,
+    /// Run each run in its own git worktree on a `ralf/run-<id>` branch.
+    Worktree,
 }
 
 /// Configuration for a single model.
@@ -89,6 +336,11 @@ pub struct ModelConfig {
     /// Default cooldown duration in seconds when rate limited.
     #[serde(default = "default_cooldown_seconds")]
     pub default_cooldown_seconds: u64,
+
+    /// Context window size in tokens. Prompts are trimmed to fit; when
+    /// unset, a default for known CLIs is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_context_tokens: Option<usize>,
 }
 
 fn default_timeout() -> u64 {
@@ -108,6 +360,58 @@ fn default_cooldown_seconds() -> u64 {
     900
 }
 
+/// Text added before and after `PROMPT.md`, such as house style rules.
+///
+/// Each part is given inline or as a file relative to the repository root,
+/// not both. Both are templates; see
+/// [`render_template`](crate::prompt_builder::render_template).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PromptConfig {
+    /// Text placed before `PROMPT.md`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preamble: Option<String>,
+
+    /// File whose contents are placed before `PROMPT.md`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preamble_path: Option<PathBuf>,
+
+    /// Text placed after `PROMPT.md`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub postamble: Option<String>,
+
+    /// File whose contents are placed after `PROMPT.md`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub postamble_path: Option<PathBuf>,
+}
+
+impl PromptConfig {
+    /// Whether nothing is wrapped around the prompt.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The preamble and postamble templates, reading any files from
+    /// `repo_path`.
+    pub fn load(&self, repo_path: &Path) -> Result<(Option<String>, Option<String>), ConfigError> {
+        let read = |inline: &Option<String>, path: &Option<PathBuf>| match path {
+            Some(path) => {
+                let path = repo_path.join(path);
+                std::fs::read_to_string(&path)
+                    .map(Some)
+                    .map_err(|e| ConfigError::File {
+                        path,
+                        source: Box::new(ConfigError::Io(e)),
+                    })
+            }
+            None => Ok(inline.clone()),
+        };
+        Ok((
+            read(&self.preamble, &self.preamble_path)?,
+            read(&self.postamble, &self.postamble_path)?,
+        ))
+    }
+}
+
 /// Configuration for a verifier.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VerifierConfig {
@@ -124,6 +428,21 @@ pub struct VerifierConfig {
     /// When to run this verifier.
     #[serde(default)]
     pub run_when: VerifierRunWhen,
+
+    /// Verifiers that must pass before this one runs. If any of them fails
+    /// (or is skipped), this verifier is skipped.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+
+    /// If this verifier fails, skip all verifiers after it.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub fail_fast: bool,
+
+    /// Criteria tags this verifier checks. A tagged verifier only runs when
+    /// some criterion in the prompt has one of its tags; untagged verifiers
+    /// always run.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 }
 
 /// When to run a verifier.
@@ -139,9 +458,250 @@ pub enum VerifierRunWhen {
 
 impl Config {
     /// Load configuration from a file.
+    ///
+    /// Files from an older schema are migrated and rewritten in place; the
+    /// original is kept as `config.json.v<N>.bak`. The loaded config is
+    /// validated before it is returned.
     pub fn load(path: &Path) -> Result<Self, ConfigError> {
-        let content = std::fs::read_to_string(path).map_err(ConfigError::Io)?;
-        serde_json::from_str(&content).map_err(ConfigError::Parse)
+        let value = read_document(path)?;
+        let config: Self = serde_json::from_value(value).map_err(ConfigError::Parse)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Load the merged config for a repo.
+    ///
+    /// Starts from the defaults, applies the global config (see
+    /// [`global_config_path`]), then the repo's `.ralf/config.json`. Either
+    /// file may be missing.
+    pub fn load_layered(repo_path: &Path) -> Result<LayeredConfig, ConfigError> {
+        let repo = repo_path.join(".ralf").join("config.json");
+        Self::load_layers(global_config_path().as_deref(), &repo)
+    }
+
+    /// Load and merge explicit global and repo config files.
+    ///
+    /// Settings in the repo config override the global ones. Lists such as
+    /// `models` are replaced, not appended to.
+    pub fn load_layers(global: Option<&Path>, repo: &Path) -> Result<LayeredConfig, ConfigError> {
+        let mut merged = serde_json::Value::Object(serde_json::Map::new());
+        let mut sources = BTreeMap::new();
+
+        let layers = [
+            (global, ConfigSource::Global),
+            (Some(repo), ConfigSource::Repo),
+        ];
+        for (path, source) in layers {
+            let Some(path) = path.filter(|p| p.exists()) else {
+                continue;
+            };
+            let value = read_document(path).map_err(|e| ConfigError::File {
+                path: path.to_path_buf(),
+                source: Box::new(e),
+            })?;
+            if let Some(obj) = value.as_object() {
+                for key in obj.keys() {
+                    sources.insert(key.clone(), source);
+                }
+            }
+            merge_values(&mut merged, value);
+        }
+
+        let config: Self = serde_json::from_value(merged).map_err(ConfigError::Parse)?;
+        config.validate()?;
+
+        // Everything not set by a file comes from the defaults
+        if let Ok(serde_json::Value::Object(obj)) = serde_json::to_value(&config) {
+            for key in obj.keys() {
+                sources.entry(key.clone()).or_insert(ConfigSource::Default);
+            }
+        }
+
+        Ok(LayeredConfig {
+            config,
+            global_path: global.map(Path::to_path_buf),
+            repo_path: repo.to_path_buf(),
+            sources,
+        })
+    }
+
+    /// Check the config for mistakes serde cannot catch.
+    ///
+    /// All problems are reported at once, each with the path of the
+    /// offending field (e.g., `models[1].command_argv`).
+    #[allow(clippy::too_many_lines)]
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut issues = Vec::new();
+        let mut issue = |path: String, message: &str| {
+            issues.push(ConfigIssue {
+                path,
+                message: message.to_string(),
+            });
+        };
+
+        if self.version > CONFIG_VERSION {
+            issue(
+                "version".into(),
+                "is newer than this version of ralf supports",
+            );
+        }
+
+        for (i, model) in self.models.iter().enumerate() {
+            if model.name.trim().is_empty() {
+                issue(format!("models[{i}].name"), "must not be empty");
+            } else if self.models[..i].iter().any(|m| m.name == model.name) {
+                issue(format!("models[{i}].name"), "duplicates an earlier model");
+            }
+            if model.command_argv.is_empty() || model.command_argv[0].trim().is_empty() {
+                issue(format!("models[{i}].command_argv"), "must not be empty");
+            }
+            if model.timeout_seconds == 0 {
+                issue(
+                    format!("models[{i}].timeout_seconds"),
+                    "must be greater than 0",
+                );
+            }
+        }
+
+        // Model names only need to resolve once models are configured
+        if !self.models.is_empty() {
+            for (i, name) in self.model_priority.iter().enumerate() {
+                if self.get_model(name).is_none() {
+                    issue(format!("model_priority[{i}]"), "names an unknown model");
+                }
+            }
+            for (i, name) in self.verifier_model_priority.iter().enumerate() {
+                if self.get_model(name).is_none() {
+                    issue(
+                        format!("verifier_model_priority[{i}]"),
+                        "names an unknown model",
+                    );
+                }
+            }
+            for (i, name) in self.disabled_models.iter().enumerate() {
+                if self.get_model(name).is_none() {
+                    issue(format!("disabled_models[{i}]"), "names an unknown model");
+                }
+            }
+            for role in ModelRole::ALL {
+                for (i, name) in self.roles.get(role).iter().enumerate() {
+                    if self.get_model(name).is_none() {
+                        issue(
+                            format!("roles.{}[{i}]", role.as_str()),
+                            "names an unknown model",
+                        );
+                    }
+                }
+            }
+        }
+
+        for (i, verifier) in self.verifiers.iter().enumerate() {
+            if verifier.name.trim().is_empty() {
+                issue(format!("verifiers[{i}].name"), "must not be empty");
+            }
+            if verifier.command_argv.is_empty() || verifier.command_argv[0].trim().is_empty() {
+                issue(format!("verifiers[{i}].command_argv"), "must not be empty");
+            }
+            if verifier.timeout_seconds == 0 {
+                issue(
+                    format!("verifiers[{i}].timeout_seconds"),
+                    "must be greater than 0",
+                );
+            }
+            for (j, dep) in verifier.depends_on.iter().enumerate() {
+                if *dep == verifier.name {
+                    issue(
+                        format!("verifiers[{i}].depends_on[{j}]"),
+                        "depends on itself",
+                    );
+                } else if self.get_verifier(dep).is_none() {
+                    issue(
+                        format!("verifiers[{i}].depends_on[{j}]"),
+                        "names an unknown verifier",
+                    );
+                }
+            }
+        }
+        let (_, cyclic) = verifier_order(&self.verifiers);
+        for i in cyclic {
+            // Self-dependencies are already reported above
+            if !self.verifiers[i]
+                .depends_on
+                .contains(&self.verifiers[i].name)
+            {
+                issue(
+                    format!("verifiers[{i}].depends_on"),
+                    "is part of a dependency cycle",
+                );
+            }
+        }
+
+        if self
+            .max_cost_usd
+            .is_some_and(|max| !max.is_finite() || max <= 0.0)
+        {
+            issue("max_cost_usd".into(), "must be a positive number");
+        }
+        if self.max_tokens == Some(0) {
+            issue("max_tokens".into(), "must be greater than 0");
+        }
+        if self.stuck_window == 1 {
+            issue("stuck_window".into(), "must be 0 (disabled) or at least 2");
+        }
+        if self.iteration_timeout_seconds == Some(0) {
+            issue("iteration_timeout_seconds".into(), "must be greater than 0");
+        }
+        if self.prompt.preamble.is_some() && self.prompt.preamble_path.is_some() {
+            issue(
+                "prompt.preamble_path".into(),
+                "cannot be set together with prompt.preamble",
+            );
+        }
+        if self.prompt.postamble.is_some() && self.prompt.postamble_path.is_some() {
+            issue(
+                "prompt.postamble_path".into(),
+                "cannot be set together with prompt.postamble",
+            );
+        }
+        if self.review.max_files == Some(0) {
+            issue("review.max_files".into(), "must be greater than 0");
+        }
+        if self.review.max_lines == Some(0) {
+            issue("review.max_lines".into(), "must be greater than 0");
+        }
+        for (i, pattern) in self.review.protected_paths.iter().enumerate() {
+            if pattern.trim().is_empty() {
+                issue(format!("review.protected_paths[{i}]"), "must not be empty");
+            }
+        }
+        for (i, pattern) in self.safety.protected_paths.iter().enumerate() {
+            if pattern.trim().is_empty() {
+                issue(format!("safety.protected_paths[{i}]"), "must not be empty");
+            }
+        }
+        for (i, command) in self.safety.forbidden_commands.iter().enumerate() {
+            if command.trim().is_empty() {
+                issue(
+                    format!("safety.forbidden_commands[{i}]"),
+                    "must not be empty",
+                );
+            }
+        }
+        if self.concurrency.max_invocations == Some(0) {
+            issue(
+                "concurrency.max_invocations".into(),
+                "must be greater than 0",
+            );
+        }
+        if self.concurrency.per_model == 0 {
+            issue("concurrency.per_model".into(), "must be greater than 0");
+        }
+
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Invalid(issues))
+        }
     }
 
     /// Save configuration to a file.
@@ -150,7 +710,7 @@ impl Config {
         if let Some(parent) = path.parent() {
             std::fs::create_dir_all(parent).map_err(ConfigError::Io)?;
         }
-        std::fs::write(path, content).map_err(ConfigError::Io)
+        atomic_write(path, content.as_bytes()).map_err(ConfigError::Io)
     }
 
     /// Create a default configuration with the given detected models.
@@ -173,28 +733,98 @@ impl Config {
         self.models.iter().find(|m| m.name == name)
     }
 
+    /// Preferred models for `role`, best first.
+    ///
+    /// Without a `roles` entry, verifiers fall back to
+    /// `verifier_model_priority` and the assessor to `assessor_model`.
+    pub fn role_models(&self, role: ModelRole) -> Vec<&str> {
+        let preferred = self.roles.get(role);
+        if !preferred.is_empty() {
+            return preferred.iter().map(String::as_str).collect();
+        }
+        match role {
+            ModelRole::Verifier => self
+                .verifier_model_priority
+                .iter()
+                .map(String::as_str)
+                .collect(),
+            ModelRole::Assessor => self.assessor_model.as_deref().into_iter().collect(),
+            ModelRole::Drafter | ModelRole::Implementer => Vec::new(),
+        }
+    }
+
+    /// Whether a model is disabled for selection.
+    pub fn is_disabled(&self, name: &str) -> bool {
+        self.disabled_models.iter().any(|n| n == name)
+    }
+
     /// Get the verifier config by name.
     pub fn get_verifier(&self, name: &str) -> Option<&VerifierConfig> {
         self.verifiers.iter().find(|v| v.name == name)
     }
+
+    /// Verifiers in the order they should run.
+    ///
+    /// Each verifier runs after everything in its `depends_on`; otherwise
+    /// config order is kept. Verifiers caught in a dependency cycle (which
+    /// [`Config::validate`] rejects) come last, in config order.
+    pub fn ordered_verifiers(&self) -> Vec<&VerifierConfig> {
+        let (mut order, cyclic) = verifier_order(&self.verifiers);
+        order.extend(cyclic);
+        order.into_iter().map(|i| &self.verifiers[i]).collect()
+    }
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
+            version: CONFIG_VERSION,
             setup_completed: false,
             model_priority: default_model_priority(),
             model_selection: default_model_selection(),
+            verifier_model_priority: Vec::new(),
+            roles: ModelRoles::default(),
+            disabled_models: Vec::new(),
             required_verifiers: default_required_verifiers(),
             completion_promise: default_completion_promise(),
-            checkpoint_commits: false,
+            commit_per_iteration: false,
             models: Vec::new(),
             verifiers: vec![VerifierConfig::default_tests()],
+            feedback_window: default_feedback_window(),
+            repo_context: false,
+            notes: default_notes(),
+            prompt: PromptConfig::default(),
+            stuck_window: default_stuck_window(),
+            max_noop_iterations: default_max_noop_iterations(),
+            max_cost_usd: None,
+            max_tokens: None,
+            iteration_timeout_seconds: None,
+            isolation: Isolation::default(),
+            review: ReviewConfig::default(),
+            polish: PolishConfig::default(),
+            safety: SafetyConfig::default(),
+            scan_secrets: default_scan_secrets(),
+            concurrency: ConcurrencyConfig::default(),
+            compress_logs: false,
+            retention: RetentionConfig::default(),
+            assessor_model: None,
+            theme: None,
+            shared_state: false,
         }
     }
 }
 
 impl ModelConfig {
+    /// Context window size in tokens: `max_context_tokens` if set,
+    /// otherwise a conservative default for known CLIs.
+    pub fn context_tokens(&self) -> usize {
+        self.max_context_tokens.unwrap_or(match self.name.as_str() {
+            "claude" | "codex" => 200_000,
+            "gemini" => 1_000_000,
+            _ => 32_000,
+        })
+    }
+
     /// Create a default configuration for a known model.
     pub fn default_for(name: &str) -> Self {
         match name {
@@ -204,12 +834,13 @@ impl ModelConfig {
                     "claude".into(),
                     "-p".into(),
                     "--output-format".into(),
-                    "text".into(),
+                    "json".into(),
                     "--dangerously-skip-permissions".into(),
                 ],
                 timeout_seconds: 300,
                 rate_limit_patterns: default_rate_limit_patterns(),
                 default_cooldown_seconds: 900,
+                max_context_tokens: None,
             },
             "codex" => Self {
                 name: "codex".into(),
@@ -222,6 +853,7 @@ impl ModelConfig {
                 timeout_seconds: 300,
                 rate_limit_patterns: default_rate_limit_patterns(),
                 default_cooldown_seconds: 900,
+                max_context_tokens: None,
             },
             "gemini" => Self {
                 name: "gemini".into(),
@@ -229,6 +861,7 @@ impl ModelConfig {
                 timeout_seconds: 300,
                 rate_limit_patterns: default_rate_limit_patterns(),
                 default_cooldown_seconds: 900,
+                max_context_tokens: None,
             },
             _ => Self {
                 name: name.into(),
@@ -236,6 +869,7 @@ impl ModelConfig {
                 timeout_seconds: 300,
                 rate_limit_patterns: default_rate_limit_patterns(),
                 default_cooldown_seconds: 900,
+                max_context_tokens: None,
             },
         }
     }
@@ -249,8 +883,246 @@ impl VerifierConfig {
             command_argv: vec!["cargo".into(), "test".into()],
             timeout_seconds: 300,
             run_when: VerifierRunWhen::OnChange,
+            depends_on: Vec::new(),
+            fail_fast: false,
+            tags: Vec::new(),
         }
     }
+
+    /// Whether this verifier should run for `criteria`: it has no tags, or
+    /// some criterion has one of them.
+    pub fn applies_to(&self, criteria: &[Criterion]) -> bool {
+        self.tags.is_empty()
+            || self
+                .tags
+                .iter()
+                .any(|tag| criteria.iter().any(|c| c.has_tag(tag)))
+    }
+}
+
+/// Topologically order verifiers by `depends_on`, keeping config order
+/// where dependencies allow.
+///
+/// Returns the ordered indices and the indices left over because they are
+/// in (or depend on) a cycle. Unknown dependencies are ignored.
+fn verifier_order(verifiers: &[VerifierConfig]) -> (Vec<usize>, Vec<usize>) {
+    let mut placed = vec![false; verifiers.len()];
+    let mut order = Vec::with_capacity(verifiers.len());
+
+    loop {
+        let ready = (0..verifiers.len()).find(|&i| {
+            !placed[i]
+                && verifiers[i].depends_on.iter().all(|dep| {
+                    verifiers
+                        .iter()
+                        .enumerate()
+                        .all(|(j, v)| v.name != *dep || placed[j])
+                })
+        });
+        let Some(i) = ready else { break };
+        placed[i] = true;
+        order.push(i);
+    }
+
+    let cyclic = (0..verifiers.len()).filter(|&i| !placed[i]).collect();
+    (order, cyclic)
+}
+
+/// Path of the global config: `$XDG_CONFIG_HOME/ralf/config.json`, falling
+/// back to `~/.config/ralf/config.json`.
+pub fn global_config_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("ralf").join("config.json"))
+}
+
+/// Where a setting's value came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigSource {
+    /// Built-in default.
+    Default,
+    /// The global config file.
+    Global,
+    /// The repo's `.ralf/config.json`.
+    Repo,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Default => "default",
+            Self::Global => "global",
+            Self::Repo => "repo",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A config merged from the defaults, the global config and the repo
+/// config, with the provenance of each top-level setting.
+#[derive(Debug, Clone, Serialize)]
+pub struct LayeredConfig {
+    /// The merged config.
+    pub config: Config,
+    /// Global config path that was consulted (may not exist).
+    pub global_path: Option<PathBuf>,
+    /// Repo config path that was consulted (may not exist).
+    pub repo_path: PathBuf,
+    /// Source of each top-level setting, by key.
+    pub sources: BTreeMap<String, ConfigSource>,
+}
+
+impl LayeredConfig {
+    /// Where a top-level setting came from.
+    pub fn source(&self, key: &str) -> ConfigSource {
+        self.sources
+            .get(key)
+            .copied()
+            .unwrap_or(ConfigSource::Default)
+    }
+
+    /// Save one top-level setting where it takes effect: the repo config if
+    /// it already sets the key, otherwise the global config (or the repo
+    /// config when there is no global one). The rest of the file is left
+    /// as it is. Returns the file written.
+    pub fn set(&self, key: &str, value: serde_json::Value) -> Result<PathBuf, ConfigError> {
+        let path = match (self.source(key), &self.global_path) {
+            (ConfigSource::Repo, _) | (_, None) => self.repo_path.clone(),
+            (_, Some(global)) => global.clone(),
+        };
+        set_value(&path, key, value).map_err(|e| ConfigError::File {
+            path: path.clone(),
+            source: Box::new(e),
+        })?;
+        Ok(path)
+    }
+
+    /// Save one top-level setting in the repo config, whatever the global
+    /// config says. For settings that steer this repo's runs.
+    pub fn set_repo(&self, key: &str, value: serde_json::Value) -> Result<(), ConfigError> {
+        set_value(&self.repo_path, key, value).map_err(|e| ConfigError::File {
+            path: self.repo_path.clone(),
+            source: Box::new(e),
+        })
+    }
+}
+
+/// Set one top-level key in the config file at `path`, creating the file
+/// if it doesn't exist.
+fn set_value(path: &Path, key: &str, value: serde_json::Value) -> Result<(), ConfigError> {
+    let mut document = if path.exists() {
+        read_document(path)?
+    } else {
+        serde_json::json!({ "version": CONFIG_VERSION })
+    };
+    if let Some(obj) = document.as_object_mut() {
+        obj.insert(key.to_string(), value);
+    }
+
+    // Don't write a file that would no longer load
+    serde_json::from_value::<Config>(document.clone())
+        .map_err(ConfigError::Parse)?
+        .validate()?;
+
+    let content = serde_json::to_string_pretty(&document).map_err(ConfigError::Serialize)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(ConfigError::Io)?;
+    }
+    atomic_write(path, content.as_bytes()).map_err(ConfigError::Io)
+}
+
+/// Read a config file as JSON, migrating it to [`CONFIG_VERSION`].
+///
+/// A migrated file is rewritten in place and the original kept as
+/// `<file>.v<N>.bak`. Only keys present in the original are written, so
+/// partial files stay partial.
+fn read_document(path: &Path) -> Result<serde_json::Value, ConfigError> {
+    let content = std::fs::read_to_string(path).map_err(ConfigError::Io)?;
+    let mut value: serde_json::Value =
+        serde_json::from_str(&content).map_err(ConfigError::Parse)?;
+
+    if let Some(from) = migrate(&mut value)? {
+        // Don't touch the file unless the migrated document is usable
+        serde_json::from_value::<Config>(value.clone()).map_err(ConfigError::Parse)?;
+
+        let mut backup = path.as_os_str().to_owned();
+        backup.push(format!(".v{from}.bak"));
+        std::fs::write(&backup, &content).map_err(ConfigError::Io)?;
+        let migrated = serde_json::to_string_pretty(&value).map_err(ConfigError::Serialize)?;
+        atomic_write(path, migrated.as_bytes()).map_err(ConfigError::Io)?;
+    }
+    Ok(value)
+}
+
+/// Merge `overlay` into `base`: objects key by key, anything else replaced.
+fn merge_values(base: &mut serde_json::Value, overlay: serde_json::Value) {
+    match (base, overlay) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_values(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Migrate a raw config document to [`CONFIG_VERSION`].
+///
+/// Returns the original version if anything changed.
+fn migrate(value: &mut serde_json::Value) -> Result<Option<u32>, ConfigError> {
+    let Some(obj) = value.as_object_mut() else {
+        // Let deserialization report the type error
+        return Ok(None);
+    };
+    let from = obj
+        .get("version")
+        .and_then(serde_json::Value::as_u64)
+        .map_or(1, |v| u32::try_from(v).unwrap_or(u32::MAX));
+    if from > CONFIG_VERSION {
+        return Err(ConfigError::UnsupportedVersion(from));
+    }
+    if from == CONFIG_VERSION {
+        return Ok(None);
+    }
+
+    // v1 -> v2: `checkpoint_commits` became `commit_per_iteration`
+    if let Some(commit) = obj.remove("checkpoint_commits") {
+        obj.entry("commit_per_iteration").or_insert(commit);
+    }
+
+    obj.insert("version".into(), CONFIG_VERSION.into());
+    Ok(Some(from))
+}
+
+/// A single validation problem, located by field path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    /// Path to the field (e.g., `verifiers[0].command_argv`).
+    pub path: String,
+    /// What is wrong with it.
+    pub message: String,
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.path, self.message)
+    }
+}
+
+fn format_issues(issues: &[ConfigIssue]) -> String {
+    let mut out = String::new();
+    for issue in issues {
+        let _ = write!(out, "\n  - {issue}");
+    }
+    out
 }
 
 /// Errors that can occur when working with configuration.
@@ -267,6 +1139,24 @@ pub enum ConfigError {
     /// Error serializing config to JSON.
     #[error("Serialize error: {0}")]
     Serialize(#[source] serde_json::Error),
+
+    /// Config was written by a newer version of ralf.
+    #[error("Unsupported config version: {0} (max supported: {CONFIG_VERSION})")]
+    UnsupportedVersion(u32),
+
+    /// Config failed validation.
+    #[error("Invalid config:{}", format_issues(.0))]
+    Invalid(Vec<ConfigIssue>),
+
+    /// Error in a specific config file while layering.
+    #[error("{}: {source}", path.display())]
+    File {
+        /// The offending file.
+        path: PathBuf,
+        /// What went wrong.
+        #[source]
+        source: Box<ConfigError>,
+    },
 }
 
 #[cfg(test)]
@@ -289,6 +1179,304 @@ mod tests {
         assert_eq!(parsed.model_priority, config.model_priority);
     }
 
+    #[test]
+    fn test_isolation_config() {
+        assert_eq!(Config::default().isolation, Isolation::None);
+
+        let config: Config = serde_json::from_str(r#"{"isolation": "worktree"}"#).unwrap();
+        assert_eq!(config.isolation, Isolation::Worktree);
+    }
+
+    #[test]
+    fn test_assessor_model_config() {
+        assert!(Config::default().assessor_model.is_none());
+
+        let config: Config = serde_json::from_str(r#"{"assessor_model": "codex"}"#).unwrap();
+        assert_eq!(config.assessor_model.as_deref(), Some("codex"));
+    }
+
+    #[test]
+    fn test_commit_per_iteration_alias() {
+        let config: Config = serde_json::from_str(r#"{"checkpoint_commits": true}"#).unwrap();
+        assert!(config.commit_per_iteration);
+    }
+
+    #[test]
+    fn test_default_config_is_valid() {
+        let config = Config::default();
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_reports_field_paths() {
+        let mut config = Config {
+            models: ["claude", "codex", "gemini"]
+                .iter()
+                .map(|n| ModelConfig::default_for(n))
+                .collect(),
+            ..Config::default()
+        };
+        config.model_priority.push("gpt".into());
+        config.disabled_models.push("gpt".into());
+        config.roles.verifier.push("gpt".into());
+        config.models[0].timeout_seconds = 0;
+        config.verifiers[0].command_argv.clear();
+        config.max_cost_usd = Some(-1.0);
+        config.stuck_window = 1;
+        config.iteration_timeout_seconds = Some(0);
+        config.prompt.preamble = Some("Be brief.".into());
+        config.prompt.preamble_path = Some("PREAMBLE.md".into());
+        config.review.max_lines = Some(0);
+        config.review.protected_paths.push(" ".into());
+        config.safety.forbidden_commands.push(String::new());
+        config.concurrency.per_model = 0;
+
+        let Err(ConfigError::Invalid(issues)) = config.validate() else {
+            panic!("expected validation failure");
+        };
+        let paths: Vec<&str> = issues.iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "models[0].timeout_seconds",
+                "model_priority[3]",
+                "disabled_models[0]",
+                "roles.verifier[0]",
+                "verifiers[0].command_argv",
+                "max_cost_usd",
+                "stuck_window",
+                "iteration_timeout_seconds",
+                "prompt.preamble_path",
+                "review.max_lines",
+                "review.protected_paths[0]",
+                "safety.forbidden_commands[0]",
+                "concurrency.per_model",
+            ]
+        );
+
+        let message = ConfigError::Invalid(issues).to_string();
+        assert!(message.contains("\n  - verifiers[0].command_argv must not be empty"));
+    }
+
+    #[test]
+    fn test_role_models() {
+        let mut config: Config =
+            serde_json::from_str(r#"{"roles": {"drafter": ["claude"], "implementer": ["codex"]}}"#)
+                .unwrap();
+        assert_eq!(config.role_models(ModelRole::Drafter), vec!["claude"]);
+        assert_eq!(config.role_models(ModelRole::Implementer), vec!["codex"]);
+        assert!(config.role_models(ModelRole::Verifier).is_empty());
+
+        // Older settings still apply to their role
+        config.verifier_model_priority = vec!["gemini".into()];
+        config.assessor_model = Some("codex".into());
+        assert_eq!(config.role_models(ModelRole::Verifier), vec!["gemini"]);
+        assert_eq!(config.role_models(ModelRole::Assessor), vec!["codex"]);
+        config.roles.verifier = vec!["claude".into()];
+        assert_eq!(config.role_models(ModelRole::Verifier), vec!["claude"]);
+    }
+
+    #[test]
+    fn test_prompt_config_load() {
+        let temp = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp.path().join("RULES.md"), "Don't touch migrations/.\n").unwrap();
+
+        let config: Config = serde_json::from_str(
+            r#"{"prompt": {"preamble": "Iteration {iteration}.", "postamble_path": "RULES.md"}}"#,
+        )
+        .unwrap();
+        let (preamble, postamble) = config.prompt.load(temp.path()).unwrap();
+        assert_eq!(preamble.as_deref(), Some("Iteration {iteration}."));
+        assert_eq!(postamble.as_deref(), Some("Don't touch migrations/.\n"));
+        assert!(!serde_json::to_string(&Config::default())
+            .unwrap()
+            .contains("\"prompt\""));
+
+        let missing = PromptConfig {
+            preamble_path: Some("MISSING.md".into()),
+            ..PromptConfig::default()
+        };
+        let err = missing.load(temp.path()).unwrap_err();
+        assert!(err.to_string().contains("MISSING.md"));
+    }
+
+    #[test]
+    fn test_load_migrates_v1_with_backup() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("config.json");
+        let v1 = r#"{"setup_completed": true, "checkpoint_commits": true}"#;
+        std::fs::write(&path, v1).unwrap();
+
+        let config = Config::load(&path).unwrap();
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert!(config.commit_per_iteration);
+
+        let backup = temp.path().join("config.json.v1.bak");
+        assert_eq!(std::fs::read_to_string(backup).unwrap(), v1);
+        let rewritten = std::fs::read_to_string(&path).unwrap();
+        assert!(rewritten.contains(r#""version": 2"#));
+        assert!(!rewritten.contains("checkpoint_commits"));
+
+        // Loading again is a no-op
+        let again = Config::load(&path).unwrap();
+        assert_eq!(again.version, CONFIG_VERSION);
+    }
+
+    #[test]
+    fn test_load_rejects_newer_version() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("config.json");
+        std::fs::write(&path, r#"{"version": 99}"#).unwrap();
+
+        assert!(matches!(
+            Config::load(&path),
+            Err(ConfigError::UnsupportedVersion(99))
+        ));
+    }
+
+    #[test]
+    fn test_load_layers_merges_and_records_sources() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let global = temp.path().join("global.json");
+        let repo = temp.path().join("repo.json");
+        std::fs::write(
+            &global,
+            r#"{"version": 2, "model_priority": ["codex"], "theme": "latte", "max_tokens": 1000}"#,
+        )
+        .unwrap();
+        std::fs::write(&repo, r#"{"version": 2, "max_tokens": 5000}"#).unwrap();
+
+        let layered = Config::load_layers(Some(&global), &repo).unwrap();
+        assert_eq!(layered.config.model_priority, vec!["codex"]);
+        assert_eq!(layered.config.theme.as_deref(), Some("latte"));
+        assert_eq!(layered.config.max_tokens, Some(5000));
+
+        assert_eq!(layered.source("model_priority"), ConfigSource::Global);
+        assert_eq!(layered.source("max_tokens"), ConfigSource::Repo);
+        assert_eq!(layered.source("completion_promise"), ConfigSource::Default);
+        assert!(layered.sources.contains_key("isolation"));
+    }
+
+    #[test]
+    fn test_load_layers_missing_files_and_bad_global() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let repo = temp.path().join("missing.json");
+
+        let layered = Config::load_layers(None, &repo).unwrap();
+        assert_eq!(layered.config.completion_promise, "COMPLETE");
+        assert!(layered
+            .sources
+            .values()
+            .all(|s| *s == ConfigSource::Default));
+
+        let global = temp.path().join("global.json");
+        std::fs::write(&global, "{not json").unwrap();
+        let err = Config::load_layers(Some(&global), &repo).unwrap_err();
+        assert!(err.to_string().starts_with(&global.display().to_string()));
+    }
+
+    #[test]
+    fn test_layered_set_writes_where_setting_takes_effect() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let global = temp.path().join("global").join("config.json");
+        let repo = temp.path().join("repo.json");
+        std::fs::write(&repo, r#"{"version": 2, "max_tokens": 5000}"#).unwrap();
+
+        // Unset keys go to the global config, created if missing
+        let layered = Config::load_layers(Some(&global), &repo).unwrap();
+        assert_eq!(layered.set("theme", "latte".into()).unwrap(), global);
+        let layered = Config::load_layers(Some(&global), &repo).unwrap();
+        assert_eq!(layered.config.theme.as_deref(), Some("latte"));
+        assert_eq!(layered.source("theme"), ConfigSource::Global);
+
+        // Keys the repo sets stay in the repo config, alongside its others
+        assert_eq!(layered.set("max_tokens", 100.into()).unwrap(), repo);
+        let layered = Config::load_layers(Some(&global), &repo).unwrap();
+        assert_eq!(layered.config.max_tokens, Some(100));
+
+        // Values that wouldn't load aren't written
+        assert!(layered.set("max_tokens", "lots".into()).is_err());
+        assert_eq!(Config::load(&repo).unwrap().max_tokens, Some(100));
+
+        // Without a global config, settings go to the repo
+        let layered = Config::load_layers(None, &repo).unwrap();
+        assert_eq!(layered.set("theme", "mocha".into()).unwrap(), repo);
+
+        // Repo settings stay in the repo even with a global config
+        let layered = Config::load_layers(Some(&global), &repo).unwrap();
+        layered
+            .set_repo("max_cost_usd", serde_json::json!(2.5))
+            .unwrap();
+        assert_eq!(Config::load(&repo).unwrap().max_cost_usd, Some(2.5));
+        assert_eq!(Config::load(&global).unwrap().max_cost_usd, None);
+    }
+
+    fn verifier(name: &str, depends_on: &[&str]) -> VerifierConfig {
+        VerifierConfig {
+            name: name.into(),
+            depends_on: depends_on.iter().map(|d| (*d).to_string()).collect(),
+            ..VerifierConfig::default_tests()
+        }
+    }
+
+    #[test]
+    fn test_verifier_applies_to_tagged_criteria() {
+        let criteria = vec![Criterion::parse(0, "Tests pass #Tests")];
+        let mut docs = verifier("docs", &[]);
+        assert!(docs.applies_to(&criteria));
+        docs.tags = vec!["docs".into()];
+        assert!(!docs.applies_to(&criteria));
+        docs.tags.push("tests".into());
+        assert!(docs.applies_to(&criteria));
+    }
+
+    #[test]
+    fn test_ordered_verifiers_respects_dependencies() {
+        let config = Config {
+            verifiers: vec![
+                verifier("test", &["check"]),
+                verifier("fmt", &[]),
+                verifier("check", &[]),
+                verifier("clippy", &["check"]),
+            ],
+            required_verifiers: vec![],
+            ..Config::default()
+        };
+        let names: Vec<&str> = config
+            .ordered_verifiers()
+            .iter()
+            .map(|v| v.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["fmt", "check", "test", "clippy"]);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_verifier_dependencies() {
+        let config = Config {
+            verifiers: vec![
+                verifier("a", &["b"]),
+                verifier("b", &["a"]),
+                verifier("c", &["c", "missing"]),
+            ],
+            ..Config::default()
+        };
+        let Err(ConfigError::Invalid(issues)) = config.validate() else {
+            panic!("expected validation failure");
+        };
+        let paths: Vec<&str> = issues.iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "verifiers[2].depends_on[0]",
+                "verifiers[2].depends_on[1]",
+                "verifiers[0].depends_on",
+                "verifiers[1].depends_on",
+            ]
+        );
+    }
+
     #[test]
     fn test_model_config_defaults() {
         let claude = ModelConfig::default_for("claude");
@@ -0,0 +1,141 @@
+//! Context window budgeting for prompts.
+//!
+//! Prompts are assembled from parts of very different sizes: the spec, a git
+//! diff, verifier output, feedback from earlier iterations. A fixed character
+//! cap either wastes a large context window or overflows a small one, so
+//! prompt builders draw from a [`ContextBudget`] sized to the target model
+//! instead.
+//!
+//! Token counts are estimates (about four characters per token); budgets
+//! keep a margin for the model's reply to absorb the error.
+
+use crate::config::ModelConfig;
+
+/// Rough number of characters per token for English text and code.
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Fraction of the context window (in percent) available for the prompt;
+/// the rest is left for the model's system prompt and reply.
+const PROMPT_SHARE_PERCENT: usize = 75;
+
+/// Marker appended to truncated text.
+const TRUNCATED: &str = "\n...[truncated]";
+
+/// Estimate the number of tokens in `text`.
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Truncate `text` to roughly `max_tokens` tokens, keeping the start and
+/// marking the cut.
+pub fn truncate_to_tokens(text: &str, max_tokens: usize) -> String {
+    if estimate_tokens(text) <= max_tokens {
+        return text.to_string();
+    }
+    let marker_chars = TRUNCATED.chars().count();
+    let keep = (max_tokens * CHARS_PER_TOKEN).saturating_sub(marker_chars);
+    let mut truncated: String = text.chars().take(keep).collect();
+    truncated.push_str(TRUNCATED);
+    truncated
+}
+
+/// A token allowance that prompt parts are drawn from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContextBudget {
+    remaining: usize,
+}
+
+impl ContextBudget {
+    /// A budget of `tokens`.
+    pub fn new(tokens: usize) -> Self {
+        Self { remaining: tokens }
+    }
+
+    /// A budget that never truncates.
+    pub fn unlimited() -> Self {
+        Self::new(usize::MAX)
+    }
+
+    /// The prompt budget for `model`: its context window minus room for the
+    /// reply.
+    pub fn for_model(model: &ModelConfig) -> Self {
+        Self::new(model.context_tokens() / 100 * PROMPT_SHARE_PERCENT)
+    }
+
+    /// Tokens left.
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    /// Account for text that must be included in full.
+    pub fn reserve(&mut self, text: &str) {
+        self.remaining = self.remaining.saturating_sub(estimate_tokens(text));
+    }
+
+    /// Take up to `max_tokens` (and no more than what's left) of `text`,
+    /// truncating it if needed. Returns an empty string when the budget is
+    /// exhausted.
+    pub fn take(&mut self, text: &str, max_tokens: usize) -> String {
+        let allowance = max_tokens.min(self.remaining);
+        if allowance == 0 {
+            return String::new();
+        }
+        let taken = truncate_to_tokens(text, allowance);
+        self.reserve(&taken);
+        taken
+    }
+
+    /// Whether `text` fits in what's left.
+    pub fn fits(&self, text: &str) -> bool {
+        estimate_tokens(text) <= self.remaining
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_and_truncate() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcd"), 1);
+        assert_eq!(estimate_tokens("abcde"), 2);
+
+        assert_eq!(truncate_to_tokens("short", 10), "short");
+        let long = "x".repeat(1000);
+        let truncated = truncate_to_tokens(&long, 50);
+        assert!(truncated.ends_with("[truncated]"));
+        assert!(estimate_tokens(&truncated) <= 50);
+    }
+
+    #[test]
+    fn test_budget_take_and_reserve() {
+        let mut budget = ContextBudget::new(100);
+        budget.reserve(&"a".repeat(200));
+        assert_eq!(budget.remaining(), 50);
+
+        // Capped by max_tokens
+        let part = budget.take(&"b".repeat(1000), 20);
+        assert!(estimate_tokens(&part) <= 20);
+        assert!(budget.remaining() >= 30);
+
+        // Capped by what's left
+        let part = budget.take(&"c".repeat(1000), usize::MAX);
+        assert!(!part.is_empty());
+        assert_eq!(budget.remaining(), 0);
+        assert!(budget.take("more", 10).is_empty());
+        assert!(!budget.fits("x"));
+    }
+
+    #[test]
+    fn test_budget_for_model() {
+        let mut model = ModelConfig::default_for("claude");
+        assert_eq!(
+            ContextBudget::for_model(&model).remaining(),
+            200_000 / 100 * PROMPT_SHARE_PERCENT
+        );
+
+        model.max_context_tokens = Some(8_000);
+        assert_eq!(ContextBudget::for_model(&model).remaining(), 6_000);
+    }
+}
@@ -0,0 +1,154 @@
+//! Completion criteria parsed from `PROMPT.md`.
+//!
+//! Each bullet in a criteria section becomes a [`Criterion`]. Trailing
+//! annotations refine it without changing its text:
+//!
+//! ```markdown
+//! - All tests pass #tests
+//! - The README mentions the new flag #docs (optional)
+//! ```
+//!
+//! `#tag`s group criteria so verifiers can be limited to the criteria they
+//! check, and `(optional)` criteria are verified and reported but don't
+//! block completion.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Suffix marking a criterion as optional (matched case-insensitively).
+const OPTIONAL_SUFFIX: &str = "(optional)";
+
+/// A completion criterion.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Criterion {
+    /// Stable ID by position in the prompt ("c1", "c2", ...).
+    pub id: String,
+    /// The criterion, without its annotations.
+    pub text: String,
+    /// Tags from `#tag` annotations, lowercased.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Whether the run can only complete once this criterion passes.
+    #[serde(default = "default_required")]
+    pub required: bool,
+}
+
+pub(crate) fn default_required() -> bool {
+    true
+}
+
+impl Criterion {
+    /// Parse the criterion at 0-based `index` from its bullet text, taking
+    /// `#tag` and `(optional)` annotations off the end.
+    pub fn parse(index: usize, line: &str) -> Self {
+        let mut text = line.trim();
+        let mut tags = Vec::new();
+        let mut required = true;
+
+        loop {
+            if let Some(rest) = strip_suffix_ignore_case(text, OPTIONAL_SUFFIX) {
+                required = false;
+                text = rest.trim_end();
+                continue;
+            }
+            let (rest, last) = text.rsplit_once(char::is_whitespace).unwrap_or(("", text));
+            match last.strip_prefix('#').filter(|tag| is_tag(tag)) {
+                Some(tag) if !rest.trim().is_empty() => {
+                    tags.insert(0, tag.to_lowercase());
+                    text = rest.trim_end();
+                }
+                _ => break,
+            }
+        }
+        tags.dedup();
+
+        Self {
+            id: format!("c{}", index + 1),
+            text: text.to_string(),
+            tags,
+            required,
+        }
+    }
+
+    /// A required criterion with no tags, for callers that only have text.
+    pub fn new(index: usize, text: impl Into<String>) -> Self {
+        Self {
+            id: format!("c{}", index + 1),
+            text: text.into(),
+            tags: Vec::new(),
+            required: true,
+        }
+    }
+
+    /// Whether the criterion is tagged `tag` (case-insensitive).
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+}
+
+impl fmt::Display for Criterion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+/// Whether `tag` is a valid tag name: a letter, then letters, digits, `-`
+/// or `_`. Keeps issue references like `#42` in the text.
+fn is_tag(tag: &str) -> bool {
+    let mut chars = tag.chars();
+    chars.next().is_some_and(char::is_alphabetic)
+        && chars.all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+}
+
+fn strip_suffix_ignore_case<'a>(text: &'a str, suffix: &str) -> Option<&'a str> {
+    let split = text.len().checked_sub(suffix.len())?;
+    (text.is_char_boundary(split) && text[split..].eq_ignore_ascii_case(suffix))
+        .then(|| &text[..split])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_annotations() {
+        let criterion = Criterion::parse(0, "All tests pass");
+        assert_eq!(criterion.id, "c1");
+        assert_eq!(criterion.text, "All tests pass");
+        assert!(criterion.tags.is_empty());
+        assert!(criterion.required);
+
+        let criterion = Criterion::parse(2, "README mentions `--fast` #docs #Release (Optional)");
+        assert_eq!(criterion.id, "c3");
+        assert_eq!(criterion.text, "README mentions `--fast`");
+        assert_eq!(criterion.tags, vec!["docs", "release"]);
+        assert!(!criterion.required);
+        assert!(criterion.has_tag("DOCS"));
+
+        // Annotations in either order
+        let criterion = Criterion::parse(0, "Lint is clean (optional) #lint");
+        assert_eq!(criterion.text, "Lint is clean");
+        assert_eq!(criterion.tags, vec!["lint"]);
+        assert!(!criterion.required);
+    }
+
+    #[test]
+    fn test_parse_keeps_non_tags() {
+        // Issue references and a lone tag stay in the text
+        assert_eq!(Criterion::parse(0, "Fix bug #42").text, "Fix bug #42");
+        assert_eq!(Criterion::parse(0, "#tests").text, "#tests");
+        assert_eq!(
+            Criterion::parse(0, "Use C# for the #core module").text,
+            "Use C# for the #core module"
+        );
+        assert!(Criterion::parse(0, "Make (optional) args work").required);
+    }
+
+    #[test]
+    fn test_serde_defaults() {
+        let criterion: Criterion = serde_json::from_str(r#"{"id":"c1","text":"Builds"}"#).unwrap();
+        assert_eq!(criterion, Criterion::new(0, "Builds"));
+        assert_eq!(criterion.to_string(), "Builds");
+    }
+}
@@ -0,0 +1,195 @@
+//! Where ralf keeps its files.
+//!
+//! Everything lives in the repository's `.ralf/` directory by default.
+//! [`RalfDirs`] names each file and directory in one place, so the runner,
+//! CLI and TUI agree on the layout, and so the state that isn't tied to one
+//! repository (cooldowns, usage and model stats) can be kept elsewhere:
+//! with `shared_state` set in the config, it goes to ralf's data directory
+//! and every repository sees the same cooldowns.
+
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+
+/// Name of ralf's directory inside a repository.
+pub const RALF_DIR: &str = ".ralf";
+
+/// Run state of the latest run.
+pub const STATE_FILE: &str = "state.json";
+
+/// Model cooldowns.
+pub const COOLDOWNS_FILE: &str = "cooldowns.json";
+
+/// Token and cost ledger.
+pub const USAGE_FILE: &str = "usage.json";
+
+/// One directory per run, named by run ID.
+pub const RUNS_DIR: &str = "runs";
+
+/// Changelog entries of every iteration.
+pub const CHANGELOG_DIR: &str = "changelog";
+
+/// Spec Studio draft snapshots.
+pub const DRAFTS_DIR: &str = "drafts";
+
+/// Persisted threads.
+pub const THREADS_DIR: &str = "threads";
+
+/// One git worktree per isolated run, named by run ID.
+pub const WORKTREES_DIR: &str = "worktrees";
+
+/// Address and token of a `ralf serve` process.
+pub const SERVE_FILE: &str = "serve.json";
+
+/// The files and directories ralf keeps for one repository.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RalfDirs {
+    root: PathBuf,
+    shared: Option<PathBuf>,
+}
+
+impl RalfDirs {
+    /// Everything under `root` (a `.ralf` directory or a stand-in for one).
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            shared: None,
+        }
+    }
+
+    /// Everything in `.ralf/` of the repository at `repo_path`.
+    pub fn for_repo(repo_path: &Path) -> Self {
+        Self::new(repo_path.join(RALF_DIR))
+    }
+
+    /// Lay files out as `config` asks: shared state goes to [`data_dir`]
+    /// when `shared_state` is set.
+    #[must_use]
+    pub fn configured(self, config: &Config) -> Self {
+        match data_dir().filter(|_| config.shared_state) {
+            Some(dir) => self.with_shared_dir(dir),
+            None => self,
+        }
+    }
+
+    /// Keep cooldowns, usage and model stats in `dir` instead of the root.
+    #[must_use]
+    pub fn with_shared_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.shared = Some(dir.into());
+        self
+    }
+
+    /// The repository's own directory.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Where state shared between repositories lives (the root unless
+    /// moved with [`with_shared_dir`](Self::with_shared_dir)).
+    pub fn shared(&self) -> &Path {
+        self.shared.as_deref().unwrap_or(&self.root)
+    }
+
+    /// `state.json`.
+    pub fn state(&self) -> PathBuf {
+        self.root.join(STATE_FILE)
+    }
+
+    /// `cooldowns.json`.
+    pub fn cooldowns(&self) -> PathBuf {
+        self.shared().join(COOLDOWNS_FILE)
+    }
+
+    /// `usage.json`.
+    pub fn usage(&self) -> PathBuf {
+        self.shared().join(USAGE_FILE)
+    }
+
+    /// `model-stats.json`.
+    pub fn model_stats(&self) -> PathBuf {
+        crate::model_stats::ModelStats::path(self.shared())
+    }
+
+    /// The directory holding every run's directory.
+    pub fn runs(&self) -> PathBuf {
+        self.root.join(RUNS_DIR)
+    }
+
+    /// A run's directory.
+    pub fn run(&self, run_id: &str) -> PathBuf {
+        self.runs().join(run_id)
+    }
+
+    /// The changelog directory.
+    pub fn changelog(&self) -> PathBuf {
+        self.root.join(CHANGELOG_DIR)
+    }
+
+    /// The draft snapshot directory.
+    pub fn drafts(&self) -> PathBuf {
+        self.root.join(DRAFTS_DIR)
+    }
+
+    /// The thread directory.
+    pub fn threads(&self) -> PathBuf {
+        self.root.join(THREADS_DIR)
+    }
+
+    /// The directory holding isolated runs' worktrees.
+    pub fn worktrees(&self) -> PathBuf {
+        self.root.join(WORKTREES_DIR)
+    }
+
+    /// `serve.json`.
+    pub fn serve(&self) -> PathBuf {
+        self.root.join(SERVE_FILE)
+    }
+}
+
+/// ralf's data directory for state shared between repositories:
+/// `$XDG_DATA_HOME/ralf`, falling back to `~/.local/share/ralf`.
+pub fn data_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_DATA_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share"))
+        })?;
+    Some(base.join("ralf"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repo_layout() {
+        let dirs = RalfDirs::for_repo(Path::new("/repo"));
+        assert_eq!(dirs.root(), Path::new("/repo/.ralf"));
+        assert_eq!(dirs.state(), PathBuf::from("/repo/.ralf/state.json"));
+        assert_eq!(
+            dirs.cooldowns(),
+            PathBuf::from("/repo/.ralf/cooldowns.json")
+        );
+        assert_eq!(dirs.run("run-1"), PathBuf::from("/repo/.ralf/runs/run-1"));
+        assert_eq!(dirs.threads(), PathBuf::from("/repo/.ralf/threads"));
+        assert_eq!(
+            RalfDirs::for_repo(Path::new("/repo")).configured(&Config::default()),
+            dirs
+        );
+    }
+
+    #[test]
+    fn test_shared_layout() {
+        let dirs = RalfDirs::for_repo(Path::new("/repo")).with_shared_dir("/data/ralf");
+        assert_eq!(dirs.cooldowns(), PathBuf::from("/data/ralf/cooldowns.json"));
+        assert_eq!(dirs.usage(), PathBuf::from("/data/ralf/usage.json"));
+        assert_eq!(
+            dirs.model_stats(),
+            PathBuf::from("/data/ralf/model-stats.json")
+        );
+        // Runs stay with the repository
+        assert_eq!(dirs.state(), PathBuf::from("/repo/.ralf/state.json"));
+        assert_eq!(dirs.changelog(), PathBuf::from("/repo/.ralf/changelog"));
+    }
+}
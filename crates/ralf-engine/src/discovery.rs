@@ -2,13 +2,28 @@
 //!
 //! This module handles detecting and probing model CLIs on the system.
 
+use crate::adapters::{adapter_for, mock_models_enabled, MockResponse, MOCK_MODEL};
+use crate::platform;
 use serde::{Deserialize, Serialize};
-use std::process::Command;
-use std::time::Duration;
+use std::path::Path;
+use std::process::Output;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::task::JoinSet;
 
 /// Known model CLI names.
 pub const KNOWN_MODELS: &[&str] = &["claude", "codex", "gemini"];
 
+/// Models to discover: [`KNOWN_MODELS`], plus the mock model when
+/// `RALF_MOCK_MODELS` is set.
+pub fn known_models() -> Vec<&'static str> {
+    let mut models = KNOWN_MODELS.to_vec();
+    if mock_models_enabled() {
+        models.push(MOCK_MODEL);
+    }
+    models
+}
+
 /// Result of model discovery.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DiscoveryResult {
@@ -36,28 +51,58 @@ pub struct ModelInfo {
 
     /// Any issues detected.
     pub issues: Vec<String>,
+
+    /// Interactive command that signs in to the model, if it has one.
+    #[serde(default)]
+    pub auth_command: Option<Vec<String>>,
+}
+
+impl ModelInfo {
+    /// How to sign in, as a shell command line for messages.
+    pub fn auth_hint(&self) -> String {
+        self.auth_command.as_ref().map_or_else(
+            || format!("{} auth login", self.name),
+            |argv| argv.join(" "),
+        )
+    }
 }
 
 /// Discover all known models on the system.
 pub fn discover_models() -> DiscoveryResult {
-    let models = KNOWN_MODELS
-        .iter()
-        .map(|name| discover_model(name))
-        .collect();
+    let models = known_models().into_iter().map(discover_model).collect();
 
     DiscoveryResult { models }
 }
 
+/// Discover all known models concurrently without blocking the runtime.
+///
+/// Models are returned in the same order as [`discover_models`].
+pub async fn discover_models_async() -> DiscoveryResult {
+    let names = known_models();
+    let mut tasks = JoinSet::new();
+    for (index, name) in names.iter().copied().enumerate() {
+        tasks.spawn(async move { (index, discover_model_async(name).await) });
+    }
+
+    let mut models = Vec::with_capacity(names.len());
+    while let Some(joined) = tasks.join_next().await {
+        if let Ok(model) = joined {
+            models.push(model);
+        }
+    }
+    models.sort_by_key(|(index, _)| *index);
+
+    DiscoveryResult {
+        models: models.into_iter().map(|(_, info)| info).collect(),
+    }
+}
+
 /// Discover a single model by name.
 pub fn discover_model(name: &str) -> ModelInfo {
-    let mut info = ModelInfo {
-        name: name.to_string(),
-        found: false,
-        callable: false,
-        path: None,
-        version: None,
-        issues: Vec::new(),
-    };
+    if name == MOCK_MODEL {
+        return mock_info();
+    }
+    let mut info = undiscovered(name);
 
     // Try to find the binary on PATH
     match which::which(name) {
@@ -66,26 +111,8 @@ pub fn discover_model(name: &str) -> ModelInfo {
             info.path = Some(path.display().to_string());
 
             // Try to call with --help to verify it's callable
-            match Command::new(name).arg("--help").output() {
-                Ok(output) => {
-                    if output.status.success() {
-                        info.callable = true;
-                        // Try to extract version from output
-                        let stdout = String::from_utf8_lossy(&output.stdout);
-                        if let Some(version) = extract_version(&stdout) {
-                            info.version = Some(version);
-                        }
-                    } else {
-                        info.issues.push(format!(
-                            "--help exited with code {}",
-                            output.status.code().unwrap_or(-1)
-                        ));
-                    }
-                }
-                Err(e) => {
-                    info.issues.push(format!("Failed to run --help: {e}"));
-                }
-            }
+            let output = platform::std_command(name, &["--help".into()]).output();
+            record_help_output(&mut info, output);
         }
         Err(_) => {
             info.issues.push(format!("{name} not found on PATH"));
@@ -95,6 +122,82 @@ pub fn discover_model(name: &str) -> ModelInfo {
     info
 }
 
+/// Discover a single model by name without blocking the runtime.
+///
+/// Dropping the future kills the `--help` process if it is still running.
+pub async fn discover_model_async(name: &str) -> ModelInfo {
+    if name == MOCK_MODEL {
+        return mock_info();
+    }
+    let mut info = undiscovered(name);
+
+    // PATH lookup is a few stat calls; only the --help run needs to be async
+    match which::which(name) {
+        Ok(path) => {
+            info.found = true;
+            info.path = Some(path.display().to_string());
+
+            let output = platform::tokio_command(name, &["--help".into()])
+                .kill_on_drop(true)
+                .output()
+                .await;
+            record_help_output(&mut info, output);
+        }
+        Err(_) => {
+            info.issues.push(format!("{name} not found on PATH"));
+        }
+    }
+
+    info
+}
+
+/// Model info before anything has been checked.
+fn undiscovered(name: &str) -> ModelInfo {
+    ModelInfo {
+        name: name.to_string(),
+        found: false,
+        callable: false,
+        path: None,
+        version: None,
+        issues: Vec::new(),
+        auth_command: adapter_for(name).auth_command(),
+    }
+}
+
+/// The mock model runs in-process, so it is always available.
+fn mock_info() -> ModelInfo {
+    ModelInfo {
+        found: true,
+        callable: true,
+        version: Some("scripted".into()),
+        ..undiscovered(MOCK_MODEL)
+    }
+}
+
+/// Record the outcome of running `<model> --help`.
+fn record_help_output(info: &mut ModelInfo, output: std::io::Result<Output>) {
+    match output {
+        Ok(output) => {
+            if output.status.success() {
+                info.callable = true;
+                // Try to extract version from output
+                let stdout = String::from_utf8_lossy(&output.stdout);
+                if let Some(version) = extract_version(&stdout) {
+                    info.version = Some(version);
+                }
+            } else {
+                info.issues.push(format!(
+                    "--help exited with code {}",
+                    output.status.code().unwrap_or(-1)
+                ));
+            }
+        }
+        Err(e) => {
+            info.issues.push(format!("Failed to run --help: {e}"));
+        }
+    }
+}
+
 /// Result of probing a model.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProbeResult {
@@ -133,12 +236,49 @@ pub fn probe_model(name: &str, timeout: Duration) -> ProbeResult {
     probe_model_with_info(&info, timeout)
 }
 
+/// Probe a model without blocking the runtime.
+///
+/// Async counterpart of [`probe_model`]. Dropping the future (e.g., aborting
+/// its task) cancels the probe and kills the model process.
+pub async fn probe_model_async(name: &str, timeout: Duration) -> ProbeResult {
+    let info = discover_model_async(name).await;
+    probe_model_with_info_async(&info, timeout).await
+}
+
 /// Probe a model with a simple test prompt, using pre-discovered model info.
 ///
 /// This avoids redundant discovery when you already have the `ModelInfo`.
 pub fn probe_model_with_info(info: &ModelInfo, timeout: Duration) -> ProbeResult {
-    let mut result = ProbeResult {
-        name: info.name.clone(),
+    if let Err(result) = check_probeable(info) {
+        return result;
+    }
+
+    // Try a simple probe with timeout
+    let start = Instant::now();
+
+    // Use a simple echo-like prompt that should return quickly
+    let output = run_probe_command(&info.name, timeout);
+    interpret_probe(info, start, output)
+}
+
+/// Probe a model using pre-discovered model info without blocking the runtime.
+///
+/// Async counterpart of [`probe_model_with_info`]. Dropping the future
+/// cancels the probe and kills the model process.
+pub async fn probe_model_with_info_async(info: &ModelInfo, timeout: Duration) -> ProbeResult {
+    if let Err(result) = check_probeable(info) {
+        return result;
+    }
+
+    let start = Instant::now();
+    let output = run_probe_command_async(&info.name, timeout).await;
+    interpret_probe(info, start, output)
+}
+
+/// An empty, unsuccessful probe result for `name`.
+fn failed_probe(name: &str) -> ProbeResult {
+    ProbeResult {
+        name: name.to_string(),
         success: false,
         response_time_ms: None,
         needs_auth: false,
@@ -146,7 +286,12 @@ pub fn probe_model_with_info(info: &ModelInfo, timeout: Duration) -> ProbeResult
         rate_limit_reset: None,
         issues: Vec::new(),
         suggestions: Vec::new(),
-    };
+    }
+}
+
+/// Check that discovery found a callable model, or explain why it can't be probed.
+fn check_probeable(info: &ModelInfo) -> Result<(), ProbeResult> {
+    let mut result = failed_probe(&info.name);
 
     if !info.found {
         result
@@ -155,19 +300,24 @@ pub fn probe_model_with_info(info: &ModelInfo, timeout: Duration) -> ProbeResult
         result
             .suggestions
             .push(format!("Install {} CLI and add to PATH", info.name));
-        return result;
+        return Err(result);
     }
 
     if !info.callable {
         result.issues.extend(info.issues.clone());
-        return result;
+        return Err(result);
     }
 
-    // Try a simple probe with timeout
-    let start = std::time::Instant::now();
+    Ok(())
+}
 
-    // Use a simple echo-like prompt that should return quickly
-    let probe_result = run_probe_command(&info.name, timeout);
+/// Turn the output of a probe command into a [`ProbeResult`].
+fn interpret_probe(
+    info: &ModelInfo,
+    start: Instant,
+    probe_result: Result<ProbeOutput, std::io::Error>,
+) -> ProbeResult {
+    let mut result = failed_probe(&info.name);
 
     match probe_result {
         Ok(output) => {
@@ -179,8 +329,8 @@ pub fn probe_model_with_info(info: &ModelInfo, timeout: Duration) -> ProbeResult
                 result.success = true;
             } else {
                 // Check for specific error conditions
+                let adapter = adapter_for(&info.name);
                 let combined = format!("{}\n{}", output.stdout, output.stderr);
-                let combined_lower = combined.to_lowercase();
 
                 // Extract actual error line for cleaner messages
                 let error_line = combined
@@ -192,29 +342,26 @@ pub fn probe_model_with_info(info: &ModelInfo, timeout: Duration) -> ProbeResult
                             .trim()
                     });
 
-                if combined_lower.contains("limit")
-                    || combined_lower.contains("quota")
-                    || combined.contains("429")
-                {
+                if adapter.is_rate_limited(&combined, &probe_rate_limit_patterns()) {
                     // Rate limit / usage limit reached
                     result.rate_limited = true;
                     result.issues.push("Rate limited".into());
 
                     // Try to extract reset time from error (e.g., "try again at Jan 12th, 2026 9:08 PM")
-                    if let Some(reset_time) = extract_reset_time(&combined) {
+                    if let Some(reset_time) = adapter.rate_limit_reset(&combined) {
                         result.rate_limit_reset = Some(reset_time);
                     } else {
                         result
                             .suggestions
                             .push("Wait for limit to reset or upgrade plan".into());
                     }
-                } else if is_auth_error(&combined_lower) {
+                } else if adapter.needs_auth(&combined) {
                     // Auth required
                     result.needs_auth = true;
                     result.issues.push("Model requires authentication".into());
                     result.suggestions.push(format!(
-                        "Run `{} auth login` or configure credentials",
-                        info.name
+                        "Run `{}` or configure credentials",
+                        info.auth_hint()
                     ));
                 } else {
                     // Generic failure - use error line if available
@@ -262,47 +409,27 @@ struct ProbeOutput {
     stderr: String,
 }
 
+/// Explicit prompt to prevent agentic models from reading the codebase.
+const PROBE_PROMPT: &str = "Ping. Just say 'ok' - do not read files or use tools.";
+
 /// Run a probe command for a model.
 fn run_probe_command(name: &str, timeout: Duration) -> Result<ProbeOutput, std::io::Error> {
     use std::io::{Read, Write};
-    use std::process::{Command, Stdio};
-
-    // Explicit prompt to prevent agentic models from reading codebase
-    let probe_prompt = "Ping. Just say 'ok' - do not read files or use tools.";
-
-    // Build command based on model
-    // Some CLIs take prompt via stdin, others via -p argument
-    let (mut cmd, uses_stdin) = match name {
-        "claude" => {
-            let mut c = Command::new("claude");
-            c.args(["-p", "--output-format", "text"]);
-            (c, true)
-        }
-        "codex" => {
-            let mut c = Command::new("codex");
-            c.args(["exec", "-"]);
-            (c, true)
-        }
-        "gemini" => {
-            // Gemini CLI takes prompt as argument to -p, not via stdin
-            let mut c = Command::new("gemini");
-            c.args(["-p", probe_prompt]);
-            (c, false)
-        }
-        _ => (Command::new(name), true),
-    };
 
-    cmd.stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped());
+    // The adapter knows whether the CLI takes the prompt via stdin or an argument
+    let adapter = adapter_for(name);
+    if let Some(response) = adapter.mock_response(Path::new("."), None) {
+        return Ok(mock_probe_output(response));
+    }
+    let spec = adapter.probe_command(PROBE_PROMPT);
+    let mut cmd = spec.to_std_command();
 
     let mut child = cmd.spawn()?;
 
     // Send prompt via stdin if needed
-    if uses_stdin {
-        if let Some(mut stdin) = child.stdin.take() {
-            let _ = stdin.write_all(probe_prompt.as_bytes());
-            let _ = stdin.write_all(b"\n");
+    if let Some(mut stdin) = child.stdin.take() {
+        if let Some(input) = &spec.stdin {
+            let _ = stdin.write_all(input.as_bytes());
         }
     }
 
@@ -346,15 +473,8 @@ fn run_probe_command(name: &str, timeout: Duration) -> Result<ProbeOutput, std::
 
             // Check if it looks like an auth issue
             // Be careful not to false-positive on success messages like "Loaded cached credentials"
-            let combined = format!("{stdout}\n{stderr}").to_lowercase();
-            let needs_auth = (combined.contains("auth") && !combined.contains("loaded"))
-                || combined.contains("please login")
-                || combined.contains("sign in")
-                || combined.contains("not authenticated")
-                || combined.contains("authentication required")
-                || combined.contains("unauthorized");
-
-            if needs_auth {
+            let combined = format!("{stdout}\n{stderr}");
+            if adapter.needs_auth(&combined) {
                 // Return as a failed probe with auth info, not a timeout error
                 return Ok(ProbeOutput {
                     success: false,
@@ -372,70 +492,98 @@ fn run_probe_command(name: &str, timeout: Duration) -> Result<ProbeOutput, std::
     }
 }
 
-/// Check if output indicates an auth error (not success messages like "Loaded cached credentials").
-fn is_auth_error(output: &str) -> bool {
-    // Positive patterns indicating auth is needed
-    let needs_auth_patterns = [
-        "not authenticated",
-        "authentication required",
-        "unauthorized",
-        "please login",
-        "please sign in",
-        "api key required",
-        "api_key required",
-        "missing api key",
-        "invalid api key",
-        "no credentials",
-    ];
-
-    // Check for explicit auth error patterns first
-    for pattern in needs_auth_patterns {
-        if output.contains(pattern) {
-            return true;
+/// Run a probe command for a model without blocking the runtime.
+///
+/// The child is killed if the probe times out or the future is dropped.
+async fn run_probe_command_async(
+    name: &str,
+    timeout: Duration,
+) -> Result<ProbeOutput, std::io::Error> {
+    let adapter = adapter_for(name);
+    if let Some(response) = adapter.mock_response(Path::new("."), None) {
+        return Ok(mock_probe_output(response));
+    }
+    let spec = adapter.probe_command(PROBE_PROMPT);
+    let mut cmd = spec.to_tokio_command();
+    cmd.kill_on_drop(true);
+
+    let mut child = cmd.spawn()?;
+
+    // Send prompt via stdin if needed, then close it so the model sees EOF
+    if let Some(mut stdin) = child.stdin.take() {
+        if let Some(input) = &spec.stdin {
+            let _ = stdin.write_all(input.as_bytes()).await;
         }
     }
 
-    // Check for "auth" keyword but exclude success messages
-    if output.contains("auth") && !output.contains("loaded") && !output.contains("success") {
-        return true;
+    // Read output while waiting so a chatty model can't fill the pipes.
+    // On timeout the buffers keep whatever was written before the deadline.
+    let mut stdout = Vec::new();
+    let mut stderr = Vec::new();
+    let mut out = child.stdout.take();
+    let mut err = child.stderr.take();
+    let waited = tokio::time::timeout(timeout, async {
+        let read_out = async {
+            if let Some(out) = out.as_mut() {
+                let _ = out.read_to_end(&mut stdout).await;
+            }
+        };
+        let read_err = async {
+            if let Some(err) = err.as_mut() {
+                let _ = err.read_to_end(&mut stderr).await;
+            }
+        };
+        tokio::join!(read_out, read_err);
+        child.wait().await
+    })
+    .await;
+
+    let stdout = String::from_utf8_lossy(&stdout).into_owned();
+    let stderr = String::from_utf8_lossy(&stderr).into_owned();
+
+    if let Ok(status) = waited {
+        return Ok(ProbeOutput {
+            success: status?.success(),
+            stdout,
+            stderr,
+        });
     }
 
-    // Check for "login" but exclude "please run ... login" success context
-    if output.contains("login required") || output.contains("must login") {
-        return true;
+    // Kill the process and reap it
+    let _ = child.kill().await;
+
+    // Output written before the timeout can reveal an auth prompt
+    let combined = format!("{stdout}\n{stderr}");
+    if adapter.needs_auth(&combined) {
+        return Ok(ProbeOutput {
+            success: false,
+            stdout,
+            stderr,
+        });
     }
 
-    false
+    Err(std::io::Error::new(
+        std::io::ErrorKind::TimedOut,
+        "Probe timed out",
+    ))
 }
 
-/// Extract rate limit reset time from error message.
-///
-/// Looks for patterns like:
-/// - "try again at Jan 12th, 2026 9:08 PM"
-/// - "resets at 2026-01-12T21:08:00"
-fn extract_reset_time(output: &str) -> Option<String> {
-    // Look for "try again at <datetime>"
-    if let Some(idx) = output.find("try again at ") {
-        let rest = &output[idx + 13..];
-        // Take until end of line or period
-        let end = rest.find(['.', '\n']).unwrap_or(rest.len());
-        let time_str = rest[..end].trim();
-        if !time_str.is_empty() {
-            return Some(time_str.to_string());
-        }
-    }
-
-    // Look for "resets at <datetime>"
-    if let Some(idx) = output.find("resets at ") {
-        let rest = &output[idx + 10..];
-        let end = rest.find(['.', '\n', '"']).unwrap_or(rest.len());
-        let time_str = rest[..end].trim();
-        if !time_str.is_empty() {
-            return Some(time_str.to_string());
-        }
+/// Probe output from the mock model's script. Delays are skipped so
+/// probing stays quick; a scripted rate limit still shows up.
+fn mock_probe_output(response: MockResponse) -> ProbeOutput {
+    ProbeOutput {
+        success: response.exit_code == 0,
+        stdout: response.stdout,
+        stderr: response.stderr,
     }
+}
 
-    None
+/// Rate limit patterns used when probing.
+///
+/// Broader than the per-model run patterns: any mention of a limit or quota
+/// in a failed probe is treated as a rate limit.
+fn probe_rate_limit_patterns() -> Vec<String> {
+    vec!["limit".into(), "quota".into(), "429".into()]
 }
 
 /// Extract version from command output.
@@ -476,6 +624,7 @@ mod tests {
                 path: Some("/usr/local/bin/claude".into()),
                 version: Some("1.0.0".into()),
                 issues: vec![],
+                auth_command: None,
             }],
         };
 
@@ -483,6 +632,16 @@ mod tests {
         assert!(json.contains("claude"));
     }
 
+    #[test]
+    fn test_auth_hint() {
+        let mut info = discover_model("ralf-test-missing-model");
+        assert!(info.auth_command.is_none());
+        assert_eq!(info.auth_hint(), "ralf-test-missing-model auth login");
+
+        info.auth_command = crate::adapters::adapter_for("codex").auth_command();
+        assert_eq!(info.auth_hint(), "codex login");
+    }
+
     #[test]
     fn test_probe_result_serialization() {
         let result = ProbeResult {
@@ -500,6 +659,55 @@ mod tests {
         assert!(json.contains("claude"));
     }
 
+    #[tokio::test]
+    async fn test_discover_models_async_keeps_order() {
+        let result = discover_models_async().await;
+        let names: Vec<_> = result.models.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, known_models());
+    }
+
+    #[tokio::test]
+    async fn test_probe_mock_model() {
+        let info = discover_model_async(MOCK_MODEL).await;
+        assert!(info.found && info.callable);
+
+        let result = probe_model_async(MOCK_MODEL, Duration::from_secs(1)).await;
+        assert!(result.success, "{:?}", result.issues);
+    }
+
+    #[tokio::test]
+    async fn test_probe_model_async_missing_model() {
+        let name = "ralf-no-such-model-cli";
+        let info = discover_model_async(name).await;
+        assert!(!info.found);
+        assert!(info.issues[0].contains("not found on PATH"));
+
+        let result = probe_model_async(name, Duration::from_secs(1)).await;
+        assert!(!result.success);
+        assert!(result.suggestions[0].contains("Install"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_probe_command_async_times_out() {
+        use std::os::unix::fs::PermissionsExt;
+
+        // A "model" that never answers
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("hang");
+        std::fs::write(&script, "#!/bin/sh\nexec sleep 30\n").unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let name = script.display().to_string();
+
+        let start = Instant::now();
+        let output = run_probe_command_async(&name, Duration::from_millis(200)).await;
+        assert!(start.elapsed() < Duration::from_secs(10));
+        let err = output.err().expect("probe should time out");
+        assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+        let result = interpret_probe(&undiscovered(&name), start, Err(err));
+        assert_eq!(result.issues, vec!["Probe timed out"]);
+    }
+
     #[test]
     fn test_extract_version() {
         assert_eq!(extract_version("v1.2.3"), Some("1.2.3".into()));
@@ -0,0 +1,680 @@
+//! Embedding the loop in other programs.
+//!
+//! [`Runner`] wraps [`start_run`] behind a builder, so a program can start
+//! a run without filling in every [`RunConfig`] field. Paths default to
+//! ralf's own layout (`PROMPT.md` and `.ralf/` in the repository), and each
+//! can be pointed elsewhere:
+//!
+//! ```no_run
+//! # async fn example() {
+//! use ralf_engine::{Config, Runner};
+//!
+//! let (events_tx, mut events) = tokio::sync::mpsc::unbounded_channel();
+//! let handle = Runner::builder(Config::default(), "/path/to/repo")
+//!     .ralf_dir("/tmp/ralf-state")
+//!     .max_iterations(5)
+//!     .events(events_tx)
+//!     .build()
+//!     .start();
+//! while let Some(event) = events.recv().await {
+//!     println!("{event:?}");
+//! }
+//! # drop(handle);
+//! # }
+//! ```
+//!
+//! A [`Clock`] and a [`ProcessSpawner`] can be swapped in too, so tests can
+//! move time on and stand in for the model and verifier processes.
+
+use std::fmt::Debug;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::clock::Clock;
+use crate::config::Config;
+use crate::criterion::Criterion;
+use crate::dirs::RalfDirs;
+use crate::events::{EventSink, MultiSink};
+use crate::platform;
+use crate::runner::{start_run, RunConfig, RunHandle};
+use crate::scheduler::Scheduler;
+
+/// Builds the commands a run starts for model CLIs and verifiers.
+pub trait ProcessSpawner: Send + Sync + Debug {
+    /// A command running `program` with `args`. The caller sets the
+    /// working directory and stdio.
+    fn command(&self, program: &str, args: &[String]) -> tokio::process::Command;
+}
+
+/// Runs programs as they are, as ralf does by default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemSpawner;
+
+impl ProcessSpawner for SystemSpawner {
+    fn command(&self, program: &str, args: &[String]) -> tokio::process::Command {
+        platform::tokio_command(program, args)
+    }
+}
+
+tokio::task_local! {
+    /// The spawner of the run this task belongs to.
+    static SPAWNER: Arc<dyn ProcessSpawner>;
+}
+
+/// A command for `program args`, built by the current run's spawner if it
+/// was given one.
+pub(crate) fn command(program: &str, args: &[String]) -> tokio::process::Command {
+    SPAWNER
+        .try_with(|spawner| spawner.command(program, args))
+        .unwrap_or_else(|_| platform::tokio_command(program, args))
+}
+
+/// Run `future` with `spawner` building its commands.
+pub(crate) async fn with_spawner<F: Future>(
+    spawner: Option<Arc<dyn ProcessSpawner>>,
+    future: F,
+) -> F::Output {
+    match spawner {
+        Some(spawner) => SPAWNER.scope(spawner, future).await,
+        None => future.await,
+    }
+}
+
+/// A run ready to start. See [`Runner::builder`].
+#[derive(Debug)]
+pub struct Runner {
+    config: Config,
+    run_config: RunConfig,
+    events: Option<Box<dyn EventSink>>,
+}
+
+impl Runner {
+    /// Start building a run of `config` in the repository at `repo_path`.
+    pub fn builder(config: Config, repo_path: impl Into<PathBuf>) -> RunnerBuilder {
+        let repo_path = repo_path.into();
+        RunnerBuilder {
+            config,
+            run_config: RunConfig {
+                max_iterations: 0,
+                max_runtime_secs: 0,
+                prompt_path: repo_path.join("PROMPT.md"),
+                repo_path,
+                target: None,
+                criteria: Vec::new(),
+                resume: None,
+                force: false,
+                max_cost_usd: None,
+                max_tokens: None,
+                no_verify_cache: false,
+                iteration_timeout_seconds: None,
+                scheduler: None,
+                dirs: None,
+                clock: None,
+                spawner: None,
+            },
+            criteria: None,
+            events: None,
+        }
+    }
+
+    /// The run's settings.
+    pub fn run_config(&self) -> &RunConfig {
+        &self.run_config
+    }
+
+    /// Start the run as a background task. Events go to the sink given
+    /// with [`RunnerBuilder::events`], if any.
+    pub fn start(self) -> RunHandle {
+        let events = self.events.unwrap_or_else(|| Box::new(MultiSink::new()));
+        start_run(self.config, self.run_config, events)
+    }
+}
+
+/// Builder for a [`Runner`].
+#[derive(Debug)]
+#[must_use]
+pub struct RunnerBuilder {
+    config: Config,
+    run_config: RunConfig,
+    criteria: Option<Vec<Criterion>>,
+    events: Option<Box<dyn EventSink>>,
+}
+
+impl RunnerBuilder {
+    /// The prompt file (default: `PROMPT.md` in the repository).
+    pub fn prompt_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.run_config.prompt_path = path.into();
+        self
+    }
+
+    /// Where state, logs and run history are kept (default: `.ralf` in the
+    /// repository).
+    pub fn ralf_dir(mut self, path: impl Into<PathBuf>) -> Self {
+        self.run_config.dirs = Some(RalfDirs::new(path));
+        self
+    }
+
+    /// Lay out state, logs and run history as `dirs` says.
+    pub fn dirs(mut self, dirs: RalfDirs) -> Self {
+        self.run_config.dirs = Some(dirs);
+        self
+    }
+
+    /// Directory models work in, relative to the repository or absolute.
+    pub fn target(mut self, path: impl Into<PathBuf>) -> Self {
+        self.run_config.target = Some(path.into());
+        self
+    }
+
+    /// Completion criteria (default: parsed from the prompt when the
+    /// runner is built).
+    pub fn criteria(mut self, criteria: Vec<Criterion>) -> Self {
+        self.criteria = Some(criteria);
+        self
+    }
+
+    /// Stop after this many iterations (default: 0, unlimited).
+    pub fn max_iterations(mut self, max: usize) -> Self {
+        self.run_config.max_iterations = max;
+        self
+    }
+
+    /// Stop after this many seconds (default: 0, unlimited).
+    pub fn max_runtime_secs(mut self, secs: u64) -> Self {
+        self.run_config.max_runtime_secs = secs;
+        self
+    }
+
+    /// Resume this run instead of starting a new one.
+    pub fn resume(mut self, run_id: impl Into<String>) -> Self {
+        self.run_config.resume = Some(run_id.into());
+        self
+    }
+
+    /// Resume even if the prompt changed, and take over a stale run lock.
+    pub fn force(mut self, force: bool) -> Self {
+        self.run_config.force = force;
+        self
+    }
+
+    /// Cost budget in US dollars, overriding the config's.
+    pub fn max_cost_usd(mut self, max: f64) -> Self {
+        self.run_config.max_cost_usd = Some(max);
+        self
+    }
+
+    /// Token budget, overriding the config's.
+    pub fn max_tokens(mut self, max: u64) -> Self {
+        self.run_config.max_tokens = Some(max);
+        self
+    }
+
+    /// Re-verify every criterion instead of reusing cached verdicts.
+    pub fn no_verify_cache(mut self, no_cache: bool) -> Self {
+        self.run_config.no_verify_cache = no_cache;
+        self
+    }
+
+    /// Wall-clock cap per iteration in seconds, overriding the config's.
+    pub fn iteration_timeout_seconds(mut self, secs: u64) -> Self {
+        self.run_config.iteration_timeout_seconds = Some(secs);
+        self
+    }
+
+    /// Share model slots (and, with worktree isolation, the run lock) with
+    /// other runs given the same scheduler.
+    pub fn scheduler(mut self, scheduler: Arc<Scheduler>) -> Self {
+        self.run_config.scheduler = Some(scheduler);
+        self
+    }
+
+    /// Send the run's events here: a channel, or any other [`EventSink`].
+    pub fn events(mut self, events: impl EventSink + 'static) -> Self {
+        self.events = Some(Box::new(events));
+        self
+    }
+
+    /// Time the run with `clock` instead of the system clock.
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.run_config.clock = Some(clock);
+        self
+    }
+
+    /// Start model and verifier processes with `spawner`.
+    pub fn spawner(mut self, spawner: Arc<dyn ProcessSpawner>) -> Self {
+        self.run_config.spawner = Some(spawner);
+        self
+    }
+
+    /// Finish the runner, reading criteria from the prompt unless they
+    /// were given. A prompt that can't be read fails the run when it
+    /// starts.
+    pub fn build(self) -> Runner {
+        let mut run_config = self.run_config;
+        run_config.criteria = self.criteria.unwrap_or_else(|| {
+            std::fs::read_to_string(&run_config.prompt_path)
+                .map(|prompt| crate::parse_criteria(&prompt))
+                .unwrap_or_default()
+        });
+        Runner {
+            config: self.config,
+            run_config,
+            events: self.events,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use tokio::sync::mpsc;
+
+    use crate::changelog::{ChangelogStore, IterationStatus, VerifierRecord, VerifierStatus};
+    use crate::config::{ModelConfig, VerifierConfig};
+    use crate::model_stats::ModelStats;
+    use crate::runner::RunEvent;
+    use crate::scheduler::ConcurrencyConfig;
+    use crate::state::{RunState, RunStatus};
+
+    /// A clock that moves on an hour every time it's read.
+    #[derive(Debug, Default)]
+    struct FastClock(AtomicU64);
+
+    impl Clock for FastClock {
+        fn now(&self) -> u64 {
+            self.0.fetch_add(3600, Ordering::SeqCst)
+        }
+    }
+
+    /// Answers every command with the completion promise.
+    #[derive(Debug)]
+    struct PromiseSpawner;
+
+    impl ProcessSpawner for PromiseSpawner {
+        fn command(&self, _program: &str, _args: &[String]) -> tokio::process::Command {
+            let mut cmd = tokio::process::Command::new("sh");
+            cmd.args(["-c", "cat >/dev/null; echo '<promise>COMPLETE</promise>'"]);
+            cmd
+        }
+    }
+
+    /// Answers with the completion promise on stderr, as some CLIs do.
+    #[derive(Debug)]
+    struct StderrSpawner;
+
+    impl ProcessSpawner for StderrSpawner {
+        fn command(&self, _program: &str, _args: &[String]) -> tokio::process::Command {
+            let mut cmd = tokio::process::Command::new("sh");
+            cmd.args([
+                "-c",
+                "cat >/dev/null; echo '<promise>COMPLETE</promise>' >&2",
+            ]);
+            cmd
+        }
+    }
+
+    /// Answers every command that it is blocked.
+    #[derive(Debug)]
+    struct BlockedSpawner;
+
+    impl ProcessSpawner for BlockedSpawner {
+        fn command(&self, _program: &str, _args: &[String]) -> tokio::process::Command {
+            let mut cmd = tokio::process::Command::new("sh");
+            cmd.args([
+                "-c",
+                "cat >/dev/null; echo '<promise>BLOCKED: no database</promise>'",
+            ]);
+            cmd
+        }
+    }
+
+    /// Asks which database to use until a prompt carries the answer.
+    #[derive(Debug)]
+    struct QuestionSpawner;
+
+    impl ProcessSpawner for QuestionSpawner {
+        fn command(&self, _program: &str, _args: &[String]) -> tokio::process::Command {
+            let mut cmd = tokio::process::Command::new("sh");
+            cmd.args([
+                "-c",
+                "if grep -q 'Answer: SQLite'; then echo '<promise>COMPLETE</promise>'; \
+                 else echo '<promise>NEEDS_INPUT: Postgres or SQLite?</promise>'; fi",
+            ]);
+            cmd
+        }
+    }
+
+    /// Takes longer than any test waits.
+    #[derive(Debug)]
+    struct SlowSpawner;
+
+    impl ProcessSpawner for SlowSpawner {
+        fn command(&self, _program: &str, _args: &[String]) -> tokio::process::Command {
+            let mut cmd = tokio::process::Command::new("sh");
+            cmd.args(["-c", "cat >/dev/null; sleep 30"]);
+            cmd
+        }
+    }
+
+    fn config() -> Config {
+        Config {
+            models: vec![ModelConfig {
+                name: "fake".into(),
+                command_argv: vec!["not-installed-model".into()],
+                timeout_seconds: 30,
+                rate_limit_patterns: Vec::new(),
+                default_cooldown_seconds: 60,
+                max_context_tokens: None,
+            }],
+            model_priority: vec!["fake".into()],
+            required_verifiers: Vec::new(),
+            ..Config::default()
+        }
+    }
+
+    async fn final_event(runner: RunnerBuilder) -> RunEvent {
+        let (events_tx, mut events) = mpsc::unbounded_channel();
+        let _handle = runner.events(events_tx).build().start();
+        let mut last = None;
+        while let Some(event) = events.recv().await {
+            last = Some(event);
+        }
+        last.unwrap()
+    }
+
+    #[test]
+    fn test_builder_defaults() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::write(
+            temp.path().join("PROMPT.md"),
+            "## Criteria\n\n- Tests pass\n",
+        )
+        .unwrap();
+
+        let runner = Runner::builder(config(), temp.path()).build();
+        let run_config = runner.run_config();
+        assert_eq!(run_config.prompt_path, temp.path().join("PROMPT.md"));
+        assert_eq!(run_config.dirs(&config()).root(), temp.path().join(".ralf"));
+        assert_eq!(run_config.criteria.len(), 1);
+
+        let runner = Runner::builder(config(), temp.path())
+            .ralf_dir("/tmp/state")
+            .criteria(Vec::new())
+            .build();
+        assert_eq!(
+            runner.run_config().dirs(&config()).root(),
+            std::path::Path::new("/tmp/state")
+        );
+        assert!(runner.run_config().criteria.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_clock_limits_runtime() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::write(temp.path().join("PROMPT.md"), "Do the thing\n").unwrap();
+
+        let runner = Runner::builder(config(), temp.path())
+            .max_runtime_secs(60)
+            .clock(Arc::new(FastClock::default()));
+        assert!(matches!(
+            final_event(runner).await,
+            RunEvent::Completed { iteration: 0, reason } if reason == "Max runtime reached"
+        ));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_spawner_stands_in_for_models() {
+        let temp = tempfile::tempdir().unwrap();
+        let state_dir = tempfile::tempdir().unwrap();
+        std::fs::write(temp.path().join("PROMPT.md"), "Do the thing\n").unwrap();
+
+        let runner = Runner::builder(config(), temp.path())
+            .ralf_dir(state_dir.path())
+            .max_iterations(3)
+            .spawner(Arc::new(PromiseSpawner));
+        assert!(matches!(
+            final_event(runner).await,
+            RunEvent::Completed { iteration: 1, .. }
+        ));
+        // State went to the given directory, not the repository
+        assert!(state_dir.path().join("state.json").exists());
+        assert!(!temp.path().join(".ralf").exists());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_verifiers_recorded_in_changelog() {
+        let temp = tempfile::tempdir().unwrap();
+        let state_dir = tempfile::tempdir().unwrap();
+        std::fs::write(temp.path().join("PROMPT.md"), "Do the thing\n").unwrap();
+
+        let config = Config {
+            verifiers: vec![VerifierConfig::default_tests()],
+            ..config()
+        };
+        let runner = Runner::builder(config, temp.path())
+            .ralf_dir(state_dir.path())
+            .max_iterations(3)
+            .spawner(Arc::new(PromiseSpawner));
+        assert!(matches!(
+            final_event(runner).await,
+            RunEvent::Completed { iteration: 1, .. }
+        ));
+        let records = ChangelogStore::new(RalfDirs::new(state_dir.path()).changelog())
+            .list(None)
+            .unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].status, IterationStatus::Success);
+        assert_eq!(
+            records[0].verifiers,
+            [VerifierRecord {
+                name: "tests".into(),
+                status: VerifierStatus::Pass,
+            }]
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_adapter_reads_response() {
+        let temp = tempfile::tempdir().unwrap();
+        let state_dir = tempfile::tempdir().unwrap();
+        std::fs::write(temp.path().join("PROMPT.md"), "Do the thing\n").unwrap();
+
+        let runner = Runner::builder(config(), temp.path())
+            .ralf_dir(state_dir.path())
+            .max_iterations(3)
+            .spawner(Arc::new(StderrSpawner));
+        assert!(matches!(
+            final_event(runner).await,
+            RunEvent::Completed { iteration: 1, .. }
+        ));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_blocked_model_is_stuck() {
+        let temp = tempfile::tempdir().unwrap();
+        let state_dir = tempfile::tempdir().unwrap();
+        std::fs::write(temp.path().join("PROMPT.md"), "Migrate the data\n").unwrap();
+
+        let runner = Runner::builder(config(), temp.path())
+            .ralf_dir(state_dir.path())
+            .max_iterations(3)
+            .spawner(Arc::new(BlockedSpawner));
+        match final_event(runner).await {
+            RunEvent::Stuck {
+                iteration,
+                diagnosis,
+            } => {
+                assert_eq!(iteration, 1);
+                assert_eq!(
+                    diagnosis.signals,
+                    vec!["the model reported it is blocked: no database"]
+                );
+            }
+            event => panic!("unexpected final event: {event:?}"),
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_answer_reaches_next_prompt() {
+        let temp = tempfile::tempdir().unwrap();
+        let state_dir = tempfile::tempdir().unwrap();
+        std::fs::write(temp.path().join("PROMPT.md"), "Store the logins\n").unwrap();
+
+        let (events_tx, mut events) = mpsc::unbounded_channel();
+        let handle = Runner::builder(config(), temp.path())
+            .ralf_dir(state_dir.path())
+            .max_iterations(3)
+            .spawner(Arc::new(QuestionSpawner))
+            .events(events_tx)
+            .build()
+            .start();
+        let mut last = None;
+        while let Some(event) = events.recv().await {
+            if let RunEvent::QuestionAsked { question, .. } = &event {
+                assert_eq!(question, "Postgres or SQLite?");
+                assert!(handle.answer("SQLite"));
+            }
+            last = Some(event);
+        }
+        assert!(matches!(
+            last,
+            Some(RunEvent::Completed { iteration: 2, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_while_waiting_for_slot() {
+        let temp = tempfile::tempdir().unwrap();
+        let state_dir = tempfile::tempdir().unwrap();
+        std::fs::write(temp.path().join("PROMPT.md"), "Do the thing\n").unwrap();
+
+        // Another run is using the only model
+        let scheduler = Arc::new(Scheduler::new(&ConcurrencyConfig::default()));
+        let _slot = scheduler.try_acquire("fake").unwrap();
+
+        let (events_tx, mut events) = mpsc::unbounded_channel();
+        let handle = Runner::builder(config(), temp.path())
+            .ralf_dir(state_dir.path())
+            .scheduler(scheduler.clone())
+            .events(events_tx)
+            .build()
+            .start();
+        let mut last = None;
+        while let Some(event) = events.recv().await {
+            if let RunEvent::Status { message } = &event {
+                if message.starts_with("Waiting for fake") {
+                    assert!(handle.try_cancel());
+                }
+            }
+            last = Some(event);
+        }
+        assert!(matches!(last, Some(RunEvent::Cancelled { iteration: 1 })));
+        let state = RunState::load(&state_dir.path().join("state.json")).unwrap();
+        assert_eq!(state.status, RunStatus::Cancelled);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_cancel_during_model_call() {
+        let temp = tempfile::tempdir().unwrap();
+        let state_dir = tempfile::tempdir().unwrap();
+        std::fs::write(temp.path().join("PROMPT.md"), "Do the thing\n").unwrap();
+
+        let (events_tx, mut events) = mpsc::unbounded_channel();
+        let handle = Runner::builder(config(), temp.path())
+            .ralf_dir(state_dir.path())
+            .spawner(Arc::new(SlowSpawner))
+            .events(events_tx)
+            .build()
+            .start();
+        let mut last = None;
+        while let Some(event) = events.recv().await {
+            if let RunEvent::IterationStarted { .. } = &event {
+                assert!(handle.try_cancel());
+            }
+            last = Some(event);
+        }
+        assert!(matches!(last, Some(RunEvent::Cancelled { iteration: 1 })));
+        let state = RunState::load(&state_dir.path().join("state.json")).unwrap();
+        assert_eq!(state.status, RunStatus::Cancelled);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_timeout_records_time_taken() {
+        let temp = tempfile::tempdir().unwrap();
+        let state_dir = tempfile::tempdir().unwrap();
+        std::fs::write(temp.path().join("PROMPT.md"), "Do the thing\n").unwrap();
+
+        let runner = Runner::builder(config(), temp.path())
+            .ralf_dir(state_dir.path())
+            .max_iterations(1)
+            .iteration_timeout_seconds(1)
+            .spawner(Arc::new(SlowSpawner));
+        final_event(runner).await;
+
+        // Stopped by the iteration timeout, not the model's 30s
+        let stats = ModelStats::load(&ModelStats::path(state_dir.path())).unwrap();
+        let latency = stats.models["fake"].avg_latency_ms;
+        assert!((1000.0..30_000.0).contains(&latency), "{latency}");
+    }
+
+    #[tokio::test]
+    async fn test_resumed_run_asks_again() {
+        let temp = tempfile::tempdir().unwrap();
+        let state_dir = tempfile::tempdir().unwrap();
+        std::fs::write(temp.path().join("PROMPT.md"), "Store the logins\n").unwrap();
+        let runner = || {
+            Runner::builder(config(), temp.path())
+                .ralf_dir(state_dir.path())
+                .max_iterations(3)
+                .spawner(Arc::new(QuestionSpawner))
+        };
+
+        // Stopped while waiting on an answer
+        let (events_tx, mut events) = mpsc::unbounded_channel();
+        let handle = runner().events(events_tx).build().start();
+        let mut run_id = None;
+        while let Some(event) = events.recv().await {
+            match event {
+                RunEvent::Started { run_id: id, .. } => run_id = Some(id),
+                RunEvent::QuestionAsked { .. } => assert!(handle.try_cancel()),
+                _ => {}
+            }
+        }
+
+        // The resumed run asks before its next iteration
+        let (events_tx, mut events) = mpsc::unbounded_channel();
+        let handle = runner()
+            .resume(run_id.unwrap())
+            .events(events_tx)
+            .build()
+            .start();
+        let mut asked = Vec::new();
+        let mut last = None;
+        while let Some(event) = events.recv().await {
+            if let RunEvent::QuestionAsked {
+                iteration,
+                question,
+            } = &event
+            {
+                asked.push((*iteration, question.clone()));
+                assert!(handle.answer("SQLite"));
+            }
+            last = Some(event);
+        }
+        assert_eq!(asked, vec![(1, "Postgres or SQLite?".to_string())]);
+        assert!(matches!(
+            last,
+            Some(RunEvent::Completed { iteration: 2, .. })
+        ));
+    }
+}
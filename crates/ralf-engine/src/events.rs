@@ -0,0 +1,354 @@
+//! Structured event log for runs.
+//!
+//! Every [`RunEvent`] emitted during a run is appended to
+//! `.ralf/runs/<run_id>/events.jsonl`, one timestamped JSON object per line.
+//! The log is an audit trail for debugging and can be replayed or tailed
+//! with `ralf events <run_id>`.
+//!
+//! A run hands its events to an [`EventSink`]. Channels, the event log and
+//! [`MultiSink`] (which passes each event to several others) are sinks, so
+//! one run can be printed, logged and shown in the TUI at the same time.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+
+use crate::dirs::RalfDirs;
+use crate::runner::RunEvent;
+
+/// Name of the event log file inside a run directory.
+pub const EVENTS_FILE: &str = "events.jsonl";
+
+/// A single line of the event log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventRecord {
+    /// When the event was emitted.
+    pub timestamp: DateTime<Utc>,
+
+    /// The event itself (flattened, tagged by `type`).
+    #[serde(flatten)]
+    pub event: RunEvent,
+}
+
+impl EventRecord {
+    /// Timestamp an event with the current time.
+    pub fn now(event: RunEvent) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            event,
+        }
+    }
+
+    /// Serialize as a single JSONL line (including the trailing newline).
+    pub fn to_line(&self) -> Result<String, EventLogError> {
+        let mut line = serde_json::to_string(self).map_err(EventLogError::Serialize)?;
+        line.push('\n');
+        Ok(line)
+    }
+}
+
+/// Path of the event log for a run.
+pub fn events_path(ralf_dir: &Path, run_id: &str) -> PathBuf {
+    RalfDirs::new(ralf_dir).run(run_id).join(EVENTS_FILE)
+}
+
+/// Synchronous append-only writer for a run's event log.
+#[derive(Debug)]
+pub struct EventLog {
+    file: File,
+}
+
+impl EventLog {
+    /// Open (or create) the event log in a run directory.
+    pub fn open(run_dir: &Path) -> Result<Self, EventLogError> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(run_dir.join(EVENTS_FILE))?;
+        Ok(Self { file })
+    }
+
+    /// Append an event, timestamped now.
+    pub fn append(&mut self, event: &RunEvent) -> Result<(), EventLogError> {
+        let line = EventRecord::now(event.clone()).to_line()?;
+        self.file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Where a run's events go.
+///
+/// Emitting never fails the run: a sink that can't deliver an event (a
+/// closed channel, an unwritable log) drops it.
+pub trait EventSink: Send + Sync + Debug {
+    /// Take one event.
+    fn emit(&self, event: RunEvent);
+}
+
+impl EventSink for mpsc::UnboundedSender<RunEvent> {
+    fn emit(&self, event: RunEvent) {
+        let _ = self.send(event);
+    }
+}
+
+impl<S: EventSink + ?Sized> EventSink for Box<S> {
+    fn emit(&self, event: RunEvent) {
+        (**self).emit(event);
+    }
+}
+
+impl<S: EventSink + ?Sized> EventSink for Arc<S> {
+    fn emit(&self, event: RunEvent) {
+        (**self).emit(event);
+    }
+}
+
+/// Appends events to a run's `events.jsonl`.
+#[derive(Debug)]
+pub struct JsonlSink {
+    log: Mutex<EventLog>,
+}
+
+impl JsonlSink {
+    /// Open (or create) the event log in a run directory.
+    pub fn open(run_dir: &Path) -> Result<Self, EventLogError> {
+        Ok(Self {
+            log: Mutex::new(EventLog::open(run_dir)?),
+        })
+    }
+}
+
+impl EventSink for JsonlSink {
+    fn emit(&self, event: RunEvent) {
+        // The file is unbuffered, so `ralf events --follow` sees each line
+        // as soon as it is written
+        let mut log = self
+            .log
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let _ = log.append(&event);
+    }
+}
+
+/// Passes every event to each of its sinks, in the order they were added.
+#[derive(Debug, Default)]
+pub struct MultiSink {
+    sinks: Vec<Box<dyn EventSink>>,
+}
+
+impl MultiSink {
+    /// A sink passing events nowhere, until sinks are added.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also pass events to `sink`.
+    #[must_use]
+    pub fn with(mut self, sink: impl EventSink + 'static) -> Self {
+        self.push(sink);
+        self
+    }
+
+    /// Also pass events to `sink`.
+    pub fn push(&mut self, sink: impl EventSink + 'static) {
+        self.sinks.push(Box::new(sink));
+    }
+}
+
+impl EventSink for MultiSink {
+    fn emit(&self, event: RunEvent) {
+        if let Some((last, rest)) = self.sinks.split_last() {
+            for sink in rest {
+                sink.emit(event.clone());
+            }
+            last.emit(event);
+        }
+    }
+}
+
+/// Follows an event log as it grows, e.g. the log of a run another
+/// process is going through.
+#[derive(Debug)]
+pub struct EventTail {
+    path: PathBuf,
+    offset: u64,
+    pending: String,
+}
+
+impl EventTail {
+    /// Follow the log at `path` from its start. The log needn't exist yet.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            offset: 0,
+            pending: String::new(),
+        }
+    }
+
+    /// The lines appended since the last call. A partly written final line
+    /// is held back until it is finished.
+    pub fn read_lines(&mut self) -> Vec<String> {
+        use std::io::{Read, Seek, SeekFrom};
+
+        if let Ok(mut file) = File::open(&self.path) {
+            if file.seek(SeekFrom::Start(self.offset)).is_ok() {
+                let mut chunk = String::new();
+                if let Ok(n) = file.read_to_string(&mut chunk) {
+                    self.offset += n as u64;
+                    self.pending.push_str(&chunk);
+                }
+            }
+        }
+        let mut lines = Vec::new();
+        while let Some(end) = self.pending.find('\n') {
+            lines.push(self.pending.drain(..=end).collect());
+        }
+        lines
+    }
+}
+
+/// Parse one line of an event log. Returns `None` for blank or malformed
+/// lines (e.g., a partially written final line).
+pub fn parse_event_line(line: &str) -> Option<EventRecord> {
+    let line = line.trim();
+    if line.is_empty() {
+        return None;
+    }
+    serde_json::from_str(line).ok()
+}
+
+/// Read all well-formed records from an event log.
+pub fn read_events(path: &Path) -> Result<Vec<EventRecord>, EventLogError> {
+    let content = std::fs::read_to_string(path)?;
+    Ok(content.lines().filter_map(parse_event_line).collect())
+}
+
+/// Errors that can occur when working with event logs.
+#[derive(Debug, thiserror::Error)]
+pub enum EventLogError {
+    /// I/O error.
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Serialize error.
+    #[error("Serialize error: {0}")]
+    Serialize(#[source] serde_json::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::usage::Usage;
+
+    #[test]
+    fn test_event_record_roundtrip() {
+        let record = EventRecord::now(RunEvent::UsageUpdated {
+            iteration: 2,
+            model: "claude".into(),
+            usage: Usage {
+                total_tokens: 10,
+                ..Usage::default()
+            },
+            run_total: Usage::default(),
+        });
+        let line = record.to_line().unwrap();
+        assert!(line.ends_with('\n'));
+        assert!(line.contains(r#""type":"usage_updated""#));
+
+        let parsed = parse_event_line(&line).unwrap();
+        assert_eq!(parsed.timestamp, record.timestamp);
+        assert!(matches!(
+            parsed.event,
+            RunEvent::UsageUpdated { iteration: 2, .. }
+        ));
+    }
+
+    #[test]
+    fn test_event_log_append_and_read() {
+        let temp = tempfile::TempDir::new().unwrap();
+
+        let mut log = EventLog::open(temp.path()).unwrap();
+        log.append(&RunEvent::IterationStarted {
+            iteration: 1,
+            model: "codex".into(),
+        })
+        .unwrap();
+        log.append(&RunEvent::Cancelled { iteration: 1 }).unwrap();
+
+        // A torn final line is skipped
+        let path = temp.path().join(EVENTS_FILE);
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"{\"timestamp\":").unwrap();
+
+        let records = read_events(&path).unwrap();
+        assert_eq!(records.len(), 2);
+        assert!(matches!(
+            records[1].event,
+            RunEvent::Cancelled { iteration: 1 }
+        ));
+    }
+
+    #[test]
+    fn test_event_tail() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join(EVENTS_FILE);
+        let mut tail = EventTail::new(&path);
+        assert!(tail.read_lines().is_empty());
+
+        let mut log = EventLog::open(temp.path()).unwrap();
+        log.append(&RunEvent::Paused { iteration: 1 }).unwrap();
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"{\"timestamp\":").unwrap();
+        let lines = tail.read_lines();
+        assert_eq!(lines.len(), 1);
+        assert!(parse_event_line(&lines[0]).is_some());
+
+        // The torn line comes through once it is finished
+        file.write_all(b"\"2026-01-01T00:00:00Z\",\"type\":\"resumed\",\"iteration\":1}\n")
+            .unwrap();
+        let lines = tail.read_lines();
+        assert!(matches!(
+            parse_event_line(&lines[0]).unwrap().event,
+            RunEvent::Resumed { iteration: 1 }
+        ));
+        assert!(tail.read_lines().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_multi_sink_logs_and_forwards() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join(EVENTS_FILE);
+
+        let (first_tx, mut first_rx) = mpsc::unbounded_channel();
+        let (second_tx, mut second_rx) = mpsc::unbounded_channel();
+        let sink = MultiSink::new()
+            .with(JsonlSink::open(temp.path()).unwrap())
+            .with(first_tx)
+            .with(second_tx);
+        sink.emit(RunEvent::Status {
+            message: "hello".into(),
+        });
+        drop(sink);
+
+        for rx in [&mut first_rx, &mut second_rx] {
+            assert!(matches!(rx.recv().await, Some(RunEvent::Status { .. })));
+            // Channels close once the sink is dropped
+            assert!(rx.recv().await.is_none());
+        }
+        let records = read_events(&path).unwrap();
+        assert_eq!(records.len(), 1);
+
+        // A closed channel doesn't stop the others
+        let (closed_tx, _) = mpsc::unbounded_channel();
+        let sink = MultiSink::new()
+            .with(closed_tx)
+            .with(JsonlSink::open(temp.path()).unwrap());
+        sink.emit(RunEvent::Cancelled { iteration: 1 });
+        assert_eq!(read_events(&path).unwrap().len(), 2);
+    }
+}
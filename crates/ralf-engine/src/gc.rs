@@ -0,0 +1,511 @@
+//! Pruning old runs.
+//!
+//! Every run leaves a directory under `.ralf/runs/` and an entry per
+//! iteration in the changelog, and Spec Studio leaves a snapshot per saved
+//! draft, so `.ralf/` grows without bound. [`gc`] removes what the
+//! [`RetentionConfig`] no longer keeps, along with the worktrees of
+//! isolated runs it removes. The latest run, a run holding the lock and the
+//! runs of open threads are always kept.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::changelog::{ChangelogError, ChangelogStore};
+use crate::dirs::RalfDirs;
+use crate::git::{GitError, GitSafety};
+use crate::health::{dir_size, format_bytes};
+use crate::lock::RunLock;
+use crate::persistence::{DraftHistory, PersistenceError, ThreadFilter, ThreadStore};
+use crate::state::RunState;
+
+/// How much of ralf's history `ralf gc` keeps.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionConfig {
+    /// Most recent runs kept.
+    #[serde(default = "default_keep_runs")]
+    pub keep_runs: usize,
+
+    /// Most space `.ralf/` may take, in MB, not counting run worktrees.
+    /// Older runs are removed, past `keep_runs`, until it fits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_ralf_dir_mb: Option<u64>,
+
+    /// Most recent draft snapshots kept.
+    #[serde(default = "default_keep_drafts")]
+    pub keep_drafts: usize,
+}
+
+fn default_keep_runs() -> usize {
+    20
+}
+
+fn default_keep_drafts() -> usize {
+    50
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            keep_runs: default_keep_runs(),
+            max_ralf_dir_mb: None,
+            keep_drafts: default_keep_drafts(),
+        }
+    }
+}
+
+impl RetentionConfig {
+    /// Whether the policy is the default one.
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// What [`gc`] removed, or would remove on a dry run.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct GcReport {
+    /// Removed runs, oldest first.
+    pub runs: Vec<String>,
+    /// Changelog entries of the removed runs.
+    pub changelog_entries: usize,
+    /// Removed draft snapshots, by name.
+    pub drafts: Vec<String>,
+    /// Removed run worktrees, by run.
+    pub worktrees: Vec<String>,
+    /// Worktrees of removed runs left in place because they have
+    /// uncommitted changes, by run.
+    pub kept_worktrees: Vec<String>,
+    /// Space freed by the removed runs, drafts and worktrees.
+    pub bytes_freed: u64,
+}
+
+impl GcReport {
+    /// Whether nothing was removed.
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+            && self.changelog_entries == 0
+            && self.drafts.is_empty()
+            && self.worktrees.is_empty()
+    }
+
+    /// One line describing what was removed, e.g. "2 runs, 5 changelog
+    /// entries and 1 draft snapshot (3.1 MB)". Worktrees are only
+    /// mentioned when some were removed.
+    pub fn summary(&self) -> String {
+        let counted = |count: usize, one: &str, many: &str| {
+            format!("{count} {}", if count == 1 { one } else { many })
+        };
+        let mut parts = vec![
+            counted(self.runs.len(), "run", "runs"),
+            counted(
+                self.changelog_entries,
+                "changelog entry",
+                "changelog entries",
+            ),
+            counted(self.drafts.len(), "draft snapshot", "draft snapshots"),
+        ];
+        if !self.worktrees.is_empty() {
+            parts.push(counted(self.worktrees.len(), "worktree", "worktrees"));
+        }
+        let last = parts.pop().unwrap_or_default();
+        format!(
+            "{} and {last} ({})",
+            parts.join(", "),
+            format_bytes(self.bytes_freed)
+        )
+    }
+}
+
+/// Error pruning `.ralf/`.
+#[derive(Debug, thiserror::Error)]
+pub enum GcError {
+    /// I/O error.
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The changelog couldn't be pruned.
+    #[error(transparent)]
+    Changelog(#[from] ChangelogError),
+
+    /// Threads or drafts couldn't be read.
+    #[error(transparent)]
+    Persistence(#[from] PersistenceError),
+
+    /// A run worktree couldn't be removed.
+    #[error(transparent)]
+    Git(#[from] GitError),
+}
+
+/// Remove the runs, changelog entries and draft snapshots `retention` no
+/// longer keeps. With `dry_run`, only report what would go.
+pub fn gc(
+    dirs: &RalfDirs,
+    retention: &RetentionConfig,
+    dry_run: bool,
+) -> Result<GcReport, GcError> {
+    let mut report = GcReport::default();
+
+    let protected = protected_runs(dirs)?;
+    let removed_runs = runs_to_remove(dirs, retention, &protected)?;
+    for run in &removed_runs {
+        report.bytes_freed += run.size;
+        if !dry_run {
+            std::fs::remove_dir_all(&run.path)?;
+        }
+    }
+    report.runs = removed_runs.into_iter().map(|run| run.id).collect();
+
+    let run_ids: HashSet<String> = report.runs.iter().cloned().collect();
+    report.changelog_entries =
+        ChangelogStore::new(dirs.changelog()).remove_runs(&run_ids, dry_run)?;
+
+    // Newest first, so everything past `keep_drafts` goes
+    let drafts = DraftHistory::new(dirs.root()).list()?;
+    for draft in drafts.into_iter().skip(retention.keep_drafts) {
+        report.bytes_freed += std::fs::metadata(&draft.path).map_or(0, |m| m.len());
+        if !dry_run {
+            std::fs::remove_file(&draft.path)?;
+        }
+        report.drafts.push(draft.name);
+    }
+
+    remove_worktrees(dirs, &run_ids, &protected, dry_run, &mut report)?;
+
+    Ok(report)
+}
+
+/// Remove the worktrees of `removed_runs`, and of runs removed before,
+/// leaving those with uncommitted changes.
+fn remove_worktrees(
+    dirs: &RalfDirs,
+    removed_runs: &HashSet<String>,
+    protected: &HashSet<String>,
+    dry_run: bool,
+    report: &mut GcReport,
+) -> Result<(), GcError> {
+    let worktrees_dir = dirs.worktrees();
+    let entries = match std::fs::read_dir(&worktrees_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(GcError::Io(e)),
+    };
+    let mut run_ids = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let Some(run_id) = entry.file_name().to_str().map(str::to_string) else {
+            continue;
+        };
+        let gone = removed_runs.contains(&run_id) || !dirs.run(&run_id).exists();
+        if gone && !protected.contains(&run_id) && entry.path().join(".git").exists() {
+            run_ids.push(run_id);
+        }
+    }
+    run_ids.sort();
+
+    // Worktrees are in the repository holding `.ralf/`
+    let repo = match dirs.root().parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let git = GitSafety::new(repo);
+    for run_id in run_ids {
+        let path = worktrees_dir.join(&run_id);
+        let size = dir_size(&path);
+        let removed = if dry_run {
+            GitSafety::new(&path).is_clean()?
+        } else {
+            git.remove_run_worktree(&worktrees_dir, &run_id)?
+        };
+        if removed {
+            report.bytes_freed += size;
+            report.worktrees.push(run_id);
+        } else {
+            report.kept_worktrees.push(run_id);
+        }
+    }
+    Ok(())
+}
+
+/// A run directory.
+#[derive(Debug)]
+struct RunDir {
+    id: String,
+    path: PathBuf,
+    modified: SystemTime,
+    size: u64,
+}
+
+/// The runs past `keep_runs`, then the oldest until `.ralf/` fits
+/// `max_ralf_dir_mb`, oldest first. `protected` runs are never included.
+/// Worktrees don't count towards the limit: only the clean ones can go,
+/// and not for the space alone.
+fn runs_to_remove(
+    dirs: &RalfDirs,
+    retention: &RetentionConfig,
+    protected: &HashSet<String>,
+) -> Result<Vec<RunDir>, GcError> {
+    let mut runs = list_runs(&dirs.runs())?;
+    // Oldest first
+    runs.sort_by(|a, b| (a.modified, &a.id).cmp(&(b.modified, &b.id)));
+
+    let excess = runs.len().saturating_sub(retention.keep_runs);
+    let mut used = dir_size(dirs.root()).saturating_sub(dir_size(&dirs.worktrees()));
+    let limit = retention
+        .max_ralf_dir_mb
+        .map(|mb| mb.saturating_mul(1024 * 1024));
+
+    let mut removed = Vec::new();
+    for (index, run) in runs.into_iter().enumerate() {
+        if protected.contains(&run.id) {
+            continue;
+        }
+        if index < excess || limit.is_some_and(|limit| used > limit) {
+            used = used.saturating_sub(run.size);
+            removed.push(run);
+        }
+    }
+    Ok(removed)
+}
+
+/// Runs that must be kept: the latest run, the run holding the lock, and
+/// the current run of each open thread.
+fn protected_runs(dirs: &RalfDirs) -> Result<HashSet<String>, GcError> {
+    let mut protected = HashSet::new();
+    if let Some(run_id) = RunState::load(&dirs.state())
+        .ok()
+        .and_then(|state| state.run_id)
+    {
+        protected.insert(run_id);
+    }
+    if let Some(run_id) = RunLock::read(dirs.root())
+        .ok()
+        .flatten()
+        .and_then(|lock| lock.run_id)
+    {
+        protected.insert(run_id);
+    }
+
+    if dirs.threads().is_dir() {
+        let store = ThreadStore::new(dirs.root())?;
+        for summary in store.list_filtered(&ThreadFilter::in_flight())? {
+            let thread = store.load(&summary.id)?;
+            protected.extend(thread.current_run_id);
+        }
+    }
+    Ok(protected)
+}
+
+/// Every run directory under `runs_dir`.
+fn list_runs(runs_dir: &Path) -> Result<Vec<RunDir>, GcError> {
+    let entries = match std::fs::read_dir(runs_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(GcError::Io(e)),
+    };
+
+    let mut runs = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        let Some(id) = entry.file_name().to_str().map(str::to_string) else {
+            continue;
+        };
+        if !metadata.is_dir() {
+            continue;
+        }
+        let path = entry.path();
+        runs.push(RunDir {
+            id,
+            size: dir_size(&path),
+            modified: metadata.modified()?,
+            path,
+        });
+    }
+    Ok(runs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::thread::Thread;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    /// A run directory holding `bytes` of logs, last modified `age_secs` ago.
+    fn add_run(dirs: &RalfDirs, run_id: &str, age_secs: u64, bytes: usize) {
+        let run_dir = dirs.run(run_id);
+        std::fs::create_dir_all(&run_dir).unwrap();
+        std::fs::write(run_dir.join("log.txt"), vec![b'x'; bytes]).unwrap();
+        let modified = SystemTime::now() - Duration::from_secs(age_secs);
+        std::fs::File::open(&run_dir)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+
+    fn retention(keep_runs: usize) -> RetentionConfig {
+        RetentionConfig {
+            keep_runs,
+            ..RetentionConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_keeps_newest_runs() {
+        let temp = TempDir::new().unwrap();
+        let dirs = RalfDirs::new(temp.path());
+        for (run_id, age) in [("a", 300), ("b", 200), ("c", 100)] {
+            add_run(&dirs, run_id, age, 10);
+        }
+
+        let dry = gc(&dirs, &retention(1), true).unwrap();
+        assert_eq!(dry.runs, ["a", "b"]);
+        assert_eq!(dry.bytes_freed, 20);
+        assert!(dirs.run("a").exists());
+
+        let report = gc(&dirs, &retention(1), false).unwrap();
+        assert_eq!(report, dry);
+        assert!(!dirs.run("a").exists());
+        assert!(!dirs.run("b").exists());
+        assert!(dirs.run("c").exists());
+        assert!(gc(&dirs, &retention(1), false).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_keeps_runs_in_use() {
+        let temp = TempDir::new().unwrap();
+        let dirs = RalfDirs::new(temp.path());
+        for (run_id, age) in [
+            ("latest", 400),
+            ("locked", 300),
+            ("thread", 200),
+            ("done", 100),
+        ] {
+            add_run(&dirs, run_id, age, 10);
+        }
+
+        let mut state = RunState::default();
+        state.start_run_with_id("latest");
+        state.save(&dirs.state()).unwrap();
+        let _lock = RunLock::acquire(dirs.root(), Some("locked"), false).unwrap();
+        let store = ThreadStore::new(dirs.root()).unwrap();
+        let mut thread = Thread::new("Open");
+        thread.current_run_id = Some("thread".to_string());
+        store.save(&thread).unwrap();
+
+        let report = gc(&dirs, &retention(0), false).unwrap();
+        assert_eq!(report.runs, ["done"]);
+    }
+
+    #[test]
+    fn test_size_limit() {
+        let temp = TempDir::new().unwrap();
+        let dirs = RalfDirs::new(temp.path());
+        for (run_id, age) in [("a", 300), ("b", 200), ("c", 100)] {
+            add_run(&dirs, run_id, age, 400 * 1024);
+        }
+        let retention = RetentionConfig {
+            max_ralf_dir_mb: Some(1),
+            ..RetentionConfig::default()
+        };
+
+        // 1.2 MB, so only the oldest has to go
+        let report = gc(&dirs, &retention, false).unwrap();
+        assert_eq!(report.runs, ["a"]);
+    }
+
+    #[test]
+    fn test_removes_clean_worktrees_of_removed_runs() {
+        let temp = TempDir::new().unwrap();
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .current_dir(temp.path())
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success());
+        };
+        git(&["init"]);
+        git(&["commit", "--allow-empty", "-m", "Initial"]);
+        let dirs = RalfDirs::for_repo(temp.path());
+        let repo = GitSafety::new(temp.path());
+        for (run_id, age) in [("clean", 300), ("dirty", 200), ("kept", 100)] {
+            add_run(&dirs, run_id, age, 10);
+            repo.ensure_run_worktree(&dirs.worktrees(), run_id).unwrap();
+        }
+        std::fs::write(dirs.worktrees().join("dirty").join("wip.txt"), "wip").unwrap();
+        // Worktrees don't count towards the size limit
+        std::fs::write(
+            dirs.worktrees().join("kept").join("big.bin"),
+            vec![0; 2 * 1024 * 1024],
+        )
+        .unwrap();
+        let retention = RetentionConfig {
+            keep_runs: 1,
+            max_ralf_dir_mb: Some(1),
+            ..RetentionConfig::default()
+        };
+
+        let dry = gc(&dirs, &retention, true).unwrap();
+        assert_eq!(dry.worktrees, ["clean"]);
+        assert!(dirs.worktrees().join("clean").exists());
+
+        let report = gc(&dirs, &retention, false).unwrap();
+        assert_eq!(report.runs, ["clean", "dirty"]);
+        assert_eq!(report.worktrees, ["clean"]);
+        assert_eq!(report.kept_worktrees, ["dirty"]);
+        assert!(!dirs.worktrees().join("clean").exists());
+        assert!(dirs.worktrees().join("dirty").join("wip.txt").exists());
+        assert!(dirs.worktrees().join("kept").exists());
+        assert!(report
+            .summary()
+            .starts_with("2 runs, 0 changelog entries, 0 draft snapshots and 1 worktree ("));
+    }
+
+    #[test]
+    fn test_prunes_changelog_and_drafts() {
+        let temp = TempDir::new().unwrap();
+        let dirs = RalfDirs::new(temp.path());
+        add_run(&dirs, "old", 200, 10);
+        add_run(&dirs, "new", 100, 10);
+        std::fs::create_dir_all(dirs.changelog()).unwrap();
+        std::fs::write(
+            dirs.changelog().join("claude.md"),
+            "\n## Run old — Iteration 1\n\n- **Status**: success\n\
+             \n## Run new — Iteration 1\n\n- **Status**: success\n\
+             \n## Run old — Iteration 2\n\n- **Status**: error\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dirs.changelog().join("codex.md"),
+            "\n## Run old — Iteration 3\n\n- **Status**: success\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(dirs.drafts()).unwrap();
+        for name in ["20260101_120000", "20260102_120000"] {
+            std::fs::write(dirs.drafts().join(format!("{name}.md")), "draft").unwrap();
+        }
+
+        let retention = RetentionConfig {
+            keep_runs: 1,
+            keep_drafts: 1,
+            ..RetentionConfig::default()
+        };
+        let report = gc(&dirs, &retention, false).unwrap();
+        assert_eq!(report.changelog_entries, 3);
+        assert_eq!(report.drafts, ["20260101_120000"]);
+        assert_eq!(
+            report.summary(),
+            "1 run, 3 changelog entries and 1 draft snapshot (15 B)"
+        );
+
+        let records = ChangelogStore::new(dirs.changelog()).list(None).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].run_id, "new");
+        assert!(!dirs.changelog().join("codex.md").exists());
+    }
+}
@@ -1,16 +1,18 @@
 //! Git safety operations for ralf workflows.
 //!
 //! Provides safe git operations: detecting working tree state, capturing baselines
-//! before implementation, creating thread branches, resetting to baseline on
-//! backward transitions, and generating diffs for review.
+//! before implementation (stashing uncommitted changes if asked), creating
+//! thread branches, resetting to baseline on backward transitions, restoring
+//! the workspace after a failed run, generating diffs for review, and
+//! creating isolated worktrees for runs.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use chrono::Utc;
 use thiserror::Error;
 
-use crate::thread::GitBaseline;
+use crate::thread::{GitBaseline, StashedChanges};
 
 /// Error type for git operations.
 #[derive(Debug, Error)]
@@ -40,6 +42,15 @@ pub enum GitError {
     Io(#[from] std::io::Error),
 }
 
+/// A dedicated worktree for an isolated run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunWorktree {
+    /// Worktree directory (models run here).
+    pub path: PathBuf,
+    /// Branch checked out in the worktree (`ralf/run-<run-id>`).
+    pub branch: String,
+}
+
 /// Git safety operations for a repository.
 pub struct GitSafety {
     repo_path: PathBuf,
@@ -93,6 +104,30 @@ impl GitSafety {
         Ok(stdout.trim().is_empty())
     }
 
+    /// Root of the repository and the path from it to `repo_path` (empty
+    /// at the root).
+    pub fn root_and_prefix(&self) -> Result<(PathBuf, PathBuf), GitError> {
+        self.ensure_repo()?;
+
+        let output = Command::new("git")
+            .args(["rev-parse", "--show-toplevel", "--show-prefix"])
+            .current_dir(&self.repo_path)
+            .output()
+            .map_err(GitError::Io)?;
+
+        if !output.status.success() {
+            return Err(GitError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut lines = stdout.lines();
+        let root = lines.next().unwrap_or_default().trim();
+        let prefix = lines.next().unwrap_or_default().trim();
+        Ok((PathBuf::from(root), PathBuf::from(prefix)))
+    }
+
     /// Get the current branch name.
     /// Returns `DetachedHead` error if not on a branch.
     pub fn current_branch(&self) -> Result<String, GitError> {
@@ -139,19 +174,248 @@ impl GitSafety {
         Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
     }
 
-    /// Capture baseline (current branch + commit SHA).
+    /// Stage all changes (except `.ralf/`) and commit them.
+    /// Returns the new commit SHA, or `None` if there was nothing to commit.
+    pub fn commit_all(&self, message: &str) -> Result<Option<String>, GitError> {
+        self.ensure_repo()?;
+
+        let output = Command::new("git")
+            .args(["add", "-A", "--", ".", ":(exclude).ralf"])
+            .current_dir(&self.repo_path)
+            .output()
+            .map_err(GitError::Io)?;
+
+        if !output.status.success() {
+            return Err(GitError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+
+        // Exit code 0 means nothing is staged
+        let staged = Command::new("git")
+            .args(["diff", "--cached", "--quiet"])
+            .current_dir(&self.repo_path)
+            .status()
+            .map_err(GitError::Io)?;
+        if staged.success() {
+            return Ok(None);
+        }
+
+        let output = Command::new("git")
+            .arg("commit")
+            .arg("-m")
+            .arg(message)
+            .current_dir(&self.repo_path)
+            .output()
+            .map_err(GitError::Io)?;
+
+        if !output.status.success() {
+            return Err(GitError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+
+        self.head_sha().map(Some)
+    }
+
+    /// Hash of the working tree as it is now, including uncommitted and
+    /// untracked changes (but not `.ralf/`).
+    ///
+    /// Stages everything into a throwaway copy of the index and writes it as
+    /// a tree, so the real index is untouched. Identical workspaces give
+    /// identical hashes.
+    pub fn worktree_tree_hash(&self) -> Result<String, GitError> {
+        self.ensure_repo()?;
+
+        let git = |args: &[&str], index: &Path| {
+            let output = Command::new("git")
+                .args(args)
+                .env("GIT_INDEX_FILE", index)
+                .current_dir(&self.repo_path)
+                .output()
+                .map_err(GitError::Io)?;
+            if output.status.success() {
+                Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+            } else {
+                Err(GitError::CommandFailed(
+                    String::from_utf8_lossy(&output.stderr).to_string(),
+                ))
+            }
+        };
+
+        // Start from a copy of the real index so unchanged files aren't rehashed
+        let real_index = Command::new("git")
+            .args(["rev-parse", "--git-path", "index"])
+            .current_dir(&self.repo_path)
+            .output()
+            .map_err(GitError::Io)?;
+        // Relative to the repo unless git returned an absolute path
+        let real_index = self
+            .repo_path
+            .join(String::from_utf8_lossy(&real_index.stdout).trim());
+        let temp_index = std::env::temp_dir().join(format!("ralf-index-{}", uuid::Uuid::new_v4()));
+        if real_index.is_file() {
+            std::fs::copy(&real_index, &temp_index).map_err(GitError::Io)?;
+        }
+
+        let result = git(&["add", "-A", "--", ".", ":(exclude).ralf"], &temp_index)
+            .and_then(|_| git(&["write-tree"], &temp_index));
+        let _ = std::fs::remove_file(&temp_index);
+        result
+    }
+
+    /// Capture baseline (current branch, commit SHA and untracked files).
     /// Returns `GitBaseline` from thread.rs.
     pub fn capture_baseline(&self) -> Result<GitBaseline, GitError> {
         let branch = self.current_branch()?;
         let commit_sha = self.head_sha()?;
+        let untracked_files = self.untracked_files()?;
 
         Ok(GitBaseline {
             branch,
             commit_sha,
             captured_at: Utc::now(),
+            untracked_files,
+            stash: None,
         })
     }
 
+    /// Capture baseline, then stash uncommitted changes so the run starts
+    /// from a clean tree. The stash is recorded in the baseline for
+    /// [`restore`](Self::restore).
+    pub fn capture_baseline_stashing(&self, message: &str) -> Result<GitBaseline, GitError> {
+        let mut baseline = self.capture_baseline()?;
+        baseline.stash = self.stash_changes(message)?;
+        Ok(baseline)
+    }
+
+    /// Untracked, non-ignored files (outside `.ralf/`).
+    pub fn untracked_files(&self) -> Result<Vec<String>, GitError> {
+        self.ensure_repo()?;
+
+        let output = Command::new("git")
+            .args(["ls-files", "--others", "--exclude-standard", "-z"])
+            .args(["--", ".", ":(exclude).ralf"])
+            .current_dir(&self.repo_path)
+            .output()
+            .map_err(GitError::Io)?;
+
+        if !output.status.success() {
+            return Err(GitError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .split('\0')
+            .filter(|path| !path.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Stash uncommitted changes, untracked files included (but not
+    /// `.ralf/`). Returns `None` if there was nothing to stash.
+    pub fn stash_changes(&self, message: &str) -> Result<Option<StashedChanges>, GitError> {
+        self.ensure_repo()?;
+
+        let status = Command::new("git")
+            .args(["status", "--porcelain", "--", ".", ":(exclude).ralf"])
+            .current_dir(&self.repo_path)
+            .output()
+            .map_err(GitError::Io)?;
+        if !status.status.success() {
+            return Err(GitError::CommandFailed(
+                String::from_utf8_lossy(&status.stderr).to_string(),
+            ));
+        }
+        let files = porcelain_paths(&String::from_utf8_lossy(&status.stdout));
+        if files.is_empty() {
+            return Ok(None);
+        }
+
+        let output = Command::new("git")
+            .args(["stash", "push", "--include-untracked", "-m", message])
+            .args(["--", ".", ":(exclude).ralf"])
+            .current_dir(&self.repo_path)
+            .output()
+            .map_err(GitError::Io)?;
+        if !output.status.success() {
+            return Err(GitError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+
+        let sha = Command::new("git")
+            .args(["rev-parse", "--verify", "refs/stash"])
+            .current_dir(&self.repo_path)
+            .output()
+            .map_err(GitError::Io)?;
+        if !sha.status.success() {
+            return Err(GitError::CommandFailed(
+                String::from_utf8_lossy(&sha.stderr).to_string(),
+            ));
+        }
+
+        Ok(Some(StashedChanges {
+            sha: String::from_utf8_lossy(&sha.stdout).trim().to_string(),
+            files,
+        }))
+    }
+
+    /// Put the workspace back the way `baseline` found it: the branch at
+    /// the baseline commit, without files the run created, and with any
+    /// stashed changes applied again (and dropped from the stash list).
+    /// WARNING: Destructive - discards the run's commits and changes. Use
+    /// with user confirmation.
+    pub fn restore(&self, baseline: &GitBaseline) -> Result<(), GitError> {
+        self.reset_to_baseline(baseline)?;
+
+        // Untracked files the run left would block the stash, or weren't there
+        for path in self.untracked_files()? {
+            let kept = baseline.stash.is_none() && baseline.untracked_files.contains(&path);
+            if !kept {
+                match std::fs::remove_file(self.repo_path.join(&path)) {
+                    Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                        return Err(GitError::Io(e));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let Some(stash) = &baseline.stash else {
+            return Ok(());
+        };
+        Self::validate_commit_sha(&stash.sha)?;
+        let output = Command::new("git")
+            .args(["stash", "apply", &stash.sha])
+            .current_dir(&self.repo_path)
+            .output()
+            .map_err(GitError::Io)?;
+        if !output.status.success() {
+            return Err(GitError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+
+        // Drop the entry if it is still in the list
+        let list = Command::new("git")
+            .args(["stash", "list", "--format=%H"])
+            .current_dir(&self.repo_path)
+            .output()
+            .map_err(GitError::Io)?;
+        let index = String::from_utf8_lossy(&list.stdout)
+            .lines()
+            .position(|sha| sha.trim() == stash.sha);
+        if let Some(index) = index {
+            let _ = Command::new("git")
+                .args(["stash", "drop", &format!("stash@{{{index}}}")])
+                .current_dir(&self.repo_path)
+                .output();
+        }
+        Ok(())
+    }
+
     /// Validate that a `thread_id` is safe for use in branch names.
     /// Only allows: alphanumeric, dash, underscore.
     pub fn validate_thread_id(thread_id: &str) -> Result<(), GitError> {
@@ -277,6 +541,160 @@ impl GitSafety {
         Ok(())
     }
 
+    /// Switch to the thread branch `ralf/<slug>`, creating it from HEAD if
+    /// it doesn't exist. Returns the branch name.
+    pub fn switch_to_thread_branch(&self, slug: &str) -> Result<String, GitError> {
+        Self::validate_thread_id(slug)?;
+        let branch = format!("ralf/{slug}");
+        self.switch_or_create_branch(&branch)?;
+        Ok(branch)
+    }
+
+    /// Switch to `branch`, creating it from HEAD if it doesn't exist.
+    /// Uncommitted changes come along. Returns whether it was created.
+    pub fn switch_or_create_branch(&self, branch: &str) -> Result<bool, GitError> {
+        self.ensure_repo()?;
+        self.validate_branch_name(branch)?;
+
+        let exists = Command::new("git")
+            .args(["show-ref", "--verify", "--quiet"])
+            .arg(format!("refs/heads/{branch}"))
+            .current_dir(&self.repo_path)
+            .status()
+            .map_err(GitError::Io)?
+            .success();
+        if exists {
+            self.checkout(branch)?;
+            return Ok(false);
+        }
+
+        let output = Command::new("git")
+            .args(["switch", "-c"])
+            .arg(branch)
+            .current_dir(&self.repo_path)
+            .output()
+            .map_err(GitError::Io)?;
+
+        if !output.status.success() {
+            return Err(GitError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+
+        Ok(true)
+    }
+
+    /// Whether every commit on `branch` is already on `into`.
+    pub fn is_merged(&self, branch: &str, into: &str) -> Result<bool, GitError> {
+        self.ensure_repo()?;
+        self.validate_branch_name(branch)?;
+        self.validate_branch_name(into)?;
+
+        let output = Command::new("git")
+            .args(["merge-base", "--is-ancestor"])
+            .args([branch, into])
+            .current_dir(&self.repo_path)
+            .output()
+            .map_err(GitError::Io)?;
+
+        // Exit code 1 means "not an ancestor"; anything else is an error
+        match output.status.code() {
+            Some(0) => Ok(true),
+            Some(1) => Ok(false),
+            _ => Err(GitError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            )),
+        }
+    }
+
+    /// URL of the remote named `remote`.
+    pub fn remote_url(&self, remote: &str) -> Result<String, GitError> {
+        self.ensure_repo()?;
+
+        let output = Command::new("git")
+            .args(["remote", "get-url", "--"])
+            .arg(remote)
+            .current_dir(&self.repo_path)
+            .output()
+            .map_err(GitError::Io)?;
+
+        if !output.status.success() {
+            return Err(GitError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// Push `branch` to `remote` and make it the branch's upstream.
+    pub fn push_branch(&self, remote: &str, branch: &str) -> Result<(), GitError> {
+        self.ensure_repo()?;
+        self.validate_branch_name(branch)?;
+        if remote.starts_with('-') {
+            return Err(GitError::InvalidName(remote.to_string()));
+        }
+
+        let output = Command::new("git")
+            .args(["push", "--set-upstream"])
+            .args([remote, branch])
+            .current_dir(&self.repo_path)
+            .output()
+            .map_err(GitError::Io)?;
+
+        if !output.status.success() {
+            return Err(GitError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Full message of the commit `sha`.
+    pub fn commit_message(&self, sha: &str) -> Result<String, GitError> {
+        self.ensure_repo()?;
+        Self::validate_commit_sha(sha)?;
+
+        let output = Command::new("git")
+            .args(["log", "-1", "--format=%B"])
+            .arg(sha)
+            .current_dir(&self.repo_path)
+            .output()
+            .map_err(GitError::Io)?;
+
+        if !output.status.success() {
+            return Err(GitError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .trim_end()
+            .to_string())
+    }
+
+    /// Validate that `branch` is a usable branch name that can't be taken
+    /// for an option.
+    fn validate_branch_name(&self, branch: &str) -> Result<(), GitError> {
+        if branch.starts_with('-') {
+            return Err(GitError::InvalidName(branch.to_string()));
+        }
+        let valid = Command::new("git")
+            .args(["check-ref-format", "--branch"])
+            .arg(branch)
+            .current_dir(&self.repo_path)
+            .output()
+            .map_err(GitError::Io)?
+            .status
+            .success();
+        if valid {
+            Ok(())
+        } else {
+            Err(GitError::InvalidName(branch.to_string()))
+        }
+    }
+
     /// Checkout a branch.
     pub fn checkout(&self, branch: &str) -> Result<(), GitError> {
         self.ensure_repo()?;
@@ -377,6 +795,198 @@ impl GitSafety {
         Ok(String::from_utf8_lossy(&output.stdout).to_string())
     }
 
+    /// Get diff stats for the changes made by the commits `first..=last`.
+    pub fn diff_stat_commits(&self, first: &str, last: &str) -> Result<String, GitError> {
+        self.ensure_repo()?;
+        Self::validate_commit_sha(first)?;
+        Self::validate_commit_sha(last)?;
+
+        let output = Command::new("git")
+            .arg("diff")
+            .arg("--stat")
+            .arg(format!("{first}^"))
+            .arg(last)
+            .current_dir(&self.repo_path)
+            .output()
+            .map_err(GitError::Io)?;
+
+        if !output.status.success() {
+            return Err(GitError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    /// `git diff --numstat` between two trees, as returned by
+    /// [`worktree_tree_hash`](Self::worktree_tree_hash). Renames count as a
+    /// deletion and an addition.
+    pub fn diff_numstat_trees(&self, from: &str, to: &str) -> Result<String, GitError> {
+        self.diff_tree_pair(&["--numstat"], from, to)
+    }
+
+    /// `git diff -U0` between two trees, as returned by
+    /// [`worktree_tree_hash`](Self::worktree_tree_hash): just the changed
+    /// lines, with unusual paths left unquoted.
+    pub fn diff_trees(&self, from: &str, to: &str) -> Result<String, GitError> {
+        self.diff_tree_pair(&["-U0", "--no-color", "--no-ext-diff"], from, to)
+    }
+
+    fn diff_tree_pair(&self, flags: &[&str], from: &str, to: &str) -> Result<String, GitError> {
+        self.ensure_repo()?;
+        Self::validate_commit_sha(from)?;
+        Self::validate_commit_sha(to)?;
+
+        let output = Command::new("git")
+            .args(["-c", "core.quotePath=false", "diff", "--no-renames"])
+            .args(flags)
+            .args([from, to])
+            .current_dir(&self.repo_path)
+            .output()
+            .map_err(GitError::Io)?;
+
+        if !output.status.success() {
+            return Err(GitError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    /// Put `path` back the way it was in `tree` (as returned by
+    /// [`worktree_tree_hash`](Self::worktree_tree_hash)), deleting it if it
+    /// wasn't there. Only the working tree changes, not the index.
+    pub fn restore_from_tree(&self, tree: &str, path: &str) -> Result<(), GitError> {
+        self.ensure_repo()?;
+        Self::validate_commit_sha(tree)?;
+
+        let existed = Command::new("git")
+            .args(["cat-file", "-e", &format!("{tree}:{path}")])
+            .current_dir(&self.repo_path)
+            .output()
+            .map_err(GitError::Io)?
+            .status
+            .success();
+        if !existed {
+            return match std::fs::remove_file(self.repo_path.join(path)) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(GitError::Io(e)),
+                _ => Ok(()),
+            };
+        }
+
+        let output = Command::new("git")
+            .args(["restore", "--worktree", &format!("--source={tree}"), "--"])
+            .arg(format!(":(literal){path}"))
+            .current_dir(&self.repo_path)
+            .output()
+            .map_err(GitError::Io)?;
+
+        if !output.status.success() {
+            return Err(GitError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Create the worktree for a run at `<worktrees_dir>/<run_id>`, on a new
+    /// `ralf/run-<run_id>` branch from HEAD.
+    /// Reuses the worktree if it already exists (e.g., when resuming a run).
+    pub fn ensure_run_worktree(
+        &self,
+        worktrees_dir: &Path,
+        run_id: &str,
+    ) -> Result<RunWorktree, GitError> {
+        self.ensure_repo()?;
+        Self::validate_thread_id(run_id)?;
+
+        let path = worktrees_dir.join(run_id);
+        let branch = format!("ralf/run-{run_id}");
+
+        if path.join(".git").exists() {
+            return Ok(RunWorktree { path, branch });
+        }
+
+        std::fs::create_dir_all(worktrees_dir)?;
+
+        let branch_exists = Command::new("git")
+            .args(["show-ref", "--verify", "--quiet"])
+            .arg(format!("refs/heads/{branch}"))
+            .current_dir(&self.repo_path)
+            .output()
+            .is_ok_and(|out| out.status.success());
+
+        // Check out the existing branch, or create it from HEAD
+        let mut cmd = Command::new("git");
+        cmd.args(["worktree", "add"]);
+        if branch_exists {
+            cmd.arg("--").arg(&path).arg(&branch);
+        } else {
+            cmd.arg("-b").arg(&branch).arg("--").arg(&path);
+        }
+        let output = cmd
+            .current_dir(&self.repo_path)
+            .output()
+            .map_err(GitError::Io)?;
+
+        if !output.status.success() {
+            return Err(GitError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+
+        Ok(RunWorktree { path, branch })
+    }
+
+    /// Remove a run's worktree from `<worktrees_dir>/<run_id>`, and its
+    /// `ralf/run-<run_id>` branch if that is merged into HEAD (or never
+    /// moved). A worktree with uncommitted changes is kept, and so is its
+    /// branch: returns whether the worktree is gone.
+    pub fn remove_run_worktree(
+        &self,
+        worktrees_dir: &Path,
+        run_id: &str,
+    ) -> Result<bool, GitError> {
+        self.ensure_repo()?;
+        Self::validate_thread_id(run_id)?;
+
+        let path = worktrees_dir.join(run_id);
+        if path.join(".git").exists() {
+            if !GitSafety::new(&path).is_clean()? {
+                return Ok(false);
+            }
+            let output = Command::new("git")
+                .args(["worktree", "remove", "--"])
+                .arg(&path)
+                .current_dir(&self.repo_path)
+                .output()
+                .map_err(GitError::Io)?;
+            if !output.status.success() {
+                return Err(GitError::CommandFailed(
+                    String::from_utf8_lossy(&output.stderr).to_string(),
+                ));
+            }
+        } else {
+            // Deleted by hand: forget it so the branch can go
+            Command::new("git")
+                .args(["worktree", "prune"])
+                .current_dir(&self.repo_path)
+                .output()
+                .map_err(GitError::Io)?;
+        }
+
+        // `-d` keeps a branch with commits HEAD doesn't have
+        Command::new("git")
+            .args(["branch", "-d", "--"])
+            .arg(format!("ralf/run-{run_id}"))
+            .current_dir(&self.repo_path)
+            .output()
+            .map_err(GitError::Io)?;
+        Ok(true)
+    }
+
     /// Helper to ensure we're in a git repo.
     fn ensure_repo(&self) -> Result<(), GitError> {
         if !self.is_repo() {
@@ -386,6 +996,20 @@ impl GitSafety {
     }
 }
 
+/// Paths listed in `git status --porcelain` output: the new path for
+/// renames, unquoted. Tolerates CRLF line endings, as some Windows git
+/// setups produce.
+pub fn porcelain_paths(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(|line| line.trim_end_matches('\r'))
+        .filter_map(|line| line.get(3..))
+        .map(|path| path.rsplit(" -> ").next().unwrap_or(path))
+        .map(|path| path.trim_matches('"').to_string())
+        .filter(|path| !path.is_empty())
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -528,6 +1152,43 @@ mod tests {
         assert!(sha.chars().all(|c| c.is_ascii_hexdigit()));
     }
 
+    #[test]
+    fn test_worktree_tree_hash_tracks_changes() {
+        let (temp, git) = setup_test_repo();
+        let clean = git.worktree_tree_hash().unwrap();
+        assert_eq!(clean.len(), 40);
+
+        std::fs::write(temp.path().join("new.txt"), "hello").unwrap();
+        let dirty = git.worktree_tree_hash().unwrap();
+        assert_ne!(clean, dirty);
+        assert_eq!(dirty, git.worktree_tree_hash().unwrap());
+        // The real index is left alone
+        assert!(!git.is_clean().unwrap());
+
+        // `.ralf/` doesn't count
+        std::fs::create_dir_all(temp.path().join(".ralf")).unwrap();
+        std::fs::write(temp.path().join(".ralf/state.json"), "{}").unwrap();
+        assert_eq!(dirty, git.worktree_tree_hash().unwrap());
+
+        std::fs::remove_file(temp.path().join("new.txt")).unwrap();
+        assert_eq!(clean, git.worktree_tree_hash().unwrap());
+    }
+
+    #[test]
+    fn test_diff_numstat_trees() {
+        let (temp, git) = setup_test_repo();
+        let before = git.worktree_tree_hash().unwrap();
+        std::fs::write(temp.path().join("new.txt"), "one\ntwo\n").unwrap();
+        let after = git.worktree_tree_hash().unwrap();
+
+        let numstat = git.diff_numstat_trees(&before, &after).unwrap();
+        assert_eq!(numstat.trim(), "2\t0\tnew.txt");
+        assert!(git.diff_numstat_trees("--output=x", &after).is_err());
+
+        let diff = git.diff_trees(&before, &after).unwrap();
+        assert!(diff.contains("+++ b/new.txt\n@@ -0,0 +1,2 @@\n+one\n+two\n"));
+    }
+
     #[test]
     fn test_capture_baseline() {
         let (_temp, git) = setup_test_repo();
@@ -535,6 +1196,121 @@ mod tests {
 
         assert!(baseline.branch == "main" || baseline.branch == "master");
         assert_eq!(baseline.commit_sha.len(), 40);
+        assert!(baseline.untracked_files.is_empty());
+        assert!(baseline.stash.is_none());
+    }
+
+    #[test]
+    fn test_thread_branch_lifecycle() {
+        let (temp, git) = setup_test_repo();
+        let base = git.current_branch().unwrap();
+
+        let branch = git.switch_to_thread_branch("add-login-1b9d6bcd").unwrap();
+        assert_eq!(branch, "ralf/add-login-1b9d6bcd");
+        assert_eq!(git.current_branch().unwrap(), branch);
+        assert!(git.is_merged(&branch, &base).unwrap());
+
+        fs::write(temp.path().join("login.rs"), "fn login() {}\n").unwrap();
+        git.commit_all("Add login").unwrap();
+        assert!(!git.is_merged(&branch, &base).unwrap());
+
+        // Switching again reuses the branch
+        git.checkout(&base).unwrap();
+        assert!(!git.switch_or_create_branch(&branch).unwrap());
+        assert_eq!(git.current_branch().unwrap(), branch);
+
+        assert!(git.switch_or_create_branch("--force").is_err());
+        assert!(git.switch_or_create_branch("bad..name").is_err());
+        assert!(git.switch_to_thread_branch("no/slashes").is_err());
+    }
+
+    #[test]
+    fn test_push_branch() {
+        let (temp, git) = setup_test_repo();
+        let remote = TempDir::new().unwrap();
+        assert!(Command::new("git")
+            .args(["init", "--bare"])
+            .current_dir(remote.path())
+            .output()
+            .unwrap()
+            .status
+            .success());
+        assert!(Command::new("git")
+            .args(["remote", "add", "origin"])
+            .arg(remote.path())
+            .current_dir(temp.path())
+            .output()
+            .unwrap()
+            .status
+            .success());
+        assert_eq!(
+            git.remote_url("origin").unwrap(),
+            remote.path().display().to_string()
+        );
+        assert!(git.remote_url("upstream").is_err());
+
+        let branch = git.switch_to_thread_branch("add-login-1b9d6bcd").unwrap();
+        fs::write(temp.path().join("login.rs"), "fn login() {}\n").unwrap();
+        let sha = git
+            .commit_all("feat: add login\n\nUsers can sign in.")
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            git.commit_message(&sha).unwrap(),
+            "feat: add login\n\nUsers can sign in."
+        );
+
+        git.push_branch("origin", &branch).unwrap();
+        let pushed = Command::new("git")
+            .args(["rev-parse", &branch])
+            .current_dir(remote.path())
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&pushed.stdout).trim(), sha);
+        assert!(git.push_branch("--mirror", &branch).is_err());
+    }
+
+    #[test]
+    fn test_stash_and_restore() {
+        let (temp, git) = setup_test_repo();
+        let path = temp.path();
+        fs::write(path.join("README.md"), "# Edited\n").unwrap();
+        fs::write(path.join("notes.txt"), "mine\n").unwrap();
+
+        let baseline = git.capture_baseline_stashing("ralf: before run").unwrap();
+        assert_eq!(baseline.untracked_files, vec!["notes.txt"]);
+        let stash = baseline.stash.clone().unwrap();
+        assert_eq!(stash.sha.len(), 40);
+        assert_eq!(stash.files, vec!["README.md", "notes.txt"]);
+        assert!(git.is_clean().unwrap());
+
+        // The run edits, commits and leaves artifacts behind
+        fs::write(path.join("README.md"), "# Run\n").unwrap();
+        fs::write(path.join("notes.txt"), "model's\n").unwrap();
+        git.commit_all("run work").unwrap();
+        fs::write(path.join("artifact.log"), "junk\n").unwrap();
+
+        git.restore(&baseline).unwrap();
+        assert_eq!(git.head_sha().unwrap(), baseline.commit_sha);
+        assert_eq!(
+            fs::read_to_string(path.join("README.md")).unwrap(),
+            "# Edited\n"
+        );
+        assert_eq!(
+            fs::read_to_string(path.join("notes.txt")).unwrap(),
+            "mine\n"
+        );
+        assert!(!path.join("artifact.log").exists());
+        let stashes = Command::new("git")
+            .args(["stash", "list"])
+            .current_dir(path)
+            .output()
+            .unwrap();
+        assert!(stashes.stdout.is_empty());
+
+        // Nothing to stash on a clean tree
+        git.commit_all("save").unwrap();
+        assert!(git.stash_changes("ralf").unwrap().is_none());
     }
 
     #[test]
@@ -653,6 +1429,99 @@ mod tests {
         assert!(matches!(result, Err(GitError::InvalidName(_))));
     }
 
+    #[test]
+    fn test_ensure_run_worktree() {
+        let (temp, git) = setup_test_repo();
+        let worktrees_dir = temp.path().join(".ralf").join("worktrees");
+
+        let worktree = git.ensure_run_worktree(&worktrees_dir, "abc123").unwrap();
+        assert_eq!(worktree.branch, "ralf/run-abc123");
+        assert_eq!(worktree.path, worktrees_dir.join("abc123"));
+        assert!(worktree.path.join("README.md").exists());
+        assert_eq!(
+            GitSafety::new(&worktree.path).current_branch().unwrap(),
+            "ralf/run-abc123"
+        );
+
+        // Main working tree stays on its original branch
+        assert_ne!(git.current_branch().unwrap(), "ralf/run-abc123");
+
+        // Reused on resume
+        let again = git.ensure_run_worktree(&worktrees_dir, "abc123").unwrap();
+        assert_eq!(again, worktree);
+    }
+
+    #[test]
+    fn test_remove_run_worktree() {
+        let (temp, git) = setup_test_repo();
+        let worktrees_dir = temp.path().join(".ralf").join("worktrees");
+        let branch_exists = |branch: &str| {
+            Command::new("git")
+                .args(["show-ref", "--verify", "--quiet"])
+                .arg(format!("refs/heads/{branch}"))
+                .current_dir(temp.path())
+                .status()
+                .unwrap()
+                .success()
+        };
+
+        // Unchanged: worktree and branch go
+        let worktree = git.ensure_run_worktree(&worktrees_dir, "empty").unwrap();
+        assert!(git.remove_run_worktree(&worktrees_dir, "empty").unwrap());
+        assert!(!worktree.path.exists());
+        assert!(!branch_exists("ralf/run-empty"));
+
+        // Uncommitted changes: both stay
+        let worktree = git.ensure_run_worktree(&worktrees_dir, "dirty").unwrap();
+        std::fs::write(worktree.path.join("new.txt"), "work").unwrap();
+        assert!(!git.remove_run_worktree(&worktrees_dir, "dirty").unwrap());
+        assert!(worktree.path.join("new.txt").exists());
+        assert!(branch_exists("ralf/run-dirty"));
+
+        // Committed but unmerged: the worktree goes, the branch stays
+        let committed = GitSafety::new(&worktree.path);
+        committed.commit_all("Add new.txt").unwrap();
+        assert!(git.remove_run_worktree(&worktrees_dir, "dirty").unwrap());
+        assert!(!worktree.path.exists());
+        assert!(branch_exists("ralf/run-dirty"));
+    }
+
+    #[test]
+    fn test_ensure_run_worktree_invalid_id() {
+        let (temp, git) = setup_test_repo();
+
+        let result = git.ensure_run_worktree(temp.path(), "../escape");
+        assert!(matches!(result, Err(GitError::InvalidName(_))));
+    }
+
+    #[test]
+    fn test_commit_all() {
+        let (temp, git) = setup_test_repo();
+        let before = git.head_sha().unwrap();
+
+        // Nothing to commit
+        assert!(git.commit_all("empty").unwrap().is_none());
+
+        fs::write(temp.path().join("new.txt"), "hello").unwrap();
+        fs::create_dir_all(temp.path().join(".ralf")).unwrap();
+        fs::write(temp.path().join(".ralf").join("state.json"), "{}").unwrap();
+
+        let sha = git
+            .commit_all("ralf(claude): iter 1 (run abc)")
+            .unwrap()
+            .unwrap();
+        assert_ne!(sha, before);
+        assert_eq!(sha, git.head_sha().unwrap());
+
+        // .ralf is never committed
+        let output = Command::new("git")
+            .args(["status", "--porcelain"])
+            .current_dir(temp.path())
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "?? .ralf/");
+    }
+
     #[test]
     fn test_delete_thread_branch() {
         let (_temp, git) = setup_test_repo();
@@ -800,4 +1669,14 @@ mod tests {
         // Should return false for non-repo, not error
         assert!(!git.thread_branch_exists("anything"));
     }
+
+    #[test]
+    fn test_porcelain_paths() {
+        let output = " M src/lib.rs\r\n?? notes/\r\nR  old.rs -> new.rs\r\n?? \"with space.txt\"\r";
+        assert_eq!(
+            porcelain_paths(output),
+            vec!["src/lib.rs", "notes/", "new.rs", "with space.txt"]
+        );
+        assert!(porcelain_paths("").is_empty());
+    }
 }
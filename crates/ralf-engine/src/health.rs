@@ -0,0 +1,542 @@
+//! Repository and environment health checks for `ralf doctor`.
+//!
+//! Unlike preflight, which gates a single thread's run, these checks look at
+//! everything a run depends on: git, the repo's `.ralf/` directory,
+//! `PROMPT.md`, the config, free disk space, the run lock, and the clock.
+//! Each check reports pass, warn, or fail with a remediation hint.
+
+use std::path::Path;
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::dirs::RalfDirs;
+use crate::git::GitSafety;
+use crate::lock::{LockError, LockInfo, LockStatus, RunLock};
+use crate::spec::{lint, Severity};
+use crate::state::{current_timestamp, Cooldowns};
+
+/// Oldest git with `git worktree remove`, which `ralf gc` uses to remove
+/// the worktrees of isolated runs.
+pub const MIN_GIT_VERSION: (u32, u32) = (2, 17);
+
+/// Warn when the filesystem holding `.ralf/` has less free space than this.
+pub const LOW_DISK_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// Fail when the filesystem holding `.ralf/` has less free space than this.
+pub const CRITICAL_DISK_BYTES: u64 = 100 * 1024 * 1024;
+
+/// Recorded timestamps further than this in the future indicate clock skew.
+pub const CLOCK_SKEW_TOLERANCE_SECS: u64 = 60;
+
+/// Outcome of a health check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    /// Nothing to do.
+    Pass,
+    /// Runs will work, but something deserves attention.
+    Warn,
+    /// Runs will fail until this is fixed.
+    Fail,
+}
+
+impl std::fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Pass => write!(f, "pass"),
+            Self::Warn => write!(f, "warn"),
+            Self::Fail => write!(f, "fail"),
+        }
+    }
+}
+
+/// A single health check result.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthCheck {
+    /// Check identifier (e.g., `git`, `disk_space`).
+    pub name: String,
+    /// Human-readable label (e.g., "Disk Space").
+    pub label: String,
+    /// Whether the check passed, warned, or failed.
+    pub status: CheckStatus,
+    /// What was found.
+    pub message: String,
+    /// How to fix it, when the check didn't pass.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remediation: Option<String>,
+}
+
+impl HealthCheck {
+    fn new(name: &str, label: &str, status: CheckStatus, message: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            label: label.to_string(),
+            status,
+            message: message.into(),
+            remediation: None,
+        }
+    }
+
+    fn pass(name: &str, label: &str, message: impl Into<String>) -> Self {
+        Self::new(name, label, CheckStatus::Pass, message)
+    }
+
+    fn warn(name: &str, label: &str, message: impl Into<String>) -> Self {
+        Self::new(name, label, CheckStatus::Warn, message)
+    }
+
+    fn fail(name: &str, label: &str, message: impl Into<String>) -> Self {
+        Self::new(name, label, CheckStatus::Fail, message)
+    }
+
+    fn fix(mut self, remediation: impl Into<String>) -> Self {
+        self.remediation = Some(remediation.into());
+        self
+    }
+}
+
+/// Results of all health checks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthReport {
+    /// Individual check results, in the order they ran.
+    pub checks: Vec<HealthCheck>,
+}
+
+impl HealthReport {
+    /// The worst status of any check.
+    pub fn status(&self) -> CheckStatus {
+        self.checks
+            .iter()
+            .map(|c| c.status)
+            .max()
+            .unwrap_or(CheckStatus::Pass)
+    }
+
+    /// Get a summary message suitable for display.
+    pub fn summary(&self) -> String {
+        let count = |status| self.checks.iter().filter(|c| c.status == status).count();
+        format!(
+            "{} passed, {} warning(s), {} failed",
+            count(CheckStatus::Pass),
+            count(CheckStatus::Warn),
+            count(CheckStatus::Fail)
+        )
+    }
+}
+
+/// Run all health checks for the repository at `repo_path`.
+///
+/// All checks run regardless of earlier failures, so the user sees every
+/// issue at once.
+pub fn run_health_checks(repo_path: &Path) -> HealthReport {
+    let config = Config::load_layered(repo_path)
+        .map(|layered| layered.config)
+        .unwrap_or_default();
+    let dirs = RalfDirs::for_repo(repo_path).configured(&config);
+    let ralf_dir = dirs.root();
+    let now = current_timestamp();
+
+    let checks = vec![
+        check_git(git_version().as_deref()),
+        check_repo(repo_path),
+        check_prompt(repo_path),
+        check_config(repo_path),
+        check_disk_space(ralf_dir),
+        check_lock(RunLock::read(ralf_dir)),
+        check_clock(&dirs, now),
+    ];
+
+    HealthReport { checks }
+}
+
+/// Output of `git --version`, or `None` if git can't be run.
+fn git_version() -> Option<String> {
+    let output = Command::new("git").arg("--version").output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Parse "git version 2.39.2 (Apple Git-143)" into (2, 39).
+fn parse_git_version(version: &str) -> Option<(u32, u32)> {
+    let number = version
+        .split_whitespace()
+        .find(|word| word.starts_with(|c: char| c.is_ascii_digit()))?;
+    let mut parts = number.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+/// Check that git is installed and recent enough for run worktrees.
+fn check_git(version: Option<&str>) -> HealthCheck {
+    const NAME: &str = "git";
+    const LABEL: &str = "Git";
+
+    let Some(version) = version else {
+        return HealthCheck::fail(NAME, LABEL, "git not found on PATH")
+            .fix("Install git and add it to PATH");
+    };
+
+    match parse_git_version(version) {
+        Some(found) if found < MIN_GIT_VERSION => HealthCheck::warn(
+            NAME,
+            LABEL,
+            format!(
+                "{version} is older than {}.{}; isolated runs may fail",
+                MIN_GIT_VERSION.0, MIN_GIT_VERSION.1
+            ),
+        )
+        .fix(format!(
+            "Upgrade git to {}.{} or later",
+            MIN_GIT_VERSION.0, MIN_GIT_VERSION.1
+        )),
+        Some(_) => HealthCheck::pass(NAME, LABEL, version),
+        None => HealthCheck::warn(NAME, LABEL, format!("Unrecognized version: {version}")),
+    }
+}
+
+/// Check that the repo is under git and has been initialized for ralf.
+fn check_repo(repo_path: &Path) -> HealthCheck {
+    const NAME: &str = "repo";
+    const LABEL: &str = "Repository";
+
+    if !GitSafety::new(repo_path).is_repo() {
+        return HealthCheck::fail(NAME, LABEL, "Not a git repository")
+            .fix("Run `git init` so ralf can checkpoint and roll back changes");
+    }
+    if !repo_path.join(".ralf").is_dir() {
+        return HealthCheck::warn(NAME, LABEL, "No .ralf/ directory").fix("Run `ralf init`");
+    }
+    HealthCheck::pass(NAME, LABEL, "Git repository initialized for ralf")
+}
+
+/// Check that `PROMPT.md` exists and lints cleanly.
+fn check_prompt(repo_path: &Path) -> HealthCheck {
+    const NAME: &str = "prompt";
+    const LABEL: &str = "PROMPT.md";
+
+    let path = repo_path.join("PROMPT.md");
+    let prompt = match std::fs::read_to_string(&path) {
+        Ok(prompt) => prompt,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return HealthCheck::warn(NAME, LABEL, "No PROMPT.md")
+                .fix("Draft one in the shell, or run `ralf init --template feature`");
+        }
+        Err(e) => {
+            return HealthCheck::fail(NAME, LABEL, format!("Can't read PROMPT.md: {e}"))
+                .fix("Check the file's permissions and encoding");
+        }
+    };
+
+    let diagnostics = lint(&prompt);
+    let describe = |severity| {
+        diagnostics
+            .iter()
+            .filter(|d| d.severity == severity)
+            .map(|d| match d.line {
+                Some(line) => format!("line {line}: {}", d.message),
+                None => d.message.clone(),
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let errors = describe(Severity::Error);
+    if !errors.is_empty() {
+        return HealthCheck::fail(NAME, LABEL, errors.join("; "))
+            .fix("Fix the errors above, or edit the spec in the shell");
+    }
+    let warnings = describe(Severity::Warning);
+    if !warnings.is_empty() {
+        return HealthCheck::warn(NAME, LABEL, warnings.join("; "));
+    }
+    let criteria = crate::parse_criteria(&prompt).len();
+    HealthCheck::pass(NAME, LABEL, format!("Valid, {criteria} criteria"))
+}
+
+/// Check that the global and repo configs load and validate.
+fn check_config(repo_path: &Path) -> HealthCheck {
+    const NAME: &str = "config";
+    const LABEL: &str = "Configuration";
+
+    match Config::load_layered(repo_path) {
+        Ok(layered) if layered.repo_path.exists() => HealthCheck::pass(
+            NAME,
+            LABEL,
+            format!("{} is valid", layered.repo_path.display()),
+        ),
+        Ok(_) => HealthCheck::pass(NAME, LABEL, "Using defaults (no repo config)"),
+        Err(e) => HealthCheck::fail(NAME, LABEL, e.to_string())
+            .fix("Fix the config file, or move it aside and run `ralf init`"),
+    }
+}
+
+/// Total size of the files under `dir`.
+pub(crate) fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(kind) if kind.is_dir() => dir_size(&entry.path()),
+            Ok(kind) if kind.is_file() => entry.metadata().map_or(0, |m| m.len()),
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Free space available to this user on the filesystem holding `path`.
+#[cfg(unix)]
+// Block counts are narrower than u64 on some platforms
+#[allow(clippy::useless_conversion)]
+fn free_space(path: &Path) -> Option<u64> {
+    let stat = nix::sys::statvfs::statvfs(path).ok()?;
+    Some(u64::from(stat.blocks_available()) * stat.fragment_size())
+}
+
+#[cfg(not(unix))]
+fn free_space(_path: &Path) -> Option<u64> {
+    None
+}
+
+/// Format a byte count for messages (e.g., "1.5 GB").
+pub(crate) fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    #[allow(clippy::cast_precision_loss)]
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+/// Check how much `.ralf/` holds and how much room runs have left.
+fn check_disk_space(ralf_dir: &Path) -> HealthCheck {
+    let used = dir_size(ralf_dir);
+    // Before `ralf init`, runs will be written next to where .ralf/ will be
+    let target = if ralf_dir.exists() {
+        ralf_dir
+    } else {
+        ralf_dir.parent().unwrap_or(ralf_dir)
+    };
+    disk_space_check(used, free_space(target))
+}
+
+fn disk_space_check(used: u64, free: Option<u64>) -> HealthCheck {
+    const NAME: &str = "disk_space";
+    const LABEL: &str = "Disk Space";
+    const PRUNE: &str = "Free up space, or run `ralf gc` to prune old runs";
+
+    let used_text = format!("{} used by .ralf/", format_bytes(used));
+    let Some(free) = free else {
+        return HealthCheck::pass(NAME, LABEL, used_text);
+    };
+
+    let message = format!("{used_text}, {} free", format_bytes(free));
+    if free < CRITICAL_DISK_BYTES {
+        HealthCheck::fail(NAME, LABEL, message).fix(PRUNE)
+    } else if free < LOW_DISK_BYTES {
+        HealthCheck::warn(NAME, LABEL, message).fix(PRUNE)
+    } else {
+        HealthCheck::pass(NAME, LABEL, message)
+    }
+}
+
+/// Check for run locks left behind by crashed or hung processes.
+fn check_lock(lock: Result<Option<LockInfo>, LockError>) -> HealthCheck {
+    const NAME: &str = "run_lock";
+    const LABEL: &str = "Run Lock";
+
+    match lock {
+        Ok(None) => HealthCheck::pass(NAME, LABEL, "No run in progress"),
+        Ok(Some(info)) => match info.status() {
+            LockStatus::Held => {
+                HealthCheck::pass(NAME, LABEL, format!("Held by running {}", info.holder()))
+            }
+            LockStatus::Abandoned => {
+                HealthCheck::warn(NAME, LABEL, format!("Left by exited {}", info.holder()))
+                    .fix("The next run will take it over automatically")
+            }
+            LockStatus::Stale => HealthCheck::fail(NAME, LABEL, LockError::Stale(info).to_string())
+                .fix("Stop the hung process, or start the next run with --force"),
+        },
+        Err(e) => {
+            HealthCheck::fail(NAME, LABEL, e.to_string()).fix("Run with --force to replace it")
+        }
+    }
+}
+
+/// Check that timestamps ralf recorded aren't in the future.
+///
+/// A clock that jumped backwards leaves cooldowns and lock heartbeats in the
+/// future, so models stay benched and live locks look stale.
+fn check_clock(dirs: &RalfDirs, now: u64) -> HealthCheck {
+    let mut recorded = Vec::new();
+    if let Ok(cooldowns) = Cooldowns::load(&dirs.cooldowns()) {
+        for (model, entry) in cooldowns.entries {
+            recorded.push((format!("{model} cooldown"), entry.observed_at));
+        }
+    }
+    if let Ok(Some(info)) = RunLock::read(dirs.root()) {
+        recorded.push(("run lock heartbeat".to_string(), info.heartbeat_at));
+    }
+    clock_check(&recorded, now)
+}
+
+fn clock_check(recorded: &[(String, u64)], now: u64) -> HealthCheck {
+    const NAME: &str = "clock";
+    const LABEL: &str = "Clock";
+
+    let ahead = recorded
+        .iter()
+        .filter(|(_, at)| at.saturating_sub(now) > CLOCK_SKEW_TOLERANCE_SECS)
+        .max_by_key(|(_, at)| *at);
+
+    match ahead {
+        Some((what, at)) => HealthCheck::warn(
+            NAME,
+            LABEL,
+            format!(
+                "{what} was recorded {}s in the future; the clock may have moved backwards",
+                at - now
+            ),
+        )
+        .fix("Sync the system clock, then run `ralf cooldown clear` to reset cooldowns"),
+        None => HealthCheck::pass(NAME, LABEL, "Recorded timestamps are consistent"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_report_status_and_summary() {
+        let report = HealthReport {
+            checks: vec![
+                HealthCheck::pass("a", "A", "ok"),
+                HealthCheck::warn("b", "B", "hmm"),
+            ],
+        };
+        assert_eq!(report.status(), CheckStatus::Warn);
+        assert_eq!(report.summary(), "1 passed, 1 warning(s), 0 failed");
+
+        let json = serde_json::to_value(&report.checks[1]).unwrap();
+        assert_eq!(json["status"], "warn");
+        assert!(json.get("remediation").is_none());
+    }
+
+    #[test]
+    fn test_check_git() {
+        assert_eq!(
+            parse_git_version("git version 2.39.2 (Apple Git-143)"),
+            Some((2, 39))
+        );
+        assert_eq!(
+            parse_git_version("git version 2.45.windows.1"),
+            Some((2, 45))
+        );
+
+        assert_eq!(
+            check_git(Some("git version 2.43.0")).status,
+            CheckStatus::Pass
+        );
+        let old = check_git(Some("git version 2.7.4"));
+        assert_eq!(old.status, CheckStatus::Warn);
+        assert!(old.remediation.unwrap().contains("2.17"));
+        let missing = check_git(None);
+        assert_eq!(missing.status, CheckStatus::Fail);
+        assert!(missing.remediation.is_some());
+    }
+
+    #[test]
+    fn test_check_repo_and_prompt() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(check_repo(temp.path()).status, CheckStatus::Fail);
+        assert_eq!(check_prompt(temp.path()).status, CheckStatus::Warn);
+
+        fs::write(
+            temp.path().join("PROMPT.md"),
+            "# Task\n\nNo promise here.\n",
+        )
+        .unwrap();
+        let check = check_prompt(temp.path());
+        assert_eq!(check.status, CheckStatus::Fail);
+        assert!(check.message.contains("promise"));
+
+        fs::write(
+            temp.path().join("PROMPT.md"),
+            "# Task\n\n## Completion Criteria\n- [ ] Tests pass\n\n\
+             <promise>COMPLETE</promise>\n",
+        )
+        .unwrap();
+        let check = check_prompt(temp.path());
+        assert_ne!(check.status, CheckStatus::Fail, "{}", check.message);
+    }
+
+    #[test]
+    fn test_check_config_invalid() {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir(temp.path().join(".ralf")).unwrap();
+        fs::write(temp.path().join(".ralf/config.json"), "{ not json").unwrap();
+        let check = check_config(temp.path());
+        assert_eq!(check.status, CheckStatus::Fail);
+        assert!(check.remediation.is_some());
+    }
+
+    #[test]
+    fn test_disk_space_check() {
+        let gb = 1024 * 1024 * 1024;
+        assert_eq!(disk_space_check(0, None).status, CheckStatus::Pass);
+        assert_eq!(disk_space_check(0, Some(10 * gb)).status, CheckStatus::Pass);
+        assert_eq!(disk_space_check(0, Some(gb / 2)).status, CheckStatus::Warn);
+        let full = disk_space_check(3 * gb / 2, Some(512));
+        assert_eq!(full.status, CheckStatus::Fail);
+        assert_eq!(full.message, "1.5 GB used by .ralf/, 512 B free");
+    }
+
+    #[test]
+    fn test_check_lock() {
+        assert_eq!(check_lock(Ok(None)).status, CheckStatus::Pass);
+
+        let now = current_timestamp();
+        let stale = LockInfo {
+            pid: std::process::id(),
+            run_id: Some("abc".into()),
+            acquired_at: now - 600,
+            heartbeat_at: now - 600,
+        };
+        let check = check_lock(Ok(Some(stale)));
+        assert_eq!(check.status, CheckStatus::Fail);
+        assert!(check.remediation.unwrap().contains("--force"));
+    }
+
+    #[test]
+    fn test_clock_check() {
+        let now = 1_000_000;
+        let fine = vec![("claude cooldown".to_string(), now - 30)];
+        assert_eq!(clock_check(&fine, now).status, CheckStatus::Pass);
+
+        let skewed = vec![
+            ("claude cooldown".to_string(), now + 30),
+            ("codex cooldown".to_string(), now + 3600),
+        ];
+        let check = clock_check(&skewed, now);
+        assert_eq!(check.status, CheckStatus::Warn);
+        assert!(check
+            .message
+            .starts_with("codex cooldown was recorded 3600s"));
+    }
+}
@@ -0,0 +1,152 @@
+//! The bit of HTTP/1.1 ralf's local servers need.
+//!
+//! The metrics and remote control servers answer a handful of requests
+//! from the same machine, so the request line, headers and a short body
+//! (sized by `Content-Length`) are all that is read: no keep-alive or
+//! chunking.
+
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+/// How long a client may take to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Request heads longer than this are cut off.
+const MAX_HEAD_BYTES: usize = 8192;
+
+/// Request bodies longer than this are cut off.
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// A request's method, path, headers and body.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct Request {
+    pub method: String,
+    pub path: String,
+    pub query: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Request {
+    /// Read a request off `stream`.
+    pub fn read(stream: &mut TcpStream) -> io::Result<Self> {
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+        let mut data = Vec::new();
+        let mut buf = [0; 1024];
+        let head_end = loop {
+            if let Some(end) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+                break end + 4;
+            }
+            let read = stream.read(&mut buf)?;
+            if read == 0 || data.len() >= MAX_HEAD_BYTES {
+                break data.len();
+            }
+            data.extend_from_slice(&buf[..read]);
+        };
+        let mut request = Self::parse(&String::from_utf8_lossy(&data[..head_end]));
+
+        let length = request
+            .header("content-length")
+            .and_then(|length| length.parse::<usize>().ok())
+            .unwrap_or(0)
+            .min(MAX_BODY_BYTES);
+        let mut body = data.split_off(head_end);
+        while body.len() < length {
+            let read = stream.read(&mut buf)?;
+            if read == 0 {
+                break;
+            }
+            body.extend_from_slice(&buf[..read]);
+        }
+        body.truncate(length);
+        request.body = String::from_utf8_lossy(&body).into_owned();
+        Ok(request)
+    }
+
+    fn parse(head: &str) -> Self {
+        let mut lines = head.lines();
+        let mut parts = lines.next().unwrap_or_default().split_whitespace();
+        let method = parts.next().unwrap_or_default().to_string();
+        let target = parts.next().unwrap_or_default();
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let headers = lines
+            .take_while(|line| !line.is_empty())
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+            .collect();
+        Self {
+            method,
+            path: path.to_string(),
+            query: query.to_string(),
+            headers,
+            body: String::new(),
+        }
+    }
+
+    /// A header's value (names match case-insensitively).
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// A query parameter's value, as sent.
+    pub fn query_param(&self, name: &str) -> Option<&str> {
+        self.query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(n, _)| *n == name)
+            .map(|(_, value)| value)
+    }
+}
+
+/// Send a whole response; the connection closes after it.
+pub(crate) fn respond(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &str,
+) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_request() {
+        let request = Request::parse(
+            "POST /pause?token=abc&x=1 HTTP/1.1\r\nHost: localhost\r\nauthorization: Bearer abc\r\n\r\n",
+        );
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/pause");
+        assert_eq!(request.query_param("token"), Some("abc"));
+        assert_eq!(request.query_param("y"), None);
+        assert_eq!(request.header("Authorization"), Some("Bearer abc"));
+        assert_eq!(Request::parse(""), Request::default());
+    }
+
+    #[test]
+    fn test_read_body() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        write!(
+            client,
+            "POST /answer HTTP/1.1\r\nContent-Length: 6\r\n\r\nSQLite and more"
+        )
+        .unwrap();
+        let (mut server, _) = listener.accept().unwrap();
+
+        let request = Request::read(&mut server).unwrap();
+        assert_eq!(request.path, "/answer");
+        assert_eq!(request.body, "SQLite");
+    }
+}
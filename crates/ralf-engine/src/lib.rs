@@ -8,37 +8,171 @@
 //! - Changelog generation
 //! - Chat/conversation management for Spec Studio
 
+pub mod adapters;
+pub mod analytics;
+pub mod archive;
 pub mod changelog;
 pub mod chat;
+pub mod checklist;
+pub mod clock;
+pub mod completion;
 pub mod config;
+pub mod context;
+pub mod criterion;
+pub mod dirs;
 pub mod discovery;
+pub mod embed;
+pub mod events;
+pub mod gc;
 pub mod git;
+pub mod health;
+mod http;
+pub mod lock;
+pub mod logs;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod model_stats;
+pub mod notes;
 pub mod persistence;
+pub mod platform;
+pub mod polish;
 pub mod preflight;
+pub mod prompt_builder;
+pub mod publish;
+pub mod queue;
+pub mod ratelimit;
+pub mod remote;
+pub mod repo_context;
+pub mod report;
+pub mod review;
 pub mod runner;
+pub mod safety;
+pub mod scenario;
+pub mod scheduler;
+pub mod secrets;
+pub mod sink;
+pub mod snapshot;
+pub mod spec;
 pub mod state;
+pub mod stuck;
+pub mod telemetry;
+pub mod templates;
 pub mod thread;
+pub mod trigger;
+pub mod usage;
+pub mod verify_cache;
 
 // Re-export commonly used types
-pub use changelog::{write_changelog_entry, ChangelogEntry, ChangelogError, IterationStatus};
+pub use adapters::{
+    adapter_for, mock_models_enabled, CommandSpec, MockResponse, ModelAdapter, MOCK_MODEL,
+    MOCK_MODELS_ENV,
+};
+pub use analytics::{Analytics, ModelAnalytics};
+pub use archive::{export_run, import_run, ArchiveError, ArchiveManifest, ARCHIVE_VERSION};
+pub use changelog::{
+    generate_commit_message, refine_commit_message, write_changelog_entry, ChangelogEntry,
+    ChangelogError, ChangelogRecord, ChangelogStore, ChangelogSummary, IterationStatus,
+    ModelSummary, VerifierRecord, VerifierStatus, CHANGELOG_SCHEMA_VERSION,
+};
 pub use chat::{
-    draft_has_promise, extract_draft_promise, extract_spec_from_response, invoke_chat,
-    save_draft_snapshot, ChatContext, ChatError, ChatMessage, ChatResult, Role, Thread,
+    build_assessment_prompt, draft_has_promise, extract_draft_promise, extract_spec_from_response,
+    invoke_assessment, invoke_chat, invoke_chat_streaming, parse_assessment, save_draft_snapshot,
+    validate_spec, AssessmentFinding, Attachment, ChatContext, ChatError, ChatMessage, ChatResult,
+    Role, Thread, ThreadBranch,
+};
+pub use checklist::{ChecklistItem, ChecklistSection, ReviewChecklist};
+pub use clock::{Clock, ManualClock, SystemClock};
+pub use completion::{
+    CompletionReport, CriterionAssessment, PromiseSignal, BLOCKED_PROMISE, NEEDS_INPUT_PROMISE,
 };
-pub use config::{Config, ConfigError, ModelConfig, ModelSelection, VerifierConfig};
+pub use config::{
+    global_config_path, Config, ConfigError, ConfigIssue, ConfigSource, Isolation, LayeredConfig,
+    ModelConfig, ModelRole, ModelRoles, ModelSelection, PromptConfig, VerifierConfig,
+    CONFIG_VERSION,
+};
+pub use context::{estimate_tokens, truncate_to_tokens, ContextBudget};
+pub use criterion::Criterion;
+pub use dirs::{data_dir, RalfDirs, RALF_DIR, SERVE_FILE};
 pub use discovery::{
-    discover_model, discover_models, probe_model, probe_model_with_info, DiscoveryResult,
+    discover_model, discover_model_async, discover_models, discover_models_async, probe_model,
+    probe_model_async, probe_model_with_info, probe_model_with_info_async, DiscoveryResult,
     ModelInfo, ProbeResult,
 };
-pub use git::{GitError, GitSafety};
-pub use persistence::{PersistenceError, ThreadStore, ThreadSummary};
-pub use preflight::{run_preflight, PreflightCheck, PreflightResult};
+pub use embed::{ProcessSpawner, Runner, RunnerBuilder, SystemSpawner};
+pub use events::{
+    events_path, parse_event_line, read_events, EventLog, EventLogError, EventRecord, EventSink,
+    EventTail, JsonlSink, MultiSink, EVENTS_FILE,
+};
+pub use gc::{gc, GcError, GcReport, RetentionConfig};
+pub use git::{porcelain_paths, GitError, GitSafety, RunWorktree};
+pub use health::{run_health_checks, CheckStatus, HealthCheck, HealthReport};
+pub use lock::{spawn_heartbeat, LockError, LockInfo, LockStatus, RunLock, LOCK_FILE};
+pub use logs::{
+    compress_logs, iteration_dir, iteration_of, latest_run, list_logs, parse_log, read_log,
+    LogFile, LogSections,
+};
+#[cfg(feature = "metrics")]
+pub use metrics::Metrics;
+pub use model_stats::{InvocationOutcome, ModelHealth, ModelStats, MODEL_STATS_FILE};
+pub use notes::{WorkingNotes, NOTES_FILE};
+pub use persistence::{
+    diff_lines, DiffLine, DraftHistory, DraftRevision, PersistenceError, ThreadFilter, ThreadStore,
+    ThreadSummary,
+};
+pub use polish::{PolishConfig, DEFAULT_POLISH_PREAMBLE};
+pub use preflight::{
+    probe_models, run_preflight, run_preflight_with_probes, selected_models, PreflightCheck,
+    PreflightResult,
+};
+pub use prompt_builder::{IterationFeedback, PromptBuilder};
+pub use publish::{open_pull_request, Forge, PublishError};
+pub use queue::{Queue, QueueEntry, QueueStatus, QUEUE_FILE};
+pub use ratelimit::{parse_reset, RateLimitReset};
+pub use remote::{
+    generate_token, RemoteAction, RemoteClient, RemoteError, RemoteEvents, RemoteRun,
+    RemoteRunState, RemoteServer, RemoteStatus, ServeInfo,
+};
+pub use repo_context::{file_tree, RepoContext};
+pub use report::{
+    report_path, CriterionOutcome, IterationReport, ReportError, ReportFormat, RunReport,
+    VerifierOutcome, REPORTS_DIR,
+};
+pub use review::{changes_since, glob_matches, parse_numstat, FileChange, ReviewConfig};
 pub use runner::{
-    check_promise, extract_promise, get_git_info, hash_prompt, invoke_model, run_verifier,
-    select_model, start_run, GitInfo, InvocationResult, RunConfig, RunEvent, RunHandle,
-    RunnerError, VerifierResult,
+    check_promise, extract_promise, find_leftover_processes, get_git_info, get_git_info_in,
+    hash_prompt, invoke_model, invoke_model_within, iteration_commit_message,
+    kill_all_process_trees, kill_process_tree, load_resumable_state, prepare_work_dir,
+    run_verifier, run_verifier_within, select_model, select_verifier_model, start_run, target_root,
+    verifier_skip_reason, GitInfo, InvocationResult, LeftoverProcess, RunConfig, RunEvent,
+    RunHandle, RunnerError, VerifierResult, RALF_PARENT_ENV, STEERING_POLL,
+};
+pub use safety::{revert_protected, violation_messages, violation_summary, SafetyConfig};
+pub use scenario::{
+    phase_transitions, run_scenario, Expectations, Scenario, ScenarioError, ScenarioIteration,
+    ScenarioOutcome,
+};
+pub use scheduler::{ConcurrencyConfig, InvocationSlot, Scheduler};
+pub use secrets::{scan_diff, secrets_since, secrets_warning, SecretFinding};
+pub use sink::{save_warning, PersistenceFailure, PersistenceSink, SAVE_ATTEMPTS};
+pub use snapshot::{
+    ModelVersion, RunManifest, RunSnapshot, CONFIG_SNAPSHOT_FILE, RUN_MANIFEST_FILE,
+    SPEC_SNAPSHOT_FILE,
 };
-pub use state::{Cooldowns, RunState, RunStatus, StateError};
+pub use spec::{lint, Severity, SpecDiagnostic, MAX_SPEC_TOKENS};
+pub use state::{
+    atomic_write, load_or_recover, CooldownEntry, Cooldowns, Corruption, PendingQuestion, RunState,
+    RunStatus, StateError,
+};
+pub use stuck::{no_op_warning, suggest_recovery, IterationSnapshot, StuckDetector, StuckSignal};
+pub use templates::{
+    find_template, list_templates, user_templates_dir, Template, TemplateError, TemplateSource,
+};
+pub use trigger::{
+    format_start, format_wait, models_ready_at, parse_start, CronSchedule, PendingStart,
+    TriggerError, PENDING_START_FILE,
+};
+pub use usage::{RunUsage, Usage, UsageError, UsageLedger};
+pub use verify_cache::{CachedVerdict, VerifyCache, VERIFY_CACHE_FILE};
 
 /// Returns the engine version.
 pub fn engine_version() -> &'static str {
@@ -48,13 +182,33 @@ pub fn engine_version() -> &'static str {
 /// Parse completion criteria from a PROMPT.md string.
 ///
 /// Looks for sections named "Requirements", "Completion Criteria", "Criteria",
-/// or "Acceptance Criteria" and extracts bullet points from them.
+/// or "Acceptance Criteria" and extracts bullet points from them, with their
+/// `#tag` and `(optional)` annotations (see [`Criterion`]).
+pub fn parse_criteria(prompt: &str) -> Vec<Criterion> {
+    criteria_with_lines(prompt)
+        .into_iter()
+        .enumerate()
+        .map(|(index, (_, line))| Criterion::parse(index, &line))
+        .collect()
+}
+
+/// Whether a level-2 header (lowercased, without the `#`s) starts a
+/// criteria section.
+pub(crate) fn is_criteria_header(header: &str) -> bool {
+    header.contains("requirement")
+        || header.contains("criteria")
+        || header.contains("acceptance")
+        || header.contains("completion")
+        || header.contains("verification")
+}
+
+/// Like [`parse_criteria`], with the 1-based line number of each criterion.
 #[allow(clippy::manual_map)]
-pub fn parse_criteria(prompt: &str) -> Vec<String> {
+pub(crate) fn criteria_with_lines(prompt: &str) -> Vec<(usize, String)> {
     let mut criteria = Vec::new();
     let mut in_criteria_section = false;
 
-    for line in prompt.lines() {
+    for (index, line) in prompt.lines().enumerate() {
         let trimmed = line.trim();
 
         // Count header level (number of leading #)
@@ -63,11 +217,7 @@ pub fn parse_criteria(prompt: &str) -> Vec<String> {
         // Check for level-2 headers (## Section)
         if header_level == 2 {
             let header = trimmed.trim_start_matches('#').trim().to_lowercase();
-            in_criteria_section = header.contains("requirement")
-                || header.contains("criteria")
-                || header.contains("acceptance")
-                || header.contains("completion")
-                || header.contains("verification");
+            in_criteria_section = is_criteria_header(&header);
             continue;
         }
 
@@ -98,7 +248,7 @@ pub fn parse_criteria(prompt: &str) -> Vec<String> {
 
             if let Some(text) = content {
                 if !text.is_empty() {
-                    criteria.push(text.to_string());
+                    criteria.push((index + 1, text.to_string()));
                 }
             }
         }
@@ -135,8 +285,8 @@ Follow these steps...
 "#;
         let criteria = parse_criteria(prompt);
         assert_eq!(criteria.len(), 2);
-        assert_eq!(criteria[0], "Create a file called `hello.txt`");
-        assert_eq!(criteria[1], "File should contain \"Hello, World!\"");
+        assert_eq!(criteria[0].text, "Create a file called `hello.txt`");
+        assert_eq!(criteria[1].text, "File should contain \"Hello, World!\"");
     }
 
     #[test]
@@ -149,9 +299,9 @@ Follow these steps...
 "#;
         let criteria = parse_criteria(prompt);
         assert_eq!(criteria.len(), 3);
-        assert_eq!(criteria[0], "First thing");
-        assert_eq!(criteria[1], "Already done");
-        assert_eq!(criteria[2], "Third thing");
+        assert_eq!(criteria[0].text, "First thing");
+        assert_eq!(criteria[1].text, "Already done");
+        assert_eq!(criteria[2].text, "Third thing");
     }
 
     #[test]
@@ -181,8 +331,19 @@ Follow these steps...
 "#;
         let criteria = parse_criteria(prompt);
         assert_eq!(criteria.len(), 3);
-        assert_eq!(criteria[0], "First requirement");
-        assert_eq!(criteria[1], "Second requirement");
-        assert_eq!(criteria[2], "Third requirement");
+        assert_eq!(criteria[0].text, "First requirement");
+        assert_eq!(criteria[1].text, "Second requirement");
+        assert_eq!(criteria[2].text, "Third requirement");
+    }
+
+    #[test]
+    fn test_parse_criteria_annotations() {
+        let prompt = "## Criteria\n- Tests pass #tests\n- Docs updated #docs (optional)\n";
+        let criteria = parse_criteria(prompt);
+        assert_eq!(criteria[0].id, "c1");
+        assert!(criteria[0].required && criteria[0].has_tag("tests"));
+        assert_eq!(criteria[1].id, "c2");
+        assert_eq!(criteria[1].text, "Docs updated");
+        assert!(!criteria[1].required);
     }
 }
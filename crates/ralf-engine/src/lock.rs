@@ -0,0 +1,380 @@
+//! Run lock shared by every ralf process working in a repository.
+//!
+//! A run holds `.ralf/lock` for as long as it is going, so a second
+//! `ralf run` (or a TUI and a CLI side by side) can't interleave writes to
+//! `state.json`. The lock records the holder's PID and a heartbeat the
+//! holder refreshes every [`HEARTBEAT_INTERVAL`]:
+//!
+//! - A lock whose process has exited is abandoned and taken over silently.
+//! - A lock whose process is alive but hasn't refreshed the heartbeat for
+//!   [`STALE_AFTER_SECS`] is stale (a hung run, or a PID that was reused);
+//!   it is only taken over with `force` (`ralf run --force`).
+//! - Anything else is held, and acquiring fails.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::state::current_timestamp;
+
+/// Name of the lock file inside `.ralf/`.
+pub const LOCK_FILE: &str = "lock";
+
+/// How often the holder refreshes its heartbeat.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// A heartbeat older than this marks the lock as stale.
+pub const STALE_AFTER_SECS: u64 = 60;
+
+/// Contents of the lock file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockInfo {
+    /// PID of the process holding the lock.
+    pub pid: u32,
+    /// Run the lock was taken for.
+    pub run_id: Option<String>,
+    /// When the lock was acquired (Unix timestamp).
+    pub acquired_at: u64,
+    /// When the holder last refreshed the lock (Unix timestamp).
+    pub heartbeat_at: u64,
+}
+
+impl LockInfo {
+    fn new(run_id: Option<&str>) -> Self {
+        let now = current_timestamp();
+        Self {
+            pid: std::process::id(),
+            run_id: run_id.map(str::to_string),
+            acquired_at: now,
+            heartbeat_at: now,
+        }
+    }
+
+    /// Whether the lock can be taken over, and how.
+    pub fn status(&self) -> LockStatus {
+        if !process_alive(self.pid) {
+            LockStatus::Abandoned
+        } else if current_timestamp().saturating_sub(self.heartbeat_at) > STALE_AFTER_SECS {
+            LockStatus::Stale
+        } else {
+            LockStatus::Held
+        }
+    }
+
+    /// "run abc123 (pid 42)", or "pid 42" before the run has an ID.
+    pub fn holder(&self) -> String {
+        match &self.run_id {
+            Some(run_id) => format!("run {run_id} (pid {})", self.pid),
+            None => format!("pid {}", self.pid),
+        }
+    }
+}
+
+/// State of an existing lock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockStatus {
+    /// The holder is running and its heartbeat is current.
+    Held,
+    /// The holder is alive but its heartbeat stopped.
+    Stale,
+    /// The holder has exited.
+    Abandoned,
+}
+
+/// Errors acquiring the run lock.
+#[derive(Debug, thiserror::Error)]
+pub enum LockError {
+    /// Another process is running.
+    #[error("Another ralf process is running ({})", .0.holder())]
+    Held(LockInfo),
+
+    /// The lock's holder stopped refreshing it.
+    #[error(
+        "Lock held by {} has had no heartbeat for {}s; use --force to take it over",
+        .0.holder(),
+        current_timestamp().saturating_sub(.0.heartbeat_at)
+    )]
+    Stale(LockInfo),
+
+    /// The lock file exists but can't be read.
+    #[error("Lock file {} is unreadable; use --force to replace it", .0.display())]
+    Corrupt(PathBuf),
+
+    /// I/O error.
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Serialize error.
+    #[error("Serialize error: {0}")]
+    Serialize(#[source] serde_json::Error),
+}
+
+/// The run lock, held until dropped or [released](RunLock::release).
+#[derive(Debug)]
+pub struct RunLock {
+    path: PathBuf,
+    info: LockInfo,
+}
+
+impl RunLock {
+    /// Path of the lock file for a `.ralf` directory.
+    pub fn path(ralf_dir: &Path) -> PathBuf {
+        ralf_dir.join(LOCK_FILE)
+    }
+
+    /// The current lock, if any. `Err` if the file exists but can't be
+    /// parsed.
+    pub fn read(ralf_dir: &Path) -> Result<Option<LockInfo>, LockError> {
+        let path = Self::path(ralf_dir);
+        match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)
+                .map(Some)
+                .map_err(|_| LockError::Corrupt(path)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(LockError::Io(e)),
+        }
+    }
+
+    /// Take the lock for `run_id`. Abandoned locks are taken over; stale
+    /// and unreadable ones only with `force`.
+    pub fn acquire(ralf_dir: &Path, run_id: Option<&str>, force: bool) -> Result<Self, LockError> {
+        std::fs::create_dir_all(ralf_dir)?;
+        let path = Self::path(ralf_dir);
+        let info = LockInfo::new(run_id);
+
+        // Two attempts: the second after clearing a lock we may take over
+        for _ in 0..2 {
+            if try_create(&path, &info)? {
+                return Ok(Self { path, info });
+            }
+            match Self::read(ralf_dir) {
+                // Released in the meantime
+                Ok(None) => continue,
+                Ok(Some(existing)) => match existing.status() {
+                    LockStatus::Held => return Err(LockError::Held(existing)),
+                    LockStatus::Stale if !force => return Err(LockError::Stale(existing)),
+                    LockStatus::Stale | LockStatus::Abandoned => {}
+                },
+                Err(e) if force => tracing::warn!("Replacing run lock: {e}"),
+                Err(e) => return Err(e),
+            }
+            match std::fs::remove_file(&path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            }
+        }
+
+        // Lost a race with another process taking over the same lock
+        match Self::read(ralf_dir)? {
+            Some(existing) => Err(LockError::Held(existing)),
+            None => Err(LockError::Corrupt(path)),
+        }
+    }
+
+    /// Whether the lock file still belongs to this process.
+    fn is_ours(&self) -> bool {
+        Self::read(self.path.parent().unwrap_or(Path::new(".")))
+            .ok()
+            .flatten()
+            .is_some_and(|info| {
+                info.pid == self.info.pid && info.acquired_at == self.info.acquired_at
+            })
+    }
+
+    /// The lock's contents.
+    pub fn info(&self) -> &LockInfo {
+        &self.info
+    }
+
+    /// Refresh the heartbeat. Returns `false` if the lock was taken over
+    /// by another process.
+    pub fn heartbeat(&self) -> Result<bool, LockError> {
+        if !self.is_ours() {
+            return Ok(false);
+        }
+        let refreshed = LockInfo {
+            heartbeat_at: current_timestamp(),
+            ..self.info.clone()
+        };
+        let temp = temp_path(&self.path);
+        write_info(&temp, &refreshed)?;
+        std::fs::rename(&temp, &self.path)?;
+        Ok(true)
+    }
+
+    /// Remove the lock file if it still belongs to this process. Called on
+    /// drop; for exiting without unwinding (e.g. on a signal).
+    pub fn release(&self) {
+        if self.is_ours() {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+impl Drop for RunLock {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+/// Create the lock file at `path`, unless it already exists. Written to a
+/// temporary file and linked into place, so readers never see a partial
+/// lock.
+fn try_create(path: &Path, info: &LockInfo) -> Result<bool, LockError> {
+    let temp = temp_path(path);
+    write_info(&temp, info)?;
+    let linked = std::fs::hard_link(&temp, path);
+    let _ = std::fs::remove_file(&temp);
+    match linked {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    path.with_extension(format!("{}.tmp", std::process::id()))
+}
+
+fn write_info(path: &Path, info: &LockInfo) -> Result<(), LockError> {
+    let content = serde_json::to_string_pretty(info).map_err(LockError::Serialize)?;
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+/// Refresh `lock`'s heartbeat every [`HEARTBEAT_INTERVAL`] until the lock
+/// is dropped or taken over.
+pub fn spawn_heartbeat(lock: &Arc<RunLock>) -> tokio::task::JoinHandle<()> {
+    let lock = Arc::downgrade(lock);
+    tokio::spawn(async move {
+        let mut ticks = tokio::time::interval(HEARTBEAT_INTERVAL);
+        ticks.tick().await;
+        loop {
+            ticks.tick().await;
+            let Some(lock) = lock.upgrade() else {
+                return;
+            };
+            let refreshed = tokio::task::spawn_blocking(move || lock.heartbeat()).await;
+            match refreshed {
+                Ok(Ok(true)) => {}
+                Ok(Ok(false)) => {
+                    tracing::warn!("Run lock was taken over by another process");
+                    return;
+                }
+                Ok(Err(e)) => tracing::warn!("Failed to refresh run lock: {e}"),
+                Err(_) => return,
+            }
+        }
+    })
+}
+
+/// Whether a process with this PID exists. Assumed true where that can't
+/// be checked.
+pub(crate) fn process_alive(pid: u32) -> bool {
+    #[cfg(unix)]
+    {
+        use nix::errno::Errno;
+        use nix::sys::signal::kill;
+        use nix::unistd::Pid;
+
+        let Ok(pid) = i32::try_from(pid) else {
+            return false;
+        };
+        // Signal 0 checks for existence; EPERM means it exists as another user
+        matches!(kill(Pid::from_raw(pid), None), Ok(()) | Err(Errno::EPERM))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = pid;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_lock(ralf_dir: &Path, info: &LockInfo) {
+        std::fs::write(
+            RunLock::path(ralf_dir),
+            serde_json::to_string(info).unwrap(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_acquire_and_release() {
+        let temp = TempDir::new().unwrap();
+        let lock = RunLock::acquire(temp.path(), Some("abc123"), false).unwrap();
+
+        let info = RunLock::read(temp.path()).unwrap().unwrap();
+        assert_eq!(info.pid, std::process::id());
+        assert_eq!(info.run_id.as_deref(), Some("abc123"));
+        assert_eq!(info.status(), LockStatus::Held);
+
+        // A second run is refused, even with force
+        let err = RunLock::acquire(temp.path(), None, true).unwrap_err();
+        assert!(matches!(err, LockError::Held(_)));
+        assert!(err.to_string().contains("run abc123"));
+
+        assert!(lock.heartbeat().unwrap());
+        drop(lock);
+        assert!(RunLock::read(temp.path()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_abandoned_lock_is_taken_over() {
+        let temp = TempDir::new().unwrap();
+        let mut info = LockInfo::new(Some("old"));
+        info.pid = u32::MAX; // Not a valid PID
+        write_lock(temp.path(), &info);
+        assert_eq!(info.status(), LockStatus::Abandoned);
+
+        let lock = RunLock::acquire(temp.path(), Some("new"), false).unwrap();
+        assert_eq!(lock.info().run_id.as_deref(), Some("new"));
+    }
+
+    #[test]
+    fn test_stale_lock_needs_force() {
+        let temp = TempDir::new().unwrap();
+        let mut info = LockInfo::new(Some("hung"));
+        info.heartbeat_at -= STALE_AFTER_SECS + 5;
+        write_lock(temp.path(), &info);
+        assert_eq!(info.status(), LockStatus::Stale);
+
+        let err = RunLock::acquire(temp.path(), None, false).unwrap_err();
+        assert!(matches!(err, LockError::Stale(_)));
+        assert!(err.to_string().contains("--force"));
+
+        let lock = RunLock::acquire(temp.path(), None, true).unwrap();
+        assert!(lock.heartbeat().unwrap());
+    }
+
+    #[test]
+    fn test_taken_over_lock_is_left_alone() {
+        let temp = TempDir::new().unwrap();
+        let lock = RunLock::acquire(temp.path(), None, false).unwrap();
+
+        let mut other = LockInfo::new(Some("other"));
+        other.acquired_at += 1;
+        write_lock(temp.path(), &other);
+
+        assert!(!lock.heartbeat().unwrap());
+        drop(lock);
+        assert_eq!(RunLock::read(temp.path()).unwrap(), Some(other));
+    }
+
+    #[test]
+    fn test_corrupt_lock() {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(RunLock::path(temp.path()), "{").unwrap();
+
+        assert!(matches!(
+            RunLock::acquire(temp.path(), None, false),
+            Err(LockError::Corrupt(_))
+        ));
+        assert!(RunLock::acquire(temp.path(), None, true).is_ok());
+    }
+}
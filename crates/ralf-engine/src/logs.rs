@@ -0,0 +1,321 @@
+//! Locating and reading a run's invocation logs.
+//!
+//! Every model invocation and verifier writes a log with its stdout and
+//! stderr to `.ralf/runs/<run_id>/iteration-<N>/<name>.log`. Models
+//! verifying completion criteria log to the iteration's `verify/`
+//! subdirectory, so they don't overwrite the implementer's log when the
+//! same model does both. Runs from before per-iteration directories have
+//! their logs directly in the run directory.
+//!
+//! With `compress_logs` set, the logs of an iteration are gzipped to
+//! `<name>.log.gz` once it's over; [`read_log`] reads either kind.
+
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+use crate::dirs::RalfDirs;
+use crate::state::{atomic_write, RunState};
+
+/// Heading of the stdout section of a log, as written by the runner.
+pub const STDOUT_MARKER: &str = "=== STDOUT ===";
+
+/// Heading of the stderr section of a log.
+pub const STDERR_MARKER: &str = "=== STDERR ===";
+
+/// Subdirectory of an iteration's log directory for verification logs.
+pub const VERIFY_LOG_DIR: &str = "verify";
+
+const ITERATION_PREFIX: &str = "iteration-";
+
+const LOG_SUFFIX: &str = ".log";
+
+const COMPRESSED_LOG_SUFFIX: &str = ".log.gz";
+
+/// Directory for iteration `iteration`'s logs.
+pub fn iteration_dir(run_dir: &Path, iteration: u64) -> PathBuf {
+    run_dir.join(format!("{ITERATION_PREFIX}{iteration}"))
+}
+
+/// Iteration whose logs `log_dir` holds, including its verification
+/// subdirectory.
+pub fn iteration_of(log_dir: &Path) -> Option<u64> {
+    let dir = if log_dir.ends_with(VERIFY_LOG_DIR) {
+        log_dir.parent()?
+    } else {
+        log_dir
+    };
+    parse_iteration(dir.file_name()?.to_str()?)
+}
+
+fn parse_iteration(dir_name: &str) -> Option<u64> {
+    dir_name.strip_prefix(ITERATION_PREFIX)?.parse().ok()
+}
+
+/// A log file in a run directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFile {
+    /// Path of the log.
+    pub path: PathBuf,
+    /// Iteration the log belongs to (`None` for runs that predate
+    /// per-iteration logs).
+    pub iteration: Option<u64>,
+    /// Model or verifier name.
+    pub name: String,
+    /// Whether the log is from verifying completion criteria.
+    pub verification: bool,
+    /// When the log was last written.
+    pub modified: Option<SystemTime>,
+}
+
+impl LogFile {
+    /// "iteration 3 · claude (verification)".
+    pub fn title(&self) -> String {
+        let mut title = match self.iteration {
+            Some(iteration) => format!("iteration {iteration} · {}", self.name),
+            None => self.name.clone(),
+        };
+        if self.verification {
+            title.push_str(" (verification)");
+        }
+        title
+    }
+}
+
+/// All logs in `run_dir`, by iteration and then in the order they were
+/// written.
+pub fn list_logs(run_dir: &Path) -> Vec<LogFile> {
+    let mut logs = logs_in(run_dir, None, false);
+
+    let Ok(entries) = std::fs::read_dir(run_dir) else {
+        return logs;
+    };
+    for entry in entries.flatten() {
+        let iteration = entry.file_name().to_str().and_then(parse_iteration);
+        if let Some(iteration) = iteration {
+            let dir = entry.path();
+            logs.extend(logs_in(&dir, Some(iteration), false));
+            logs.extend(logs_in(&dir.join(VERIFY_LOG_DIR), Some(iteration), true));
+        }
+    }
+
+    logs.sort_by(|a, b| {
+        (a.iteration, a.modified, &a.name).cmp(&(b.iteration, b.modified, &b.name))
+    });
+    logs
+}
+
+fn logs_in(dir: &Path, iteration: Option<u64>, verification: bool) -> Vec<LogFile> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let file_name = path.file_name()?.to_str()?;
+            let name = file_name
+                .strip_suffix(LOG_SUFFIX)
+                .or_else(|| file_name.strip_suffix(COMPRESSED_LOG_SUFFIX))?
+                .to_string();
+            let modified = entry.metadata().and_then(|m| m.modified()).ok();
+            Some(LogFile {
+                path,
+                iteration,
+                name,
+                verification,
+                modified,
+            })
+        })
+        .collect()
+}
+
+/// Read a log, decompressing it if it was compressed. A `.log` path that
+/// has since been compressed (as recorded in the changelog) is read from
+/// its `.log.gz`.
+pub fn read_log(path: &Path) -> std::io::Result<String> {
+    let compressed = path
+        .to_str()
+        .is_some_and(|p| p.ends_with(COMPRESSED_LOG_SUFFIX));
+    if !compressed {
+        match std::fs::read_to_string(path) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            result => return result,
+        }
+    }
+
+    let gz_path = if compressed {
+        path.to_path_buf()
+    } else {
+        compressed_path(path)
+    };
+    let mut content = String::new();
+    GzDecoder::new(std::fs::File::open(gz_path)?).read_to_string(&mut content)?;
+    Ok(content)
+}
+
+/// Gzip every uncompressed log of the run in `run_dir`, returning how many
+/// were compressed. Only call this between iterations, so no log is still
+/// being written.
+pub fn compress_logs(run_dir: &Path) -> std::io::Result<usize> {
+    let mut compressed = 0;
+    for log in list_logs(run_dir) {
+        if log.path.extension().and_then(|ext| ext.to_str()) != Some("log") {
+            continue;
+        }
+        let content = std::fs::read(&log.path)?;
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&content)?;
+        atomic_write(&compressed_path(&log.path), &encoder.finish()?)?;
+        std::fs::remove_file(&log.path)?;
+        compressed += 1;
+    }
+    Ok(compressed)
+}
+
+fn compressed_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".gz");
+    PathBuf::from(name)
+}
+
+/// The stdout and stderr sections of a log.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LogSections {
+    /// What the process wrote to stdout.
+    pub stdout: String,
+    /// What the process wrote to stderr.
+    pub stderr: String,
+}
+
+/// Split a log into its sections. Content without section markers is
+/// taken as stdout.
+pub fn parse_log(content: &str) -> LogSections {
+    let body = content
+        .strip_prefix(STDOUT_MARKER)
+        .map_or(content, |rest| rest.strip_prefix('\n').unwrap_or(rest));
+    let (stdout, stderr) = match body.find(STDERR_MARKER) {
+        Some(at) => (&body[..at], &body[at + STDERR_MARKER.len()..]),
+        None => (body, ""),
+    };
+    LogSections {
+        stdout: stdout.trim_end().to_string(),
+        stderr: stderr.trim_matches('\n').trim_end().to_string(),
+    }
+}
+
+/// The most recent run: the one in `.ralf/state.json` if its directory
+/// exists, otherwise the most recently modified run directory.
+pub fn latest_run(ralf_dir: &Path) -> Option<String> {
+    let dirs = RalfDirs::new(ralf_dir);
+    let runs_dir = dirs.runs();
+    let current = RunState::load(&dirs.state())
+        .ok()
+        .and_then(|state| state.run_id);
+    if let Some(run_id) = current {
+        if runs_dir.join(&run_id).is_dir() {
+            return Some(run_id);
+        }
+    }
+
+    std::fs::read_dir(&runs_dir)
+        .ok()?
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| {
+            let modified = entry.metadata().and_then(|m| m.modified()).ok()?;
+            Some((modified, entry.file_name().to_str()?.to_string()))
+        })
+        .max()
+        .map(|(_, run_id)| run_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_list_logs() {
+        let temp = TempDir::new().unwrap();
+        let run_dir = temp.path();
+        fs::write(run_dir.join("legacy.log"), "").unwrap();
+        fs::write(run_dir.join("events.jsonl"), "").unwrap();
+        for iteration in [2, 1] {
+            let dir = iteration_dir(run_dir, iteration);
+            fs::create_dir_all(dir.join(VERIFY_LOG_DIR)).unwrap();
+            fs::write(dir.join("claude.log"), "").unwrap();
+            fs::write(dir.join(VERIFY_LOG_DIR).join("codex.log"), "").unwrap();
+        }
+
+        let logs = list_logs(run_dir);
+        let titles: Vec<String> = logs.iter().map(LogFile::title).collect();
+        assert_eq!(titles.len(), 5);
+        assert_eq!(titles[0], "legacy");
+        assert!(titles[1].starts_with("iteration 1 · "));
+        assert!(titles.contains(&"iteration 2 · codex (verification)".to_string()));
+        assert_eq!(logs[4].iteration, Some(2));
+    }
+
+    #[test]
+    fn test_iteration_of() {
+        let dir = iteration_dir(Path::new("runs/run-1"), 7);
+        assert_eq!(iteration_of(&dir), Some(7));
+        assert_eq!(iteration_of(&dir.join(VERIFY_LOG_DIR)), Some(7));
+        assert_eq!(iteration_of(Path::new("runs/run-1")), None);
+    }
+
+    #[test]
+    fn test_parse_log() {
+        let sections = parse_log("=== STDOUT ===\nhello\nworld\n\n=== STDERR ===\noops\n");
+        assert_eq!(sections.stdout, "hello\nworld");
+        assert_eq!(sections.stderr, "oops");
+
+        let sections = parse_log("=== STDOUT ===\n\n\n=== STDERR ===\n\n");
+        assert_eq!(sections, LogSections::default());
+
+        assert_eq!(parse_log("plain output\n").stdout, "plain output");
+    }
+
+    #[test]
+    fn test_compress_logs() {
+        let temp = TempDir::new().unwrap();
+        let run_dir = temp.path();
+        let dir = iteration_dir(run_dir, 1);
+        fs::create_dir_all(dir.join(VERIFY_LOG_DIR)).unwrap();
+        let log = "=== STDOUT ===\nhello\n";
+        fs::write(dir.join("claude.log"), log).unwrap();
+        fs::write(dir.join(VERIFY_LOG_DIR).join("codex.log"), log).unwrap();
+
+        assert_eq!(compress_logs(run_dir).unwrap(), 2);
+        assert!(!dir.join("claude.log").exists());
+        assert_eq!(compress_logs(run_dir).unwrap(), 0);
+
+        let logs = list_logs(run_dir);
+        assert_eq!(logs.len(), 2);
+        assert_eq!(logs[0].title(), "iteration 1 · claude");
+        assert_eq!(read_log(&logs[0].path).unwrap(), log);
+        // Paths recorded before compressing still read
+        assert_eq!(read_log(&dir.join("claude.log")).unwrap(), log);
+    }
+
+    #[test]
+    fn test_latest_run() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(latest_run(temp.path()), None);
+
+        fs::create_dir_all(temp.path().join("runs").join("older")).unwrap();
+        assert_eq!(latest_run(temp.path()).as_deref(), Some("older"));
+
+        fs::create_dir_all(temp.path().join("runs").join("current")).unwrap();
+        let mut state = RunState::default();
+        state.start_run_with_id("current");
+        state.save(&temp.path().join("state.json")).unwrap();
+        assert_eq!(latest_run(temp.path()).as_deref(), Some("current"));
+    }
+}
@@ -0,0 +1,347 @@
+//! Prometheus metrics for long-running loops.
+//!
+//! [`Metrics`] is an [`EventSink`] that counts what runs do (iterations,
+//! model invocations, failed iterations, rate limits and cost) and tracks
+//! cooldowns and how long the current run has gone. [`serve`] exposes them
+//! in the `OpenMetrics` text format for Prometheus to scrape, e.g. with
+//! `ralf run --metrics-addr 127.0.0.1:9464`.
+//!
+//! Only built with the `metrics` feature.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::clock::{Clock, SystemClock};
+use crate::events::EventSink;
+use crate::http::{respond, Request};
+use crate::runner::RunEvent;
+
+/// Content type of the metrics page.
+const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// Run metrics, fed by run events.
+#[derive(Debug)]
+pub struct Metrics {
+    clock: Arc<dyn Clock>,
+    state: Mutex<MetricsState>,
+}
+
+#[derive(Debug, Default)]
+struct MetricsState {
+    iterations: u64,
+    failed_iterations: u64,
+    invocations: BTreeMap<String, u64>,
+    rate_limits: BTreeMap<String, u64>,
+    cost_usd: BTreeMap<String, f64>,
+    tokens: BTreeMap<String, u64>,
+    /// When each model's latest cooldown ends (Unix timestamp).
+    cooldown_ends: BTreeMap<String, u64>,
+    /// Start and, once over, end of the latest run (Unix timestamps).
+    run: Option<(u64, Option<u64>)>,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    /// Metrics that read the system clock.
+    pub fn new() -> Self {
+        Self::with_clock(Arc::new(SystemClock))
+    }
+
+    /// Metrics that read the time from `clock`.
+    pub fn with_clock(clock: Arc<dyn Clock>) -> Self {
+        Self {
+            clock,
+            state: Mutex::new(MetricsState::default()),
+        }
+    }
+
+    /// Count `event`.
+    pub fn record(&self, event: &RunEvent) {
+        let now = self.clock.now();
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        match event {
+            RunEvent::Started { .. } => state.run = Some((now, None)),
+            RunEvent::IterationStarted { .. } => state.iterations += 1,
+            RunEvent::ModelCompleted {
+                model,
+                rate_limited,
+                ..
+            } => {
+                *state.invocations.entry(model.clone()).or_default() += 1;
+                if *rate_limited {
+                    *state.rate_limits.entry(model.clone()).or_default() += 1;
+                }
+            }
+            RunEvent::UsageUpdated { model, usage, .. } => {
+                *state.cost_usd.entry(model.clone()).or_default() += usage.cost_usd;
+                *state.tokens.entry(model.clone()).or_default() += usage.total_tokens;
+            }
+            RunEvent::CooldownStarted {
+                model,
+                duration_secs,
+            } => {
+                state
+                    .cooldown_ends
+                    .insert(model.clone(), now + duration_secs);
+            }
+            RunEvent::IterationCompleted {
+                all_verifiers_passed: false,
+                ..
+            } => state.failed_iterations += 1,
+            RunEvent::Completed { .. }
+            | RunEvent::Failed { .. }
+            | RunEvent::Stuck { .. }
+            | RunEvent::Cancelled { .. } => {
+                if let Some((_, ended)) = &mut state.run {
+                    ended.get_or_insert(now);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The metrics in the `OpenMetrics` text format.
+    pub fn render(&self) -> String {
+        let now = self.clock.now();
+        let state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut out = String::new();
+
+        family(
+            &mut out,
+            "ralf_iterations",
+            "counter",
+            "Iterations started.",
+        );
+        let _ = writeln!(out, "ralf_iterations_total {}", state.iterations);
+        family(
+            &mut out,
+            "ralf_iteration_failures",
+            "counter",
+            "Iterations that ended without their criteria or verifiers passing.",
+        );
+        let _ = writeln!(
+            out,
+            "ralf_iteration_failures_total {}",
+            state.failed_iterations
+        );
+
+        family(
+            &mut out,
+            "ralf_model_invocations",
+            "counter",
+            "Model invocations that finished.",
+        );
+        per_model(&mut out, "ralf_model_invocations_total", &state.invocations);
+        family(
+            &mut out,
+            "ralf_rate_limits",
+            "counter",
+            "Model invocations that were rate limited.",
+        );
+        per_model(&mut out, "ralf_rate_limits_total", &state.rate_limits);
+        family(
+            &mut out,
+            "ralf_cost_usd",
+            "counter",
+            "Cost reported by the models, in US dollars.",
+        );
+        per_model(&mut out, "ralf_cost_usd_total", &state.cost_usd);
+        family(
+            &mut out,
+            "ralf_tokens",
+            "counter",
+            "Tokens reported by the models.",
+        );
+        per_model(&mut out, "ralf_tokens_total", &state.tokens);
+
+        family(
+            &mut out,
+            "ralf_cooldown_seconds",
+            "gauge",
+            "Seconds until a model's cooldown ends.",
+        );
+        let remaining: BTreeMap<String, u64> = state
+            .cooldown_ends
+            .iter()
+            .map(|(model, ends)| (model.clone(), ends.saturating_sub(now)))
+            .collect();
+        per_model(&mut out, "ralf_cooldown_seconds", &remaining);
+
+        family(
+            &mut out,
+            "ralf_run_duration_seconds",
+            "gauge",
+            "How long the latest run has gone, or went.",
+        );
+        if let Some((started, ended)) = state.run {
+            let _ = writeln!(
+                out,
+                "ralf_run_duration_seconds {}",
+                ended.unwrap_or(now).saturating_sub(started)
+            );
+        }
+
+        out.push_str("# EOF\n");
+        out
+    }
+}
+
+impl EventSink for Metrics {
+    fn emit(&self, event: RunEvent) {
+        self.record(&event);
+    }
+}
+
+fn family(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+    let _ = writeln!(out, "# HELP {name} {help}");
+}
+
+fn per_model<T: std::fmt::Display>(out: &mut String, name: &str, values: &BTreeMap<String, T>) {
+    for (model, value) in values {
+        let model = model
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n");
+        let _ = writeln!(out, "{name}{{model=\"{model}\"}} {value}");
+    }
+}
+
+/// Serve `metrics` at `http://<addr>/metrics` from a background thread.
+/// Returns the address listened on (useful when `addr` has port 0).
+pub fn serve(addr: SocketAddr, metrics: Arc<Metrics>) -> std::io::Result<SocketAddr> {
+    let listener = TcpListener::bind(addr)?;
+    let bound = listener.local_addr()?;
+    std::thread::Builder::new()
+        .name("metrics".to_string())
+        .spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if let Err(e) = answer(stream, &metrics) {
+                    tracing::debug!("Metrics request failed: {e}");
+                }
+            }
+        })?;
+    Ok(bound)
+}
+
+/// Answer one HTTP request.
+fn answer(mut stream: TcpStream, metrics: &Metrics) -> std::io::Result<()> {
+    let request = Request::read(&mut stream)?;
+    let (status, content_type, body) = match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/metrics" | "/") => ("200 OK", CONTENT_TYPE, metrics.render()),
+        ("GET", _) => ("404 Not Found", "text/plain", "Not found\n".to_string()),
+        _ => (
+            "405 Method Not Allowed",
+            "text/plain",
+            "Method not allowed\n".to_string(),
+        ),
+    };
+    respond(&mut stream, status, content_type, &body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::usage::Usage;
+    use std::io::{Read, Write};
+    use std::time::Duration;
+
+    fn model_completed(model: &str, rate_limited: bool) -> RunEvent {
+        RunEvent::ModelCompleted {
+            iteration: 1,
+            model: model.to_string(),
+            duration_ms: 10,
+            has_promise: false,
+            rate_limited,
+            output_preview: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let clock = Arc::new(ManualClock::new(1000));
+        let metrics = Metrics::with_clock(clock.clone());
+        metrics.record(&RunEvent::Started {
+            run_id: "run-1".to_string(),
+            max_iterations: 5,
+            branch: None,
+        });
+        metrics.record(&RunEvent::IterationStarted {
+            iteration: 1,
+            model: "claude".to_string(),
+        });
+        metrics.record(&model_completed("claude", true));
+        metrics.record(&RunEvent::CooldownStarted {
+            model: "claude".to_string(),
+            duration_secs: 300,
+        });
+        metrics.record(&model_completed("codex", false));
+        metrics.record(&RunEvent::UsageUpdated {
+            iteration: 1,
+            model: "codex".to_string(),
+            usage: Usage {
+                total_tokens: 1200,
+                cost_usd: 0.25,
+                ..Usage::default()
+            },
+            run_total: Usage::default(),
+        });
+        metrics.record(&RunEvent::IterationCompleted {
+            iteration: 1,
+            all_verifiers_passed: false,
+        });
+        clock.advance(Duration::from_secs(100));
+
+        let page = metrics.render();
+        for line in [
+            "ralf_iterations_total 1",
+            "ralf_iteration_failures_total 1",
+            "ralf_model_invocations_total{model=\"claude\"} 1",
+            "ralf_model_invocations_total{model=\"codex\"} 1",
+            "ralf_rate_limits_total{model=\"claude\"} 1",
+            "ralf_cost_usd_total{model=\"codex\"} 0.25",
+            "ralf_tokens_total{model=\"codex\"} 1200",
+            "ralf_cooldown_seconds{model=\"claude\"} 200",
+            "ralf_run_duration_seconds 100",
+        ] {
+            assert!(page.lines().any(|l| l == line), "missing {line}:\n{page}");
+        }
+        assert!(page.ends_with("# EOF\n"));
+
+        // The run's duration stops when it ends
+        metrics.record(&RunEvent::Cancelled { iteration: 1 });
+        clock.advance(Duration::from_secs(50));
+        assert!(metrics.render().contains("ralf_run_duration_seconds 100\n"));
+    }
+
+    #[test]
+    fn test_serve() {
+        let metrics = Arc::new(Metrics::new());
+        metrics.record(&RunEvent::IterationStarted {
+            iteration: 1,
+            model: "claude".to_string(),
+        });
+        let addr = serve("127.0.0.1:0".parse().unwrap(), metrics).unwrap();
+
+        let get = |path: &str| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            write!(stream, "GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+        let response = get("/metrics");
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains(CONTENT_TYPE));
+        assert!(response.contains("ralf_iterations_total 1\n"));
+        assert!(get("/other").starts_with("HTTP/1.1 404"));
+    }
+}
@@ -0,0 +1,224 @@
+//! Per-model health tracking for adaptive model selection.
+//!
+//! Every invocation updates the model's success rate, rate-limit rate and
+//! average latency, kept as exponentially weighted moving averages so that
+//! recent behavior counts most. [`ModelSelection::Adaptive`] picks the
+//! available model with the best [`ModelHealth::score`]. Stats persist in
+//! `.ralf/model-stats.json` and carry over between runs.
+//!
+//! [`ModelSelection::Adaptive`]: crate::config::ModelSelection::Adaptive
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::runner::InvocationResult;
+use crate::state::{atomic_write, current_timestamp, StateError};
+
+/// Name of the stats file inside `.ralf/`.
+pub const MODEL_STATS_FILE: &str = "model-stats.json";
+
+/// Weight of the newest observation in each moving average.
+const RECENT_WEIGHT: f64 = 0.3;
+
+/// Latency at which a model's score is halved (10 minutes).
+const LATENCY_HALF_SCORE_MS: f64 = 600_000.0;
+
+/// Stats unused for this long are ignored, so a model that was struggling
+/// gets another chance.
+const STALE_AFTER_SECS: u64 = 24 * 60 * 60;
+
+/// Score of a model with no recent stats. Higher than any measured score,
+/// so new models are tried before settling on a favorite.
+const UNKNOWN_SCORE: f64 = 1.0;
+
+/// How a model invocation ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvocationOutcome {
+    /// The model ran to completion.
+    Success,
+    /// The model exited with an error or could not be run.
+    Failure,
+    /// The model was rate limited.
+    RateLimited,
+    /// The model did not finish within its timeout.
+    TimedOut,
+}
+
+impl InvocationOutcome {
+    /// Classify a completed invocation.
+    pub fn of(result: &InvocationResult) -> Self {
+        if result.rate_limited {
+            Self::RateLimited
+        } else if result.exit_code == Some(0) {
+            Self::Success
+        } else {
+            Self::Failure
+        }
+    }
+}
+
+/// Recent behavior of one model.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelHealth {
+    /// Invocations recorded.
+    pub invocations: u64,
+    /// Moving average of successful invocations (0.0 to 1.0).
+    pub success_rate: f64,
+    /// Moving average of rate-limited invocations (0.0 to 1.0).
+    pub rate_limit_rate: f64,
+    /// Moving average of invocation latency in milliseconds.
+    pub avg_latency_ms: f64,
+    /// When the model was last invoked (Unix timestamp).
+    pub last_used: u64,
+}
+
+impl ModelHealth {
+    /// Fold one invocation into the averages.
+    pub fn record(&mut self, outcome: InvocationOutcome, duration_ms: u64) {
+        let success = f64::from(u8::from(outcome == InvocationOutcome::Success));
+        let rate_limited = f64::from(u8::from(outcome == InvocationOutcome::RateLimited));
+        #[allow(clippy::cast_precision_loss)]
+        let latency = duration_ms as f64;
+
+        if self.invocations == 0 {
+            self.success_rate = success;
+            self.rate_limit_rate = rate_limited;
+        } else {
+            self.success_rate = moving_average(self.success_rate, success);
+            self.rate_limit_rate = moving_average(self.rate_limit_rate, rate_limited);
+        }
+        // Calls that return early (rate limited, failed to start) say
+        // nothing about speed
+        if outcome != InvocationOutcome::RateLimited && duration_ms > 0 {
+            self.avg_latency_ms = if self.avg_latency_ms > 0.0 {
+                moving_average(self.avg_latency_ms, latency)
+            } else {
+                latency
+            };
+        }
+        self.invocations += 1;
+        self.last_used = current_timestamp();
+    }
+
+    /// Health score from 0.0 (unusable) to 1.0: success rate, discounted by
+    /// rate-limit frequency and latency.
+    pub fn score(&self) -> f64 {
+        let latency_factor = LATENCY_HALF_SCORE_MS / (LATENCY_HALF_SCORE_MS + self.avg_latency_ms);
+        self.success_rate * (1.0 - self.rate_limit_rate) * latency_factor
+    }
+}
+
+fn moving_average(average: f64, value: f64) -> f64 {
+    average * (1.0 - RECENT_WEIGHT) + value * RECENT_WEIGHT
+}
+
+/// Health stats for every model, keyed by model name.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModelStats {
+    /// Per-model health.
+    #[serde(flatten)]
+    pub models: BTreeMap<String, ModelHealth>,
+}
+
+impl ModelStats {
+    /// Path of the stats file for a `.ralf` directory.
+    pub fn path(ralf_dir: &Path) -> PathBuf {
+        ralf_dir.join(MODEL_STATS_FILE)
+    }
+
+    /// Load stats from a file (empty if it doesn't exist).
+    pub fn load(path: &Path) -> Result<Self, StateError> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path).map_err(StateError::Io)?;
+        serde_json::from_str(&content).map_err(StateError::Parse)
+    }
+
+    /// Save stats to a file.
+    pub fn save(&self, path: &Path) -> Result<(), StateError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(StateError::Io)?;
+        }
+        let content = serde_json::to_string_pretty(self).map_err(StateError::Serialize)?;
+        atomic_write(path, content.as_bytes()).map_err(StateError::Io)
+    }
+
+    /// Record an invocation of `model`.
+    pub fn record(&mut self, model: &str, outcome: InvocationOutcome, duration_ms: u64) {
+        let health = self.models.entry(model.to_string()).or_default();
+        if current_timestamp().saturating_sub(health.last_used) > STALE_AFTER_SECS {
+            *health = ModelHealth::default();
+        }
+        health.record(outcome, duration_ms);
+    }
+
+    /// Score for `model`; models without recent stats get the highest score.
+    pub fn score(&self, model: &str) -> f64 {
+        self.models
+            .get(model)
+            .filter(|h| current_timestamp().saturating_sub(h.last_used) <= STALE_AFTER_SECS)
+            .map_or(UNKNOWN_SCORE, ModelHealth::score)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_score() {
+        let mut stats = ModelStats::default();
+        assert!((stats.score("claude") - UNKNOWN_SCORE).abs() < f64::EPSILON);
+
+        stats.record("claude", InvocationOutcome::Success, 60_000);
+        stats.record("codex", InvocationOutcome::Success, 600_000);
+        stats.record("gemini", InvocationOutcome::RateLimited, 1_000);
+
+        // Faster wins; rate-limited scores zero
+        assert!(stats.score("claude") > stats.score("codex"));
+        assert!((stats.score("codex") - 0.5).abs() < 1e-9);
+        assert!(stats.score("gemini").abs() < f64::EPSILON);
+
+        // Recent failures pull the score down
+        let before = stats.score("claude");
+        stats.record("claude", InvocationOutcome::TimedOut, 600_000);
+        assert!(stats.score("claude") < before);
+        assert_eq!(stats.models["claude"].invocations, 2);
+    }
+
+    #[test]
+    fn test_stale_stats_are_reset() {
+        let mut stats = ModelStats::default();
+        stats.models.insert(
+            "codex".into(),
+            ModelHealth {
+                invocations: 5,
+                last_used: current_timestamp() - STALE_AFTER_SECS - 1,
+                ..ModelHealth::default()
+            },
+        );
+        assert!((stats.score("codex") - UNKNOWN_SCORE).abs() < f64::EPSILON);
+
+        stats.record("codex", InvocationOutcome::Success, 1_000);
+        assert_eq!(stats.models["codex"].invocations, 1);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = ModelStats::path(temp.path());
+
+        let mut stats = ModelStats::default();
+        stats.record("claude", InvocationOutcome::Failure, 5_000);
+        stats.save(&path).unwrap();
+
+        let loaded = ModelStats::load(&path).unwrap();
+        assert_eq!(loaded.models, stats.models);
+        assert!(ModelStats::load(&temp.path().join("missing.json"))
+            .unwrap()
+            .models
+            .is_empty());
+    }
+}
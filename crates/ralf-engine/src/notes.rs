@@ -0,0 +1,122 @@
+//! Working notes kept by the model across iterations.
+//!
+//! Each model invocation starts from scratch, so without help an iteration
+//! re-explores everything the previous one already found. With `notes`
+//! enabled, [`PromptBuilder`](crate::PromptBuilder) asks the model to keep
+//! `.ralf/runs/<run_id>/NOTES.md` up to date (what it tried, what worked,
+//! what is left) and shows the next iteration what the file says.
+
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use crate::context::ContextBudget;
+
+/// Working notes file in a run's directory.
+pub const NOTES_FILE: &str = "NOTES.md";
+
+/// Heading of the working notes section.
+const NOTES_HEADER: &str = "\n\n## Working notes\n";
+
+/// Most tokens the previous notes may take.
+const MAX_NOTES_TOKENS: usize = 2_000;
+
+/// The run's working notes file and what it says so far.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorkingNotes {
+    /// Where the model keeps the notes.
+    pub path: PathBuf,
+    /// The notes as the last iteration left them (empty before the first).
+    pub contents: String,
+}
+
+impl WorkingNotes {
+    /// Path of the notes file for the run in `run_dir`.
+    pub fn path(run_dir: &Path) -> PathBuf {
+        run_dir.join(NOTES_FILE)
+    }
+
+    /// Read the notes at `path`. A missing or unreadable file means no
+    /// notes yet.
+    pub fn read(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            contents: std::fs::read_to_string(path).unwrap_or_default(),
+        }
+    }
+
+    /// The working notes section, fitted to `budget`: the request to keep
+    /// the notes, then the previous notes if any fit. Empty if even the
+    /// request doesn't fit.
+    pub fn render_within(&self, budget: &mut ContextBudget) -> String {
+        let mut out = NOTES_HEADER.to_string();
+        let _ = write!(
+            out,
+            "\nKeep working notes in `{}`: what you tried, what worked, what didn't, and what is left. \
+             Update the file before you finish; the next iteration starts from it.\n",
+            self.path.display()
+        );
+        if !budget.fits(&out) {
+            return String::new();
+        }
+        budget.reserve(&out);
+
+        let contents = self.contents.trim();
+        if contents.is_empty() {
+            return out;
+        }
+        let heading = "\nNotes so far:\n```markdown\n";
+        if !budget.fits(heading) {
+            return out;
+        }
+        budget.reserve(heading);
+        let notes = budget.take(contents, MAX_NOTES_TOKENS);
+        if !notes.is_empty() {
+            let _ = write!(out, "{heading}{}\n```\n", notes.trim_end());
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_missing_notes() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = WorkingNotes::path(temp.path());
+        assert_eq!(path, temp.path().join("NOTES.md"));
+
+        let notes = WorkingNotes::read(&path);
+        assert!(notes.contents.is_empty());
+        let section = notes.render_within(&mut ContextBudget::unlimited());
+        assert!(section.starts_with("\n\n## Working notes\n"));
+        assert!(section.contains(&format!("`{}`", path.display())));
+        assert!(!section.contains("Notes so far"));
+    }
+
+    #[test]
+    fn test_render_previous_notes_within_budget() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = WorkingNotes::path(temp.path());
+        std::fs::write(&path, "- Tried bumping serde; broke the build\n").unwrap();
+
+        let notes = WorkingNotes::read(&path);
+        let section = notes.render_within(&mut ContextBudget::unlimited());
+        assert!(section
+            .contains("Notes so far:\n```markdown\n- Tried bumping serde; broke the build\n```\n"));
+
+        // Long notes are cut to what's left of the budget
+        let long = WorkingNotes {
+            contents: "note ".repeat(5_000),
+            ..notes.clone()
+        };
+        let mut budget = ContextBudget::new(200);
+        let section = long.render_within(&mut budget);
+        assert!(section.contains("Notes so far"));
+        assert!(section.len() < long.contents.len());
+
+        // No room at all
+        assert_eq!(notes.render_within(&mut ContextBudget::new(1)), "");
+    }
+}
@@ -1,20 +1,21 @@
 //! Thread persistence for ralf workflows.
 //!
 //! Provides reliable persistence for Thread state with atomic writes,
-//! schema versioning, and active thread tracking.
+//! schema versioning, and active thread tracking, plus [`DraftHistory`]
+//! for browsing the draft snapshots in `.ralf/drafts/`.
 
-use std::fs::{self, File};
-use std::io::Write;
+use std::fs;
 use std::path::{Path, PathBuf};
-use std::time::{SystemTime, UNIX_EPOCH};
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use tracing::warn;
 
-use crate::thread::Thread;
+use crate::dirs::{DRAFTS_DIR, THREADS_DIR};
+use crate::state::atomic_write;
+use crate::thread::{PhaseKind, Thread, ThreadPhase, TransitionError};
 
 /// Current schema version for thread persistence.
 const CURRENT_SCHEMA_VERSION: u32 = 1;
@@ -39,6 +40,9 @@ pub enum PersistenceError {
 
     #[error("Invalid thread ID: {0}")]
     InvalidId(String),
+
+    #[error(transparent)]
+    Transition(#[from] TransitionError),
 }
 
 /// Summary info for listing threads without loading full state.
@@ -50,6 +54,42 @@ pub struct ThreadSummary {
     pub phase_category: u8,
     pub updated_at: DateTime<Utc>,
     pub is_active: bool,
+    #[serde(default)]
+    pub is_archived: bool,
+}
+
+/// Filter for [`ThreadStore::list_filtered`].
+#[derive(Debug, Clone, Default)]
+pub struct ThreadFilter {
+    /// Only include threads in these phases (empty = any phase).
+    pub phases: Vec<PhaseKind>,
+    /// Only include threads in this phase category (1-5).
+    pub category: Option<u8>,
+    /// Exclude terminal threads (Done, Abandoned).
+    pub exclude_terminal: bool,
+    /// List archived threads instead of live ones.
+    pub archived: bool,
+}
+
+impl ThreadFilter {
+    /// Filter for threads that are still in flight (not Done or Abandoned).
+    pub fn in_flight() -> Self {
+        Self {
+            exclude_terminal: true,
+            ..Self::default()
+        }
+    }
+
+    /// Check whether a thread matches this filter (ignores `archived`).
+    pub fn matches(&self, thread: &Thread) -> bool {
+        if !self.phases.is_empty() && !self.phases.contains(&thread.phase.kind()) {
+            return false;
+        }
+        if self.category.is_some_and(|c| c != thread.phase_category()) {
+            return false;
+        }
+        !(self.exclude_terminal && thread.is_terminal())
+    }
 }
 
 /// On-disk format with schema versioning.
@@ -70,11 +110,18 @@ impl ThreadStore {
     /// Creates the threads directory if it doesn't exist.
     pub fn new(base_path: impl Into<PathBuf>) -> Result<Self, PersistenceError> {
         let base_path = base_path.into();
-        let threads_dir = base_path.join("threads");
+        let threads_dir = base_path.join(THREADS_DIR);
         fs::create_dir_all(&threads_dir)?;
         Ok(Self { base_path })
     }
 
+    /// Create and save a new thread with the given title.
+    pub fn create(&self, title: impl Into<String>) -> Result<Thread, PersistenceError> {
+        let thread = Thread::new(title);
+        self.save(&thread)?;
+        Ok(thread)
+    }
+
     /// Save a thread with atomic write pattern.
     /// Creates thread directory if needed.
     pub fn save(&self, thread: &Thread) -> Result<(), PersistenceError> {
@@ -98,8 +145,18 @@ impl ThreadStore {
     /// Load a thread by ID.
     pub fn load(&self, id: &str) -> Result<Thread, PersistenceError> {
         Self::validate_id(id)?;
+        Self::load_from(&self.thread_dir(id), id)
+    }
+
+    /// Load an archived thread by ID.
+    pub fn load_archived(&self, id: &str) -> Result<Thread, PersistenceError> {
+        Self::validate_id(id)?;
+        Self::load_from(&self.archive_dir(id), id)
+    }
 
-        let path = self.thread_dir(id).join("thread.json");
+    /// Load a thread from its directory.
+    fn load_from(thread_dir: &Path, id: &str) -> Result<Thread, PersistenceError> {
+        let path = thread_dir.join("thread.json");
         if !path.exists() {
             return Err(PersistenceError::ThreadNotFound(id.to_string()));
         }
@@ -158,10 +215,77 @@ impl ThreadStore {
         Ok(())
     }
 
+    /// Load a thread, move it to `target`, and save it.
+    ///
+    /// The transition is validated by the thread state machine; on error the
+    /// stored thread is left unchanged.
+    pub fn transition(&self, id: &str, target: ThreadPhase) -> Result<Thread, PersistenceError> {
+        let mut thread = self.load(id)?;
+        thread.transition_to(target)?;
+        self.save(&thread)?;
+        Ok(thread)
+    }
+
+    /// Archive a thread, hiding it from [`list`](Self::list).
+    /// The thread's data is moved to `archive/<id>` and can be restored.
+    pub fn archive(&self, id: &str) -> Result<(), PersistenceError> {
+        Self::validate_id(id)?;
+
+        let thread_dir = self.thread_dir(id);
+        if !thread_dir.exists() {
+            return Err(PersistenceError::ThreadNotFound(id.to_string()));
+        }
+
+        // Clear active if this was the active thread
+        if let Ok(Some(active_id)) = self.get_active() {
+            if active_id == id {
+                self.clear_active()?;
+            }
+        }
+
+        let archive_dir = self.archive_dir(id);
+        if let Some(parent) = archive_dir.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::rename(&thread_dir, &archive_dir)?;
+        Ok(())
+    }
+
+    /// Restore an archived thread.
+    pub fn unarchive(&self, id: &str) -> Result<(), PersistenceError> {
+        Self::validate_id(id)?;
+
+        let archive_dir = self.archive_dir(id);
+        if !archive_dir.exists() {
+            return Err(PersistenceError::ThreadNotFound(id.to_string()));
+        }
+        if self.thread_dir(id).exists() {
+            return Err(PersistenceError::InvalidData(format!(
+                "thread {id} already exists"
+            )));
+        }
+
+        fs::rename(&archive_dir, self.thread_dir(id))?;
+        Ok(())
+    }
+
     /// List all threads with summary info.
     /// Sorted by `updated_at` descending (most recent first).
     pub fn list(&self) -> Result<Vec<ThreadSummary>, PersistenceError> {
-        let threads_dir = self.base_path.join("threads");
+        self.list_filtered(&ThreadFilter::default())
+    }
+
+    /// List threads matching a filter.
+    /// Sorted by `updated_at` descending (most recent first).
+    pub fn list_filtered(
+        &self,
+        filter: &ThreadFilter,
+    ) -> Result<Vec<ThreadSummary>, PersistenceError> {
+        let threads_dir = if filter.archived {
+            self.base_path.join("archive")
+        } else {
+            self.base_path.join(THREADS_DIR)
+        };
         if !threads_dir.exists() {
             return Ok(Vec::new());
         }
@@ -182,9 +306,13 @@ impl ThreadStore {
                 None => continue,
             };
 
+            if Self::validate_id(&id).is_err() {
+                continue;
+            }
+
             // Try to load the thread, skip if corrupted
-            match self.load(&id) {
-                Ok(thread) => {
+            match Self::load_from(&path, &id) {
+                Ok(thread) if filter.matches(&thread) => {
                     summaries.push(ThreadSummary {
                         id: thread.id.clone(),
                         title: thread.title.clone(),
@@ -192,8 +320,10 @@ impl ThreadStore {
                         phase_category: thread.phase_category(),
                         updated_at: thread.updated_at,
                         is_active: thread.id == active_id,
+                        is_archived: filter.archived,
                     });
                 }
+                Ok(_) => {}
                 Err(e) => {
                     warn!(thread_id = %id, error = %e, "Skipping corrupted thread");
                 }
@@ -360,40 +490,126 @@ impl ThreadStore {
 
     /// Get the path to a thread's directory.
     fn thread_dir(&self, id: &str) -> PathBuf {
-        self.base_path.join("threads").join(id)
+        self.base_path.join(THREADS_DIR).join(id)
+    }
+
+    /// Get the path to an archived thread's directory.
+    fn archive_dir(&self, id: &str) -> PathBuf {
+        self.base_path.join("archive").join(id)
     }
 }
 
-/// Write content atomically using temp file + fsync + rename.
-fn atomic_write(path: &Path, content: &[u8]) -> std::io::Result<()> {
-    // Generate unique temp filename using timestamp and process ID
-    let timestamp = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|d| d.as_nanos())
-        .unwrap_or(0);
-    let pid = std::process::id();
-
-    let file_name = path
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("file");
-    let tmp_name = format!("{file_name}.{timestamp}.{pid}.tmp");
-    let tmp_path = path.with_file_name(tmp_name);
-
-    let result = (|| {
-        let mut file = File::create(&tmp_path)?;
-        file.write_all(content)?;
-        file.sync_all()?;
-        fs::rename(&tmp_path, path)?;
-        Ok(())
-    })();
+/// A saved draft snapshot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DraftRevision {
+    /// Snapshot name (its file stem, e.g. `20260101_120000`).
+    pub name: String,
+    /// Path of the snapshot file.
+    pub path: PathBuf,
+    /// When the snapshot was saved.
+    pub saved_at: DateTime<Utc>,
+}
 
-    if result.is_err() {
-        // Best-effort cleanup
-        let _ = fs::remove_file(&tmp_path);
+/// The draft snapshots written by
+/// [`save_draft_snapshot`](crate::chat::save_draft_snapshot).
+pub struct DraftHistory {
+    drafts_dir: PathBuf,
+}
+
+impl DraftHistory {
+    /// History of the snapshots under `base_path/drafts`.
+    pub fn new(base_path: impl AsRef<Path>) -> Self {
+        Self {
+            drafts_dir: base_path.as_ref().join(DRAFTS_DIR),
+        }
     }
 
-    result
+    /// All revisions, newest first. Empty if nothing was saved yet.
+    pub fn list(&self) -> Result<Vec<DraftRevision>, PersistenceError> {
+        if !self.drafts_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut revisions = Vec::new();
+        for entry in fs::read_dir(&self.drafts_dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("md") {
+                continue;
+            }
+            let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            // Snapshots are named by when they were saved; fall back to the
+            // file's mtime for anything else
+            let saved_at = NaiveDateTime::parse_from_str(name, "%Y%m%d_%H%M%S")
+                .map(|t| t.and_utc())
+                .or_else(|_| {
+                    fs::metadata(&path)
+                        .and_then(|m| m.modified())
+                        .map(Into::into)
+                })?;
+            revisions.push(DraftRevision {
+                name: name.to_string(),
+                path,
+                saved_at,
+            });
+        }
+
+        revisions.sort_by(|a, b| (b.saved_at, &b.name).cmp(&(a.saved_at, &a.name)));
+        Ok(revisions)
+    }
+
+    /// Content of a revision.
+    pub fn load(&self, revision: &DraftRevision) -> Result<String, PersistenceError> {
+        Ok(fs::read_to_string(&revision.path)?)
+    }
+}
+
+/// One line of a [`diff_lines`] result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffLine {
+    /// Line present in both versions.
+    Same(String),
+    /// Line only in the old version.
+    Removed(String),
+    /// Line only in the new version.
+    Added(String),
+}
+
+/// Line diff from `old` to `new` (longest common subsequence), with
+/// removals before additions where lines changed.
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // common[i][j]: length of the LCS of old[i..] and new[j..]
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            diff.push(DiffLine::Same(old[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || common[i + 1][j] >= common[i][j + 1]) {
+            diff.push(DiffLine::Removed(old[i].to_string()));
+            i += 1;
+        } else {
+            diff.push(DiffLine::Added(new[j].to_string()));
+            j += 1;
+        }
+    }
+    diff
 }
 
 #[cfg(test)]
@@ -402,6 +618,47 @@ mod tests {
     use crate::thread::{Thread, ThreadPhase};
     use tempfile::TempDir;
 
+    #[test]
+    fn test_draft_history_list() {
+        let temp = TempDir::new().unwrap();
+        let history = DraftHistory::new(temp.path());
+        assert!(history.list().unwrap().is_empty());
+
+        let drafts = temp.path().join("drafts");
+        fs::create_dir_all(&drafts).unwrap();
+        fs::write(drafts.join("20260102_090000.md"), "# Second").unwrap();
+        fs::write(drafts.join("20260101_120000.md"), "# First").unwrap();
+        fs::write(drafts.join("notes.txt"), "ignored").unwrap();
+
+        let revisions = history.list().unwrap();
+        let names: Vec<&str> = revisions.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["20260102_090000", "20260101_120000"]);
+        assert_eq!(
+            revisions[1].saved_at.to_rfc3339(),
+            "2026-01-01T12:00:00+00:00"
+        );
+        assert_eq!(history.load(&revisions[1]).unwrap(), "# First");
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        let diff = diff_lines(
+            "# Task\nold line\nkeep\n",
+            "# Task\nnew line\nkeep\nadded\n",
+        );
+        assert_eq!(
+            diff,
+            vec![
+                DiffLine::Same("# Task".into()),
+                DiffLine::Removed("old line".into()),
+                DiffLine::Added("new line".into()),
+                DiffLine::Same("keep".into()),
+                DiffLine::Added("added".into()),
+            ]
+        );
+        assert!(diff_lines("", "").is_empty());
+    }
+
     fn setup_test_store() -> (TempDir, ThreadStore) {
         let temp = TempDir::new().unwrap();
         let store = ThreadStore::new(temp.path()).unwrap();
@@ -577,6 +834,115 @@ mod tests {
         assert_eq!(list[0].title, "Valid Thread");
     }
 
+    #[test]
+    fn test_create() {
+        let (_temp, store) = setup_test_store();
+
+        let thread = store.create("New Thread").unwrap();
+        let loaded = store.load(&thread.id).unwrap();
+        assert_eq!(loaded.title, "New Thread");
+        assert!(matches!(loaded.phase, ThreadPhase::Drafting));
+    }
+
+    #[test]
+    fn test_archive_and_unarchive() {
+        let (_temp, store) = setup_test_store();
+
+        let thread = store.create("Archive Me").unwrap();
+        let other = store.create("Keep Me").unwrap();
+        store.set_active(&thread.id).unwrap();
+
+        store.archive(&thread.id).unwrap();
+        assert!(!store.exists(&thread.id));
+        assert!(store.get_active().unwrap().is_none());
+        assert_eq!(store.load_archived(&thread.id).unwrap().title, "Archive Me");
+
+        let live = store.list().unwrap();
+        assert_eq!(live.len(), 1);
+        assert_eq!(live[0].id, other.id);
+
+        let archived = store
+            .list_filtered(&ThreadFilter {
+                archived: true,
+                ..ThreadFilter::default()
+            })
+            .unwrap();
+        assert_eq!(archived.len(), 1);
+        assert!(archived[0].is_archived);
+
+        store.unarchive(&thread.id).unwrap();
+        assert!(store.exists(&thread.id));
+        assert_eq!(store.list().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_transition_persists() {
+        let (_temp, store) = setup_test_store();
+
+        let mut thread = store.create("Review Me").unwrap();
+        thread.phase = ThreadPhase::PendingReview;
+        store.save(&thread).unwrap();
+
+        let approved = store.transition(&thread.id, ThreadPhase::Approved).unwrap();
+        assert_eq!(approved.phase, ThreadPhase::Approved);
+        assert_eq!(store.load(&thread.id).unwrap().phase, ThreadPhase::Approved);
+
+        assert!(matches!(
+            store.transition(&thread.id, ThreadPhase::Drafting),
+            Err(PersistenceError::Transition(_))
+        ));
+        assert_eq!(store.load(&thread.id).unwrap().phase, ThreadPhase::Approved);
+    }
+
+    #[test]
+    fn test_archive_not_found() {
+        let (_temp, store) = setup_test_store();
+
+        assert!(matches!(
+            store.archive("nonexistent"),
+            Err(PersistenceError::ThreadNotFound(_))
+        ));
+        assert!(matches!(
+            store.unarchive("nonexistent"),
+            Err(PersistenceError::ThreadNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_list_filtered_by_phase() {
+        let (_temp, store) = setup_test_store();
+
+        store.create("Drafting").unwrap();
+        let mut running = Thread::new("Running");
+        running.phase = ThreadPhase::Running { iteration: 1 };
+        store.save(&running).unwrap();
+        let mut done = Thread::new("Done");
+        done.phase = ThreadPhase::Done {
+            commit_sha: "abc123".into(),
+        };
+        store.save(&done).unwrap();
+
+        let filter = ThreadFilter {
+            phases: vec![PhaseKind::Running],
+            ..ThreadFilter::default()
+        };
+        let list = store.list_filtered(&filter).unwrap();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].title, "Running");
+
+        let filter = ThreadFilter {
+            category: Some(1),
+            ..ThreadFilter::default()
+        };
+        let list = store.list_filtered(&filter).unwrap();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].title, "Drafting");
+
+        let list = store.list_filtered(&ThreadFilter::in_flight()).unwrap();
+        assert_eq!(list.len(), 2);
+        assert!(list.iter().all(|s| s.title != "Done"));
+    }
+
     #[test]
     fn test_get_active_none() {
         let (_temp, store) = setup_test_store();
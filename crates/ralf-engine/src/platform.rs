@@ -0,0 +1,180 @@
+//! Platform differences in spawning and stopping processes.
+//!
+//! On Unix, each model or verifier process leads its own process group,
+//! which is how ralf stops everything it started. Windows has no process
+//! groups, so there each process is put in a Job Object instead. Most model
+//! CLIs install on Windows as batch-file shims (`claude.cmd`), which can't
+//! be started without `cmd /C`.
+//!
+//! The decisions are plain functions, so they're tested on every platform;
+//! only the Win32 calls are Windows-only.
+
+use std::path::Path;
+
+/// Whether `program` is a batch file (`.cmd` or `.bat`).
+pub fn is_batch_file(program: &Path) -> bool {
+    program
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("cmd") || ext.eq_ignore_ascii_case("bat"))
+}
+
+/// Program and arguments that run `program args` on Windows, given where
+/// `program` was found on PATH.
+///
+/// Executables run as they are. Batch files go through `cmd /C`, as do
+/// programs that weren't found, since they may be `cmd` builtins (`dir`,
+/// `type`) or rely on `PATHEXT`.
+pub fn windows_command_line(
+    program: &str,
+    args: &[String],
+    resolved: Option<&Path>,
+) -> (String, Vec<String>) {
+    match resolved {
+        Some(path) if !is_batch_file(path) => (program.to_string(), args.to_vec()),
+        _ => {
+            let mut cmd_args = vec!["/C".to_string(), program.to_string()];
+            cmd_args.extend_from_slice(args);
+            ("cmd".to_string(), cmd_args)
+        }
+    }
+}
+
+/// A command that runs `program args` on this platform.
+pub fn std_command(program: &str, args: &[String]) -> std::process::Command {
+    let (program, args) = if cfg!(windows) {
+        windows_command_line(program, args, which::which(program).ok().as_deref())
+    } else {
+        (program.to_string(), args.to_vec())
+    };
+    let mut cmd = std::process::Command::new(program);
+    cmd.args(args);
+    cmd
+}
+
+/// An async command that runs `program args` on this platform.
+pub fn tokio_command(program: &str, args: &[String]) -> tokio::process::Command {
+    std_command(program, args).into()
+}
+
+/// Job Objects holding each spawned process and everything it starts.
+#[cfg(windows)]
+pub(crate) mod job {
+    use std::os::windows::io::RawHandle;
+    use std::sync::Mutex;
+
+    use windows_sys::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows_sys::Win32::System::JobObjects::{
+        AssignProcessToJobObject, CreateJobObjectW, JobObjectExtendedLimitInformation,
+        SetInformationJobObject, TerminateJobObject, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+        JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+    };
+
+    /// Job handles by the PID of the process they were created for.
+    static JOBS: Mutex<Vec<(u32, usize)>> = Mutex::new(Vec::new());
+
+    /// Put the process `pid` in a new Job Object. Its children join the job
+    /// as they start, and closing the job stops whatever is left in it.
+    ///
+    /// Anything the process starts before it is assigned escapes the job,
+    /// which is why [`terminate`] falls back to `taskkill /T`.
+    pub(crate) fn attach(pid: u32, process: RawHandle) {
+        // SAFETY: `process` is a live process handle owned by the caller's
+        // `Child`, and the job handle is closed in `release`
+        unsafe {
+            let job = CreateJobObjectW(std::ptr::null(), std::ptr::null());
+            if job.is_null() {
+                return;
+            }
+            let mut limits: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = std::mem::zeroed();
+            limits.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+            let configured = SetInformationJobObject(
+                job,
+                JobObjectExtendedLimitInformation,
+                std::ptr::from_ref(&limits).cast(),
+                u32::try_from(std::mem::size_of_val(&limits)).unwrap_or(u32::MAX),
+            ) != 0;
+            if !configured || AssignProcessToJobObject(job, process) == 0 {
+                CloseHandle(job);
+                return;
+            }
+            if let Ok(mut jobs) = JOBS.lock() {
+                jobs.push((pid, job as usize));
+            }
+        }
+    }
+
+    /// Stop everything in the job of process `pid`. Returns whether there
+    /// was a job to stop.
+    pub(crate) fn terminate(pid: u32) -> bool {
+        let Some(job) = find(pid) else {
+            return false;
+        };
+        // SAFETY: the handle stays open until `release` removes it
+        unsafe { TerminateJobObject(job as HANDLE, 1) != 0 }
+    }
+
+    /// Close the job of process `pid`, stopping anything still in it.
+    pub(crate) fn release(pid: u32) {
+        let job = JOBS.lock().ok().and_then(|mut jobs| {
+            let index = jobs.iter().position(|(p, _)| *p == pid)?;
+            Some(jobs.remove(index).1)
+        });
+        if let Some(job) = job {
+            // SAFETY: removed from `JOBS` above, so it is closed only once
+            unsafe {
+                CloseHandle(job as HANDLE);
+            }
+        }
+    }
+
+    fn find(pid: u32) -> Option<usize> {
+        let jobs = JOBS.lock().ok()?;
+        jobs.iter().find(|(p, _)| *p == pid).map(|(_, job)| *job)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_is_batch_file() {
+        assert!(is_batch_file(Path::new(r"C:\npm\claude.cmd")));
+        assert!(is_batch_file(Path::new("build.BAT")));
+        assert!(!is_batch_file(Path::new(r"C:\git\git.exe")));
+        assert!(!is_batch_file(Path::new("/usr/bin/claude")));
+    }
+
+    #[test]
+    fn test_windows_command_line() {
+        let exe = Path::new(r"C:\Program Files\Git\cmd\git.exe");
+        assert_eq!(
+            windows_command_line("git", &args(&["status"]), Some(exe)),
+            ("git".to_string(), args(&["status"]))
+        );
+
+        // Batch-file shims and unresolved programs go through cmd
+        let shim = Path::new(r"C:\Users\me\AppData\Roaming\npm\npm.cmd");
+        assert_eq!(
+            windows_command_line("npm", &args(&["test"]), Some(shim)),
+            ("cmd".to_string(), args(&["/C", "npm", "test"]))
+        );
+        assert_eq!(
+            windows_command_line("dir", &[], None),
+            ("cmd".to_string(), args(&["/C", "dir"]))
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_std_command_runs_program_directly() {
+        let cmd = std_command("cargo", &args(&["test"]));
+        assert_eq!(cmd.get_program(), "cargo");
+        assert_eq!(cmd.get_args().collect::<Vec<_>>(), ["test"]);
+    }
+}
@@ -0,0 +1,183 @@
+//! Polishing runs.
+//!
+//! Once a thread is implemented, `/polish` runs the loop again over the same
+//! spec with a different profile: the prompt asks for documentation, tests
+//! and cleanup only, the review gate allows smaller changes, and
+//! doc-oriented verifiers (such as `cargo doc` or a link checker) run
+//! alongside the usual ones. [`PolishConfig::profile`] turns the repo's
+//! config into the one a polishing run uses.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{Config, ConfigError, PromptConfig, VerifierConfig, VerifierRunWhen};
+
+/// What a polishing run is asked to do, ahead of the spec.
+pub const DEFAULT_POLISH_PREAMBLE: &str =
+    "This is a polishing pass over work that already meets the spec below. \
+Only improve documentation, tests and code clarity: fix or add doc comments and \
+README sections, add tests for paths nothing tests yet, and remove dead code. \
+Do not change behavior or add features.";
+
+/// Most files a polishing iteration may touch without review, by default.
+const DEFAULT_MAX_FILES: usize = 10;
+
+/// Most lines a polishing iteration may change without review, by default.
+const DEFAULT_MAX_LINES: usize = 300;
+
+/// How polishing runs differ from implementation runs.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PolishConfig {
+    /// Text placed before `PROMPT.md` (and any configured preamble) instead
+    /// of [`DEFAULT_POLISH_PREAMBLE`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preamble: Option<String>,
+
+    /// Most files an iteration may touch before it needs review (default
+    /// 10, or the review limit if that is lower).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_files: Option<usize>,
+
+    /// Most lines an iteration may add and remove before it needs review
+    /// (default 300, or the review limit if that is lower).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_lines: Option<usize>,
+
+    /// Verifiers run on top of the configured ones, e.g. a link checker.
+    /// Without any, Rust repositories get `cargo doc --no-deps`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub verifiers: Vec<VerifierConfig>,
+}
+
+impl PolishConfig {
+    /// Whether nothing differs from the defaults.
+    pub fn is_default(&self) -> bool {
+        self.preamble.is_none()
+            && self.max_files.is_none()
+            && self.max_lines.is_none()
+            && self.verifiers.is_empty()
+    }
+
+    /// `config` as a polishing run in `repo_path` uses it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a configured preamble or postamble file can't be
+    /// read.
+    pub fn profile(&self, config: &Config, repo_path: &Path) -> Result<Config, ConfigError> {
+        let mut polished = config.clone();
+
+        let (preamble, postamble) = config.prompt.load(repo_path)?;
+        let mut polish_preamble = self
+            .preamble
+            .clone()
+            .unwrap_or_else(|| DEFAULT_POLISH_PREAMBLE.to_string());
+        if let Some(preamble) = preamble {
+            polish_preamble.push_str("\n\n");
+            polish_preamble.push_str(&preamble);
+        }
+        polished.prompt = PromptConfig {
+            preamble: Some(polish_preamble),
+            postamble,
+            ..PromptConfig::default()
+        };
+
+        let tighter =
+            |limit: Option<usize>, polish: usize| Some(limit.map_or(polish, |l| l.min(polish)));
+        polished.review.max_files = tighter(
+            config.review.max_files,
+            self.max_files.unwrap_or(DEFAULT_MAX_FILES),
+        );
+        polished.review.max_lines = tighter(
+            config.review.max_lines,
+            self.max_lines.unwrap_or(DEFAULT_MAX_LINES),
+        );
+
+        let verifiers = if self.verifiers.is_empty() && repo_path.join("Cargo.toml").is_file() {
+            vec![doc_verifier()]
+        } else {
+            self.verifiers.clone()
+        };
+        for verifier in verifiers {
+            if !polished.verifiers.iter().any(|v| v.name == verifier.name) {
+                polished.verifiers.push(verifier);
+            }
+        }
+        Ok(polished)
+    }
+}
+
+/// `cargo doc`, catching broken doc comments and intra-doc links.
+fn doc_verifier() -> VerifierConfig {
+    VerifierConfig {
+        name: "docs".into(),
+        command_argv: vec!["cargo".into(), "doc".into(), "--no-deps".into()],
+        timeout_seconds: 300,
+        run_when: VerifierRunWhen::OnChange,
+        depends_on: Vec::new(),
+        fail_fast: false,
+        tags: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::review::ReviewConfig;
+
+    #[test]
+    fn test_profile() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::write(temp.path().join("Cargo.toml"), "[package]\n").unwrap();
+        let config = Config {
+            prompt: PromptConfig {
+                preamble: Some("Use British spelling.".into()),
+                postamble: Some("Output DONE when done.".into()),
+                ..PromptConfig::default()
+            },
+            review: ReviewConfig {
+                max_files: Some(4),
+                ..ReviewConfig::default()
+            },
+            ..Config::default()
+        };
+
+        let polished = PolishConfig::default()
+            .profile(&config, temp.path())
+            .unwrap();
+        let preamble = polished.prompt.preamble.unwrap();
+        assert!(preamble.starts_with(DEFAULT_POLISH_PREAMBLE));
+        assert!(preamble.ends_with("\n\nUse British spelling."));
+        assert_eq!(
+            polished.prompt.postamble.as_deref(),
+            Some("Output DONE when done.")
+        );
+        // The lower of the two limits holds
+        assert_eq!(polished.review.max_files, Some(4));
+        assert_eq!(polished.review.max_lines, Some(DEFAULT_MAX_LINES));
+        let names: Vec<&str> = polished.verifiers.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(names, vec!["tests", "docs"]);
+    }
+
+    #[test]
+    fn test_profile_with_own_verifiers() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::write(temp.path().join("Cargo.toml"), "[package]\n").unwrap();
+        let polish = PolishConfig {
+            preamble: Some("Docs only.".into()),
+            verifiers: vec![VerifierConfig {
+                name: "links".into(),
+                command_argv: vec!["lychee".into(), "docs/".into()],
+                ..doc_verifier()
+            }],
+            ..PolishConfig::default()
+        };
+        assert!(!polish.is_default());
+
+        let polished = polish.profile(&Config::default(), temp.path()).unwrap();
+        assert_eq!(polished.prompt.preamble.as_deref(), Some("Docs only."));
+        let names: Vec<&str> = polished.verifiers.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(names, vec!["tests", "links"]);
+    }
+}
@@ -1,17 +1,32 @@
 //! Preflight checks for thread implementation.
 //!
 //! Validates all prerequisites before a thread can transition from Finalized
-//! to Preflight phase. Ensures git safety, spec validity, model availability,
-//! verifier availability, and single-run enforcement.
+//! to Preflight phase. Ensures git safety, spec validity, that the spec fits
+//! each model's context window, model availability, verifier availability,
+//! and single-run enforcement (within the store and across processes), and
+//! warns about processes left running by earlier sessions and about specs
+//! that ask for changes to protected paths.
+//!
+//! [`run_preflight_with_probes`] additionally sends each selected model a
+//! tiny test prompt, so missing auth or an exhausted quota is caught before
+//! it burns an iteration.
 
 use std::collections::HashSet;
 use std::path::Path;
+use std::time::Duration;
+
+use tokio::task::JoinSet;
 
 use crate::chat::draft_has_promise;
-use crate::config::Config;
+use crate::config::{Config, ModelConfig};
+use crate::context::{estimate_tokens, ContextBudget};
+use crate::discovery::{probe_model_async, ProbeResult};
 use crate::git::GitSafety;
+use crate::lock::{LockError, LockInfo, LockStatus, RunLock};
 use crate::parse_criteria;
 use crate::persistence::ThreadStore;
+use crate::runner::{find_leftover_processes, LeftoverProcess};
+use crate::safety::SafetyConfig;
 use crate::thread::Thread;
 
 /// Result of running preflight checks.
@@ -84,15 +99,25 @@ pub fn run_preflight(
     store: &ThreadStore,
     config: &Config,
 ) -> PreflightResult {
+    // Git checks look at the repository the thread's runs work in
+    let target = thread
+        .run_config
+        .as_ref()
+        .map_or_else(|| repo_path.to_path_buf(), |c| c.target_root(repo_path));
+
     // Run all checks in order
     let checks = vec![
-        check_git_state(thread, repo_path),
-        check_baseline_capturable(repo_path),
+        check_git_state(thread, &target),
+        check_baseline_capturable(&target),
         check_spec_has_promise(thread, store),
         check_criteria_parseable(thread, store),
+        check_prompt_fits(thread, store, config),
         check_models_available(thread, config),
         check_verifiers_available(config),
         check_no_concurrent_run(thread, store),
+        check_leftover_processes(&find_leftover_processes()),
+        check_run_lock(RunLock::read(&repo_path.join(".ralf"))),
+        check_protected_paths(thread, store, config),
     ];
 
     let passed = checks.iter().all(|c| c.passed);
@@ -100,6 +125,55 @@ pub fn run_preflight(
     PreflightResult { passed, checks }
 }
 
+/// Run all preflight checks, then probe each selected model.
+///
+/// Each probe is a real (tiny) model invocation, so this costs one request
+/// per model and can take up to `timeout`. Use [`run_preflight`] when only
+/// local checks are wanted.
+pub async fn run_preflight_with_probes(
+    thread: &Thread,
+    repo_path: &Path,
+    store: &ThreadStore,
+    config: &Config,
+    timeout: Duration,
+) -> PreflightResult {
+    let mut result = run_preflight(thread, repo_path, store, config);
+
+    let probes = probe_models(&selected_models(thread, config), timeout).await;
+    let check = check_models_reachable(&probes);
+    result.passed &= check.passed;
+    result.checks.push(check);
+
+    result
+}
+
+/// Models a run of `thread` will use: the thread's own list, falling back to
+/// the configured models.
+pub fn selected_models(thread: &Thread, config: &Config) -> Vec<String> {
+    match &thread.run_config {
+        Some(run_config) if !run_config.models.is_empty() => run_config.models.clone(),
+        _ => config.models.iter().map(|m| m.name.clone()).collect(),
+    }
+}
+
+/// Probe models concurrently, returning results in the order given.
+pub async fn probe_models(names: &[String], timeout: Duration) -> Vec<ProbeResult> {
+    let mut tasks = JoinSet::new();
+    for (index, name) in names.iter().enumerate() {
+        let name = name.clone();
+        tasks.spawn(async move { (index, probe_model_async(&name, timeout).await) });
+    }
+
+    let mut probes = Vec::with_capacity(names.len());
+    while let Some(joined) = tasks.join_next().await {
+        if let Ok(probe) = joined {
+            probes.push(probe);
+        }
+    }
+    probes.sort_by_key(|(index, _)| *index);
+    probes.into_iter().map(|(_, probe)| probe).collect()
+}
+
 /// Check 1: Git working tree is clean or on a ralf-managed branch.
 ///
 /// Passes if:
@@ -107,6 +181,14 @@ pub fn run_preflight(
 /// - Working tree is clean (no uncommitted changes), OR
 /// - Currently on a ralf/<thread-id> branch for this thread
 fn check_git_state(thread: &Thread, repo_path: &Path) -> PreflightCheck {
+    if !repo_path.is_dir() {
+        return PreflightCheck {
+            name: "git_state".to_string(),
+            label: "Git Working Tree".to_string(),
+            passed: false,
+            message: format!("Target directory {} not found", repo_path.display()),
+        };
+    }
     let git = GitSafety::new(repo_path);
 
     // Not a git repo? Pass with warning (user's choice to run without git safety)
@@ -131,13 +213,13 @@ fn check_git_state(thread: &Thread, repo_path: &Path) -> PreflightCheck {
 
     // On this thread's branch? Pass (resuming previous work).
     if let Ok(branch) = git.current_branch() {
-        let thread_branch = format!("ralf/{}", thread.id);
-        if branch == thread_branch {
+        let recorded = thread.branch.as_ref().map(|b| b.name.as_str());
+        if branch == format!("ralf/{}", thread.id) || recorded == Some(branch.as_str()) {
             return PreflightCheck {
                 name: "git_state".to_string(),
                 label: "Git Working Tree".to_string(),
                 passed: true,
-                message: format!("On thread branch {thread_branch}"),
+                message: format!("On thread branch {branch}"),
             };
         }
     }
@@ -293,7 +375,84 @@ fn check_criteria_parseable(thread: &Thread, store: &ThreadStore) -> PreflightCh
     }
 }
 
-/// Check 5: At least one model is configured.
+/// Check 5: The spec fits every selected model's context window.
+///
+/// Passes if the estimated size of the spec is within each model's prompt
+/// budget (its context window minus room for the reply).
+fn check_prompt_fits(thread: &Thread, store: &ThreadStore, config: &Config) -> PreflightCheck {
+    let spec_content = match store.load_latest_spec(&thread.id) {
+        Ok(Some(content)) => content,
+        Ok(None) => {
+            return PreflightCheck {
+                name: "prompt_fits".to_string(),
+                label: "Context Window".to_string(),
+                passed: false,
+                message: "No spec saved for this thread".to_string(),
+            };
+        }
+        Err(e) => {
+            return PreflightCheck {
+                name: "prompt_fits".to_string(),
+                label: "Context Window".to_string(),
+                passed: false,
+                message: format!("Failed to load spec: {e}"),
+            };
+        }
+    };
+
+    let models: Vec<ModelConfig> = selected_models(thread, config)
+        .iter()
+        .map(|name| {
+            config
+                .models
+                .iter()
+                .find(|m| &m.name == name)
+                .cloned()
+                .unwrap_or_else(|| ModelConfig::default_for(name))
+        })
+        .collect();
+    prompt_fits(estimate_tokens(&spec_content), &models)
+}
+
+fn prompt_fits(tokens: usize, models: &[ModelConfig]) -> PreflightCheck {
+    let budgets: Vec<(&str, usize)> = models
+        .iter()
+        .map(|m| (m.name.as_str(), ContextBudget::for_model(m).remaining()))
+        .collect();
+    let too_small: Vec<String> = budgets
+        .iter()
+        .filter(|(_, budget)| tokens > *budget)
+        .map(|(name, budget)| format!("{name} ({budget} available)"))
+        .collect();
+
+    let (passed, message) = if !too_small.is_empty() {
+        (
+            false,
+            format!(
+                "Spec is ~{tokens} tokens, too large for {}",
+                too_small.join(", ")
+            ),
+        )
+    } else if let Some(smallest) = budgets.iter().map(|(_, budget)| *budget).min() {
+        (
+            true,
+            format!(
+                "Spec is ~{tokens} tokens; fits all {} model(s) (smallest allows {smallest})",
+                budgets.len()
+            ),
+        )
+    } else {
+        (true, format!("Spec is ~{tokens} tokens"))
+    };
+    PreflightCheck {
+        name: "prompt_fits".to_string(),
+        label: "Context Window".to_string(),
+        passed,
+        message,
+    }
+}
+
+/// Check 6: At least one model is configured.
 ///
 /// Passes if:
 /// - Config has at least one model in models vec, OR
@@ -332,7 +491,7 @@ fn check_models_available(thread: &Thread, config: &Config) -> PreflightCheck {
     }
 }
 
-/// Check 6: Required verifiers are configured.
+/// Check 7: Required verifiers are configured.
 ///
 /// Passes if:
 /// - All verifiers listed in `config.required_verifiers` exist in `config.verifiers`
@@ -376,7 +535,7 @@ fn check_verifiers_available(config: &Config) -> PreflightCheck {
     }
 }
 
-/// Check 7: No other thread is currently Running.
+/// Check 8: No other thread is currently Running.
 ///
 /// Passes if:
 /// - No other thread in the store is in Running, Verifying, or Paused phase
@@ -414,6 +573,154 @@ fn check_no_concurrent_run(thread: &Thread, store: &ThreadStore) -> PreflightChe
     }
 }
 
+/// Check 9: No processes left over from earlier sessions.
+///
+/// Always passes: model CLIs and verifiers orphaned by a ralf session that
+/// exited don't block a run, but they compete with it for CPU, so they are
+/// listed for the user to stop.
+fn check_leftover_processes(leftovers: &[LeftoverProcess]) -> PreflightCheck {
+    let message = if leftovers.is_empty() {
+        "No leftover processes from earlier runs".to_string()
+    } else {
+        let list: Vec<String> = leftovers
+            .iter()
+            .map(|p| format!("{} ({})", p.pid, p.name))
+            .collect();
+        format!(
+            "Warning: {} process(es) left running by earlier runs: {}",
+            leftovers.len(),
+            list.join(", ")
+        )
+    };
+    PreflightCheck {
+        name: "leftover_processes".to_string(),
+        label: "Leftover Processes".to_string(),
+        passed: true,
+        message,
+    }
+}
+
+/// Check 10: No other ralf process holds the run lock.
+///
+/// Passes if:
+/// - There is no `.ralf/lock`, OR
+/// - The process that held it has exited (the lock is taken over)
+fn check_run_lock(lock: Result<Option<LockInfo>, LockError>) -> PreflightCheck {
+    let (passed, message) = match lock {
+        Ok(None) => (true, "No other ralf process running".to_string()),
+        Ok(Some(info)) => match info.status() {
+            LockStatus::Abandoned => (
+                true,
+                format!("Lock left by exited {} will be taken over", info.holder()),
+            ),
+            LockStatus::Stale => (false, LockError::Stale(info).to_string()),
+            LockStatus::Held => (false, LockError::Held(info).to_string()),
+        },
+        Err(e) => (false, e.to_string()),
+    };
+    PreflightCheck {
+        name: "run_lock".to_string(),
+        label: "Run Lock".to_string(),
+        passed,
+        message,
+    }
+}
+
+/// Check 11: The spec doesn't ask for changes to protected paths.
+///
+/// Always passes: the runner reverts such changes anyway, but a spec that
+/// asks for them sends the model after work it can't keep.
+fn check_protected_paths(thread: &Thread, store: &ThreadStore, config: &Config) -> PreflightCheck {
+    let spec = store
+        .load_latest_spec(&thread.id)
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+    protected_paths_mentioned(&spec, &config.safety)
+}
+
+fn protected_paths_mentioned(spec: &str, safety: &SafetyConfig) -> PreflightCheck {
+    let message = if safety.protected_paths.is_empty() {
+        "No protected paths configured".to_string()
+    } else {
+        let mentioned = safety.mentioned_in(spec);
+        if mentioned.is_empty() {
+            "Spec doesn't mention protected paths".to_string()
+        } else {
+            format!(
+                "Warning: spec mentions protected paths, whose changes will be reverted: {}",
+                mentioned.join(", ")
+            )
+        }
+    };
+    PreflightCheck {
+        name: "protected_paths".to_string(),
+        label: "Protected Paths".to_string(),
+        passed: true,
+        message,
+    }
+}
+
+/// Check 12: Selected models answer a test prompt.
+///
+/// Passes if at least one model is ready; models that need auth or are out
+/// of quota are listed so they can be fixed or dropped before the run.
+fn check_models_reachable(probes: &[ProbeResult]) -> PreflightCheck {
+    let ready: Vec<&str> = probes
+        .iter()
+        .filter(|p| p.success)
+        .map(|p| p.name.as_str())
+        .collect();
+    let problems: Vec<String> = probes
+        .iter()
+        .filter(|p| !p.success)
+        .map(|p| {
+            let reason = if p.needs_auth {
+                p.suggestions
+                    .first()
+                    .map_or_else(|| "needs auth".to_string(), |s| format!("needs auth ({s})"))
+            } else if p.rate_limited {
+                p.rate_limit_reset.as_ref().map_or_else(
+                    || "out of quota".to_string(),
+                    |reset| format!("out of quota until {reset}"),
+                )
+            } else {
+                p.issues
+                    .first()
+                    .cloned()
+                    .unwrap_or_else(|| "probe failed".to_string())
+            };
+            format!("{}: {reason}", p.name)
+        })
+        .collect();
+
+    let (passed, message) = if probes.is_empty() {
+        (true, "No models to probe".to_string())
+    } else if problems.is_empty() {
+        (true, format!("{} responded", ready.join(", ")))
+    } else if ready.is_empty() {
+        (
+            false,
+            format!("No model responded: {}", problems.join("; ")),
+        )
+    } else {
+        (
+            true,
+            format!(
+                "{} responded; skipping {}",
+                ready.join(", "),
+                problems.join("; ")
+            ),
+        )
+    };
+    PreflightCheck {
+        name: "models_reachable".to_string(),
+        label: "Model Auth & Quota".to_string(),
+        passed,
+        message,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -484,12 +791,16 @@ mod tests {
                 timeout_seconds: 300,
                 rate_limit_patterns: vec![],
                 default_cooldown_seconds: 900,
+                max_context_tokens: None,
             }],
             verifiers: vec![VerifierConfig {
                 name: "tests".to_string(),
                 command_argv: vec!["cargo".to_string(), "test".to_string()],
                 timeout_seconds: 300,
                 run_when: crate::config::VerifierRunWhen::OnChange,
+                depends_on: vec![],
+                fail_fast: false,
+                tags: vec![],
             }],
             required_verifiers: vec!["tests".to_string()],
             ..Default::default()
@@ -772,6 +1083,8 @@ mod tests {
         thread.run_config = Some(crate::thread::RunConfig {
             models: vec!["model1".to_string()],
             max_iterations: 5,
+            target: None,
+            completion_promise: None,
         });
 
         let config = Config::default();
@@ -820,6 +1133,9 @@ mod tests {
             command_argv: vec!["cargo".to_string(), "test".to_string()],
             timeout_seconds: 300,
             run_when: crate::config::VerifierRunWhen::OnChange,
+            depends_on: vec![],
+            fail_fast: false,
+            tags: vec![],
         }];
 
         let check = check_verifiers_available(&config);
@@ -902,7 +1218,7 @@ mod tests {
         let result = run_preflight(&thread, temp.path(), &store, &config);
 
         assert!(result.passed);
-        assert_eq!(result.checks.len(), 7);
+        assert_eq!(result.checks.len(), 11);
         assert!(result.checks.iter().all(|c| c.passed));
     }
 
@@ -924,7 +1240,153 @@ mod tests {
         // Should have multiple failures
         let failure_count = result.checks.iter().filter(|c| !c.passed).count();
         assert!(failure_count > 1);
-        // All 7 checks should still run
-        assert_eq!(result.checks.len(), 7);
+        // All 11 checks should still run
+        assert_eq!(result.checks.len(), 11);
+    }
+
+    #[test]
+    fn test_prompt_fits() {
+        let small = ModelConfig {
+            max_context_tokens: Some(1_000),
+            ..ModelConfig::default_for("local")
+        };
+        let check = prompt_fits(500, &[ModelConfig::default_for("claude"), small.clone()]);
+        assert!(check.passed);
+        assert!(check
+            .message
+            .contains("fits all 2 model(s) (smallest allows 750)"));
+
+        let check = prompt_fits(900, &[ModelConfig::default_for("claude"), small]);
+        assert!(!check.passed);
+        assert_eq!(
+            check.message,
+            "Spec is ~900 tokens, too large for local (750 available)"
+        );
+    }
+
+    #[test]
+    fn test_check_prompt_fits_uses_thread_models() {
+        let (_temp, store) = setup_test_env();
+        let mut thread = create_thread_with_spec(&store, true, true);
+        thread.run_config = Some(crate::thread::RunConfig {
+            max_iterations: 5,
+            models: vec!["gemini".into()],
+            target: None,
+            completion_promise: None,
+        });
+        let check = check_prompt_fits(&thread, &store, &default_config_with_models());
+        assert!(check.passed);
+        assert!(check.message.contains("smallest allows 750000"));
+    }
+
+    fn probe(name: &str, success: bool) -> ProbeResult {
+        ProbeResult {
+            name: name.into(),
+            success,
+            response_time_ms: None,
+            needs_auth: false,
+            rate_limited: false,
+            rate_limit_reset: None,
+            issues: Vec::new(),
+            suggestions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_check_models_reachable() {
+        let check = check_models_reachable(&[probe("claude", true)]);
+        assert!(check.passed);
+        assert_eq!(check.message, "claude responded");
+
+        let mut limited = probe("codex", false);
+        limited.rate_limited = true;
+        limited.rate_limit_reset = Some("3:00 PM".into());
+        let check = check_models_reachable(&[probe("claude", true), limited.clone()]);
+        assert!(check.passed);
+        assert!(check
+            .message
+            .contains("skipping codex: out of quota until 3:00 PM"));
+
+        let mut auth = probe("gemini", false);
+        auth.needs_auth = true;
+        auth.suggestions = vec!["Run `gemini`".into()];
+        let check = check_models_reachable(&[limited, auth]);
+        assert!(!check.passed);
+        assert!(check.message.contains("gemini: needs auth (Run `gemini`)"));
+    }
+
+    #[tokio::test]
+    async fn test_run_preflight_with_probes_missing_model() {
+        let (temp, store) = setup_test_env();
+        let thread = create_thread_with_spec(&store, true, true);
+        let mut config = default_config_with_models();
+        config.models[0].name = "ralf-no-such-model-cli".into();
+
+        let result = run_preflight_with_probes(
+            &thread,
+            temp.path(),
+            &store,
+            &config,
+            Duration::from_secs(1),
+        )
+        .await;
+
+        assert!(!result.passed);
+        let check = result.checks.last().unwrap();
+        assert_eq!(check.name, "models_reachable");
+        assert!(check.message.contains("not found on PATH"));
+    }
+
+    #[test]
+    fn test_check_leftover_processes_warns_but_passes() {
+        let check = check_leftover_processes(&[]);
+        assert!(check.passed);
+        assert!(check.message.starts_with("No leftover"));
+
+        let check = check_leftover_processes(&[LeftoverProcess {
+            pid: 4242,
+            name: "node".into(),
+            parent_pid: 100,
+        }]);
+        assert!(check.passed);
+        assert!(check.message.contains("1 process(es)"));
+        assert!(check.message.contains("4242 (node)"));
+    }
+
+    #[test]
+    fn test_protected_paths_mentioned_warns_but_passes() {
+        let check = protected_paths_mentioned("Edit Cargo.lock", &SafetyConfig::default());
+        assert!(check.passed);
+        assert!(check.message.starts_with("No protected paths"));
+
+        let safety = SafetyConfig {
+            protected_paths: vec![".github/".into(), "Cargo.lock".into()],
+            ..SafetyConfig::default()
+        };
+        let check = protected_paths_mentioned("Add a --fast flag", &safety);
+        assert!(check.passed);
+        assert!(check.message.starts_with("Spec doesn't"));
+
+        let check = protected_paths_mentioned("Update `Cargo.lock` and .github/ci.yml.", &safety);
+        assert!(check.passed);
+        assert!(check.message.starts_with("Warning:"));
+        assert!(check.message.ends_with("Cargo.lock, .github/ci.yml"));
+    }
+
+    #[test]
+    fn test_check_run_lock() {
+        assert!(check_run_lock(Ok(None)).passed);
+
+        let temp = TempDir::new().unwrap();
+        let _lock = RunLock::acquire(temp.path(), Some("abc123"), false).unwrap();
+        let check = check_run_lock(RunLock::read(temp.path()));
+        assert!(!check.passed);
+        assert!(check.message.contains("run abc123"));
+
+        let mut abandoned = RunLock::read(temp.path()).unwrap().unwrap();
+        abandoned.pid = u32::MAX;
+        let check = check_run_lock(Ok(Some(abandoned)));
+        assert!(check.passed);
+        assert!(check.message.contains("taken over"));
     }
 }
@@ -0,0 +1,622 @@
+//! Per-iteration prompt composition.
+//!
+//! Every iteration starts from the same `PROMPT.md`. To stop models from
+//! repeating the same mistakes, [`PromptBuilder`] appends a "Previous
+//! iteration feedback" section describing what went wrong in the last few
+//! iterations: failed criteria and their reasons, failing verifier output,
+//! and a summary of the changes made so far. With `repo_context` enabled,
+//! a map of the repository ([`RepoContext`]) comes before the feedback.
+//! With `notes` enabled, a "Working notes" section ([`WorkingNotes`])
+//! between the two asks the model to keep notes for the next iteration and
+//! shows what the previous ones wrote. Answers the user gave to the
+//! model's questions (`<promise>NEEDS_INPUT: ...`) come right after the
+//! base prompt, in every iteration that follows.
+//!
+//! A configured preamble and postamble are wrapped around the base prompt,
+//! after filling in their placeholders (see [`render_template`]).
+
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::path::Path;
+use std::process::Command;
+
+use crate::context::ContextBudget;
+use crate::notes::WorkingNotes;
+use crate::repo_context::RepoContext;
+
+/// Maximum lines kept from a failing verifier's output.
+const EXCERPT_LINES: usize = 30;
+
+/// Maximum characters kept from a failing verifier's output.
+const EXCERPT_CHARS: usize = 2000;
+
+/// Maximum lines kept from the diff summary.
+const DIFF_SUMMARY_LINES: usize = 20;
+
+/// Heading and preamble of the feedback section.
+const FEEDBACK_HEADER: &str = "\n\n## Previous iteration feedback\n\nEarlier attempts at this task did not finish. Fix these problems rather than repeating them.\n";
+
+/// Heading and preamble of the answers section.
+const ANSWERS_HEADER: &str = "\n\n## Answers from the user\n\nEarlier iterations asked these questions. Go by the answers rather than asking again.\n";
+
+/// What went wrong in one iteration.
+#[derive(Debug, Clone, Default)]
+pub struct IterationFeedback {
+    /// Iteration number.
+    pub iteration: u64,
+    /// Model that ran the iteration.
+    pub model: String,
+    /// Whether the output lacked the completion promise.
+    pub promise_missing: bool,
+    /// Failed completion criteria and the verifier's reason.
+    pub failed_criteria: Vec<(String, Option<String>)>,
+    /// Failed verifiers and an excerpt of their output.
+    pub failed_verifiers: Vec<(String, String)>,
+    /// What the iteration did that it wasn't allowed to (see
+    /// [`violation_messages`](crate::safety::violation_messages)).
+    pub safety: Vec<String>,
+    /// `git diff --stat` of the workspace after the iteration.
+    pub diff_summary: String,
+}
+
+impl IterationFeedback {
+    /// Whether there is anything worth telling the next model.
+    pub fn is_empty(&self) -> bool {
+        !self.promise_missing
+            && self.failed_criteria.is_empty()
+            && self.failed_verifiers.is_empty()
+            && self.safety.is_empty()
+    }
+}
+
+/// Builds each iteration's prompt from the base prompt and recent feedback.
+#[derive(Debug, Clone)]
+pub struct PromptBuilder {
+    base: String,
+    window: usize,
+    history: VecDeque<IterationFeedback>,
+    repo_context: Option<RepoContext>,
+    notes: Option<WorkingNotes>,
+    preamble: Option<String>,
+    postamble: Option<String>,
+    iteration: u64,
+    model: String,
+    last_failed: Vec<String>,
+    answers: Vec<(String, String)>,
+}
+
+impl PromptBuilder {
+    /// Create a builder keeping feedback from the last `window` iterations
+    /// (0 disables feedback).
+    pub fn new(base: impl Into<String>, window: usize) -> Self {
+        Self {
+            base: base.into(),
+            window,
+            history: VecDeque::with_capacity(window),
+            repo_context: None,
+            notes: None,
+            preamble: None,
+            postamble: None,
+            iteration: 0,
+            model: String::new(),
+            last_failed: Vec::new(),
+            answers: Vec::new(),
+        }
+    }
+
+    /// Wrap templates around the base prompt, as loaded by
+    /// [`PromptConfig::load`](crate::PromptConfig::load).
+    pub fn set_wrapper(&mut self, preamble: Option<String>, postamble: Option<String>) {
+        self.preamble = preamble;
+        self.postamble = postamble;
+    }
+
+    /// Set the iteration and model the next prompt is for, as filled into
+    /// the preamble and postamble.
+    pub fn start_iteration(&mut self, iteration: u64, model: &str) {
+        self.iteration = iteration;
+        self.model = model.to_string();
+    }
+
+    /// Describe the repository in the prompts that follow (`None` leaves it
+    /// out). Refreshed each iteration, as the run changes files.
+    pub fn set_repo_context(&mut self, context: Option<RepoContext>) {
+        self.repo_context = context;
+    }
+
+    /// Ask for working notes in the prompts that follow, showing what they
+    /// say so far (`None` leaves the section out). Re-read each iteration,
+    /// as the model updates them.
+    pub fn set_notes(&mut self, notes: Option<WorkingNotes>) {
+        self.notes = notes;
+    }
+
+    /// Record the user's `answer` to a question the model asked, for every
+    /// prompt that follows.
+    pub fn record_answer(&mut self, question: impl Into<String>, answer: impl Into<String>) {
+        self.answers.push((question.into(), answer.into()));
+    }
+
+    /// Record an iteration's feedback. Iterations with nothing to report
+    /// still count toward the window, so stale feedback ages out.
+    pub fn record(&mut self, feedback: IterationFeedback) {
+        self.last_failed = feedback
+            .failed_criteria
+            .iter()
+            .map(|(criterion, _)| criterion.clone())
+            .collect();
+        if self.window == 0 {
+            return;
+        }
+        if self.history.len() == self.window {
+            self.history.pop_front();
+        }
+        self.history.push_back(feedback);
+    }
+
+    /// The prompt for the next iteration, with no size limit.
+    pub fn build(&self) -> String {
+        self.build_within(&mut ContextBudget::unlimited())
+    }
+
+    /// The prompt for the next iteration, fitted to `budget`.
+    ///
+    /// The base prompt and its wrapper are always included in full, as are
+    /// the user's answers. Feedback is added newest
+    /// first until the budget runs out; the newest section is truncated if
+    /// it doesn't fit on its own, older ones are dropped. Working notes come
+    /// next, and the repository context gets whatever is left.
+    pub fn build_within(&self, budget: &mut ContextBudget) -> String {
+        let mut base = self.wrapped_base();
+        if !self.answers.is_empty() {
+            base = base.trim_end().to_string();
+            base.push_str(ANSWERS_HEADER);
+            for (question, answer) in &self.answers {
+                let _ = write!(base, "\n- Question: {question}\n  Answer: {answer}\n");
+            }
+        }
+        budget.reserve(&base);
+        let feedback = self.feedback_within(budget);
+        let notes = self
+            .notes
+            .as_ref()
+            .map(|notes| notes.render_within(budget))
+            .unwrap_or_default();
+        let context = self
+            .repo_context
+            .as_ref()
+            .map(|context| context.render_within(budget))
+            .unwrap_or_default();
+        if feedback.is_empty() && notes.is_empty() && context.is_empty() {
+            return base;
+        }
+
+        let mut prompt = base.trim_end().to_string();
+        prompt.push_str(&context);
+        prompt.push_str(&notes);
+        prompt.push_str(&feedback);
+        prompt
+    }
+
+    /// The base prompt between the rendered preamble and postamble.
+    fn wrapped_base(&self) -> String {
+        if self.preamble.is_none() && self.postamble.is_none() {
+            return self.base.clone();
+        }
+        let render = |template: &str| {
+            render_template(template, self.iteration, &self.model, &self.last_failed)
+        };
+        let parts = [
+            self.preamble.as_deref().map(render),
+            Some(self.base.clone()),
+            self.postamble.as_deref().map(render),
+        ];
+        parts
+            .iter()
+            .flatten()
+            .map(|part| part.trim())
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    /// The feedback section, or nothing if there is none or no room.
+    fn feedback_within(&self, budget: &mut ContextBudget) -> String {
+        if !budget.fits(FEEDBACK_HEADER) {
+            return String::new();
+        }
+        budget.reserve(FEEDBACK_HEADER);
+
+        let mut sections = Vec::new();
+        for feedback in self.history.iter().rev().filter(|f| !f.is_empty()) {
+            let mut section = String::new();
+            write_feedback(&mut section, feedback);
+            if budget.fits(&section) {
+                budget.reserve(&section);
+                sections.push(section);
+            } else {
+                if sections.is_empty() {
+                    sections.push(budget.take(&section, usize::MAX));
+                }
+                break;
+            }
+        }
+        if sections.iter().all(String::is_empty) {
+            return String::new();
+        }
+
+        let mut feedback = FEEDBACK_HEADER.to_string();
+        for section in sections.iter().rev() {
+            feedback.push_str(section);
+        }
+        feedback
+    }
+}
+
+/// Fill in a preamble or postamble template.
+///
+/// `{iteration}` becomes the iteration number, `{model}` the model's name,
+/// and `{failed_criteria}` the criteria the previous iteration failed,
+/// comma-separated ("none" if there were none). `{{` and `}}` stand for
+/// literal braces; anything else in braces is left as it is.
+pub fn render_template(
+    template: &str,
+    iteration: u64,
+    model: &str,
+    failed_criteria: &[String],
+) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(i) = rest.find(['{', '}']) {
+        out.push_str(&rest[..i]);
+        rest = &rest[i..];
+        if rest.starts_with("{{") || rest.starts_with("}}") {
+            out.push_str(&rest[..1]);
+            rest = &rest[2..];
+            continue;
+        }
+        let placeholder = rest
+            .strip_prefix('{')
+            .and_then(|inner| inner.split_once('}'));
+        let value = placeholder.and_then(|(name, after)| {
+            let value = match name {
+                "iteration" => iteration.to_string(),
+                "model" => model.to_string(),
+                "failed_criteria" if failed_criteria.is_empty() => "none".to_string(),
+                "failed_criteria" => failed_criteria.join(", "),
+                _ => return None,
+            };
+            Some((value, after))
+        });
+        if let Some((value, after)) = value {
+            out.push_str(&value);
+            rest = after;
+        } else {
+            out.push_str(&rest[..1]);
+            rest = &rest[1..];
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn write_feedback(out: &mut String, feedback: &IterationFeedback) {
+    let _ = write!(
+        out,
+        "\n### Iteration {} ({})\n\n",
+        feedback.iteration, feedback.model
+    );
+
+    if feedback.promise_missing {
+        out.push_str("- The completion promise was not in the output.\n");
+    }
+    for message in &feedback.safety {
+        let _ = writeln!(out, "- {message}");
+    }
+
+    if !feedback.failed_criteria.is_empty() {
+        out.push_str("\nFailed criteria:\n");
+        for (criterion, reason) in &feedback.failed_criteria {
+            match reason {
+                Some(reason) => {
+                    let _ = writeln!(out, "- {criterion}: {reason}");
+                }
+                None => {
+                    let _ = writeln!(out, "- {criterion}");
+                }
+            }
+        }
+    }
+
+    if !feedback.failed_verifiers.is_empty() {
+        out.push_str("\nFailed verifiers:\n");
+        for (name, output) in &feedback.failed_verifiers {
+            let _ = writeln!(out, "- {name}:\n```\n{}\n```", excerpt(output));
+        }
+    }
+
+    if !feedback.diff_summary.trim().is_empty() {
+        let _ = writeln!(
+            out,
+            "\nChanges so far:\n```\n{}\n```",
+            feedback.diff_summary.trim_end()
+        );
+    }
+}
+
+/// The tail of a verifier's output: the last [`EXCERPT_LINES`] lines, capped
+/// at [`EXCERPT_CHARS`] characters. Failures usually show up at the end.
+pub fn excerpt(output: &str) -> String {
+    let lines: Vec<&str> = output.trim_end().lines().collect();
+    let start = lines.len().saturating_sub(EXCERPT_LINES);
+    let tail = lines[start..].join("\n");
+
+    let chars = tail.chars().count();
+    if chars > EXCERPT_CHARS {
+        let kept: String = tail.chars().skip(chars - EXCERPT_CHARS).collect();
+        format!("...{kept}")
+    } else if start > 0 {
+        format!("...\n{tail}")
+    } else {
+        tail
+    }
+}
+
+/// `git diff --stat HEAD` for `work_dir`, truncated to a readable length.
+/// Only changes under `work_dir` count, so a run targeting a subdirectory
+/// sees its own. Empty if git isn't available or nothing changed.
+pub fn diff_summary(work_dir: &Path) -> String {
+    let Ok(output) = Command::new("git")
+        .args(["diff", "--stat", "--relative", "HEAD"])
+        .current_dir(work_dir)
+        .output()
+    else {
+        return String::new();
+    };
+    if !output.status.success() {
+        return String::new();
+    }
+
+    let stat = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stat.lines().collect();
+    if lines.len() > DIFF_SUMMARY_LINES {
+        // Keep the "N files changed" total at the end
+        let mut kept = lines[..DIFF_SUMMARY_LINES - 1].join("\n");
+        let _ = write!(
+            kept,
+            "\n ... ({} more files)\n{}",
+            lines.len() - DIFF_SUMMARY_LINES,
+            lines[lines.len() - 1]
+        );
+        kept
+    } else {
+        stat.trim_end().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::estimate_tokens;
+
+    fn failed_iteration(iteration: u64) -> IterationFeedback {
+        IterationFeedback {
+            iteration,
+            model: "claude".into(),
+            promise_missing: false,
+            failed_criteria: vec![("Tests pass".into(), Some("2 tests fail".into()))],
+            failed_verifiers: vec![("tests".into(), "test foo ... FAILED".into())],
+            safety: Vec::new(),
+            diff_summary: " src/lib.rs | 4 ++--".into(),
+        }
+    }
+
+    #[test]
+    fn test_build_without_feedback_is_base() {
+        let mut builder = PromptBuilder::new("Do the thing", 2);
+        assert_eq!(builder.build(), "Do the thing");
+
+        builder.record(IterationFeedback {
+            iteration: 1,
+            model: "codex".into(),
+            ..IterationFeedback::default()
+        });
+        assert_eq!(builder.build(), "Do the thing");
+    }
+
+    #[test]
+    fn test_safety_feedback() {
+        let mut builder = PromptBuilder::new("base", 2);
+        builder.record(IterationFeedback {
+            iteration: 1,
+            model: "codex".into(),
+            safety: vec![
+                "Changes to protected paths were reverted: Cargo.lock. Leave them alone.".into(),
+            ],
+            ..IterationFeedback::default()
+        });
+        assert!(builder.build().contains(
+            "### Iteration 1 (codex)\n\n- Changes to protected paths were reverted: Cargo.lock."
+        ));
+    }
+
+    #[test]
+    fn test_build_includes_feedback() {
+        let mut builder = PromptBuilder::new("Do the thing\n", 2);
+        builder.record(failed_iteration(1));
+
+        let prompt = builder.build();
+        assert!(prompt.starts_with("Do the thing\n\n## Previous iteration feedback"));
+        assert!(prompt.contains("### Iteration 1 (claude)"));
+        assert!(prompt.contains("- Tests pass: 2 tests fail"));
+        assert!(prompt.contains("- tests:\n```\ntest foo ... FAILED\n```"));
+        assert!(prompt.contains("src/lib.rs | 4 ++--"));
+    }
+
+    #[test]
+    fn test_feedback_window() {
+        let mut builder = PromptBuilder::new("base", 2);
+        for i in 1..=3 {
+            builder.record(failed_iteration(i));
+        }
+        let prompt = builder.build();
+        assert!(!prompt.contains("Iteration 1 "));
+        assert!(prompt.contains("Iteration 2 "));
+        assert!(prompt.contains("Iteration 3 "));
+
+        let mut disabled = PromptBuilder::new("base", 0);
+        disabled.record(failed_iteration(1));
+        assert_eq!(disabled.build(), "base");
+    }
+
+    #[test]
+    fn test_build_within_budget_prefers_newest() {
+        let mut builder = PromptBuilder::new("base", 3);
+        for i in 1..=3 {
+            builder.record(failed_iteration(i));
+        }
+        let full = builder.build();
+        let mut one_section = String::new();
+        write_feedback(&mut one_section, &failed_iteration(3));
+
+        // Room for the base, header and a single section: only the newest fits
+        let tokens = estimate_tokens("base")
+            + estimate_tokens(FEEDBACK_HEADER)
+            + estimate_tokens(&one_section)
+            + 1;
+        let prompt = builder.build_within(&mut ContextBudget::new(tokens));
+        assert!(prompt.len() < full.len());
+        assert!(prompt.contains("Iteration 3 "));
+        assert!(!prompt.contains("Iteration 2 "));
+
+        // No room for feedback at all
+        let prompt = builder.build_within(&mut ContextBudget::new(1));
+        assert_eq!(prompt, "base");
+    }
+
+    #[test]
+    fn test_repo_context_comes_before_feedback() {
+        let mut builder = PromptBuilder::new("base\n", 2);
+        builder.set_repo_context(Some(RepoContext {
+            files: vec!["src/lib.rs".into()],
+            ..RepoContext::default()
+        }));
+        assert!(builder
+            .build()
+            .starts_with("base\n\n## Repository context\n\nFiles"));
+
+        builder.record(failed_iteration(1));
+        let prompt = builder.build();
+        let context = prompt.find("## Repository context").unwrap();
+        let feedback = prompt.find("## Previous iteration feedback").unwrap();
+        assert!(context < feedback);
+
+        builder.set_repo_context(None);
+        assert!(!builder.build().contains("## Repository context"));
+    }
+
+    #[test]
+    fn test_notes_between_context_and_feedback() {
+        let mut builder = PromptBuilder::new("base\n", 2);
+        builder.set_notes(Some(WorkingNotes {
+            path: "/repo/.ralf/runs/run-1/NOTES.md".into(),
+            contents: "- Parser done, CLI flag left".into(),
+        }));
+        assert!(builder.build().starts_with("base\n\n## Working notes\n"));
+
+        builder.set_repo_context(Some(RepoContext {
+            files: vec!["src/lib.rs".into()],
+            ..RepoContext::default()
+        }));
+        builder.record(failed_iteration(1));
+        let prompt = builder.build();
+        let context = prompt.find("## Repository context").unwrap();
+        let notes = prompt.find("## Working notes").unwrap();
+        let feedback = prompt.find("## Previous iteration feedback").unwrap();
+        assert!(context < notes && notes < feedback);
+        assert!(prompt.contains("- Parser done, CLI flag left"));
+
+        builder.set_notes(None);
+        assert!(!builder.build().contains("## Working notes"));
+    }
+
+    #[test]
+    fn test_answers_follow_base() {
+        let mut builder = PromptBuilder::new("base\n", 2);
+        builder.record_answer("Postgres or SQLite?", "SQLite");
+        assert_eq!(
+            builder.build(),
+            format!("base{ANSWERS_HEADER}\n- Question: Postgres or SQLite?\n  Answer: SQLite\n")
+        );
+
+        // Answers are kept whole, before any feedback
+        builder.record(failed_iteration(1));
+        let prompt = builder.build_within(&mut ContextBudget::new(1));
+        assert!(prompt.ends_with("Answer: SQLite\n"));
+        let prompt = builder.build();
+        let answers = prompt.find("## Answers from the user").unwrap();
+        let feedback = prompt.find("## Previous iteration feedback").unwrap();
+        assert!(answers < feedback);
+    }
+
+    #[test]
+    fn test_render_template() {
+        let failed = vec!["Tests pass".to_string(), "Docs updated".to_string()];
+        assert_eq!(
+            render_template(
+                "Iteration {iteration} by {model}: fix {failed_criteria}",
+                3,
+                "codex",
+                &failed
+            ),
+            "Iteration 3 by codex: fix Tests pass, Docs updated"
+        );
+        assert_eq!(
+            render_template("{failed_criteria}", 1, "claude", &[]),
+            "none"
+        );
+        assert_eq!(
+            render_template(
+                "{{model}} {unknown} {\"json\": 1} }model}",
+                1,
+                "claude",
+                &[]
+            ),
+            "{model} {unknown} {\"json\": 1} }model}"
+        );
+    }
+
+    #[test]
+    fn test_wrapper_surrounds_base() {
+        let mut builder = PromptBuilder::new("Do the thing\n", 2);
+        builder.set_wrapper(
+            Some("House rules for iteration {iteration} ({model}).\n".into()),
+            Some("Don't touch migrations/. Still failing: {failed_criteria}".into()),
+        );
+        builder.start_iteration(1, "claude");
+        assert_eq!(
+            builder.build(),
+            "House rules for iteration 1 (claude).\n\nDo the thing\n\nDon't touch migrations/. Still failing: none"
+        );
+
+        builder.record(failed_iteration(1));
+        builder.start_iteration(2, "codex");
+        let prompt = builder.build();
+        assert!(prompt.starts_with("House rules for iteration 2 (codex)."));
+        assert!(prompt.contains("Still failing: Tests pass\n\n## Previous iteration feedback"));
+    }
+
+    #[test]
+    fn test_excerpt_keeps_tail() {
+        let mut output = String::new();
+        for i in 1..=50 {
+            let _ = writeln!(output, "line {i}");
+        }
+        let excerpt = excerpt(&output);
+        assert!(excerpt.starts_with("...\nline 21"));
+        assert!(excerpt.ends_with("line 50"));
+
+        assert_eq!(super::excerpt("short"), "short");
+        assert!(super::excerpt(&"x".repeat(5000)).len() <= EXCERPT_CHARS + 3);
+    }
+}
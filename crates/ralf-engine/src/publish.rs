@@ -0,0 +1,340 @@
+//! Publishing a finished thread.
+//!
+//! Once a thread is approved, its changes are committed with a
+//! conventional commit message (see
+//! [`generate_commit_message`](crate::changelog::generate_commit_message)),
+//! and can be pushed and opened as a pull request (a merge
+//! request on GitLab) with the forge's own CLI, `gh` or `glab`.
+
+use std::path::Path;
+use std::process::Command;
+
+use thiserror::Error;
+
+use crate::git::{GitError, GitSafety};
+use crate::thread::ThreadBranch;
+
+/// Remote that thread branches are pushed to.
+pub const REMOTE: &str = "origin";
+
+/// Longest commit subject, type prefix included.
+pub(crate) const MAX_SUBJECT_LEN: usize = 72;
+
+/// Conventional commit types, with the title words that suggest them.
+/// Titles matching none of these are features.
+const COMMIT_TYPES: &[(&str, &[&str])] = &[
+    (
+        "fix",
+        &["fix", "fixes", "fixed", "bug", "repair", "resolve"],
+    ),
+    (
+        "docs",
+        &["doc", "docs", "document", "documentation", "readme"],
+    ),
+    (
+        "refactor",
+        &[
+            "refactor",
+            "rename",
+            "restructure",
+            "simplify",
+            "clean",
+            "extract",
+        ],
+    ),
+    ("test", &["test", "tests"]),
+    ("perf", &["perf", "optimize", "speed"]),
+    ("chore", &["bump", "upgrade", "chore"]),
+];
+
+/// Errors from opening a pull request.
+#[derive(Debug, Error)]
+pub enum PublishError {
+    #[error(transparent)]
+    Git(#[from] GitError),
+
+    #[error("Can't tell which forge hosts {0}; pull requests need GitHub or GitLab")]
+    UnknownForge(String),
+
+    #[error("`{0}` not found; install it to open pull requests")]
+    CliMissing(&'static str),
+
+    #[error("{cli} failed: {message}")]
+    CliFailed { cli: &'static str, message: String },
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Where the repository's remote is hosted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Forge {
+    GitHub,
+    GitLab,
+}
+
+impl Forge {
+    /// The forge a remote URL points at, if it's one we know.
+    pub fn detect(remote_url: &str) -> Option<Self> {
+        let url = remote_url.to_lowercase();
+        if url.contains("github") {
+            Some(Self::GitHub)
+        } else if url.contains("gitlab") {
+            Some(Self::GitLab)
+        } else {
+            None
+        }
+    }
+
+    /// The forge's command-line tool.
+    pub fn cli(self) -> &'static str {
+        match self {
+            Self::GitHub => "gh",
+            Self::GitLab => "glab",
+        }
+    }
+
+    /// Arguments that open a pull request from `branch` into its base.
+    pub fn create_args(self, branch: &ThreadBranch, title: &str, body: &str) -> Vec<String> {
+        let args = match self {
+            Self::GitHub => vec![
+                "pr",
+                "create",
+                "--base",
+                &branch.base,
+                "--head",
+                &branch.name,
+                "--title",
+                title,
+                "--body",
+                body,
+            ],
+            Self::GitLab => vec![
+                "mr",
+                "create",
+                "--target-branch",
+                &branch.base,
+                "--source-branch",
+                &branch.name,
+                "--title",
+                title,
+                "--description",
+                body,
+                "--yes",
+            ],
+        };
+        args.into_iter().map(String::from).collect()
+    }
+}
+
+/// Push the thread's branch and open a pull request for it into the
+/// branch it started from, titled and described by the commit `message`.
+/// Returns the pull request's URL.
+///
+/// # Errors
+///
+/// Returns an error if the remote isn't on GitHub or GitLab, its CLI isn't
+/// installed, or the push or the CLI fails.
+pub fn open_pull_request(
+    repo_path: &Path,
+    branch: &ThreadBranch,
+    message: &str,
+) -> Result<String, PublishError> {
+    let git = GitSafety::new(repo_path);
+    let remote_url = git.remote_url(REMOTE)?;
+    let forge = Forge::detect(&remote_url).ok_or(PublishError::UnknownForge(remote_url))?;
+    let cli = which::which(forge.cli()).map_err(|_| PublishError::CliMissing(forge.cli()))?;
+
+    git.push_branch(REMOTE, &branch.name)?;
+
+    let (title, body) = message.split_once('\n').unwrap_or((message, ""));
+    let output = Command::new(cli)
+        .args(forge.create_args(branch, title.trim(), body.trim()))
+        .current_dir(repo_path)
+        .output()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(PublishError::CliFailed {
+            cli: forge.cli(),
+            message: stderr.trim().to_string(),
+        });
+    }
+    pull_request_url(&stdout).ok_or_else(|| PublishError::CliFailed {
+        cli: forge.cli(),
+        message: "no pull request URL in its output".to_string(),
+    })
+}
+
+/// The last URL in a CLI's output, which `gh` and `glab` print for the
+/// pull request they created.
+pub fn pull_request_url(output: &str) -> Option<String> {
+    output
+        .split_whitespace()
+        .rev()
+        .find(|word| word.starts_with("https://") || word.starts_with("http://"))
+        .map(String::from)
+}
+
+/// `type: description` for a thread title. Titles that are already
+/// conventional are kept.
+pub(crate) fn commit_subject(title: &str) -> String {
+    let title = title.trim().trim_end_matches('.');
+    if let Some((prefix, _)) = title.split_once(": ") {
+        let kind = prefix
+            .split('(')
+            .next()
+            .unwrap_or(prefix)
+            .trim_end_matches('!');
+        if COMMIT_TYPES.iter().any(|(t, _)| *t == kind) || kind == "feat" {
+            return truncate_subject(title.to_string());
+        }
+    }
+
+    let first = title
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    let kind = COMMIT_TYPES
+        .iter()
+        .find(|(_, words)| words.contains(&first.as_str()))
+        .map_or("feat", |(kind, _)| kind);
+
+    // Lowercase the first word unless it's an acronym like "API"
+    let mut chars = title.chars();
+    let description = match (chars.next(), chars.next()) {
+        (Some(c), Some(next)) if !next.is_uppercase() => {
+            format!("{}{}", c.to_lowercase(), &title[c.len_utf8()..])
+        }
+        _ => title.to_string(),
+    };
+    truncate_subject(format!("{kind}: {description}"))
+}
+
+/// Cut a subject to [`MAX_SUBJECT_LEN`] at a word boundary.
+fn truncate_subject(subject: String) -> String {
+    if subject.chars().count() <= MAX_SUBJECT_LEN {
+        return subject;
+    }
+    let mut out = String::new();
+    for word in subject.split_whitespace() {
+        if out.chars().count() + word.chars().count() + 1 > MAX_SUBJECT_LEN {
+            break;
+        }
+        if !out.is_empty() {
+            out.push(' ');
+        }
+        out.push_str(word);
+    }
+    out
+}
+
+/// The spec's first paragraph of prose, skipping headings, lists, quotes,
+/// tables and code.
+pub(crate) fn spec_summary(spec: &str) -> String {
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut in_code = false;
+    for line in spec.lines() {
+        let line = line.trim();
+        if line.starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        let prose = !in_code
+            && !line.is_empty()
+            && !line.starts_with(['#', '-', '*', '>', '|', '<'])
+            && !line.starts_with(|c: char| c.is_ascii_digit());
+        if prose {
+            paragraph.push(line);
+        } else if !paragraph.is_empty() {
+            break;
+        }
+    }
+    paragraph.join(" ")
+}
+
+/// Wrap `text` into lines of at most `width` characters, breaking at
+/// spaces.
+pub(crate) fn wrap(text: &str, width: usize) -> String {
+    let mut out = String::new();
+    let mut line_len = 0;
+    for word in text.split_whitespace() {
+        let len = word.chars().count();
+        if line_len > 0 && line_len + 1 + len > width {
+            out.push('\n');
+            line_len = 0;
+        } else if line_len > 0 {
+            out.push(' ');
+            line_len += 1;
+        }
+        out.push_str(word);
+        line_len += len;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commit_subject() {
+        assert_eq!(commit_subject("Add OAuth login."), "feat: add OAuth login");
+        assert_eq!(
+            commit_subject("Fix crash on empty config"),
+            "fix: fix crash on empty config"
+        );
+        assert_eq!(commit_subject("API rate limits"), "feat: API rate limits");
+        assert_eq!(
+            commit_subject("docs(readme): explain --fast"),
+            "docs(readme): explain --fast"
+        );
+        assert_eq!(
+            commit_subject("Rename the config loader"),
+            "refactor: rename the config loader"
+        );
+        let long = commit_subject(&"word ".repeat(30));
+        assert!(long.len() <= MAX_SUBJECT_LEN);
+        assert!(long.starts_with("feat: word word"));
+    }
+
+    #[test]
+    fn test_forge() {
+        assert_eq!(
+            Forge::detect("git@github.com:daveremy/ralf.git"),
+            Some(Forge::GitHub)
+        );
+        assert_eq!(
+            Forge::detect("https://gitlab.example.com/team/app.git"),
+            Some(Forge::GitLab)
+        );
+        assert_eq!(Forge::detect("/srv/git/app.git"), None);
+
+        let branch = ThreadBranch {
+            name: "ralf/add-login-1b9d6bcd".into(),
+            base: "main".into(),
+        };
+        let args = Forge::GitLab.create_args(&branch, "feat: add login", "Body");
+        assert_eq!(args[..2], ["mr", "create"]);
+        assert!(args.windows(2).any(|w| w == ["--target-branch", "main"]));
+        assert!(args
+            .windows(2)
+            .any(|w| w == ["--source-branch", "ralf/add-login-1b9d6bcd"]));
+        assert_eq!(
+            Forge::GitHub.create_args(&branch, "t", "b")[..2],
+            ["pr", "create"]
+        );
+    }
+
+    #[test]
+    fn test_pull_request_url() {
+        assert_eq!(
+            pull_request_url(
+                "Creating pull request for ralf/x into main\n\nhttps://github.com/o/r/pull/42\n"
+            ),
+            Some("https://github.com/o/r/pull/42".into())
+        );
+        assert_eq!(pull_request_url("nothing here"), None);
+    }
+}
@@ -0,0 +1,202 @@
+//! Queue of threads to run one after another.
+//!
+//! `ralf queue add <thread-id>` lines finalized threads up, and
+//! `ralf queue start` works through them unattended, e.g. overnight. The
+//! queue lives in `.ralf/queue.json`, so an interrupted `queue start` picks
+//! up where it stopped, and the TUI shows each thread's place in line.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::state::{atomic_write, current_timestamp, StateError};
+
+/// Name of the queue file inside `.ralf/`.
+pub const QUEUE_FILE: &str = "queue.json";
+
+/// Where a queued thread is in the queue.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueueStatus {
+    /// Waiting for its turn.
+    #[default]
+    Pending,
+    /// Being run now.
+    Running,
+    /// Its run completed.
+    Completed,
+    /// Preflight or its run failed.
+    Failed,
+}
+
+impl QueueStatus {
+    /// Whether the thread is still waiting or running.
+    pub fn is_waiting(self) -> bool {
+        matches!(self, Self::Pending | Self::Running)
+    }
+
+    /// Lowercase name, as `ralf queue list` shows it.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Running => "running",
+            Self::Completed => "completed",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+/// A thread in the queue.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueueEntry {
+    /// Thread ID.
+    pub thread_id: String,
+    /// When the thread was queued (Unix timestamp).
+    pub added_at: u64,
+    /// Where the thread is in the queue.
+    #[serde(default)]
+    pub status: QueueStatus,
+    /// How its turn ended, e.g. "completed after 4 iterations".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<String>,
+}
+
+/// Threads queued to run, in order.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Queue {
+    #[serde(default)]
+    pub entries: Vec<QueueEntry>,
+}
+
+impl Queue {
+    /// Path of the queue file for a `.ralf` directory.
+    pub fn path(ralf_dir: &Path) -> PathBuf {
+        ralf_dir.join(QUEUE_FILE)
+    }
+
+    /// Load the queue, empty if the file doesn't exist.
+    pub fn load(path: &Path) -> Result<Self, StateError> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path).map_err(StateError::Io)?;
+        serde_json::from_str(&content).map_err(StateError::Parse)
+    }
+
+    /// Save the queue.
+    pub fn save(&self, path: &Path) -> Result<(), StateError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(StateError::Io)?;
+        }
+        let content = serde_json::to_string_pretty(self).map_err(StateError::Serialize)?;
+        atomic_write(path, content.as_bytes()).map_err(StateError::Io)
+    }
+
+    /// Queue `thread_id` at the back. A thread whose turn is over is queued
+    /// again; returns false if it is already waiting.
+    pub fn add(&mut self, thread_id: &str) -> bool {
+        if self.position(thread_id).is_some() {
+            return false;
+        }
+        self.entries.retain(|e| e.thread_id != thread_id);
+        self.entries.push(QueueEntry {
+            thread_id: thread_id.to_string(),
+            added_at: current_timestamp(),
+            status: QueueStatus::Pending,
+            outcome: None,
+        });
+        true
+    }
+
+    /// Take `thread_id` out of the queue. Returns false if it wasn't in it.
+    pub fn remove(&mut self, thread_id: &str) -> bool {
+        let before = self.entries.len();
+        self.entries.retain(|e| e.thread_id != thread_id);
+        self.entries.len() < before
+    }
+
+    /// The thread's place in line (1 = next or running), if it is waiting.
+    pub fn position(&self, thread_id: &str) -> Option<usize> {
+        self.entries
+            .iter()
+            .filter(|e| e.status.is_waiting())
+            .position(|e| e.thread_id == thread_id)
+            .map(|index| index + 1)
+    }
+
+    /// The next thread waiting its turn.
+    pub fn next_pending(&self) -> Option<&QueueEntry> {
+        self.entries
+            .iter()
+            .find(|e| e.status == QueueStatus::Pending)
+    }
+
+    /// Record where `thread_id` is in the queue, and how its turn ended.
+    pub fn set_status(&mut self, thread_id: &str, status: QueueStatus, outcome: Option<String>) {
+        if let Some(entry) = self.entries.iter_mut().find(|e| e.thread_id == thread_id) {
+            entry.status = status;
+            entry.outcome = outcome;
+        }
+    }
+
+    /// Put threads left running by an interrupted `queue start` back in
+    /// line, returning how many.
+    pub fn requeue_interrupted(&mut self) -> usize {
+        let mut count = 0;
+        for entry in &mut self.entries {
+            if entry.status == QueueStatus::Running {
+                entry.status = QueueStatus::Pending;
+                count += 1;
+            }
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queue_order() {
+        let mut queue = Queue::default();
+        assert!(queue.add("a"));
+        assert!(queue.add("b"));
+        assert!(queue.add("c"));
+        assert!(!queue.add("b"));
+        assert_eq!(queue.next_pending().unwrap().thread_id, "a");
+
+        queue.set_status("a", QueueStatus::Running, None);
+        assert_eq!(queue.position("a"), Some(1));
+        assert_eq!(queue.position("c"), Some(3));
+        assert_eq!(queue.next_pending().unwrap().thread_id, "b");
+
+        queue.set_status("a", QueueStatus::Completed, Some("done".into()));
+        assert_eq!(queue.position("a"), None);
+        assert_eq!(queue.position("b"), Some(1));
+
+        // A finished thread goes to the back when queued again
+        assert!(queue.add("a"));
+        assert_eq!(queue.position("a"), Some(3));
+        assert!(queue.remove("b"));
+        assert!(!queue.remove("b"));
+        assert_eq!(queue.position("c"), Some(1));
+    }
+
+    #[test]
+    fn test_queue_save_and_requeue() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = Queue::path(temp.path());
+        assert_eq!(Queue::load(&path).unwrap(), Queue::default());
+
+        let mut queue = Queue::default();
+        queue.add("a");
+        queue.add("b");
+        queue.set_status("a", QueueStatus::Running, None);
+        queue.save(&path).unwrap();
+
+        let mut loaded = Queue::load(&path).unwrap();
+        assert_eq!(loaded, queue);
+        assert_eq!(loaded.requeue_interrupted(), 1);
+        assert_eq!(loaded.next_pending().unwrap().thread_id, "a");
+    }
+}
@@ -0,0 +1,272 @@
+//! Rate-limit reset parsing.
+//!
+//! Many model CLIs say when a rate limit lifts: "resets at 3:00 PM",
+//! "retry after 120s", "try again in 2 hours 10 minutes". Cooling a model
+//! down until that moment, rather than for a fixed
+//! `default_cooldown_seconds`, avoids both retrying too early and idling
+//! long after the limit has lifted.
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use regex::Regex;
+
+use crate::state::Cooldowns;
+
+/// Reset times further out than this are treated as misparses.
+const MAX_RESET_SECS: i64 = 7 * 24 * 60 * 60;
+
+/// "usage limit reached|1736700000" (Unix timestamp of the reset).
+const UNIX_RESET: &str = r"(?i)limit reached\|(\d{10})\b";
+
+/// "retry after 120s", "Retry-After: 120", "try again in 2h 30m".
+const RELATIVE_RESET: &str = r"(?i)\b(?:retry[- ]after|retry in|try again in|resets? in|available in)\s*:?\s*(\d+(?:\.\d+)?\s*[a-z]*(?:(?:\s*,\s*|\s+and\s+|\s+)\d+(?:\.\d+)?\s*[a-z]*)*)";
+
+/// "resets at 3:00 PM", "try again at Jan 12th, 2026 9:08 PM", "resets 3pm".
+const ABSOLUTE_RESET: &str =
+    r#"(?i)\b(?:(?:resets?|try again|retry|available again)\s+at|resets)\s+([^\n;"(]+)"#;
+
+/// Date-time formats tried for absolute resets, after ordinals are removed.
+const DATETIME_FORMATS: &[&str] = &[
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%d %H:%M",
+    "%b %d, %Y %I:%M %p",
+    "%B %d, %Y %I:%M %p",
+    "%b %d %Y %I:%M %p",
+    "%B %d %Y %I:%M %p",
+];
+
+/// When a rate limit resets, as reported by the model CLI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimitReset {
+    /// Unix timestamp of the reset.
+    pub at: u64,
+
+    /// The text the reset was parsed from (e.g. "resets at 3:00 PM").
+    pub hint: String,
+}
+
+/// Find the reset time in a rate-limited invocation's output.
+pub fn parse_reset(output: &str) -> Option<RateLimitReset> {
+    parse_reset_at(output, Local::now())
+}
+
+/// Like [`parse_reset`], relative to `now`. Times without a date are taken
+/// as the next occurrence in local time. Resets in the past or more than a
+/// week out are ignored.
+pub fn parse_reset_at(output: &str, now: DateTime<Local>) -> Option<RateLimitReset> {
+    let candidates = [
+        Regex::new(UNIX_RESET)
+            .expect("Invalid regex pattern")
+            .captures(output)
+            .and_then(|caps| {
+                let at = caps[1].parse::<i64>().ok()?;
+                Some((at, caps[0].to_string()))
+            }),
+        Regex::new(RELATIVE_RESET)
+            .expect("Invalid regex pattern")
+            .captures(output)
+            .and_then(|caps| {
+                let secs = parse_duration(&caps[1])?;
+                Some((now.timestamp() + secs, caps[0].to_string()))
+            }),
+        Regex::new(ABSOLUTE_RESET)
+            .expect("Invalid regex pattern")
+            .captures(output)
+            .and_then(|caps| {
+                let text = clean_time_text(&caps[1]);
+                let at = parse_absolute(&text, now)?;
+                let prefix = &caps[0][..caps.get(1)?.start() - caps.get(0)?.start()];
+                Some((at, format!("{prefix}{text}")))
+            }),
+    ];
+
+    candidates.into_iter().flatten().find_map(|(at, hint)| {
+        let delta = at - now.timestamp();
+        if delta <= 0 || delta > MAX_RESET_SECS {
+            return None;
+        }
+        Some(RateLimitReset {
+            at: u64::try_from(at).ok()?,
+            hint: hint.trim().to_string(),
+        })
+    })
+}
+
+/// Put `model` in cooldown after a rate limit: until the reset reported in
+/// `output` if there is one, otherwise for `default_seconds`. Returns the
+/// cooldown length in seconds.
+pub fn start_cooldown(
+    cooldowns: &mut Cooldowns,
+    model: &str,
+    output: &str,
+    default_seconds: u64,
+    reason: &str,
+) -> u64 {
+    let now = i64::try_from(cooldowns.now())
+        .ok()
+        .and_then(|now| Local.timestamp_opt(now, 0).single())
+        .unwrap_or_else(Local::now);
+    if let Some(reset) = parse_reset_at(output, now) {
+        cooldowns.set_cooldown_until(model, reset.at, reason, Some(reset.hint));
+        cooldowns.remaining_seconds(model).unwrap_or(0)
+    } else {
+        cooldowns.set_cooldown(model, default_seconds, reason);
+        default_seconds
+    }
+}
+
+/// Sum a duration like "2h 30m", "120s", "37.5 seconds" or a bare "120"
+/// (seconds). `None` if any unit is unknown.
+fn parse_duration(text: &str) -> Option<i64> {
+    let part = Regex::new(r"(?i)(\d+(?:\.\d+)?)\s*([a-z]*)").expect("Invalid regex pattern");
+    let mut total = 0.0;
+    for caps in part.captures_iter(text) {
+        let value: f64 = caps[1].parse().ok()?;
+        let unit_secs = match caps[2].to_ascii_lowercase().as_str() {
+            "" | "s" | "sec" | "secs" | "second" | "seconds" => 1.0,
+            "m" | "min" | "mins" | "minute" | "minutes" => 60.0,
+            "h" | "hr" | "hrs" | "hour" | "hours" => 3600.0,
+            "d" | "day" | "days" => 86400.0,
+            _ => return None,
+        };
+        total += value * unit_secs;
+    }
+    #[allow(clippy::cast_possible_truncation)]
+    let secs = total.ceil() as i64;
+    (secs > 0).then_some(secs)
+}
+
+/// Trim a captured time down to the time itself: stop at the end of the
+/// sentence and drop ordinal suffixes.
+fn clean_time_text(text: &str) -> String {
+    let text = text.split(". ").next().unwrap_or(text);
+    let text = text.trim().trim_end_matches('.').trim_end_matches(',');
+    Regex::new(r"(?i)\b(\d{1,2})(?:st|nd|rd|th)\b")
+        .expect("Invalid regex pattern")
+        .replace_all(text, "$1")
+        .into_owned()
+}
+
+/// Parse a reset time as a Unix timestamp.
+pub(crate) fn parse_absolute(text: &str, now: DateTime<Local>) -> Option<i64> {
+    if let Ok(at) = DateTime::parse_from_rfc3339(text) {
+        return Some(at.timestamp());
+    }
+
+    for format in DATETIME_FORMATS {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(text, format) {
+            return local_timestamp(naive);
+        }
+    }
+
+    let time = parse_time_of_day(text)?;
+    let today = now.date_naive();
+    let at = local_timestamp(today.and_time(time))?;
+    if at > now.timestamp() {
+        Some(at)
+    } else {
+        local_timestamp(today.succ_opt().unwrap_or(NaiveDate::MAX).and_time(time))
+    }
+}
+
+/// Parse "3pm", "3:00 PM" or "15:00".
+fn parse_time_of_day(text: &str) -> Option<NaiveTime> {
+    let compact: String = text
+        .split_whitespace()
+        .collect::<String>()
+        .to_ascii_uppercase();
+    let with_minutes = match compact.strip_suffix("AM").or(compact.strip_suffix("PM")) {
+        Some(hour) if !hour.contains(':') => format!("{hour}:00{}", &compact[hour.len()..]),
+        _ => compact.clone(),
+    };
+    NaiveTime::parse_from_str(&with_minutes, "%I:%M%p")
+        .or_else(|_| NaiveTime::parse_from_str(&with_minutes, "%H:%M"))
+        .ok()
+}
+
+fn local_timestamp(naive: NaiveDateTime) -> Option<i64> {
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|at| at.timestamp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 1, 12, 14, 0, 0).unwrap()
+    }
+
+    fn reset_in(output: &str) -> Option<i64> {
+        parse_reset_at(output, now()).map(|r| i64::try_from(r.at).unwrap() - now().timestamp())
+    }
+
+    #[test]
+    fn test_relative_resets() {
+        assert_eq!(reset_in("429: retry after 120s"), Some(120));
+        assert_eq!(reset_in("Retry-After: 90"), Some(90));
+        assert_eq!(reset_in("Please retry in 37.5s."), Some(38));
+        assert_eq!(
+            reset_in("Usage limit hit, try again in 2 hours 10 minutes."),
+            Some(2 * 3600 + 600)
+        );
+        assert_eq!(reset_in("Quota resets in 1h 30m"), Some(5400));
+        assert_eq!(reset_in("retry after 5 requests"), None);
+    }
+
+    #[test]
+    fn test_absolute_resets() {
+        let reset = parse_reset_at("Limit reached, resets at 3:00 PM. Upgrade?", now()).unwrap();
+        assert_eq!(i64::try_from(reset.at).unwrap() - now().timestamp(), 3600);
+        assert_eq!(reset.hint, "resets at 3:00 PM");
+
+        assert_eq!(
+            reset_in("5-hour limit reached ∙ resets 3pm (America/Los_Angeles)"),
+            Some(3600)
+        );
+        // Already past today: the next occurrence is tomorrow
+        assert_eq!(reset_in("resets at 9am"), Some(19 * 3600));
+        assert_eq!(
+            reset_in("You've hit your limit, try again at Jan 13th, 2026 2:00 PM."),
+            Some(24 * 3600)
+        );
+        assert_eq!(reset_in("resets at 2026-01-12T16:30:00"), Some(9000));
+    }
+
+    #[test]
+    fn test_unix_and_out_of_range_resets() {
+        let at = now().timestamp() + 600;
+        assert_eq!(
+            reset_in(&format!("Claude AI usage limit reached|{at}")),
+            Some(600)
+        );
+
+        assert_eq!(reset_in("resets at 2020-01-01T00:00:00Z"), None);
+        assert_eq!(reset_in("try again in 30 days"), None);
+        assert_eq!(reset_in("rate limit exceeded"), None);
+    }
+
+    #[test]
+    fn test_start_cooldown() {
+        let mut cooldowns = Cooldowns::default();
+        let secs = start_cooldown(
+            &mut cooldowns,
+            "claude",
+            "retry after 600s",
+            300,
+            "rate limited",
+        );
+        assert!((599..=600).contains(&secs));
+        assert_eq!(
+            cooldowns.entries["claude"].reset_hint.as_deref(),
+            Some("retry after 600s")
+        );
+
+        let secs = start_cooldown(&mut cooldowns, "codex", "slow down", 300, "rate limited");
+        assert_eq!(secs, 300);
+        assert!(cooldowns.entries["codex"].reset_hint.is_none());
+    }
+}
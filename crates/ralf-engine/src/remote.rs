@@ -0,0 +1,816 @@
+//! Remote control of a running loop.
+//!
+//! `ralf serve` runs a loop headless and serves a small HTTP API on a local
+//! address, so another process (the TUI, a web dashboard, `curl`) can
+//! watch the run and steer it without having started it:
+//!
+//! - `GET /status`: the run's [`RemoteStatus`] as JSON.
+//! - `GET /events`: every event so far, then new ones as they happen, as
+//!   server-sent events (one [`EventRecord`] per `data:` line).
+//! - `POST /cancel`, `/pause`, `/resume`, `/approve` and `/reject`: the
+//!   [`RunHandle`] controls, answered with the status, or 409 when the run
+//!   isn't in a state to take them.
+//! - `POST /answer`: the request body answers the question the model asked
+//!   (see [`RemoteStatus::question`]), likewise.
+//!
+//! Every request needs the server's token, as `Authorization: Bearer
+//! <token>`, or as `?token=` where headers can't be set (a browser's
+//! `EventSource`). The address and token are written to `.ralf/serve.json`,
+//! readable only by the user, for [`RemoteClient::discover`] to find.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::OpenOptions;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use crate::dirs::RalfDirs;
+use crate::events::{EventRecord, EventSink};
+use crate::http::{respond, Request};
+use crate::lock::process_alive;
+use crate::runner::{RunEvent, RunHandle};
+use crate::state::current_timestamp;
+
+/// Events kept for clients that connect after they happened.
+const MAX_HISTORY: usize = 10_000;
+
+/// How often an idle event stream sends a comment, so clients that went
+/// away are noticed.
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// A token for a server, unless one is given.
+pub fn generate_token() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
+
+/// Where the run stands.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RemoteRunState {
+    /// The run hasn't emitted its first event yet.
+    #[default]
+    Starting,
+    /// Iterating.
+    Running,
+    /// Paused between iterations.
+    Paused,
+    /// Waiting for its changes to be approved or rejected.
+    AwaitingReview,
+    /// Waiting for an answer to the model's question.
+    AwaitingInput,
+    /// Over; see [`RemoteStatus::outcome`].
+    Finished,
+}
+
+/// A run's status, as served at `/status`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RemoteStatus {
+    /// The run's ID, once it has started.
+    pub run_id: Option<String>,
+    /// Where the run stands.
+    pub state: RemoteRunState,
+    /// The current (or last) iteration.
+    pub iteration: usize,
+    /// Iteration limit (0 = unlimited).
+    pub max_iterations: usize,
+    /// Model of the current iteration.
+    pub model: Option<String>,
+    /// How the run ended, once it has.
+    pub outcome: Option<String>,
+    /// Whether a pause was asked for (it takes effect once the current
+    /// model invocation finishes).
+    #[serde(default)]
+    pub pause_requested: bool,
+    /// The question the run is waiting on an answer to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub question: Option<String>,
+}
+
+impl RemoteStatus {
+    /// Move the status on by `event`.
+    pub fn apply(&mut self, event: &RunEvent) {
+        let outcome = match event {
+            RunEvent::Started {
+                run_id,
+                max_iterations,
+                ..
+            } => {
+                self.run_id = Some(run_id.clone());
+                self.max_iterations = *max_iterations;
+                self.state = RemoteRunState::Running;
+                return;
+            }
+            RunEvent::IterationStarted { iteration, model } => {
+                self.iteration = *iteration;
+                self.model = Some(model.clone());
+                self.state = RemoteRunState::Running;
+                return;
+            }
+            RunEvent::Paused { .. } => {
+                self.state = RemoteRunState::Paused;
+                return;
+            }
+            RunEvent::Resumed { .. } | RunEvent::ReviewResolved { .. } => {
+                self.state = RemoteRunState::Running;
+                return;
+            }
+            RunEvent::ReviewRequired { .. } => {
+                self.state = RemoteRunState::AwaitingReview;
+                return;
+            }
+            RunEvent::QuestionAsked { question, .. } => {
+                self.state = RemoteRunState::AwaitingInput;
+                self.question = Some(question.clone());
+                return;
+            }
+            RunEvent::QuestionAnswered { .. } => {
+                self.state = RemoteRunState::Running;
+                self.question = None;
+                return;
+            }
+            RunEvent::Completed { reason, .. } => format!("completed: {reason}"),
+            RunEvent::Failed { error, .. } => format!("failed: {error}"),
+            RunEvent::Stuck { iteration, .. } => format!("stuck at iteration {iteration}"),
+            RunEvent::Cancelled { .. } => "cancelled".to_string(),
+            _ => return,
+        };
+        self.state = RemoteRunState::Finished;
+        self.question = None;
+        self.outcome = Some(outcome);
+    }
+
+    /// Whether the run is over.
+    pub fn is_finished(&self) -> bool {
+        self.state == RemoteRunState::Finished
+    }
+}
+
+/// A control request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoteAction {
+    /// Cancel the run.
+    Cancel,
+    /// Pause once the current model invocation finishes.
+    Pause,
+    /// Resume a paused run.
+    Resume,
+    /// Approve the changes the run is waiting on.
+    Approve,
+    /// Reject the changes the run is waiting on, stopping it.
+    Reject,
+}
+
+impl RemoteAction {
+    /// Every action.
+    pub const ALL: [Self; 5] = [
+        Self::Cancel,
+        Self::Pause,
+        Self::Resume,
+        Self::Approve,
+        Self::Reject,
+    ];
+
+    /// The path the action is posted to.
+    pub fn path(self) -> &'static str {
+        match self {
+            Self::Cancel => "/cancel",
+            Self::Pause => "/pause",
+            Self::Resume => "/resume",
+            Self::Approve => "/approve",
+            Self::Reject => "/reject",
+        }
+    }
+
+    /// Take the action on the run `handle` controls. Returns why it
+    /// couldn't be taken, if it couldn't.
+    fn apply(self, handle: &RunHandle, status: &RemoteStatus) -> Result<(), &'static str> {
+        if status.is_finished() {
+            return Err("the run has finished");
+        }
+        let reviewing = status.state == RemoteRunState::AwaitingReview;
+        let (taken, reason) = match self {
+            Self::Cancel => (handle.try_cancel(), "the run is already being cancelled"),
+            Self::Pause => (handle.pause(), "the run is already paused"),
+            Self::Resume => (handle.resume(), "the run isn't paused"),
+            Self::Approve => (
+                reviewing && handle.approve_review(),
+                "the run isn't waiting for review",
+            ),
+            Self::Reject => (
+                reviewing && handle.reject_review(),
+                "the run isn't waiting for review",
+            ),
+        };
+        if taken {
+            Ok(())
+        } else {
+            Err(reason)
+        }
+    }
+}
+
+/// A run's events and status as the server sees them. Give it to the run
+/// as (one of) its [`EventSink`]s.
+#[derive(Debug, Default)]
+pub struct RemoteRun {
+    inner: Mutex<RemoteRunInner>,
+}
+
+#[derive(Debug, Default)]
+struct RemoteRunInner {
+    status: RemoteStatus,
+    history: VecDeque<EventRecord>,
+    subscribers: Vec<Sender<EventRecord>>,
+}
+
+impl RemoteRun {
+    /// The run's status so far.
+    pub fn status(&self) -> RemoteStatus {
+        self.lock().status.clone()
+    }
+
+    /// The events so far, and a receiver for the ones to come unless the
+    /// run is over.
+    fn subscribe(&self) -> (Vec<EventRecord>, Option<Receiver<EventRecord>>) {
+        let mut inner = self.lock();
+        let history = inner.history.iter().cloned().collect();
+        if inner.status.is_finished() {
+            return (history, None);
+        }
+        let (tx, rx) = mpsc::channel();
+        inner.subscribers.push(tx);
+        (history, Some(rx))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, RemoteRunInner> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl EventSink for RemoteRun {
+    fn emit(&self, event: RunEvent) {
+        let mut inner = self.lock();
+        inner.status.apply(&event);
+        let record = EventRecord::now(event);
+        inner
+            .subscribers
+            .retain(|subscriber| subscriber.send(record.clone()).is_ok());
+        if inner.history.len() == MAX_HISTORY {
+            inner.history.pop_front();
+        }
+        inner.history.push_back(record);
+        // Streams end with the run
+        if inner.status.is_finished() {
+            inner.subscribers.clear();
+        }
+    }
+}
+
+/// Serves one run's status, events and controls.
+#[derive(Debug)]
+pub struct RemoteServer {
+    listener: TcpListener,
+    token: String,
+    run: Arc<RemoteRun>,
+}
+
+impl RemoteServer {
+    /// Listen on `addr`, accepting requests that carry `token`.
+    pub fn bind(addr: SocketAddr, token: impl Into<String>) -> io::Result<Self> {
+        let token = token.into();
+        if token.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the token can't be empty",
+            ));
+        }
+        Ok(Self {
+            listener: TcpListener::bind(addr)?,
+            token,
+            run: Arc::default(),
+        })
+    }
+
+    /// The address listened on (useful when binding port 0).
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// The sink to give the run, so the server sees its events.
+    pub fn events(&self) -> Arc<RemoteRun> {
+        Arc::clone(&self.run)
+    }
+
+    /// Answer requests for the run `handle` controls from background
+    /// threads, one per connection.
+    pub fn spawn(self, handle: Arc<RunHandle>) -> io::Result<()> {
+        let Self {
+            listener,
+            token,
+            run,
+        } = self;
+        let token: Arc<str> = token.into();
+        std::thread::Builder::new()
+            .name("remote".to_string())
+            .spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    let (token, run, handle) = (token.clone(), run.clone(), handle.clone());
+                    let spawned = std::thread::Builder::new()
+                        .name("remote-client".to_string())
+                        .spawn(move || {
+                            if let Err(e) = answer(stream, &token, &run, &handle) {
+                                tracing::debug!("Remote request failed: {e}");
+                            }
+                        });
+                    if let Err(e) = spawned {
+                        tracing::warn!("Can't answer a remote request: {e}");
+                    }
+                }
+            })?;
+        Ok(())
+    }
+}
+
+/// Answer one request.
+fn answer(
+    mut stream: TcpStream,
+    token: &str,
+    run: &RemoteRun,
+    handle: &RunHandle,
+) -> io::Result<()> {
+    let request = Request::read(&mut stream)?;
+    if !authorized(&request, token) {
+        return respond(
+            &mut stream,
+            "401 Unauthorized",
+            "text/plain",
+            "Missing or wrong token\n",
+        );
+    }
+    let action = RemoteAction::ALL
+        .into_iter()
+        .find(|action| action.path() == request.path);
+    let status = || RemoteStatus {
+        pause_requested: handle.is_paused(),
+        ..run.status()
+    };
+    match (request.method.as_str(), request.path.as_str(), action) {
+        ("GET", "/status", _) => send_json(&mut stream, "200 OK", &status()),
+        ("GET", "/events", _) => stream_events(stream, run),
+        ("POST", "/answer", _) => {
+            let answer = request.body.trim();
+            let refused = if status().state != RemoteRunState::AwaitingInput {
+                Some("the run isn't waiting for an answer")
+            } else if answer.is_empty() {
+                Some("the answer is empty")
+            } else if !handle.answer(answer) {
+                Some("the run already has an answer")
+            } else {
+                None
+            };
+            match refused {
+                None => send_json(&mut stream, "200 OK", &status()),
+                Some(reason) => send_json(
+                    &mut stream,
+                    "409 Conflict",
+                    &serde_json::json!({ "error": reason }),
+                ),
+            }
+        }
+        ("POST", _, Some(action)) => match action.apply(handle, &status()) {
+            Ok(()) => send_json(&mut stream, "200 OK", &status()),
+            Err(reason) => send_json(
+                &mut stream,
+                "409 Conflict",
+                &serde_json::json!({ "error": reason }),
+            ),
+        },
+        ("GET" | "POST", _, None) => {
+            respond(&mut stream, "404 Not Found", "text/plain", "Not found\n")
+        }
+        _ => respond(
+            &mut stream,
+            "405 Method Not Allowed",
+            "text/plain",
+            "Method not allowed\n",
+        ),
+    }
+}
+
+/// Whether `request` carries `token`, compared in constant time.
+fn authorized(request: &Request, token: &str) -> bool {
+    let given = request
+        .header("authorization")
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .or_else(|| request.query_param("token"));
+    given.is_some_and(|given| {
+        given.len() == token.len()
+            && given
+                .bytes()
+                .zip(token.bytes())
+                .fold(0, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    })
+}
+
+fn send_json(stream: &mut TcpStream, status: &str, body: &impl Serialize) -> io::Result<()> {
+    let body = serde_json::to_string(body)?;
+    respond(stream, status, "application/json", &body)
+}
+
+/// Send the events so far, then each new one until the run ends or the
+/// client goes away.
+fn stream_events(mut stream: TcpStream, run: &RemoteRun) -> io::Result<()> {
+    let (history, live) = run.subscribe();
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n"
+    )?;
+    for record in &history {
+        send_event(&mut stream, record)?;
+    }
+    stream.flush()?;
+    let Some(live) = live else {
+        return Ok(());
+    };
+    loop {
+        match live.recv_timeout(KEEPALIVE_INTERVAL) {
+            Ok(record) => send_event(&mut stream, &record)?,
+            Err(RecvTimeoutError::Timeout) => stream.write_all(b": keep-alive\n\n")?,
+            Err(RecvTimeoutError::Disconnected) => return Ok(()),
+        }
+        stream.flush()?;
+    }
+}
+
+fn send_event(stream: &mut TcpStream, record: &EventRecord) -> io::Result<()> {
+    let data = serde_json::to_string(record)?;
+    write!(stream, "data: {data}\n\n")
+}
+
+/// Where a `ralf serve` process can be reached, kept in `.ralf/serve.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServeInfo {
+    /// Address the server listens on.
+    pub addr: SocketAddr,
+    /// Token requests must carry.
+    pub token: String,
+    /// PID of the serving process.
+    pub pid: u32,
+    /// When the server started (Unix timestamp).
+    pub started_at: u64,
+}
+
+impl ServeInfo {
+    /// This process serving at `addr` with `token`.
+    pub fn new(addr: SocketAddr, token: impl Into<String>) -> Self {
+        Self {
+            addr,
+            token: token.into(),
+            pid: std::process::id(),
+            started_at: current_timestamp(),
+        }
+    }
+
+    /// Write to `path`, readable only by the user.
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        // Recreated rather than truncated, so the mode applies
+        let _ = std::fs::remove_file(path);
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        options.open(path)?.write_all(content.as_bytes())
+    }
+
+    /// The info at `path`, if the process that wrote it is still running.
+    pub fn read(path: &Path) -> Option<Self> {
+        let content = std::fs::read_to_string(path).ok()?;
+        serde_json::from_str::<Self>(&content)
+            .ok()
+            .filter(|info| process_alive(info.pid))
+    }
+}
+
+/// Errors talking to a `ralf serve` process.
+#[derive(Debug, thiserror::Error)]
+pub enum RemoteError {
+    /// I/O error.
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    /// The server didn't accept the token.
+    #[error("The server rejected the token")]
+    Unauthorized,
+
+    /// The run couldn't take the action.
+    #[error("Not possible now: {0}")]
+    Conflict(String),
+
+    /// Any other unexpected response.
+    #[error("Unexpected response: {0}")]
+    Http(String),
+
+    /// A response couldn't be parsed.
+    #[error("Invalid response: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Talks to a `ralf serve` process.
+#[derive(Debug, Clone)]
+pub struct RemoteClient {
+    addr: SocketAddr,
+    token: String,
+}
+
+impl RemoteClient {
+    /// A client for the server at `addr`.
+    pub fn new(addr: SocketAddr, token: impl Into<String>) -> Self {
+        Self {
+            addr,
+            token: token.into(),
+        }
+    }
+
+    /// A client for the server `.ralf/serve.json` names, if it is running.
+    pub fn discover(dirs: &RalfDirs) -> Option<Self> {
+        ServeInfo::read(&dirs.serve()).map(|info| Self::new(info.addr, info.token))
+    }
+
+    /// The run's status.
+    pub fn status(&self) -> Result<RemoteStatus, RemoteError> {
+        let body = self.request("GET", "/status")?;
+        Ok(serde_json::from_reader(body)?)
+    }
+
+    /// Take `action`, returning the run's status after it.
+    pub fn send(&self, action: RemoteAction) -> Result<RemoteStatus, RemoteError> {
+        let body = self.request("POST", action.path())?;
+        Ok(serde_json::from_reader(body)?)
+    }
+
+    /// Answer the question the run is waiting on, returning the run's
+    /// status after it.
+    pub fn answer(&self, answer: &str) -> Result<RemoteStatus, RemoteError> {
+        let body = self.request_with("POST", "/answer", answer)?;
+        Ok(serde_json::from_reader(body)?)
+    }
+
+    /// The run's events so far, then new ones until it ends.
+    pub fn events(&self) -> Result<RemoteEvents, RemoteError> {
+        Ok(RemoteEvents {
+            reader: self.request("GET", "/events")?,
+        })
+    }
+
+    /// Send a request, returning the body of a 200 response.
+    fn request(&self, method: &str, path: &str) -> Result<BufReader<TcpStream>, RemoteError> {
+        self.request_with(method, path, "")
+    }
+
+    /// Send a request with `body`, returning the body of a 200 response.
+    fn request_with(
+        &self,
+        method: &str,
+        path: &str,
+        body: &str,
+    ) -> Result<BufReader<TcpStream>, RemoteError> {
+        let mut stream = TcpStream::connect(self.addr)?;
+        write!(
+            stream,
+            "{method} {path} HTTP/1.1\r\nHost: {}\r\nAuthorization: Bearer {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            self.addr,
+            self.token,
+            body.len()
+        )?;
+        let mut reader = BufReader::new(stream);
+        let mut status_line = String::new();
+        reader.read_line(&mut status_line)?;
+        loop {
+            let mut header = String::new();
+            if reader.read_line(&mut header)? == 0 || header.trim_end().is_empty() {
+                break;
+            }
+        }
+        match status_line.split_whitespace().nth(1) {
+            Some("200") => Ok(reader),
+            Some("401") => Err(RemoteError::Unauthorized),
+            Some("409") => {
+                let mut body = String::new();
+                reader.read_to_string(&mut body)?;
+                let error: serde_json::Value = serde_json::from_str(&body)?;
+                Err(RemoteError::Conflict(
+                    error["error"].as_str().unwrap_or_default().to_string(),
+                ))
+            }
+            _ => Err(RemoteError::Http(status_line.trim().to_string())),
+        }
+    }
+}
+
+/// A run's event stream; see [`RemoteClient::events`].
+#[derive(Debug)]
+pub struct RemoteEvents {
+    reader: BufReader<TcpStream>,
+}
+
+impl Iterator for RemoteEvents {
+    type Item = Result<EventRecord, RemoteError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            match self.reader.read_line(&mut line) {
+                Ok(0) => return None,
+                Ok(_) => {}
+                Err(e) => return Some(Err(e.into())),
+            }
+            if let Some(data) = line.trim_end().strip_prefix("data: ") {
+                return Some(serde_json::from_str(data).map_err(RemoteError::from));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::runner::Controls;
+
+    /// A server on a free port, the run it serves, and the handle's
+    /// receivers (dropping them would fail the controls).
+    fn serve() -> (RemoteClient, Arc<RemoteRun>, Arc<RunHandle>, Controls) {
+        let server = RemoteServer::bind("127.0.0.1:0".parse().unwrap(), "secret").unwrap();
+        let addr = server.local_addr().unwrap();
+        let run = server.events();
+        let (handle, controls) = RunHandle::detached();
+        let handle = Arc::new(handle);
+        server.spawn(handle.clone()).unwrap();
+        (RemoteClient::new(addr, "secret"), run, handle, controls)
+    }
+
+    fn started() -> RunEvent {
+        RunEvent::Started {
+            run_id: "run-1".to_string(),
+            max_iterations: 5,
+            branch: None,
+        }
+    }
+
+    #[test]
+    fn test_status_needs_token() {
+        let (client, run, _, _controls) = serve();
+        run.emit(started());
+        run.emit(RunEvent::IterationStarted {
+            iteration: 2,
+            model: "claude".to_string(),
+        });
+
+        let status = client.status().unwrap();
+        assert_eq!(status.run_id.as_deref(), Some("run-1"));
+        assert_eq!(status.state, RemoteRunState::Running);
+        assert_eq!(status.iteration, 2);
+        assert_eq!(status.model.as_deref(), Some("claude"));
+
+        let wrong = RemoteClient::new(client.addr, "wrong");
+        assert!(matches!(wrong.status(), Err(RemoteError::Unauthorized)));
+        // The token also works as a query parameter
+        let mut stream = TcpStream::connect(client.addr).unwrap();
+        write!(stream, "GET /status?token=secret HTTP/1.1\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{response}");
+    }
+
+    #[test]
+    fn test_controls() {
+        let (client, run, handle, _controls) = serve();
+        run.emit(started());
+
+        assert!(client.send(RemoteAction::Pause).unwrap().pause_requested);
+        assert!(handle.is_paused());
+        assert!(matches!(
+            client.send(RemoteAction::Pause),
+            Err(RemoteError::Conflict(_))
+        ));
+        client.send(RemoteAction::Resume).unwrap();
+        assert!(!handle.is_paused());
+
+        // Reviews can only be decided while one is pending
+        assert!(matches!(
+            client.send(RemoteAction::Approve),
+            Err(RemoteError::Conflict(_))
+        ));
+        run.emit(RunEvent::ReviewRequired {
+            iteration: 1,
+            reasons: vec!["big change".to_string()],
+            files_changed: 40,
+            lines_changed: 2000,
+        });
+        let status = client.send(RemoteAction::Approve).unwrap();
+        assert_eq!(status.state, RemoteRunState::AwaitingReview);
+
+        client.send(RemoteAction::Cancel).unwrap();
+        run.emit(RunEvent::Cancelled { iteration: 1 });
+        assert!(matches!(
+            client.send(RemoteAction::Cancel),
+            Err(RemoteError::Conflict(reason)) if reason == "the run has finished"
+        ));
+        assert_eq!(
+            client.status().unwrap().outcome.as_deref(),
+            Some("cancelled")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_answer() {
+        let (client, run, _, mut controls) = serve();
+        run.emit(started());
+        assert!(matches!(
+            client.answer("SQLite"),
+            Err(RemoteError::Conflict(reason)) if reason == "the run isn't waiting for an answer"
+        ));
+
+        run.emit(RunEvent::QuestionAsked {
+            iteration: 1,
+            question: "Postgres or SQLite?".to_string(),
+        });
+        let status = client.status().unwrap();
+        assert_eq!(status.state, RemoteRunState::AwaitingInput);
+        assert_eq!(status.question.as_deref(), Some("Postgres or SQLite?"));
+        assert!(matches!(
+            client.answer("  "),
+            Err(RemoteError::Conflict(reason)) if reason == "the answer is empty"
+        ));
+        client.answer("SQLite, for now").unwrap();
+        assert_eq!(
+            controls.answer.recv().await.as_deref(),
+            Some("SQLite, for now")
+        );
+
+        run.emit(RunEvent::QuestionAnswered {
+            iteration: 1,
+            answer: "SQLite, for now".to_string(),
+        });
+        let status = client.status().unwrap();
+        assert_eq!(status.state, RemoteRunState::Running);
+        assert_eq!(status.question, None);
+    }
+
+    #[test]
+    fn test_event_stream() {
+        let (client, run, _, _controls) = serve();
+        run.emit(started());
+
+        let mut events = client.events().unwrap();
+        let first = events.next().unwrap().unwrap();
+        assert!(matches!(first.event, RunEvent::Started { .. }));
+
+        run.emit(RunEvent::IterationStarted {
+            iteration: 1,
+            model: "claude".to_string(),
+        });
+        run.emit(RunEvent::Completed {
+            iteration: 1,
+            reason: "all criteria met".to_string(),
+        });
+        let rest: Vec<_> = events.map(|record| record.unwrap().event).collect();
+        assert!(matches!(rest[0], RunEvent::IterationStarted { .. }));
+        assert!(matches!(rest[1], RunEvent::Completed { .. }));
+        assert_eq!(rest.len(), 2);
+
+        // Once the run is over, a stream replays it and ends
+        assert_eq!(client.events().unwrap().count(), 3);
+    }
+
+    #[test]
+    fn test_serve_info() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(crate::dirs::SERVE_FILE);
+        let info = ServeInfo::new("127.0.0.1:7878".parse().unwrap(), "secret");
+        info.write(&path).unwrap();
+        assert_eq!(ServeInfo::read(&path), Some(info));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+
+            // A server that has gone away isn't found
+            let gone = ServeInfo {
+                pid: u32::MAX,
+                ..ServeInfo::new("127.0.0.1:7878".parse().unwrap(), "secret")
+            };
+            gone.write(&path).unwrap();
+            assert_eq!(ServeInfo::read(&path), None);
+        }
+    }
+}
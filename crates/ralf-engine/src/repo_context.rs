@@ -0,0 +1,323 @@
+//! A compact map of the repository for the prompt.
+//!
+//! Given only `PROMPT.md`, models tend to spend their first iteration
+//! listing directories and opening files to find their way around. With
+//! `repo_context` enabled, [`PromptBuilder`](crate::PromptBuilder) adds a
+//! "Repository context" section instead: the file tree (as git sees it, so
+//! `.gitignore` is respected), the files changed most recently, and the
+//! start of a few key files such as the README and build manifest.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+use std::process::Command;
+
+use crate::context::ContextBudget;
+use crate::git::porcelain_paths;
+
+/// Heading of the repository context section.
+const CONTEXT_HEADER: &str = "\n\n## Repository context\n";
+
+/// Files listed per directory before the rest are summarized.
+const MAX_DIR_FILES: usize = 20;
+
+/// Most tokens the file tree may take.
+const MAX_TREE_TOKENS: usize = 2_000;
+
+/// Recently changed files listed.
+const MAX_RECENT_FILES: usize = 10;
+
+/// Commits searched for recently changed files.
+const RECENT_COMMITS: &str = "20";
+
+/// Lines kept from the start of each key file.
+const KEY_FILE_LINES: usize = 40;
+
+/// Most tokens each key file excerpt may take.
+const MAX_EXCERPT_TOKENS: usize = 600;
+
+/// Top-level files that say the most about a project, in the order they
+/// are shown.
+const KEY_FILES: &[&str] = &[
+    "README.md",
+    "AGENTS.md",
+    "CLAUDE.md",
+    "Cargo.toml",
+    "package.json",
+    "pyproject.toml",
+    "go.mod",
+    "Makefile",
+];
+
+/// What the prompt says about the repository.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepoContext {
+    /// Tracked and untracked, not ignored, files.
+    pub files: Vec<String>,
+    /// Files changed most recently, uncommitted first.
+    pub recent: Vec<String>,
+    /// The start of each key file present.
+    pub excerpts: Vec<(String, String)>,
+}
+
+impl RepoContext {
+    /// Gather the context of the git repository at `work_dir`. `None` if it
+    /// isn't one or git isn't available.
+    pub fn gather(work_dir: &Path) -> Option<Self> {
+        let listing = git(
+            work_dir,
+            &["ls-files", "--cached", "--others", "--exclude-standard"],
+        )?;
+        let mut files: Vec<String> = listing.lines().map(str::to_string).collect();
+        files.sort();
+        files.dedup();
+
+        let mut recent = git(work_dir, &["status", "--porcelain"])
+            .map(|status| porcelain_paths(&status))
+            .unwrap_or_default();
+        if let Some(log) = git(
+            work_dir,
+            &[
+                "log",
+                "-n",
+                RECENT_COMMITS,
+                "--name-only",
+                "--pretty=format:",
+            ],
+        ) {
+            recent.extend(log.lines().map(str::to_string));
+        }
+        let mut seen = Vec::new();
+        for path in recent {
+            // Deleted files are of no use to the model
+            if !seen.contains(&path) && files.binary_search(&path).is_ok() {
+                seen.push(path);
+            }
+            if seen.len() == MAX_RECENT_FILES {
+                break;
+            }
+        }
+
+        let excerpts = KEY_FILES
+            .iter()
+            .filter(|name| files.binary_search_by(|f| f.as_str().cmp(name)).is_ok())
+            .filter_map(|name| {
+                let text = std::fs::read_to_string(work_dir.join(name)).ok()?;
+                Some(((*name).to_string(), head(&text, KEY_FILE_LINES)))
+            })
+            .collect();
+
+        Some(Self {
+            files,
+            recent: seen,
+            excerpts,
+        })
+    }
+
+    /// The "Repository context" section, fitted to `budget`: the file tree
+    /// first (truncated if needed), then the recent files and key file
+    /// excerpts that fit. Empty if not even the tree fits.
+    pub fn render_within(&self, budget: &mut ContextBudget) -> String {
+        if self.files.is_empty() || !budget.fits(CONTEXT_HEADER) {
+            return String::new();
+        }
+        budget.reserve(CONTEXT_HEADER);
+
+        let tree = budget.take(&file_tree(&self.files), MAX_TREE_TOKENS);
+        if tree.is_empty() {
+            return String::new();
+        }
+        let mut out = CONTEXT_HEADER.to_string();
+        let _ = write!(out, "\nFiles (as tracked by git):\n```\n{tree}\n```\n");
+
+        if !self.recent.is_empty() {
+            let mut recent = "\nRecently changed:\n".to_string();
+            for path in &self.recent {
+                let _ = writeln!(recent, "- {path}");
+            }
+            if budget.fits(&recent) {
+                budget.reserve(&recent);
+                out.push_str(&recent);
+            }
+        }
+
+        for (name, text) in &self.excerpts {
+            let heading = format!("\n### {name}\n```\n");
+            if !budget.fits(&heading) {
+                break;
+            }
+            budget.reserve(&heading);
+            let excerpt = budget.take(text, MAX_EXCERPT_TOKENS);
+            if excerpt.is_empty() {
+                break;
+            }
+            let _ = write!(out, "{heading}{}\n```\n", excerpt.trim_end());
+        }
+        out
+    }
+}
+
+/// Run git in `work_dir`, returning its stdout if it succeeded.
+fn git(work_dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(work_dir)
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// The first `lines` lines of `text`, noting that there is more.
+fn head(text: &str, lines: usize) -> String {
+    let total = text.lines().count();
+    let mut kept = text.lines().take(lines).collect::<Vec<_>>().join("\n");
+    if total > lines {
+        let _ = write!(kept, "\n... ({} more lines)", total - lines);
+    }
+    kept
+}
+
+/// A directory in the file tree.
+#[derive(Debug, Default)]
+struct Dir<'a> {
+    dirs: BTreeMap<&'a str, Dir<'a>>,
+    files: Vec<&'a str>,
+}
+
+/// `paths` as an indented tree, directories before files. Directories with
+/// many files list the first [`MAX_DIR_FILES`] and count the rest.
+pub fn file_tree(paths: &[String]) -> String {
+    let mut root = Dir::default();
+    for path in paths {
+        let mut dir = &mut root;
+        let mut parts = path.split('/').peekable();
+        while let Some(part) = parts.next() {
+            if parts.peek().is_some() {
+                dir = dir.dirs.entry(part).or_default();
+            } else {
+                dir.files.push(part);
+            }
+        }
+    }
+    let mut out = String::new();
+    write_dir(&mut out, &root, 0);
+    out.trim_end().to_string()
+}
+
+fn write_dir(out: &mut String, dir: &Dir<'_>, depth: usize) {
+    let indent = "  ".repeat(depth);
+    for (name, sub) in &dir.dirs {
+        let _ = writeln!(out, "{indent}{name}/");
+        write_dir(out, sub, depth + 1);
+    }
+    for name in dir.files.iter().take(MAX_DIR_FILES) {
+        let _ = writeln!(out, "{indent}{name}");
+    }
+    if dir.files.len() > MAX_DIR_FILES {
+        let _ = writeln!(
+            out,
+            "{indent}... ({} more files)",
+            dir.files.len() - MAX_DIR_FILES
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn paths(paths: &[&str]) -> Vec<String> {
+        paths.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_file_tree() {
+        let tree = file_tree(&paths(&[
+            "Cargo.toml",
+            "README.md",
+            "src/lib.rs",
+            "src/cli/main.rs",
+            "tests/it.rs",
+        ]));
+        assert_eq!(
+            tree,
+            "src/\n  cli/\n    main.rs\n  lib.rs\ntests/\n  it.rs\nCargo.toml\nREADME.md"
+        );
+
+        let many: Vec<String> = (0..25).map(|i| format!("data/{i:02}.json")).collect();
+        let tree = file_tree(&many);
+        assert!(tree.contains("  19.json\n  ... (5 more files)"));
+        assert!(!tree.contains("20.json"));
+    }
+
+    #[test]
+    fn test_render_within_budget() {
+        let context = RepoContext {
+            files: paths(&["README.md", "src/lib.rs"]),
+            recent: paths(&["src/lib.rs"]),
+            excerpts: vec![("README.md".into(), "# Demo\nA demo crate.".into())],
+        };
+
+        let full = context.render_within(&mut ContextBudget::unlimited());
+        assert!(full.starts_with("\n\n## Repository context\n"));
+        assert!(full.contains("```\nsrc/\n  lib.rs\nREADME.md\n```"));
+        assert!(full.contains("Recently changed:\n- src/lib.rs\n"));
+        assert!(full.contains("### README.md\n```\n# Demo\nA demo crate.\n```"));
+
+        // Only the tree fits
+        let mut budget = ContextBudget::new(25);
+        let tight = context.render_within(&mut budget);
+        assert!(tight.contains("src/"));
+        assert!(!tight.contains("### README.md"));
+
+        assert_eq!(context.render_within(&mut ContextBudget::new(1)), "");
+        assert_eq!(
+            RepoContext::default().render_within(&mut ContextBudget::unlimited()),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_gather_respects_gitignore() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .args(args)
+                .current_dir(dir)
+                .output()
+                .expect("git failed");
+        };
+        git(&["init"]);
+        git(&["config", "user.email", "test@example.com"]);
+        git(&["config", "user.name", "Test"]);
+        std::fs::write(dir.join(".gitignore"), "target/\n").unwrap();
+        std::fs::write(dir.join("README.md"), "# Demo\n").unwrap();
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        std::fs::write(dir.join("src/lib.rs"), "").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "init"]);
+        std::fs::create_dir_all(dir.join("target")).unwrap();
+        std::fs::write(dir.join("target/out.bin"), "").unwrap();
+        std::fs::write(dir.join("notes.md"), "").unwrap();
+
+        let context = RepoContext::gather(dir).unwrap();
+        assert_eq!(
+            context.files,
+            paths(&[".gitignore", "README.md", "notes.md", "src/lib.rs"])
+        );
+        // Uncommitted first, then the latest commit's files
+        assert_eq!(context.recent[0], "notes.md");
+        assert!(context.recent.contains(&"src/lib.rs".to_string()));
+        assert_eq!(
+            context.excerpts,
+            vec![("README.md".to_string(), "# Demo".to_string())]
+        );
+
+        assert!(RepoContext::gather(&dir.join("missing")).is_none());
+    }
+}
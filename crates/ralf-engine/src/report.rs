@@ -0,0 +1,848 @@
+//! Shareable run reports.
+//!
+//! A report summarizes a run from its event log: each iteration's model,
+//! duration, verifier results and commit, the final verdict on every
+//! completion criterion, token usage, and the diff stats of the commits the
+//! run made. `ralf report` and the TUI's `/export` render it as Markdown or
+//! HTML to attach to a pull request.
+
+use chrono::{DateTime, Utc};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::completion::CompletionReport;
+use crate::criterion::Criterion;
+use crate::events::{events_path, read_events, EventLogError, EventRecord};
+use crate::git::{GitError, GitSafety};
+use crate::runner::RunEvent;
+use crate::usage::Usage;
+
+/// Directory under `.ralf` that exported reports are written to.
+pub const REPORTS_DIR: &str = "reports";
+
+/// Output format of a report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReportFormat {
+    /// GitHub-flavored Markdown.
+    #[default]
+    Markdown,
+    /// A self-contained HTML page.
+    Html,
+}
+
+impl ReportFormat {
+    /// File extension for the format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Html => "html",
+        }
+    }
+}
+
+impl FromStr for ReportFormat {
+    type Err = ReportError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "md" | "markdown" => Ok(Self::Markdown),
+            "html" => Ok(Self::Html),
+            _ => Err(ReportError::UnknownFormat(s.to_string())),
+        }
+    }
+}
+
+/// Default path of a run's report: `.ralf/reports/<run_id>.<ext>`.
+pub fn report_path(ralf_dir: &Path, run_id: &str, format: ReportFormat) -> PathBuf {
+    ralf_dir
+        .join(REPORTS_DIR)
+        .join(format!("{run_id}.{}", format.extension()))
+}
+
+/// Result of one verifier in an iteration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifierOutcome {
+    /// Verifier name.
+    pub name: String,
+    /// Whether it passed.
+    pub passed: bool,
+    /// Whether it was skipped.
+    pub skipped: bool,
+    /// How long it ran.
+    pub duration_ms: u64,
+}
+
+/// One iteration of a run.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IterationReport {
+    /// Iteration number (1-based).
+    pub number: usize,
+    /// Model that implemented the iteration.
+    pub model: String,
+    /// How long the model ran (`None` if it never finished).
+    pub duration_ms: Option<u64>,
+    /// Whether the model claimed the work was complete.
+    pub has_promise: bool,
+    /// Whether the model hit a rate limit.
+    pub rate_limited: bool,
+    /// Whether the model left the workspace unchanged.
+    pub no_op: bool,
+    /// Verifier results, in the order they finished.
+    pub verifiers: Vec<VerifierOutcome>,
+    /// Model that verified the completion criteria, if any did.
+    pub verified_by: Option<String>,
+    /// Whether every verifier passed (`None` if the iteration didn't finish).
+    pub all_verifiers_passed: Option<bool>,
+    /// Commit made after the iteration.
+    pub commit: Option<String>,
+    /// Tokens and cost of the iteration.
+    pub usage: Usage,
+}
+
+impl IterationReport {
+    /// "passed", "verifiers failed", "rate limited", ...
+    pub fn result(&self) -> &'static str {
+        match self.all_verifiers_passed {
+            _ if self.rate_limited => "rate limited",
+            _ if self.no_op => "no changes",
+            Some(true) if self.has_promise => "claimed complete",
+            Some(true) => "passed",
+            Some(false) => "verifiers failed",
+            None if self.duration_ms.is_none() => "interrupted",
+            None => "incomplete",
+        }
+    }
+}
+
+/// Final verdict on a completion criterion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CriterionOutcome {
+    /// Index of the criterion in the prompt (0-based).
+    pub index: usize,
+    /// Text of the criterion, when the prompt is available.
+    pub text: Option<String>,
+    /// Whether the criterion had to pass for the run to complete.
+    pub required: bool,
+    /// Whether the criterion was met.
+    pub passed: bool,
+    /// The verifier's explanation.
+    pub reason: Option<String>,
+}
+
+/// Everything a report shows about a run.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RunReport {
+    /// Run identifier.
+    pub run_id: String,
+    /// Worktree branch, when the run was isolated.
+    pub branch: Option<String>,
+    /// When the run started.
+    pub started_at: Option<DateTime<Utc>>,
+    /// When the run ended (`None` while still running).
+    pub ended_at: Option<DateTime<Utc>>,
+    /// How the run ended (e.g., "Completed after iteration 3: ...").
+    pub outcome: Option<String>,
+    /// Iterations, in order.
+    pub iterations: Vec<IterationReport>,
+    /// Latest verdict on each criterion, by index.
+    pub criteria: Vec<CriterionOutcome>,
+    /// The model's report from its latest completion promise.
+    pub completion: Option<CompletionReport>,
+    /// Tokens and cost of the whole run.
+    pub usage: Usage,
+    /// `git diff --stat` of the run's commits.
+    pub diff_stat: Option<String>,
+}
+
+impl RunReport {
+    /// Load the report for `run_id` from its event log.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the run has no event log.
+    pub fn load(ralf_dir: &Path, run_id: &str) -> Result<Self, ReportError> {
+        let path = events_path(ralf_dir, run_id);
+        if !path.exists() {
+            return Err(ReportError::NotFound(run_id.to_string()));
+        }
+        Ok(Self::from_events(run_id, &read_events(&path)?))
+    }
+
+    /// Build a report from a run's event records.
+    pub fn from_events(run_id: &str, records: &[EventRecord]) -> Self {
+        let mut report = Self {
+            run_id: run_id.to_string(),
+            started_at: records.first().map(|r| r.timestamp),
+            ..Self::default()
+        };
+        for record in records {
+            report.apply(record);
+        }
+        report.criteria.sort_by_key(|c| c.index);
+        report
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn apply(&mut self, record: &EventRecord) {
+        match &record.event {
+            RunEvent::Started { branch, .. } => {
+                self.branch.clone_from(branch);
+                // A resumed run starts again; it isn't over until it ends again
+                self.ended_at = None;
+                self.outcome = None;
+            }
+            RunEvent::IterationStarted { iteration, model } => {
+                // A resumed run may restart an iteration that was interrupted
+                self.iterations.retain(|it| it.number != *iteration);
+                self.iterations.push(IterationReport {
+                    number: *iteration,
+                    model: model.clone(),
+                    ..IterationReport::default()
+                });
+            }
+            RunEvent::ModelCompleted {
+                iteration,
+                duration_ms,
+                has_promise,
+                rate_limited,
+                ..
+            } => {
+                if let Some(it) = self.iteration_mut(*iteration) {
+                    it.duration_ms = Some(*duration_ms);
+                    it.has_promise = *has_promise;
+                    it.rate_limited = *rate_limited;
+                }
+            }
+            RunEvent::VerifierCompleted {
+                iteration,
+                name,
+                passed,
+                duration_ms,
+                skipped,
+            } => {
+                if let Some(it) = self.iteration_mut(*iteration) {
+                    it.verifiers.push(VerifierOutcome {
+                        name: name.clone(),
+                        passed: *passed,
+                        skipped: *skipped,
+                        duration_ms: *duration_ms,
+                    });
+                }
+            }
+            RunEvent::VerificationStarted {
+                iteration, model, ..
+            } => {
+                if let Some(it) = self.iteration_mut(*iteration) {
+                    it.verified_by = Some(model.clone());
+                }
+            }
+            RunEvent::CriterionVerified {
+                index,
+                required,
+                passed,
+                reason,
+                ..
+            } => {
+                self.criteria.retain(|c| c.index != *index);
+                self.criteria.push(CriterionOutcome {
+                    index: *index,
+                    text: None,
+                    required: *required,
+                    passed: *passed,
+                    reason: reason.clone(),
+                });
+            }
+            RunEvent::UsageUpdated {
+                iteration,
+                usage,
+                run_total,
+                ..
+            } => {
+                if let Some(it) = self.iteration_mut(*iteration) {
+                    it.usage.add(usage);
+                }
+                self.usage = *run_total;
+            }
+            RunEvent::IterationCompleted {
+                iteration,
+                all_verifiers_passed,
+            } => {
+                if let Some(it) = self.iteration_mut(*iteration) {
+                    it.all_verifiers_passed = Some(*all_verifiers_passed);
+                }
+            }
+            RunEvent::IterationCommitted {
+                iteration,
+                commit_sha,
+            } => {
+                if let Some(it) = self.iteration_mut(*iteration) {
+                    it.commit = Some(commit_sha.clone());
+                }
+            }
+            RunEvent::Completed { iteration, reason } => {
+                self.finish(
+                    record,
+                    format!("Completed after iteration {iteration}: {reason}"),
+                );
+            }
+            RunEvent::Failed { iteration, error } => {
+                self.finish(record, format!("Failed at iteration {iteration}: {error}"));
+            }
+            RunEvent::Stuck {
+                iteration,
+                diagnosis,
+            } => {
+                self.finish(
+                    record,
+                    format!(
+                        "Stuck after iteration {iteration}: {}",
+                        diagnosis.signals.join("; ")
+                    ),
+                );
+            }
+            RunEvent::Cancelled { iteration } => {
+                self.finish(record, format!("Cancelled at iteration {iteration}"));
+            }
+            RunEvent::NoOp { iteration, .. } => {
+                if let Some(it) = self.iteration_mut(*iteration) {
+                    it.no_op = true;
+                }
+            }
+            RunEvent::CompletionReported { report, .. } => {
+                self.completion = Some(report.clone());
+            }
+            RunEvent::CooldownStarted { .. }
+            | RunEvent::Paused { .. }
+            | RunEvent::Resumed { .. }
+            | RunEvent::QuestionAsked { .. }
+            | RunEvent::QuestionAnswered { .. }
+            | RunEvent::ReviewRequired { .. }
+            | RunEvent::ReviewResolved { .. }
+            | RunEvent::SafetyViolation { .. }
+            | RunEvent::SecretsDetected { .. }
+            | RunEvent::SaveFailed { .. }
+            | RunEvent::Status { .. } => {}
+        }
+    }
+
+    fn iteration_mut(&mut self, number: usize) -> Option<&mut IterationReport> {
+        self.iterations
+            .iter_mut()
+            .rev()
+            .find(|it| it.number == number)
+    }
+
+    fn finish(&mut self, record: &EventRecord, outcome: String) {
+        self.ended_at = Some(record.timestamp);
+        self.outcome = Some(outcome);
+    }
+
+    /// Fill in criterion text from the prompt's criteria.
+    #[must_use]
+    pub fn with_criteria(mut self, criteria: &[Criterion]) -> Self {
+        for outcome in &mut self.criteria {
+            outcome.text = criteria.get(outcome.index).map(|c| c.text.clone());
+        }
+        self
+    }
+
+    /// Commits the run made, in order.
+    pub fn commits(&self) -> Vec<&str> {
+        self.iterations
+            .iter()
+            .filter_map(|it| it.commit.as_deref())
+            .collect()
+    }
+
+    /// Fill in the diff stats of the run's commits. Leaves them empty if
+    /// the run made no commits.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if git can't diff the commits.
+    pub fn collect_diff_stat(&mut self, git: &GitSafety) -> Result<(), GitError> {
+        let commits = self.commits();
+        if let (Some(first), Some(last)) = (commits.first(), commits.last()) {
+            self.diff_stat = Some(git.diff_stat_commits(first, last)?);
+        }
+        Ok(())
+    }
+
+    /// Render the report.
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Markdown => self.to_markdown(),
+            ReportFormat::Html => self.to_html(),
+        }
+    }
+
+    /// Summary rows shared by both formats.
+    fn summary_rows(&self) -> Vec<(&'static str, String)> {
+        let mut rows = vec![(
+            "Outcome",
+            self.outcome
+                .clone()
+                .unwrap_or_else(|| "Running".to_string()),
+        )];
+        if let Some(branch) = &self.branch {
+            rows.push(("Branch", branch.clone()));
+        }
+        if let Some(started) = self.started_at {
+            rows.push(("Started", format_time(started)));
+        }
+        if let Some(ended) = self.ended_at {
+            rows.push(("Finished", format_time(ended)));
+            if let Some(started) = self.started_at {
+                let ms = (ended - started).num_milliseconds().max(0).unsigned_abs();
+                rows.push(("Duration", format_duration(ms)));
+            }
+        }
+        rows.push(("Iterations", self.iterations.len().to_string()));
+        if !self.usage.is_empty() {
+            rows.push(("Usage", self.usage.summary()));
+        }
+        rows
+    }
+
+    /// Render as GitHub-flavored Markdown.
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# Run report: {}\n", self.run_id);
+
+        out.push_str("| | |\n|---|---|\n");
+        for (label, value) in self.summary_rows() {
+            let _ = writeln!(out, "| {label} | {} |", escape_cell(&value));
+        }
+
+        out.push_str("\n## Iterations\n\n");
+        if self.iterations.is_empty() {
+            out.push_str("No iterations ran.\n");
+        } else {
+            out.push_str("| # | Model | Duration | Verifiers | Result | Commit |\n");
+            out.push_str("|---|---|---|---|---|---|\n");
+            for it in &self.iterations {
+                let _ = writeln!(
+                    out,
+                    "| {} | {} | {} | {} | {} | {} |",
+                    it.number,
+                    escape_cell(&it.model),
+                    it.duration_ms
+                        .map_or_else(|| "-".to_string(), format_duration),
+                    escape_cell(&verifier_summary(&it.verifiers)),
+                    it.result(),
+                    it.commit.as_deref().map_or("-", short_sha),
+                );
+            }
+        }
+
+        if !self.criteria.is_empty() {
+            out.push_str("\n## Completion criteria\n\n");
+            for c in &self.criteria {
+                let check = if c.passed { "x" } else { " " };
+                let _ = write!(out, "- [{check}] {}", criterion_label(c));
+                if let Some(reason) = &c.reason {
+                    let _ = write!(out, " — {}", reason.replace('\n', " "));
+                }
+                out.push('\n');
+            }
+        }
+
+        if let Some(report) = &self.completion {
+            write_completion_markdown(&mut out, report);
+        }
+
+        if let Some(stat) = &self.diff_stat {
+            out.push_str("\n## Changes\n\n```text\n");
+            out.push_str(stat.trim_end());
+            out.push_str("\n```\n");
+        }
+        out
+    }
+
+    /// Render as a self-contained HTML page.
+    pub fn to_html(&self) -> String {
+        let mut out = String::new();
+        let title = format!("Run report: {}", escape_html(&self.run_id));
+        let _ = writeln!(
+            out,
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>"
+        );
+        out.push_str(
+            "<style>body{font-family:sans-serif;max-width:960px;margin:2em auto;padding:0 1em}\
+             table{border-collapse:collapse}th,td{border:1px solid #ccc;padding:4px 8px;text-align:left}\
+             .pass{color:#1a7f37}.fail{color:#cf222e}pre{background:#f6f8fa;padding:1em}</style>\n",
+        );
+        let _ = writeln!(out, "</head>\n<body>\n<h1>{title}</h1>");
+
+        out.push_str("<table>\n");
+        for (label, value) in self.summary_rows() {
+            let _ = writeln!(
+                out,
+                "<tr><th>{label}</th><td>{}</td></tr>",
+                escape_html(&value)
+            );
+        }
+        out.push_str("</table>\n<h2>Iterations</h2>\n");
+
+        if self.iterations.is_empty() {
+            out.push_str("<p>No iterations ran.</p>\n");
+        } else {
+            out.push_str(
+                "<table>\n<tr><th>#</th><th>Model</th><th>Duration</th><th>Verifiers</th>\
+                 <th>Result</th><th>Commit</th></tr>\n",
+            );
+            for it in &self.iterations {
+                let class = if it.all_verifiers_passed == Some(true) {
+                    "pass"
+                } else {
+                    "fail"
+                };
+                let _ = writeln!(
+                    out,
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td>\
+                     <td class=\"{class}\">{}</td><td><code>{}</code></td></tr>",
+                    it.number,
+                    escape_html(&it.model),
+                    it.duration_ms
+                        .map_or_else(|| "-".to_string(), format_duration),
+                    escape_html(&verifier_summary(&it.verifiers)),
+                    it.result(),
+                    it.commit.as_deref().map_or("-", short_sha),
+                );
+            }
+            out.push_str("</table>\n");
+        }
+
+        if !self.criteria.is_empty() {
+            out.push_str("<h2>Completion criteria</h2>\n<ul>\n");
+            for c in &self.criteria {
+                let (class, mark) = if c.passed {
+                    ("pass", "&#10003;")
+                } else {
+                    ("fail", "&#10007;")
+                };
+                let _ = write!(
+                    out,
+                    "<li><span class=\"{class}\">{mark}</span> {}",
+                    escape_html(&criterion_label(c))
+                );
+                if let Some(reason) = &c.reason {
+                    let _ = write!(out, " &mdash; {}", escape_html(reason));
+                }
+                out.push_str("</li>\n");
+            }
+            out.push_str("</ul>\n");
+        }
+
+        if let Some(report) = &self.completion {
+            write_completion_html(&mut out, report);
+        }
+
+        if let Some(stat) = &self.diff_stat {
+            let _ = writeln!(
+                out,
+                "<h2>Changes</h2>\n<pre>{}</pre>",
+                escape_html(stat.trim_end())
+            );
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+}
+
+/// The "Completion report" section of the Markdown report.
+fn write_completion_markdown(out: &mut String, report: &CompletionReport) {
+    out.push_str("\n## Completion report\n\n");
+    if let Some(summary) = &report.summary {
+        let _ = writeln!(out, "{}\n", summary.trim());
+    }
+    if !report.files_changed.is_empty() {
+        let _ = writeln!(out, "Files changed: {}\n", report.files_changed.join(", "));
+    }
+    if !report.criteria.is_empty() {
+        for c in &report.criteria {
+            let check = if c.met { "x" } else { " " };
+            let _ = write!(out, "- [{check}] {}", c.id);
+            if let Some(notes) = &c.notes {
+                let _ = write!(out, " — {}", notes.replace('\n', " "));
+            }
+            out.push('\n');
+        }
+        out.push('\n');
+    }
+    if !report.follow_up.is_empty() {
+        out.push_str("Follow-up:\n\n");
+        for item in &report.follow_up {
+            let _ = writeln!(out, "- {item}");
+        }
+    }
+}
+
+/// The "Completion report" section of the HTML report.
+fn write_completion_html(out: &mut String, report: &CompletionReport) {
+    out.push_str("<h2>Completion report</h2>\n");
+    if let Some(summary) = &report.summary {
+        let _ = writeln!(out, "<p>{}</p>", escape_html(summary.trim()));
+    }
+    if !report.files_changed.is_empty() {
+        let _ = writeln!(
+            out,
+            "<p>Files changed: {}</p>",
+            escape_html(&report.files_changed.join(", "))
+        );
+    }
+    if !report.criteria.is_empty() {
+        out.push_str("<ul>\n");
+        for c in &report.criteria {
+            let (class, mark) = if c.met {
+                ("pass", "&#10003;")
+            } else {
+                ("fail", "&#10007;")
+            };
+            let _ = write!(
+                out,
+                "<li><span class=\"{class}\">{mark}</span> {}",
+                escape_html(&c.id)
+            );
+            if let Some(notes) = &c.notes {
+                let _ = write!(out, " &mdash; {}", escape_html(notes));
+            }
+            out.push_str("</li>\n");
+        }
+        out.push_str("</ul>\n");
+    }
+    if !report.follow_up.is_empty() {
+        out.push_str("<p>Follow-up:</p>\n<ul>\n");
+        for item in &report.follow_up {
+            let _ = writeln!(out, "<li>{}</li>", escape_html(item));
+        }
+        out.push_str("</ul>\n");
+    }
+}
+
+/// "✓ tests, ✗ lint, - fmt (skipped)".
+fn verifier_summary(verifiers: &[VerifierOutcome]) -> String {
+    if verifiers.is_empty() {
+        return "-".to_string();
+    }
+    verifiers
+        .iter()
+        .map(|v| match (v.skipped, v.passed) {
+            (true, _) => format!("- {} (skipped)", v.name),
+            (false, true) => format!("\u{2713} {}", v.name),
+            (false, false) => format!("\u{2717} {}", v.name),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn criterion_label(c: &CriterionOutcome) -> String {
+    let label = match &c.text {
+        Some(text) => format!("{}. {text}", c.index + 1),
+        None => format!("Criterion {}", c.index + 1),
+    };
+    if c.required {
+        label
+    } else {
+        format!("{label} (optional)")
+    }
+}
+
+fn short_sha(sha: &str) -> &str {
+    sha.get(..8).unwrap_or(sha)
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    time.format("%Y-%m-%d %H:%M:%S UTC").to_string()
+}
+
+/// "850ms", "42s", "3m 5s", "1h 2m".
+fn format_duration(ms: u64) -> String {
+    let secs = ms / 1000;
+    if secs == 0 {
+        format!("{ms}ms")
+    } else if secs < 60 {
+        format!("{secs}s")
+    } else if secs < 3600 {
+        format!("{}m {}s", secs / 60, secs % 60)
+    } else {
+        format!("{}h {}m", secs / 3600, secs % 3600 / 60)
+    }
+}
+
+/// Keep a value from breaking out of its Markdown table cell.
+fn escape_cell(value: &str) -> String {
+    value.replace('|', "\\|").replace('\n', " ")
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Errors that can occur when building a report.
+#[derive(Debug, thiserror::Error)]
+pub enum ReportError {
+    /// The run has no event log.
+    #[error("No events found for run {0}")]
+    NotFound(String),
+
+    /// Unsupported output format.
+    #[error("Unknown report format: {0} (expected md or html)")]
+    UnknownFormat(String),
+
+    /// Event log could not be read.
+    #[error("Failed to read events: {0}")]
+    Events(#[from] EventLogError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(event: RunEvent) -> EventRecord {
+        EventRecord::now(event)
+    }
+
+    fn sample_report() -> RunReport {
+        let records = vec![
+            record(RunEvent::Started {
+                run_id: "run-1".into(),
+                max_iterations: 5,
+                branch: None,
+            }),
+            record(RunEvent::IterationStarted {
+                iteration: 1,
+                model: "claude".into(),
+            }),
+            record(RunEvent::ModelCompleted {
+                iteration: 1,
+                model: "claude".into(),
+                duration_ms: 65_000,
+                has_promise: false,
+                rate_limited: false,
+                output_preview: String::new(),
+            }),
+            record(RunEvent::VerifierCompleted {
+                iteration: 1,
+                name: "tests".into(),
+                passed: false,
+                duration_ms: 1200,
+                skipped: false,
+            }),
+            record(RunEvent::IterationCompleted {
+                iteration: 1,
+                all_verifiers_passed: false,
+            }),
+            record(RunEvent::IterationStarted {
+                iteration: 2,
+                model: "codex".into(),
+            }),
+            record(RunEvent::VerifierCompleted {
+                iteration: 2,
+                name: "tests".into(),
+                passed: true,
+                duration_ms: 1100,
+                skipped: false,
+            }),
+            record(RunEvent::CriterionVerified {
+                index: 1,
+                id: "c2".into(),
+                required: false,
+                passed: true,
+                reason: Some("Tests cover <login>".into()),
+                cached: false,
+            }),
+            record(RunEvent::CriterionVerified {
+                index: 0,
+                id: "c1".into(),
+                required: true,
+                passed: false,
+                reason: None,
+                cached: false,
+            }),
+            record(RunEvent::IterationCompleted {
+                iteration: 2,
+                all_verifiers_passed: true,
+            }),
+            record(RunEvent::IterationCommitted {
+                iteration: 2,
+                commit_sha: "0123456789abcdef".into(),
+            }),
+            record(RunEvent::CompletionReported {
+                iteration: 2,
+                report: CompletionReport {
+                    summary: Some("Added the login page".into()),
+                    follow_up: vec!["Rate-limit logins".into()],
+                    ..CompletionReport::default()
+                },
+            }),
+            record(RunEvent::Completed {
+                iteration: 2,
+                reason: "All criteria met".into(),
+            }),
+        ];
+        RunReport::from_events("run-1", &records).with_criteria(&[
+            Criterion::new(0, "Login page exists"),
+            Criterion::new(1, "Tests pass"),
+        ])
+    }
+
+    #[test]
+    fn test_report_from_events() {
+        let report = sample_report();
+        assert_eq!(report.iterations.len(), 2);
+        assert_eq!(report.iterations[0].result(), "verifiers failed");
+        assert_eq!(report.iterations[1].result(), "passed");
+        assert_eq!(report.commits(), vec!["0123456789abcdef"]);
+        assert_eq!(
+            report.outcome.as_deref(),
+            Some("Completed after iteration 2: All criteria met")
+        );
+        assert_eq!(
+            report.criteria[0].text.as_deref(),
+            Some("Login page exists")
+        );
+        assert!(report.criteria[1].passed);
+    }
+
+    #[test]
+    fn test_render_markdown_and_html() {
+        let mut report = sample_report();
+        report.diff_stat = Some(" src/login.rs | 10 ++++++++++\n".into());
+
+        let md = report.render(ReportFormat::Markdown);
+        assert!(md.starts_with("# Run report: run-1\n"));
+        assert!(md.contains("| 1 | claude | 1m 5s | \u{2717} tests | verifiers failed | - |"));
+        assert!(md.contains("| 2 | codex | - | \u{2713} tests | passed | 01234567 |"));
+        assert!(md.contains("- [ ] 1. Login page exists\n"));
+        assert!(md.contains("- [x] 2. Tests pass (optional) — Tests cover <login>\n"));
+        assert!(md.contains(
+            "## Completion report\n\nAdded the login page\n\nFollow-up:\n\n- Rate-limit logins\n"
+        ));
+        assert!(md.contains("```text\n src/login.rs | 10 ++++++++++\n```"));
+
+        let html = report.render(ReportFormat::Html);
+        assert!(html.contains("<title>Run report: run-1</title>"));
+        assert!(html.contains("Tests cover &lt;login&gt;"));
+        assert!(html.ends_with("</html>\n"));
+    }
+
+    #[test]
+    fn test_report_format() {
+        assert_eq!(
+            "md".parse::<ReportFormat>().unwrap(),
+            ReportFormat::Markdown
+        );
+        assert_eq!("HTML".parse::<ReportFormat>().unwrap(), ReportFormat::Html);
+        assert!("pdf".parse::<ReportFormat>().is_err());
+        assert_eq!(
+            report_path(Path::new(".ralf"), "run-1", ReportFormat::Html),
+            Path::new(".ralf/reports/run-1.html")
+        );
+    }
+}
@@ -0,0 +1,244 @@
+//! Review gate for iterations that change too much.
+//!
+//! A model that goes off track can rewrite half the repository in a single
+//! iteration. With `review` configured, the runner measures what each
+//! iteration changed and, if it touched more files or lines than allowed or
+//! any protected path, holds the run with
+//! [`RunEvent::ReviewRequired`](crate::runner::RunEvent::ReviewRequired)
+//! until the user approves or rejects the changes.
+
+use std::fmt::Write;
+use std::path::Path;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::git::{GitError, GitSafety};
+
+/// Protected files named in a review reason before the rest are counted.
+const MAX_NAMED_PATHS: usize = 3;
+
+/// When an iteration's changes need review before the run goes on.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReviewConfig {
+    /// Most files an iteration may touch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_files: Option<usize>,
+
+    /// Most lines an iteration may add and remove, together.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_lines: Option<usize>,
+
+    /// Globs of paths that need review whenever they are touched, e.g.
+    /// `migrations/`, `*.lock` or `.github/**`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub protected_paths: Vec<String>,
+}
+
+impl ReviewConfig {
+    /// Whether no iteration ever needs review.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Why `changes` need review; empty if they don't.
+    pub fn reasons(&self, changes: &[FileChange]) -> Vec<String> {
+        let mut reasons = Vec::new();
+        if let Some(max) = self.max_files.filter(|max| changes.len() > *max) {
+            reasons.push(format!("Changed {} files (limit {max})", changes.len()));
+        }
+        let lines = lines_changed(changes);
+        if let Some(max) = self.max_lines.filter(|max| lines > *max) {
+            reasons.push(format!("Changed {lines} lines (limit {max})"));
+        }
+
+        let protected: Vec<&str> = changes
+            .iter()
+            .map(|change| change.path.as_str())
+            .filter(|path| {
+                self.protected_paths
+                    .iter()
+                    .any(|pattern| glob_matches(pattern, path))
+            })
+            .collect();
+        if !protected.is_empty() {
+            let mut named = protected[..protected.len().min(MAX_NAMED_PATHS)].join(", ");
+            if protected.len() > MAX_NAMED_PATHS {
+                let _ = write!(named, " and {} more", protected.len() - MAX_NAMED_PATHS);
+            }
+            reasons.push(format!("Touched protected paths: {named}"));
+        }
+        reasons
+    }
+}
+
+/// A file changed by an iteration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    /// Path relative to the repository root.
+    pub path: String,
+    /// Lines added (0 for binary files).
+    pub added: usize,
+    /// Lines removed (0 for binary files).
+    pub removed: usize,
+}
+
+/// Files changed in `work_dir` since its tree hash was `before`, as
+/// returned by [`GitSafety::worktree_tree_hash`].
+///
+/// # Errors
+///
+/// Returns the git error if the workspace can't be hashed or diffed.
+pub fn changes_since(work_dir: &Path, before: &str) -> Result<Vec<FileChange>, GitError> {
+    let git = GitSafety::new(work_dir);
+    let after = git.worktree_tree_hash()?;
+    if after == before {
+        return Ok(Vec::new());
+    }
+    Ok(parse_numstat(&git.diff_numstat_trees(before, &after)?))
+}
+
+/// Parse `git diff --numstat` output. Binary files, shown as `-`, count as
+/// changed files without lines.
+pub fn parse_numstat(output: &str) -> Vec<FileChange> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            let added = fields.next()?;
+            let removed = fields.next()?;
+            let path = fields.next()?;
+            Some(FileChange {
+                path: path.to_string(),
+                added: added.parse().unwrap_or(0),
+                removed: removed.parse().unwrap_or(0),
+            })
+        })
+        .collect()
+}
+
+/// Lines added and removed across `changes`.
+pub fn lines_changed(changes: &[FileChange]) -> usize {
+    changes.iter().map(|c| c.added + c.removed).sum()
+}
+
+/// Whether `path` matches the glob `pattern`.
+///
+/// `*` matches within a path component, `**` across components and `?` a
+/// single character. A pattern ending in `/` matches everything under that
+/// directory, and a pattern without a `/` matches file names at any depth,
+/// as in `.gitignore`.
+pub fn glob_matches(pattern: &str, path: &str) -> bool {
+    let mut pattern = pattern.trim().trim_start_matches('/').to_string();
+    if pattern.is_empty() {
+        return false;
+    }
+    if pattern.ends_with('/') {
+        pattern.push_str("**");
+    }
+
+    let mut regex = String::from("^");
+    if !pattern.contains('/') {
+        regex.push_str("(?:.*/)?");
+    }
+    let mut rest = pattern.as_str();
+    while let Some(c) = rest.chars().next() {
+        if let Some(after) = rest.strip_prefix("**/") {
+            regex.push_str("(?:.*/)?");
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("**") {
+            regex.push_str(".*");
+            rest = after;
+        } else {
+            match c {
+                '*' => regex.push_str("[^/]*"),
+                '?' => regex.push_str("[^/]"),
+                _ => regex.push_str(&regex::escape(&c.to_string())),
+            }
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    regex.push('$');
+    Regex::new(&regex).is_ok_and(|re| re.is_match(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(path: &str, added: usize, removed: usize) -> FileChange {
+        FileChange {
+            path: path.into(),
+            added,
+            removed,
+        }
+    }
+
+    #[test]
+    fn test_parse_numstat() {
+        let changes = parse_numstat("3\t1\tsrc/lib.rs\n-\t-\tlogo.png\n10\t0\tdocs/a b.md\n");
+        assert_eq!(
+            changes,
+            vec![
+                change("src/lib.rs", 3, 1),
+                change("logo.png", 0, 0),
+                change("docs/a b.md", 10, 0),
+            ]
+        );
+        assert_eq!(lines_changed(&changes), 14);
+        assert!(parse_numstat("").is_empty());
+    }
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches("migrations/", "migrations/0001_init.sql"));
+        assert!(glob_matches("migrations/", "migrations/old/0001.sql"));
+        assert!(!glob_matches("migrations/", "db/migrations/0001.sql"));
+        assert!(glob_matches("*.lock", "Cargo.lock"));
+        assert!(glob_matches("*.lock", "web/yarn.lock"));
+        assert!(glob_matches(".github/**", ".github/workflows/ci.yml"));
+        assert!(glob_matches("src/**/auth?.rs", "src/auth1.rs"));
+        assert!(glob_matches("src/**/auth?.rs", "src/api/v2/auth2.rs"));
+        assert!(!glob_matches("src/*.rs", "src/api/mod.rs"));
+        assert!(!glob_matches("Cargo.toml", "Cargo.toml.bak"));
+        assert!(!glob_matches("", "anything"));
+    }
+
+    #[test]
+    fn test_review_reasons() {
+        let config = ReviewConfig {
+            max_files: Some(2),
+            max_lines: Some(100),
+            protected_paths: vec!["migrations/".into(), "*.lock".into()],
+        };
+        assert!(config.reasons(&[change("src/lib.rs", 50, 10)]).is_empty());
+
+        let changes = vec![
+            change("src/lib.rs", 80, 30),
+            change("Cargo.lock", 5, 5),
+            change("migrations/0002.sql", 12, 0),
+        ];
+        assert_eq!(
+            config.reasons(&changes),
+            vec![
+                "Changed 3 files (limit 2)",
+                "Changed 132 lines (limit 100)",
+                "Touched protected paths: Cargo.lock, migrations/0002.sql",
+            ]
+        );
+
+        let many: Vec<FileChange> = (1..=5)
+            .map(|i| change(&format!("migrations/{i}.sql"), 1, 0))
+            .collect();
+        let reasons = ReviewConfig {
+            protected_paths: vec!["migrations/".into()],
+            ..ReviewConfig::default()
+        }
+        .reasons(&many);
+        assert_eq!(
+            reasons,
+            vec!["Touched protected paths: migrations/1.sql, migrations/2.sql, migrations/3.sql and 2 more"]
+        );
+        assert!(ReviewConfig::default().is_empty());
+    }
+}
@@ -12,26 +12,63 @@
 #![allow(clippy::too_many_arguments)]
 #![allow(clippy::ignored_unit_patterns)]
 
-use crate::config::{Config, ModelConfig, ModelSelection, VerifierConfig};
-use crate::state::{Cooldowns, RunState};
+use crate::adapters::{adapter_for, MockResponse, ModelAdapter};
+use crate::changelog::{write_changelog_entry, ChangelogEntry, IterationStatus};
+use crate::clock::{Clock, SystemClock};
+use crate::completion::{self, CompletionReport, PromiseSignal};
+use crate::config::{Config, Isolation, ModelConfig, ModelRole, ModelSelection, VerifierConfig};
+use crate::context::ContextBudget;
+use crate::criterion::{default_required, Criterion};
+use crate::dirs::{RalfDirs, STATE_FILE};
+use crate::embed::{self, with_spawner, ProcessSpawner};
+use crate::events::{EventSink, JsonlSink, MultiSink};
+use crate::git::{porcelain_paths, GitError, GitSafety, RunWorktree};
+use crate::lock::{spawn_heartbeat, LockError, RunLock};
+use crate::logs::{compress_logs, iteration_dir, VERIFY_LOG_DIR};
+use crate::model_stats::{InvocationOutcome, ModelStats};
+use crate::notes::WorkingNotes;
+#[cfg(windows)]
+use crate::platform;
+use crate::prompt_builder::{diff_summary, IterationFeedback, PromptBuilder};
+use crate::ratelimit;
+use crate::repo_context::RepoContext;
+use crate::review;
+use crate::safety::{revert_protected, violation_messages};
+use crate::scheduler::Scheduler;
+use crate::secrets::{secrets_since, SecretFinding};
+use crate::sink::{self, PersistenceSink};
+use crate::snapshot::RunSnapshot;
+use crate::state::{load_or_recover, Cooldowns, PendingQuestion, RunState, RunStatus};
+use crate::stuck::{suggest_recovery, IterationSnapshot, StuckDetector};
+use crate::thread::StuckDiagnosis;
+use crate::usage::{Usage, UsageLedger};
+use crate::verify_cache::VerifyCache;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::path::{Path, PathBuf};
-use std::process::Stdio;
-use std::time::{Duration, Instant};
-use tokio::io::{AsyncWriteExt, BufWriter};
-use tokio::process::Command;
-use tokio::sync::mpsc;
+use std::process::{ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt, BufWriter};
+use tokio::process::{Child, ChildStderr, ChildStdout, Command};
+use tokio::sync::{mpsc, watch};
 use tokio::time::timeout;
+use tracing::Instrument;
 use uuid::Uuid;
 
 /// Events emitted during a run for TUI observation.
-#[derive(Debug, Clone)]
+///
+/// Serialized (tagged by `type`) into the run's `events.jsonl`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum RunEvent {
     /// Run started.
     Started {
         run_id: String,
         max_iterations: usize,
+        /// Worktree branch when running with worktree isolation.
+        branch: Option<String>,
     },
     /// Iteration started.
     IterationStarted { iteration: usize, model: String },
@@ -44,24 +81,49 @@ pub enum RunEvent {
         rate_limited: bool,
         output_preview: String,
     },
-    /// Verifier completed.
+    /// Verifier completed (or was skipped because a dependency or a
+    /// fail-fast verifier failed).
     VerifierCompleted {
         iteration: usize,
         name: String,
         passed: bool,
         duration_ms: u64,
+        #[serde(default)]
+        skipped: bool,
     },
     /// AI verification of completion criteria started.
     VerificationStarted {
         iteration: usize,
+        /// Model verifying the criteria.
         model: String,
+        /// Model whose work is being verified.
+        #[serde(default)]
+        implementer: String,
         criteria_count: usize,
     },
     /// A single criterion was verified.
     CriterionVerified {
         index: usize,
+        /// The criterion's ID ("c1", ...).
+        #[serde(default)]
+        id: String,
+        /// Whether the criterion must pass for the run to complete.
+        #[serde(default = "default_required")]
+        required: bool,
         passed: bool,
         reason: Option<String>,
+        /// The verdict was reused from the verification cache.
+        #[serde(default)]
+        cached: bool,
+    },
+    /// Token/cost usage was recorded for a model invocation.
+    UsageUpdated {
+        iteration: usize,
+        model: String,
+        /// Usage reported by this invocation.
+        usage: Usage,
+        /// Running totals for the whole run.
+        run_total: Usage,
     },
     /// Model entered cooldown.
     CooldownStarted { model: String, duration_secs: u64 },
@@ -70,12 +132,72 @@ pub enum RunEvent {
         iteration: usize,
         all_verifiers_passed: bool,
     },
+    /// Working tree was committed after a successful iteration.
+    IterationCommitted {
+        iteration: usize,
+        commit_sha: String,
+    },
+    /// The model's completion promise came with a report.
+    CompletionReported {
+        iteration: usize,
+        report: CompletionReport,
+    },
     /// Run completed successfully.
     Completed { iteration: usize, reason: String },
     /// Run failed.
     Failed { iteration: usize, error: String },
+    /// Run stopped because it was no longer making progress.
+    Stuck {
+        iteration: usize,
+        diagnosis: StuckDiagnosis,
+    },
     /// Run was cancelled.
     Cancelled { iteration: usize },
+    /// The model asked the user a question (`<promise>NEEDS_INPUT`); the
+    /// run waits for [`RunHandle::answer`] before the next iteration.
+    QuestionAsked { iteration: usize, question: String },
+    /// The user answered the model's question, and the prompts that follow
+    /// carry the answer.
+    QuestionAnswered { iteration: usize, answer: String },
+    /// Run paused after finishing an iteration.
+    Paused { iteration: usize },
+    /// Run resumed after a pause.
+    Resumed { iteration: usize },
+    /// The iteration's changes went past the review limits; the run waits
+    /// for [`RunHandle::approve_review`] or [`RunHandle::reject_review`].
+    ReviewRequired {
+        iteration: usize,
+        /// Why review is needed, one limit per reason.
+        reasons: Vec<String>,
+        files_changed: usize,
+        lines_changed: usize,
+    },
+    /// The changes under review were approved (the run goes on) or
+    /// rejected (the run stops, leaving them in place).
+    ReviewResolved { iteration: usize, approved: bool },
+    /// The iteration changed protected paths, which were reverted, or ran
+    /// forbidden commands, so it can't complete.
+    SafetyViolation {
+        iteration: usize,
+        reverted: Vec<String>,
+        forbidden_commands: Vec<String>,
+    },
+    /// The iteration added something that looks like a secret, so it won't
+    /// be committed.
+    SecretsDetected {
+        iteration: usize,
+        findings: Vec<SecretFinding>,
+    },
+    /// The model left the workspace exactly as it found it, `consecutive`
+    /// iterations in a row counting this one.
+    NoOp {
+        iteration: usize,
+        model: String,
+        consecutive: u32,
+    },
+    /// A run file couldn't be saved, so the run's history may be
+    /// incomplete. Sent the first time each file fails.
+    SaveFailed { what: String, error: String },
     /// Status update (for progress display).
     Status { message: String },
 }
@@ -91,15 +213,61 @@ pub struct RunConfig {
     pub prompt_path: PathBuf,
     /// Repository path.
     pub repo_path: PathBuf,
+    /// Directory models work in, relative to `repo_path` or absolute
+    /// (`None` = `repo_path`). See [`target_root`].
+    pub target: Option<PathBuf>,
     /// Parsed completion criteria from prompt.
-    pub criteria: Vec<String>,
+    pub criteria: Vec<Criterion>,
+    /// Run ID to resume instead of starting a new run.
+    pub resume: Option<String>,
+    /// Resume even if the prompt changed since the run started, and take
+    /// over a stale run lock.
+    pub force: bool,
+    /// Cost budget in US dollars (overrides config; `None` = use config).
+    pub max_cost_usd: Option<f64>,
+    /// Token budget (overrides config; `None` = use config).
+    pub max_tokens: Option<u64>,
+    /// Re-verify every criterion instead of reusing cached verdicts for an
+    /// unchanged workspace.
+    pub no_verify_cache: bool,
+    /// Wall-clock cap per iteration in seconds, including verification
+    /// (overrides config; `None` = use config).
+    pub iteration_timeout_seconds: Option<u64>,
+    /// Shared with other runs going at the same time, which then queue for
+    /// models and, with worktree isolation, share the run lock.
+    pub scheduler: Option<Arc<Scheduler>>,
+    /// Where state, logs and run history are kept (`None` = the layout
+    /// the config asks for in `repo_path`).
+    pub dirs: Option<RalfDirs>,
+    /// Time source for the runtime limit and cooldown waits (`None` = the
+    /// system clock).
+    pub clock: Option<Arc<dyn Clock>>,
+    /// Builds the model and verifier commands (`None` = run them as
+    /// configured).
+    pub spawner: Option<Arc<dyn ProcessSpawner>>,
+}
+
+impl RunConfig {
+    /// Where the run keeps state, logs and history.
+    pub fn dirs(&self, config: &Config) -> RalfDirs {
+        self.dirs
+            .clone()
+            .unwrap_or_else(|| RalfDirs::for_repo(&self.repo_path).configured(config))
+    }
 }
 
 /// Handle for controlling a running loop.
 #[derive(Debug)]
+#[allow(clippy::struct_field_names)] // One sender per control channel
 pub struct RunHandle {
     /// Channel to send cancel signal.
     cancel_tx: mpsc::Sender<()>,
+    /// Pause flag observed by the loop between iterations.
+    pause_tx: watch::Sender<bool>,
+    /// Review decisions (`true` approves) for a run waiting on review.
+    review_tx: mpsc::Sender<bool>,
+    /// Answers for a run waiting on the user's input.
+    answer_tx: mpsc::Sender<String>,
 }
 
 impl RunHandle {
@@ -113,159 +281,610 @@ impl RunHandle {
     pub fn try_cancel(&self) -> bool {
         self.cancel_tx.try_send(()).is_ok()
     }
+
+    /// Pause the loop once the current model invocation finishes.
+    /// Returns false if the run was already paused.
+    pub fn pause(&self) -> bool {
+        self.pause_tx
+            .send_if_modified(|paused| !std::mem::replace(paused, true))
+    }
+
+    /// Resume a paused loop.
+    /// Returns false if the run was not paused.
+    pub fn resume(&self) -> bool {
+        self.pause_tx
+            .send_if_modified(|paused| std::mem::replace(paused, false))
+    }
+
+    /// Whether a pause has been requested.
+    pub fn is_paused(&self) -> bool {
+        *self.pause_tx.borrow()
+    }
+
+    /// Approve the changes a [`RunEvent::ReviewRequired`] is waiting on.
+    /// Returns false if the decision couldn't be sent.
+    pub fn approve_review(&self) -> bool {
+        self.review_tx.try_send(true).is_ok()
+    }
+
+    /// Reject the changes a [`RunEvent::ReviewRequired`] is waiting on,
+    /// stopping the run. Returns false if the decision couldn't be sent.
+    pub fn reject_review(&self) -> bool {
+        self.review_tx.try_send(false).is_ok()
+    }
+
+    /// Answer the question a [`RunEvent::QuestionAsked`] is waiting on.
+    /// Returns false if the answer couldn't be sent.
+    pub fn answer(&self, answer: impl Into<String>) -> bool {
+        self.answer_tx.try_send(answer.into()).is_ok()
+    }
+
+    /// A handle to no run, with the receiving ends of its controls.
+    #[cfg(test)]
+    pub(crate) fn detached() -> (Self, Controls) {
+        Controls::new()
+    }
+}
+
+/// The loop's ends of a [`RunHandle`]'s controls.
+#[derive(Debug)]
+pub(crate) struct Controls {
+    pub(crate) cancel: mpsc::Receiver<()>,
+    pub(crate) pause: watch::Receiver<bool>,
+    pub(crate) review: mpsc::Receiver<bool>,
+    pub(crate) answer: mpsc::Receiver<String>,
+}
+
+impl Controls {
+    /// The controls of a new [`RunHandle`].
+    fn new() -> (RunHandle, Self) {
+        let (cancel_tx, cancel_rx) = mpsc::channel(1);
+        let (pause_tx, pause_rx) = watch::channel(false);
+        let (review_tx, review_rx) = mpsc::channel(1);
+        let (answer_tx, answer_rx) = mpsc::channel(1);
+        let handle = RunHandle {
+            cancel_tx,
+            pause_tx,
+            review_tx,
+            answer_tx,
+        };
+        let controls = Self {
+            cancel: cancel_rx,
+            pause: pause_rx,
+            review: review_rx,
+            answer: answer_rx,
+        };
+        (handle, controls)
+    }
 }
 
 /// Run the main loop with event emission.
 ///
-/// Returns a handle for cancellation and spawns the loop as a background task.
+/// Returns a handle for cancellation and pausing, and spawns the loop as a
+/// background task. [`Runner`](crate::embed::Runner) builds the
+/// `run_config` with defaults for programs embedding the loop.
 pub fn start_run(
     config: Config,
     run_config: RunConfig,
-    event_tx: mpsc::UnboundedSender<RunEvent>,
+    events: impl EventSink + 'static,
 ) -> RunHandle {
-    let (cancel_tx, cancel_rx) = mpsc::channel(1);
+    let (handle, controls) = Controls::new();
 
     tokio::spawn(async move {
-        run_loop(config, run_config, event_tx, cancel_rx).await;
+        let spawner = run_config.spawner.clone();
+        let events = Box::new(events);
+        let run = run_loop(config, run_config, events, controls);
+        with_spawner(spawner, Box::pin(run)).await;
     });
 
-    RunHandle { cancel_tx }
+    handle
 }
 
 /// The main run loop.
 ///
-/// # Event Channel
-/// All event sends use `let _ = event_tx.send(...)` to silently ignore
-/// failures. This is intentional: if the receiver is dropped (e.g., TUI
-/// closed), the run should continue but stop sending events.
+/// # Events
+/// Emitting an event never fails the run: if nobody is listening any more
+/// (e.g., the TUI closed), the run carries on without them.
 #[allow(clippy::too_many_lines)]
+#[tracing::instrument(name = "run", skip_all, fields(run_id = tracing::field::Empty))]
 async fn run_loop(
-    config: Config,
+    mut config: Config,
     run_config: RunConfig,
-    event_tx: mpsc::UnboundedSender<RunEvent>,
-    mut cancel_rx: mpsc::Receiver<()>,
+    events: Box<dyn EventSink>,
+    controls: Controls,
 ) {
-    let run_id = Uuid::new_v4().to_string()[..8].to_string();
-    let start_time = Instant::now();
+    let Controls {
+        cancel: mut cancel_rx,
+        pause: mut pause_rx,
+        review: mut review_rx,
+        answer: mut answer_rx,
+    } = controls;
+    let clock = run_config
+        .clock
+        .clone()
+        .unwrap_or_else(|| Arc::new(SystemClock));
+    let started_at = clock.now();
 
     // Load or create state (using spawn_blocking for serde operations)
-    let ralf_dir = run_config.repo_path.join(".ralf");
-    let state_path = ralf_dir.join("state.json");
-    let cooldowns_path = ralf_dir.join("cooldowns.json");
+    let dirs = run_config.dirs(&config);
+    let ralf_dir = dirs.root().to_path_buf();
+    let state_path = dirs.state();
+    let cooldowns_path = dirs.cooldowns();
+    let changelog_dir = dirs.changelog();
+
+    // Cooldowns are global, so a resumed run picks up whatever is still active
+    let mut cooldowns = load_state_file(cooldowns_path.clone(), Cooldowns::load, &*events).await;
+    cooldowns.set_clock(Arc::clone(&clock));
+    let mut sink = PersistenceSink::new();
+
+    let usage_path = dirs.usage();
+    let mut usage_ledger = load_state_file(usage_path.clone(), UsageLedger::load, &*events).await;
+
+    let model_stats_path = dirs.model_stats();
+    let mut model_stats =
+        load_state_file(model_stats_path.clone(), ModelStats::load, &*events).await;
+
+    // Cached verification verdicts, unless disabled for this run
+    let verify_cache_path = VerifyCache::path(&ralf_dir);
+    let mut verify_cache = if run_config.no_verify_cache {
+        None
+    } else {
+        Some(load_state_file(verify_cache_path.clone(), VerifyCache::load, &*events).await)
+    };
 
-    let state_path_clone = state_path.clone();
-    let mut state = tokio::task::spawn_blocking(move || {
-        RunState::load(&state_path_clone).unwrap_or_default()
-    })
-    .await
-    .unwrap_or_default();
+    // Load prompt (async)
+    let prompt = match tokio::fs::read_to_string(&run_config.prompt_path).await {
+        Ok(p) => p,
+        Err(e) => {
+            events.emit(RunEvent::Failed {
+                iteration: 0,
+                error: format!("Failed to read prompt: {e}"),
+            });
+            return;
+        }
+    };
+    let prompt_hash = hash_prompt(&prompt);
+    let spec = prompt.clone();
+    let mut prompt_builder = PromptBuilder::new(prompt, config.feedback_window);
+    match config.prompt.load(&run_config.repo_path) {
+        Ok((preamble, postamble)) => prompt_builder.set_wrapper(preamble, postamble),
+        Err(e) => {
+            events.emit(RunEvent::Failed {
+                iteration: 0,
+                error: format!("Failed to read prompt wrapper: {e}"),
+            });
+            return;
+        }
+    }
+    let total_criteria = u32::try_from(run_config.criteria.len()).unwrap_or(u32::MAX);
+    let mut stuck_detector = StuckDetector::new(config.stuck_window, total_criteria)
+        .with_no_op_limit(config.max_noop_iterations);
+
+    let (run_id, mut state) = if let Some(resume_id) = run_config.resume.clone() {
+        let ralf_dir_clone = ralf_dir.clone();
+        let hash = prompt_hash.clone();
+        let force = run_config.force;
+        let loaded = tokio::task::spawn_blocking(move || {
+            load_resumable_state(&ralf_dir_clone, &resume_id, &hash, force)
+        })
+        .await
+        .unwrap_or_else(|e| Err(RunnerError::Config(e.to_string())));
+        match loaded {
+            Ok(mut state) => {
+                state.resume();
+                events.emit(RunEvent::Status {
+                    message: format!(
+                        "Resuming run {} after iteration {}",
+                        state.run_id.as_deref().unwrap_or_default(),
+                        state.completed_iterations
+                    ),
+                });
+                (state.run_id.clone().unwrap_or_default(), state)
+            }
+            Err(e) => {
+                events.emit(RunEvent::Failed {
+                    iteration: 0,
+                    error: format!("Cannot resume run: {e}"),
+                });
+                return;
+            }
+        }
+    } else {
+        let run_id = Uuid::new_v4().to_string()[..8].to_string();
+        let mut state = load_state_file(state_path.clone(), RunState::load, &*events).await;
+        state.start_run_with_id(&run_id);
+        state.prompt_hash = Some(prompt_hash.clone());
+        (run_id, state)
+    };
 
-    let cooldowns_path_clone = cooldowns_path.clone();
-    let mut cooldowns = tokio::task::spawn_blocking(move || {
-        Cooldowns::load(&cooldowns_path_clone).unwrap_or_default()
-    })
-    .await
-    .unwrap_or_default();
+    tracing::Span::current().record("run_id", run_id.as_str());
+    tracing::info!(resumed = run_config.resume.is_some(), "run started");
+
+    // Hold the run lock until the loop returns, so no other ralf process
+    // writes state.json meanwhile. Runs in their own worktrees can share it.
+    let force = run_config.force;
+    let lock = match &run_config.scheduler {
+        Some(scheduler) if config.isolation == Isolation::Worktree => {
+            scheduler.run_lock(&ralf_dir, &run_id, force).await
+        }
+        _ => {
+            let lock_dir = ralf_dir.clone();
+            let lock_run_id = run_id.clone();
+            tokio::task::spawn_blocking(move || {
+                RunLock::acquire(&lock_dir, Some(&lock_run_id), force)
+            })
+            .await
+            .unwrap_or_else(|e| Err(LockError::Io(std::io::Error::other(e))))
+            .map(|lock| {
+                let lock = Arc::new(lock);
+                spawn_heartbeat(&lock);
+                lock
+            })
+        }
+    };
+    let _lock = match lock {
+        Ok(lock) => lock,
+        Err(e) => {
+            events.emit(RunEvent::Failed {
+                iteration: 0,
+                error: format!("Cannot start run: {e}"),
+            });
+            return;
+        }
+    };
 
     // Create run directory (async)
-    let run_dir = ralf_dir.join("runs").join(&run_id);
+    let run_dir = dirs.run(&run_id);
     if let Err(e) = tokio::fs::create_dir_all(&run_dir).await {
-        let _ = event_tx.send(RunEvent::Failed {
+        events.emit(RunEvent::Failed {
             iteration: 0,
             error: format!("Failed to create run directory: {e}"),
         });
         return;
     }
+    save_run_state(&mut state, &state_path, &run_dir, &mut sink, &*events).await;
 
-    // Load prompt (async)
-    let prompt = match tokio::fs::read_to_string(&run_config.prompt_path).await {
-        Ok(p) => p,
+    // Record every event from here on in the run's events.jsonl
+    let events: Box<dyn EventSink> = match JsonlSink::open(&run_dir) {
+        Ok(log) => Box::new(MultiSink::new().with(log).with(events)),
+        Err(_) => events,
+    };
+
+    // Keep the spec, config and tool versions the run started with; a
+    // resumed run keeps the ones it first started with
+    if !RunSnapshot::exists(&run_dir) {
+        let snapshot = RunSnapshot::capture(&run_id, &spec, &config).await;
+        let saved = save_file(snapshot, run_dir.clone(), RunSnapshot::save).await;
+        record_save(&mut sink, &*events, "run snapshot", saved);
+    }
+
+    // Set up the directory models work in (a dedicated worktree when isolated)
+    let config_clone = config.clone();
+    let repo_path = run_config.repo_path.clone();
+    let target = target_root(&repo_path, run_config.target.as_deref());
+    let run_id_clone = run_id.clone();
+    let prepared = tokio::task::spawn_blocking(move || {
+        prepare_work_dir(&config_clone, &repo_path, &target, &run_id_clone)
+    })
+    .await
+    .unwrap_or_else(|e| Err(RunnerError::Config(e.to_string())));
+    let (work_dir, worktree) = match prepared {
+        Ok(prepared) => prepared,
         Err(e) => {
-            let _ = event_tx.send(RunEvent::Failed {
+            events.emit(RunEvent::Failed {
                 iteration: 0,
-                error: format!("Failed to read prompt: {e}"),
+                error: format!("Failed to prepare the work directory: {e}"),
             });
+            state.fail();
+            save_run_state(&mut state, &state_path, &run_dir, &mut sink, &*events).await;
             return;
         }
     };
 
-    let _ = event_tx.send(RunEvent::Started {
+    events.emit(RunEvent::Started {
         run_id: run_id.clone(),
         max_iterations: run_config.max_iterations,
+        branch: worktree.as_ref().map(|w| w.branch.clone()),
     });
+    if let Some(worktree) = &worktree {
+        events.emit(RunEvent::Status {
+            message: format!(
+                "Worktree: {} (branch {})",
+                worktree.path.display(),
+                worktree.branch
+            ),
+        });
+    }
+
+    // The model would be asked to change files it isn't allowed to
+    let mentioned = config.safety.mentioned_in(&spec);
+    if !mentioned.is_empty() {
+        events.emit(RunEvent::Status {
+            message: format!(
+                "Warning: PROMPT.md mentions protected paths, whose changes will be reverted: {}",
+                mentioned.join(", ")
+            ),
+        });
+    }
+
+    // Tagged verifiers only run when the spec has criteria they check
+    for verifier in config
+        .verifiers
+        .iter()
+        .filter(|v| !v.applies_to(&run_config.criteria))
+    {
+        events.emit(RunEvent::Status {
+            message: format!(
+                "Not running verifier '{}': no criteria tagged {}",
+                verifier.name,
+                verifier.tags.join(", ")
+            ),
+        });
+    }
 
-    let mut iteration = 0;
+    let max_cost_usd = run_config.max_cost_usd.or(config.max_cost_usd);
+    let max_tokens = run_config.max_tokens.or(config.max_tokens);
+    let iteration_timeout = run_config
+        .iteration_timeout_seconds
+        .or(config.iteration_timeout_seconds)
+        .map(Duration::from_secs);
+
+    #[allow(clippy::cast_possible_truncation)]
+    let mut iteration = state.completed_iterations as usize;
+    // Whether the "all models in cooldown" wait has been announced
+    let mut waiting = false;
 
     loop {
+        // Everything before this pass has finished; record it as the resume point
+        state.completed_iterations = iteration as u64;
         iteration += 1;
 
         // Check cancellation
         if cancel_rx.try_recv().is_ok() {
-            let _ = event_tx.send(RunEvent::Cancelled { iteration });
+            events.emit(RunEvent::Cancelled { iteration });
+            state.cancel();
             break;
         }
 
+        // Wait here while paused, before selecting the next model
+        if *pause_rx.borrow_and_update() {
+            events.emit(RunEvent::Paused {
+                iteration: iteration - 1,
+            });
+            tokio::select! {
+                _ = cancel_rx.recv() => {
+                    events.emit(RunEvent::Cancelled { iteration });
+                    state.cancel();
+                    break;
+                }
+                _ = pause_rx.wait_for(|paused| !paused) => {
+                    events.emit(RunEvent::Resumed { iteration });
+                }
+            }
+        }
+
         // Check max iterations
         if run_config.max_iterations > 0 && iteration > run_config.max_iterations {
-            let _ = event_tx.send(RunEvent::Completed {
+            events.emit(RunEvent::Completed {
                 iteration: iteration - 1,
                 reason: "Max iterations reached".into(),
             });
+            state.fail();
             break;
         }
 
         // Check max runtime
         if run_config.max_runtime_secs > 0
-            && start_time.elapsed().as_secs() > run_config.max_runtime_secs
+            && clock.now().saturating_sub(started_at) > run_config.max_runtime_secs
         {
-            let _ = event_tx.send(RunEvent::Completed {
+            events.emit(RunEvent::Completed {
                 iteration: iteration - 1,
                 reason: "Max runtime reached".into(),
             });
+            state.fail();
+            break;
+        }
+
+        // Check budget
+        let run_usage = usage_ledger
+            .run(&run_id)
+            .map(|r| r.total)
+            .unwrap_or_default();
+        if run_usage.exceeds(max_cost_usd, max_tokens) {
+            events.emit(RunEvent::Completed {
+                iteration: iteration - 1,
+                reason: "Budget exceeded".into(),
+            });
+            state.fail();
             break;
         }
 
+        // Wait for the user to answer the question the model asked, here
+        // or before the run was interrupted
+        if let Some(pending) = state.pending_question.clone() {
+            let asked = usize::try_from(pending.iteration).unwrap_or(usize::MAX);
+            // Answers sent while nothing was asked don't count
+            while answer_rx.try_recv().is_ok() {}
+            events.emit(RunEvent::QuestionAsked {
+                iteration: asked,
+                question: pending.question.clone(),
+            });
+            let answer = tokio::select! {
+                _ = cancel_rx.recv() => {
+                    events.emit(RunEvent::Cancelled { iteration });
+                    state.cancel();
+                    break;
+                }
+                answer = answer_rx.recv() => answer,
+            };
+            // Nobody left to answer once the handle is gone
+            let Some(answer) = answer else {
+                events.emit(RunEvent::Failed {
+                    iteration: asked,
+                    error: format!("Nobody answered the model's question: {}", pending.question),
+                });
+                state.fail();
+                break;
+            };
+            events.emit(RunEvent::QuestionAnswered {
+                iteration: asked,
+                answer: answer.clone(),
+            });
+            prompt_builder.record_answer(pending.question, answer);
+            state.pending_question = None;
+        }
+
+        // Pick up `ralf cooldown` and `ralf model` changes made while running
+        reload_cooldowns(&mut cooldowns, &cooldowns_path).await;
+        reload_disabled_models(&mut config, &run_config.repo_path).await;
+
         // Clear expired cooldowns
         cooldowns.clear_expired();
 
         // Select model
-        let model = match select_model(&config, &cooldowns, &mut state) {
+        let model = match select_model(&config, &cooldowns, &model_stats, &mut state) {
             Some(m) => m.clone(),
             None => {
-                // Use actual remaining cooldown time instead of fixed 5 seconds
-                let wait_secs = cooldowns.earliest_expiry().map_or(5, |exp| {
-                    let now = crate::state::current_timestamp();
-                    exp.saturating_sub(now).max(1) // At least 1 second
-                });
+                // With no cooldown to wait out, every model is disabled
+                let Some(expiry) = cooldowns.earliest_expiry() else {
+                    events.emit(RunEvent::Failed {
+                        iteration: iteration - 1,
+                        error: "No models available".into(),
+                    });
+                    state.fail();
+                    break;
+                };
+                let wait_secs = expiry.saturating_sub(clock.now()).max(1);
 
-                let _ = event_tx.send(RunEvent::Status {
-                    message: format!("All models in cooldown, waiting {wait_secs}s..."),
-                });
-                // Wait for cooldown with cancel check
+                if !waiting {
+                    events.emit(RunEvent::Status {
+                        message: format!("All models in cooldown, waiting {wait_secs}s..."),
+                    });
+                    waiting = true;
+                }
+                // Wait for cooldown with cancel check, waking up now and then
+                // in case a cooldown is cleared or a model re-enabled
+                let wait = Duration::from_secs(wait_secs).min(STEERING_POLL);
                 tokio::select! {
                     _ = cancel_rx.recv() => {
-                        let _ = event_tx.send(RunEvent::Cancelled { iteration });
-                        return;
+                        events.emit(RunEvent::Cancelled { iteration });
+                        state.cancel();
+                        break;
                     }
-                    _ = tokio::time::sleep(Duration::from_secs(wait_secs)) => {}
+                    _ = tokio::time::sleep(wait) => {}
                 }
+                // Waiting doesn't use up an iteration
+                iteration -= 1;
                 continue;
             }
         };
 
-        let _ = event_tx.send(RunEvent::IterationStarted {
+        waiting = false;
+
+        events.emit(RunEvent::IterationStarted {
             iteration,
             model: model.name.clone(),
         });
+        let iteration_span = tracing::info_span!("iteration", iteration, model = %model.name);
+        tracing::info!(parent: &iteration_span, "iteration started");
+        let iteration_deadline = iteration_timeout.map(|t| tokio::time::Instant::now() + t);
+
+        // The previous iterations' logs are complete
+        if config.compress_logs {
+            compress_run_logs(&run_dir).await;
+        }
 
         // Invoke model with cancel check
+        let log_dir = iteration_dir(&run_dir, iteration as u64);
+        if config.repo_context {
+            prompt_builder.set_repo_context(RepoContext::gather(&work_dir));
+        }
+        if config.notes {
+            prompt_builder.set_notes(Some(WorkingNotes::read(&WorkingNotes::path(&run_dir))));
+        }
+        prompt_builder.start_iteration(iteration as u64, &model.name);
+        let iteration_prompt = prompt_builder.build_within(&mut ContextBudget::for_model(&model));
+        // The workspace before the model changes it, to measure its changes
+        let tree_before = if config.review.is_empty()
+            && config.safety.protected_paths.is_empty()
+            && !config.scan_secrets
+            && config.max_noop_iterations == 0
+        {
+            None
+        } else {
+            GitSafety::new(&work_dir).worktree_tree_hash().ok()
+        };
+        // Wait for the model if other runs are using it
+        let slot = match &run_config.scheduler {
+            Some(scheduler) => match scheduler.try_acquire(&model.name) {
+                Some(slot) => Some(slot),
+                None => {
+                    events.emit(RunEvent::Status {
+                        message: format!("Waiting for {}: another run is using it", model.name),
+                    });
+                    tokio::select! {
+                        _ = cancel_rx.recv() => {
+                            events.emit(RunEvent::Cancelled { iteration });
+                            state.cancel();
+                            break;
+                        }
+                        slot = scheduler.acquire(&model.name) => Some(slot),
+                    }
+                }
+            },
+            None => None,
+        };
+        let invoked_at = std::time::Instant::now();
         let invoke_result = tokio::select! {
             _ = cancel_rx.recv() => {
-                let _ = event_tx.send(RunEvent::Cancelled { iteration });
-                return;
+                let entry = ChangelogEntry {
+                    changelog_dir: &changelog_dir,
+                    run_id: &run_id,
+                    iteration: iteration as u64,
+                    invocation: &unfinished_invocation(&model.name, invoked_at.elapsed(), String::new()),
+                    verifier_results: &[],
+                    criteria: &run_config.criteria,
+                    prompt_hash: &prompt_hash,
+                    git_info: &get_git_info_in(&work_dir),
+                    status: IterationStatus::Cancelled,
+                    reason: "Cancelled while the model was running",
+                    log_path: log_dir.join(format!("{}.log", model.name)),
+                    commit_sha: None,
+                };
+                record_changelog(&mut sink, &*events, &entry);
+                events.emit(RunEvent::Cancelled { iteration });
+                state.cancel();
+                break;
             }
-            result = invoke_model(&model, &prompt, &run_dir) => result
+            result = invoke_model_within(
+                &model,
+                &iteration_prompt,
+                &log_dir,
+                &work_dir,
+                iteration_timeout.unwrap_or(Duration::MAX),
+            )
+            .instrument(iteration_span.clone()) => result
         };
+        drop(slot);
+
+        let (outcome, duration_ms) = match &invoke_result {
+            Ok(r) => (InvocationOutcome::of(r), r.duration_ms),
+            // The iteration timeout can stop the model before its own does
+            Err(RunnerError::Timeout(_)) => {
+                #[allow(clippy::cast_possible_truncation)]
+                let elapsed = invoked_at.elapsed().as_millis() as u64;
+                (InvocationOutcome::TimedOut, elapsed)
+            }
+            Err(_) => (InvocationOutcome::Failure, 0),
+        };
+        model_stats.record(&model.name, outcome, duration_ms);
+        let saved = save_file(
+            model_stats.clone(),
+            model_stats_path.clone(),
+            ModelStats::save,
+        )
+        .await;
+        record_save(&mut sink, &*events, "model stats", saved);
 
         let result = match invoke_result {
             Ok(mut r) => {
@@ -273,23 +892,53 @@ async fn run_loop(
                 r
             }
             Err(e) => {
-                let _ = event_tx.send(RunEvent::Failed {
+                tracing::warn!(parent: &iteration_span, "model invocation failed: {e}");
+                events.emit(RunEvent::Failed {
                     iteration,
                     error: format!("Model invocation failed: {e}"),
                 });
+                let (status, reason, cooldown_reason) = match e {
+                    RunnerError::Timeout(_) => {
+                        (IterationStatus::Timeout, "Model timed out", "timeout")
+                    }
+                    _ => (
+                        IterationStatus::Error,
+                        "Model invocation failed",
+                        "invocation error",
+                    ),
+                };
+                let entry = ChangelogEntry {
+                    changelog_dir: &changelog_dir,
+                    run_id: &run_id,
+                    iteration: iteration as u64,
+                    invocation: &unfinished_invocation(
+                        &model.name,
+                        Duration::from_millis(duration_ms),
+                        e.to_string(),
+                    ),
+                    verifier_results: &[],
+                    criteria: &run_config.criteria,
+                    prompt_hash: &prompt_hash,
+                    git_info: &get_git_info_in(&work_dir),
+                    status,
+                    reason,
+                    log_path: log_dir.join(format!("{}.log", model.name)),
+                    commit_sha: None,
+                };
+                record_changelog(&mut sink, &*events, &entry);
 
                 // Apply cooldown on error
+                reload_cooldowns(&mut cooldowns, &cooldowns_path).await;
                 cooldowns.set_cooldown(
                     &model.name,
                     model.default_cooldown_seconds,
-                    "invocation error",
+                    cooldown_reason,
                 );
-                // Save cooldowns asynchronously
-                let cooldowns_clone = cooldowns.clone();
-                let path = cooldowns_path.clone();
-                let _ = tokio::task::spawn_blocking(move || cooldowns_clone.save(&path)).await;
+                let saved =
+                    save_file(cooldowns.clone(), cooldowns_path.clone(), Cooldowns::save).await;
+                record_save(&mut sink, &*events, "cooldowns", saved);
 
-                let _ = event_tx.send(RunEvent::CooldownStarted {
+                events.emit(RunEvent::CooldownStarted {
                     model: model.name.clone(),
                     duration_secs: model.default_cooldown_seconds,
                 });
@@ -300,8 +949,14 @@ async fn run_loop(
 
         // Send full output to TUI (no truncation - TUI handles display)
         let output_preview = result.stdout.clone();
+        // A blocked model or one with a question stops short of completing
+        let signal = if result.has_promise {
+            None
+        } else {
+            PromiseSignal::from_output(&result.stdout)
+        };
 
-        let _ = event_tx.send(RunEvent::ModelCompleted {
+        events.emit(RunEvent::ModelCompleted {
             iteration,
             model: model.name.clone(),
             duration_ms: result.duration_ms,
@@ -310,94 +965,757 @@ async fn run_loop(
             output_preview,
         });
 
+        // Record token/cost usage
+        if let Some(usage) = result.usage {
+            let run_total = usage_ledger.record(&run_id, &model.name, &usage).total;
+            let saved =
+                save_file(usage_ledger.clone(), usage_path.clone(), UsageLedger::save).await;
+            record_save(&mut sink, &*events, "usage", saved);
+
+            events.emit(RunEvent::UsageUpdated {
+                iteration,
+                model: model.name.clone(),
+                usage,
+                run_total,
+            });
+        }
+
         // Handle rate limiting
         if result.rate_limited {
-            cooldowns.set_cooldown(&model.name, model.default_cooldown_seconds, "rate limited");
-            // Save cooldowns asynchronously
-            let cooldowns_clone = cooldowns.clone();
-            let path = cooldowns_path.clone();
-            let _ = tokio::task::spawn_blocking(move || cooldowns_clone.save(&path)).await;
-
-            let _ = event_tx.send(RunEvent::CooldownStarted {
+            reload_cooldowns(&mut cooldowns, &cooldowns_path).await;
+            let duration_secs = ratelimit::start_cooldown(
+                &mut cooldowns,
+                &model.name,
+                &format!("{}\n{}", result.stdout, result.stderr),
+                model.default_cooldown_seconds,
+                "rate limited",
+            );
+            tracing::info!(parent: &iteration_span, duration_secs, "rate limited, cooling down");
+            let saved = save_file(cooldowns.clone(), cooldowns_path.clone(), Cooldowns::save).await;
+            record_save(&mut sink, &*events, "cooldowns", saved);
+
+            events.emit(RunEvent::CooldownStarted {
                 model: model.name.clone(),
-                duration_secs: model.default_cooldown_seconds,
+                duration_secs,
             });
+            let entry = ChangelogEntry {
+                changelog_dir: &changelog_dir,
+                run_id: &run_id,
+                iteration: iteration as u64,
+                invocation: &result,
+                verifier_results: &[],
+                criteria: &run_config.criteria,
+                prompt_hash: &prompt_hash,
+                git_info: &get_git_info_in(&work_dir),
+                status: IterationStatus::RateLimited,
+                reason: "Rate limited",
+                log_path: log_dir.join(format!("{}.log", model.name)),
+                commit_sha: None,
+            };
+            record_changelog(&mut sink, &*events, &entry);
 
             continue;
         }
 
-        // Check for completion promise and verify criteria
-        if result.has_promise {
-            // If there are criteria to verify, run AI verification
-            if !run_config.criteria.is_empty() {
-                // Run verification with cancel check
-                let verification_results = tokio::select! {
-                    _ = cancel_rx.recv() => {
-                        let _ = event_tx.send(RunEvent::Cancelled { iteration });
-                        return;
-                    }
-                    results = verify_criteria(
-                        &config,
-                        &run_config.criteria,
-                        &result.stdout,
-                        &run_dir,
-                        &mut state,
-                        &cooldowns,
-                        &event_tx,
-                        iteration,
-                    ) => results
-                };
-
-                let all_passed = verification_results.iter().all(|r| r.passed);
+        // Put protected files back, and refuse to complete after a forbidden command
+        let mut reverted = Vec::new();
+        if let Some(before) = &tree_before {
+            match revert_protected(&config.safety, &work_dir, before) {
+                Ok(paths) => reverted = paths,
+                Err(e) => {
+                    events.emit(RunEvent::Status {
+                        message: format!("Cannot check protected paths: {e}"),
+                    });
+                }
+            }
+        }
+        let forbidden = config
+            .safety
+            .forbidden_in(&format!("{}\n{}", result.stdout, result.stderr));
+        if !reverted.is_empty() || !forbidden.is_empty() {
+            tracing::warn!(parent: &iteration_span, ?reverted, ?forbidden, "safety violation");
+            events.emit(RunEvent::SafetyViolation {
+                iteration,
+                reverted: reverted.clone(),
+                forbidden_commands: forbidden.clone(),
+            });
+        }
+        let safety_feedback = violation_messages(&reverted, &forbidden);
+        let promised = result.has_promise && forbidden.is_empty();
+        // Whether the model did nothing, protected paths it changed aside; a
+        // model asking a question needn't have changed anything
+        let no_op = config.max_noop_iterations > 0
+            && signal.is_none()
+            && tree_before.as_ref().is_some_and(|before| {
+                GitSafety::new(&work_dir)
+                    .worktree_tree_hash()
+                    .is_ok_and(|after| &after == before)
+            });
 
-                let _ = event_tx.send(RunEvent::IterationCompleted {
-                    iteration,
-                    all_verifiers_passed: all_passed,
+        // Keep secrets the model added out of commits
+        let mut secrets = Vec::new();
+        if let Some(before) = tree_before.as_ref().filter(|_| config.scan_secrets) {
+            match secrets_since(&work_dir, before) {
+                Ok(findings) => secrets = findings,
+                Err(e) => {
+                    events.emit(RunEvent::Status {
+                        message: format!("Cannot scan changes for secrets: {e}"),
+                    });
+                }
+            }
+        }
+        if !secrets.is_empty() {
+            tracing::warn!(parent: &iteration_span, count = secrets.len(), "possible secrets added");
+            events.emit(RunEvent::SecretsDetected {
+                iteration,
+                findings: secrets.clone(),
+            });
+            if config.commit_per_iteration {
+                events.emit(RunEvent::Status {
+                    message: format!(
+                        "Not committing iteration {iteration}: possible secrets in its changes"
+                    ),
                 });
-
-                if all_passed {
-                    let _ = event_tx.send(RunEvent::Completed {
-                        iteration,
-                        reason: "All criteria verified".into(),
+            }
+        }
+        let commit = config.commit_per_iteration && secrets.is_empty();
+
+        // Hold large or sensitive changes for review before verifying them
+        if let Some(before) = &tree_before {
+            match review::changes_since(&work_dir, before) {
+                Ok(changes) => {
+                    let reasons = config.review.reasons(&changes);
+                    if !reasons.is_empty() {
+                        // Decisions sent while nothing was under review don't count
+                        while review_rx.try_recv().is_ok() {}
+                        events.emit(RunEvent::ReviewRequired {
+                            iteration,
+                            reasons,
+                            files_changed: changes.len(),
+                            lines_changed: review::lines_changed(&changes),
+                        });
+                        let approved = tokio::select! {
+                            _ = cancel_rx.recv() => {
+                                events.emit(RunEvent::Cancelled { iteration });
+                                state.cancel();
+                                break;
+                            }
+                            // Nobody left to approve once the handle is gone
+                            decision = review_rx.recv() => decision.unwrap_or(false),
+                        };
+                        events.emit(RunEvent::ReviewResolved {
+                            iteration,
+                            approved,
+                        });
+                        if !approved {
+                            events.emit(RunEvent::Completed {
+                                iteration,
+                                reason: "Changes rejected in review".into(),
+                            });
+                            state.fail();
+                            break;
+                        }
+                    }
+                }
+                Err(e) => {
+                    events.emit(RunEvent::Status {
+                        message: format!("Cannot measure changes for review: {e}"),
                     });
-                    break;
                 }
-                // Criteria failed - continue to next iteration
+            }
+        }
+
+        // Run the verifiers the spec's criteria call for, within what is
+        // left of the iteration
+        let mut verifier_results = Vec::new();
+        let mut cancelled = false;
+        let verifiers = config.ordered_verifiers().into_iter();
+        for verifier in verifiers.filter(|v| v.applies_to(&run_config.criteria)) {
+            let time_left = iteration_deadline
+                .map(|deadline| deadline.saturating_duration_since(tokio::time::Instant::now()));
+            let skip_reason = if time_left == Some(Duration::ZERO) {
+                Some("iteration timeout reached".to_string())
             } else {
-                // No criteria to verify, complete immediately
-                let _ = event_tx.send(RunEvent::IterationCompleted {
+                verifier_skip_reason(verifier, &config.verifiers, &verifier_results)
+            };
+            if let Some(reason) = skip_reason {
+                events.emit(RunEvent::Status {
+                    message: format!("Skipping verifier '{}': {reason}", verifier.name),
+                });
+                events.emit(RunEvent::VerifierCompleted {
                     iteration,
-                    all_verifiers_passed: true,
+                    name: verifier.name.clone(),
+                    passed: false,
+                    duration_ms: 0,
+                    skipped: true,
                 });
+                verifier_results.push(VerifierResult::skipped(&verifier.name, &reason));
+                continue;
+            }
+            let verified = tokio::select! {
+                _ = cancel_rx.recv() => {
+                    cancelled = true;
+                    break;
+                }
+                result = run_verifier_within(
+                    verifier,
+                    &log_dir,
+                    &work_dir,
+                    time_left.unwrap_or(Duration::MAX),
+                )
+                .instrument(iteration_span.clone()) => result,
+            };
+            let verifier_result = verified.unwrap_or_else(|e| VerifierResult {
+                name: verifier.name.clone(),
+                passed: false,
+                exit_code: None,
+                output: e.to_string(),
+                duration_ms: 0,
+                skipped: false,
+            });
+            events.emit(RunEvent::VerifierCompleted {
+                iteration,
+                name: verifier_result.name.clone(),
+                passed: verifier_result.passed,
+                duration_ms: verifier_result.duration_ms,
+                skipped: false,
+            });
+            verifier_results.push(verifier_result);
+        }
+        if cancelled {
+            let entry = ChangelogEntry {
+                changelog_dir: &changelog_dir,
+                run_id: &run_id,
+                iteration: iteration as u64,
+                invocation: &result,
+                verifier_results: &verifier_results,
+                criteria: &run_config.criteria,
+                prompt_hash: &prompt_hash,
+                git_info: &get_git_info_in(&work_dir),
+                status: IterationStatus::Cancelled,
+                reason: "Cancelled while verifying",
+                log_path: log_dir.join(format!("{}.log", model.name)),
+                commit_sha: None,
+            };
+            record_changelog(&mut sink, &*events, &entry);
+            events.emit(RunEvent::Cancelled { iteration });
+            state.cancel();
+            break;
+        }
+        let verifiers_passed = verifier_results.iter().all(|r| r.passed);
+        let failed_verifiers: Vec<&VerifierResult> = verifier_results
+            .iter()
+            .filter(|r| !r.passed && !r.skipped)
+            .collect();
 
-                let _ = event_tx.send(RunEvent::Completed {
+        let mut snapshot = IterationSnapshot {
+            model: model.name.clone(),
+            no_op,
+            failed_verifiers: failed_verifiers.iter().map(|r| r.name.clone()).collect(),
+            error: failed_verifiers
+                .first()
+                .map(|r| format!("verifier '{}' failed", r.name)),
+            ..IterationSnapshot::default()
+        };
+        let mut failing_criteria = Vec::new();
+        let mut failed_criteria = Vec::new();
+
+        // A promise may carry the model's report, kept if the run completes
+        let completion_report = if promised {
+            read_completion_report(
+                &result.stdout,
+                &config.completion_promise,
+                iteration,
+                &*events,
+            )
+        } else {
+            None
+        };
+
+        // Once the verifiers pass, a promise completes the run if the
+        // criteria it claims are met verify too
+        let mut completed_because = (promised && verifiers_passed)
+            .then(|| "Promise fulfilled (no criteria to verify)".to_string());
+        let mut criteria_passed = true;
+        let mut criteria_summary = None;
+        if completed_because.is_some() && !run_config.criteria.is_empty() {
+            // Run verification with cancel check
+            let verification_results = tokio::select! {
+                _ = cancel_rx.recv() => {
+                    let entry = ChangelogEntry {
+                        changelog_dir: &changelog_dir,
+                        run_id: &run_id,
+                        iteration: iteration as u64,
+                        invocation: &result,
+                        verifier_results: &verifier_results,
+                        criteria: &run_config.criteria,
+                        prompt_hash: &prompt_hash,
+                        git_info: &get_git_info_in(&work_dir),
+                        status: IterationStatus::Cancelled,
+                        reason: "Cancelled while verifying criteria",
+                        log_path: log_dir.join(format!("{}.log", model.name)),
+                        commit_sha: None,
+                    };
+                    record_changelog(&mut sink, &*events, &entry);
+                    events.emit(RunEvent::Cancelled { iteration });
+                    state.cancel();
+                    break;
+                }
+                results = verify_criteria(
+                    &config,
+                    &run_config.criteria,
+                    &result.stdout,
+                    &log_dir,
+                    &work_dir,
+                    &model.name,
+                    &cooldowns,
+                    &*events,
                     iteration,
-                    reason: "Promise fulfilled (no criteria to verify)".into(),
-                });
-                break;
+                    verify_cache.as_mut(),
+                )
+                .instrument(iteration_span.clone()) => results,
+                () = sleep_until_deadline(iteration_deadline) => {
+                    tracing::warn!(parent: &iteration_span, "iteration timed out during verification");
+                    events.emit(RunEvent::Failed {
+                        iteration,
+                        error: format!(
+                            "Iteration timed out after {}s",
+                            iteration_timeout.unwrap_or_default().as_secs()
+                        ),
+                    });
+                    let entry = ChangelogEntry {
+                        changelog_dir: &changelog_dir,
+                        run_id: &run_id,
+                        iteration: iteration as u64,
+                        invocation: &result,
+                        verifier_results: &verifier_results,
+                        criteria: &run_config.criteria,
+                        prompt_hash: &prompt_hash,
+                        git_info: &get_git_info_in(&work_dir),
+                        status: IterationStatus::Timeout,
+                        reason: "Iteration timed out while verifying criteria",
+                        log_path: log_dir.join(format!("{}.log", model.name)),
+                        commit_sha: None,
+                    };
+                    record_changelog(&mut sink, &*events, &entry);
+                    continue;
+                }
+            };
+            if let Some(cache) = &verify_cache {
+                let saved = sink::retry(|| cache.save(&verify_cache_path));
+                record_save(&mut sink, &*events, "verification cache", saved);
+            }
+
+            // Optional criteria are verified and reported, but only the
+            // required ones decide completion
+            let passed = verification_results.iter().filter(|r| r.passed).count();
+            criteria_passed = verification_results
+                .iter()
+                .all(|r| r.passed || !run_config.criteria[r.index].required);
+            tracing::info!(
+                parent: &iteration_span,
+                passed,
+                total = verification_results.len(),
+                "criteria verified"
+            );
+            criteria_summary = Some(format!(
+                "Criteria: {passed}/{} passed",
+                run_config.criteria.len()
+            ));
+            completed_because = criteria_passed
+                .then(|| completion_reason(&run_config.criteria, &verification_results));
+
+            snapshot.criteria_passed = Some(u32::try_from(passed).unwrap_or(u32::MAX));
+            if let Some(error) = verification_results
+                .iter()
+                .find(|r| !r.passed && run_config.criteria[r.index].required)
+                .map(|r| {
+                    let criterion = &run_config.criteria[r.index];
+                    match &r.reason {
+                        Some(reason) => format!("{criterion}: {reason}"),
+                        None => criterion.to_string(),
+                    }
+                })
+            {
+                snapshot.error = Some(error);
             }
+            failing_criteria = verification_results
+                .iter()
+                .filter(|r| !r.passed)
+                .map(|r| run_config.criteria[r.index].text.clone())
+                .collect();
+            failed_criteria = verification_results
+                .iter()
+                .filter(|r| !r.passed)
+                .map(|r| {
+                    (
+                        feedback_label(&run_config.criteria[r.index]),
+                        r.reason.clone(),
+                    )
+                })
+                .collect();
+        }
+
+        events.emit(RunEvent::IterationCompleted {
+            iteration,
+            all_verifiers_passed: verifiers_passed && criteria_passed,
+        });
+
+        let (status, reason) = if !secrets.is_empty() {
+            (
+                IterationStatus::NeedsAttention,
+                "Possible secrets added, not committed",
+            )
+        } else if completed_because.is_some() {
+            (
+                IterationStatus::Success,
+                "All verifiers passed with promise",
+            )
+        } else if no_op {
+            (IterationStatus::NoOp, "Model changed nothing")
+        } else if promised && !verifiers_passed {
+            (
+                IterationStatus::VerifierFailed,
+                "Promise found but verifiers failed",
+            )
+        } else if promised {
+            (
+                IterationStatus::VerifierFailed,
+                "Promise found but criteria failed",
+            )
+        } else if verifiers_passed {
+            (
+                IterationStatus::VerifierFailed,
+                "Verifiers passed but no promise",
+            )
         } else {
-            let _ = event_tx.send(RunEvent::IterationCompleted {
+            (
+                IterationStatus::VerifierFailed,
+                "Verifiers failed, no promise",
+            )
+        };
+
+        // Commit the work tree when its verifiers pass
+        let commit_sha = if commit && verifiers_passed {
+            let summary = criteria_summary.unwrap_or_else(|| verifier_summary(&verifier_results));
+            commit_iteration(
+                &work_dir,
+                &run_id,
                 iteration,
-                all_verifiers_passed: false,
+                &model.name,
+                &summary,
+                &*events,
+            )
+            .await
+        } else {
+            None
+        };
+
+        let entry = ChangelogEntry {
+            changelog_dir: &changelog_dir,
+            run_id: &run_id,
+            iteration: iteration as u64,
+            invocation: &result,
+            verifier_results: &verifier_results,
+            criteria: &run_config.criteria,
+            prompt_hash: &prompt_hash,
+            git_info: &get_git_info_in(&work_dir),
+            status,
+            reason,
+            log_path: log_dir.join(format!("{}.log", model.name)),
+            commit_sha: commit_sha.as_deref(),
+        };
+        record_changelog(&mut sink, &*events, &entry);
+
+        if let Some(reason) = completed_because {
+            events.emit(RunEvent::Completed { iteration, reason });
+            state.completion_report = completion_report;
+            state.complete();
+            break;
+        }
+
+        // Tell the next iteration why this one didn't finish
+        prompt_builder.record(IterationFeedback {
+            iteration: iteration as u64,
+            model: model.name.clone(),
+            promise_missing: !result.has_promise && signal.is_none(),
+            failed_criteria,
+            failed_verifiers: failed_verifiers
+                .iter()
+                .map(|r| (r.name.clone(), r.output.clone()))
+                .collect(),
+            safety: safety_feedback,
+            diff_summary: diff_summary(&work_dir),
+            ..IterationFeedback::default()
+        });
+
+        // The next pass asks the model's question first
+        if let Some(PromiseSignal::NeedsInput { question }) = &signal {
+            state.pending_question = Some(PendingQuestion {
+                iteration: iteration as u64,
+                question: question.clone(),
+                asked_at: clock.now(),
             });
         }
 
         // Save state (iteration is u64 now, safe conversion)
         state.iteration = iteration as u64;
-        let state_clone = state.clone();
-        let path = state_path.clone();
-        let _ = tokio::task::spawn_blocking(move || state_clone.save(&path)).await;
+        state.completed_iterations = iteration as u64;
+        save_run_state(&mut state, &state_path, &run_dir, &mut sink, &*events).await;
+
+        // Stop once the last few iterations have gone nowhere, or the model
+        // says it can't go on
+        let blocked = match &signal {
+            Some(PromiseSignal::Blocked { reason }) => Some(reason.as_deref()),
+            _ => None,
+        };
+        if blocked.is_some() || config.stuck_window > 0 || config.max_noop_iterations > 0 {
+            snapshot.tree_hash = GitSafety::new(&work_dir).worktree_tree_hash().ok();
+            stuck_detector.record(snapshot);
+            if no_op {
+                events.emit(RunEvent::NoOp {
+                    iteration,
+                    model: model.name.clone(),
+                    consecutive: stuck_detector.consecutive_no_ops(),
+                });
+            }
+            let diagnosis = match blocked {
+                Some(reason) => Some(stuck_detector.blocked(reason)),
+                None => stuck_detector.check(),
+            };
+            if let Some(mut diagnosis) = diagnosis {
+                // Ask a second opinion for ways out before stopping
+                if let Some(helper) = select_verifier_model(&config, &cooldowns, &model.name) {
+                    events.emit(RunEvent::Status {
+                        message: format!("Asking {} for recovery suggestions", helper.name),
+                    });
+                    let suggestions = tokio::select! {
+                        _ = cancel_rx.recv() => {
+                            events.emit(RunEvent::Cancelled { iteration });
+                            state.cancel();
+                            break;
+                        }
+                        result = suggest_recovery(helper, &diagnosis, &failing_criteria, &work_dir) => result
+                    };
+                    match suggestions {
+                        Ok(suggestions) => diagnosis.suggestions = suggestions,
+                        Err(e) => {
+                            events.emit(RunEvent::Status {
+                                message: format!("No recovery suggestions: {e}"),
+                            });
+                        }
+                    }
+                }
+                tracing::warn!(parent: &iteration_span, "run is stuck: {}", diagnosis.signals.join(", "));
+                events.emit(RunEvent::Stuck {
+                    iteration,
+                    diagnosis,
+                });
+                state.fail();
+                break;
+            }
+        }
     }
 
     // Final state save (awaited to ensure completion before function returns)
+    save_run_state(&mut state, &state_path, &run_dir, &mut sink, &*events).await;
+    if config.compress_logs {
+        compress_run_logs(&run_dir).await;
+    }
+    tracing::info!(
+        status = ?state.status,
+        iterations = state.completed_iterations,
+        "run finished"
+    );
+
+    reload_cooldowns(&mut cooldowns, &cooldowns_path).await;
+    let saved = save_file(cooldowns, cooldowns_path, Cooldowns::save).await;
+    record_save(&mut sink, &*events, "cooldowns", saved);
+}
+
+/// Gzip the run's finished logs off the async runtime. Logs that can't be
+/// compressed are left as they are.
+async fn compress_run_logs(run_dir: &Path) {
+    let run_dir = run_dir.to_path_buf();
+    if let Ok(Err(e)) = tokio::task::spawn_blocking(move || compress_logs(&run_dir)).await {
+        tracing::warn!("Failed to compress logs: {e}");
+    }
+}
+
+/// Save `value` off the async runtime, retrying a failed write.
+async fn save_file<T, E>(
+    value: T,
+    path: PathBuf,
+    save: fn(&T, &Path) -> Result<(), E>,
+) -> Result<(), String>
+where
+    T: Send + 'static,
+    E: std::fmt::Display + Send + 'static,
+{
+    tokio::task::spawn_blocking(move || {
+        sink::retry(|| save(&value, &path)).map_err(|e| format!("{}: {e}", path.display()))
+    })
+    .await
+    .unwrap_or_else(|e| Err(e.to_string()))
+}
+
+/// Write an iteration's changelog entry, telling the user the first time
+/// the changelog can't be saved.
+fn record_changelog(
+    sink: &mut PersistenceSink,
+    events: &dyn EventSink,
+    entry: &ChangelogEntry<'_>,
+) {
+    let saved = sink::retry(|| write_changelog_entry(entry));
+    record_save(sink, events, "changelog entry", saved);
+}
+
+/// The changelog's record of a model call that was cut short, with
+/// `stderr` saying why.
+fn unfinished_invocation(model: &str, elapsed: Duration, stderr: String) -> InvocationResult {
+    InvocationResult {
+        model: model.to_string(),
+        exit_code: None,
+        stdout: String::new(),
+        stderr,
+        rate_limited: false,
+        duration_ms: u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX),
+        has_promise: false,
+        usage: None,
+    }
+}
+
+/// Record a save in `sink`, telling the user the first time `what` fails.
+/// The run carries on either way.
+fn record_save(
+    sink: &mut PersistenceSink,
+    events: &dyn EventSink,
+    what: &str,
+    result: Result<(), String>,
+) {
+    if let Some(failure) = sink.record(what, result) {
+        events.emit(RunEvent::SaveFailed {
+            what: failure.what,
+            error: failure.error,
+        });
+    }
+}
+
+/// Load a state file off the async runtime. A corrupt file is reported as
+/// a status event rather than silently replaced by defaults.
+async fn load_state_file<T, E>(
+    path: PathBuf,
+    load: fn(&Path) -> Result<T, E>,
+    events: &dyn EventSink,
+) -> T
+where
+    T: Default + Send + 'static,
+    E: std::fmt::Display + 'static,
+{
+    let (value, corruption) = tokio::task::spawn_blocking(move || load_or_recover(&path, load))
+        .await
+        .unwrap_or_default();
+    if let Some(corruption) = corruption {
+        events.emit(RunEvent::Status {
+            message: format!("Warning: {corruption}"),
+        });
+    }
+    value
+}
+
+/// Re-read `.ralf/cooldowns.json`, which `ralf cooldown` may have edited.
+async fn reload_cooldowns(cooldowns: &mut Cooldowns, path: &Path) {
+    let path = path.to_path_buf();
+    if let Ok(Ok(on_disk)) = tokio::task::spawn_blocking(move || Cooldowns::load(&path)).await {
+        cooldowns.entries = on_disk.entries;
+    }
+}
+
+/// Re-read the disabled models, which `ralf model` may have changed.
+async fn reload_disabled_models(config: &mut Config, repo_path: &Path) {
+    let repo_path = repo_path.to_path_buf();
+    if let Ok(Ok(layered)) =
+        tokio::task::spawn_blocking(move || Config::load_layered(&repo_path)).await
+    {
+        config.disabled_models = layered.config.disabled_models;
+    }
+}
+
+/// Persist run state to `.ralf/state.json` and the run's own directory.
+///
+/// The per-run copy is what `--resume` reads, so it survives later runs
+/// overwriting the global state file. A run that failed to save anything
+/// is marked degraded.
+async fn save_run_state(
+    state: &mut RunState,
+    state_path: &Path,
+    run_dir: &Path,
+    sink: &mut PersistenceSink,
+    events: &dyn EventSink,
+) {
+    state.degraded |= sink.is_degraded();
     let state_clone = state.clone();
-    let path = state_path.clone();
-    let _ = tokio::task::spawn_blocking(move || state_clone.save(&path)).await;
+    let path = state_path.to_path_buf();
+    let run_state_path = run_dir.join(STATE_FILE);
+    let saved = tokio::task::spawn_blocking(move || {
+        // Both copies are attempted, whichever fails
+        let run_copy = sink::retry(|| state_clone.save(&run_state_path));
+        let global = sink::retry(|| state_clone.save(&path));
+        run_copy.and(global)
+    })
+    .await
+    .unwrap_or_else(|e| Err(e.to_string()));
+    record_save(sink, events, "run state", saved);
+    state.degraded |= sink.is_degraded();
+}
+
+/// Load the persisted state of an interrupted run so it can be resumed.
+///
+/// Looks for `.ralf/runs/<run_id>/state.json`, falling back to
+/// `.ralf/state.json` when it belongs to the same run. Refuses to resume
+/// completed runs, and runs whose prompt hash no longer matches unless
+/// `force` is set.
+pub fn load_resumable_state(
+    ralf_dir: &Path,
+    run_id: &str,
+    prompt_hash: &str,
+    force: bool,
+) -> Result<RunState, RunnerError> {
+    let dirs = RalfDirs::new(ralf_dir);
+    let run_dir = dirs.run(run_id);
+    if !run_dir.is_dir() {
+        return Err(RunnerError::RunNotFound(run_id.to_string()));
+    }
+
+    let load = |path: &Path| {
+        RunState::load(path).map_err(|e| RunnerError::Config(format!("{}: {e}", path.display())))
+    };
+    let run_state_path = run_dir.join(STATE_FILE);
+    let state = if run_state_path.exists() {
+        load(&run_state_path)?
+    } else {
+        let global = load(&dirs.state())?;
+        if global.run_id.as_deref() != Some(run_id) {
+            return Err(RunnerError::RunNotFound(run_id.to_string()));
+        }
+        global
+    };
+
+    if state.status == RunStatus::Completed {
+        return Err(RunnerError::NotResumable(format!(
+            "run {run_id} already completed"
+        )));
+    }
+
+    if !force {
+        if let Some(expected) = &state.prompt_hash {
+            if expected != prompt_hash {
+                return Err(RunnerError::PromptChanged(run_id.to_string()));
+            }
+        }
+    }
 
-    let cooldowns_clone = cooldowns.clone();
-    let path = cooldowns_path.clone();
-    let _ = tokio::task::spawn_blocking(move || cooldowns_clone.save(&path)).await;
+    Ok(state)
 }
 
 /// Result of a model invocation.
@@ -409,7 +1727,9 @@ pub struct InvocationResult {
     /// Exit code.
     pub exit_code: Option<i32>,
 
-    /// Stdout output.
+    /// The model's response, as its adapter reads it from the process
+    /// output (see [`ModelAdapter::parse_output`]). The log keeps the raw
+    /// stdout.
     pub stdout: String,
 
     /// Stderr output.
@@ -423,6 +1743,9 @@ pub struct InvocationResult {
 
     /// Whether the output contains the completion promise.
     pub has_promise: bool,
+
+    /// Token/cost usage reported by the model CLI, if any.
+    pub usage: Option<Usage>,
 }
 
 /// Result of running a verifier.
@@ -442,83 +1765,532 @@ pub struct VerifierResult {
 
     /// Duration in milliseconds.
     pub duration_ms: u64,
+
+    /// Whether the verifier was skipped rather than run.
+    pub skipped: bool,
+}
+
+impl VerifierResult {
+    /// Result for a verifier that was not run.
+    pub fn skipped(name: &str, reason: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            passed: false,
+            exit_code: None,
+            output: format!("Skipped: {reason}"),
+            duration_ms: 0,
+            skipped: true,
+        }
+    }
+}
+
+/// Why `verifier` should be skipped given the results so far, if it should.
+///
+/// A verifier is skipped when one of its `depends_on` did not pass, or when
+/// an earlier `fail_fast` verifier failed.
+pub fn verifier_skip_reason(
+    verifier: &VerifierConfig,
+    verifiers: &[VerifierConfig],
+    results: &[VerifierResult],
+) -> Option<String> {
+    let fail_fast = results.iter().find(|r| {
+        !r.passed && !r.skipped && verifiers.iter().any(|v| v.name == r.name && v.fail_fast)
+    });
+    if let Some(failed) = fail_fast {
+        return Some(format!("'{}' failed (fail-fast)", failed.name));
+    }
+
+    verifier
+        .depends_on
+        .iter()
+        .find(|dep| results.iter().any(|r| &r.name == *dep && !r.passed))
+        .map(|dep| format!("dependency '{dep}' did not pass"))
 }
 
-/// Invoke a model with the given prompt.
+/// Invoke a model with the given prompt, running it in `work_dir`.
 pub async fn invoke_model(
     model: &ModelConfig,
     prompt: &str,
     run_dir: &Path,
+    work_dir: &Path,
 ) -> Result<InvocationResult, RunnerError> {
-    let start = std::time::Instant::now();
+    invoke_model_within(model, prompt, run_dir, work_dir, Duration::MAX).await
+}
 
-    // Build command
-    let mut cmd = Command::new(&model.command_argv[0]);
-    for arg in &model.command_argv[1..] {
-        cmd.arg(arg);
+/// Like [`invoke_model`], but stop the model after `limit` if that comes
+/// before its own timeout. A timed-out model's partial output is still
+/// written to its log.
+#[tracing::instrument(name = "invocation", skip_all, fields(model = %model.name))]
+pub async fn invoke_model_within(
+    model: &ModelConfig,
+    prompt: &str,
+    run_dir: &Path,
+    work_dir: &Path,
+    limit: Duration,
+) -> Result<InvocationResult, RunnerError> {
+    let start = std::time::Instant::now();
+    let adapter = adapter_for(&model.name);
+    let timeout_duration = Duration::from_secs(model.timeout_seconds).min(limit);
+    let log_path = run_dir.join(format!("{}.log", model.name));
+
+    // The mock model answers from its script without spawning anything
+    if let Some(response) = adapter.mock_response(work_dir, Some(run_dir)) {
+        return invoke_mock(
+            model,
+            adapter.as_ref(),
+            response,
+            &log_path,
+            timeout_duration,
+        )
+        .await;
     }
 
-    cmd.stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .kill_on_drop(true);
-
-    let mut child = cmd.spawn().map_err(RunnerError::Spawn)?;
-
-    // Write prompt to stdin
+    // Build command
+    let spec = adapter.command(model, prompt);
+    let mut cmd = spec.to_tokio_command();
+    cmd.current_dir(work_dir);
+
+    let (mut child, guard) = spawn_process(&mut cmd)?;
+    tracing::debug!(
+        program = %spec.program,
+        pid = child.id(),
+        timeout_secs = timeout_duration.as_secs(),
+        "model started"
+    );
+
+    // Write prompt to stdin (dropping stdin closes it and signals EOF)
     if let Some(mut stdin) = child.stdin.take() {
-        stdin
-            .write_all(prompt.as_bytes())
-            .await
-            .map_err(RunnerError::Io)?;
-        // Drop stdin to close it and signal EOF
+        if let Some(input) = &spec.stdin {
+            stdin
+                .write_all(input.as_bytes())
+                .await
+                .map_err(RunnerError::Io)?;
+        }
         drop(stdin);
     }
 
     // Wait with timeout
-    let timeout_duration = Duration::from_secs(model.timeout_seconds);
-    let result = timeout(timeout_duration, child.wait_with_output()).await;
+    let output = wait_with_limit(child, guard, timeout_duration)
+        .await
+        .map_err(RunnerError::Io)?;
 
     #[allow(clippy::cast_possible_truncation)]
     let duration_ms = start.elapsed().as_millis() as u64;
 
-    match result {
-        Ok(Ok(output)) => {
+    match output.status {
+        Some(status) => {
             let stdout = String::from_utf8_lossy(&output.stdout).to_string();
             let stderr = String::from_utf8_lossy(&output.stderr).to_string();
 
             // Check for rate limiting
             let combined = format!("{stdout}\n{stderr}");
-            let rate_limited = check_rate_limit(&combined, &model.rate_limit_patterns);
+            let rate_limited = adapter.is_rate_limited(&combined, &model.rate_limit_patterns);
+            let usage = adapter.parse_usage(&stdout, &stderr);
 
             // Write log file (async)
-            let log_path = run_dir.join(format!("{}.log", model.name));
             write_log(&log_path, &stdout, &stderr).await?;
+            let stdout = adapter.parse_output(&stdout, &stderr);
+            tracing::info!(
+                exit_code = status.code(),
+                duration_ms,
+                rate_limited,
+                "model finished"
+            );
 
             Ok(InvocationResult {
                 model: model.name.clone(),
-                exit_code: output.status.code(),
+                exit_code: status.code(),
                 stdout,
                 stderr,
                 rate_limited,
                 duration_ms,
                 has_promise: false, // Set by caller after checking
+                usage,
             })
         }
-        Ok(Err(e)) => Err(RunnerError::Io(e)),
-        Err(_) => {
-            // Timeout - process was killed by kill_on_drop
+        None => {
+            // Timeout - keep whatever the model wrote before it was stopped
+            write_log(
+                &log_path,
+                &String::from_utf8_lossy(&output.stdout),
+                &String::from_utf8_lossy(&output.stderr),
+            )
+            .await?;
+            tracing::warn!(duration_ms, "model timed out");
             Err(RunnerError::Timeout(model.name.clone()))
         }
     }
 }
 
-/// Check if output contains rate limit patterns.
-fn check_rate_limit(output: &str, patterns: &[String]) -> bool {
-    let lower = output.to_lowercase();
-    patterns.iter().any(|p| lower.contains(&p.to_lowercase()))
+/// Answer with the mock model's scripted `response` after its delay, timing
+/// out like a real model if the delay is longer than `timeout_duration`.
+async fn invoke_mock(
+    model: &ModelConfig,
+    adapter: &dyn ModelAdapter,
+    response: MockResponse,
+    log_path: &Path,
+    timeout_duration: Duration,
+) -> Result<InvocationResult, RunnerError> {
+    if response.delay > timeout_duration {
+        tokio::time::sleep(timeout_duration).await;
+        write_log(log_path, "", "").await?;
+        return Err(RunnerError::Timeout(model.name.clone()));
+    }
+    tokio::time::sleep(response.delay).await;
+
+    let MockResponse {
+        stdout,
+        stderr,
+        exit_code,
+        delay,
+    } = response;
+    let combined = format!("{stdout}\n{stderr}");
+    let rate_limited = adapter.is_rate_limited(&combined, &model.rate_limit_patterns);
+    let usage = adapter.parse_usage(&stdout, &stderr);
+    write_log(log_path, &stdout, &stderr).await?;
+    let stdout = adapter.parse_output(&stdout, &stderr);
+
+    #[allow(clippy::cast_possible_truncation)]
+    let duration_ms = delay.as_millis() as u64;
+    Ok(InvocationResult {
+        model: model.name.clone(),
+        exit_code: Some(exit_code),
+        stdout,
+        stderr,
+        rate_limited,
+        duration_ms,
+        has_promise: false, // Set by caller after checking
+        usage,
+    })
+}
+
+/// Environment variable set on every process ralf spawns (and so inherited
+/// by their descendants), holding the spawning ralf process's PID. Lets
+/// [`find_leftover_processes`] recognize orphans of earlier sessions.
+pub const RALF_PARENT_ENV: &str = "RALF_PARENT_PID";
+
+/// Process groups of model and verifier processes still running.
+static LIVE_PROCESS_GROUPS: Mutex<Vec<u32>> = Mutex::new(Vec::new());
+
+/// Kills a spawned process's whole tree when dropped, unless disarmed.
+/// Cancelling an invocation drops its future, and with it the guard, so
+/// nothing the process started is left running.
+pub(crate) struct ProcessTreeGuard {
+    pid: Option<u32>,
+}
+
+impl ProcessTreeGuard {
+    fn new(pid: Option<u32>) -> Self {
+        if let Some(pid) = pid {
+            if let Ok(mut groups) = LIVE_PROCESS_GROUPS.lock() {
+                groups.push(pid);
+            }
+        }
+        Self { pid }
+    }
+
+    /// Stop tracking the process; it exited or was already stopped.
+    fn disarm(&mut self) {
+        if let Some(pid) = self.pid.take() {
+            if let Ok(mut groups) = LIVE_PROCESS_GROUPS.lock() {
+                groups.retain(|p| *p != pid);
+            }
+            #[cfg(windows)]
+            platform::job::release(pid);
+        }
+    }
+}
+
+impl Drop for ProcessTreeGuard {
+    fn drop(&mut self) {
+        if let Some(pid) = self.pid {
+            kill_process_tree(pid, true);
+            self.disarm();
+        }
+    }
+}
+
+/// Spawn a model or verifier process as the leader of its own process
+/// group (in its own Job Object on Windows), marked with
+/// [`RALF_PARENT_ENV`].
+pub(crate) fn spawn_process(cmd: &mut Command) -> Result<(Child, ProcessTreeGuard), RunnerError> {
+    cmd.env(RALF_PARENT_ENV, std::process::id().to_string())
+        .kill_on_drop(true);
+    #[cfg(unix)]
+    cmd.process_group(0);
+
+    let child = cmd.spawn().map_err(RunnerError::Spawn)?;
+    #[cfg(windows)]
+    if let (Some(pid), Some(handle)) = (child.id(), child.raw_handle()) {
+        platform::job::attach(pid, handle);
+    }
+    let guard = ProcessTreeGuard::new(child.id());
+    Ok((child, guard))
+}
+
+/// Signal every process in the process group led by `pid`: SIGTERM, or
+/// SIGKILL when `force` is set. Processes from [`invoke_model`] and
+/// [`run_verifier`] lead their own group, so this reaches everything they
+/// started. Returns whether the signal was sent.
+///
+/// Without process groups (Windows), only a forced kill is supported. It
+/// terminates the process's Job Object, falling back to `taskkill /T`.
+pub fn kill_process_tree(pid: u32, force: bool) -> bool {
+    #[cfg(unix)]
+    {
+        use nix::sys::signal::{killpg, Signal};
+        use nix::unistd::Pid;
+
+        let Ok(leader) = i32::try_from(pid) else {
+            return false;
+        };
+        let signal = if force {
+            Signal::SIGKILL
+        } else {
+            Signal::SIGTERM
+        };
+        killpg(Pid::from_raw(leader), signal).is_ok()
+    }
+    #[cfg(not(unix))]
+    {
+        #[cfg(windows)]
+        if force && platform::job::terminate(pid) {
+            return true;
+        }
+        force
+            && std::process::Command::new("taskkill")
+                .args(["/T", "/F", "/PID", &pid.to_string()])
+                .output()
+                .is_ok_and(|o| o.status.success())
+    }
+}
+
+/// Force-kill every model and verifier process tree still running. For
+/// exiting on a signal, when futures don't get the chance to drop.
+pub fn kill_all_process_trees() {
+    let groups = LIVE_PROCESS_GROUPS
+        .lock()
+        .map(|groups| groups.clone())
+        .unwrap_or_default();
+    for pid in groups {
+        kill_process_tree(pid, true);
+    }
+}
+
+/// A process left running by an earlier ralf session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeftoverProcess {
+    /// Process ID.
+    pub pid: u32,
+    /// Command name (e.g. "node").
+    pub name: String,
+    /// PID of the ralf process that spawned it (no longer running).
+    pub parent_pid: u32,
+}
+
+/// Processes spawned by ralf sessions that have since exited. Uses
+/// `/proc`, so only finds anything on Linux.
+pub fn find_leftover_processes() -> Vec<LeftoverProcess> {
+    find_leftover_processes_in(Path::new("/proc"))
+}
+
+fn find_leftover_processes_in(proc_dir: &Path) -> Vec<LeftoverProcess> {
+    let Ok(entries) = std::fs::read_dir(proc_dir) else {
+        return Vec::new();
+    };
+    let marker = format!("{RALF_PARENT_ENV}=");
+
+    let mut leftovers: Vec<LeftoverProcess> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let pid: u32 = entry.file_name().to_str()?.parse().ok()?;
+            let environ = std::fs::read(entry.path().join("environ")).ok()?;
+            let parent_pid: u32 = environ
+                .split(|b| *b == 0)
+                .find_map(|var| std::str::from_utf8(var).ok()?.strip_prefix(&marker))?
+                .parse()
+                .ok()?;
+            // Children of a live ralf (this one or another) are still in use
+            if proc_dir.join(parent_pid.to_string()).exists() {
+                return None;
+            }
+            let name = std::fs::read_to_string(entry.path().join("comm"))
+                .map(|comm| comm.trim().to_string())
+                .unwrap_or_default();
+            Some(LeftoverProcess {
+                pid,
+                name,
+                parent_pid,
+            })
+        })
+        .collect();
+    leftovers.sort_by_key(|p| p.pid);
+    leftovers
+}
+
+/// Output of a process run under a time limit.
+pub(crate) struct LimitedOutput {
+    /// Exit status, or `None` if the process was stopped at the limit.
+    pub(crate) status: Option<ExitStatus>,
+    pub(crate) stdout: Vec<u8>,
+    pub(crate) stderr: Vec<u8>,
+}
+
+/// Wait up to `limit` for `child` to exit, collecting its output. A process
+/// still running at the limit is [terminated](terminate) along with its
+/// descendants; what it wrote before exiting is kept.
+pub(crate) async fn wait_with_limit(
+    child: Child,
+    guard: ProcessTreeGuard,
+    limit: Duration,
+) -> std::io::Result<LimitedOutput> {
+    wait_with_limit_streaming(child, guard, limit, None).await
+}
+
+/// [`wait_with_limit`], also sending stdout to `partial` as it is written.
+pub(crate) async fn wait_with_limit_streaming(
+    mut child: Child,
+    mut guard: ProcessTreeGuard,
+    limit: Duration,
+    partial: Option<&mpsc::UnboundedSender<String>>,
+) -> std::io::Result<LimitedOutput> {
+    let mut stdout = Vec::new();
+    let mut stderr = Vec::new();
+
+    let status = {
+        let read_output = read_output(
+            child.stdout.take(),
+            child.stderr.take(),
+            &mut stdout,
+            &mut stderr,
+            partial,
+        );
+        tokio::pin!(read_output);
+        let deadline = tokio::time::sleep(limit);
+        tokio::pin!(deadline);
+
+        let mut output_done = false;
+        let status = loop {
+            tokio::select! {
+                status = child.wait() => break Some(status?),
+                () = &mut read_output, if !output_done => output_done = true,
+                () = &mut deadline => break None,
+            }
+        };
+
+        if status.is_some() {
+            if !output_done {
+                read_output.await;
+            }
+        } else {
+            // Keep reading while the process shuts down, so a full pipe
+            // doesn't stop it from exiting
+            let drain = async {
+                if !output_done {
+                    let _ = timeout(TERMINATE_GRACE + OUTPUT_DRAIN, &mut read_output).await;
+                }
+            };
+            tokio::join!(terminate(&mut child, TERMINATE_GRACE), drain);
+        }
+        status
+    };
+    guard.disarm();
+
+    Ok(LimitedOutput {
+        status,
+        stdout,
+        stderr,
+    })
+}
+
+/// Read a child's stdout and stderr to the end, sending stdout to
+/// `partial` as it arrives.
+async fn read_output(
+    stdout: Option<ChildStdout>,
+    stderr: Option<ChildStderr>,
+    stdout_buf: &mut Vec<u8>,
+    stderr_buf: &mut Vec<u8>,
+    partial: Option<&mpsc::UnboundedSender<String>>,
+) {
+    let read_stdout = async {
+        match (stdout, partial) {
+            (Some(mut pipe), Some(partial)) => stream_to_end(&mut pipe, stdout_buf, partial).await,
+            (Some(mut pipe), None) => {
+                let _ = pipe.read_to_end(stdout_buf).await;
+            }
+            (None, _) => {}
+        }
+    };
+    let read_stderr = async {
+        if let Some(mut pipe) = stderr {
+            let _ = pipe.read_to_end(stderr_buf).await;
+        }
+    };
+    tokio::join!(read_stdout, read_stderr);
+}
+
+/// Read `pipe` to the end into `buf`, sending the text to `partial` as it
+/// arrives. A character split between reads is sent once it is complete.
+async fn stream_to_end(
+    pipe: &mut ChildStdout,
+    buf: &mut Vec<u8>,
+    partial: &mpsc::UnboundedSender<String>,
+) {
+    let mut chunk = [0; 4096];
+    let mut sent = 0;
+    loop {
+        match pipe.read(&mut chunk).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => buf.extend_from_slice(&chunk[..n]),
+        }
+        let pending = &buf[sent..];
+        let complete = match std::str::from_utf8(pending) {
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            _ => pending.len(),
+        };
+        if complete > 0 {
+            let _ = partial.send(String::from_utf8_lossy(&pending[..complete]).into_owned());
+            sent += complete;
+        }
+    }
+    if sent < buf.len() {
+        let _ = partial.send(String::from_utf8_lossy(&buf[sent..]).into_owned());
+    }
+}
+
+/// Stop a process tree gracefully: SIGTERM first so the processes can
+/// flush their output, then SIGKILL for whatever is still running once the
+/// leader exits or `grace` runs out.
+async fn terminate(child: &mut Child, grace: Duration) {
+    if let Some(pid) = child.id() {
+        if kill_process_tree(pid, false) {
+            let _ = timeout(grace, child.wait()).await;
+        }
+        kill_process_tree(pid, true);
+    }
+    let _ = child.kill().await;
+}
+
+/// Sleep until `deadline`, or forever if there is none.
+async fn sleep_until_deadline(deadline: Option<tokio::time::Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
 }
 
+/// How often a run waiting on cooldowns checks for `ralf cooldown` and
+/// `ralf model` changes.
+pub const STEERING_POLL: Duration = Duration::from_secs(10);
+
+/// How long a timed-out process gets to exit after SIGTERM before it is
+/// killed.
+const TERMINATE_GRACE: Duration = Duration::from_secs(5);
+
+/// How long to keep reading a stopped process's output after the grace
+/// period, in case descendants still hold its pipes open.
+const OUTPUT_DRAIN: Duration = Duration::from_secs(2);
+
 /// Write log file with stdout and stderr.
 async fn write_log(path: &Path, stdout: &str, stderr: &str) -> Result<(), RunnerError> {
     if let Some(parent) = path.parent() {
@@ -536,69 +2308,245 @@ async fn write_log(path: &Path, stdout: &str, stderr: &str) -> Result<(), Runner
     Ok(())
 }
 
-/// Run a verifier.
+/// Run a verifier in `work_dir`.
 pub async fn run_verifier(
     verifier: &VerifierConfig,
     run_dir: &Path,
+    work_dir: &Path,
+) -> Result<VerifierResult, RunnerError> {
+    run_verifier_within(verifier, run_dir, work_dir, Duration::MAX).await
+}
+
+/// Like [`run_verifier`], but stop the verifier after `limit` if that comes
+/// before its own timeout.
+#[tracing::instrument(name = "verifier", skip_all, fields(verifier = %verifier.name))]
+pub async fn run_verifier_within(
+    verifier: &VerifierConfig,
+    run_dir: &Path,
+    work_dir: &Path,
+    limit: Duration,
 ) -> Result<VerifierResult, RunnerError> {
     let start = std::time::Instant::now();
 
-    let mut cmd = Command::new(&verifier.command_argv[0]);
-    for arg in &verifier.command_argv[1..] {
-        cmd.arg(arg);
+    let mut cmd = embed::command(&verifier.command_argv[0], &verifier.command_argv[1..]);
+    cmd.current_dir(work_dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    let (child, guard) = spawn_process(&mut cmd)?;
+    let timeout_duration = Duration::from_secs(verifier.timeout_seconds).min(limit);
+    tracing::debug!(
+        pid = child.id(),
+        timeout_secs = timeout_duration.as_secs(),
+        "verifier started"
+    );
+    let output = wait_with_limit(child, guard, timeout_duration)
+        .await
+        .map_err(RunnerError::Io)?;
+
+    #[allow(clippy::cast_possible_truncation)]
+    let duration_ms = start.elapsed().as_millis() as u64;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let combined = format!("{stdout}\n{stderr}");
+
+    // Write verifier log (async)
+    let log_path = run_dir.join(format!("{}.log", verifier.name));
+    write_log(&log_path, &stdout, &stderr).await?;
+
+    match output.status {
+        Some(status) => {
+            tracing::info!(exit_code = status.code(), duration_ms, "verifier finished");
+            Ok(VerifierResult {
+                name: verifier.name.clone(),
+                passed: status.success(),
+                exit_code: status.code(),
+                output: combined,
+                duration_ms,
+                skipped: false,
+            })
+        }
+        None => {
+            tracing::warn!(duration_ms, "verifier timed out");
+            Err(RunnerError::Timeout(verifier.name.clone()))
+        }
     }
+}
 
-    cmd.stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .kill_on_drop(true);
+/// Build the commit message for a successful iteration.
+///
+/// The subject is `ralf(<model>): iter <n> (run <run_id>)`; the body is a
+/// summary of the criteria or verifiers that passed.
+pub fn iteration_commit_message(
+    run_id: &str,
+    iteration: u64,
+    model: &str,
+    summary: &str,
+) -> String {
+    format!("ralf({model}): iter {iteration} (run {run_id})\n\n{summary}\n")
+}
 
-    let timeout_duration = Duration::from_secs(verifier.timeout_seconds);
-    let result = timeout(timeout_duration, cmd.output()).await;
+/// Commit the work tree after a successful iteration and emit
+/// `IterationCommitted`, returning the commit. Failures are reported as a
+/// status message.
+async fn commit_iteration(
+    work_dir: &Path,
+    run_id: &str,
+    iteration: usize,
+    model: &str,
+    summary: &str,
+    events: &dyn EventSink,
+) -> Option<String> {
+    let message = iteration_commit_message(run_id, iteration as u64, model, summary);
+    let git = GitSafety::new(work_dir);
+    let result = tokio::task::spawn_blocking(move || git.commit_all(&message))
+        .await
+        .unwrap_or_else(|e| Err(GitError::CommandFailed(e.to_string())));
 
-    #[allow(clippy::cast_possible_truncation)]
-    let duration_ms = start.elapsed().as_millis() as u64;
+    match result {
+        Ok(Some(commit_sha)) => {
+            events.emit(RunEvent::IterationCommitted {
+                iteration,
+                commit_sha: commit_sha.clone(),
+            });
+            Some(commit_sha)
+        }
+        Ok(None) => None,
+        Err(e) => {
+            events.emit(RunEvent::Status {
+                message: format!("Failed to commit iteration {iteration}: {e}"),
+            });
+            None
+        }
+    }
+}
 
-    match result {
-        Ok(Ok(output)) => {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            let combined = format!("{stdout}\n{stderr}");
+/// The commit message body for an iteration whose verifiers passed.
+fn verifier_summary(results: &[VerifierResult]) -> String {
+    if results.is_empty() {
+        return "No verifiers configured".to_string();
+    }
+    let passed: Vec<&str> = results.iter().map(|r| r.name.as_str()).collect();
+    format!("Verifiers passed: {}", passed.join(", "))
+}
 
-            // Write verifier log (async)
-            let log_path = run_dir.join(format!("{}.log", verifier.name));
-            write_log(&log_path, &stdout, &stderr).await?;
+/// The directory a run works in: `target` under `repo_path` (or as is, if
+/// absolute), or `repo_path` itself without one.
+pub fn target_root(repo_path: &Path, target: Option<&Path>) -> PathBuf {
+    match target {
+        Some(target) if !target.as_os_str().is_empty() => repo_path.join(target),
+        _ => repo_path.to_path_buf(),
+    }
+}
 
-            Ok(VerifierResult {
-                name: verifier.name.clone(),
-                passed: output.status.success(),
-                exit_code: output.status.code(),
-                output: combined,
-                duration_ms,
-            })
+/// Set up the directory a run works in, according to `config.isolation`.
+///
+/// Without isolation that's `target` itself. When isolated, the run gets a
+/// worktree of `target`'s repository under `repo_path`'s `.ralf/worktrees/`
+/// (reused when resuming) and works in the same directory inside it.
+/// Returns the directory and the worktree, if any.
+pub fn prepare_work_dir(
+    config: &Config,
+    repo_path: &Path,
+    target: &Path,
+    run_id: &str,
+) -> Result<(PathBuf, Option<RunWorktree>), RunnerError> {
+    if !target.is_dir() {
+        return Err(RunnerError::Config(format!(
+            "target directory {} not found",
+            target.display()
+        )));
+    }
+    match config.isolation {
+        Isolation::None => Ok((target.to_path_buf(), None)),
+        Isolation::Worktree => {
+            let (root, prefix) = GitSafety::new(target).root_and_prefix()?;
+            let worktrees_dir = RalfDirs::for_repo(repo_path).worktrees();
+            let worktree = GitSafety::new(root).ensure_run_worktree(&worktrees_dir, run_id)?;
+            Ok((worktree.path.join(prefix), Some(worktree)))
         }
-        Ok(Err(e)) => Err(RunnerError::Io(e)),
-        Err(_) => Err(RunnerError::Timeout(verifier.name.clone())),
     }
 }
 
+/// Select the model to verify work done by `implementer`.
+///
+/// Candidates are the models not disabled or in cooldown, ordered by the
+/// verifier role's preferences (see [`Config::role_models`]), then
+/// `model_priority`, then config order. The
+/// first one that isn't the implementer wins; the implementer is used only
+/// if nothing else is available. Unlike [`select_model`], this doesn't
+/// advance the round-robin rotation.
+pub fn select_verifier_model<'a>(
+    config: &'a Config,
+    cooldowns: &Cooldowns,
+    implementer: &str,
+) -> Option<&'a ModelConfig> {
+    let preferred = config.role_models(ModelRole::Verifier);
+    let rank = |model: &ModelConfig| {
+        (
+            preferred
+                .iter()
+                .position(|n| *n == model.name)
+                .unwrap_or(usize::MAX),
+            config
+                .model_priority
+                .iter()
+                .position(|n| *n == model.name)
+                .unwrap_or(usize::MAX),
+        )
+    };
+
+    let mut available: Vec<&ModelConfig> = config
+        .models
+        .iter()
+        .filter(|m| !config.is_disabled(&m.name) && !cooldowns.is_cooling(&m.name))
+        .collect();
+    // Stable sort keeps config order among unranked models
+    available.sort_by_key(|m| rank(m));
+
+    available
+        .iter()
+        .find(|m| m.name != implementer)
+        .or_else(|| available.first())
+        .copied()
+}
+
 /// Select the next model to use based on the selection strategy.
 ///
-/// For round-robin selection, this advances the index for the next call.
+/// Disabled models and models in cooldown are skipped. When the implementer
+/// role lists models and any of them is available, only those are
+/// considered, and their order replaces `model_priority`. For round-robin
+/// selection, this advances the index for the next call.
+/// Adaptive selection ranks models by their health in `model_stats`.
 pub fn select_model<'a>(
     config: &'a Config,
     cooldowns: &Cooldowns,
+    model_stats: &ModelStats,
     state: &mut RunState,
 ) -> Option<&'a ModelConfig> {
-    let available: Vec<&ModelConfig> = config
+    let mut available: Vec<&ModelConfig> = config
         .models
         .iter()
-        .filter(|m| !cooldowns.is_cooling(&m.name))
+        .filter(|m| !config.is_disabled(&m.name) && !cooldowns.is_cooling(&m.name))
         .collect();
 
     if available.is_empty() {
         return None;
     }
 
+    let mut priority: Vec<&str> = config.model_priority.iter().map(String::as_str).collect();
+    let preferred = config.role_models(ModelRole::Implementer);
+    let listed: Vec<&ModelConfig> = preferred
+        .iter()
+        .filter_map(|name| available.iter().find(|m| m.name == *name).copied())
+        .collect();
+    if !listed.is_empty() {
+        available = listed;
+        priority = preferred;
+    }
+
     match config.model_selection {
         ModelSelection::RoundRobin => {
             // Get next model in rotation
@@ -609,21 +2557,67 @@ pub fn select_model<'a>(
         }
         ModelSelection::Priority => {
             // Find first available model in priority order
-            for name in &config.model_priority {
-                if let Some(model) = available.iter().find(|m| &m.name == name) {
+            for name in &priority {
+                if let Some(model) = available.iter().find(|m| m.name == *name) {
                     return Some(model);
                 }
             }
             // Fall back to first available
             available.first().copied()
         }
+        ModelSelection::Adaptive => {
+            // Priority order first, so ties go to the preferred model
+            let rank = |m: &ModelConfig| {
+                priority
+                    .iter()
+                    .position(|name| *name == m.name)
+                    .unwrap_or(usize::MAX)
+            };
+            let mut ranked = available;
+            ranked.sort_by_key(|m| rank(m));
+            let mut best = ranked[0];
+            for model in ranked.into_iter().skip(1) {
+                if model_stats.score(&model.name) > model_stats.score(&best.name) {
+                    best = model;
+                }
+            }
+            Some(best)
+        }
     }
 }
 
-/// Check if output contains the completion promise.
+/// Check if output contains the completion promise, with or without a
+/// report (see [`CompletionReport`]).
 pub fn check_promise(output: &str, promise: &str) -> bool {
-    let pattern = format!("<promise>{promise}</promise>");
-    output.contains(&pattern)
+    completion::has_promise(output, promise)
+}
+
+/// The report that came with the completion promise in `output`. A report
+/// that can't be parsed is skipped with a status message; the promise
+/// still counts.
+fn read_completion_report(
+    output: &str,
+    promise: &str,
+    iteration: usize,
+    events: &dyn EventSink,
+) -> Option<CompletionReport> {
+    match CompletionReport::from_output(output, promise) {
+        Ok(Some(report)) => {
+            events.emit(RunEvent::CompletionReported {
+                iteration,
+                report: report.clone(),
+            });
+            Some(report)
+        }
+        Ok(None) => None,
+        Err(e) => {
+            tracing::warn!("invalid completion report: {e}");
+            events.emit(RunEvent::Status {
+                message: format!("Ignoring completion report: {e}"),
+            });
+            None
+        }
+    }
 }
 
 /// Extract promise from output if present.
@@ -644,8 +2638,14 @@ pub fn hash_prompt(prompt: &str) -> String {
 
 /// Get git information for changelog.
 pub fn get_git_info() -> GitInfo {
+    get_git_info_in(Path::new("."))
+}
+
+/// Get git information for the repository or worktree at `work_dir`.
+pub fn get_git_info_in(work_dir: &Path) -> GitInfo {
     let branch = std::process::Command::new("git")
         .args(["rev-parse", "--abbrev-ref", "HEAD"])
+        .current_dir(work_dir)
         .output()
         .ok()
         .and_then(|o| {
@@ -659,23 +2659,17 @@ pub fn get_git_info() -> GitInfo {
 
     let dirty = std::process::Command::new("git")
         .args(["status", "--porcelain"])
+        .current_dir(work_dir)
         .output()
         .ok()
         .is_some_and(|o| !o.stdout.is_empty());
 
     let changed_files = std::process::Command::new("git")
         .args(["status", "--porcelain"])
+        .current_dir(work_dir)
         .output()
         .ok()
-        .map(|o| {
-            String::from_utf8_lossy(&o.stdout)
-                .lines()
-                .filter_map(|line| {
-                    let parts: Vec<&str> = line.trim().splitn(2, ' ').collect();
-                    parts.get(1).map(|s| s.trim().to_string())
-                })
-                .collect::<Vec<_>>()
-        })
+        .map(|o| porcelain_paths(&String::from_utf8_lossy(&o.stdout)))
         .unwrap_or_default();
 
     GitInfo {
@@ -696,6 +2690,33 @@ pub struct GitInfo {
     pub changed_files: Vec<String>,
 }
 
+/// Why a run whose required criteria all passed is complete, naming any
+/// optional criteria that didn't.
+fn completion_reason(criteria: &[Criterion], results: &[CriterionResult]) -> String {
+    let unmet: Vec<&str> = results
+        .iter()
+        .filter(|r| !r.passed)
+        .map(|r| criteria[r.index].id.as_str())
+        .collect();
+    if unmet.is_empty() {
+        "All criteria verified".into()
+    } else {
+        format!(
+            "All required criteria verified (optional not met: {})",
+            unmet.join(", ")
+        )
+    }
+}
+
+/// A failed criterion as the next iteration's feedback names it.
+fn feedback_label(criterion: &Criterion) -> String {
+    if criterion.required {
+        criterion.text.clone()
+    } else {
+        format!("{} (optional)", criterion.text)
+    }
+}
+
 /// Result of verifying a single criterion.
 #[derive(Debug, Clone)]
 pub struct CriterionResult {
@@ -707,29 +2728,34 @@ pub struct CriterionResult {
     pub reason: Option<String>,
 }
 
-/// Get git diff output for verification context.
-fn get_git_diff(max_chars: usize) -> String {
+/// Reason given to criteria the verifier response didn't mention.
+const NO_RESULT_REASON: &str = "No result found in verifier response";
+
+/// Maximum tokens of the implementer's output shown to the verifier.
+const VERIFIER_OUTPUT_TOKENS: usize = 500;
+
+/// Get git diff output for verification context, limited to `work_dir`.
+pub(crate) fn get_git_diff(work_dir: &Path) -> String {
     std::process::Command::new("git")
-        .args(["diff", "HEAD"])
+        .args(["diff", "--relative", "HEAD"])
+        .current_dir(work_dir)
         .output()
         .ok()
-        .map(|o| {
-            let diff = String::from_utf8_lossy(&o.stdout).to_string();
-            if diff.len() > max_chars {
-                format!("{}...[truncated]", &diff[..max_chars])
-            } else {
-                diff
-            }
-        })
+        .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
         .unwrap_or_else(|| "(no diff available)".into())
 }
 
 /// Build a prompt for the verifier model.
+///
+/// The criteria, repository state and instructions are always included;
+/// the git diff and the implementer's output are trimmed to what's left of
+/// `budget`.
 fn build_verifier_prompt(
-    criteria: &[String],
+    criteria: &[Criterion],
     git_info: &GitInfo,
     git_diff: &str,
     model_output: &str,
+    budget: &mut ContextBudget,
 ) -> String {
     let mut prompt = String::new();
 
@@ -752,31 +2778,37 @@ fn build_verifier_prompt(
         }
     ));
 
+    let mut task = String::new();
+    task.push_str("## Task\n");
+    task.push_str(
+        "For each criterion above, determine if it is satisfied based on the repository state.\n",
+    );
+    task.push_str("Respond with EXACTLY this format for each criterion:\n\n");
+    task.push_str("CRITERION 1: PASS\n");
+    task.push_str("CRITERION 2: FAIL - reason why it failed\n");
+    task.push_str("...\n\n");
+    task.push_str("Be strict: only mark PASS if you can verify the criterion is definitely met.\n");
+
+    budget.reserve(&prompt);
+    budget.reserve(&task);
+
+    // Leave room for the output excerpt before giving the rest to the diff
+    let output_share = VERIFIER_OUTPUT_TOKENS.min(budget.remaining() / 4);
+    let diff_share = budget.remaining() - output_share;
     prompt.push_str("## Git Diff\n```\n");
-    prompt.push_str(git_diff);
+    prompt.push_str(&budget.take(git_diff, diff_share));
     prompt.push_str("\n```\n\n");
 
     if !model_output.is_empty() {
-        prompt.push_str("## Recent Model Output (excerpt)\n```\n");
-        // Truncate model output to avoid context overflow
-        let max_output = 2000;
-        if model_output.len() > max_output {
-            prompt.push_str(&model_output[..max_output]);
-            prompt.push_str("\n...[truncated]");
-        } else {
-            prompt.push_str(model_output);
+        let excerpt = budget.take(model_output, VERIFIER_OUTPUT_TOKENS);
+        if !excerpt.is_empty() {
+            prompt.push_str("## Recent Model Output (excerpt)\n```\n");
+            prompt.push_str(&excerpt);
+            prompt.push_str("\n```\n\n");
         }
-        prompt.push_str("\n```\n\n");
     }
 
-    prompt.push_str("## Task\n");
-    prompt.push_str("For each criterion above, determine if it is satisfied based on the repository state.\n");
-    prompt.push_str("Respond with EXACTLY this format for each criterion:\n\n");
-    prompt.push_str("CRITERION 1: PASS\n");
-    prompt.push_str("CRITERION 2: FAIL - reason why it failed\n");
-    prompt.push_str("...\n\n");
-    prompt.push_str("Be strict: only mark PASS if you can verify the criterion is definitely met.\n");
-
+    prompt.push_str(&task);
     prompt
 }
 
@@ -789,7 +2821,7 @@ fn parse_verification_response(response: &str, criteria_count: usize) -> Vec<Cri
         results.push(CriterionResult {
             index: i,
             passed: false,
-            reason: Some("No result found in verifier response".into()),
+            reason: Some(NO_RESULT_REASON.into()),
         });
     }
 
@@ -838,91 +2870,166 @@ fn parse_verification_response(response: &str, criteria_count: usize) -> Vec<Cri
 
 /// Verify completion criteria using an AI model.
 ///
+/// With a `cache`, criteria already verified against the current workspace
+/// state reuse their verdict, and only the rest are sent to the model.
+///
 /// Returns a vector of results for each criterion.
+#[tracing::instrument(name = "verification", skip_all, fields(criteria = criteria.len()))]
 pub async fn verify_criteria(
     config: &Config,
-    criteria: &[String],
+    criteria: &[Criterion],
     model_output: &str,
     run_dir: &Path,
-    state: &mut RunState,
+    work_dir: &Path,
+    implementer: &str,
     cooldowns: &Cooldowns,
-    event_tx: &mpsc::UnboundedSender<RunEvent>,
+    events: &dyn EventSink,
     iteration: usize,
+    mut cache: Option<&mut VerifyCache>,
 ) -> Vec<CriterionResult> {
-    // Select a verifier model (prefer different from the one that just ran)
-    let verifier = match select_model(config, cooldowns, state) {
-        Some(m) => m.clone(),
-        None => {
-            // No models available, fail all criteria
-            return criteria
-                .iter()
-                .enumerate()
-                .map(|(i, _)| CriterionResult {
-                    index: i,
-                    passed: false,
-                    reason: Some("No verifier model available".into()),
-                })
-                .collect();
-        }
+    let tree_hash = if cache.is_some() {
+        GitSafety::new(work_dir).worktree_tree_hash().ok()
+    } else {
+        None
     };
 
-    // Emit verification started event
-    let _ = event_tx.send(RunEvent::VerificationStarted {
-        iteration,
-        model: verifier.name.clone(),
-        criteria_count: criteria.len(),
-    });
-
-    // Gather context
-    let git_info = get_git_info();
-    let git_diff = get_git_diff(4000);
-
-    // Build verifier prompt
-    let prompt = build_verifier_prompt(criteria, &git_info, &git_diff, model_output);
+    let mut results = Vec::with_capacity(criteria.len());
+    let mut pending = Vec::new();
+    for (i, criterion) in criteria.iter().enumerate() {
+        let verdict = cache
+            .as_deref()
+            .zip(tree_hash.as_deref())
+            .and_then(|(cache, tree)| cache.get(&criterion.text, tree));
+        match verdict {
+            Some(verdict) => results.push(CriterionResult {
+                index: i,
+                passed: verdict.passed,
+                reason: verdict.reason.clone(),
+            }),
+            None => pending.push(i),
+        }
+    }
+    for r in &results {
+        events.emit(RunEvent::CriterionVerified {
+            index: r.index,
+            id: criteria[r.index].id.clone(),
+            required: criteria[r.index].required,
+            passed: r.passed,
+            reason: r.reason.clone(),
+            cached: true,
+        });
+    }
+    tracing::debug!(
+        cached = results.len(),
+        pending = pending.len(),
+        "verifying criteria"
+    );
+    if pending.is_empty() {
+        return results;
+    }
 
-    // Invoke verifier model
-    let result = match invoke_model(&verifier, &prompt, run_dir).await {
-        Ok(r) => r,
-        Err(e) => {
-            // Verifier failed, fail all criteria
-            let results: Vec<CriterionResult> = criteria
-                .iter()
-                .enumerate()
-                .map(|(i, _)| CriterionResult {
+    let pending_criteria: Vec<Criterion> = pending.iter().map(|&i| criteria[i].clone()).collect();
+    let verified = verify_with_model(
+        config,
+        &pending_criteria,
+        model_output,
+        run_dir,
+        work_dir,
+        implementer,
+        cooldowns,
+        events,
+        iteration,
+    )
+    .await;
+    // Verdicts from a failed model call are never cached
+    let (mut fresh, cacheable) = match verified {
+        Ok(results) => (results, true),
+        Err(reason) => {
+            tracing::warn!("verification failed: {reason}");
+            let failed = (0..pending.len())
+                .map(|i| CriterionResult {
                     index: i,
                     passed: false,
-                    reason: Some(format!("Verifier error: {e}")),
+                    reason: Some(reason.clone()),
                 })
                 .collect();
-
-            // Emit events for each failed criterion
-            for r in &results {
-                let _ = event_tx.send(RunEvent::CriterionVerified {
-                    index: r.index,
-                    passed: r.passed,
-                    reason: r.reason.clone(),
-                });
-            }
-
-            return results;
+            (failed, false)
         }
     };
 
-    // Parse the response
-    let results = parse_verification_response(&result.stdout, criteria.len());
-
-    // Emit events for each criterion
-    for r in &results {
-        let _ = event_tx.send(RunEvent::CriterionVerified {
+    for r in &mut fresh {
+        let criterion = &pending_criteria[r.index];
+        r.index = pending[r.index];
+        events.emit(RunEvent::CriterionVerified {
             index: r.index,
+            id: criterion.id.clone(),
+            required: criterion.required,
             passed: r.passed,
             reason: r.reason.clone(),
+            cached: false,
         });
+
+        // Only cache verdicts the model actually gave
+        let answered = cacheable && (r.passed || r.reason.as_deref() != Some(NO_RESULT_REASON));
+        if let (Some(cache), Some(tree), true) = (cache.as_deref_mut(), &tree_hash, answered) {
+            cache.insert(&criterion.text, tree, r.passed, r.reason.clone());
+        }
     }
 
+    results.extend(fresh);
+    results.sort_by_key(|r| r.index);
     results
 }
 
+/// Ask a model to verify `criteria`, returning results indexed into
+/// `criteria`.
+///
+/// Errors (no model available, the model call failing) apply to every
+/// criterion and are returned as the failure reason.
+async fn verify_with_model(
+    config: &Config,
+    criteria: &[Criterion],
+    model_output: &str,
+    run_dir: &Path,
+    work_dir: &Path,
+    implementer: &str,
+    cooldowns: &Cooldowns,
+    events: &dyn EventSink,
+    iteration: usize,
+) -> Result<Vec<CriterionResult>, String> {
+    // Select a verifier model (different from the implementer if possible)
+    let verifier = select_verifier_model(config, cooldowns, implementer)
+        .cloned()
+        .ok_or_else(|| "No verifier model available".to_string())?;
+    if verifier.name == implementer {
+        events.emit(RunEvent::Status {
+            message: format!("No other model available; {implementer} is verifying its own work"),
+        });
+    }
+
+    // Emit verification started event
+    events.emit(RunEvent::VerificationStarted {
+        iteration,
+        model: verifier.name.clone(),
+        implementer: implementer.to_string(),
+        criteria_count: criteria.len(),
+    });
+
+    // Gather context
+    let git_info = get_git_info_in(work_dir);
+    let git_diff = get_git_diff(work_dir);
+
+    // Build verifier prompt, sized to the verifier's context window
+    let mut budget = ContextBudget::for_model(&verifier);
+    let prompt = build_verifier_prompt(criteria, &git_info, &git_diff, model_output, &mut budget);
+
+    // Invoke verifier model and parse the response
+    let result = invoke_model(&verifier, &prompt, &run_dir.join(VERIFY_LOG_DIR), work_dir)
+        .await
+        .map_err(|e| format!("Verifier error: {e}"))?;
+    Ok(parse_verification_response(&result.stdout, criteria.len()))
+}
+
 /// Errors that can occur during running.
 #[derive(Debug, thiserror::Error)]
 pub enum RunnerError {
@@ -949,11 +3056,28 @@ pub enum RunnerError {
     /// Prompt file not found.
     #[error("Prompt file not found: {0}")]
     PromptNotFound(PathBuf),
+
+    /// No persisted state for the requested run.
+    #[error("Run not found: {0}")]
+    RunNotFound(String),
+
+    /// The run cannot be resumed.
+    #[error("Run cannot be resumed: {0}")]
+    NotResumable(String),
+
+    /// The prompt changed since the run started.
+    #[error("Prompt changed since run {0} started (use --force to resume anyway)")]
+    PromptChanged(String),
+
+    /// Git operation failed.
+    #[error("Git error: {0}")]
+    Git(#[from] GitError),
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::adapters::check_rate_limit;
 
     #[test]
     fn test_check_rate_limit() {
@@ -964,6 +3088,203 @@ mod tests {
         assert!(!check_rate_limit("Success", &patterns));
     }
 
+    #[test]
+    fn test_run_handle_pause_resume() {
+        let (
+            handle,
+            Controls {
+                pause: pause_rx, ..
+            },
+        ) = RunHandle::detached();
+
+        assert!(!handle.is_paused());
+        assert!(!handle.resume());
+        assert!(handle.pause());
+        assert!(!handle.pause());
+        assert!(handle.is_paused());
+        assert!(*pause_rx.borrow());
+        assert!(handle.resume());
+        assert!(!handle.is_paused());
+        assert!(!*pause_rx.borrow());
+    }
+
+    #[tokio::test]
+    async fn test_run_handle_review_decisions() {
+        let (
+            handle,
+            Controls {
+                review: mut review_rx,
+                ..
+            },
+        ) = RunHandle::detached();
+
+        assert!(handle.approve_review());
+        // One decision at a time
+        assert!(!handle.reject_review());
+        assert_eq!(review_rx.recv().await, Some(true));
+        assert!(handle.reject_review());
+        assert_eq!(review_rx.recv().await, Some(false));
+
+        drop(review_rx);
+        assert!(!handle.approve_review());
+    }
+
+    #[tokio::test]
+    async fn test_run_handle_answer() {
+        let (
+            handle,
+            Controls {
+                answer: mut answer_rx,
+                ..
+            },
+        ) = RunHandle::detached();
+
+        assert!(handle.answer("SQLite"));
+        assert!(!handle.answer("Postgres"));
+        assert_eq!(answer_rx.recv().await.as_deref(), Some("SQLite"));
+
+        drop(answer_rx);
+        assert!(!handle.answer("SQLite"));
+    }
+
+    #[test]
+    fn test_iteration_commit_message() {
+        assert_eq!(
+            iteration_commit_message("abc123", 3, "claude", "Criteria: 2/2 passed"),
+            "ralf(claude): iter 3 (run abc123)\n\nCriteria: 2/2 passed\n"
+        );
+    }
+
+    #[test]
+    fn test_completion_reason() {
+        let criteria = vec![
+            Criterion::parse(0, "Tests pass"),
+            Criterion::parse(1, "Docs updated (optional)"),
+        ];
+        let result = |index: usize, passed: bool| CriterionResult {
+            index,
+            passed,
+            reason: None,
+        };
+        assert_eq!(
+            completion_reason(&criteria, &[result(0, true), result(1, true)]),
+            "All criteria verified"
+        );
+        assert_eq!(
+            completion_reason(&criteria, &[result(0, true), result(1, false)]),
+            "All required criteria verified (optional not met: c2)"
+        );
+        assert_eq!(feedback_label(&criteria[1]), "Docs updated (optional)");
+    }
+
+    #[test]
+    fn test_verifier_skip_reason() {
+        let verifier = |name: &str, depends_on: &[&str], fail_fast: bool| VerifierConfig {
+            name: name.into(),
+            depends_on: depends_on.iter().map(|d| (*d).to_string()).collect(),
+            fail_fast,
+            ..VerifierConfig::default_tests()
+        };
+        let result = |name: &str, passed: bool| VerifierResult {
+            name: name.into(),
+            passed,
+            exit_code: Some(i32::from(!passed)),
+            output: String::new(),
+            duration_ms: 1,
+            skipped: false,
+        };
+        let verifiers = vec![
+            verifier("check", &[], false),
+            verifier("test", &["check"], false),
+            verifier("fmt", &[], false),
+        ];
+
+        assert!(
+            verifier_skip_reason(&verifiers[1], &verifiers, &[result("check", true)]).is_none()
+        );
+        assert_eq!(
+            verifier_skip_reason(&verifiers[1], &verifiers, &[result("check", false)]).unwrap(),
+            "dependency 'check' did not pass"
+        );
+        // Only dependents are skipped unless the failure is fail-fast
+        assert!(
+            verifier_skip_reason(&verifiers[2], &verifiers, &[result("check", false)]).is_none()
+        );
+
+        let verifiers = vec![verifier("check", &[], true), verifier("fmt", &[], false)];
+        assert_eq!(
+            verifier_skip_reason(&verifiers[1], &verifiers, &[result("check", false)]).unwrap(),
+            "'check' failed (fail-fast)"
+        );
+        assert!(VerifierResult::skipped("fmt", "x").skipped);
+    }
+
+    #[test]
+    fn test_prepare_work_dir() {
+        let temp = tempfile::TempDir::new().unwrap();
+
+        let config = Config::default();
+        let (work_dir, worktree) =
+            prepare_work_dir(&config, temp.path(), temp.path(), "abc123").unwrap();
+        assert_eq!(work_dir, temp.path());
+        assert!(worktree.is_none());
+
+        // A target directory must exist
+        let target = target_root(temp.path(), Some(Path::new("services/api")));
+        assert_eq!(target, temp.path().join("services/api"));
+        assert!(matches!(
+            prepare_work_dir(&config, temp.path(), &target, "abc123"),
+            Err(RunnerError::Config(_))
+        ));
+        std::fs::create_dir_all(&target).unwrap();
+        let (work_dir, _) = prepare_work_dir(&config, temp.path(), &target, "abc123").unwrap();
+        assert_eq!(work_dir, target);
+        assert_eq!(target_root(temp.path(), None), temp.path());
+
+        // Worktree isolation requires a git repository
+        let config = Config {
+            isolation: Isolation::Worktree,
+            ..Config::default()
+        };
+        let result = prepare_work_dir(&config, temp.path(), temp.path(), "abc123");
+        assert!(matches!(
+            result,
+            Err(RunnerError::Git(GitError::NotARepo(_)))
+        ));
+    }
+
+    #[test]
+    fn test_prepare_work_dir_in_worktree_subdirectory() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let repo = temp.path();
+        let git = |args: &[&str]| {
+            assert!(std::process::Command::new("git")
+                .args(args)
+                .current_dir(repo)
+                .output()
+                .unwrap()
+                .status
+                .success());
+        };
+        git(&["init"]);
+        git(&["config", "user.email", "test@test.com"]);
+        git(&["config", "user.name", "Test"]);
+        std::fs::create_dir_all(repo.join("services/api")).unwrap();
+        std::fs::write(repo.join("services/api/main.rs"), "fn main() {}\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "init"]);
+
+        let config = Config {
+            isolation: Isolation::Worktree,
+            ..Config::default()
+        };
+        let target = target_root(repo, Some(Path::new("services/api")));
+        let (work_dir, worktree) = prepare_work_dir(&config, repo, &target, "abc123").unwrap();
+        let worktree = worktree.unwrap();
+        assert_eq!(work_dir, worktree.path.join("services/api"));
+        assert!(work_dir.join("main.rs").exists());
+    }
+
     #[test]
     fn test_check_promise() {
         assert!(check_promise(
@@ -1006,12 +3327,12 @@ mod tests {
         state.last_model_index = 0;
 
         // First selection should get first model and advance index
-        let model1 = select_model(&config, &cooldowns, &mut state);
+        let model1 = select_model(&config, &cooldowns, &ModelStats::default(), &mut state);
         assert!(model1.is_some());
         assert_eq!(state.last_model_index, 1);
 
         // Second selection should get second model and advance index
-        let model2 = select_model(&config, &cooldowns, &mut state);
+        let model2 = select_model(&config, &cooldowns, &ModelStats::default(), &mut state);
         assert!(model2.is_some());
         assert_eq!(state.last_model_index, 2);
 
@@ -1019,6 +3340,130 @@ mod tests {
         assert_ne!(model1.unwrap().name, model2.unwrap().name);
     }
 
+    #[test]
+    fn test_select_model_adaptive() {
+        let mut config =
+            Config::with_detected_models(&["claude".into(), "codex".into(), "gemini".into()]);
+        config.model_selection = ModelSelection::Adaptive;
+        let mut cooldowns = Cooldowns::default();
+        let mut model_stats = ModelStats::default();
+        let mut state = RunState::default();
+
+        // No stats yet: priority order decides
+        let model = select_model(&config, &cooldowns, &model_stats, &mut state).unwrap();
+        assert_eq!(model.name, "claude");
+
+        // Untried models are preferred over measured ones
+        model_stats.record("claude", InvocationOutcome::Success, 30_000);
+        let model = select_model(&config, &cooldowns, &model_stats, &mut state).unwrap();
+        assert_eq!(model.name, "codex");
+
+        // Once measured, the healthiest available model wins
+        model_stats.record("codex", InvocationOutcome::TimedOut, 600_000);
+        model_stats.record("gemini", InvocationOutcome::RateLimited, 1_000);
+        let model = select_model(&config, &cooldowns, &model_stats, &mut state).unwrap();
+        assert_eq!(model.name, "claude");
+
+        cooldowns.set_cooldown("claude", 60, "rate limit");
+        let model = select_model(&config, &cooldowns, &model_stats, &mut state).unwrap();
+        assert_eq!(model.name, "codex");
+
+        config.disabled_models = vec!["codex".into()];
+        let model = select_model(&config, &cooldowns, &model_stats, &mut state).unwrap();
+        assert_eq!(model.name, "gemini");
+    }
+
+    #[test]
+    fn test_select_model_follows_implementer_role() {
+        let mut config =
+            Config::with_detected_models(&["claude".into(), "codex".into(), "gemini".into()]);
+        config.roles.implementer = vec!["gemini".into(), "codex".into()];
+        let mut cooldowns = Cooldowns::default();
+        let model_stats = ModelStats::default();
+        let mut state = RunState::default();
+
+        // Round-robin rotates through the role's models only
+        let picks: Vec<String> = (0..4)
+            .map(|_| {
+                select_model(&config, &cooldowns, &model_stats, &mut state)
+                    .unwrap()
+                    .name
+                    .clone()
+            })
+            .collect();
+        assert!(picks.iter().all(|name| name != "claude"));
+
+        config.model_selection = ModelSelection::Priority;
+        let model = select_model(&config, &cooldowns, &model_stats, &mut state).unwrap();
+        assert_eq!(model.name, "gemini");
+
+        // Other models step in when none of the role's are available
+        cooldowns.set_cooldown("gemini", 60, "test");
+        cooldowns.set_cooldown("codex", 60, "test");
+        let model = select_model(&config, &cooldowns, &model_stats, &mut state).unwrap();
+        assert_eq!(model.name, "claude");
+    }
+
+    #[test]
+    fn test_select_verifier_model_prefers_other_model() {
+        let mut config =
+            Config::with_detected_models(&["claude".into(), "codex".into(), "gemini".into()]);
+        let mut cooldowns = Cooldowns::default();
+
+        // Follows model_priority, skipping the implementer
+        let verifier = select_verifier_model(&config, &cooldowns, "claude").unwrap();
+        assert_eq!(verifier.name, "codex");
+
+        config.verifier_model_priority = vec!["gemini".into()];
+        let verifier = select_verifier_model(&config, &cooldowns, "claude").unwrap();
+        assert_eq!(verifier.name, "gemini");
+        let verifier = select_verifier_model(&config, &cooldowns, "gemini").unwrap();
+        assert_eq!(verifier.name, "claude");
+
+        // The verifier role takes precedence over verifier_model_priority
+        config.roles.verifier = vec!["codex".into()];
+        let verifier = select_verifier_model(&config, &cooldowns, "claude").unwrap();
+        assert_eq!(verifier.name, "codex");
+        config.roles.verifier.clear();
+
+        // Falls back to the implementer only when nothing else is available
+        cooldowns.set_cooldown("codex", 60, "test");
+        cooldowns.set_cooldown("gemini", 60, "test");
+        let verifier = select_verifier_model(&config, &cooldowns, "claude").unwrap();
+        assert_eq!(verifier.name, "claude");
+
+        cooldowns.set_cooldown("claude", 60, "test");
+        assert!(select_verifier_model(&config, &cooldowns, "claude").is_none());
+
+        config.disabled_models = vec!["codex".into()];
+        let cooldowns = Cooldowns::default();
+        let verifier = select_verifier_model(&config, &cooldowns, "claude").unwrap();
+        assert_eq!(verifier.name, "gemini");
+    }
+
+    #[test]
+    fn test_build_verifier_prompt_fits_budget() {
+        let criteria = vec![Criterion::new(0, "Tests pass")];
+        let git_info = GitInfo {
+            branch: "main".into(),
+            dirty: true,
+            changed_files: vec!["src/lib.rs".into()],
+        };
+        let diff = "+line\n".repeat(10_000);
+
+        let mut budget = ContextBudget::new(2_000);
+        let prompt = build_verifier_prompt(&criteria, &git_info, &diff, "done", &mut budget);
+        assert!(crate::context::estimate_tokens(&prompt) <= 2_000);
+        assert!(prompt.contains("[truncated]"));
+        assert!(prompt.contains("1. Tests pass"));
+        assert!(prompt.contains("## Recent Model Output"));
+        assert!(prompt.ends_with("definitely met.\n"));
+
+        let mut budget = ContextBudget::unlimited();
+        let prompt = build_verifier_prompt(&criteria, &git_info, &diff, "done", &mut budget);
+        assert!(!prompt.contains("[truncated]"));
+    }
+
     #[test]
     fn test_parse_verification_response_all_pass() {
         let response = r#"
@@ -1096,4 +3541,245 @@ criterion  4 : fail - file not found
             "Should extract reason with extra whitespace"
         );
     }
+
+    fn write_run_state(ralf_dir: &Path, state: &RunState) {
+        let run_id = state.run_id.as_deref().unwrap();
+        state
+            .save(&ralf_dir.join("runs").join(run_id).join("state.json"))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_load_resumable_state() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let mut state = RunState::default();
+        state.start_run_with_id("run1");
+        state.prompt_hash = Some(hash_prompt("prompt"));
+        state.completed_iterations = 2;
+        write_run_state(temp.path(), &state);
+
+        let loaded =
+            load_resumable_state(temp.path(), "run1", &hash_prompt("prompt"), false).unwrap();
+        assert_eq!(loaded.completed_iterations, 2);
+    }
+
+    #[test]
+    fn test_load_resumable_state_prompt_changed() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let mut state = RunState::default();
+        state.start_run_with_id("run1");
+        state.prompt_hash = Some(hash_prompt("prompt"));
+        write_run_state(temp.path(), &state);
+
+        let changed = hash_prompt("edited prompt");
+        assert!(matches!(
+            load_resumable_state(temp.path(), "run1", &changed, false),
+            Err(RunnerError::PromptChanged(_))
+        ));
+        assert!(load_resumable_state(temp.path(), "run1", &changed, true).is_ok());
+    }
+
+    #[test]
+    fn test_load_resumable_state_rejects_completed_and_missing() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let mut state = RunState::default();
+        state.start_run_with_id("run1");
+        state.complete();
+        write_run_state(temp.path(), &state);
+
+        assert!(matches!(
+            load_resumable_state(temp.path(), "run1", "hash", true),
+            Err(RunnerError::NotResumable(_))
+        ));
+        assert!(matches!(
+            load_resumable_state(temp.path(), "nope", "hash", true),
+            Err(RunnerError::RunNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_load_resumable_state_falls_back_to_global_state() {
+        let temp = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(temp.path().join("runs").join("run1")).unwrap();
+        let mut state = RunState::default();
+        state.start_run_with_id("run1");
+        state.save(&temp.path().join("state.json")).unwrap();
+
+        assert!(load_resumable_state(temp.path(), "run1", "hash", false).is_ok());
+
+        std::fs::create_dir_all(temp.path().join("runs").join("run2")).unwrap();
+        assert!(matches!(
+            load_resumable_state(temp.path(), "run2", "hash", false),
+            Err(RunnerError::RunNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_invoke_mock_model() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let mock_dir = temp.path().join(".ralf").join("mock");
+        std::fs::create_dir_all(&mock_dir).unwrap();
+        std::fs::write(
+            mock_dir.join("1.md"),
+            "---\nrate_limit: true\n---\nSlow down",
+        )
+        .unwrap();
+        std::fs::write(mock_dir.join("2.md"), "---\npromise: COMPLETE\n---\nDone").unwrap();
+        std::fs::write(mock_dir.join("3.md"), "---\ndelay_ms: 5000\n---\n").unwrap();
+
+        let model = ModelConfig::default_for("mock");
+        let run_dir = temp.path().join("runs").join("run1");
+        let invoke = |iteration: u64, limit: Duration| {
+            let log_dir = iteration_dir(&run_dir, iteration);
+            let model = &model;
+            let work_dir = temp.path();
+            async move { invoke_model_within(model, "prompt", &log_dir, work_dir, limit).await }
+        };
+
+        let limited = invoke(1, Duration::MAX).await.unwrap();
+        assert!(limited.rate_limited);
+        assert_eq!(limited.exit_code, Some(1));
+
+        let done = invoke(2, Duration::MAX).await.unwrap();
+        assert!(check_promise(&done.stdout, "COMPLETE"));
+        let log = std::fs::read_to_string(iteration_dir(&run_dir, 2).join("mock.log")).unwrap();
+        assert!(log.contains("Done"));
+
+        let slow = invoke(3, Duration::from_millis(10)).await;
+        assert!(matches!(slow, Err(RunnerError::Timeout(_))));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_wait_with_limit_terminates_gracefully() {
+        let spawn = |script: &str| {
+            spawn_process(
+                Command::new("sh")
+                    .args(["-c", script])
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::piped()),
+            )
+            .unwrap()
+        };
+
+        // SIGTERM lets the process flush its last words
+        let (child, guard) = spawn(
+            "echo started; trap 'echo stopping; exit 3' TERM; sleep 5 >/dev/null 2>&1 & wait",
+        );
+        let output = wait_with_limit(child, guard, Duration::from_millis(300))
+            .await
+            .unwrap();
+        assert!(output.status.is_none());
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "started\nstopping\n"
+        );
+
+        let (child, guard) = spawn("echo done; echo oops >&2");
+        let output = wait_with_limit(child, guard, Duration::from_secs(10))
+            .await
+            .unwrap();
+        assert!(output.status.unwrap().success());
+        assert_eq!(output.stdout, b"done\n");
+        assert_eq!(output.stderr, b"oops\n");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_wait_with_limit_streams_stdout() {
+        let (child, guard) = spawn_process(
+            Command::new("sh")
+                .args([
+                    "-c",
+                    r"printf 'Hel'; sleep 0.2; printf 'lo \303'; sleep 0.2; printf '\251\n'",
+                ])
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped()),
+        )
+        .unwrap();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let output = wait_with_limit_streaming(child, guard, Duration::from_secs(10), Some(&tx))
+            .await
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "Hello é\n");
+
+        let mut chunks = Vec::new();
+        while let Ok(chunk) = rx.try_recv() {
+            chunks.push(chunk);
+        }
+        // The split "é" arrives whole
+        assert_eq!(chunks, vec!["Hel", "lo ", "é\n"]);
+    }
+
+    /// Whether a process exists and isn't a zombie.
+    #[cfg(target_os = "linux")]
+    fn is_running(pid: &str) -> bool {
+        std::fs::read_to_string(Path::new("/proc").join(pid).join("stat")).is_ok_and(|stat| {
+            !stat
+                .rsplit(')')
+                .next()
+                .unwrap_or("")
+                .trim_start()
+                .starts_with('Z')
+        })
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_dropping_guard_kills_process_tree() {
+        use tokio::io::AsyncBufReadExt;
+
+        let (mut child, guard) = spawn_process(
+            Command::new("sh")
+                .args(["-c", "sleep 30 & echo $!; wait"])
+                .stdout(Stdio::piped()),
+        )
+        .unwrap();
+        let mut line = String::new();
+        tokio::io::BufReader::new(child.stdout.take().unwrap())
+            .read_line(&mut line)
+            .await
+            .unwrap();
+        let grandchild = line.trim().to_string();
+        assert!(is_running(&grandchild));
+
+        // What cancelling an invocation does
+        drop(guard);
+        let _ = child.wait().await;
+        for _ in 0..50 {
+            if !is_running(&grandchild) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(!is_running(&grandchild));
+    }
+
+    #[test]
+    fn test_find_leftover_processes() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let process = |pid: &str, environ: &str, comm: &str| {
+            let dir = temp.path().join(pid);
+            std::fs::create_dir(&dir).unwrap();
+            std::fs::write(dir.join("environ"), environ).unwrap();
+            std::fs::write(dir.join("comm"), format!("{comm}\n")).unwrap();
+        };
+        process("100", "PATH=/bin\0", "ralf");
+        // Spawned by a ralf that is still running
+        process("200", "PATH=/bin\0RALF_PARENT_PID=100\0", "claude");
+        // Orphaned when ralf 999 exited
+        process("300", "RALF_PARENT_PID=999\0HOME=/root\0", "node");
+        process("400", "HOME=/root\0", "bash");
+        std::fs::create_dir(temp.path().join("self")).unwrap();
+
+        assert_eq!(
+            find_leftover_processes_in(temp.path()),
+            vec![LeftoverProcess {
+                pid: 300,
+                name: "node".into(),
+                parent_pid: 999,
+            }]
+        );
+        assert!(find_leftover_processes_in(&temp.path().join("missing")).is_empty());
+    }
 }
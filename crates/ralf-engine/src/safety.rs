@@ -0,0 +1,218 @@
+//! Protected paths and forbidden commands.
+//!
+//! Some files should never be changed by a run, however the prompt reads:
+//! CI workflows, lockfiles, secrets. With a `safety` section configured,
+//! the runner puts any protected file an iteration changed back the way it
+//! was, and refuses to let an iteration complete if the model's output
+//! shows it running a forbidden command. Preflight warns when the spec
+//! mentions a protected path, since the model will be asked to change
+//! something it can't.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::git::{GitError, GitSafety};
+use crate::review::{changes_since, glob_matches};
+
+/// Paths and commands a run must not touch.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SafetyConfig {
+    /// Globs of paths whose changes are reverted after every iteration,
+    /// e.g. `.github/`, `Cargo.lock` or `*.pem` (see [`glob_matches`]).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub protected_paths: Vec<String>,
+
+    /// Commands the model must not run, matched case-insensitively
+    /// against its output, e.g. `git push --force` or `rm -rf /`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub forbidden_commands: Vec<String>,
+}
+
+impl SafetyConfig {
+    /// Whether nothing is protected or forbidden.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Whether `path` is protected.
+    pub fn is_protected(&self, path: &str) -> bool {
+        self.protected_paths
+            .iter()
+            .any(|pattern| glob_matches(pattern, path))
+    }
+
+    /// The forbidden commands that show up in `output`.
+    pub fn forbidden_in(&self, output: &str) -> Vec<String> {
+        let lower = output.to_lowercase();
+        self.forbidden_commands
+            .iter()
+            .filter(|command| {
+                let command = command.trim();
+                !command.is_empty() && lower.contains(&command.to_lowercase())
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Protected paths that `text` (a spec) mentions, in order, without
+    /// repeats.
+    pub fn mentioned_in(&self, text: &str) -> Vec<String> {
+        let mut mentioned: Vec<String> = Vec::new();
+        for word in text.split_whitespace() {
+            let punctuation = |c: char| "`'\"()[]{}<>,;:!?".contains(c);
+            let word = word
+                .trim_end_matches(|c: char| punctuation(c) || c == '.')
+                .trim_start_matches(punctuation);
+            if !word.is_empty() && self.is_protected(word) && !mentioned.iter().any(|m| m == word) {
+                mentioned.push(word.to_string());
+            }
+        }
+        mentioned
+    }
+}
+
+/// Revert every protected path changed in `work_dir` since its tree hash
+/// was `before` (see [`GitSafety::worktree_tree_hash`]), returning the
+/// reverted paths.
+///
+/// # Errors
+///
+/// Returns the git error if the changes can't be listed or a path can't be
+/// restored.
+pub fn revert_protected(
+    config: &SafetyConfig,
+    work_dir: &Path,
+    before: &str,
+) -> Result<Vec<String>, GitError> {
+    if config.protected_paths.is_empty() {
+        return Ok(Vec::new());
+    }
+    let git = GitSafety::new(work_dir);
+    let mut reverted = Vec::new();
+    for change in changes_since(work_dir, before)? {
+        if config.is_protected(&change.path) {
+            git.restore_from_tree(before, &change.path)?;
+            reverted.push(change.path);
+        }
+    }
+    Ok(reverted)
+}
+
+/// What the feedback to the next iteration says about a safety violation.
+pub fn violation_messages(reverted: &[String], forbidden: &[String]) -> Vec<String> {
+    let mut messages = Vec::new();
+    if !reverted.is_empty() {
+        messages.push(format!(
+            "Changes to protected paths were reverted: {}. Leave them alone.",
+            reverted.join(", ")
+        ));
+    }
+    for command in forbidden {
+        messages.push(format!(
+            "Ran a forbidden command (`{command}`), so the iteration can't complete. Don't run it."
+        ));
+    }
+    messages
+}
+
+/// A one-line account of a safety violation, for status lines and logs.
+pub fn violation_summary(reverted: &[String], forbidden: &[String]) -> String {
+    let mut parts = Vec::new();
+    if !reverted.is_empty() {
+        parts.push(format!("reverted changes to {}", reverted.join(", ")));
+    }
+    if !forbidden.is_empty() {
+        let commands: Vec<String> = forbidden.iter().map(|c| format!("`{c}`")).collect();
+        parts.push(format!("ran forbidden {}", commands.join(", ")));
+    }
+    parts.join("; ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    fn config() -> SafetyConfig {
+        SafetyConfig {
+            protected_paths: vec![".github/".into(), "Cargo.lock".into(), "*.pem".into()],
+            forbidden_commands: vec!["git push --force".into(), " ".into()],
+        }
+    }
+
+    #[test]
+    fn test_forbidden_in() {
+        let config = config();
+        assert_eq!(
+            config.forbidden_in("$ GIT PUSH --FORCE origin main"),
+            vec!["git push --force"]
+        );
+        assert!(config.forbidden_in("$ git push origin main").is_empty());
+        assert!(SafetyConfig::default().is_empty());
+    }
+
+    #[test]
+    fn test_mentioned_in() {
+        let spec = "Bump serde and update `Cargo.lock`. Also fix .github/workflows/ci.yml, \
+                    and regenerate Cargo.lock; keep certs/dev.pem.";
+        assert_eq!(
+            config().mentioned_in(spec),
+            vec!["Cargo.lock", ".github/workflows/ci.yml", "certs/dev.pem"]
+        );
+        assert!(config().mentioned_in("Add a --fast flag").is_empty());
+    }
+
+    #[test]
+    fn test_revert_protected() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let git = |args: &[&str]| {
+            assert!(Command::new("git")
+                .args(args)
+                .current_dir(dir)
+                .output()
+                .unwrap()
+                .status
+                .success());
+        };
+        git(&["init"]);
+        git(&["config", "user.email", "test@test.com"]);
+        git(&["config", "user.name", "Test"]);
+        std::fs::write(dir.join("Cargo.lock"), "v1\n").unwrap();
+        std::fs::write(dir.join("lib.rs"), "fn a() {}\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "init"]);
+
+        let before = GitSafety::new(dir).worktree_tree_hash().unwrap();
+        std::fs::write(dir.join("Cargo.lock"), "v2\n").unwrap();
+        std::fs::write(dir.join("lib.rs"), "fn b() {}\n").unwrap();
+        std::fs::write(dir.join("key.pem"), "secret\n").unwrap();
+
+        let mut reverted = revert_protected(&config(), dir, &before).unwrap();
+        reverted.sort();
+        assert_eq!(reverted, vec!["Cargo.lock", "key.pem"]);
+        assert_eq!(
+            std::fs::read_to_string(dir.join("Cargo.lock")).unwrap(),
+            "v1\n"
+        );
+        assert!(!dir.join("key.pem").exists());
+        // Unprotected changes stay
+        assert_eq!(
+            std::fs::read_to_string(dir.join("lib.rs")).unwrap(),
+            "fn b() {}\n"
+        );
+
+        assert_eq!(
+            violation_messages(&reverted, &["rm -rf /".into()]),
+            vec![
+                "Changes to protected paths were reverted: Cargo.lock, key.pem. Leave them alone.",
+                "Ran a forbidden command (`rm -rf /`), so the iteration can't complete. Don't run it.",
+            ]
+        );
+        assert_eq!(
+            violation_summary(&reverted, &["rm -rf /".into()]),
+            "reverted changes to Cargo.lock, key.pem; ran forbidden `rm -rf /`"
+        );
+    }
+}
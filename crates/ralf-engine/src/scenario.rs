@@ -0,0 +1,611 @@
+//! Deterministic end-to-end scenarios, for testing.
+//!
+//! A scenario scripts a whole run against the [mock model](crate::adapters::MockAdapter):
+//! what the model answers each iteration, what the verifier says about each
+//! completion criterion, and which phases the thread should pass through.
+//! [`run_scenario`] writes the script to `.ralf/mock/` in a scratch repo,
+//! runs the real loop there, and checks the outcome, so engine and TUI tests
+//! can assert full-loop behavior without any model CLI installed.
+//!
+//! Scenarios are JSON:
+//!
+//! ```json
+//! {
+//!   "name": "passes on the second try",
+//!   "criteria": ["Tests pass"],
+//!   "iterations": [
+//!     { "response": "Fixed it", "promise": true, "verdicts": ["FAIL - tests fail"] },
+//!     { "response": "Fixed it properly", "promise": true, "verdicts": ["PASS"] }
+//!   ],
+//!   "expect": {
+//!     "phases": ["Running", "Verifying", "Running", "Verifying", "Implemented"],
+//!     "status": "completed",
+//!     "iterations": 2
+//!   }
+//! }
+//! ```
+
+use std::fmt::Write;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::mpsc;
+
+use crate::adapters::{MOCK_DIR, MOCK_MODEL};
+use crate::config::{Config, ModelConfig};
+use crate::criterion::Criterion;
+use crate::embed::Runner;
+use crate::runner::RunEvent;
+use crate::state::{RunState, RunStatus};
+use crate::thread::PhaseKind;
+
+/// How long a scenario may run before it's abandoned.
+pub const SCENARIO_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Cooldown given to the mock model after a scripted rate limit, so
+/// scenarios that hit one don't wait out the real default.
+const SCENARIO_COOLDOWN_SECS: u64 = 1;
+
+/// Errors from loading or running a scenario.
+#[derive(Debug, Error)]
+pub enum ScenarioError {
+    /// Reading the scenario or writing its scripts failed.
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The scenario file isn't valid.
+    #[error("Invalid scenario: {0}")]
+    Parse(#[from] serde_json::Error),
+
+    /// An expected phase isn't a phase name.
+    #[error("Unknown phase '{0}'")]
+    UnknownPhase(String),
+
+    /// The run didn't finish in time.
+    #[error("Scenario '{0}' did not finish in time")]
+    TimedOut(String),
+
+    /// The run finished differently than expected.
+    #[error("Scenario '{scenario}': expected {what} {expected}, got {actual}")]
+    Mismatch {
+        scenario: String,
+        what: &'static str,
+        expected: String,
+        actual: String,
+    },
+}
+
+/// A scripted run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    /// Name used in failure messages.
+    pub name: String,
+
+    /// Contents of the spec (`PROMPT.md`).
+    #[serde(default = "default_spec")]
+    pub spec: String,
+
+    /// Completion criteria the verifier is asked about, with the same
+    /// annotations as in `PROMPT.md`.
+    #[serde(default)]
+    pub criteria: Vec<String>,
+
+    /// Iteration limit for the run.
+    #[serde(default = "default_max_iterations")]
+    pub max_iterations: usize,
+
+    /// Iterations without progress before the run is stuck (`None` uses the
+    /// config default, 0 disables stuck detection).
+    #[serde(default)]
+    pub stuck_window: Option<usize>,
+
+    /// Iterations in a row without changes before the run is stuck (`None`
+    /// uses the config default, 0 disables no-op detection).
+    #[serde(default)]
+    pub max_noop_iterations: Option<u32>,
+
+    /// What the mock model does each iteration, in order. Iterations past
+    /// the end answer without a promise.
+    #[serde(default)]
+    pub iterations: Vec<ScenarioIteration>,
+
+    /// What the run should look like once it finishes.
+    #[serde(default)]
+    pub expect: Expectations,
+}
+
+impl Default for Scenario {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            spec: default_spec(),
+            criteria: Vec::new(),
+            max_iterations: default_max_iterations(),
+            stuck_window: None,
+            max_noop_iterations: None,
+            iterations: Vec::new(),
+            expect: Expectations::default(),
+        }
+    }
+}
+
+fn default_spec() -> String {
+    "# Scenario\n\nDo the scripted task.\n".into()
+}
+
+fn default_max_iterations() -> usize {
+    10
+}
+
+/// One iteration of a scenario.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioIteration {
+    /// What the model prints.
+    #[serde(default)]
+    pub response: String,
+
+    /// Whether the model emits the completion promise.
+    #[serde(default)]
+    pub promise: bool,
+
+    /// Whether the model reports a rate limit instead of answering.
+    #[serde(default)]
+    pub rate_limit: bool,
+
+    /// How long the model takes to answer, in milliseconds.
+    #[serde(default)]
+    pub delay_ms: u64,
+
+    /// Exit code of the model, if not the default for the response.
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+
+    /// The verifier's verdict on each criterion, in order (e.g., `PASS`,
+    /// `FAIL - no tests`). Missing verdicts count as failures.
+    #[serde(default)]
+    pub verdicts: Vec<String>,
+}
+
+impl ScenarioIteration {
+    /// The mock response file for this iteration.
+    fn response_file(&self, promise: &str) -> String {
+        let mut header = String::new();
+        if self.delay_ms > 0 {
+            let _ = writeln!(header, "delay_ms: {}", self.delay_ms);
+        }
+        if self.rate_limit {
+            header.push_str("rate_limit: true\n");
+        }
+        if self.promise {
+            let _ = writeln!(header, "promise: {promise}");
+        }
+        if let Some(code) = self.exit_code {
+            let _ = writeln!(header, "exit_code: {code}");
+        }
+        if header.is_empty() {
+            self.response.clone()
+        } else {
+            format!("---\n{header}---\n{}", self.response)
+        }
+    }
+
+    /// The mock verification file for this iteration.
+    fn verify_file(&self) -> String {
+        self.verdicts
+            .iter()
+            .enumerate()
+            .fold(String::new(), |mut out, (i, verdict)| {
+                let _ = writeln!(out, "CRITERION {}: {verdict}", i + 1);
+                out
+            })
+    }
+}
+
+/// Expected outcome of a scenario. Unset fields aren't checked.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Expectations {
+    /// Phases the thread passes through, in order (see [`phase_transitions`]).
+    #[serde(default)]
+    pub phases: Option<Vec<String>>,
+
+    /// Final status of the run.
+    #[serde(default)]
+    pub status: Option<RunStatus>,
+
+    /// Iterations the run started.
+    #[serde(default)]
+    pub iterations: Option<usize>,
+}
+
+/// What happened when a scenario ran.
+#[derive(Debug, Clone)]
+pub struct ScenarioOutcome {
+    /// Every event the run emitted.
+    pub events: Vec<RunEvent>,
+    /// Phases the thread passed through.
+    pub phases: Vec<PhaseKind>,
+    /// Final status of the run.
+    pub status: RunStatus,
+    /// Iterations the run started.
+    pub iterations: usize,
+}
+
+impl Scenario {
+    /// Parse a scenario from JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the JSON doesn't describe a scenario.
+    pub fn from_json(json: &str) -> Result<Self, ScenarioError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Load a scenario file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be read or isn't a scenario.
+    pub fn load(path: &Path) -> Result<Self, ScenarioError> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    /// Config running only the mock model, with no verifiers.
+    fn config(&self) -> Config {
+        let mut config = Config {
+            model_priority: vec![MOCK_MODEL.into()],
+            models: vec![ModelConfig {
+                default_cooldown_seconds: SCENARIO_COOLDOWN_SECS,
+                ..ModelConfig::default_for(MOCK_MODEL)
+            }],
+            verifiers: Vec::new(),
+            required_verifiers: Vec::new(),
+            ..Config::default()
+        };
+        if let Some(window) = self.stuck_window {
+            config.stuck_window = window;
+        }
+        if let Some(limit) = self.max_noop_iterations {
+            config.max_noop_iterations = limit;
+        }
+        config
+    }
+
+    /// Write the spec and the mock's scripts into `repo_path`.
+    fn write_scripts(&self, repo_path: &Path, promise: &str) -> std::io::Result<()> {
+        std::fs::write(repo_path.join("PROMPT.md"), &self.spec)?;
+        let mock_dir = repo_path.join(".ralf").join(MOCK_DIR);
+        std::fs::create_dir_all(&mock_dir)?;
+        for (i, iteration) in self.iterations.iter().enumerate() {
+            let n = i + 1;
+            std::fs::write(
+                mock_dir.join(format!("{n}.md")),
+                iteration.response_file(promise),
+            )?;
+            std::fs::write(
+                mock_dir.join(format!("{n}.verify.md")),
+                iteration.verify_file(),
+            )?;
+        }
+        std::fs::write(mock_dir.join("default.md"), "No response scripted.\n")
+    }
+
+    /// Check `outcome` against [`Scenario::expect`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the first expectation the outcome doesn't meet.
+    pub fn check(&self, outcome: &ScenarioOutcome) -> Result<(), ScenarioError> {
+        let mismatch = |what, expected: String, actual: String| ScenarioError::Mismatch {
+            scenario: self.name.clone(),
+            what,
+            expected,
+            actual,
+        };
+
+        if let Some(names) = &self.expect.phases {
+            let expected = names
+                .iter()
+                .map(|name| {
+                    PhaseKind::from_name(name)
+                        .ok_or_else(|| ScenarioError::UnknownPhase(name.clone()))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if expected != outcome.phases {
+                return Err(mismatch(
+                    "phases",
+                    format!("{expected:?}"),
+                    format!("{:?}", outcome.phases),
+                ));
+            }
+        }
+        if let Some(status) = self.expect.status {
+            if status != outcome.status {
+                return Err(mismatch(
+                    "status",
+                    status.to_string(),
+                    outcome.status.to_string(),
+                ));
+            }
+        }
+        if let Some(iterations) = self.expect.iterations {
+            if iterations != outcome.iterations {
+                return Err(mismatch(
+                    "iterations",
+                    iterations.to_string(),
+                    outcome.iterations.to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Run `scenario` in `repo_path` (a scratch directory) and check its
+/// expectations.
+///
+/// `repo_path` is made a git repository if it isn't one, so the stuck
+/// detector can tell when iterations leave the workspace unchanged.
+///
+/// # Errors
+///
+/// Returns an error if the scripts can't be written, the run doesn't finish
+/// within [`SCENARIO_TIMEOUT`], or it doesn't meet the expectations.
+pub async fn run_scenario(
+    scenario: &Scenario,
+    repo_path: &Path,
+) -> Result<ScenarioOutcome, ScenarioError> {
+    let config = scenario.config();
+    scenario.write_scripts(repo_path, &config.completion_promise)?;
+    if !repo_path.join(".git").exists() {
+        tokio::process::Command::new("git")
+            .args(["init", "--quiet"])
+            .current_dir(repo_path)
+            .output()
+            .await?;
+    }
+
+    let criteria = scenario
+        .criteria
+        .iter()
+        .enumerate()
+        .map(|(i, line)| Criterion::parse(i, line))
+        .collect();
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+    let _handle = Runner::builder(config, repo_path)
+        .max_iterations(scenario.max_iterations)
+        .criteria(criteria)
+        .no_verify_cache(true)
+        .events(event_tx)
+        .build()
+        .start();
+
+    // The channel closes once the loop returns
+    let mut events = Vec::new();
+    let collect = async {
+        while let Some(event) = event_rx.recv().await {
+            events.push(event);
+        }
+    };
+    if tokio::time::timeout(SCENARIO_TIMEOUT, collect)
+        .await
+        .is_err()
+    {
+        return Err(ScenarioError::TimedOut(scenario.name.clone()));
+    }
+
+    let state = RunState::load(&repo_path.join(".ralf").join("state.json")).unwrap_or_default();
+    let outcome = ScenarioOutcome {
+        phases: phase_transitions(&events, state.status),
+        iterations: events
+            .iter()
+            .filter(|e| matches!(e, RunEvent::IterationStarted { .. }))
+            .count(),
+        events,
+        status: state.status,
+    };
+    scenario.check(&outcome)?;
+    Ok(outcome)
+}
+
+/// The thread phases a run with these `events` passes through, ending in
+/// `Implemented` if it finished with `status` completed.
+///
+/// Repeats are collapsed, so two iterations in a row without verification
+/// are a single `Running`.
+pub fn phase_transitions(events: &[RunEvent], status: RunStatus) -> Vec<PhaseKind> {
+    let mut phases: Vec<PhaseKind> = Vec::new();
+    let kinds = events.iter().filter_map(|event| match event {
+        RunEvent::IterationStarted { .. } | RunEvent::Resumed { .. } => Some(PhaseKind::Running),
+        RunEvent::VerificationStarted { .. } => Some(PhaseKind::Verifying),
+        RunEvent::Paused { .. } => Some(PhaseKind::Paused),
+        RunEvent::Stuck { .. } => Some(PhaseKind::Stuck),
+        _ => None,
+    });
+    let finished = (status == RunStatus::Completed).then_some(PhaseKind::Implemented);
+    for kind in kinds.chain(finished) {
+        if phases.last() != Some(&kind) {
+            phases.push(kind);
+        }
+    }
+    phases
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_from_json_defaults() {
+        let scenario = Scenario::from_json(r#"{"name": "empty"}"#).unwrap();
+        assert_eq!(scenario.max_iterations, 10);
+        assert!(scenario.iterations.is_empty());
+        assert!(scenario.expect.status.is_none());
+
+        let err = Scenario::from_json(r#"{"name": "typo", "iteratons": []}"#).unwrap_err();
+        assert!(matches!(err, ScenarioError::Parse(_)));
+    }
+
+    #[test]
+    fn test_iteration_files() {
+        let iteration = ScenarioIteration {
+            response: "Done".into(),
+            promise: true,
+            delay_ms: 20,
+            verdicts: vec!["PASS".into(), "FAIL - no docs".into()],
+            ..ScenarioIteration::default()
+        };
+        assert_eq!(
+            iteration.response_file("COMPLETE"),
+            "---\ndelay_ms: 20\npromise: COMPLETE\n---\nDone"
+        );
+        assert_eq!(
+            iteration.verify_file(),
+            "CRITERION 1: PASS\nCRITERION 2: FAIL - no docs\n"
+        );
+
+        let plain = ScenarioIteration {
+            response: "Working".into(),
+            ..ScenarioIteration::default()
+        };
+        assert_eq!(plain.response_file("COMPLETE"), "Working");
+    }
+
+    #[test]
+    fn test_phase_transitions() {
+        let events = vec![
+            RunEvent::IterationStarted {
+                iteration: 1,
+                model: "mock".into(),
+            },
+            RunEvent::IterationStarted {
+                iteration: 2,
+                model: "mock".into(),
+            },
+            RunEvent::VerificationStarted {
+                iteration: 2,
+                model: "mock".into(),
+                implementer: "mock".into(),
+                criteria_count: 1,
+            },
+        ];
+        assert_eq!(
+            phase_transitions(&events, RunStatus::Completed),
+            vec![
+                PhaseKind::Running,
+                PhaseKind::Verifying,
+                PhaseKind::Implemented
+            ]
+        );
+        assert_eq!(
+            phase_transitions(&events, RunStatus::Failed),
+            vec![PhaseKind::Running, PhaseKind::Verifying]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_scenario_verifies_criteria() {
+        let temp = TempDir::new().unwrap();
+        let scenario = Scenario::from_json(
+            r#"{
+                "name": "passes on the second try",
+                "criteria": ["Tests pass"],
+                "iterations": [
+                    {"response": "Fixed it", "promise": true, "verdicts": ["FAIL - tests fail"]},
+                    {"response": "Fixed it properly", "promise": true, "verdicts": ["PASS"]}
+                ],
+                "expect": {
+                    "phases": ["Running", "Verifying", "Running", "Verifying", "Implemented"],
+                    "status": "completed"
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let outcome = run_scenario(&scenario, temp.path()).await.unwrap();
+        assert!(outcome.events.iter().any(|e| matches!(
+            e,
+            RunEvent::CriterionVerified { passed: false, reason: Some(reason), .. }
+                if reason == "tests fail"
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_run_scenario_optional_criteria() {
+        let temp = TempDir::new().unwrap();
+        let scenario = Scenario::from_json(
+            r#"{
+                "name": "optional criteria don't block",
+                "criteria": ["Tests pass", "Docs updated #docs (optional)"],
+                "iterations": [
+                    {"response": "Done", "promise": true, "verdicts": ["PASS", "FAIL - no docs"]}
+                ],
+                "expect": {"status": "completed"}
+            }"#,
+        )
+        .unwrap();
+
+        let outcome = run_scenario(&scenario, temp.path()).await.unwrap();
+        assert!(outcome.events.iter().any(|e| matches!(
+            e,
+            RunEvent::CriterionVerified { id, required: false, passed: false, .. } if id == "c2"
+        )));
+        assert!(outcome.events.iter().any(|e| matches!(
+            e,
+            RunEvent::Completed { reason, .. }
+                if reason == "All required criteria verified (optional not met: c2)"
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_run_scenario_keeps_completion_report() {
+        let temp = TempDir::new().unwrap();
+        let scenario = Scenario::from_json(
+            r#"{
+                "name": "promise with a report",
+                "iterations": [{
+                    "response": "Done\n<promise>COMPLETE\n{\"summary\": \"Added it\", \"follow_up\": [\"Docs\"]}\n</promise>"
+                }],
+                "expect": {"status": "completed"}
+            }"#,
+        )
+        .unwrap();
+
+        let outcome = run_scenario(&scenario, temp.path()).await.unwrap();
+        assert!(outcome.events.iter().any(|e| matches!(
+            e,
+            RunEvent::CompletionReported { report, .. } if report.follow_up == ["Docs"]
+        )));
+        let state = RunState::load(&temp.path().join(".ralf/state.json")).unwrap();
+        assert_eq!(
+            state.completion_report.unwrap().summary.as_deref(),
+            Some("Added it")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_scenario_reports_mismatch() {
+        let temp = TempDir::new().unwrap();
+        let scenario = Scenario {
+            name: "never promises".into(),
+            max_iterations: 2,
+            stuck_window: Some(0),
+            expect: Expectations {
+                status: Some(RunStatus::Completed),
+                ..Expectations::default()
+            },
+            ..Scenario::default()
+        };
+
+        let err = run_scenario(&scenario, temp.path()).await.unwrap_err();
+        assert!(
+            matches!(err, ScenarioError::Mismatch { what: "status", ref actual, .. } if actual == "failed"),
+            "{err}"
+        );
+    }
+}
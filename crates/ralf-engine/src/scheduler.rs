@@ -0,0 +1,206 @@
+//! Model arbitration between runs going at the same time.
+//!
+//! With worktree isolation, the TUI can run several threads' loops side by
+//! side. Left alone, they would all invoke the same model CLI at once and
+//! run into its rate limits together. Runs given the same [`Scheduler`]
+//! instead queue for each model, first come first served, within the
+//! limits of the config's `concurrency` section. They also share the
+//! repository's [`RunLock`], which would otherwise let only one of them
+//! start.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex, Weak};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::lock::{spawn_heartbeat, LockError, RunLock};
+
+/// How many model invocations may be in flight at once, across runs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConcurrencyConfig {
+    /// Most invocations of any models at once (`None` = one per model).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_invocations: Option<usize>,
+
+    /// Most invocations of one model at once.
+    #[serde(default = "default_per_model")]
+    pub per_model: usize,
+}
+
+fn default_per_model() -> usize {
+    1
+}
+
+impl Default for ConcurrencyConfig {
+    fn default() -> Self {
+        Self {
+            max_invocations: None,
+            per_model: default_per_model(),
+        }
+    }
+}
+
+impl ConcurrencyConfig {
+    /// Whether the limits are the defaults.
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Hands out model invocation slots to the runs of one repository.
+#[derive(Debug)]
+pub struct Scheduler {
+    per_model: usize,
+    global: Option<Arc<Semaphore>>,
+    models: Mutex<HashMap<String, Arc<Semaphore>>>,
+    lock: tokio::sync::Mutex<Weak<RunLock>>,
+}
+
+/// A model invocation slot, given back when dropped.
+#[derive(Debug)]
+pub struct InvocationSlot {
+    _model: OwnedSemaphorePermit,
+    _global: Option<OwnedSemaphorePermit>,
+}
+
+impl Scheduler {
+    /// A scheduler enforcing `config`'s limits. Limits of 0 are taken as 1.
+    pub fn new(config: &ConcurrencyConfig) -> Self {
+        Self {
+            per_model: config.per_model.max(1),
+            global: config
+                .max_invocations
+                .map(|max| Arc::new(Semaphore::new(max.max(1)))),
+            models: Mutex::new(HashMap::new()),
+            lock: tokio::sync::Mutex::new(Weak::new()),
+        }
+    }
+
+    /// The queue for `model`.
+    fn model_semaphore(&self, model: &str) -> Arc<Semaphore> {
+        let mut models = self
+            .models
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        Arc::clone(
+            models
+                .entry(model.to_string())
+                .or_insert_with(|| Arc::new(Semaphore::new(self.per_model))),
+        )
+    }
+
+    /// A slot for `model` if one is free now.
+    pub fn try_acquire(&self, model: &str) -> Option<InvocationSlot> {
+        let model = self.model_semaphore(model).try_acquire_owned().ok()?;
+        let global = match &self.global {
+            Some(global) => Some(Arc::clone(global).try_acquire_owned().ok()?),
+            None => None,
+        };
+        Some(InvocationSlot {
+            _model: model,
+            _global: global,
+        })
+    }
+
+    /// Wait in line for a slot for `model`. The model's own queue comes
+    /// first, so a run waiting on a busy model doesn't hold a global slot
+    /// another model could use.
+    pub async fn acquire(&self, model: &str) -> InvocationSlot {
+        let model = self
+            .model_semaphore(model)
+            .acquire_owned()
+            .await
+            .expect("Scheduler semaphores are never closed");
+        let global = match &self.global {
+            Some(global) => Some(
+                Arc::clone(global)
+                    .acquire_owned()
+                    .await
+                    .expect("Scheduler semaphores are never closed"),
+            ),
+            None => None,
+        };
+        InvocationSlot {
+            _model: model,
+            _global: global,
+        }
+    }
+
+    /// The run lock for `ralf_dir`, shared with the scheduler's other runs
+    /// while any of them holds it, and acquired (with its heartbeat) by
+    /// the first.
+    ///
+    /// # Errors
+    ///
+    /// Returns the lock error if another process holds the lock.
+    pub async fn run_lock(
+        &self,
+        ralf_dir: &Path,
+        run_id: &str,
+        force: bool,
+    ) -> Result<Arc<RunLock>, LockError> {
+        let mut shared = self.lock.lock().await;
+        if let Some(lock) = shared.upgrade() {
+            return Ok(lock);
+        }
+        let (dir, run_id) = (ralf_dir.to_path_buf(), run_id.to_string());
+        let lock =
+            tokio::task::spawn_blocking(move || RunLock::acquire(&dir, Some(&run_id), force))
+                .await
+                .unwrap_or_else(|e| Err(LockError::Io(std::io::Error::other(e))))?;
+        let lock = Arc::new(lock);
+        spawn_heartbeat(&lock);
+        *shared = Arc::downgrade(&lock);
+        Ok(lock)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_slots() {
+        let scheduler = Scheduler::new(&ConcurrencyConfig {
+            max_invocations: Some(2),
+            per_model: 1,
+        });
+        let claude = scheduler.try_acquire("claude").unwrap();
+        assert!(scheduler.try_acquire("claude").is_none());
+        let codex = scheduler.try_acquire("codex").unwrap();
+        // Both global slots are taken
+        assert!(scheduler.try_acquire("gemini").is_none());
+
+        drop(codex);
+        let gemini = scheduler.acquire("gemini").await;
+        drop(claude);
+        let _claude = scheduler.acquire("claude").await;
+        drop(gemini);
+        assert!(scheduler.try_acquire("codex").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_run_lock_is_shared() {
+        let temp = tempfile::tempdir().unwrap();
+        let scheduler = Scheduler::new(&ConcurrencyConfig::default());
+        let first = scheduler
+            .run_lock(temp.path(), "run-1", false)
+            .await
+            .unwrap();
+        let second = scheduler
+            .run_lock(temp.path(), "run-2", false)
+            .await
+            .unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(first.info().run_id.as_deref(), Some("run-1"));
+
+        drop((first, second));
+        let third = scheduler
+            .run_lock(temp.path(), "run-3", false)
+            .await
+            .unwrap();
+        assert_eq!(third.info().run_id.as_deref(), Some("run-3"));
+    }
+}
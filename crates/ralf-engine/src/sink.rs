@@ -0,0 +1,183 @@
+//! Saving a run's files without losing track of the ones that failed.
+//!
+//! A run keeps going when state, cooldowns, or changelog entries can't be
+//! written, but its history is then incomplete. [`PersistenceSink`] retries
+//! each save a few times, remembers what still failed, and reports the
+//! first failure of each file so the CLI and TUI can tell the user.
+
+use std::fmt::Display;
+use std::time::Duration;
+
+/// Times a save is attempted before it counts as failed.
+pub const SAVE_ATTEMPTS: u32 = 3;
+
+/// Pause between attempts, for transient errors like a file briefly locked
+/// by a virus scanner or an editor.
+const RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// A file that couldn't be saved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PersistenceFailure {
+    /// What was being saved ("run state", "cooldowns").
+    pub what: String,
+    /// The latest error.
+    pub error: String,
+    /// Number of saves of it that failed.
+    pub count: u32,
+}
+
+impl PersistenceFailure {
+    /// Warning shown to the user.
+    pub fn warning(&self) -> String {
+        save_warning(&self.what, &self.error)
+    }
+}
+
+/// Warning for a file that couldn't be saved, as the CLI and TUI show it.
+pub fn save_warning(what: &str, error: &str) -> String {
+    format!("Failed to save {what}: {error}; run history may be incomplete")
+}
+
+/// Tracks a run's saves.
+#[derive(Debug, Clone, Default)]
+pub struct PersistenceSink {
+    failures: Vec<PersistenceFailure>,
+}
+
+impl PersistenceSink {
+    /// Create a sink with no failures.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run `save`, retrying if it fails. Returns the failure the first time
+    /// `what` can't be saved, so it is reported once rather than on every
+    /// iteration.
+    ///
+    /// Blocks between attempts; async callers run [`retry`] off the
+    /// runtime and pass its result to [`record`](Self::record).
+    pub fn save<E: Display>(
+        &mut self,
+        what: &str,
+        save: impl FnMut() -> Result<(), E>,
+    ) -> Option<PersistenceFailure> {
+        self.record(what, retry(save))
+    }
+
+    /// Record the result of saving `what`. Returns the failure the first
+    /// time `what` fails.
+    pub fn record(&mut self, what: &str, result: Result<(), String>) -> Option<PersistenceFailure> {
+        let error = result.err()?;
+        tracing::warn!("Failed to save {what}: {error}");
+        if let Some(failure) = self.failures.iter_mut().find(|f| f.what == what) {
+            failure.error = error;
+            failure.count += 1;
+            return None;
+        }
+        let failure = PersistenceFailure {
+            what: what.to_string(),
+            error,
+            count: 1,
+        };
+        self.failures.push(failure.clone());
+        Some(failure)
+    }
+
+    /// Whether any save failed, leaving the run's history incomplete.
+    pub fn is_degraded(&self) -> bool {
+        !self.failures.is_empty()
+    }
+
+    /// Files that failed to save, in the order they first failed.
+    pub fn failures(&self) -> &[PersistenceFailure] {
+        &self.failures
+    }
+
+    /// One-line summary of the failures, if there were any:
+    /// "Run history may be incomplete: failed to save run state (2x), cooldowns".
+    pub fn summary(&self) -> Option<String> {
+        if self.failures.is_empty() {
+            return None;
+        }
+        let failed = self
+            .failures
+            .iter()
+            .map(|f| {
+                if f.count > 1 {
+                    format!("{} ({}x)", f.what, f.count)
+                } else {
+                    f.what.clone()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        Some(format!(
+            "Run history may be incomplete: failed to save {failed}"
+        ))
+    }
+}
+
+/// Run `save` up to [`SAVE_ATTEMPTS`] times, returning the last error.
+pub fn retry<E: Display>(mut save: impl FnMut() -> Result<(), E>) -> Result<(), String> {
+    let mut attempt = 1;
+    loop {
+        match save() {
+            Ok(()) => return Ok(()),
+            Err(e) if attempt >= SAVE_ATTEMPTS => return Err(e.to_string()),
+            Err(_) => {
+                attempt += 1;
+                std::thread::sleep(RETRY_DELAY);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_until_success() {
+        let mut calls = 0;
+        let result = retry(|| {
+            calls += 1;
+            if calls < SAVE_ATTEMPTS {
+                Err("busy")
+            } else {
+                Ok(())
+            }
+        });
+        assert_eq!(result, Ok(()));
+        assert_eq!(calls, SAVE_ATTEMPTS);
+
+        let mut calls = 0;
+        let result = retry(|| {
+            calls += 1;
+            Err(format!("attempt {calls}"))
+        });
+        assert_eq!(result, Err(format!("attempt {SAVE_ATTEMPTS}")));
+    }
+
+    #[test]
+    fn test_sink_reports_each_file_once() {
+        let mut sink = PersistenceSink::new();
+        assert!(sink.save("cooldowns", || Ok::<(), String>(())).is_none());
+        assert!(!sink.is_degraded());
+        assert_eq!(sink.summary(), None);
+
+        let failure = sink.record("run state", Err("disk full".into())).unwrap();
+        assert_eq!(
+            failure.warning(),
+            "Failed to save run state: disk full; run history may be incomplete"
+        );
+        assert!(sink.record("run state", Err("still full".into())).is_none());
+        assert!(sink.record("cooldowns", Err("read-only".into())).is_some());
+
+        assert!(sink.is_degraded());
+        assert_eq!(sink.failures()[0].error, "still full");
+        assert_eq!(
+            sink.summary().unwrap(),
+            "Run history may be incomplete: failed to save run state (2x), cooldowns"
+        );
+    }
+}
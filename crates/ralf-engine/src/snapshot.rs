@@ -0,0 +1,237 @@
+//! What a run started with.
+//!
+//! Debugging a run after the fact goes wrong when `PROMPT.md` or the config
+//! has changed since. So when a run starts, the runner copies both into
+//! `.ralf/runs/<run_id>/`, as `spec.md` and `config.json` (the names
+//! `ralf archive --import` uses), and records the versions of ralf, git and
+//! each model CLI in `manifest.json`. A resumed run keeps the snapshot
+//! taken when it first started.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::discovery::KNOWN_MODELS;
+use crate::platform;
+
+/// The spec the run started with, in its directory.
+pub const SPEC_SNAPSHOT_FILE: &str = "spec.md";
+
+/// The config the run started with, in its directory.
+pub const CONFIG_SNAPSHOT_FILE: &str = "config.json";
+
+/// Tool versions and such, in the run's directory.
+pub const RUN_MANIFEST_FILE: &str = "manifest.json";
+
+/// Longest a `--version` call may take.
+const VERSION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A model's CLI, as the run found it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelVersion {
+    /// The program the model is invoked with.
+    pub command: String,
+    /// What `<command> --version` printed, if it is a known model CLI that
+    /// answered.
+    pub version: Option<String>,
+}
+
+/// What a run started with, besides its spec and config.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunManifest {
+    /// The run.
+    pub run_id: String,
+    /// When the snapshot was taken.
+    pub started_at: DateTime<Utc>,
+    /// Hash of the spec (see [`hash_prompt`](crate::hash_prompt)).
+    pub prompt_hash: String,
+    /// ralf version running the loop.
+    pub ralf_version: String,
+    /// Output of `git --version`, if git could be run.
+    pub git_version: Option<String>,
+    /// Each configured model's CLI, by model name.
+    pub models: BTreeMap<String, ModelVersion>,
+}
+
+impl RunManifest {
+    /// Path of the manifest for the run in `run_dir`.
+    pub fn path(run_dir: &Path) -> PathBuf {
+        run_dir.join(RUN_MANIFEST_FILE)
+    }
+
+    /// The manifest of the run in `run_dir`, if it has a readable one.
+    pub fn load(run_dir: &Path) -> Option<Self> {
+        let json = std::fs::read_to_string(Self::path(run_dir)).ok()?;
+        serde_json::from_str(&json).ok()
+    }
+}
+
+/// A run's spec, config and manifest, as saved in its directory.
+#[derive(Debug, Clone)]
+pub struct RunSnapshot {
+    /// The spec, as read from `PROMPT.md`.
+    pub spec: String,
+    /// The config the run uses.
+    pub config: Config,
+    /// Tool versions and such.
+    pub manifest: RunManifest,
+}
+
+impl RunSnapshot {
+    /// Snapshot run `run_id`, asking git and each model CLI in `config`
+    /// for its version.
+    ///
+    /// Only the CLIs ralf knows are asked: any other command could be a
+    /// script that does its work whatever its arguments.
+    pub async fn capture(run_id: &str, spec: &str, config: &Config) -> Self {
+        let mut models = BTreeMap::new();
+        for model in &config.models {
+            let command = model
+                .command_argv
+                .first()
+                .cloned()
+                .unwrap_or_else(|| model.name.clone());
+            let version = if is_known_cli(&command) {
+                tool_version(&command).await
+            } else {
+                None
+            };
+            models.insert(model.name.clone(), ModelVersion { command, version });
+        }
+        Self {
+            spec: spec.to_string(),
+            config: config.clone(),
+            manifest: RunManifest {
+                run_id: run_id.to_string(),
+                started_at: Utc::now(),
+                prompt_hash: crate::runner::hash_prompt(spec),
+                ralf_version: env!("CARGO_PKG_VERSION").to_string(),
+                git_version: tool_version("git").await,
+                models,
+            },
+        }
+    }
+
+    /// Save the snapshot in `run_dir`, the manifest last so that a
+    /// manifest means the snapshot is whole.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a file can't be written.
+    pub fn save(&self, run_dir: &Path) -> std::io::Result<()> {
+        std::fs::create_dir_all(run_dir)?;
+        std::fs::write(run_dir.join(SPEC_SNAPSHOT_FILE), &self.spec)?;
+        std::fs::write(
+            run_dir.join(CONFIG_SNAPSHOT_FILE),
+            serde_json::to_vec_pretty(&self.config)?,
+        )?;
+        std::fs::write(
+            RunManifest::path(run_dir),
+            serde_json::to_vec_pretty(&self.manifest)?,
+        )
+    }
+
+    /// Whether the run in `run_dir` already has a snapshot.
+    pub fn exists(run_dir: &Path) -> bool {
+        RunManifest::path(run_dir).is_file()
+    }
+}
+
+/// Whether `command` runs one of the model CLIs ralf knows.
+fn is_known_cli(command: &str) -> bool {
+    Path::new(command)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .is_some_and(|stem| KNOWN_MODELS.contains(&stem))
+}
+
+/// The first line `<program> --version` prints, if it succeeds in time.
+async fn tool_version(program: &str) -> Option<String> {
+    let output = platform::tokio_command(program, &["--version".into()])
+        .stdin(std::process::Stdio::null())
+        .kill_on_drop(true)
+        .output();
+    let output = tokio::time::timeout(VERSION_TIMEOUT, output)
+        .await
+        .ok()?
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    stdout
+        .lines()
+        .chain(stderr.lines())
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ModelConfig;
+
+    fn model(name: &str, command: &str) -> ModelConfig {
+        ModelConfig {
+            name: name.into(),
+            command_argv: vec![command.into(), "-p".into()],
+            timeout_seconds: 30,
+            rate_limit_patterns: Vec::new(),
+            default_cooldown_seconds: 60,
+            max_context_tokens: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_round_trip() {
+        let temp = tempfile::tempdir().unwrap();
+        let run_dir = temp.path().join("runs").join("run-1");
+        let config = Config {
+            models: vec![model("fake", "./scripts/model.sh")],
+            ..Config::default()
+        };
+        assert!(!RunSnapshot::exists(&run_dir));
+
+        let snapshot = RunSnapshot::capture("run-1", "## Criteria\n- Works\n", &config).await;
+        // Commands ralf doesn't know aren't run
+        assert_eq!(
+            snapshot.manifest.models["fake"],
+            ModelVersion {
+                command: "./scripts/model.sh".into(),
+                version: None,
+            }
+        );
+        snapshot.save(&run_dir).unwrap();
+        assert!(RunSnapshot::exists(&run_dir));
+
+        assert_eq!(
+            std::fs::read_to_string(run_dir.join(SPEC_SNAPSHOT_FILE)).unwrap(),
+            "## Criteria\n- Works\n"
+        );
+        let saved: Config =
+            serde_json::from_slice(&std::fs::read(run_dir.join(CONFIG_SNAPSHOT_FILE)).unwrap())
+                .unwrap();
+        assert_eq!(saved.models[0].command_argv[0], "./scripts/model.sh");
+        let manifest = RunManifest::load(&run_dir).unwrap();
+        assert_eq!(manifest, snapshot.manifest);
+        assert_eq!(manifest.ralf_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(
+            manifest.prompt_hash,
+            crate::runner::hash_prompt("## Criteria\n- Works\n")
+        );
+    }
+
+    #[test]
+    fn test_known_cli() {
+        assert!(is_known_cli("claude"));
+        assert!(is_known_cli("/usr/local/bin/codex"));
+        assert!(!is_known_cli("./scripts/model.sh"));
+        assert!(!is_known_cli("sh"));
+    }
+}
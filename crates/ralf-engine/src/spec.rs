@@ -0,0 +1,228 @@
+//! Linting PROMPT.md specs before they are finalized.
+//!
+//! [`lint`] catches the problems that make autonomous runs go badly: no
+//! criteria to verify, criteria no verifier can check, no promise for the
+//! model to emit when it's done, and specs long enough to crowd out the
+//! model's context. Errors block finalizing; warnings are advice.
+
+use std::collections::HashMap;
+
+use crate::chat::extract_draft_promise;
+use crate::context::estimate_tokens;
+use crate::{criteria_with_lines, is_criteria_header};
+
+/// Specs estimated above this many tokens get a warning.
+pub const MAX_SPEC_TOKENS: usize = 8_000;
+
+/// Phrases that describe a quality instead of a checkable outcome.
+const VAGUE_PHRASES: &[&str] = &[
+    "works well",
+    "work well",
+    "works correctly",
+    "works properly",
+    "properly",
+    "as expected",
+    "user-friendly",
+    "user friendly",
+    "intuitive",
+    "robust",
+    "clean code",
+    "looks good",
+    "looks nice",
+    "high quality",
+    "high-quality",
+    "performant",
+    "efficient",
+    "reasonable",
+    "appropriate",
+];
+
+/// How serious a diagnostic is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The spec can't be finalized until this is fixed.
+    Error,
+    /// Likely to cause a poor run, but allowed.
+    Warning,
+}
+
+/// A problem found in a spec.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpecDiagnostic {
+    /// How serious the problem is.
+    pub severity: Severity,
+    /// What is wrong and how to fix it.
+    pub message: String,
+    /// 1-based line the problem is on, if it has one.
+    pub line: Option<usize>,
+}
+
+impl SpecDiagnostic {
+    fn error(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            message: message.into(),
+            line: None,
+        }
+    }
+
+    fn warning(message: impl Into<String>, line: Option<usize>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            message: message.into(),
+            line,
+        }
+    }
+
+    /// Whether this diagnostic blocks finalizing.
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+/// Check a spec for problems, errors first.
+pub fn lint(prompt: &str) -> Vec<SpecDiagnostic> {
+    let mut diagnostics = Vec::new();
+
+    if extract_draft_promise(prompt).is_none() {
+        diagnostics.push(SpecDiagnostic::error(
+            "Missing <promise>...</promise> tag: tell the model what to output when done",
+        ));
+    }
+
+    let criteria = criteria_with_lines(prompt);
+    if criteria.is_empty() {
+        let has_section = prompt.lines().any(|line| {
+            let trimmed = line.trim();
+            trimmed.starts_with("## ")
+                && is_criteria_header(&trimmed.trim_start_matches('#').trim().to_lowercase())
+        });
+        diagnostics.push(SpecDiagnostic::error(if has_section {
+            "No completion criteria found: add one bullet per check to the criteria section"
+        } else {
+            "No completion criteria found: add a `## Completion Criteria` section with one bullet per check"
+        }));
+    }
+
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for (number, (line, criterion)) in criteria.iter().enumerate() {
+        let number = number + 1;
+        let normalized = normalize(criterion);
+
+        if let Some(phrase) = VAGUE_PHRASES
+            .iter()
+            .find(|p| contains_phrase(&normalized, p))
+        {
+            diagnostics.push(SpecDiagnostic::warning(
+                format!(
+                    "Criterion {number} is hard to verify (\"{phrase}\"): describe an observable \
+                     result, such as a command that succeeds or a test that passes"
+                ),
+                Some(*line),
+            ));
+        }
+
+        if let Some(first) = seen.get(&normalized) {
+            diagnostics.push(SpecDiagnostic::warning(
+                format!("Criterion {number} duplicates criterion {first}"),
+                Some(*line),
+            ));
+        } else {
+            seen.insert(normalized, number);
+        }
+    }
+
+    let tokens = estimate_tokens(prompt);
+    if tokens > MAX_SPEC_TOKENS {
+        diagnostics.push(SpecDiagnostic::warning(
+            format!(
+                "Spec is long (~{tokens} tokens, over {MAX_SPEC_TOKENS}): it takes context away \
+                 from the code; move background material to files the model can read"
+            ),
+            None,
+        ));
+    }
+
+    diagnostics.sort_by_key(|d| d.severity);
+    diagnostics
+}
+
+/// Lowercase with whitespace collapsed and trailing punctuation removed.
+fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_end_matches(['.', '!', ';'])
+        .to_lowercase()
+}
+
+/// Whether `text` contains `phrase` as whole words.
+fn contains_phrase(text: &str, phrase: &str) -> bool {
+    text.match_indices(phrase).any(|(at, _)| {
+        let before = text[..at].chars().next_back();
+        let after = text[at + phrase.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROMISE: &str = "\n<promise>COMPLETE</promise>\n";
+
+    fn messages(prompt: &str) -> Vec<String> {
+        lint(prompt).into_iter().map(|d| d.message).collect()
+    }
+
+    #[test]
+    fn test_lint_clean_spec() {
+        let spec = format!(
+            "# Task\n\n## Completion Criteria\n- [ ] `cargo test` passes\n- [ ] README lists the flag\n{PROMISE}"
+        );
+        assert!(lint(&spec).is_empty());
+    }
+
+    #[test]
+    fn test_lint_errors() {
+        let diagnostics = lint("# Task\n\nJust prose.");
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics.iter().all(SpecDiagnostic::is_error));
+        assert!(diagnostics[0].message.contains("promise"));
+        assert!(diagnostics[1].message.contains("## Completion Criteria"));
+
+        let empty_section = format!("# Task\n\n## Acceptance Criteria\n\nTBD\n{PROMISE}");
+        assert_eq!(
+            messages(&empty_section),
+            vec!["No completion criteria found: add one bullet per check to the criteria section"]
+        );
+    }
+
+    #[test]
+    fn test_lint_warnings() {
+        let spec = format!(
+            "# Task\n\n## Criteria\n- The UI works well\n- Tests pass.\n- tests  pass\n\
+             - Inefficiently named files are renamed\n{PROMISE}"
+        );
+        let diagnostics = lint(&spec);
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics.iter().all(|d| !d.is_error()));
+        assert!(diagnostics[0]
+            .message
+            .starts_with("Criterion 1 is hard to verify"));
+        assert_eq!(diagnostics[0].line, Some(4));
+        assert_eq!(diagnostics[1].message, "Criterion 3 duplicates criterion 2");
+        assert_eq!(diagnostics[1].line, Some(6));
+    }
+
+    #[test]
+    fn test_lint_long_spec() {
+        let spec = format!(
+            "# Task\n\n{}\n\n## Criteria\n- Tests pass\n{PROMISE}",
+            "background ".repeat(MAX_SPEC_TOKENS)
+        );
+        let diagnostics = lint(&spec);
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].message.starts_with("Spec is long"));
+    }
+}
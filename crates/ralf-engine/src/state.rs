@@ -1,12 +1,23 @@
 //! State management for ralf engine.
 //!
-//! This module handles run state persistence and cooldown tracking.
+//! This module handles run state persistence and cooldown tracking, and
+//! the crash-safe file writes all of ralf's persistence goes through: every
+//! file is written to a temporary file and renamed into place, with the
+//! previous version kept as `<file>.bak`. [`load_or_recover`] falls back to
+//! that backup when a file is corrupt and says so, instead of starting
+//! over from empty state.
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::Path;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use crate::clock::Clock;
+use crate::completion::CompletionReport;
+
 /// Current run state.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct RunState {
@@ -27,6 +38,39 @@ pub struct RunState {
 
     /// When the run ended (Unix timestamp).
     pub ended_at: Option<u64>,
+
+    /// Number of iterations that ran to completion (resume point).
+    #[serde(default)]
+    pub completed_iterations: u64,
+
+    /// SHA256 hash of the prompt the run was started with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_hash: Option<String>,
+
+    /// Some of the run's files failed to save, so its history may be
+    /// incomplete.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub degraded: bool,
+
+    /// The report that came with the completing iteration's promise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completion_report: Option<CompletionReport>,
+
+    /// The model's question the run is waiting on an answer to. Kept
+    /// until answered, so a resumed run asks it again.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending_question: Option<PendingQuestion>,
+}
+
+/// A question a model asked with `<promise>NEEDS_INPUT: ...</promise>`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingQuestion {
+    /// Iteration that asked it.
+    pub iteration: u64,
+    /// The question.
+    pub question: String,
+    /// When it was asked (Unix timestamp).
+    pub asked_at: u64,
 }
 
 /// Run status.
@@ -74,19 +118,38 @@ impl RunState {
             std::fs::create_dir_all(parent).map_err(StateError::Io)?;
         }
         let content = serde_json::to_string_pretty(self).map_err(StateError::Serialize)?;
-        std::fs::write(path, content).map_err(StateError::Io)
+        atomic_write(path, content.as_bytes()).map_err(StateError::Io)
     }
 
     /// Start a new run.
     pub fn start_run(&mut self) -> String {
         let run_id = generate_run_id();
-        self.run_id = Some(run_id.clone());
+        self.start_run_with_id(&run_id);
+        run_id
+    }
+
+    /// Start a new run with a caller-provided run ID.
+    pub fn start_run_with_id(&mut self, run_id: &str) {
+        self.run_id = Some(run_id.to_string());
         self.iteration = 0;
+        self.completed_iterations = 0;
         self.status = RunStatus::Running;
         self.last_model_index = 0;
         self.started_at = Some(current_timestamp());
         self.ended_at = None;
-        run_id
+        self.prompt_hash = None;
+        self.degraded = false;
+        self.pending_question = None;
+    }
+
+    /// Resume an interrupted run at its last completed iteration.
+    ///
+    /// Any iteration that was in flight when the run stopped is discarded
+    /// and will be re-run.
+    pub fn resume(&mut self) {
+        self.iteration = self.completed_iterations;
+        self.status = RunStatus::Running;
+        self.ended_at = None;
     }
 
     /// Increment iteration counter.
@@ -124,6 +187,10 @@ pub struct Cooldowns {
     /// Per-model cooldown entries.
     #[serde(flatten)]
     pub entries: HashMap<String, CooldownEntry>,
+
+    /// Time source (default: the system clock).
+    #[serde(skip)]
+    clock: Option<Arc<dyn Clock>>,
 }
 
 /// A single cooldown entry for a model.
@@ -137,6 +204,11 @@ pub struct CooldownEntry {
 
     /// When the cooldown was observed.
     pub observed_at: u64,
+
+    /// The model's own word on when the limit resets (e.g. "resets at
+    /// 3:00 PM"), when `cooldown_until` was parsed from it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reset_hint: Option<String>,
 }
 
 impl Cooldowns {
@@ -148,7 +220,10 @@ impl Cooldowns {
         let content = std::fs::read_to_string(path).map_err(StateError::Io)?;
         let entries: HashMap<String, CooldownEntry> =
             serde_json::from_str(&content).map_err(StateError::Parse)?;
-        Ok(Self { entries })
+        Ok(Self {
+            entries,
+            clock: None,
+        })
     }
 
     /// Save cooldowns to a file.
@@ -157,20 +232,42 @@ impl Cooldowns {
             std::fs::create_dir_all(parent).map_err(StateError::Io)?;
         }
         let content = serde_json::to_string_pretty(&self.entries).map_err(StateError::Serialize)?;
-        std::fs::write(path, content).map_err(StateError::Io)
+        atomic_write(path, content.as_bytes()).map_err(StateError::Io)
+    }
+
+    /// Replace the entries with the file's, so edits made by another
+    /// process (e.g. `ralf cooldown clear`) aren't overwritten. Keeps the
+    /// current entries if the file can't be read.
+    pub fn reload(&mut self, path: &Path) {
+        if let Ok(on_disk) = Self::load(path) {
+            self.entries = on_disk.entries;
+        }
+    }
+
+    /// Tell the time with `clock` instead of the system clock.
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = Some(clock);
+    }
+
+    /// The current time, as cooldowns are measured against it.
+    pub fn now(&self) -> u64 {
+        self.clock
+            .as_ref()
+            .map_or_else(current_timestamp, |clock| clock.now())
     }
 
     /// Check if a model is in cooldown.
     pub fn is_cooling(&self, model: &str) -> bool {
+        let now = self.now();
         self.entries
             .get(model)
-            .is_some_and(|entry| entry.cooldown_until > current_timestamp())
+            .is_some_and(|entry| entry.cooldown_until > now)
     }
 
     /// Get remaining cooldown time in seconds for a model.
     pub fn remaining_seconds(&self, model: &str) -> Option<u64> {
         self.entries.get(model).and_then(|entry| {
-            let now = current_timestamp();
+            let now = self.now();
             if entry.cooldown_until > now {
                 Some(entry.cooldown_until - now)
             } else {
@@ -181,26 +278,47 @@ impl Cooldowns {
 
     /// Set cooldown for a model.
     pub fn set_cooldown(&mut self, model: &str, duration_seconds: u64, reason: &str) {
-        let now = current_timestamp();
+        let now = self.now();
         self.entries.insert(
             model.to_string(),
             CooldownEntry {
                 cooldown_until: now + duration_seconds,
                 reason: reason.to_string(),
                 observed_at: now,
+                reset_hint: None,
+            },
+        );
+    }
+
+    /// Set cooldown for a model until a known reset time.
+    pub fn set_cooldown_until(
+        &mut self,
+        model: &str,
+        until: u64,
+        reason: &str,
+        reset_hint: Option<String>,
+    ) {
+        let observed_at = self.now();
+        self.entries.insert(
+            model.to_string(),
+            CooldownEntry {
+                cooldown_until: until,
+                reason: reason.to_string(),
+                observed_at,
+                reset_hint,
             },
         );
     }
 
     /// Clear expired cooldowns.
     pub fn clear_expired(&mut self) {
-        let now = current_timestamp();
+        let now = self.now();
         self.entries.retain(|_, entry| entry.cooldown_until > now);
     }
 
     /// Get the earliest cooldown expiry time.
     pub fn earliest_expiry(&self) -> Option<u64> {
-        let now = current_timestamp();
+        let now = self.now();
         self.entries
             .values()
             .filter(|e| e.cooldown_until > now)
@@ -210,7 +328,7 @@ impl Cooldowns {
 
     /// Get all models currently in cooldown.
     pub fn cooling_models(&self) -> Vec<&str> {
-        let now = current_timestamp();
+        let now = self.now();
         self.entries
             .iter()
             .filter(|(_, entry)| entry.cooldown_until > now)
@@ -219,6 +337,132 @@ impl Cooldowns {
     }
 }
 
+/// Path of the backup kept of `path`'s previous version.
+pub fn backup_path(path: &Path) -> PathBuf {
+    with_suffix(path, "bak")
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{suffix}"));
+    PathBuf::from(name)
+}
+
+/// Write `content` to `path` so that a crash leaves either the old or the
+/// new version, never a mix: write a temporary file, fsync it and rename
+/// it over `path`. The version being replaced is kept at
+/// [`backup_path`].
+pub fn atomic_write(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    // Unique temp filename, so concurrent writers don't share one
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos());
+    let pid = std::process::id();
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("file");
+    let tmp_path = path.with_file_name(format!("{file_name}.{timestamp}.{pid}.tmp"));
+
+    let result = (|| {
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(content)?;
+        file.sync_all()?;
+        if path.exists() {
+            keep_backup(path);
+        }
+        fs::rename(&tmp_path, path)
+    })();
+
+    if result.is_err() {
+        // Best-effort cleanup
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+/// Keep the current version of `path` as its backup. Best effort: a
+/// missing backup only matters if the next write is interrupted too.
+fn keep_backup(path: &Path) {
+    let backup = backup_path(path);
+    let _ = fs::remove_file(&backup);
+    // The rename that follows gives `path` a new inode, so a hard link
+    // keeps the old contents without copying them
+    if fs::hard_link(path, &backup).is_err() {
+        if let Err(e) = fs::copy(path, &backup) {
+            tracing::warn!("Failed to back up {}: {e}", path.display());
+        }
+    }
+}
+
+/// A state file that couldn't be loaded, and what was done about it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Corruption {
+    /// The file that failed to load.
+    pub path: PathBuf,
+    /// Why it failed.
+    pub error: String,
+    /// Where the unreadable file was moved, for inspection.
+    pub moved_to: Option<PathBuf>,
+    /// Whether the previous version was restored from its backup (if not,
+    /// loading started from defaults).
+    pub restored: bool,
+}
+
+impl std::fmt::Display for Corruption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} is corrupt ({})", self.path.display(), self.error)?;
+        if self.restored {
+            write!(f, "; restored the previous version from its backup")?;
+        } else {
+            write!(f, "; no usable backup, starting from empty state")?;
+        }
+        if let Some(moved_to) = &self.moved_to {
+            write!(f, ". The corrupt file was kept as {}", moved_to.display())?;
+        }
+        Ok(())
+    }
+}
+
+/// Load a state file with `load`, recovering from corruption.
+///
+/// If `path` fails to load, it is moved to `<file>.corrupt` and the backup
+/// of its previous version is tried; a good backup is restored in its
+/// place. Failing that, loading starts from `T::default()`. Either way the
+/// returned [`Corruption`] describes what happened so it can be reported.
+pub fn load_or_recover<T, E>(
+    path: &Path,
+    load: impl Fn(&Path) -> Result<T, E>,
+) -> (T, Option<Corruption>)
+where
+    T: Default,
+    E: std::fmt::Display,
+{
+    let error = match load(path) {
+        Ok(value) => return (value, None),
+        Err(e) => e.to_string(),
+    };
+
+    let corrupt = with_suffix(path, "corrupt");
+    let moved_to = fs::rename(path, &corrupt).is_ok().then_some(corrupt);
+
+    let backup = backup_path(path);
+    let restored = backup.exists()
+        && load(&backup).is_ok()
+        && fs::read(&backup).is_ok_and(|content| atomic_write(path, &content).is_ok());
+    let value = if restored {
+        load(path).unwrap_or_default()
+    } else {
+        T::default()
+    };
+
+    let corruption = Corruption {
+        path: path.to_path_buf(),
+        error,
+        moved_to,
+        restored,
+    };
+    tracing::warn!("{corruption}");
+    (value, Some(corruption))
+}
+
 /// Generate a unique run ID.
 fn generate_run_id() -> String {
     use std::time::{SystemTime, UNIX_EPOCH};
@@ -256,6 +500,8 @@ pub enum StateError {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::clock::ManualClock;
+    use std::time::Duration;
 
     #[test]
     fn test_run_state_lifecycle() {
@@ -276,6 +522,44 @@ mod tests {
         assert!(!state.is_running());
     }
 
+    #[test]
+    fn test_run_state_resume() {
+        let mut state = RunState::default();
+        state.start_run_with_id("abc12345");
+        state.next_iteration();
+        state.completed_iterations = 1;
+        state.pending_question = Some(PendingQuestion {
+            iteration: 1,
+            question: "Which database?".into(),
+            asked_at: 100,
+        });
+        state.next_iteration(); // Interrupted mid-iteration
+        state.cancel();
+
+        state.resume();
+        assert!(state.is_running());
+        assert_eq!(state.run_id.as_deref(), Some("abc12345"));
+        assert_eq!(state.iteration, 1);
+        assert!(state.ended_at.is_none());
+        // The question survives to be asked again
+        let json = serde_json::to_string(&state).unwrap();
+        let restored: RunState = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.pending_question, state.pending_question);
+
+        state.start_run_with_id("def67890");
+        assert!(state.pending_question.is_none());
+    }
+
+    #[test]
+    fn test_run_state_legacy_json() {
+        // State files written before resume support lack the new fields
+        let json = r#"{"run_id":"x","iteration":3,"status":"running","last_model_index":1,"started_at":null,"ended_at":null}"#;
+        let state: RunState = serde_json::from_str(json).unwrap();
+        assert_eq!(state.completed_iterations, 0);
+        assert!(state.prompt_hash.is_none());
+        assert!(state.pending_question.is_none());
+    }
+
     #[test]
     fn test_cooldowns() {
         let mut cooldowns = Cooldowns::default();
@@ -293,6 +577,81 @@ mod tests {
         assert_eq!(cooling, vec!["claude"]);
     }
 
+    #[test]
+    fn test_cooldowns_follow_clock() {
+        let clock = Arc::new(ManualClock::new(1000));
+        let mut cooldowns = Cooldowns::default();
+        cooldowns.set_clock(clock.clone());
+
+        cooldowns.set_cooldown("claude", 60, "rate limit");
+        cooldowns.set_cooldown_until("codex", 1030, "rate limit", None);
+        assert_eq!(cooldowns.entries["claude"].cooldown_until, 1060);
+        assert_eq!(cooldowns.remaining_seconds("claude"), Some(60));
+        assert_eq!(cooldowns.earliest_expiry(), Some(1030));
+
+        clock.advance(Duration::from_secs(30));
+        assert!(!cooldowns.is_cooling("codex"));
+        assert_eq!(cooldowns.remaining_seconds("claude"), Some(30));
+        cooldowns.clear_expired();
+        assert_eq!(cooldowns.cooling_models(), vec!["claude"]);
+
+        clock.advance(Duration::from_secs(30));
+        assert!(!cooldowns.is_cooling("claude"));
+        assert_eq!(cooldowns.earliest_expiry(), None);
+    }
+
+    #[test]
+    fn test_atomic_write_keeps_backup() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("state.json");
+
+        atomic_write(&path, b"one").unwrap();
+        assert!(!backup_path(&path).exists());
+
+        atomic_write(&path, b"two").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "two");
+        assert_eq!(fs::read_to_string(backup_path(&path)).unwrap(), "one");
+
+        // Only the file and its backup remain
+        assert_eq!(fs::read_dir(temp.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn test_load_or_recover() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("state.json");
+
+        let mut state = RunState::default();
+        state.start_run_with_id("first");
+        state.save(&path).unwrap();
+        state.next_iteration();
+        state.save(&path).unwrap();
+
+        let (loaded, corruption) = load_or_recover(&path, RunState::load);
+        assert_eq!(loaded.iteration, 1);
+        assert!(corruption.is_none());
+
+        // A torn write: the previous version comes back from the backup
+        fs::write(&path, "{\"run_id\": \"fir").unwrap();
+        let (loaded, corruption) = load_or_recover(&path, RunState::load);
+        let corruption = corruption.unwrap();
+        assert!(corruption.restored);
+        assert_eq!(loaded.run_id.as_deref(), Some("first"));
+        assert_eq!(loaded.iteration, 0);
+        assert!(with_suffix(&path, "corrupt").exists());
+        assert!(RunState::load(&path).is_ok());
+        assert!(corruption
+            .to_string()
+            .contains("restored the previous version"));
+
+        // No usable backup: start over, but say so
+        fs::write(&path, "garbage").unwrap();
+        fs::write(backup_path(&path), "garbage").unwrap();
+        let (loaded, corruption) = load_or_recover(&path, RunState::load);
+        assert!(loaded.run_id.is_none());
+        assert!(!corruption.unwrap().restored);
+    }
+
     #[test]
     fn test_cooldowns_serialization() {
         let mut cooldowns = Cooldowns::default();
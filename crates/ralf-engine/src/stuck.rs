@@ -0,0 +1,541 @@
+//! Stuck detection.
+//!
+//! Without it a run that has stopped making progress keeps going until it
+//! hits its iteration limit. [`StuckDetector`] looks at the last few
+//! iterations for the signs: no new completion criteria passing, the
+//! workspace left exactly as it was, and the same verifiers failing every
+//! time. A run is stuck when nothing improved *and* the model is either
+//! changing nothing or hitting the same failures, or when the model hasn't
+//! changed a thing for several iterations in a row (no-ops); the resulting
+//! [`StuckDiagnosis`] is what the thread's `Stuck` phase shows. A model can
+//! also say it is blocked itself ([`StuckDetector::blocked`]).
+//!
+//! Once stuck, [`suggest_recovery`] asks a model for a few concrete ways
+//! out, each tagged with the [`RecoveryAction`] that applies it.
+
+use regex::Regex;
+use std::collections::VecDeque;
+use std::fmt;
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::chat::invoke_prompt;
+use crate::config::ModelConfig;
+use crate::context::truncate_to_tokens;
+use crate::runner::{get_git_diff, RunnerError};
+use crate::thread::{RecoveryAction, RecoverySuggestion, StuckDiagnosis};
+
+/// Instructions for the model asked to get a stuck run moving.
+const RECOVERY_PROMPT: &str = r"An autonomous coding loop has stopped making progress on a task. Below is its diagnosis, the criteria still failing, and its uncommitted changes.
+
+Suggest 2 or 3 concrete ways to get it unstuck, best first. Write one line per suggestion, in exactly this format:
+
+RECONFIGURE: <summary> - <what to change>
+EDIT_SPEC: <summary> - <what to change>
+MANUAL_FIX: <summary> - <what to change>
+
+RECONFIGURE means changing models, iteration limits or verifiers and running again. EDIT_SPEC means revising the task description or its completion criteria. MANUAL_FIX means a person fixing the blocker directly before the loop resumes. Use each kind at most once, and be specific to this task.";
+
+/// Maximum number of suggestions kept.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Maximum tokens of the diff included in the recovery prompt.
+const RECOVERY_DIFF_TOKENS: usize = 8_000;
+
+/// What happened in one iteration, as far as stuck detection cares.
+#[derive(Debug, Clone, Default)]
+pub struct IterationSnapshot {
+    /// Model that ran the iteration.
+    pub model: String,
+    /// Criteria that passed, if criteria were verified this iteration.
+    pub criteria_passed: Option<u32>,
+    /// Hash of the workspace after the iteration, if known.
+    pub tree_hash: Option<String>,
+    /// Whether the model left the workspace exactly as it found it.
+    pub no_op: bool,
+    /// Verifiers that failed.
+    pub failed_verifiers: Vec<String>,
+    /// The most relevant failure, for the diagnosis.
+    pub error: Option<String>,
+}
+
+/// A sign that the run is not getting anywhere.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StuckSignal {
+    /// No iteration in the window passed more criteria than before.
+    NoProgress,
+    /// Every iteration in the window left the same workspace.
+    IdenticalDiffs,
+    /// These verifiers failed in every iteration in the window.
+    RepeatedVerifierFailures(Vec<String>),
+    /// The model changed nothing in this many iterations in a row.
+    NoOps(u32),
+    /// The model said it can't go on, and why if it said.
+    Blocked(Option<String>),
+}
+
+impl fmt::Display for StuckSignal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoProgress => write!(f, "no new criteria passing"),
+            Self::IdenticalDiffs => write!(f, "workspace unchanged between iterations"),
+            Self::RepeatedVerifierFailures(names) => {
+                write!(f, "verifiers failing every time: {}", names.join(", "))
+            }
+            Self::NoOps(count) => write!(f, "no changes in {count} iterations in a row"),
+            Self::Blocked(None) => write!(f, "the model reported it is blocked"),
+            Self::Blocked(Some(reason)) => {
+                write!(f, "the model reported it is blocked: {reason}")
+            }
+        }
+    }
+}
+
+/// Warning for an iteration that changed nothing, as the CLI and TUI show
+/// it.
+pub fn no_op_warning(iteration: usize, model: &str, consecutive: u32) -> String {
+    let mut warning = format!("Iteration {iteration}: {model} changed nothing");
+    if consecutive > 1 {
+        let _ = write!(warning, " ({consecutive} iterations in a row)");
+    }
+    warning
+}
+
+/// One recorded iteration plus whether it set a new best.
+#[derive(Debug, Clone)]
+struct Entry {
+    snapshot: IterationSnapshot,
+    improved: bool,
+}
+
+/// Watches recent iterations and decides when a run is stuck.
+#[derive(Debug, Clone)]
+pub struct StuckDetector {
+    window: usize,
+    total_criteria: u32,
+    history: VecDeque<Entry>,
+    iterations: u32,
+    models_tried: Vec<String>,
+    best_criteria_passed: u32,
+    no_op_limit: u32,
+    consecutive_no_ops: u32,
+}
+
+impl StuckDetector {
+    /// Create a detector over the last `window` iterations (0 disables it)
+    /// for a task with `total_criteria` completion criteria.
+    pub fn new(window: usize, total_criteria: u32) -> Self {
+        Self {
+            window,
+            total_criteria,
+            history: VecDeque::with_capacity(window),
+            iterations: 0,
+            models_tried: Vec::new(),
+            best_criteria_passed: 0,
+            no_op_limit: 0,
+            consecutive_no_ops: 0,
+        }
+    }
+
+    /// Also count the run stuck once `limit` iterations in a row change
+    /// nothing, whatever the window shows (0 disables it).
+    #[must_use]
+    pub fn with_no_op_limit(mut self, limit: u32) -> Self {
+        self.no_op_limit = limit;
+        self
+    }
+
+    /// How many of the latest iterations in a row changed nothing.
+    pub fn consecutive_no_ops(&self) -> u32 {
+        self.consecutive_no_ops
+    }
+
+    /// Record an iteration that did not complete the run.
+    pub fn record(&mut self, snapshot: IterationSnapshot) {
+        self.iterations += 1;
+        if !self.models_tried.contains(&snapshot.model) {
+            self.models_tried.push(snapshot.model.clone());
+        }
+
+        let passed = snapshot.criteria_passed.unwrap_or(0);
+        let improved = passed > self.best_criteria_passed;
+        self.best_criteria_passed = self.best_criteria_passed.max(passed);
+        self.consecutive_no_ops = if snapshot.no_op {
+            self.consecutive_no_ops + 1
+        } else {
+            0
+        };
+
+        if self.window == 0 {
+            return;
+        }
+        if self.history.len() == self.window {
+            self.history.pop_front();
+        }
+        self.history.push_back(Entry { snapshot, improved });
+    }
+
+    /// Signals present across the full window (none until it fills), and
+    /// no-ops once they reach the limit.
+    pub fn signals(&self) -> Vec<StuckSignal> {
+        let mut signals = Vec::new();
+        if self.no_op_limit > 0 && self.consecutive_no_ops >= self.no_op_limit {
+            signals.push(StuckSignal::NoOps(self.consecutive_no_ops));
+        }
+        if self.window < 2 || self.history.len() < self.window {
+            return signals;
+        }
+
+        if !self.history.iter().any(|e| e.improved) {
+            signals.push(StuckSignal::NoProgress);
+        }
+
+        let first_hash = &self.history[0].snapshot.tree_hash;
+        if first_hash.is_some()
+            && self
+                .history
+                .iter()
+                .all(|e| &e.snapshot.tree_hash == first_hash)
+        {
+            signals.push(StuckSignal::IdenticalDiffs);
+        }
+
+        let repeated: Vec<String> = self.history[0]
+            .snapshot
+            .failed_verifiers
+            .iter()
+            .filter(|name| {
+                self.history
+                    .iter()
+                    .all(|e| e.snapshot.failed_verifiers.contains(name))
+            })
+            .cloned()
+            .collect();
+        if !repeated.is_empty() {
+            signals.push(StuckSignal::RepeatedVerifierFailures(repeated));
+        }
+
+        signals
+    }
+
+    /// A diagnosis if the run is stuck: no progress, plus an unchanged
+    /// workspace or repeated verifier failures; or too many no-ops.
+    pub fn check(&self) -> Option<StuckDiagnosis> {
+        let signals = self.signals();
+        let no_ops = signals.iter().any(|s| matches!(s, StuckSignal::NoOps(_)));
+        let no_progress = signals.contains(&StuckSignal::NoProgress);
+        if !no_ops && (!no_progress || signals.len() < 2) {
+            return None;
+        }
+        Some(self.diagnosis(&signals, None))
+    }
+
+    /// The diagnosis for a model that said it is blocked (`<promise>BLOCKED`),
+    /// whatever the other signals say.
+    pub fn blocked(&self, reason: Option<&str>) -> StuckDiagnosis {
+        let signal = StuckSignal::Blocked(reason.map(ToString::to_string));
+        self.diagnosis(&[signal], reason)
+    }
+
+    fn diagnosis(&self, signals: &[StuckSignal], error: Option<&str>) -> StuckDiagnosis {
+        StuckDiagnosis {
+            iterations_attempted: self.iterations,
+            models_tried: self.models_tried.clone(),
+            best_criteria_passed: self.best_criteria_passed,
+            total_criteria: self.total_criteria,
+            last_error: error.map(ToString::to_string).or_else(|| {
+                self.history
+                    .iter()
+                    .rev()
+                    .find_map(|e| e.snapshot.error.clone())
+            }),
+            signals: signals.iter().map(ToString::to_string).collect(),
+            suggestions: Vec::new(),
+        }
+    }
+}
+
+/// Build the prompt asking for recovery suggestions.
+pub fn build_recovery_prompt(
+    diagnosis: &StuckDiagnosis,
+    failing_criteria: &[String],
+    diff: &str,
+) -> String {
+    let mut prompt = format!("{RECOVERY_PROMPT}\n\n## Diagnosis\n\n");
+    let _ = writeln!(
+        prompt,
+        "- Iterations attempted: {}",
+        diagnosis.iterations_attempted
+    );
+    let _ = writeln!(
+        prompt,
+        "- Models tried: {}",
+        diagnosis.models_tried.join(", ")
+    );
+    let _ = writeln!(
+        prompt,
+        "- Best criteria passed: {}/{}",
+        diagnosis.best_criteria_passed, diagnosis.total_criteria
+    );
+    for signal in &diagnosis.signals {
+        let _ = writeln!(prompt, "- {signal}");
+    }
+    if let Some(error) = &diagnosis.last_error {
+        let _ = writeln!(prompt, "- Last error: {error}");
+    }
+
+    if !failing_criteria.is_empty() {
+        prompt.push_str("\n## Failing criteria\n\n");
+        for criterion in failing_criteria {
+            let _ = writeln!(prompt, "- {criterion}");
+        }
+    }
+
+    let diff = diff.trim();
+    let diff = if diff.is_empty() {
+        "(no changes)".to_string()
+    } else {
+        truncate_to_tokens(diff, RECOVERY_DIFF_TOKENS)
+    };
+    let _ = write!(prompt, "\n## Changes\n\n```diff\n{diff}\n```\n");
+    prompt
+}
+
+/// Parse `RECONFIGURE:` / `EDIT_SPEC:` / `MANUAL_FIX:` lines from a
+/// recovery response. Other lines are ignored; at most three suggestions
+/// are kept.
+pub fn parse_recovery(response: &str) -> Vec<RecoverySuggestion> {
+    let re = Regex::new(
+        r"(?i)^\s*(?:[-*]|\d+[.)])?\s*\**(reconfigure|edit[_ ]spec|manual[_ ]fix)\**\s*:\s*(.+)$",
+    )
+    .expect("Invalid regex pattern");
+
+    response
+        .lines()
+        .filter_map(|line| {
+            let caps = re.captures(line)?;
+            let action = match caps[1].to_ascii_lowercase().as_str() {
+                "reconfigure" => RecoveryAction::Reconfigure,
+                "edit_spec" | "edit spec" => RecoveryAction::EditSpec,
+                _ => RecoveryAction::ManualFix,
+            };
+            let text = caps[2].trim();
+            let (title, details) = text
+                .split_once(" - ")
+                .or_else(|| text.split_once(" — "))
+                .unwrap_or((text, ""));
+            Some(RecoverySuggestion {
+                action,
+                title: title.trim().to_string(),
+                details: details.trim().to_string(),
+            })
+        })
+        .take(MAX_SUGGESTIONS)
+        .collect()
+}
+
+/// Ask `model` for ways out of a stuck run, given the criteria still
+/// failing and the uncommitted changes in `work_dir`.
+pub async fn suggest_recovery(
+    model: &ModelConfig,
+    diagnosis: &StuckDiagnosis,
+    failing_criteria: &[String],
+    work_dir: &Path,
+) -> Result<Vec<RecoverySuggestion>, RunnerError> {
+    let prompt = build_recovery_prompt(diagnosis, failing_criteria, &get_git_diff(work_dir));
+    let result = invoke_prompt(model, &prompt, model.timeout_seconds, None).await?;
+    Ok(parse_recovery(&result.content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(passed: u32, tree: &str, failed: &[&str]) -> IterationSnapshot {
+        IterationSnapshot {
+            model: "claude".into(),
+            criteria_passed: Some(passed),
+            tree_hash: Some(tree.into()),
+            no_op: false,
+            failed_verifiers: failed.iter().map(|s| (*s).to_string()).collect(),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_progress_is_not_stuck() {
+        let mut detector = StuckDetector::new(3, 4);
+        for (i, tree) in ["a", "a", "a"].iter().enumerate() {
+            detector.record(snapshot(u32::try_from(i).unwrap() + 1, tree, &[]));
+        }
+        // Workspace unchanged, but criteria keep passing
+        assert!(detector.check().is_none());
+        assert!(detector.signals().contains(&StuckSignal::IdenticalDiffs));
+    }
+
+    #[test]
+    fn test_unchanged_workspace_is_stuck() {
+        let mut detector = StuckDetector::new(3, 4);
+        detector.record(snapshot(2, "a", &[]));
+        detector.record(IterationSnapshot {
+            model: "codex".into(),
+            error: Some("Tests pass: 2 tests fail".into()),
+            ..snapshot(2, "b", &[])
+        });
+        detector.record(snapshot(2, "b", &[]));
+        assert!(detector.check().is_none(), "window starts with progress");
+
+        detector.record(snapshot(1, "b", &[]));
+        let diagnosis = detector.check().unwrap();
+        assert_eq!(diagnosis.iterations_attempted, 4);
+        assert_eq!(diagnosis.models_tried, vec!["claude", "codex"]);
+        assert_eq!(diagnosis.best_criteria_passed, 2);
+        assert_eq!(diagnosis.total_criteria, 4);
+        assert_eq!(
+            diagnosis.last_error.as_deref(),
+            Some("Tests pass: 2 tests fail")
+        );
+        assert_eq!(
+            diagnosis.signals,
+            vec![
+                "no new criteria passing",
+                "workspace unchanged between iterations"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_repeated_verifier_failures_are_stuck() {
+        let mut detector = StuckDetector::new(2, 0);
+        detector.record(snapshot(0, "a", &["tests", "lint"]));
+        detector.record(snapshot(0, "b", &["tests"]));
+        let diagnosis = detector.check().unwrap();
+        assert_eq!(
+            diagnosis.signals,
+            vec![
+                "no new criteria passing",
+                "verifiers failing every time: tests"
+            ]
+        );
+
+        // Changing workspace, different failures: not stuck
+        let mut detector = StuckDetector::new(2, 0);
+        detector.record(snapshot(0, "a", &["lint"]));
+        detector.record(snapshot(0, "b", &["tests"]));
+        assert!(detector.check().is_none());
+
+        // Disabled
+        let mut detector = StuckDetector::new(0, 0);
+        for _ in 0..5 {
+            detector.record(snapshot(0, "a", &["tests"]));
+        }
+        assert!(detector.check().is_none());
+    }
+
+    #[test]
+    fn test_no_op_warning() {
+        assert_eq!(
+            no_op_warning(2, "claude", 1),
+            "Iteration 2: claude changed nothing"
+        );
+        assert_eq!(
+            no_op_warning(4, "codex", 3),
+            "Iteration 4: codex changed nothing (3 iterations in a row)"
+        );
+    }
+
+    #[test]
+    fn test_no_ops_are_stuck() {
+        let no_op = |passed| IterationSnapshot {
+            no_op: true,
+            ..snapshot(passed, "a", &[])
+        };
+        // Even with the window disabled and criteria improving
+        let mut detector = StuckDetector::new(0, 3).with_no_op_limit(2);
+        detector.record(no_op(1));
+        assert_eq!(detector.consecutive_no_ops(), 1);
+        assert!(detector.check().is_none());
+        // A change starts the count again
+        detector.record(snapshot(1, "b", &[]));
+        assert_eq!(detector.consecutive_no_ops(), 0);
+        detector.record(no_op(1));
+        detector.record(no_op(2));
+        let diagnosis = detector.check().unwrap();
+        assert_eq!(
+            diagnosis.signals,
+            vec!["no changes in 2 iterations in a row"]
+        );
+        assert_eq!(diagnosis.iterations_attempted, 4);
+
+        // Without a limit, no-ops alone don't count
+        let mut detector = StuckDetector::new(0, 0);
+        for _ in 0..5 {
+            detector.record(no_op(0));
+        }
+        assert!(detector.check().is_none());
+    }
+
+    #[test]
+    fn test_blocked() {
+        let mut detector = StuckDetector::new(0, 2);
+        detector.record(snapshot(1, "a", &[]));
+        assert!(detector.check().is_none());
+
+        let diagnosis = detector.blocked(Some("no database to migrate"));
+        assert_eq!(
+            diagnosis.signals,
+            vec!["the model reported it is blocked: no database to migrate"]
+        );
+        assert_eq!(
+            diagnosis.last_error.as_deref(),
+            Some("no database to migrate")
+        );
+        assert_eq!(diagnosis.iterations_attempted, 1);
+        assert_eq!(diagnosis.best_criteria_passed, 1);
+
+        let diagnosis = detector.blocked(None);
+        assert_eq!(diagnosis.signals, vec!["the model reported it is blocked"]);
+        assert_eq!(diagnosis.last_error, None);
+    }
+
+    #[test]
+    fn test_build_recovery_prompt() {
+        let mut detector = StuckDetector::new(2, 3);
+        for _ in 0..3 {
+            detector.record(snapshot(1, "a", &["tests"]));
+        }
+        let diagnosis = StuckDiagnosis {
+            last_error: Some("2 tests fail".into()),
+            ..detector.check().unwrap()
+        };
+
+        let prompt = build_recovery_prompt(&diagnosis, &["Tests pass".into()], "");
+        assert!(prompt.contains("- Best criteria passed: 1/3"));
+        assert!(prompt.contains("- workspace unchanged between iterations"));
+        assert!(prompt.contains("- Last error: 2 tests fail"));
+        assert!(prompt.contains("## Failing criteria\n\n- Tests pass"));
+        assert!(prompt.contains("(no changes)"));
+    }
+
+    #[test]
+    fn test_parse_recovery() {
+        let response = "Here are some options:\n\
+            1. **EDIT_SPEC**: Split the task - Move the migration into its own spec\n\
+            - reconfigure: Use codex - It handled the parser changes before\n\
+            MANUAL_FIX: Fix the flaky test\n\
+            MANUAL_FIX: One too many - ignored\n";
+        let suggestions = parse_recovery(response);
+        assert_eq!(suggestions.len(), 3);
+        assert_eq!(suggestions[0].action, RecoveryAction::EditSpec);
+        assert_eq!(suggestions[0].title, "Split the task");
+        assert_eq!(
+            suggestions[0].details,
+            "Move the migration into its own spec"
+        );
+        assert_eq!(suggestions[1].action, RecoveryAction::Reconfigure);
+        assert_eq!(suggestions[2].action, RecoveryAction::ManualFix);
+        assert!(suggestions[2].details.is_empty());
+
+        assert!(parse_recovery("No idea, sorry.").is_empty());
+    }
+}
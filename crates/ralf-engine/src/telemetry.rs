@@ -0,0 +1,415 @@
+//! The engine log: `tracing` output written to `.ralf/logs/engine.log`.
+//!
+//! The engine emits spans for runs, iterations, model invocations, and
+//! verifications, and events for what happens inside them, so a stalled or
+//! failing run can be diagnosed after the fact. [`init`] installs a
+//! subscriber that writes one line per event, prefixed with the spans it
+//! happened in:
+//!
+//! ```text
+//! 2026-01-05T10:31:02.114Z  INFO run{run_id=a1b2c3d4}:iteration{iteration=3 model=claude}: model invocation finished exit_code=0 duration_ms=41200
+//! ```
+//!
+//! The log rotates by size, keeping a few previous files alongside it
+//! (`engine.log.1` is the most recent).
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::{self, Write as _};
+use std::fs::{File, OpenOptions};
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+use tracing_core::span::Current;
+
+/// Subdirectory of `.ralf` holding the engine log.
+pub const LOGS_DIR: &str = "logs";
+
+/// File name of the engine log.
+pub const ENGINE_LOG: &str = "engine.log";
+
+/// Size at which the engine log is rotated.
+pub const MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Number of rotated logs kept besides the current one.
+pub const KEPT_LOGS: usize = 3;
+
+/// Errors from setting up the engine log.
+#[derive(Debug, thiserror::Error)]
+pub enum TelemetryError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("A tracing subscriber is already installed")]
+    AlreadyInstalled,
+}
+
+/// Path of the engine log in `ralf_dir`.
+pub fn engine_log_path(ralf_dir: &Path) -> PathBuf {
+    ralf_dir.join(LOGS_DIR).join(ENGINE_LOG)
+}
+
+/// Send the engine's spans and events to `.ralf/logs/engine.log` for the
+/// rest of the process. Logs info and above, or debug and above when
+/// `verbose`.
+pub fn init(ralf_dir: &Path, verbose: bool) -> Result<(), TelemetryError> {
+    let level = if verbose { Level::DEBUG } else { Level::INFO };
+    let subscriber = LogSubscriber::new(&engine_log_path(ralf_dir), level)?;
+    tracing::subscriber::set_global_default(subscriber)
+        .map_err(|_| TelemetryError::AlreadyInstalled)
+}
+
+/// A log file that moves aside once it reaches `max_bytes`.
+#[derive(Debug)]
+struct RotatingFile {
+    path: PathBuf,
+    file: Option<File>,
+    size: u64,
+    max_bytes: u64,
+    keep: usize,
+}
+
+impl RotatingFile {
+    fn open(path: &Path, max_bytes: u64, keep: usize) -> std::io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path: path.to_path_buf(),
+            file: Some(file),
+            size,
+            max_bytes,
+            keep,
+        })
+    }
+
+    /// Append `line`, rotating first if it would take the file past its
+    /// limit. Logging never fails the caller, so write errors are dropped.
+    fn write_line(&mut self, line: &str) {
+        let len = line.len() as u64;
+        if self.size > 0 && self.size + len > self.max_bytes {
+            self.rotate();
+        }
+        if let Some(file) = &mut self.file {
+            if file.write_all(line.as_bytes()).is_ok() {
+                self.size += len;
+            }
+        }
+    }
+
+    /// Shift `engine.log.N` to `engine.log.N+1`, dropping the oldest, and
+    /// start a new file.
+    fn rotate(&mut self) {
+        self.file = None;
+        for n in (1..self.keep).rev() {
+            let _ = std::fs::rename(self.rotated(n), self.rotated(n + 1));
+        }
+        if self.keep > 0 {
+            let _ = std::fs::rename(&self.path, self.rotated(1));
+        } else {
+            let _ = std::fs::remove_file(&self.path);
+        }
+        self.file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&self.path)
+            .ok();
+        self.size = 0;
+    }
+
+    fn rotated(&self, n: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{n}"));
+        PathBuf::from(name)
+    }
+}
+
+/// A span's name and fields, kept while any handle to it is open.
+#[derive(Debug)]
+struct SpanData {
+    metadata: &'static Metadata<'static>,
+    fields: String,
+    parent: Option<u64>,
+    refs: usize,
+}
+
+thread_local! {
+    /// Spans entered on this thread, innermost last.
+    static CURRENT: RefCell<Vec<u64>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Writes every enabled event to a [`RotatingFile`].
+#[derive(Debug)]
+struct LogSubscriber {
+    level: Level,
+    file: Mutex<RotatingFile>,
+    spans: Mutex<HashMap<u64, SpanData>>,
+    next_id: AtomicU64,
+}
+
+impl LogSubscriber {
+    fn new(path: &Path, level: Level) -> std::io::Result<Self> {
+        Ok(Self::with_file(
+            RotatingFile::open(path, MAX_LOG_BYTES, KEPT_LOGS)?,
+            level,
+        ))
+    }
+
+    fn with_file(file: RotatingFile, level: Level) -> Self {
+        Self {
+            level,
+            file: Mutex::new(file),
+            spans: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    fn current_id() -> Option<u64> {
+        CURRENT.with(|stack| stack.borrow().last().copied())
+    }
+
+    /// `run{run_id=..}:iteration{..}` for `span` and its ancestors.
+    fn context(&self, span: Option<u64>) -> String {
+        let Ok(spans) = self.spans.lock() else {
+            return String::new();
+        };
+        let mut chain = Vec::new();
+        let mut next = span;
+        while let Some(data) = next.and_then(|id| spans.get(&id)) {
+            let name = data.metadata.name();
+            chain.push(if data.fields.is_empty() {
+                name.to_string()
+            } else {
+                format!("{name}{{{}}}", data.fields)
+            });
+            next = data.parent;
+        }
+        chain.reverse();
+        chain.join(":")
+    }
+
+    /// Drop a reference to `id`, removing it (and releasing its parent)
+    /// once nothing holds it. Returns whether it was removed.
+    fn release(spans: &mut HashMap<u64, SpanData>, id: u64) -> bool {
+        let Some(data) = spans.get_mut(&id) else {
+            return false;
+        };
+        data.refs = data.refs.saturating_sub(1);
+        if data.refs > 0 {
+            return false;
+        }
+        let mut parent = spans.remove(&id).and_then(|data| data.parent);
+        while let Some(id) = parent.take() {
+            let Some(data) = spans.get_mut(&id) else {
+                break;
+            };
+            data.refs = data.refs.saturating_sub(1);
+            if data.refs == 0 {
+                parent = spans.remove(&id).and_then(|data| data.parent);
+            }
+        }
+        true
+    }
+}
+
+impl Subscriber for LogSubscriber {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        *metadata.level() <= self.level
+    }
+
+    fn max_level_hint(&self) -> Option<tracing::level_filters::LevelFilter> {
+        Some(tracing::level_filters::LevelFilter::from_level(self.level))
+    }
+
+    fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+        let mut fields = String::new();
+        attrs.record(&mut FieldWriter(&mut fields));
+        let parent = if attrs.is_contextual() {
+            Self::current_id()
+        } else {
+            attrs.parent().map(Id::into_u64)
+        };
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        if let Ok(mut spans) = self.spans.lock() {
+            // A child keeps its parent's name and fields around
+            let parent = parent.filter(|p| match spans.get_mut(p) {
+                Some(data) => {
+                    data.refs += 1;
+                    true
+                }
+                None => false,
+            });
+            spans.insert(
+                id,
+                SpanData {
+                    metadata: attrs.metadata(),
+                    fields,
+                    parent,
+                    refs: 1,
+                },
+            );
+        }
+        Id::from_u64(id)
+    }
+
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        if let Ok(mut spans) = self.spans.lock() {
+            if let Some(data) = spans.get_mut(&span.into_u64()) {
+                values.record(&mut FieldWriter(&mut data.fields));
+            }
+        }
+    }
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let span = if event.is_contextual() {
+            Self::current_id()
+        } else {
+            event.parent().map(Id::into_u64)
+        };
+        let context = self.context(span);
+        let mut message = String::new();
+        event.record(&mut FieldWriter(&mut message));
+
+        let mut line = format!(
+            "{} {:>5} ",
+            chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ"),
+            event.metadata().level()
+        );
+        if !context.is_empty() {
+            let _ = write!(line, "{context}: ");
+        }
+        let _ = writeln!(line, "{message}");
+        if let Ok(mut file) = self.file.lock() {
+            file.write_line(&line);
+        }
+    }
+
+    fn enter(&self, span: &Id) {
+        CURRENT.with(|stack| stack.borrow_mut().push(span.into_u64()));
+    }
+
+    fn exit(&self, span: &Id) {
+        CURRENT.with(|stack| {
+            let mut stack = stack.borrow_mut();
+            if let Some(pos) = stack.iter().rposition(|id| *id == span.into_u64()) {
+                stack.remove(pos);
+            }
+        });
+    }
+
+    fn current_span(&self) -> Current {
+        let current = Self::current_id().and_then(|id| {
+            let spans = self.spans.lock().ok()?;
+            Some(Current::new(Id::from_u64(id), spans.get(&id)?.metadata))
+        });
+        current.unwrap_or_else(Current::none)
+    }
+
+    fn clone_span(&self, span: &Id) -> Id {
+        if let Ok(mut spans) = self.spans.lock() {
+            if let Some(data) = spans.get_mut(&span.into_u64()) {
+                data.refs += 1;
+            }
+        }
+        span.clone()
+    }
+
+    fn try_close(&self, span: Id) -> bool {
+        self.spans
+            .lock()
+            .is_ok_and(|mut spans| Self::release(&mut spans, span.into_u64()))
+    }
+}
+
+/// Formats fields as `name=value`, with the message unlabelled.
+struct FieldWriter<'a>(&'a mut String);
+
+impl FieldWriter<'_> {
+    fn separate(&mut self) {
+        if !self.0.is_empty() {
+            self.0.push(' ');
+        }
+    }
+}
+
+impl Visit for FieldWriter<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.separate();
+        if field.name() == "message" {
+            self.0.push_str(value);
+        } else {
+            let _ = write!(self.0, "{}={value}", field.name());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.separate();
+        if field.name() == "message" {
+            let _ = write!(self.0, "{value:?}");
+        } else {
+            let _ = write!(self.0, "{}={value:?}", field.name());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_rotating_file_keeps_recent_logs() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join(LOGS_DIR).join(ENGINE_LOG);
+        let mut file = RotatingFile::open(&path, 10, 2).unwrap();
+
+        for line in ["first\n", "second\n", "third\n", "fourth\n"] {
+            file.write_line(line);
+        }
+
+        let read = |p: &Path| std::fs::read_to_string(p).unwrap();
+        assert_eq!(read(&path), "fourth\n");
+        assert_eq!(read(&file.rotated(1)), "third\n");
+        assert_eq!(read(&file.rotated(2)), "second\n");
+        assert!(!file.rotated(3).exists());
+    }
+
+    #[test]
+    fn test_events_are_prefixed_with_their_spans() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join(ENGINE_LOG);
+        let file = RotatingFile::open(&path, MAX_LOG_BYTES, KEPT_LOGS).unwrap();
+
+        tracing::subscriber::with_default(LogSubscriber::with_file(file, Level::INFO), || {
+            let run = tracing::info_span!("run", run_id = tracing::field::Empty);
+            let entered = run.enter();
+            tracing::Span::current().record("run_id", "abc123");
+            let iteration = tracing::info_span!("iteration", iteration = 2, model = "claude");
+            // Explicit parents are followed even when not entered
+            let invocation = tracing::info_span!(parent: &iteration, "invocation");
+            tracing::info!(parent: &invocation, exit_code = 0, "model finished");
+            tracing::debug!("not logged at info");
+            drop(entered);
+            tracing::warn!("outside any span");
+        });
+
+        let log = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = log.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with(
+            " INFO run{run_id=abc123}:iteration{iteration=2 model=claude}:invocation: \
+             model finished exit_code=0"
+        ));
+        assert!(lines[1].ends_with(" WARN outside any span"));
+    }
+}
@@ -0,0 +1,289 @@
+//! PROMPT.md templates for scaffolding new specs.
+//!
+//! Built-in templates cover common task shapes (bugfix, feature, refactor,
+//! test coverage). Users can add their own as `<name>.md` files in
+//! `~/.config/ralf/templates/`; a user template with a built-in's name
+//! replaces it.
+
+use std::path::{Path, PathBuf};
+
+/// Built-in templates: name, description, content.
+const BUILTIN_TEMPLATES: &[(&str, &str, &str)] = &[
+    ("bugfix", "Fix a bug with a regression test", BUGFIX),
+    ("feature", "Add a new feature", FEATURE),
+    (
+        "refactor",
+        "Restructure code without changing behavior",
+        REFACTOR,
+    ),
+    (
+        "test-coverage",
+        "Add tests for existing code",
+        TEST_COVERAGE,
+    ),
+];
+
+const BUGFIX: &str = r"# Bugfix: <short description>
+
+## Problem
+
+Describe the bug: what happens, what should happen, and how to reproduce it.
+
+## Root Cause
+
+What is known (or suspected) about the cause. Leave blank if unknown.
+
+## Completion Criteria
+
+- [ ] A test reproduces the bug and fails without the fix
+- [ ] The bug is fixed and the new test passes
+- [ ] All existing tests pass
+
+## Instructions
+
+Keep the fix minimal and don't change unrelated behavior.
+
+When the task is complete, output:
+
+<promise>COMPLETE</promise>
+
+If something outside your control stops you, output
+`<promise>BLOCKED: reason</promise>`; if you need a decision from the user,
+output `<promise>NEEDS_INPUT: question</promise>`; the next prompt has the answer.
+";
+
+const FEATURE: &str = r"# Feature: <short description>
+
+## Goal
+
+What the feature does and who it is for.
+
+## Requirements
+
+Describe the expected behavior, inputs and outputs, and edge cases.
+
+## Completion Criteria
+
+- [ ] The feature works as described in Requirements
+- [ ] New behavior is covered by tests
+- [ ] All existing tests pass
+- [ ] User-facing documentation is updated
+
+## Instructions
+
+Follow the existing code style and module layout.
+
+When the task is complete, output:
+
+<promise>COMPLETE</promise>
+
+If something outside your control stops you, output
+`<promise>BLOCKED: reason</promise>`; if you need a decision from the user,
+output `<promise>NEEDS_INPUT: question</promise>`; the next prompt has the answer.
+";
+
+const REFACTOR: &str = r"# Refactor: <short description>
+
+## Motivation
+
+Why the current structure is a problem.
+
+## Target Structure
+
+Describe the intended shape of the code after the refactor.
+
+## Completion Criteria
+
+- [ ] The code matches the target structure
+- [ ] Behavior is unchanged: all existing tests pass without modification
+- [ ] No new warnings from the compiler or linter
+
+## Instructions
+
+Do not change behavior or public interfaces unless listed above.
+
+When the task is complete, output:
+
+<promise>COMPLETE</promise>
+
+If something outside your control stops you, output
+`<promise>BLOCKED: reason</promise>`; if you need a decision from the user,
+output `<promise>NEEDS_INPUT: question</promise>`; the next prompt has the answer.
+";
+
+const TEST_COVERAGE: &str = r"# Test Coverage: <module or area>
+
+## Scope
+
+Which code needs tests and which behaviors matter most.
+
+## Completion Criteria
+
+- [ ] Tests cover the main paths and error cases of the code in Scope
+- [ ] Tests follow the project's existing test layout and conventions
+- [ ] All tests pass
+
+## Instructions
+
+Don't change the code under test; if you find a bug, note it instead of fixing it.
+
+When the task is complete, output:
+
+<promise>COMPLETE</promise>
+
+If something outside your control stops you, output
+`<promise>BLOCKED: reason</promise>`; if you need a decision from the user,
+output `<promise>NEEDS_INPUT: question</promise>`; the next prompt has the answer.
+";
+
+/// Where a template comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemplateSource {
+    /// Shipped with ralf.
+    BuiltIn,
+    /// A file in the user's templates directory.
+    User,
+}
+
+/// A PROMPT.md template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    /// Template name (e.g., "feature").
+    pub name: String,
+    /// One-line description.
+    pub description: String,
+    /// The PROMPT.md content.
+    pub content: String,
+    /// Where the template comes from.
+    pub source: TemplateSource,
+}
+
+/// Directory for user templates: `$XDG_CONFIG_HOME/ralf/templates`,
+/// falling back to `~/.config/ralf/templates`.
+pub fn user_templates_dir() -> Option<PathBuf> {
+    crate::config::global_config_path().and_then(|path| Some(path.parent()?.join("templates")))
+}
+
+/// Built-in templates, followed by user templates from `user_dir`, sorted
+/// by name. User templates replace built-ins of the same name.
+pub fn list_templates(user_dir: Option<&Path>) -> Vec<Template> {
+    let mut templates: Vec<Template> = BUILTIN_TEMPLATES
+        .iter()
+        .map(|(name, description, content)| Template {
+            name: (*name).to_string(),
+            description: (*description).to_string(),
+            content: (*content).to_string(),
+            source: TemplateSource::BuiltIn,
+        })
+        .collect();
+
+    for template in user_dir.map(user_templates).unwrap_or_default() {
+        templates.retain(|t| t.name != template.name);
+        templates.push(template);
+    }
+    templates.sort_by(|a, b| a.name.cmp(&b.name));
+    templates
+}
+
+fn user_templates(dir: &Path) -> Vec<Template> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("md") {
+                return None;
+            }
+            let name = path.file_stem()?.to_str()?.to_string();
+            let content = std::fs::read_to_string(&path).ok()?;
+            // The title line doubles as the description
+            let description = content
+                .lines()
+                .find(|line| !line.trim().is_empty())
+                .map(|line| line.trim_start_matches('#').trim().to_string())
+                .unwrap_or_default();
+            Some(Template {
+                name,
+                description,
+                content,
+                source: TemplateSource::User,
+            })
+        })
+        .collect()
+}
+
+/// Find a template by name among the built-ins and `user_dir`.
+pub fn find_template(name: &str, user_dir: Option<&Path>) -> Result<Template, TemplateError> {
+    let templates = list_templates(user_dir);
+    if let Some(template) = templates.iter().find(|t| t.name == name) {
+        return Ok(template.clone());
+    }
+    Err(TemplateError::NotFound {
+        name: name.to_string(),
+        available: templates
+            .iter()
+            .map(|t| t.name.clone())
+            .collect::<Vec<_>>()
+            .join(", "),
+    })
+}
+
+/// Errors that can occur when looking up templates.
+#[derive(Debug, thiserror::Error)]
+pub enum TemplateError {
+    /// No template with that name.
+    #[error("Unknown template: {name} (available: {available})")]
+    NotFound {
+        /// The requested name.
+        name: String,
+        /// Names of the available templates.
+        available: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spec::lint;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_builtin_templates_are_valid_specs() {
+        let templates = list_templates(None);
+        let names: Vec<&str> = templates.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["bugfix", "feature", "refactor", "test-coverage"]
+        );
+        for template in &templates {
+            assert!(
+                lint(&template.content).is_empty(),
+                "{} has lint diagnostics",
+                template.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_user_templates() {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(temp.path().join("feature.md"), "# My feature\n").unwrap();
+        std::fs::write(temp.path().join("docs.md"), "\n# Docs update\n").unwrap();
+        std::fs::write(temp.path().join("notes.txt"), "ignored").unwrap();
+
+        let templates = list_templates(Some(temp.path()));
+        assert_eq!(templates.len(), 5);
+
+        let feature = find_template("feature", Some(temp.path())).unwrap();
+        assert_eq!(feature.source, TemplateSource::User);
+        assert_eq!(feature.content, "# My feature\n");
+
+        let docs = find_template("docs", Some(temp.path())).unwrap();
+        assert_eq!(docs.description, "Docs update");
+
+        let err = find_template("notes", Some(temp.path())).unwrap_err();
+        assert!(err.to_string().contains("available: bugfix, docs"));
+    }
+}
@@ -3,11 +3,18 @@
 //! A Thread represents a single work item (feature, fix, improvement) that
 //! progresses through well-defined phases from initial idea to merged code.
 
+use std::path::{Path, PathBuf};
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use uuid::Uuid;
 
+use crate::checklist::ReviewChecklist;
+
+/// Most characters of the title kept in a branch slug.
+const MAX_SLUG_TITLE_LEN: usize = 40;
+
 /// Error returned when a state transition is invalid.
 #[derive(Debug, Clone, Error, PartialEq, Eq)]
 pub enum TransitionError {
@@ -56,6 +63,19 @@ pub struct Thread {
 
     /// Git baseline captured at Preflight for workspace reset.
     pub baseline: Option<GitBaseline>,
+
+    /// Branch the thread's work happens on, once Preflight switched to it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<ThreadBranch>,
+
+    /// Pull request opened for the thread's branch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pr_url: Option<String>,
+
+    /// Checklist for reviewing the implementation, generated when the
+    /// thread went to `PendingReview`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub review_checklist: Option<ReviewChecklist>,
 }
 
 impl Thread {
@@ -75,7 +95,36 @@ impl Thread {
             mode: ThreadMode::default(),
             run_config: None,
             baseline: None,
+            branch: None,
+            pr_url: None,
+            review_checklist: None,
+        }
+    }
+
+    /// Slug naming the thread's branch (`ralf/<slug>`): the title in
+    /// lowercase words joined by dashes, then the start of the ID so two
+    /// threads with the same title don't share a branch.
+    pub fn branch_slug(&self) -> String {
+        let mut slug = String::new();
+        for word in self
+            .title
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|word| !word.is_empty())
+        {
+            if slug.len() + word.len() > MAX_SLUG_TITLE_LEN {
+                break;
+            }
+            slug.push_str(&word.to_ascii_lowercase());
+            slug.push('-');
         }
+        let id: String = self
+            .id
+            .chars()
+            .filter(char::is_ascii_alphanumeric)
+            .take(8)
+            .collect();
+        slug.push_str(&id);
+        slug
     }
 
     /// Check if the thread is in a terminal state (Done or Abandoned).
@@ -159,9 +208,15 @@ impl Thread {
 
     /// Execute transition: validates, updates phase, updates timestamp.
     ///
+    /// Leaving `PendingReview` other than by approval drops the review
+    /// checklist, which was for the implementation under review.
+    ///
     /// Returns error if transition is invalid, leaving state unchanged.
     pub fn transition_to(&mut self, target: ThreadPhase) -> Result<(), TransitionError> {
         self.can_transition_to(&target)?;
+        if self.phase == ThreadPhase::PendingReview && target != ThreadPhase::Approved {
+            self.review_checklist = None;
+        }
         self.phase = target;
         self.updated_at = Utc::now();
         Ok(())
@@ -192,9 +247,21 @@ impl Thread {
     pub fn requires_workspace_reset(&self, target: &ThreadPhase) -> bool {
         matches!(
             (&self.phase, target),
-            (ThreadPhase::Stuck { .. } | ThreadPhase::PendingReview, ThreadPhase::Drafting)
+            (
+                ThreadPhase::Stuck { .. } | ThreadPhase::PendingReview,
+                ThreadPhase::Drafting
+            )
         )
     }
+
+    /// Make `model` the first choice for implementation runs, keeping the
+    /// other configured models as fallbacks.
+    pub fn prefer_model(&mut self, model: &str) {
+        let config = self.run_config.get_or_insert_with(RunConfig::default);
+        config.models.retain(|m| m != model);
+        config.models.insert(0, model.to_string());
+        self.updated_at = Utc::now();
+    }
 }
 
 /// All possible phases a thread can be in.
@@ -280,6 +347,42 @@ impl std::fmt::Display for PhaseKind {
 }
 
 impl PhaseKind {
+    /// All phase kinds, in workflow order.
+    pub const ALL: [PhaseKind; 17] = [
+        PhaseKind::Drafting,
+        PhaseKind::Assessing,
+        PhaseKind::Finalized,
+        PhaseKind::Preflight,
+        PhaseKind::PreflightFailed,
+        PhaseKind::Configuring,
+        PhaseKind::Running,
+        PhaseKind::Paused,
+        PhaseKind::Verifying,
+        PhaseKind::Stuck,
+        PhaseKind::Implemented,
+        PhaseKind::Polishing,
+        PhaseKind::PendingReview,
+        PhaseKind::Approved,
+        PhaseKind::ReadyToCommit,
+        PhaseKind::Done,
+        PhaseKind::Abandoned,
+    ];
+
+    /// Look up a phase kind by name (case-insensitive).
+    ///
+    /// Accepts both the variant name (`PendingReview`) and the display
+    /// name (`Pending Review`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim();
+        Self::ALL.into_iter().find(|kind| {
+            kind.to_string().eq_ignore_ascii_case(name)
+                || kind
+                    .to_phase_with_defaults()
+                    .display_name()
+                    .eq_ignore_ascii_case(name)
+        })
+    }
+
     /// Convert to a `ThreadPhase` with sensible default data.
     pub fn to_phase_with_defaults(self) -> ThreadPhase {
         match self {
@@ -301,6 +404,8 @@ impl PhaseKind {
                     best_criteria_passed: 0,
                     total_criteria: 0,
                     last_error: None,
+                    signals: vec![],
+                    suggestions: vec![],
                 },
             },
             PhaseKind::Implemented => ThreadPhase::Implemented,
@@ -427,6 +532,56 @@ pub struct StuckDiagnosis {
     pub total_criteria: u32,
     /// Last error message, if any.
     pub last_error: Option<String>,
+    /// Why the run was judged stuck (e.g. "no new criteria passing").
+    #[serde(default)]
+    pub signals: Vec<String>,
+    /// Ways out suggested by a model, best first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suggestions: Vec<RecoverySuggestion>,
+}
+
+/// How a stuck thread can move on.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RecoveryAction {
+    /// Change models, iterations or verifiers and run again.
+    Reconfigure,
+    /// Go back and revise the spec.
+    EditSpec,
+    /// Fix the blocker by hand, then resume the loop.
+    ManualFix,
+}
+
+impl RecoveryAction {
+    /// Short label for display.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Reconfigure => "Reconfigure",
+            Self::EditSpec => "Edit spec",
+            Self::ManualFix => "Manual fix",
+        }
+    }
+
+    /// The phase a stuck thread moves to when taking this action.
+    pub fn target_phase(self) -> ThreadPhase {
+        match self {
+            Self::Reconfigure => ThreadPhase::Configuring,
+            Self::EditSpec => ThreadPhase::Drafting,
+            Self::ManualFix => ThreadPhase::Running { iteration: 1 },
+        }
+    }
+}
+
+/// A concrete way out of a stuck run.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RecoverySuggestion {
+    /// Which recovery path this takes.
+    pub action: RecoveryAction,
+    /// One-line summary.
+    pub title: String,
+    /// What exactly to change.
+    #[serde(default)]
+    pub details: String,
 }
 
 /// Captured git state for workspace reset.
@@ -438,6 +593,31 @@ pub struct GitBaseline {
     pub commit_sha: String,
     /// When the baseline was captured.
     pub captured_at: DateTime<Utc>,
+    /// Untracked files at capture time (outside `.ralf/`), which a restore
+    /// keeps.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub untracked_files: Vec<String>,
+    /// Uncommitted changes stashed when the baseline was captured.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stash: Option<StashedChanges>,
+}
+
+/// A thread's own branch.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ThreadBranch {
+    /// Branch name (`ralf/<slug>`).
+    pub name: String,
+    /// Branch it was created from, which cleanup switches back to.
+    pub base: String,
+}
+
+/// Uncommitted changes put aside in `git stash` before a run.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StashedChanges {
+    /// Commit SHA of the stash entry.
+    pub sha: String,
+    /// Files that were stashed, untracked ones included.
+    pub files: Vec<String>,
 }
 
 /// Configuration for implementation runs.
@@ -447,6 +627,21 @@ pub struct RunConfig {
     pub max_iterations: u32,
     /// Models to use (in order of preference).
     pub models: Vec<String>,
+    /// Directory runs work in, relative to the repository or absolute,
+    /// e.g. `services/api` in a monorepo (`None` = the repository root).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<PathBuf>,
+    /// Word the thread's runs promise when done, in place of the
+    /// configured `completion_promise` (`None` = the configured word).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completion_promise: Option<String>,
+}
+
+impl RunConfig {
+    /// The directory runs work in, for the repository at `repo_path`.
+    pub fn target_root(&self, repo_path: &Path) -> PathBuf {
+        crate::runner::target_root(repo_path, self.target.as_deref())
+    }
 }
 
 impl Default for RunConfig {
@@ -454,6 +649,8 @@ impl Default for RunConfig {
         Self {
             max_iterations: 5,
             models: vec!["claude-sonnet".to_string()],
+            target: None,
+            completion_promise: None,
         }
     }
 }
@@ -462,6 +659,18 @@ impl Default for RunConfig {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_prefer_model() {
+        let mut thread = Thread::new("Test feature");
+        thread.prefer_model("codex");
+        let models = &thread.run_config.as_ref().unwrap().models;
+        assert_eq!(models, &vec!["codex", "claude-sonnet"]);
+
+        thread.prefer_model("claude-sonnet");
+        let models = &thread.run_config.as_ref().unwrap().models;
+        assert_eq!(models, &vec!["claude-sonnet", "codex"]);
+    }
+
     #[test]
     fn test_thread_new() {
         let thread = Thread::new("Test feature");
@@ -478,6 +687,27 @@ mod tests {
         assert!(thread.current_run_id.is_none());
         assert!(thread.run_config.is_none());
         assert!(thread.baseline.is_none());
+        assert!(thread.branch.is_none());
+    }
+
+    #[test]
+    fn test_branch_slug() {
+        let mut thread = Thread::new("Add OAuth login (GitHub & Google)!");
+        thread.id = "1b9d6bcd-bbfd-4b2d-9b5d-ab8dfbbd4bed".into();
+        assert_eq!(
+            thread.branch_slug(),
+            "add-oauth-login-github-google-1b9d6bcd"
+        );
+
+        thread.title = "a very long title that goes on and on past the limit".into();
+        assert_eq!(
+            thread.branch_slug(),
+            "a-very-long-title-that-goes-on-and-on-1b9d6bcd"
+        );
+
+        thread.title = "???".into();
+        assert_eq!(thread.branch_slug(), "1b9d6bcd");
+        assert!(crate::git::GitSafety::validate_thread_id(&thread.branch_slug()).is_ok());
     }
 
     #[test]
@@ -498,6 +728,8 @@ mod tests {
                 best_criteria_passed: 2,
                 total_criteria: 5,
                 last_error: None,
+                signals: vec![],
+                suggestions: vec![],
             },
         };
         assert!(!thread.is_terminal());
@@ -595,6 +827,8 @@ mod tests {
                     best_criteria_passed: 0,
                     total_criteria: 1,
                     last_error: None,
+                    signals: vec![],
+                    suggestions: vec![],
                 },
             },
             ThreadPhase::Implemented,
@@ -636,6 +870,8 @@ mod tests {
                 best_criteria_passed: 3,
                 total_criteria: 5,
                 last_error: Some("Build failed".to_string()),
+                signals: vec![],
+                suggestions: vec![],
             },
         };
         let json = serde_json::to_string(&thread_stuck).expect("serialize stuck");
@@ -649,8 +885,15 @@ mod tests {
             best_criteria_passed: 2,
             total_criteria: 4,
             last_error: Some("error".to_string()),
+            signals: vec![],
+            suggestions: vec![RecoverySuggestion {
+                action: RecoveryAction::EditSpec,
+                title: "Split the task".to_string(),
+                details: String::new(),
+            }],
         };
         let json = serde_json::to_string(&diagnosis).expect("serialize diagnosis");
+        assert!(json.contains(r#""action":"edit_spec""#));
         let restored: StuckDiagnosis = serde_json::from_str(&json).expect("deserialize diagnosis");
         assert_eq!(diagnosis, restored);
 
@@ -659,6 +902,8 @@ mod tests {
             branch: "main".to_string(),
             commit_sha: "abc123def456".to_string(),
             captured_at: Utc::now(),
+            untracked_files: Vec::new(),
+            stash: None,
         };
         let json = serde_json::to_string(&baseline).expect("serialize baseline");
         let restored: GitBaseline = serde_json::from_str(&json).expect("deserialize baseline");
@@ -669,6 +914,8 @@ mod tests {
         let config = RunConfig {
             max_iterations: 10,
             models: vec!["model1".to_string(), "model2".to_string()],
+            target: Some(PathBuf::from("services/api")),
+            completion_promise: Some("SHIPPED".to_string()),
         };
         let json = serde_json::to_string(&config).expect("serialize config");
         let restored: RunConfig = serde_json::from_str(&json).expect("deserialize config");
@@ -768,6 +1015,8 @@ mod tests {
                     best_criteria_passed: 0,
                     total_criteria: 1,
                     last_error: None,
+                    signals: vec![],
+                    suggestions: vec![],
                 }
             })
             .is_ok());
@@ -798,6 +1047,8 @@ mod tests {
                     best_criteria_passed: 0,
                     total_criteria: 1,
                     last_error: None,
+                    signals: vec![],
+                    suggestions: vec![],
                 }
             })
             .is_ok());
@@ -815,6 +1066,8 @@ mod tests {
                 best_criteria_passed: 2,
                 total_criteria: 5,
                 last_error: None,
+                signals: vec![],
+                suggestions: vec![],
             },
         };
 
@@ -916,6 +1169,8 @@ mod tests {
                     best_criteria_passed: 0,
                     total_criteria: 1,
                     last_error: None,
+                    signals: vec![],
+                    suggestions: vec![],
                 },
             },
             ThreadPhase::Implemented,
@@ -964,6 +1219,25 @@ mod tests {
         assert_eq!(thread.phase, original_phase); // State unchanged
     }
 
+    #[test]
+    fn test_review_checklist_kept_through_approval() {
+        let checklist =
+            ReviewChecklist::generate(&[], &crate::parse_criteria("## Criteria\n- Works\n"), &[]);
+        let mut thread = Thread::new("Test");
+        thread.phase = ThreadPhase::PendingReview;
+        thread.review_checklist = Some(checklist);
+
+        let mut rejected = thread.clone();
+        rejected
+            .transition_to(ThreadPhase::Running { iteration: 1 })
+            .unwrap();
+        assert!(rejected.review_checklist.is_none());
+
+        thread.transition_to(ThreadPhase::Approved).unwrap();
+        thread.transition_to(ThreadPhase::ReadyToCommit).unwrap();
+        assert!(thread.review_checklist.is_some());
+    }
+
     #[test]
     fn test_available_transitions_from_drafting() {
         let mut thread = Thread::new("Test");
@@ -1022,6 +1296,8 @@ mod tests {
                 best_criteria_passed: 0,
                 total_criteria: 1,
                 last_error: None,
+                signals: vec![],
+                suggestions: vec![],
             },
         };
 
@@ -1076,6 +1352,20 @@ mod tests {
         assert!(msg.contains("Done"));
     }
 
+    #[test]
+    fn test_phase_kind_from_name() {
+        assert_eq!(PhaseKind::from_name("running"), Some(PhaseKind::Running));
+        assert_eq!(
+            PhaseKind::from_name("PendingReview"),
+            Some(PhaseKind::PendingReview)
+        );
+        assert_eq!(
+            PhaseKind::from_name("pending review"),
+            Some(PhaseKind::PendingReview)
+        );
+        assert_eq!(PhaseKind::from_name("bogus"), None);
+    }
+
     #[test]
     fn test_phase_kind_to_phase_with_defaults() {
         // Verify all PhaseKind variants can be converted to ThreadPhase
@@ -1115,8 +1405,12 @@ mod tests {
         // Implementation
         assert!(thread.transition_to(ThreadPhase::Preflight).is_ok());
         assert!(thread.transition_to(ThreadPhase::Configuring).is_ok());
-        assert!(thread.transition_to(ThreadPhase::Running { iteration: 1 }).is_ok());
-        assert!(thread.transition_to(ThreadPhase::Verifying { iteration: 1 }).is_ok());
+        assert!(thread
+            .transition_to(ThreadPhase::Running { iteration: 1 })
+            .is_ok());
+        assert!(thread
+            .transition_to(ThreadPhase::Verifying { iteration: 1 })
+            .is_ok());
         assert!(thread.transition_to(ThreadPhase::Implemented).is_ok());
 
         // Review
@@ -1154,6 +1448,8 @@ mod tests {
                     best_criteria_passed: 2,
                     total_criteria: 5,
                     last_error: Some("Tests failed".to_string()),
+                    signals: vec![],
+                    suggestions: vec![],
                 },
             })
             .unwrap();
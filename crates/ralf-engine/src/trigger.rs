@@ -0,0 +1,447 @@
+//! Start times for scheduled runs.
+//!
+//! `ralf run --at 02:00` waits until a time of day (or a full date and
+//! time) before starting, and `ralf run --cron "0 2 * * *"` starts a run
+//! every time a cron expression matches. Model quotas often reset
+//! overnight, so a run can be set to start the moment they do. If every
+//! model is still cooling down when the trigger fires, the run waits for
+//! the first one to come back ([`models_ready_at`]); `ralf run
+//! --wait-for-models` does the same without a start time.
+//!
+//! While a run waits, a [`PendingStart`] in `.ralf/pending_start.json`
+//! lets `ralf status` show it and `ralf cancel` call it off.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDateTime, TimeZone};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::lock::process_alive;
+use crate::ratelimit::parse_absolute;
+use crate::state::{atomic_write, current_timestamp, Cooldowns, StateError};
+
+/// Name of the pending start file inside `.ralf/`.
+pub const PENDING_START_FILE: &str = "pending_start.json";
+
+/// How far ahead [`CronSchedule::next_after`] looks for a match. Long
+/// enough for a February 29th in a leap year.
+const MAX_LOOKAHEAD_DAYS: i64 = 8 * 366;
+
+/// Errors from parsing a start time or cron expression.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TriggerError {
+    #[error("Can't read {0:?} as a time; use e.g. 02:00, 2am or \"2026-01-13 02:00\"")]
+    InvalidTime(String),
+
+    #[error("{0} is in the past")]
+    InPast(String),
+
+    #[error("Invalid cron expression {expr:?}: {reason}")]
+    InvalidCron { expr: String, reason: String },
+}
+
+/// When to start a run given with `--at`, as a Unix timestamp: the next
+/// time the time of day comes round, or the given date and time.
+///
+/// # Errors
+///
+/// Returns an error if the time can't be parsed or is already past.
+pub fn parse_start(text: &str) -> Result<u64, TriggerError> {
+    parse_start_at(text, Local::now())
+}
+
+/// Like [`parse_start`], relative to `now`.
+///
+/// # Errors
+///
+/// Returns an error if the time can't be parsed or is before `now`.
+pub fn parse_start_at(text: &str, now: DateTime<Local>) -> Result<u64, TriggerError> {
+    let at = parse_absolute(text.trim(), now)
+        .ok_or_else(|| TriggerError::InvalidTime(text.to_string()))?;
+    if at <= now.timestamp() {
+        return Err(TriggerError::InPast(text.to_string()));
+    }
+    u64::try_from(at).map_err(|_| TriggerError::InPast(text.to_string()))
+}
+
+/// A standard five-field cron expression (minute, hour, day of month,
+/// month, day of week), in local time.
+///
+/// Fields take `*`, numbers, ranges (`1-5`), steps (`*/15`, `0-30/10`) and
+/// comma-separated lists of those. Days of the week run from 0 (Sunday)
+/// to 6, with 7 also Sunday. As in cron, when both the day of the month
+/// and the day of the week are restricted, a day matching either counts.
+/// `@hourly`, `@daily` (or `@midnight`) and `@weekly` are accepted too.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    any_day_of_month: bool,
+    any_day_of_week: bool,
+}
+
+impl CronSchedule {
+    /// Parse a cron expression.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the field that can't be parsed.
+    pub fn parse(expr: &str) -> Result<Self, TriggerError> {
+        let expanded = match expr.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            other => other,
+        };
+        let invalid = |reason: String| TriggerError::InvalidCron {
+            expr: expr.to_string(),
+            reason,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day_of_month, month, day_of_week] = fields[..] else {
+            return Err(invalid(format!(
+                "expected 5 fields (minute hour day-of-month month day-of-week), found {}",
+                fields.len()
+            )));
+        };
+
+        let mut weekdays = parse_field(day_of_week, "day of week", 0, 7).map_err(invalid)?;
+        // 7 is Sunday too
+        if weekdays & (1 << 7) != 0 {
+            weekdays |= 1;
+        }
+        Ok(Self {
+            minutes: parse_field(minute, "minute", 0, 59).map_err(invalid)?,
+            hours: parse_field(hour, "hour", 0, 23).map_err(invalid)?,
+            days_of_month: parse_field(day_of_month, "day of month", 1, 31).map_err(invalid)?,
+            months: parse_field(month, "month", 1, 12).map_err(invalid)?,
+            days_of_week: weekdays,
+            any_day_of_month: day_of_month == "*",
+            any_day_of_week: day_of_week == "*",
+        })
+    }
+
+    /// The next matching minute from now, as a Unix timestamp.
+    pub fn next(&self) -> Option<u64> {
+        self.next_after(Local::now())
+            .and_then(|at| u64::try_from(at.timestamp()).ok())
+    }
+
+    /// The first matching minute after `after`, if there is one within a
+    /// few years.
+    pub fn next_after(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        let start = after.date_naive();
+        for offset in 0..MAX_LOOKAHEAD_DAYS {
+            let date = start + Duration::days(offset);
+            if !self.matches_day(
+                date.month(),
+                date.day(),
+                date.weekday().num_days_from_sunday(),
+            ) {
+                continue;
+            }
+            for hour in (0..24).filter(|h| bit(self.hours, *h)) {
+                for minute in (0..60).filter(|m| bit(self.minutes, *m)) {
+                    let Some(naive) = date.and_hms_opt(hour, minute, 0) else {
+                        continue;
+                    };
+                    // Times skipped by a daylight saving change never match
+                    let Some(at) = local(naive) else {
+                        continue;
+                    };
+                    if at > after {
+                        return Some(at);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Whether a day, given by its month, day of the month and day of the
+    /// week (0 = Sunday), matches.
+    fn matches_day(&self, month: u32, day: u32, weekday: u32) -> bool {
+        if !bit(self.months, month) {
+            return false;
+        }
+        let day_of_month = bit(self.days_of_month, day);
+        let day_of_week = bit(self.days_of_week, weekday);
+        match (self.any_day_of_month, self.any_day_of_week) {
+            (true, true) => true,
+            (false, true) => day_of_month,
+            (true, false) => day_of_week,
+            (false, false) => day_of_month || day_of_week,
+        }
+    }
+}
+
+/// Parse one cron field into a bitmask of the values it allows.
+fn parse_field(field: &str, name: &str, min: u32, max: u32) -> Result<u64, String> {
+    let number = |text: &str| -> Result<u32, String> {
+        match text.parse::<u32>() {
+            Ok(n) if (min..=max).contains(&n) => Ok(n),
+            _ => Err(format!(
+                "{name} {text:?} isn't a number from {min} to {max}"
+            )),
+        }
+    };
+
+    let mut mask = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => match step.parse::<u32>() {
+                Ok(step) if step > 0 => (range, step),
+                _ => return Err(format!("{name} step {step:?} isn't a positive number")),
+            },
+            None => (part, 1),
+        };
+        let (first, last) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((first, last)) => (number(first)?, number(last)?),
+                // "5/15" means from 5 to the end, every 15
+                None if step > 1 => (number(range)?, max),
+                None => {
+                    let n = number(range)?;
+                    (n, n)
+                }
+            },
+        };
+        if first > last {
+            return Err(format!("{name} range {range:?} runs backwards"));
+        }
+        for value in (first..=last).step_by(step as usize) {
+            mask |= 1 << value;
+        }
+    }
+    Ok(mask)
+}
+
+fn bit(mask: u64, value: u32) -> bool {
+    mask & (1 << value) != 0
+}
+
+fn local(naive: NaiveDateTime) -> Option<DateTime<Local>> {
+    Local.from_local_datetime(&naive).earliest()
+}
+
+/// When the first of `models` is out of cooldown, as a Unix timestamp no
+/// earlier than `at`. `None` if there are no models.
+pub fn models_ready_at<'a>(
+    models: impl IntoIterator<Item = &'a str>,
+    cooldowns: &Cooldowns,
+    at: u64,
+) -> Option<u64> {
+    models
+        .into_iter()
+        .map(|model| {
+            cooldowns
+                .entries
+                .get(model)
+                .map_or(at, |entry| entry.cooldown_until.max(at))
+        })
+        .min()
+}
+
+/// A run waiting for its start time or for models to come out of
+/// cooldown.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingStart {
+    /// PID of the waiting `ralf run`.
+    pub pid: u32,
+    /// When the run was asked for (Unix timestamp).
+    pub requested_at: u64,
+    /// When the run is expected to start (Unix timestamp).
+    pub start_at: u64,
+    /// Whether it is waiting for models to come out of cooldown, rather
+    /// than for a scheduled time.
+    #[serde(default)]
+    pub waiting_for_models: bool,
+}
+
+impl PendingStart {
+    /// A pending start for this process.
+    pub fn new(start_at: u64, waiting_for_models: bool) -> Self {
+        Self {
+            pid: std::process::id(),
+            requested_at: current_timestamp(),
+            start_at,
+            waiting_for_models,
+        }
+    }
+
+    /// Path of the pending start file for a `.ralf` directory.
+    pub fn path(ralf_dir: &Path) -> PathBuf {
+        ralf_dir.join(PENDING_START_FILE)
+    }
+
+    /// The pending start, if a live process is waiting.
+    pub fn load(ralf_dir: &Path) -> Result<Option<Self>, StateError> {
+        let path = Self::path(ralf_dir);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(&path).map_err(StateError::Io)?;
+        let pending: Self = serde_json::from_str(&content).map_err(StateError::Parse)?;
+        Ok(process_alive(pending.pid).then_some(pending))
+    }
+
+    /// Record the pending start.
+    pub fn save(&self, ralf_dir: &Path) -> Result<(), StateError> {
+        std::fs::create_dir_all(ralf_dir).map_err(StateError::Io)?;
+        let content = serde_json::to_string_pretty(self).map_err(StateError::Serialize)?;
+        atomic_write(&Self::path(ralf_dir), content.as_bytes()).map_err(StateError::Io)
+    }
+
+    /// Remove the pending start file, as `ralf cancel` does to call the
+    /// run off, or the run does once it starts.
+    pub fn remove(ralf_dir: &Path) {
+        let _ = std::fs::remove_file(Self::path(ralf_dir));
+    }
+}
+
+/// A wait in seconds as e.g. "1h 5m", "42m" or "30s".
+pub fn format_wait(secs: u64) -> String {
+    let (hours, minutes) = (secs / 3600, secs % 3600 / 60);
+    match (hours, minutes) {
+        (0, 0) => format!("{secs}s"),
+        (0, m) => format!("{m}m"),
+        (h, 0) => format!("{h}h"),
+        (h, m) => format!("{h}h {m}m"),
+    }
+}
+
+/// A Unix timestamp as local `HH:MM`, with the date if it isn't today.
+pub fn format_start(at: u64) -> String {
+    let Some(when) = i64::try_from(at)
+        .ok()
+        .and_then(|secs| Local.timestamp_opt(secs, 0).single())
+    else {
+        return at.to_string();
+    };
+    if when.date_naive() == Local::now().date_naive() {
+        when.format("%H:%M").to_string()
+    } else {
+        when.format("%a %b %-d %H:%M").to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Timelike;
+
+    fn now() -> DateTime<Local> {
+        // A Monday
+        Local.with_ymd_and_hms(2026, 1, 12, 14, 0, 0).unwrap()
+    }
+
+    fn minute_of_day(at: DateTime<Local>) -> u32 {
+        at.hour() * 60 + at.minute()
+    }
+
+    #[test]
+    fn test_parse_start() {
+        let start = |text: &str| {
+            parse_start_at(text, now()).map(|at| i64::try_from(at).unwrap() - now().timestamp())
+        };
+        assert_eq!(start("15:30"), Ok(5400));
+        assert_eq!(start("2am"), Ok(12 * 3600));
+        assert_eq!(start("2026-01-13 02:00"), Ok(12 * 3600));
+        assert_eq!(
+            start("2026-01-01 02:00"),
+            Err(TriggerError::InPast("2026-01-01 02:00".into()))
+        );
+        assert!(matches!(start("soon"), Err(TriggerError::InvalidTime(_))));
+    }
+
+    #[test]
+    fn test_cron_parse() {
+        assert!(CronSchedule::parse("0 2 * * *").is_ok());
+        assert!(CronSchedule::parse("*/15 9-17 * * 1-5").is_ok());
+        assert_eq!(
+            CronSchedule::parse("@daily"),
+            CronSchedule::parse("0 0 * * *")
+        );
+
+        let error = |expr: &str| match CronSchedule::parse(expr) {
+            Err(TriggerError::InvalidCron { reason, .. }) => reason,
+            other => panic!("expected an error for {expr:?}, got {other:?}"),
+        };
+        assert!(error("0 2 * *").contains("expected 5 fields"));
+        assert!(error("60 2 * * *").contains("minute \"60\""));
+        assert!(error("0 2 * * mon").contains("day of week"));
+        assert!(error("*/0 * * * *").contains("step"));
+        assert!(error("0 5-2 * * *").contains("backwards"));
+    }
+
+    #[test]
+    fn test_cron_next_after() {
+        let next = |expr: &str| {
+            CronSchedule::parse(expr)
+                .unwrap()
+                .next_after(now())
+                .unwrap()
+        };
+
+        // Later today
+        assert_eq!(minute_of_day(next("30 14 * * *")), 14 * 60 + 30);
+        assert_eq!(next("*/15 * * * *") - now(), Duration::minutes(15));
+        // Tomorrow at 02:00
+        assert_eq!(next("0 2 * * *") - now(), Duration::hours(12));
+        // Saturday (also as 6), then Sunday as 7
+        assert_eq!(next("0 9 * * 6").date_naive().day(), 17);
+        assert_eq!(next("0 9 * * 7").date_naive().day(), 18);
+        // Day of month or day of week, whichever is first
+        assert_eq!(next("0 9 20 * 3").date_naive().day(), 14);
+        assert_eq!(next("0 9 13 * 6").date_naive().day(), 13);
+        // Only in leap years
+        assert_eq!(next("0 0 29 2 *").date_naive().year(), 2028);
+        assert_eq!(
+            CronSchedule::parse("0 0 31 2 *").unwrap().next_after(now()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_models_ready_at() {
+        let at = 1_000;
+        let mut cooldowns = Cooldowns::default();
+        assert_eq!(
+            models_ready_at(["claude", "codex"], &cooldowns, at),
+            Some(at)
+        );
+        assert_eq!(models_ready_at([], &cooldowns, at), None);
+
+        cooldowns.set_cooldown_until("claude", 5_000, "rate limit", None);
+        cooldowns.set_cooldown_until("codex", 3_000, "rate limit", None);
+        assert_eq!(
+            models_ready_at(["claude", "codex"], &cooldowns, at),
+            Some(3_000)
+        );
+        assert_eq!(models_ready_at(["claude"], &cooldowns, at), Some(5_000));
+        // Cooldowns over by the trigger time don't hold it back
+        assert_eq!(models_ready_at(["codex"], &cooldowns, 4_000), Some(4_000));
+    }
+
+    #[test]
+    fn test_pending_start() {
+        let temp = tempfile::tempdir().unwrap();
+        assert_eq!(PendingStart::load(temp.path()).unwrap(), None);
+
+        let pending = PendingStart::new(current_timestamp() + 600, true);
+        pending.save(temp.path()).unwrap();
+        assert_eq!(PendingStart::load(temp.path()).unwrap(), Some(pending));
+        PendingStart::remove(temp.path());
+        assert_eq!(PendingStart::load(temp.path()).unwrap(), None);
+
+        assert_eq!(format_wait(30), "30s");
+        assert_eq!(format_wait(42 * 60 + 10), "42m");
+        assert_eq!(format_wait(2 * 3600), "2h");
+        assert_eq!(format_wait(3900), "1h 5m");
+    }
+}
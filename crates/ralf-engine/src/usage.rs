@@ -0,0 +1,330 @@
+//! Token and cost usage tracking for ralf engine.
+//!
+//! Model CLIs report usage in their own formats (claude's JSON result
+//! includes `total_cost_usd` and token counts, codex prints a
+//! `tokens used` summary). This module parses those reports and keeps
+//! per-run and per-model totals in `.ralf/usage.json`.
+//!
+//! Only the CLIs' own reports are read: the JSON result claude prints last
+//! on stdout, and summaries on stderr. A model that mentions a cost or
+//! token count in its answer doesn't change the numbers.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::state::{atomic_write, current_timestamp};
+
+/// Token and cost usage for one or more model invocations.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Usage {
+    /// Input (prompt) tokens.
+    #[serde(default)]
+    pub input_tokens: u64,
+
+    /// Output (completion) tokens.
+    #[serde(default)]
+    pub output_tokens: u64,
+
+    /// Total tokens, including any the CLI did not break down.
+    #[serde(default)]
+    pub total_tokens: u64,
+
+    /// Cost in US dollars (0 if the CLI does not report cost).
+    #[serde(default)]
+    pub cost_usd: f64,
+}
+
+impl Usage {
+    /// Add another usage report to this one.
+    pub fn add(&mut self, other: &Usage) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.total_tokens += other.total_tokens;
+        self.cost_usd += other.cost_usd;
+    }
+
+    /// Whether nothing has been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.total_tokens == 0 && self.cost_usd == 0.0
+    }
+
+    /// Whether this usage has reached either budget limit.
+    pub fn exceeds(&self, max_cost_usd: Option<f64>, max_tokens: Option<u64>) -> bool {
+        max_cost_usd.is_some_and(|max| self.cost_usd >= max)
+            || max_tokens.is_some_and(|max| self.total_tokens >= max)
+    }
+
+    /// Compact summary for status lines (e.g., "12.3k tok · $0.42").
+    pub fn summary(&self) -> String {
+        let tokens = format!("{} tok", format_count(self.total_tokens));
+        if self.cost_usd > 0.0 {
+            format!("{tokens} · ${:.2}", self.cost_usd)
+        } else {
+            tokens
+        }
+    }
+}
+
+/// Format a token count compactly (e.g., 950, 12.3k, 1.2M).
+#[allow(clippy::cast_precision_loss)]
+fn format_count(count: u64) -> String {
+    if count >= 1_000_000 {
+        format!("{:.1}M", count as f64 / 1_000_000.0)
+    } else if count >= 1_000 {
+        format!("{:.1}k", count as f64 / 1_000.0)
+    } else {
+        count.to_string()
+    }
+}
+
+/// Parse usage from any supported model CLI's stdout and stderr.
+pub fn parse_usage(stdout: &str, stderr: &str) -> Option<Usage> {
+    parse_claude_usage(stdout, stderr).or_else(|| parse_codex_usage(stderr))
+}
+
+/// Parse usage from claude's stdout and stderr.
+///
+/// Understands the JSON result (`--output-format json`, which the claude
+/// adapter asks for, or `stream-json`), the last line of stdout, which
+/// reports `total_cost_usd` and a `usage` object, and a `Total cost: $X`
+/// summary on stderr.
+pub fn parse_claude_usage(stdout: &str, stderr: &str) -> Option<Usage> {
+    if let Some(result) = claude_result(stdout) {
+        let tokens = |key: &str| result["usage"][key].as_u64().unwrap_or(0);
+        let (input_tokens, output_tokens) = (tokens("input_tokens"), tokens("output_tokens"));
+        return Some(Usage {
+            input_tokens,
+            output_tokens,
+            total_tokens: input_tokens + output_tokens,
+            cost_usd: result["total_cost_usd"].as_f64().unwrap_or(0.0),
+        });
+    }
+    let cost = capture_f64(stderr, r"(?i)total cost:\s*\$([0-9.]+)")?;
+    Some(Usage {
+        cost_usd: cost,
+        ..Usage::default()
+    })
+}
+
+/// Claude's JSON result, if it is the last line of `stdout`.
+pub(crate) fn claude_result(stdout: &str) -> Option<serde_json::Value> {
+    let last = stdout.lines().rev().find(|line| !line.trim().is_empty())?;
+    let result: serde_json::Value = serde_json::from_str(last.trim()).ok()?;
+    (result["type"] == "result").then_some(result)
+}
+
+/// Parse usage from codex's stderr.
+///
+/// `codex exec` prints its progress, ending in a `tokens used: 1,234`
+/// summary (newer versions put the number on the following line), to
+/// stderr, and only the answer to stdout. It does not report cost.
+pub fn parse_codex_usage(stderr: &str) -> Option<Usage> {
+    let re = Regex::new(r"(?i)tokens used:?\s*([\d,]+)").expect("Invalid regex pattern");
+    let total = re
+        .captures_iter(stderr)
+        .filter_map(|c| c[1].replace(',', "").parse::<u64>().ok())
+        .last()?;
+    Some(Usage {
+        total_tokens: total,
+        ..Usage::default()
+    })
+}
+
+/// Get the last match of a single-capture regex as an `f64`.
+fn capture_f64(output: &str, pattern: &str) -> Option<f64> {
+    let re = Regex::new(pattern).expect("Invalid regex pattern");
+    re.captures_iter(output)
+        .filter_map(|c| c[1].parse().ok())
+        .last()
+}
+
+/// Usage totals for a single run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunUsage {
+    /// Totals across all models.
+    pub total: Usage,
+
+    /// Per-model totals.
+    pub models: BTreeMap<String, Usage>,
+
+    /// When usage was last recorded (Unix timestamp).
+    pub updated_at: u64,
+}
+
+/// Persisted usage ledger (`.ralf/usage.json`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageLedger {
+    /// Per-run usage, keyed by run ID.
+    #[serde(default)]
+    pub runs: BTreeMap<String, RunUsage>,
+}
+
+impl UsageLedger {
+    /// Load the ledger from a file.
+    pub fn load(path: &Path) -> Result<Self, UsageError> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path).map_err(UsageError::Io)?;
+        serde_json::from_str(&content).map_err(UsageError::Parse)
+    }
+
+    /// Save the ledger to a file.
+    pub fn save(&self, path: &Path) -> Result<(), UsageError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(UsageError::Io)?;
+        }
+        let content = serde_json::to_string_pretty(self).map_err(UsageError::Serialize)?;
+        atomic_write(path, content.as_bytes()).map_err(UsageError::Io)
+    }
+
+    /// Record usage for a model invocation, returning the updated run totals.
+    pub fn record(&mut self, run_id: &str, model: &str, usage: &Usage) -> &RunUsage {
+        let run = self.runs.entry(run_id.to_string()).or_default();
+        run.total.add(usage);
+        run.models.entry(model.to_string()).or_default().add(usage);
+        run.updated_at = current_timestamp();
+        run
+    }
+
+    /// Get usage for a run.
+    pub fn run(&self, run_id: &str) -> Option<&RunUsage> {
+        self.runs.get(run_id)
+    }
+
+    /// Totals across all runs.
+    pub fn total(&self) -> Usage {
+        let mut total = Usage::default();
+        for run in self.runs.values() {
+            total.add(&run.total);
+        }
+        total
+    }
+}
+
+/// Errors that can occur when working with usage data.
+#[derive(Debug, thiserror::Error)]
+pub enum UsageError {
+    /// I/O error.
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Parse error.
+    #[error("Parse error: {0}")]
+    Parse(#[source] serde_json::Error),
+
+    /// Serialize error.
+    #[error("Serialize error: {0}")]
+    Serialize(#[source] serde_json::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_claude_json_usage() {
+        let output = r#"{"type":"result","total_cost_usd":0.0421,"usage":{"input_tokens":1200,"cache_read_input_tokens":50,"output_tokens":300}}"#;
+        let usage = parse_claude_usage(output, "").unwrap();
+        assert_eq!(usage.input_tokens, 1200);
+        assert_eq!(usage.output_tokens, 300);
+        assert_eq!(usage.total_tokens, 1500);
+        assert!((usage.cost_usd - 0.0421).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_parse_claude_text_cost() {
+        let usage = parse_claude_usage("Done.\n", "Total cost: $0.1234\n").unwrap();
+        assert!((usage.cost_usd - 0.1234).abs() < f64::EPSILON);
+        assert_eq!(usage.total_tokens, 0);
+    }
+
+    #[test]
+    fn test_answer_does_not_report_usage() {
+        let answer = "Total cost: $12.50, tokens used: 9,999\n\"total_cost_usd\": 3.0\n";
+        assert!(parse_usage(answer, "").is_none());
+        assert!(parse_claude_usage(answer, "").is_none());
+    }
+
+    #[test]
+    fn test_parse_codex_usage() {
+        assert_eq!(
+            parse_codex_usage("[2026-01-01] tokens used: 12,345")
+                .unwrap()
+                .total_tokens,
+            12_345
+        );
+        assert_eq!(
+            parse_codex_usage("tokens used\n987\n")
+                .unwrap()
+                .total_tokens,
+            987
+        );
+        assert!(parse_codex_usage("no usage here").is_none());
+    }
+
+    #[test]
+    fn test_parse_usage_none() {
+        assert!(parse_usage("Just some model output", "").is_none());
+    }
+
+    #[test]
+    fn test_usage_summary() {
+        let usage = Usage {
+            total_tokens: 12_345,
+            cost_usd: 0.42,
+            ..Usage::default()
+        };
+        assert_eq!(usage.summary(), "12.3k tok · $0.42");
+
+        let usage = Usage {
+            total_tokens: 950,
+            ..Usage::default()
+        };
+        assert_eq!(usage.summary(), "950 tok");
+    }
+
+    #[test]
+    fn test_usage_exceeds() {
+        let usage = Usage {
+            total_tokens: 1_000,
+            cost_usd: 1.0,
+            ..Usage::default()
+        };
+        assert!(!usage.exceeds(None, None));
+        assert!(usage.exceeds(Some(1.0), None));
+        assert!(!usage.exceeds(Some(1.5), None));
+        assert!(usage.exceeds(None, Some(500)));
+        assert!(!usage.exceeds(Some(2.0), Some(2_000)));
+    }
+
+    #[test]
+    fn test_ledger_record_and_persist() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("usage.json");
+
+        let mut ledger = UsageLedger::default();
+        let one = Usage {
+            input_tokens: 10,
+            output_tokens: 5,
+            total_tokens: 15,
+            cost_usd: 0.5,
+        };
+        ledger.record("run1", "claude", &one);
+        let run = ledger.record("run1", "codex", &one);
+        assert_eq!(run.total.total_tokens, 30);
+        assert_eq!(run.models.len(), 2);
+        ledger.record("run2", "claude", &one);
+        ledger.save(&path).unwrap();
+
+        let loaded = UsageLedger::load(&path).unwrap();
+        assert_eq!(
+            loaded.run("run1").unwrap().models["claude"].total_tokens,
+            15
+        );
+        assert_eq!(loaded.total().total_tokens, 45);
+        assert!((loaded.total().cost_usd - 1.5).abs() < f64::EPSILON);
+    }
+}
@@ -0,0 +1,173 @@
+//! Cache of AI verification verdicts.
+//!
+//! Verifying completion criteria costs a model call per iteration. When
+//! neither a criterion nor the workspace has changed since it was last
+//! verified, the previous verdict is reused. Entries are keyed on the
+//! criterion text and the working tree hash (see
+//! [`GitSafety::worktree_tree_hash`](crate::git::GitSafety::worktree_tree_hash))
+//! and stored in `.ralf/verify-cache.json`.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::state::{atomic_write, current_timestamp, StateError};
+
+/// Name of the cache file inside `.ralf/`.
+pub const VERIFY_CACHE_FILE: &str = "verify-cache.json";
+
+/// Maximum number of verdicts kept; the oldest are dropped first.
+const MAX_ENTRIES: usize = 500;
+
+/// A cached verdict for one criterion in one workspace state.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedVerdict {
+    /// Whether the criterion passed.
+    pub passed: bool,
+    /// Reason given for the verdict.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// When the verdict was recorded (Unix timestamp).
+    pub verified_at: u64,
+}
+
+/// Verification verdicts keyed on (criterion, tree hash).
+#[derive(Debug, Clone, Default)]
+pub struct VerifyCache {
+    entries: HashMap<String, CachedVerdict>,
+}
+
+impl VerifyCache {
+    /// Path of the cache file for a `.ralf` directory.
+    pub fn path(ralf_dir: &Path) -> PathBuf {
+        ralf_dir.join(VERIFY_CACHE_FILE)
+    }
+
+    /// Load the cache from a file (empty if it doesn't exist).
+    pub fn load(path: &Path) -> Result<Self, StateError> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path).map_err(StateError::Io)?;
+        let entries = serde_json::from_str(&content).map_err(StateError::Parse)?;
+        Ok(Self { entries })
+    }
+
+    /// Save the cache to a file.
+    pub fn save(&self, path: &Path) -> Result<(), StateError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(StateError::Io)?;
+        }
+        let content = serde_json::to_string_pretty(&self.entries).map_err(StateError::Serialize)?;
+        atomic_write(path, content.as_bytes()).map_err(StateError::Io)
+    }
+
+    /// Look up the verdict for `criterion` in workspace state `tree_hash`.
+    pub fn get(&self, criterion: &str, tree_hash: &str) -> Option<&CachedVerdict> {
+        self.entries.get(&cache_key(criterion, tree_hash))
+    }
+
+    /// Record a verdict, evicting the oldest entries if the cache is full.
+    pub fn insert(
+        &mut self,
+        criterion: &str,
+        tree_hash: &str,
+        passed: bool,
+        reason: Option<String>,
+    ) {
+        self.entries.insert(
+            cache_key(criterion, tree_hash),
+            CachedVerdict {
+                passed,
+                reason,
+                verified_at: current_timestamp(),
+            },
+        );
+
+        if self.entries.len() > MAX_ENTRIES {
+            let mut by_age: Vec<(u64, String)> = self
+                .entries
+                .iter()
+                .map(|(key, v)| (v.verified_at, key.clone()))
+                .collect();
+            by_age.sort();
+            let excess = self.entries.len() - MAX_ENTRIES;
+            for (_, key) in by_age.into_iter().take(excess) {
+                self.entries.remove(&key);
+            }
+        }
+    }
+
+    /// Number of cached verdicts.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Key for a (criterion, tree hash) pair.
+fn cache_key(criterion: &str, tree_hash: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(tree_hash.as_bytes());
+    hasher.update([0]);
+    hasher.update(criterion.trim().as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_cache_roundtrip() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = VerifyCache::path(temp.path());
+
+        let mut cache = VerifyCache::load(&path).unwrap();
+        assert!(cache.is_empty());
+        cache.insert("Tests pass", "tree1", true, None);
+        cache.insert(
+            "Docs updated",
+            "tree1",
+            false,
+            Some("README unchanged".into()),
+        );
+        cache.save(&path).unwrap();
+
+        let cache = VerifyCache::load(&path).unwrap();
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get("Tests pass", "tree1").unwrap().passed);
+        assert_eq!(
+            cache
+                .get("Docs updated", "tree1")
+                .unwrap()
+                .reason
+                .as_deref(),
+            Some("README unchanged")
+        );
+        // A different workspace state or criterion misses
+        assert!(cache.get("Tests pass", "tree2").is_none());
+        assert!(cache.get("Tests pass quickly", "tree1").is_none());
+    }
+
+    #[test]
+    fn test_verify_cache_evicts_oldest() {
+        let mut cache = VerifyCache::default();
+        for i in 0..MAX_ENTRIES {
+            cache.insert(&format!("criterion {i}"), "tree", true, None);
+        }
+        // Make one entry clearly the oldest
+        let oldest = cache_key("criterion 0", "tree");
+        cache.entries.get_mut(&oldest).unwrap().verified_at = 0;
+
+        cache.insert("one more", "tree", true, None);
+        assert_eq!(cache.len(), MAX_ENTRIES);
+        assert!(cache.get("criterion 0", "tree").is_none());
+        assert!(cache.get("one more", "tree").is_some());
+    }
+}
@@ -0,0 +1,25 @@
+//! Runs every scenario in `tests/scenarios/` against the mock model.
+
+use std::path::Path;
+
+use ralf_engine::{run_scenario, Scenario};
+
+#[tokio::test]
+async fn test_scenarios() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/scenarios");
+    let mut paths: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty(), "no scenarios in {}", dir.display());
+
+    for path in paths {
+        let scenario = Scenario::load(&path).unwrap();
+        let repo = tempfile::TempDir::new().unwrap();
+        if let Err(e) = run_scenario(&scenario, repo.path()).await {
+            panic!("{}: {e}", path.display());
+        }
+    }
+}
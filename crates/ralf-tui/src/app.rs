@@ -3,9 +3,10 @@
 use crate::event::Action;
 use crate::ui::widgets::TextInputState;
 use ralf_engine::{
-    discover_models, draft_has_promise, extract_spec_from_response, get_git_info, parse_criteria,
-    save_draft_snapshot, ChatMessage, Config, GitInfo, ModelConfig, ModelInfo, ProbeResult,
-    RunConfig, RunEvent, RunHandle, Thread,
+    discover_models, draft_has_promise, extract_spec_from_response, get_git_info, no_op_warning,
+    parse_criteria, save_draft_snapshot, save_warning, secrets_warning, violation_summary,
+    ChatMessage, Config, Criterion, GitInfo, ModelConfig, ModelInfo, ProbeResult, RunConfig,
+    RunEvent, RunHandle, Thread, Usage,
 };
 use std::collections::VecDeque;
 use std::path::PathBuf;
@@ -84,8 +85,8 @@ pub struct RunState {
     pub model_output: String,
     /// Verifier results: (name, passed, `duration_ms`).
     pub verifier_results: Vec<(String, bool, u64)>,
-    /// Active cooldowns: (model, `remaining_secs`).
-    pub cooldowns: Vec<(String, u64)>,
+    /// Active cooldowns: (model, when the cooldown ends).
+    pub cooldowns: Vec<(String, Instant)>,
     /// Event log messages (bounded to `MAX_EVENTS`).
     pub events: VecDeque<String>,
     /// Scroll offset for output.
@@ -99,11 +100,13 @@ pub struct RunState {
     /// Error message (if failed).
     pub error_message: Option<String>,
     /// Parsed completion criteria from PROMPT.md.
-    pub criteria: Vec<String>,
+    pub criteria: Vec<Criterion>,
     /// Verification status for each criterion.
     pub criteria_status: Vec<CriterionStatus>,
     /// Model performing verification (if verifying).
     pub verifier_model: Option<String>,
+    /// Token/cost usage totals for this run.
+    pub usage: Option<Usage>,
 }
 
 impl RunState {
@@ -219,6 +222,7 @@ impl App {
                 path: Some("/usr/bin/claude".to_string()),
                 version: Some("1.0.0".to_string()),
                 issues: vec![],
+                auth_command: None,
             },
             probe_result: None,
             probing: false,
@@ -266,7 +270,11 @@ impl App {
         let git_info = get_git_info();
         let config_path = repo_path.join(".ralf").join("config.json");
         let config_exists = config_path.exists();
-        let config = Config::load(&config_path).ok();
+        // Global settings only apply once the repo has been set up
+        let config = config_exists
+            .then(|| Config::load_layered(&repo_path).ok())
+            .flatten()
+            .map(|layered| layered.config);
 
         // Discover available models
         let discovered = discover_models();
@@ -876,7 +884,18 @@ impl App {
             max_runtime_secs: 0, // No timeout for now
             prompt_path,
             repo_path: self.repo_path.clone(),
+            target: None,
             criteria: self.run_state.criteria.clone(),
+            resume: None,
+            force: false,
+            max_cost_usd: None,
+            max_tokens: None,
+            no_verify_cache: false,
+            iteration_timeout_seconds: None,
+            scheduler: None,
+            dirs: None,
+            clock: None,
+            spawner: None,
         };
 
         // Update git info at run start
@@ -936,10 +955,16 @@ impl App {
             RunEvent::Started {
                 run_id,
                 max_iterations,
+                branch,
             } => {
                 self.run_state.run_id = Some(run_id.clone());
                 self.run_state.max_iterations = max_iterations;
-                self.run_state.push_event(format!("Run {run_id} started"));
+                match branch {
+                    Some(branch) => self
+                        .run_state
+                        .push_event(format!("Run {run_id} started on branch {branch}")),
+                    None => self.run_state.push_event(format!("Run {run_id} started")),
+                }
             }
             RunEvent::IterationStarted { iteration, model } => {
                 self.run_state.status = RunStatus::Running;
@@ -994,28 +1019,49 @@ impl App {
                 name,
                 passed,
                 duration_ms,
+                skipped,
             } => {
                 self.run_state
                     .verifier_results
                     .push((name.clone(), passed, duration_ms));
-                let status = if passed { "PASS" } else { "FAIL" };
+                let status = if skipped {
+                    "SKIPPED"
+                } else if passed {
+                    "PASS"
+                } else {
+                    "FAIL"
+                };
                 self.run_state
                     .push_event(format!("Verifier {name}: {status}"));
                 let _ = iteration;
             }
+            RunEvent::UsageUpdated {
+                iteration,
+                model,
+                usage,
+                run_total,
+            } => {
+                self.run_state.usage = Some(run_total);
+                self.run_state.push_event(format!(
+                    "Usage {model} (iter {iteration}): {}",
+                    usage.summary()
+                ));
+            }
             RunEvent::CooldownStarted {
                 model,
                 duration_secs,
             } => {
-                self.run_state
-                    .cooldowns
-                    .push((model.clone(), duration_secs));
+                self.run_state.cooldowns.push((
+                    model.clone(),
+                    Instant::now() + std::time::Duration::from_secs(duration_secs),
+                ));
                 self.run_state
                     .push_event(format!("{model} in cooldown ({duration_secs}s)"));
             }
             RunEvent::VerificationStarted {
                 iteration,
                 model,
+                implementer,
                 criteria_count,
             } => {
                 self.run_state.status = RunStatus::Verifying;
@@ -1026,13 +1072,16 @@ impl App {
                     self.run_state.criteria_status[0] = CriterionStatus::Verifying;
                 }
                 self.run_state.push_event(format!(
-                    "Verifying {criteria_count} criteria with {model} (iter {iteration})"
+                    "Verifying {criteria_count} criteria with {model} (work by {implementer}, iter {iteration})"
                 ));
             }
             RunEvent::CriterionVerified {
                 index,
+                required,
                 passed,
                 reason,
+                cached,
+                ..
             } => {
                 // Update this criterion's status
                 if index < self.run_state.criteria_status.len() {
@@ -1048,8 +1097,10 @@ impl App {
                 }
                 let status = if passed { "PASS" } else { "FAIL" };
                 let reason_str = reason.map(|r| format!(" - {r}")).unwrap_or_default();
+                let cached_str = if cached { " (cached)" } else { "" };
+                let optional_str = if required { "" } else { " (optional)" };
                 self.run_state.push_event(format!(
-                    "Criterion {}: {status}{reason_str}",
+                    "Criterion {}{optional_str}: {status}{cached_str}{reason_str}",
                     index + 1
                 ));
             }
@@ -1067,6 +1118,11 @@ impl App {
                     "Iteration {iteration} complete - verifiers: {status}"
                 ));
             }
+            RunEvent::CompletionReported { iteration, report } => {
+                let summary = report.summary.unwrap_or_default();
+                self.run_state
+                    .push_event(format!("Completion report (iter {iteration}): {summary}"));
+            }
             RunEvent::Completed { iteration, reason } => {
                 self.run_state.status = RunStatus::Completed;
                 self.run_state.completion_reason = Some(reason.clone());
@@ -1087,6 +1143,20 @@ impl App {
                 // Refresh git info in background to show final state
                 self.spawn_git_info_update();
             }
+            RunEvent::Stuck {
+                iteration,
+                diagnosis,
+            } => {
+                let error = format!("Stuck: {}", diagnosis.signals.join("; "));
+                self.run_state.status = RunStatus::Failed;
+                self.run_state
+                    .push_event(format!("Stuck at iteration {iteration}: {error}"));
+                self.run_state.error_message = Some(error);
+                self.run_handle = None;
+                self.run_event_rx = None;
+                // Refresh git info in background to show final state
+                self.spawn_git_info_update();
+            }
             RunEvent::Cancelled { iteration } => {
                 self.run_state.status = RunStatus::Cancelled;
                 self.run_state
@@ -1096,6 +1166,78 @@ impl App {
                 // Refresh git info in background to show final state
                 self.spawn_git_info_update();
             }
+            RunEvent::Paused { iteration } => {
+                self.run_state
+                    .push_event(format!("Paused after iteration {iteration}"));
+            }
+            RunEvent::Resumed { iteration } => {
+                self.run_state
+                    .push_event(format!("Resumed at iteration {iteration}"));
+            }
+            RunEvent::ReviewRequired {
+                iteration, reasons, ..
+            } => {
+                self.run_state.push_event(format!(
+                    "Iteration {iteration} needs review: {}",
+                    reasons.join("; ")
+                ));
+            }
+            RunEvent::ReviewResolved {
+                iteration,
+                approved,
+            } => {
+                let decision = if approved { "approved" } else { "rejected" };
+                self.run_state
+                    .push_event(format!("Iteration {iteration} changes {decision}"));
+            }
+            RunEvent::QuestionAsked {
+                iteration,
+                question,
+            } => {
+                self.run_state
+                    .push_event(format!("Iteration {iteration} asks: {question}"));
+            }
+            RunEvent::QuestionAnswered { iteration, answer } => {
+                self.run_state
+                    .push_event(format!("Answered iteration {iteration}: {answer}"));
+            }
+            RunEvent::SafetyViolation {
+                iteration,
+                reverted,
+                forbidden_commands,
+            } => {
+                self.run_state.push_event(format!(
+                    "Iteration {iteration} {}",
+                    violation_summary(&reverted, &forbidden_commands)
+                ));
+            }
+            RunEvent::SecretsDetected {
+                iteration,
+                findings,
+            } => {
+                self.run_state
+                    .push_event(secrets_warning(iteration, &findings));
+            }
+            RunEvent::NoOp {
+                iteration,
+                model,
+                consecutive,
+            } => {
+                self.run_state
+                    .push_event(no_op_warning(iteration, &model, consecutive));
+            }
+            RunEvent::IterationCommitted {
+                iteration,
+                commit_sha,
+            } => {
+                let short = commit_sha.get(..8).unwrap_or(&commit_sha);
+                self.run_state
+                    .push_event(format!("Iteration {iteration} committed: {short}"));
+            }
+            RunEvent::SaveFailed { what, error } => {
+                self.run_state
+                    .push_event(format!("Warning: {}", save_warning(&what, &error)));
+            }
             RunEvent::Status { message } => {
                 self.run_state.push_event(message);
             }
@@ -1145,6 +1287,7 @@ mod tests {
             path: Some("/usr/bin/test".to_string()),
             version: None,
             issues: vec![],
+            auth_command: None,
         };
         let status = ModelStatus {
             info,
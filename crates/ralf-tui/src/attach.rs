@@ -0,0 +1,273 @@
+//! Attaching the shell to runs other processes own.
+//!
+//! A run started with `ralf run`, `ralf serve` or `ralf queue start` isn't
+//! the shell's, but the shell can still show it. [`find_external_run`]
+//! looks for one in `.ralf/`: a server named in `serve.json`, or else a
+//! run in `state.json` whose lock another live process holds. Its events
+//! come from the server's event stream or, without a server, from tailing
+//! the run's `events.jsonl`, into the same channel a run started in the
+//! shell sends them to.
+//!
+//! [`RunControl`] steers whichever run is attached. A served run takes
+//! every control; an unserved one can only be cancelled, by sending its
+//! process SIGTERM as Ctrl+C in its terminal would.
+
+use std::time::Duration;
+
+use ralf_engine::runner::{RunEvent, RunHandle};
+use ralf_engine::{
+    events_path, parse_event_line, EventTail, LockStatus, RalfDirs, RemoteAction, RemoteClient,
+    RunLock, RunState,
+};
+use tokio::sync::mpsc as tokio_mpsc;
+
+/// How often the events of an unserved run are read.
+const TAIL_POLL: Duration = Duration::from_millis(250);
+
+/// Controls for the run the shell shows.
+#[derive(Debug)]
+pub enum RunControl {
+    /// A run the shell started.
+    Local(RunHandle),
+    /// A run `ralf serve` is serving.
+    Remote(RemoteClient),
+    /// A run another process started without serving it.
+    External {
+        /// PID of the process going through the run.
+        pid: u32,
+    },
+}
+
+impl RunControl {
+    /// Whether pausing, resuming and review decisions reach the run.
+    pub fn can_steer(&self) -> bool {
+        !matches!(self, Self::External { .. })
+    }
+
+    /// Pause the run once the current model invocation finishes. Returns
+    /// false if it was already paused or can't be reached.
+    pub fn pause(&self) -> bool {
+        match self {
+            Self::Local(handle) => handle.pause(),
+            Self::Remote(client) => client.send(RemoteAction::Pause).is_ok(),
+            Self::External { .. } => false,
+        }
+    }
+
+    /// Resume a paused run. Returns false if it wasn't paused or can't be
+    /// reached.
+    pub fn resume(&self) -> bool {
+        match self {
+            Self::Local(handle) => handle.resume(),
+            Self::Remote(client) => client.send(RemoteAction::Resume).is_ok(),
+            Self::External { .. } => false,
+        }
+    }
+
+    /// Whether a pause has been asked for.
+    pub fn is_paused(&self) -> bool {
+        match self {
+            Self::Local(handle) => handle.is_paused(),
+            Self::Remote(client) => client.status().is_ok_and(|s| s.pause_requested),
+            Self::External { .. } => false,
+        }
+    }
+
+    /// Cancel the run. Returns whether the request reached it.
+    pub fn try_cancel(&self) -> bool {
+        match self {
+            Self::Local(handle) => handle.try_cancel(),
+            Self::Remote(client) => client.send(RemoteAction::Cancel).is_ok(),
+            Self::External { pid } => terminate(*pid),
+        }
+    }
+
+    /// Approve the changes the run holds for review.
+    pub fn approve_review(&self) -> bool {
+        match self {
+            Self::Local(handle) => handle.approve_review(),
+            Self::Remote(client) => client.send(RemoteAction::Approve).is_ok(),
+            Self::External { .. } => false,
+        }
+    }
+
+    /// Answer the question the model asked. Returns false if the run
+    /// can't be reached or already has an answer.
+    pub fn answer(&self, reply: &str) -> bool {
+        match self {
+            Self::Local(handle) => handle.answer(reply),
+            Self::Remote(client) => client.answer(reply).is_ok(),
+            Self::External { .. } => false,
+        }
+    }
+
+    /// Reject the changes the run holds for review, stopping it.
+    pub fn reject_review(&self) -> bool {
+        match self {
+            Self::Local(handle) => handle.reject_review(),
+            Self::Remote(client) => client.send(RemoteAction::Reject).is_ok(),
+            Self::External { .. } => false,
+        }
+    }
+}
+
+/// Ask the process `pid` to stop, as Ctrl+C would.
+fn terminate(pid: u32) -> bool {
+    #[cfg(unix)]
+    {
+        use nix::sys::signal::{kill, Signal};
+        use nix::unistd::Pid;
+
+        i32::try_from(pid).is_ok_and(|pid| kill(Pid::from_raw(pid), Signal::SIGTERM).is_ok())
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = pid;
+        false
+    }
+}
+
+/// A run another process owns, with its events so far and to come.
+#[derive(Debug)]
+pub struct ExternalRun {
+    /// The run's ID.
+    pub run_id: String,
+    /// How to steer it.
+    pub control: RunControl,
+    /// Its events, from the first.
+    pub events: tokio_mpsc::UnboundedReceiver<RunEvent>,
+}
+
+/// The run another process is going through in `dirs`, if there is one,
+/// with a background thread feeding its events.
+pub fn find_external_run(dirs: &RalfDirs) -> Option<ExternalRun> {
+    let (tx, rx) = tokio_mpsc::unbounded_channel();
+
+    if let Some(client) = RemoteClient::discover(dirs) {
+        let run_id = client
+            .status()
+            .ok()
+            .filter(|status| !status.is_finished())
+            .and_then(|status| status.run_id);
+        if let Some(run_id) = run_id {
+            let stream = client.events().ok()?;
+            std::thread::Builder::new()
+                .name("attach-events".to_string())
+                .spawn(move || {
+                    for record in stream.map_while(Result::ok) {
+                        if tx.send(record.event).is_err() {
+                            break;
+                        }
+                    }
+                })
+                .ok()?;
+            return Some(ExternalRun {
+                run_id,
+                control: RunControl::Remote(client),
+                events: rx,
+            });
+        }
+    }
+
+    let state = RunState::load(&dirs.state())
+        .ok()
+        .filter(RunState::is_running)?;
+    let run_id = state.run_id?;
+    let lock = RunLock::read(dirs.root()).ok().flatten()?;
+    if lock.pid == std::process::id() || lock.status() == LockStatus::Abandoned {
+        return None;
+    }
+    let pid = lock.pid;
+    let mut tail = EventTail::new(events_path(dirs.root(), &run_id));
+    let root = dirs.root().to_path_buf();
+    std::thread::Builder::new()
+        .name("attach-events".to_string())
+        .spawn(move || loop {
+            // A holder that goes away without a final event leaves nothing
+            // more to read
+            let holder_gone = !RunLock::read(&root)
+                .ok()
+                .flatten()
+                .is_some_and(|lock| lock.pid == pid && lock.status() != LockStatus::Abandoned);
+            for line in tail.read_lines() {
+                let Some(record) = parse_event_line(&line) else {
+                    continue;
+                };
+                let finished = matches!(
+                    record.event,
+                    RunEvent::Completed { .. }
+                        | RunEvent::Failed { .. }
+                        | RunEvent::Stuck { .. }
+                        | RunEvent::Cancelled { .. }
+                );
+                if tx.send(record.event).is_err() || finished {
+                    return;
+                }
+            }
+            if holder_gone {
+                return;
+            }
+            std::thread::sleep(TAIL_POLL);
+        })
+        .ok()?;
+    Some(ExternalRun {
+        run_id,
+        control: RunControl::External { pid },
+        events: rx,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ralf_engine::{EventLog, LockInfo};
+
+    #[cfg(unix)]
+    #[test]
+    fn test_finds_unserved_run() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let dirs = RalfDirs::new(temp.path());
+        assert!(find_external_run(&dirs).is_none());
+
+        let mut state = RunState::default();
+        state.start_run_with_id("run-1");
+        state.save(&dirs.state()).unwrap();
+        // This process's own run isn't external
+        let lock = RunLock::acquire(dirs.root(), Some("run-1"), false).unwrap();
+        assert!(find_external_run(&dirs).is_none());
+        lock.release();
+
+        // Another live process (the test's parent) holds the lock
+        let info = LockInfo {
+            pid: std::os::unix::process::parent_id(),
+            ..lock.info().clone()
+        };
+        std::fs::write(
+            RunLock::path(dirs.root()),
+            serde_json::to_string(&info).unwrap(),
+        )
+        .unwrap();
+        std::fs::create_dir_all(dirs.run("run-1")).unwrap();
+        let mut log = EventLog::open(&dirs.run("run-1")).unwrap();
+        log.append(&RunEvent::IterationStarted {
+            iteration: 1,
+            model: "claude".to_string(),
+        })
+        .unwrap();
+        log.append(&RunEvent::Cancelled { iteration: 1 }).unwrap();
+
+        let mut run = find_external_run(&dirs).unwrap();
+        assert_eq!(run.run_id, "run-1");
+        assert!(!run.control.can_steer());
+        assert!(matches!(
+            run.events.blocking_recv(),
+            Some(RunEvent::IterationStarted { iteration: 1, .. })
+        ));
+        assert!(matches!(
+            run.events.blocking_recv(),
+            Some(RunEvent::Cancelled { .. })
+        ));
+        // The feed ends with the run
+        assert!(run.events.blocking_recv().is_none());
+    }
+}
@@ -54,6 +54,20 @@ pub fn get_completions(partial: &str, phase: Option<PhaseKind>) -> Vec<&'static
                     (Some(PhaseKind::PendingReview), "approve" | "reject")
                         | (Some(PhaseKind::Running), "pause" | "cancel")
                         | (Some(PhaseKind::Paused), "resume" | "cancel")
+                        | (Some(PhaseKind::Running | PhaseKind::Polishing), "answer")
+                        | (
+                            Some(PhaseKind::Running | PhaseKind::Paused | PhaseKind::Stuck),
+                            "restore"
+                        )
+                        | (
+                            Some(PhaseKind::Approved | PhaseKind::ReadyToCommit),
+                            "commit"
+                        )
+                        | (
+                            Some(PhaseKind::Implemented | PhaseKind::Polishing),
+                            "polish"
+                        )
+                        | (Some(PhaseKind::Done), "pr" | "cleanup")
                         | (Some(PhaseKind::Drafting), "finalize" | "assess")
                 )
             } else {
@@ -142,5 +156,14 @@ mod tests {
 
         let completions = get_completions("/app", Some(PhaseKind::Drafting));
         assert!(!completions.iter().any(|c| c.name == "approve"));
+
+        let completions = get_completions("/com", Some(PhaseKind::ReadyToCommit));
+        assert!(completions.iter().any(|c| c.name == "commit"));
+        let completions = get_completions("/cle", Some(PhaseKind::Running));
+        assert!(!completions.iter().any(|c| c.name == "cleanup"));
+        let completions = get_completions("/pol", Some(PhaseKind::Implemented));
+        assert!(completions.iter().any(|c| c.name == "polish"));
+        let completions = get_completions("/pol", Some(PhaseKind::PendingReview));
+        assert!(!completions.iter().any(|c| c.name == "polish"));
     }
 }
@@ -22,26 +22,78 @@ pub enum Command {
     Search(Option<String>),
     /// Switch active model
     Model(Option<String>),
+    /// Switch color theme, or list themes
+    Theme(Option<String>),
+    /// Toggle following new timeline events, or set it with "on"/"off"
+    Follow(Option<String>),
+    /// Collapse every timeline event (`/collapse all`)
+    Collapse(Option<String>),
+    /// Expand every timeline event (`/expand all`)
+    Expand(Option<String>),
     /// Copy last response to clipboard
     Copy,
     /// Open in $EDITOR
     Editor,
+    /// Show thread picker with optional filter (phase name, "all", or "archived")
+    Threads(Option<String>),
+    /// Show a run's logs (the latest run by default)
+    Logs(Option<String>),
+    /// Export a report of the current run (md or html)
+    Export(Option<String>),
+    /// Run an interactive command (e.g., `git commit`) with the TUI suspended
+    Exec(Option<String>),
+    /// Show model cooldowns, or clear one with `clear <model>`
+    Cooldown(Option<String>),
+    /// Show or set the directory the thread's runs work in
+    Target(Option<String>),
+    /// Show or set the word the thread's runs promise when done
+    Promise(Option<String>),
+    /// Attach to a run another process started
+    Watch,
 
     // Phase-specific commands (stubs for now)
     /// Approve pending changes (`PendingReview` phase)
     Approve,
     /// Reject pending changes with optional feedback (`PendingReview` phase)
     Reject(Option<String>),
+    /// Start the implementation loop (after preflight)
+    Run,
+    /// Answer the question the running model asked (Running phase)
+    Answer(Option<String>),
+    /// Run the loop again for docs, tests and cleanup only (Implemented phase)
+    Polish,
     /// Pause running operation (Running phase)
     Pause,
     /// Resume paused operation (Paused phase)
     Resume,
     /// Cancel current operation (Running/Paused phases)
     Cancel,
+    /// Put the workspace back as it was before the thread's first run
+    Restore,
+    /// Commit the thread's changes, with an optional message (Approved and `ReadyToCommit` phases)
+    Commit(Option<String>),
+    /// Push the thread's branch and open a pull request (Done phase)
+    Pr,
+    /// Leave a done thread's branch and delete it once merged (Done phase)
+    Cleanup,
     /// Finalize the spec (Drafting phase)
     Finalize,
+    /// Check git, spec, and model auth/quota before a run (Finalized phase)
+    Preflight,
     /// Request AI assessment (Drafting phase)
     Assess,
+    /// Scaffold the draft from a template, or list templates (Drafting phase)
+    Template(Option<String>),
+    /// Browse saved draft snapshots (Drafting phase)
+    History,
+    /// Send messages to several models and pick a reply (Drafting phase)
+    Compare(Option<String>),
+    /// Edit an earlier message and regenerate from it (Drafting phase)
+    Edit(Option<String>),
+    /// List conversation branches, or switch to one (Drafting phase)
+    Branch(Option<String>),
+    /// Attach a file to the chat, or list attachments (Drafting phase)
+    Attach(Option<String>),
 
     /// Unknown command
     Unknown(String),
@@ -135,6 +187,34 @@ pub static COMMANDS: &[CommandInfo] = &[
         keybinding: None,
         phase_specific: false,
     },
+    CommandInfo {
+        name: "theme",
+        aliases: &[],
+        description: "Switch color theme",
+        keybinding: None,
+        phase_specific: false,
+    },
+    CommandInfo {
+        name: "follow",
+        aliases: &[],
+        description: "Follow new timeline events",
+        keybinding: Some("End"),
+        phase_specific: false,
+    },
+    CommandInfo {
+        name: "collapse",
+        aliases: &[],
+        description: "Collapse all timeline events",
+        keybinding: Some("Z"),
+        phase_specific: false,
+    },
+    CommandInfo {
+        name: "expand",
+        aliases: &[],
+        description: "Expand all timeline events",
+        keybinding: Some("Z"),
+        phase_specific: false,
+    },
     CommandInfo {
         name: "copy",
         aliases: &[],
@@ -149,6 +229,62 @@ pub static COMMANDS: &[CommandInfo] = &[
         keybinding: None,
         phase_specific: false,
     },
+    CommandInfo {
+        name: "threads",
+        aliases: &["t"],
+        description: "Switch threads",
+        keybinding: None,
+        phase_specific: false,
+    },
+    CommandInfo {
+        name: "logs",
+        aliases: &[],
+        description: "View run logs",
+        keybinding: None,
+        phase_specific: false,
+    },
+    CommandInfo {
+        name: "export",
+        aliases: &[],
+        description: "Export run report (md/html)",
+        keybinding: None,
+        phase_specific: false,
+    },
+    CommandInfo {
+        name: "exec",
+        aliases: &[],
+        description: "Run an interactive command",
+        keybinding: None,
+        phase_specific: false,
+    },
+    CommandInfo {
+        name: "cooldown",
+        aliases: &[],
+        description: "Show cooldowns, or clear one",
+        keybinding: None,
+        phase_specific: false,
+    },
+    CommandInfo {
+        name: "target",
+        aliases: &[],
+        description: "Show or set the directory runs work in",
+        keybinding: None,
+        phase_specific: false,
+    },
+    CommandInfo {
+        name: "promise",
+        aliases: &[],
+        description: "Show or set the completion promise",
+        keybinding: None,
+        phase_specific: false,
+    },
+    CommandInfo {
+        name: "watch",
+        aliases: &[],
+        description: "Attach to a run another process started",
+        keybinding: None,
+        phase_specific: false,
+    },
     // Phase-specific commands
     CommandInfo {
         name: "approve",
@@ -164,6 +300,27 @@ pub static COMMANDS: &[CommandInfo] = &[
         keybinding: None,
         phase_specific: true,
     },
+    CommandInfo {
+        name: "run",
+        aliases: &[],
+        description: "Start the implementation loop",
+        keybinding: None,
+        phase_specific: true,
+    },
+    CommandInfo {
+        name: "answer",
+        aliases: &[],
+        description: "Answer the model's question",
+        keybinding: None,
+        phase_specific: true,
+    },
+    CommandInfo {
+        name: "polish",
+        aliases: &[],
+        description: "Polish docs, tests and cleanup",
+        keybinding: None,
+        phase_specific: true,
+    },
     CommandInfo {
         name: "pause",
         aliases: &[],
@@ -185,6 +342,34 @@ pub static COMMANDS: &[CommandInfo] = &[
         keybinding: None,
         phase_specific: true,
     },
+    CommandInfo {
+        name: "restore",
+        aliases: &[],
+        description: "Restore the workspace from before the run",
+        keybinding: None,
+        phase_specific: true,
+    },
+    CommandInfo {
+        name: "commit",
+        aliases: &[],
+        description: "Commit the thread's changes",
+        keybinding: None,
+        phase_specific: true,
+    },
+    CommandInfo {
+        name: "pr",
+        aliases: &[],
+        description: "Push the thread's branch and open a pull request",
+        keybinding: None,
+        phase_specific: true,
+    },
+    CommandInfo {
+        name: "cleanup",
+        aliases: &[],
+        description: "Switch back and delete the thread's branch",
+        keybinding: None,
+        phase_specific: true,
+    },
     CommandInfo {
         name: "finalize",
         aliases: &[],
@@ -192,6 +377,13 @@ pub static COMMANDS: &[CommandInfo] = &[
         keybinding: None,
         phase_specific: true,
     },
+    CommandInfo {
+        name: "preflight",
+        aliases: &[],
+        description: "Check models, auth, and quota before a run",
+        keybinding: None,
+        phase_specific: true,
+    },
     CommandInfo {
         name: "assess",
         aliases: &[],
@@ -199,6 +391,48 @@ pub static COMMANDS: &[CommandInfo] = &[
         keybinding: None,
         phase_specific: true,
     },
+    CommandInfo {
+        name: "template",
+        aliases: &[],
+        description: "Start the draft from a template",
+        keybinding: None,
+        phase_specific: true,
+    },
+    CommandInfo {
+        name: "history",
+        aliases: &[],
+        description: "Browse draft history",
+        keybinding: None,
+        phase_specific: true,
+    },
+    CommandInfo {
+        name: "compare",
+        aliases: &[],
+        description: "Compare replies from 2-3 models",
+        keybinding: None,
+        phase_specific: true,
+    },
+    CommandInfo {
+        name: "edit",
+        aliases: &[],
+        description: "Edit a message and regenerate",
+        keybinding: None,
+        phase_specific: true,
+    },
+    CommandInfo {
+        name: "branch",
+        aliases: &[],
+        description: "List or switch conversation branches",
+        keybinding: None,
+        phase_specific: true,
+    },
+    CommandInfo {
+        name: "attach",
+        aliases: &[],
+        description: "Attach a file to the chat",
+        keybinding: None,
+        phase_specific: true,
+    },
 ];
 
 /// Parse a slash command from user input.
@@ -253,17 +487,43 @@ pub fn parse_command(input: &str) -> Option<Command> {
         "clear" => Command::Clear,
         "search" | "find" => Command::Search(args),
         "model" => Command::Model(args),
+        "theme" => Command::Theme(args),
+        "follow" => Command::Follow(args),
+        "collapse" => Command::Collapse(args),
+        "expand" => Command::Expand(args),
         "copy" => Command::Copy,
         "editor" => Command::Editor,
+        "threads" | "t" => Command::Threads(args),
+        "logs" => Command::Logs(args),
+        "export" => Command::Export(args),
+        "exec" => Command::Exec(args),
+        "cooldown" => Command::Cooldown(args),
+        "target" => Command::Target(args),
+        "promise" => Command::Promise(args),
+        "watch" => Command::Watch,
 
         // Phase-specific
         "approve" | "a" => Command::Approve,
         "reject" | "r" => Command::Reject(args),
+        "run" => Command::Run,
+        "answer" => Command::Answer(args),
+        "polish" => Command::Polish,
         "pause" => Command::Pause,
         "resume" => Command::Resume,
         "cancel" => Command::Cancel,
+        "restore" => Command::Restore,
+        "commit" => Command::Commit(args),
+        "pr" => Command::Pr,
+        "cleanup" => Command::Cleanup,
         "finalize" => Command::Finalize,
+        "preflight" => Command::Preflight,
         "assess" => Command::Assess,
+        "template" => Command::Template(args),
+        "history" => Command::History,
+        "compare" => Command::Compare(args),
+        "edit" => Command::Edit(args),
+        "branch" => Command::Branch(args),
+        "attach" => Command::Attach(args),
 
         // Unknown
         other => Command::Unknown(other.to_string()),
@@ -305,6 +565,54 @@ mod tests {
         assert!(matches!(parse_command("/clear"), Some(Command::Clear)));
         assert!(matches!(parse_command("/copy"), Some(Command::Copy)));
         assert!(matches!(parse_command("/editor"), Some(Command::Editor)));
+        assert!(matches!(
+            parse_command("/threads"),
+            Some(Command::Threads(None))
+        ));
+        assert!(matches!(parse_command("/t"), Some(Command::Threads(None))));
+        assert!(matches!(parse_command("/logs"), Some(Command::Logs(None))));
+        assert!(matches!(
+            parse_command("/export"),
+            Some(Command::Export(None))
+        ));
+        assert!(matches!(
+            parse_command("/exec git commit"),
+            Some(Command::Exec(Some(line))) if line == "git commit"
+        ));
+        assert!(matches!(
+            parse_command("/cooldown clear claude"),
+            Some(Command::Cooldown(Some(args))) if args == "clear claude"
+        ));
+        assert!(matches!(
+            parse_command("/target services/api"),
+            Some(Command::Target(Some(dir))) if dir == "services/api"
+        ));
+        assert!(matches!(
+            parse_command("/promise SHIPPED"),
+            Some(Command::Promise(Some(word))) if word == "SHIPPED"
+        ));
+        assert!(matches!(
+            parse_command("/answer Use SQLite"),
+            Some(Command::Answer(Some(reply))) if reply == "Use SQLite"
+        ));
+        assert!(matches!(
+            parse_command("/template feature"),
+            Some(Command::Template(Some(name))) if name == "feature"
+        ));
+        assert!(matches!(parse_command("/history"), Some(Command::History)));
+        assert!(matches!(
+            parse_command("/compare claude codex"),
+            Some(Command::Compare(Some(args))) if args == "claude codex"
+        ));
+        assert!(matches!(parse_command("/edit"), Some(Command::Edit(None))));
+        assert!(matches!(
+            parse_command("/branch 2"),
+            Some(Command::Branch(Some(n))) if n == "2"
+        ));
+        assert!(matches!(
+            parse_command("/attach logs/error.log"),
+            Some(Command::Attach(Some(path))) if path == "logs/error.log"
+        ));
     }
 
     #[test]
@@ -319,6 +627,32 @@ mod tests {
             other => panic!("Expected Model with args, got {:?}", other),
         }
 
+        match parse_command("/theme high-contrast") {
+            Some(Command::Theme(Some(s))) => assert_eq!(s, "high-contrast"),
+            other => panic!("Expected Theme with args, got {other:?}"),
+        }
+
+        assert_eq!(parse_command("/follow"), Some(Command::Follow(None)));
+        assert_eq!(
+            parse_command("/collapse all"),
+            Some(Command::Collapse(Some("all".into())))
+        );
+        assert_eq!(parse_command("/expand"), Some(Command::Expand(None)));
+        assert_eq!(
+            parse_command("/follow off"),
+            Some(Command::Follow(Some("off".into())))
+        );
+
+        match parse_command("/threads running") {
+            Some(Command::Threads(Some(s))) => assert_eq!(s, "running"),
+            other => panic!("Expected Threads with args, got {other:?}"),
+        }
+
+        match parse_command("/logs 20250101-120000") {
+            Some(Command::Logs(Some(s))) => assert_eq!(s, "20250101-120000"),
+            other => panic!("Expected Logs with args, got {other:?}"),
+        }
+
         match parse_command("/reject This needs more work") {
             Some(Command::Reject(Some(s))) => assert_eq!(s, "This needs more work"),
             other => panic!("Expected Reject with args, got {:?}", other),
@@ -340,10 +674,30 @@ mod tests {
     fn test_parse_phase_specific_commands() {
         assert!(matches!(parse_command("/approve"), Some(Command::Approve)));
         assert!(matches!(parse_command("/a"), Some(Command::Approve)));
+        assert!(matches!(parse_command("/polish"), Some(Command::Polish)));
+        assert!(matches!(parse_command("/run"), Some(Command::Run)));
         assert!(matches!(parse_command("/pause"), Some(Command::Pause)));
         assert!(matches!(parse_command("/resume"), Some(Command::Resume)));
         assert!(matches!(parse_command("/cancel"), Some(Command::Cancel)));
-        assert!(matches!(parse_command("/finalize"), Some(Command::Finalize)));
+        assert!(matches!(parse_command("/restore"), Some(Command::Restore)));
+        assert!(matches!(
+            parse_command("/commit Add login"),
+            Some(Command::Commit(Some(m))) if m == "Add login"
+        ));
+        assert!(matches!(
+            parse_command("/commit"),
+            Some(Command::Commit(None))
+        ));
+        assert!(matches!(parse_command("/pr"), Some(Command::Pr)));
+        assert!(matches!(parse_command("/cleanup"), Some(Command::Cleanup)));
+        assert!(matches!(
+            parse_command("/finalize"),
+            Some(Command::Finalize)
+        ));
+        assert!(matches!(
+            parse_command("/preflight"),
+            Some(Command::Preflight)
+        ));
         assert!(matches!(parse_command("/assess"), Some(Command::Assess)));
     }
 
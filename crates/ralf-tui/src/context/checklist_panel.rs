@@ -0,0 +1,231 @@
+//! Review checklist panel for the context pane.
+//!
+//! While a thread is pending review, lists the checklist generated from its
+//! changes and criteria, grouped by section, so the reviewer can tick off
+//! what they looked at before `/approve` records it.
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Paragraph, Widget, Wrap},
+};
+
+use ralf_engine::ReviewChecklist;
+
+use crate::theme::Theme;
+
+/// A thread's review checklist and the selected item.
+#[derive(Debug, Clone)]
+pub struct ChecklistState {
+    /// The checklist, as saved with the thread.
+    pub checklist: ReviewChecklist,
+    selected: usize,
+}
+
+impl ChecklistState {
+    /// Show `checklist` with its first item selected.
+    pub fn new(checklist: ReviewChecklist) -> Self {
+        Self {
+            checklist,
+            selected: 0,
+        }
+    }
+
+    /// Index of the selected item.
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Select the next item.
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.checklist.items.len() {
+            self.selected += 1;
+        }
+    }
+
+    /// Select the previous item.
+    pub fn select_prev(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Check or uncheck the selected item. Returns false if there is none.
+    pub fn toggle_selected(&mut self) -> bool {
+        self.checklist.toggle(self.selected)
+    }
+}
+
+/// Review checklist widget.
+pub struct ChecklistPanel<'a> {
+    /// Checklist to render.
+    state: &'a ChecklistState,
+    /// Theme for styling.
+    theme: &'a Theme,
+    /// Whether to use ASCII markers.
+    ascii_mode: bool,
+    /// Whether the pane is focused (shows the selection).
+    focused: bool,
+}
+
+impl<'a> ChecklistPanel<'a> {
+    /// Create a new checklist panel.
+    pub fn new(state: &'a ChecklistState, theme: &'a Theme) -> Self {
+        Self {
+            state,
+            theme,
+            ascii_mode: false,
+            focused: false,
+        }
+    }
+
+    /// Set ASCII mode.
+    #[must_use]
+    pub fn ascii_mode(mut self, ascii: bool) -> Self {
+        self.ascii_mode = ascii;
+        self
+    }
+
+    /// Set whether the pane is focused.
+    #[must_use]
+    pub fn focused(mut self, focused: bool) -> Self {
+        self.focused = focused;
+        self
+    }
+
+    /// Build styled lines from the checklist.
+    fn build_lines(&self) -> Vec<Line<'static>> {
+        let muted = Style::default().fg(self.theme.muted);
+        let checklist = &self.state.checklist;
+        if checklist.items.is_empty() {
+            return vec![Line::from(Span::styled(
+                "Nothing changed since the thread's baseline".to_string(),
+                muted,
+            ))];
+        }
+
+        let done = checklist.checked_count() == checklist.items.len();
+        let mut lines = vec![Line::from(Span::styled(
+            checklist.to_string(),
+            Style::default()
+                .fg(if done {
+                    self.theme.success
+                } else {
+                    self.theme.text
+                })
+                .add_modifier(Modifier::BOLD),
+        ))];
+
+        let (checked, unchecked) = if self.ascii_mode {
+            ("[x]", "[ ]")
+        } else {
+            ("☑", "☐")
+        };
+        let mut section = None;
+        for (index, item) in checklist.items.iter().enumerate() {
+            if section != Some(item.section) {
+                section = Some(item.section);
+                lines.push(Line::from(""));
+                lines.push(Line::from(Span::styled(
+                    item.section.label().to_string(),
+                    Style::default()
+                        .fg(self.theme.info)
+                        .add_modifier(Modifier::BOLD),
+                )));
+            }
+            let (marker, color) = if item.checked {
+                (checked, self.theme.success)
+            } else {
+                (unchecked, self.theme.muted)
+            };
+            let mut text_style = Style::default().fg(self.theme.text);
+            if self.focused && index == self.state.selected {
+                text_style = text_style.add_modifier(Modifier::REVERSED);
+            }
+            lines.push(Line::from(vec![
+                Span::styled(format!("{marker} "), Style::default().fg(color)),
+                Span::styled(item.text.clone(), text_style),
+            ]));
+        }
+
+        lines.push(Line::from(""));
+        let hint = if checklist.approved_at.is_some() {
+            "Approved with the items checked above."
+        } else {
+            "Space checks an item; /approve records what was checked."
+        };
+        lines.push(Line::from(Span::styled(hint.to_string(), muted)));
+        lines
+    }
+}
+
+impl Widget for ChecklistPanel<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Paragraph::new(self.build_lines())
+            .wrap(Wrap { trim: false })
+            .render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ralf_engine::FileChange;
+
+    fn text(lines: &[Line<'_>]) -> String {
+        lines
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn checklist() -> ReviewChecklist {
+        let changes = vec![FileChange {
+            path: "src/auth.rs".into(),
+            added: 10,
+            removed: 2,
+        }];
+        let criteria = ralf_engine::parse_criteria("## Criteria\n- Logins are checked\n");
+        ReviewChecklist::generate(&changes, &criteria, &[])
+    }
+
+    #[test]
+    fn test_lists_sections() {
+        let theme = Theme::default();
+        let state = ChecklistState::new(checklist());
+        let rendered = text(&ChecklistPanel::new(&state, &theme).build_lines());
+
+        assert!(rendered.starts_with("0 of 3 review items checked"));
+        assert!(rendered.contains("Files to inspect\n☐ src/auth.rs (+10 -2)"));
+        assert!(rendered.contains("Untested paths\n☐ src/auth.rs: no test changes"));
+        assert!(rendered.contains("Criteria\n☐ Logins are checked"));
+        assert!(!rendered.contains("Risky changes"));
+    }
+
+    #[test]
+    fn test_toggle_selected() {
+        let theme = Theme::default();
+        let mut state = ChecklistState::new(checklist());
+        state.select_next();
+        state.select_next();
+        state.select_next();
+        assert_eq!(state.selected(), 2);
+        assert!(state.toggle_selected());
+        state.select_prev();
+
+        let panel = ChecklistPanel::new(&state, &theme).ascii_mode(true);
+        let rendered = text(&panel.build_lines());
+        assert!(rendered.starts_with("1 of 3 review items checked"));
+        assert!(rendered.contains("[x] Logins are checked"));
+        assert!(rendered.contains("[ ] src/auth.rs: no test changes"));
+    }
+
+    #[test]
+    fn test_empty() {
+        let theme = Theme::default();
+        let state = ChecklistState::new(ReviewChecklist::generate(&[], &[], &[]));
+        let rendered = text(&ChecklistPanel::new(&state, &theme).build_lines());
+        assert_eq!(rendered, "Nothing changed since the thread's baseline");
+    }
+}
@@ -0,0 +1,209 @@
+//! Reply comparison state for the context pane.
+//!
+//! In compare mode (`/compare <model> <model> [model]`), Spec Studio sends
+//! each message to every chosen model at once. The view shows their replies
+//! side by side; the one the user picks joins the thread and updates the
+//! draft as if it were the only reply.
+
+use crate::text::wrap_text;
+use crate::ui::widgets::ScrollState;
+
+/// Fewest models worth comparing.
+pub const MIN_COMPARED: usize = 2;
+
+/// Most models compared at once (one column each).
+pub const MAX_COMPARED: usize = 3;
+
+/// A model's reply, as far as it has come.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reply {
+    /// Still waiting for the model.
+    Pending,
+    /// The model's full reply.
+    Done(String),
+    /// The model failed, with the error.
+    Failed(String),
+}
+
+/// One column of the comparison.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComparedReply {
+    /// Model asked.
+    pub model: String,
+    /// Its reply.
+    pub reply: Reply,
+}
+
+impl ComparedReply {
+    /// The reply (or its status) wrapped to `width` columns.
+    pub fn lines(&self, width: usize) -> Vec<String> {
+        match &self.reply {
+            Reply::Pending => vec!["Waiting for reply...".to_string()],
+            Reply::Done(content) => wrap_text(content, width),
+            Reply::Failed(error) => wrap_text(&format!("Failed: {error}"), width),
+        }
+    }
+}
+
+/// State of the comparison view.
+#[derive(Debug, Clone, Default)]
+pub struct ComparisonState {
+    /// The message sent to every model.
+    pub message: String,
+    /// Replies in the order the models were listed.
+    pub replies: Vec<ComparedReply>,
+    /// Index of the selected reply.
+    pub selected: usize,
+    /// First visible line of each reply.
+    pub scroll: usize,
+}
+
+impl ComparisonState {
+    /// Start comparing `models`' replies to `message`.
+    pub fn new(message: impl Into<String>, models: &[String]) -> Self {
+        Self {
+            message: message.into(),
+            replies: models
+                .iter()
+                .map(|model| ComparedReply {
+                    model: model.clone(),
+                    reply: Reply::Pending,
+                })
+                .collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Record the reply of the model at `index`.
+    pub fn record(&mut self, index: usize, result: Result<String, String>) {
+        if let Some(compared) = self.replies.get_mut(index) {
+            compared.reply = match result {
+                Ok(content) => Reply::Done(content),
+                Err(error) => Reply::Failed(error),
+            };
+        }
+    }
+
+    /// Whether every model has replied or failed.
+    pub fn is_complete(&self) -> bool {
+        self.replies.iter().all(|r| r.reply != Reply::Pending)
+    }
+
+    /// Select the reply at `index`, if there is one.
+    pub fn select(&mut self, index: usize) -> bool {
+        if index < self.replies.len() {
+            self.selected = index;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Select the next reply, wrapping around.
+    pub fn select_next(&mut self) {
+        if !self.replies.is_empty() {
+            self.selected = (self.selected + 1) % self.replies.len();
+        }
+    }
+
+    /// Select the previous reply, wrapping around.
+    pub fn select_prev(&mut self) {
+        if !self.replies.is_empty() {
+            self.selected = (self.selected + self.replies.len() - 1) % self.replies.len();
+        }
+    }
+
+    /// The selected reply, as `(model, content)`, if it is ready to pick.
+    ///
+    /// # Errors
+    ///
+    /// Returns a message for the user if the model hasn't replied or failed.
+    pub fn chosen(&self) -> Result<(&str, &str), String> {
+        let Some(compared) = self.replies.get(self.selected) else {
+            return Err("No reply selected".to_string());
+        };
+        match &compared.reply {
+            Reply::Done(content) => Ok((&compared.model, content)),
+            Reply::Pending => Err(format!("{} hasn't replied yet", compared.model)),
+            Reply::Failed(_) => Err(format!("{} failed; pick another reply", compared.model)),
+        }
+    }
+
+    /// Columns each reply wraps to in a pane `width` columns wide, leaving
+    /// a gap between replies.
+    pub fn column_width(&self, width: u16) -> usize {
+        let columns = self.replies.len().max(1);
+        (usize::from(width) / columns).saturating_sub(1).max(1)
+    }
+
+    /// Scroll every reply by `delta` lines, up to the end of the longest
+    /// when wrapped to `width` columns.
+    pub fn scroll_by(&mut self, delta: isize, viewport: usize, width: usize) {
+        let total = self
+            .replies
+            .iter()
+            .map(|r| r.lines(width).len())
+            .max()
+            .unwrap_or(0);
+        let scroll = ScrollState {
+            total,
+            viewport,
+            offset: self.scroll,
+        };
+        self.scroll = if delta < 0 {
+            scroll.scroll_up(delta.unsigned_abs())
+        } else {
+            scroll.scroll_down(delta.unsigned_abs())
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_choose() {
+        let models = vec!["claude".to_string(), "codex".to_string()];
+        let mut state = ComparisonState::new("Draft a spec", &models);
+        assert!(!state.is_complete());
+        assert_eq!(state.chosen().unwrap_err(), "claude hasn't replied yet");
+
+        state.record(1, Ok("## Goal\nShip it".into()));
+        state.record(0, Err("rate limited".into()));
+        assert!(state.is_complete());
+        assert_eq!(
+            state.chosen().unwrap_err(),
+            "claude failed; pick another reply"
+        );
+
+        state.select_next();
+        assert_eq!(state.chosen().unwrap(), ("codex", "## Goal\nShip it"));
+        state.select_next();
+        assert_eq!(state.selected, 0);
+        state.select_prev();
+        assert_eq!(state.selected, 1);
+
+        assert!(state.select(0));
+        assert!(!state.select(2));
+        assert_eq!(state.selected, 0);
+    }
+
+    #[test]
+    fn test_scroll_follows_longest_reply() {
+        let models = vec!["claude".to_string(), "codex".to_string()];
+        let mut state = ComparisonState::new("Draft a spec", &models);
+        state.record(0, Ok(vec!["line"; 30].join("\n")));
+        state.record(1, Ok("short".into()));
+
+        state.scroll_by(100, 10, 40);
+        assert_eq!(state.scroll, 20);
+        state.scroll_by(-5, 10, 40);
+        assert_eq!(state.scroll, 15);
+
+        // Wrapped lines count too
+        state.record(1, Ok("word ".repeat(200)));
+        state.scroll_by(100, 10, 20);
+        assert!(state.scroll > 20);
+    }
+}
@@ -0,0 +1,406 @@
+//! Criteria progress panel for the context pane.
+//!
+//! Lists the spec's completion criteria with their verification state as
+//! the run reports it, so a long verification shows how far it has got
+//! and why criteria failed without digging through the timeline.
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Paragraph, Widget, Wrap},
+};
+
+use ralf_engine::runner::RunEvent;
+use ralf_engine::Criterion;
+
+use crate::theme::Theme;
+
+/// Verification state of one criterion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CriterionState {
+    /// Not verified yet this iteration.
+    Pending,
+    /// Being verified now.
+    Verifying,
+    /// Verified as met.
+    Passed,
+    /// Verified as not met.
+    Failed,
+}
+
+/// One criterion and its latest verdict.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CriterionProgress {
+    /// Criterion text from the spec.
+    pub text: String,
+    /// Whether the criterion must pass for the run to complete.
+    pub required: bool,
+    /// Verification state.
+    pub state: CriterionState,
+    /// Why the verifier failed it.
+    pub reason: Option<String>,
+    /// Whether the verdict came from the verification cache.
+    pub cached: bool,
+    /// Whether the failure reason is shown.
+    pub expanded: bool,
+}
+
+impl CriterionProgress {
+    fn new(text: String, required: bool) -> Self {
+        Self {
+            text,
+            required,
+            state: CriterionState::Pending,
+            reason: None,
+            cached: false,
+            expanded: false,
+        }
+    }
+}
+
+/// Live criteria progress for the attached run.
+#[derive(Debug, Clone, Default)]
+pub struct CriteriaState {
+    criteria: Vec<CriterionProgress>,
+    selected: usize,
+}
+
+impl CriteriaState {
+    /// Track `criteria` (as parsed from the spec), all pending.
+    pub fn new(criteria: Vec<Criterion>) -> Self {
+        Self {
+            criteria: criteria
+                .into_iter()
+                .map(|c| CriterionProgress::new(c.text, c.required))
+                .collect(),
+            selected: 0,
+        }
+    }
+
+    /// The criteria and their states.
+    pub fn criteria(&self) -> &[CriterionProgress] {
+        &self.criteria
+    }
+
+    /// Index of the selected criterion.
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Number of criteria verified as met.
+    pub fn passed(&self) -> usize {
+        self.criteria
+            .iter()
+            .filter(|c| c.state == CriterionState::Passed)
+            .count()
+    }
+
+    /// Whether every required criterion is verified as met.
+    pub fn required_passed(&self) -> bool {
+        self.criteria
+            .iter()
+            .all(|c| !c.required || c.state == CriterionState::Passed)
+    }
+
+    /// Progress summary, e.g. "3/7 passed".
+    pub fn summary(&self) -> String {
+        format!("{}/{} passed", self.passed(), self.criteria.len())
+    }
+
+    /// Update states from a run event.
+    pub fn apply(&mut self, event: &RunEvent) {
+        match event {
+            // Each iteration is verified afresh
+            RunEvent::IterationStarted { .. } => self.reset(),
+            // Cached verdicts arrive first; the model verifies the rest
+            RunEvent::VerificationStarted { .. } => {
+                for criterion in &mut self.criteria {
+                    if criterion.state == CriterionState::Pending {
+                        criterion.state = CriterionState::Verifying;
+                    }
+                }
+            }
+            RunEvent::CriterionVerified {
+                index,
+                required,
+                passed,
+                reason,
+                cached,
+                ..
+            } => {
+                // The spec may have gained criteria since it was parsed
+                while self.criteria.len() <= *index {
+                    let text = format!("Criterion {}", self.criteria.len() + 1);
+                    self.criteria.push(CriterionProgress::new(text, true));
+                }
+                if let Some(criterion) = self.criteria.get_mut(*index) {
+                    criterion.required = *required;
+                    criterion.state = if *passed {
+                        CriterionState::Passed
+                    } else {
+                        CriterionState::Failed
+                    };
+                    criterion.reason = reason.clone().filter(|_| !*passed);
+                    criterion.cached = *cached;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn reset(&mut self) {
+        for criterion in &mut self.criteria {
+            criterion.state = CriterionState::Pending;
+            criterion.reason = None;
+            criterion.cached = false;
+        }
+    }
+
+    /// Select the next criterion.
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.criteria.len() {
+            self.selected += 1;
+        }
+    }
+
+    /// Select the previous criterion.
+    pub fn select_prev(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Show or hide the selected criterion's failure reason.
+    pub fn toggle_selected(&mut self) {
+        if let Some(criterion) = self.criteria.get_mut(self.selected) {
+            criterion.expanded = !criterion.expanded;
+        }
+    }
+}
+
+/// Criteria progress widget.
+pub struct CriteriaPanel<'a> {
+    /// Criteria to render.
+    state: &'a CriteriaState,
+    /// Theme for styling.
+    theme: &'a Theme,
+    /// Whether to use ASCII markers.
+    ascii_mode: bool,
+    /// Whether the pane is focused (shows the selection).
+    focused: bool,
+}
+
+impl<'a> CriteriaPanel<'a> {
+    /// Create a new criteria panel.
+    pub fn new(state: &'a CriteriaState, theme: &'a Theme) -> Self {
+        Self {
+            state,
+            theme,
+            ascii_mode: false,
+            focused: false,
+        }
+    }
+
+    /// Set ASCII mode.
+    #[must_use]
+    pub fn ascii_mode(mut self, ascii: bool) -> Self {
+        self.ascii_mode = ascii;
+        self
+    }
+
+    /// Set whether the pane is focused.
+    #[must_use]
+    pub fn focused(mut self, focused: bool) -> Self {
+        self.focused = focused;
+        self
+    }
+
+    /// Marker and color for a criterion state.
+    fn marker(&self, state: CriterionState) -> (&'static str, ratatui::style::Color) {
+        let (unicode, ascii, color) = match state {
+            CriterionState::Pending => ("○", "[  ]", self.theme.muted),
+            CriterionState::Verifying => ("◐", "[..]", self.theme.info),
+            CriterionState::Passed => ("✓", "[ok]", self.theme.success),
+            CriterionState::Failed => ("✗", "[!!]", self.theme.error),
+        };
+        (if self.ascii_mode { ascii } else { unicode }, color)
+    }
+
+    /// Build styled lines from the criteria.
+    fn build_lines(&self) -> Vec<Line<'static>> {
+        let muted = Style::default().fg(self.theme.muted);
+        if self.state.criteria.is_empty() {
+            return vec![Line::from(Span::styled(
+                "No completion criteria in PROMPT.md".to_string(),
+                muted,
+            ))];
+        }
+
+        let done = self.state.required_passed();
+        let mut lines = vec![
+            Line::from(Span::styled(
+                self.state.summary(),
+                Style::default()
+                    .fg(if done {
+                        self.theme.success
+                    } else {
+                        self.theme.text
+                    })
+                    .add_modifier(Modifier::BOLD),
+            )),
+            Line::from(""),
+        ];
+
+        for (index, criterion) in self.state.criteria.iter().enumerate() {
+            let (marker, color) = self.marker(criterion.state);
+            let selected = self.focused && index == self.state.selected;
+            let mut text_style = Style::default().fg(self.theme.text);
+            if selected {
+                text_style = text_style.add_modifier(Modifier::REVERSED);
+            }
+            let mut spans = vec![
+                Span::styled(format!("{marker} "), Style::default().fg(color)),
+                Span::styled(format!("{}. {}", index + 1, criterion.text), text_style),
+            ];
+            if !criterion.required {
+                spans.push(Span::styled(" (optional)".to_string(), muted));
+            }
+            if criterion.cached {
+                spans.push(Span::styled(" (cached)".to_string(), muted));
+            }
+            if criterion.reason.is_some() && !criterion.expanded {
+                spans.push(Span::styled(" [Enter: why]".to_string(), muted));
+            }
+            lines.push(Line::from(spans));
+
+            if let Some(reason) = criterion.reason.as_ref().filter(|_| criterion.expanded) {
+                lines.extend(reason.lines().map(|line| {
+                    Line::from(Span::styled(
+                        format!("    {line}"),
+                        Style::default().fg(self.theme.error),
+                    ))
+                }));
+            }
+        }
+        lines
+    }
+}
+
+impl Widget for CriteriaPanel<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Paragraph::new(self.build_lines())
+            .wrap(Wrap { trim: false })
+            .render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn verified(index: usize, passed: bool, reason: Option<&str>) -> RunEvent {
+        RunEvent::CriterionVerified {
+            index,
+            id: format!("c{}", index + 1),
+            required: true,
+            passed,
+            reason: reason.map(Into::into),
+            cached: false,
+        }
+    }
+
+    fn criteria(lines: &[&str]) -> Vec<Criterion> {
+        lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| Criterion::parse(i, line))
+            .collect()
+    }
+
+    fn text(lines: &[Line<'_>]) -> String {
+        lines
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_apply_events() {
+        let mut state = CriteriaState::new(criteria(&["Tests pass", "Docs updated"]));
+        state.apply(&RunEvent::CriterionVerified {
+            index: 0,
+            id: "c1".into(),
+            required: true,
+            passed: true,
+            reason: None,
+            cached: true,
+        });
+        state.apply(&RunEvent::VerificationStarted {
+            iteration: 1,
+            model: "claude".into(),
+            implementer: "codex".into(),
+            criteria_count: 1,
+        });
+        let states: Vec<_> = state.criteria().iter().map(|c| c.state).collect();
+        assert_eq!(
+            states,
+            vec![CriterionState::Passed, CriterionState::Verifying]
+        );
+        assert!(state.criteria()[0].cached);
+
+        state.apply(&verified(1, false, Some("README unchanged")));
+        state.apply(&verified(2, true, None));
+        assert_eq!(state.criteria()[1].state, CriterionState::Failed);
+        assert_eq!(state.criteria()[2].text, "Criterion 3");
+        assert_eq!(state.summary(), "2/3 passed");
+
+        state.apply(&RunEvent::IterationStarted {
+            iteration: 2,
+            model: "codex".into(),
+        });
+        assert_eq!(state.summary(), "0/3 passed");
+        assert!(state.criteria()[1].reason.is_none());
+    }
+
+    #[test]
+    fn test_failure_reasons_expand() {
+        let theme = Theme::default();
+        let mut state = CriteriaState::new(criteria(&["Tests pass", "Docs updated"]));
+        state.apply(&verified(0, true, None));
+        state.apply(&verified(1, false, Some("README unchanged")));
+
+        let rendered = text(&CriteriaPanel::new(&state, &theme).build_lines());
+        assert!(rendered.starts_with("1/2 passed"));
+        assert!(rendered.contains("✓ 1. Tests pass"));
+        assert!(rendered.contains("✗ 2. Docs updated [Enter: why]"));
+        assert!(!rendered.contains("README unchanged"));
+
+        state.select_next();
+        state.toggle_selected();
+        let panel = CriteriaPanel::new(&state, &theme).ascii_mode(true);
+        let rendered = text(&panel.build_lines());
+        assert!(rendered.contains("[!!] 2. Docs updated\n    README unchanged"));
+    }
+
+    #[test]
+    fn test_optional_criteria() {
+        let theme = Theme::default();
+        let mut state = CriteriaState::new(criteria(&["Tests pass", "Docs updated (optional)"]));
+        assert!(!state.required_passed());
+        state.apply(&verified(0, true, None));
+        assert!(state.required_passed());
+        assert_eq!(state.summary(), "1/2 passed");
+
+        let rendered = text(&CriteriaPanel::new(&state, &theme).build_lines());
+        assert!(rendered.contains("○ 2. Docs updated (optional)"));
+    }
+
+    #[test]
+    fn test_no_criteria() {
+        let theme = Theme::default();
+        let state = CriteriaState::default();
+        let rendered = text(&CriteriaPanel::new(&state, &theme).build_lines());
+        assert_eq!(rendered, "No completion criteria in PROMPT.md");
+    }
+}
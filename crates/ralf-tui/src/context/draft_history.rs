@@ -0,0 +1,136 @@
+//! Draft history state for the context pane.
+//!
+//! Opened by `/history`, the view lists the saved draft snapshots, newest
+//! first, and shows a diff from the selected snapshot to the current draft:
+//! removed lines are the snapshot's wording, added lines the draft's.
+
+use std::path::Path;
+
+use ralf_engine::{diff_lines, DiffLine, DraftHistory, DraftRevision};
+
+use crate::ui::widgets::ScrollState;
+
+/// State of the draft history view.
+#[derive(Debug, Clone, Default)]
+pub struct DraftHistoryState {
+    /// Saved snapshots, newest first.
+    pub revisions: Vec<DraftRevision>,
+    /// Index of the selected snapshot.
+    pub selected: usize,
+    /// Content of the selected snapshot.
+    pub content: String,
+    /// Diff from the selected snapshot to the current draft.
+    pub diff: Vec<DiffLine>,
+    /// First visible diff line.
+    pub scroll: usize,
+}
+
+impl DraftHistoryState {
+    /// Open the snapshots in `ralf_dir/drafts`, diffed against `draft`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a message for the user if the history can't be read or has
+    /// no snapshots yet.
+    pub fn open(ralf_dir: &Path, draft: &str) -> Result<Self, String> {
+        let revisions = DraftHistory::new(ralf_dir)
+            .list()
+            .map_err(|e| format!("Failed to read draft history: {e}"))?;
+        if revisions.is_empty() {
+            return Err("No draft history yet".to_string());
+        }
+        let mut state = Self {
+            revisions,
+            ..Self::default()
+        };
+        state.load_selected(draft);
+        Ok(state)
+    }
+
+    /// The selected snapshot.
+    pub fn current(&self) -> Option<&DraftRevision> {
+        self.revisions.get(self.selected)
+    }
+
+    /// Select the next (older) snapshot.
+    pub fn select_next(&mut self, draft: &str) {
+        if self.selected + 1 < self.revisions.len() {
+            self.selected += 1;
+            self.load_selected(draft);
+        }
+    }
+
+    /// Select the previous (newer) snapshot.
+    pub fn select_prev(&mut self, draft: &str) {
+        if self.selected > 0 {
+            self.selected -= 1;
+            self.load_selected(draft);
+        }
+    }
+
+    /// Scroll the diff by `delta` lines.
+    pub fn scroll_by(&mut self, delta: isize, viewport: usize) {
+        let scroll = ScrollState {
+            total: self.diff.len(),
+            viewport,
+            offset: self.scroll,
+        };
+        self.scroll = if delta < 0 {
+            scroll.scroll_up(delta.unsigned_abs())
+        } else {
+            scroll.scroll_down(delta.unsigned_abs())
+        };
+    }
+
+    /// Whether the selected snapshot matches the current draft.
+    pub fn unchanged(&self) -> bool {
+        self.diff
+            .iter()
+            .all(|line| matches!(line, DiffLine::Same(_)))
+    }
+
+    fn load_selected(&mut self, draft: &str) {
+        let content = self
+            .current()
+            .and_then(|revision| std::fs::read_to_string(&revision.path).ok())
+            .unwrap_or_default();
+        self.diff = diff_lines(&content, draft);
+        self.content = content;
+        self.scroll = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open_and_select() {
+        let temp = tempfile::TempDir::new().unwrap();
+        assert_eq!(
+            DraftHistoryState::open(temp.path(), "# Draft").unwrap_err(),
+            "No draft history yet"
+        );
+
+        let drafts = temp.path().join("drafts");
+        std::fs::create_dir_all(&drafts).unwrap();
+        std::fs::write(drafts.join("20260101_120000.md"), "# Draft\nold\n").unwrap();
+        std::fs::write(drafts.join("20260102_120000.md"), "# Draft\n").unwrap();
+
+        let mut state = DraftHistoryState::open(temp.path(), "# Draft").unwrap();
+        assert_eq!(state.current().unwrap().name, "20260102_120000");
+        assert!(state.unchanged());
+
+        state.select_next("# Draft");
+        assert_eq!(state.content, "# Draft\nold\n");
+        assert_eq!(state.diff[1], DiffLine::Removed("old".into()));
+        assert!(!state.unchanged());
+
+        // Selection stops at the ends
+        state.select_next("# Draft");
+        assert_eq!(state.selected, 1);
+        state.select_prev("# Draft");
+        state.select_prev("# Draft");
+        assert_eq!(state.selected, 0);
+    }
+}
@@ -0,0 +1,422 @@
+//! Log view state for the context pane.
+//!
+//! Opened by `/logs`, the view shows one invocation log of a run at a time,
+//! starting with the model log of the latest iteration. It keeps a line
+//! cursor for navigation, an optional selection for copying, and an
+//! incremental search.
+
+use std::path::{Path, PathBuf};
+
+use ralf_engine::logs::{latest_run, list_logs, parse_log, read_log, LogFile};
+use ralf_engine::RalfDirs;
+
+use crate::ui::widgets::ScrollState;
+
+/// State of the log view.
+#[derive(Debug, Clone, Default)]
+pub struct LogViewState {
+    /// Run the logs belong to.
+    pub run_id: String,
+    /// The run's logs, by iteration.
+    pub logs: Vec<LogFile>,
+    /// Index of the log being shown.
+    pub selected: usize,
+    /// Lines of the log being shown (stdout, then stderr).
+    pub lines: Vec<String>,
+    /// Line the cursor is on.
+    pub cursor: usize,
+    /// Other end of the selection, when selecting.
+    pub anchor: Option<usize>,
+    /// First visible line.
+    pub scroll: usize,
+    /// Search query (empty when not searching).
+    pub query: String,
+    /// Whether the query is being typed.
+    pub searching: bool,
+    /// Lines matching the query.
+    pub matches: Vec<usize>,
+    /// Cursor when the search started, restored if it is cancelled.
+    search_origin: usize,
+}
+
+impl LogViewState {
+    /// Open the logs of `run_id`, or of the latest run when `None`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a message for the user if there is no such run or it has no
+    /// logs yet.
+    pub fn open(ralf_dir: &Path, run_id: Option<&str>) -> Result<Self, String> {
+        let run_id = match run_id {
+            Some(id) => id.to_string(),
+            None => latest_run(ralf_dir).ok_or("No runs yet")?,
+        };
+        let run_dir: PathBuf = RalfDirs::new(ralf_dir).run(&run_id);
+        if !run_dir.is_dir() {
+            return Err(format!("Run not found: {run_id}"));
+        }
+        let logs = list_logs(&run_dir);
+        if logs.is_empty() {
+            return Err(format!("No logs for run {run_id}"));
+        }
+        Ok(Self::new(run_id, logs))
+    }
+
+    /// Create the view, showing the model log of the latest iteration.
+    pub fn new(run_id: String, logs: Vec<LogFile>) -> Self {
+        let selected = logs
+            .iter()
+            .rposition(|log| !log.verification)
+            .and_then(|last| {
+                let iteration = logs[last].iteration;
+                logs.iter()
+                    .position(|log| log.iteration == iteration && !log.verification)
+            })
+            .unwrap_or(0);
+        let mut state = Self {
+            run_id,
+            logs,
+            ..Self::default()
+        };
+        state.select(selected);
+        state
+    }
+
+    /// The log being shown.
+    pub fn current(&self) -> Option<&LogFile> {
+        self.logs.get(self.selected)
+    }
+
+    /// Show the log at `index`, keeping the search query.
+    pub fn select(&mut self, index: usize) {
+        if index >= self.logs.len() {
+            return;
+        }
+        self.selected = index;
+        self.reload();
+    }
+
+    /// Re-read the log being shown (e.g., while a run is still writing it).
+    pub fn reload(&mut self) {
+        let content = self
+            .current()
+            .and_then(|log| read_log(&log.path).ok())
+            .unwrap_or_default();
+        let sections = parse_log(&content);
+        let mut lines: Vec<String> = sections.stdout.lines().map(String::from).collect();
+        if !sections.stderr.is_empty() {
+            lines.push(String::new());
+            lines.push("--- stderr ---".to_string());
+            lines.extend(sections.stderr.lines().map(String::from));
+        }
+        self.lines = lines;
+        self.cursor = 0;
+        self.anchor = None;
+        self.scroll = 0;
+        self.update_matches();
+    }
+
+    /// Show the next log.
+    pub fn next_log(&mut self) {
+        self.select(self.selected + 1);
+    }
+
+    /// Show the previous log.
+    pub fn prev_log(&mut self) {
+        if self.selected > 0 {
+            self.select(self.selected - 1);
+        }
+    }
+
+    /// Show the first log of the next iteration.
+    pub fn next_iteration(&mut self) {
+        let iteration = self.current().and_then(|log| log.iteration);
+        if let Some(index) = self
+            .logs
+            .iter()
+            .position(|log| log.iteration > iteration && !log.verification)
+            .or_else(|| self.logs.iter().position(|log| log.iteration > iteration))
+        {
+            self.select(index);
+        }
+    }
+
+    /// Show the first log of the previous iteration.
+    pub fn prev_iteration(&mut self) {
+        let iteration = self.current().and_then(|log| log.iteration);
+        let Some(previous) = self
+            .logs
+            .iter()
+            .rev()
+            .find(|log| log.iteration < iteration)
+            .map(|log| log.iteration)
+        else {
+            return;
+        };
+        if let Some(index) = self
+            .logs
+            .iter()
+            .position(|log| log.iteration == previous && !log.verification)
+            .or_else(|| self.logs.iter().position(|log| log.iteration == previous))
+        {
+            self.select(index);
+        }
+    }
+
+    /// Move the cursor by `delta` lines, scrolling to keep it visible.
+    pub fn move_cursor(&mut self, delta: isize, viewport: usize) {
+        let last = self.lines.len().saturating_sub(1);
+        self.cursor = self.cursor.saturating_add_signed(delta).min(last);
+        self.ensure_cursor_visible(viewport);
+    }
+
+    /// Move the cursor to the first line.
+    pub fn cursor_to_top(&mut self) {
+        self.cursor = 0;
+        self.scroll = 0;
+    }
+
+    /// Move the cursor to the last line.
+    pub fn cursor_to_bottom(&mut self, viewport: usize) {
+        self.cursor = self.lines.len().saturating_sub(1);
+        self.ensure_cursor_visible(viewport);
+    }
+
+    /// Scroll so the cursor is within a viewport of `viewport` lines.
+    pub fn ensure_cursor_visible(&mut self, viewport: usize) {
+        let viewport = viewport.max(1);
+        if self.cursor < self.scroll {
+            self.scroll = self.cursor;
+        } else if self.cursor >= self.scroll + viewport {
+            self.scroll = self.cursor + 1 - viewport;
+        }
+        let state = ScrollState {
+            total: self.lines.len(),
+            viewport,
+            offset: self.scroll,
+        };
+        self.scroll = state.scroll_down(0);
+    }
+
+    /// Start or stop selecting lines from the cursor.
+    pub fn toggle_selection(&mut self) {
+        self.anchor = match self.anchor {
+            Some(_) => None,
+            None => Some(self.cursor),
+        };
+    }
+
+    /// First and last selected line (the cursor line when not selecting).
+    pub fn selection(&self) -> (usize, usize) {
+        let anchor = self.anchor.unwrap_or(self.cursor);
+        (anchor.min(self.cursor), anchor.max(self.cursor))
+    }
+
+    /// Text of the selected lines, or `None` if the log is empty.
+    pub fn selected_text(&self) -> Option<String> {
+        let (start, end) = self.selection();
+        let lines = self.lines.get(start..=end)?;
+        Some(lines.join("\n"))
+    }
+
+    /// Start typing a search query.
+    pub fn start_search(&mut self) {
+        self.searching = true;
+        self.query.clear();
+        self.search_origin = self.cursor;
+        self.update_matches();
+    }
+
+    /// Add a character to the query, jumping to the first match.
+    pub fn push_search(&mut self, c: char, viewport: usize) {
+        self.query.push(c);
+        self.update_matches();
+        self.jump_from_origin(viewport);
+    }
+
+    /// Remove the last character of the query.
+    pub fn pop_search(&mut self, viewport: usize) {
+        self.query.pop();
+        self.update_matches();
+        self.jump_from_origin(viewport);
+    }
+
+    /// Stop typing, keeping the query for `n`/`N`.
+    pub fn finish_search(&mut self) {
+        self.searching = false;
+    }
+
+    /// Abandon the search, returning the cursor to where it started.
+    pub fn cancel_search(&mut self, viewport: usize) {
+        if self.searching {
+            self.cursor = self.search_origin;
+            self.ensure_cursor_visible(viewport);
+        }
+        self.searching = false;
+        self.query.clear();
+        self.matches.clear();
+    }
+
+    /// Move the cursor to the next match, wrapping around.
+    pub fn next_match(&mut self, viewport: usize) {
+        let next = self
+            .matches
+            .iter()
+            .find(|&&line| line > self.cursor)
+            .or_else(|| self.matches.first());
+        if let Some(&line) = next {
+            self.cursor = line;
+            self.ensure_cursor_visible(viewport);
+        }
+    }
+
+    /// Move the cursor to the previous match, wrapping around.
+    pub fn prev_match(&mut self, viewport: usize) {
+        let prev = self
+            .matches
+            .iter()
+            .rev()
+            .find(|&&line| line < self.cursor)
+            .or_else(|| self.matches.last());
+        if let Some(&line) = prev {
+            self.cursor = line;
+            self.ensure_cursor_visible(viewport);
+        }
+    }
+
+    /// Position of the cursor's match among all matches (1-based).
+    pub fn match_position(&self) -> Option<usize> {
+        self.matches
+            .iter()
+            .position(|&line| line == self.cursor)
+            .map(|i| i + 1)
+    }
+
+    fn jump_from_origin(&mut self, viewport: usize) {
+        let first = self
+            .matches
+            .iter()
+            .find(|&&line| line >= self.search_origin)
+            .or_else(|| self.matches.first());
+        self.cursor = first.copied().unwrap_or(self.search_origin);
+        self.ensure_cursor_visible(viewport);
+    }
+
+    fn update_matches(&mut self) {
+        if self.query.is_empty() {
+            self.matches.clear();
+            return;
+        }
+        let query = self.query.to_lowercase();
+        self.matches = self
+            .lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| line.to_lowercase().contains(&query))
+            .map(|(i, _)| i)
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ralf_engine::logs::iteration_dir;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn write_run(ralf_dir: &Path) {
+        let run_dir = ralf_dir.join("runs").join("run-1");
+        for iteration in 1..=2 {
+            let dir = iteration_dir(&run_dir, iteration);
+            fs::create_dir_all(dir.join("verify")).unwrap();
+            fs::write(
+                dir.join("claude.log"),
+                format!(
+                    "=== STDOUT ===\niteration {iteration}\nbuilding\nError: test failed\n\n=== STDERR ===\nwarning: unused\n"
+                ),
+            )
+            .unwrap();
+            fs::write(dir.join("verify").join("codex.log"), "=== STDOUT ===\nok\n").unwrap();
+        }
+    }
+
+    #[test]
+    fn test_open_shows_latest_model_log() {
+        let temp = TempDir::new().unwrap();
+        assert!(LogViewState::open(temp.path(), None).is_err());
+
+        write_run(temp.path());
+        let state = LogViewState::open(temp.path(), None).unwrap();
+        let current = state.current().unwrap();
+        assert_eq!(current.iteration, Some(2));
+        assert!(!current.verification);
+        assert_eq!(state.lines[0], "iteration 2");
+        assert_eq!(state.lines[4], "--- stderr ---");
+        assert!(LogViewState::open(temp.path(), Some("missing")).is_err());
+    }
+
+    #[test]
+    fn test_iteration_navigation() {
+        let temp = TempDir::new().unwrap();
+        write_run(temp.path());
+        let mut state = LogViewState::open(temp.path(), Some("run-1")).unwrap();
+
+        state.prev_iteration();
+        assert_eq!(state.current().unwrap().iteration, Some(1));
+        assert!(!state.current().unwrap().verification);
+        state.prev_iteration();
+        assert_eq!(state.current().unwrap().iteration, Some(1));
+
+        state.next_log();
+        assert!(state.current().unwrap().verification);
+        state.next_iteration();
+        assert_eq!(state.current().unwrap().iteration, Some(2));
+        assert_eq!(state.lines[0], "iteration 2");
+    }
+
+    #[test]
+    fn test_incremental_search() {
+        let mut state = LogViewState {
+            lines: ["start", "error one", "middle", "Error two"]
+                .map(String::from)
+                .to_vec(),
+            ..LogViewState::default()
+        };
+        state.cursor = 2;
+        state.start_search();
+        for c in "err".chars() {
+            state.push_search(c, 10);
+        }
+        assert_eq!(state.matches, vec![1, 3]);
+        assert_eq!(state.cursor, 3);
+        assert_eq!(state.match_position(), Some(2));
+
+        state.next_match(10);
+        assert_eq!(state.cursor, 1);
+        state.prev_match(10);
+        assert_eq!(state.cursor, 3);
+
+        state.cancel_search(10);
+        assert_eq!(state.cursor, 2);
+        assert!(state.matches.is_empty());
+    }
+
+    #[test]
+    fn test_selection_and_scrolling() {
+        let mut state = LogViewState {
+            lines: (0..20).map(|i| format!("line {i}")).collect(),
+            ..LogViewState::default()
+        };
+        state.move_cursor(12, 5);
+        assert_eq!(state.scroll, 8);
+        state.toggle_selection();
+        state.move_cursor(-2, 5);
+        assert_eq!(state.selection(), (10, 12));
+        assert_eq!(state.selected_text().unwrap(), "line 10\nline 11\nline 12");
+        state.cursor_to_bottom(5);
+        assert_eq!((state.cursor, state.scroll), (19, 15));
+        state.cursor_to_top();
+        assert_eq!(state.scroll, 0);
+    }
+}
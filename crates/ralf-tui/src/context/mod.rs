@@ -4,9 +4,33 @@
 //! - [`ContextView`] - View variants for the context pane
 //! - [`CompletionKind`] - Done vs Abandoned completion states
 //! - [`SpecPreview`] - Spec preview widget with markdown rendering
+//! - [`RecoveryPanel`] - Stuck diagnosis and recovery suggestions
+//! - [`PreflightPanel`] - Preflight check results
+//! - [`CriteriaPanel`] - Completion criteria progress while verifying
+//! - [`ChecklistPanel`] - Review checklist while pending review
+//! - [`RunDashboard`] - Live view of the attached run
+//! - [`LogViewState`] - Run log view opened by `/logs`
+//! - [`DraftHistoryState`] - Draft snapshot history opened by `/history`
+//! - [`ComparisonState`] - Side-by-side replies in compare mode (`/compare`)
 
+mod checklist_panel;
+mod comparison;
+mod criteria_panel;
+mod draft_history;
+mod log_view;
+mod preflight_panel;
+mod recovery_panel;
 mod router;
+mod run_dashboard;
 mod spec_preview;
 
+pub use checklist_panel::{ChecklistPanel, ChecklistState};
+pub use comparison::{ComparedReply, ComparisonState, Reply, MAX_COMPARED, MIN_COMPARED};
+pub use criteria_panel::{CriteriaPanel, CriteriaState, CriterionProgress, CriterionState};
+pub use draft_history::DraftHistoryState;
+pub use log_view::LogViewState;
+pub use preflight_panel::PreflightPanel;
+pub use recovery_panel::RecoveryPanel;
 pub use router::{CompletionKind, ContextView};
+pub use run_dashboard::{RunDashboard, RunDashboardState, RunOutcome, VerifierOutcome};
 pub use spec_preview::{SpecPhase, SpecPreview};
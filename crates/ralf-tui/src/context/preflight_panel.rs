@@ -0,0 +1,165 @@
+//! Preflight panel for the context pane.
+//!
+//! Lists each preflight check with its outcome, so the user can see what
+//! blocks a run (missing auth, exhausted quota, a spec too large for a
+//! model) before an iteration is spent on it.
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Paragraph, Widget, Wrap},
+};
+
+use ralf_engine::PreflightResult;
+
+use crate::theme::Theme;
+
+/// Preflight results widget.
+pub struct PreflightPanel<'a> {
+    /// Results to render (None while checks are running).
+    result: Option<&'a PreflightResult>,
+    /// Theme for styling.
+    theme: &'a Theme,
+    /// Whether to use ASCII markers.
+    ascii_mode: bool,
+}
+
+impl<'a> PreflightPanel<'a> {
+    /// Create a new preflight panel.
+    pub fn new(result: Option<&'a PreflightResult>, theme: &'a Theme) -> Self {
+        Self {
+            result,
+            theme,
+            ascii_mode: false,
+        }
+    }
+
+    /// Set ASCII mode.
+    #[must_use]
+    pub fn ascii_mode(mut self, ascii: bool) -> Self {
+        self.ascii_mode = ascii;
+        self
+    }
+
+    /// Build styled lines from the results.
+    fn build_lines(&self) -> Vec<Line<'static>> {
+        let muted = Style::default().fg(self.theme.muted);
+        let Some(result) = self.result else {
+            return vec![Line::from(Span::styled(
+                "Running preflight checks...".to_string(),
+                muted,
+            ))];
+        };
+
+        let (heading, color) = if result.passed {
+            ("Ready to run", self.theme.success)
+        } else {
+            ("Preflight failed", self.theme.error)
+        };
+        let mut lines = vec![
+            Line::from(Span::styled(
+                heading.to_string(),
+                Style::default().fg(color).add_modifier(Modifier::BOLD),
+            )),
+            Line::from(Span::styled(result.summary(), muted)),
+            Line::from(""),
+        ];
+
+        let (pass, fail) = if self.ascii_mode {
+            ("[ok]", "[!!]")
+        } else {
+            ("✓", "✗")
+        };
+        for check in &result.checks {
+            let (marker, color) = if check.passed {
+                (pass, self.theme.success)
+            } else {
+                (fail, self.theme.error)
+            };
+            lines.push(Line::from(vec![
+                Span::styled(format!("{marker} "), Style::default().fg(color)),
+                Span::styled(
+                    check.label.clone(),
+                    Style::default()
+                        .fg(self.theme.text)
+                        .add_modifier(Modifier::BOLD),
+                ),
+            ]));
+            lines.push(Line::from(Span::styled(
+                format!("    {}", check.message),
+                muted,
+            )));
+        }
+
+        lines.push(Line::from(""));
+        let hint = if result.passed {
+            "Run /preflight again after changing models or the spec."
+        } else {
+            "Fix the failed checks, then run /preflight to retry."
+        };
+        lines.push(Line::from(Span::styled(hint.to_string(), muted)));
+
+        lines
+    }
+}
+
+impl Widget for PreflightPanel<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Paragraph::new(self.build_lines())
+            .wrap(Wrap { trim: false })
+            .render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ralf_engine::PreflightCheck;
+
+    fn check(label: &str, passed: bool, message: &str) -> PreflightCheck {
+        PreflightCheck {
+            name: label.to_lowercase(),
+            label: label.into(),
+            passed,
+            message: message.into(),
+        }
+    }
+
+    fn text(lines: &[Line<'_>]) -> String {
+        lines
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_lists_checks() {
+        let theme = Theme::default();
+        let result = PreflightResult {
+            passed: false,
+            checks: vec![
+                check("Context Window", true, "Spec is ~900 tokens"),
+                check("Model Auth & Quota", false, "No model responded"),
+            ],
+        };
+        let rendered = text(&PreflightPanel::new(Some(&result), &theme).build_lines());
+
+        assert!(rendered.contains("Preflight failed"));
+        assert!(rendered.contains("✓ Context Window\n    Spec is ~900 tokens"));
+        assert!(rendered.contains("✗ Model Auth & Quota\n    No model responded"));
+        assert!(rendered.contains("/preflight to retry"));
+
+        let panel = PreflightPanel::new(Some(&result), &theme).ascii_mode(true);
+        assert!(text(&panel.build_lines()).contains("[!!] Model Auth & Quota"));
+    }
+
+    #[test]
+    fn test_pending() {
+        let theme = Theme::default();
+        let text = text(&PreflightPanel::new(None, &theme).build_lines());
+        assert_eq!(text, "Running preflight checks...");
+    }
+}
@@ -0,0 +1,181 @@
+//! Recovery panel for the context pane.
+//!
+//! Shows why a run got stuck and the recovery suggestions, numbered so each
+//! can be applied with a single key.
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Paragraph, Widget, Wrap},
+};
+
+use ralf_engine::thread::StuckDiagnosis;
+
+use crate::theme::Theme;
+
+/// Recovery panel widget for a stuck thread.
+pub struct RecoveryPanel<'a> {
+    /// The diagnosis to render.
+    diagnosis: &'a StuckDiagnosis,
+    /// Theme for styling.
+    theme: &'a Theme,
+}
+
+impl<'a> RecoveryPanel<'a> {
+    /// Create a new recovery panel.
+    pub fn new(diagnosis: &'a StuckDiagnosis, theme: &'a Theme) -> Self {
+        Self { diagnosis, theme }
+    }
+
+    /// Build styled lines from the diagnosis.
+    fn build_lines(&self) -> Vec<Line<'static>> {
+        let d = self.diagnosis;
+        let muted = Style::default().fg(self.theme.muted);
+        let mut lines = vec![
+            Line::from(Span::styled(
+                "Run stuck".to_string(),
+                Style::default()
+                    .fg(self.theme.warning)
+                    .add_modifier(Modifier::BOLD),
+            )),
+            Line::from(Span::styled(
+                format!(
+                    "{}/{} criteria after {} iterations ({})",
+                    d.best_criteria_passed,
+                    d.total_criteria,
+                    d.iterations_attempted,
+                    d.models_tried.join(", ")
+                ),
+                muted,
+            )),
+        ];
+        for signal in &d.signals {
+            lines.push(Line::from(Span::styled(format!("- {signal}"), muted)));
+        }
+        if let Some(error) = &d.last_error {
+            lines.push(Line::from(Span::styled(
+                format!("Last error: {error}"),
+                muted,
+            )));
+        }
+        lines.push(Line::from(""));
+
+        if d.suggestions.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "No recovery suggestions.".to_string(),
+                muted,
+            )));
+            return lines;
+        }
+
+        lines.push(Line::from(Span::styled(
+            "Suggestions".to_string(),
+            Style::default()
+                .fg(self.theme.text)
+                .add_modifier(Modifier::BOLD),
+        )));
+        for (i, suggestion) in d.suggestions.iter().enumerate() {
+            lines.push(Line::from(""));
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("[{}] ", i + 1),
+                    Style::default()
+                        .fg(self.theme.info)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    format!("{}: ", suggestion.action.label()),
+                    Style::default().fg(self.theme.info),
+                ),
+                Span::styled(
+                    suggestion.title.clone(),
+                    Style::default().fg(self.theme.text),
+                ),
+            ]));
+            if !suggestion.details.is_empty() {
+                lines.push(Line::from(Span::styled(
+                    format!("    {}", suggestion.details),
+                    muted,
+                )));
+            }
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            format!("Press 1-{} to apply a suggestion.", d.suggestions.len()),
+            muted,
+        )));
+
+        lines
+    }
+}
+
+impl Widget for RecoveryPanel<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Paragraph::new(self.build_lines())
+            .wrap(Wrap { trim: false })
+            .render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ralf_engine::thread::{RecoveryAction, RecoverySuggestion};
+
+    fn diagnosis(suggestions: Vec<RecoverySuggestion>) -> StuckDiagnosis {
+        StuckDiagnosis {
+            iterations_attempted: 4,
+            models_tried: vec!["claude".into()],
+            best_criteria_passed: 1,
+            total_criteria: 3,
+            last_error: None,
+            signals: vec!["no new criteria passing".into()],
+            suggestions,
+        }
+    }
+
+    fn text(lines: &[Line<'_>]) -> String {
+        lines
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_lists_numbered_suggestions() {
+        let theme = Theme::default();
+        let d = diagnosis(vec![
+            RecoverySuggestion {
+                action: RecoveryAction::EditSpec,
+                title: "Split the task".into(),
+                details: "Move the migration into its own spec".into(),
+            },
+            RecoverySuggestion {
+                action: RecoveryAction::Reconfigure,
+                title: "Use codex".into(),
+                details: String::new(),
+            },
+        ]);
+        let text = text(&RecoveryPanel::new(&d, &theme).build_lines());
+
+        assert!(text.contains("1/3 criteria after 4 iterations (claude)"));
+        assert!(text.contains("[1] Edit spec: Split the task"));
+        assert!(text.contains("    Move the migration into its own spec"));
+        assert!(text.contains("[2] Reconfigure: Use codex"));
+        assert!(text.contains("Press 1-2 to apply"));
+    }
+
+    #[test]
+    fn test_without_suggestions() {
+        let theme = Theme::default();
+        let d = diagnosis(vec![]);
+        let text = text(&RecoveryPanel::new(&d, &theme).build_lines());
+
+        assert!(text.contains("- no new criteria passing"));
+        assert!(text.contains("No recovery suggestions."));
+        assert!(!text.contains("Press"));
+    }
+}
@@ -28,8 +28,10 @@ pub enum ContextView {
     PreflightResults,
     /// Run configuration (Configuring).
     RunConfig,
-    /// Run output streaming (Running, Verifying).
+    /// Run output streaming (Running).
     RunOutput,
+    /// Completion criteria progress (Verifying).
+    Criteria,
     /// Decision prompt (Paused, Stuck).
     DecisionPrompt,
     /// Implementation summary (Implemented, Polishing).
@@ -53,7 +55,8 @@ impl ContextView {
             }
             Some(PhaseKind::Preflight | PhaseKind::PreflightFailed) => Self::PreflightResults,
             Some(PhaseKind::Configuring) => Self::RunConfig,
-            Some(PhaseKind::Running | PhaseKind::Verifying) => Self::RunOutput,
+            Some(PhaseKind::Running) => Self::RunOutput,
+            Some(PhaseKind::Verifying) => Self::Criteria,
             Some(PhaseKind::Paused | PhaseKind::Stuck) => Self::DecisionPrompt,
             Some(PhaseKind::Implemented | PhaseKind::Polishing) => Self::Summary,
             Some(PhaseKind::PendingReview | PhaseKind::Approved) => Self::DiffViewer,
@@ -72,6 +75,7 @@ impl ContextView {
             Self::PreflightResults => " Preflight ",
             Self::RunConfig => " Configure ",
             Self::RunOutput => " Output ",
+            Self::Criteria => " Criteria ",
             Self::DecisionPrompt => " Decision ",
             Self::Summary => " Summary ",
             Self::DiffViewer => " Diff ",
@@ -89,6 +93,7 @@ impl ContextView {
             Self::PreflightResults => "Preflight Results\n\n(Implementation in M5-B.4)",
             Self::RunConfig => "Run Configuration\n\n(Implementation in M5-B.4)",
             Self::RunOutput => "Run Output\n\n(Implementation in M5-B.3)",
+            Self::Criteria => "Verifying completion criteria...",
             Self::DecisionPrompt => "Decision Required\n\n(Implementation in M5-B.4)",
             Self::Summary => "Implementation Summary\n\n(Implementation in M5-B.3)",
             Self::DiffViewer => "Diff Viewer\n\n(Implementation in M5-B.4)",
@@ -146,10 +151,15 @@ mod tests {
             ContextView::from_phase(Some(PhaseKind::Running)),
             ContextView::RunOutput
         );
+    }
+
+    #[test]
+    fn test_verifying_routes_to_criteria() {
         assert_eq!(
             ContextView::from_phase(Some(PhaseKind::Verifying)),
-            ContextView::RunOutput
+            ContextView::Criteria
         );
+        assert_eq!(ContextView::Criteria.title(), " Criteria ");
     }
 
     #[test]
@@ -0,0 +1,837 @@
+//! Run dashboard for the context pane.
+//!
+//! Shows the attached run while it implements: iteration and model, elapsed
+//! time, cooldown countdowns, verifier results and the latest model output,
+//! with the pause and cancel keys, so a run never needs the legacy status
+//! screen. When an iteration's changes need review, the dashboard says why
+//! and offers approve and reject keys; when the model asks a question, the
+//! dashboard shows it until the user answers. Once the run completes, the
+//! report that came with the model's promise is shown with the outcome.
+
+use std::time::{Duration, Instant};
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Paragraph, Widget},
+};
+
+use ralf_engine::runner::RunEvent;
+use ralf_engine::CompletionReport;
+
+use crate::models::format_countdown;
+use crate::theme::Theme;
+use crate::timeline::format_elapsed;
+
+/// A verifier's result in the current iteration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifierOutcome {
+    /// Verifier name.
+    pub name: String,
+    /// Whether it passed.
+    pub passed: bool,
+    /// Whether it was skipped.
+    pub skipped: bool,
+    /// How long it took.
+    pub duration_ms: u64,
+}
+
+/// Changes the run is holding for review.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingReview {
+    /// Why review is needed.
+    pub reasons: Vec<String>,
+    /// Files the iteration changed.
+    pub files_changed: usize,
+    /// Lines the iteration added and removed.
+    pub lines_changed: usize,
+}
+
+/// How the run ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RunOutcome {
+    /// Completed successfully, with the reason.
+    Completed(String),
+    /// Failed, with the error.
+    Failed(String),
+    /// Stopped making progress.
+    Stuck,
+    /// Cancelled by the user.
+    Cancelled,
+}
+
+/// Live state of the attached run.
+#[derive(Debug, Clone)]
+pub struct RunDashboardState {
+    /// Run ID, once the run has started.
+    pub run_id: Option<String>,
+    /// Current iteration (0 before the first).
+    pub iteration: usize,
+    /// Iteration limit (0 = unlimited).
+    pub max_iterations: usize,
+    /// Model implementing the current iteration.
+    pub model: Option<String>,
+    /// Model verifying the current iteration's criteria.
+    pub verifier_model: Option<String>,
+    /// Verifier results for the current iteration.
+    pub verifiers: Vec<VerifierOutcome>,
+    /// Preview of the latest model output.
+    pub output: String,
+    /// Models in cooldown and when their cooldown ends.
+    cooldowns: Vec<(String, Instant)>,
+    /// When the run was attached.
+    started_at: Instant,
+    /// When the run ended (freezes the elapsed time).
+    finished_at: Option<Instant>,
+    /// How the run ended.
+    pub outcome: Option<RunOutcome>,
+    /// The report that came with the current iteration's promise.
+    pub report: Option<CompletionReport>,
+    /// Changes waiting for the user to approve or reject them.
+    pub review: Option<PendingReview>,
+    /// The model's question, waiting for the user's answer.
+    pub question: Option<String>,
+    /// Whether a pause was requested or the run is paused.
+    pub pause_requested: bool,
+    /// Whether the run has paused.
+    pub paused: bool,
+    /// Whether a cancel was requested.
+    pub cancel_requested: bool,
+    /// Files the run failed to save, leaving its history incomplete.
+    pub save_failures: Vec<String>,
+}
+
+impl RunDashboardState {
+    /// Track a run attached at `now`.
+    pub fn new(now: Instant) -> Self {
+        Self {
+            run_id: None,
+            iteration: 0,
+            max_iterations: 0,
+            model: None,
+            verifier_model: None,
+            verifiers: Vec::new(),
+            output: String::new(),
+            cooldowns: Vec::new(),
+            started_at: now,
+            finished_at: None,
+            outcome: None,
+            report: None,
+            review: None,
+            question: None,
+            pause_requested: false,
+            paused: false,
+            cancel_requested: false,
+            save_failures: Vec::new(),
+        }
+    }
+
+    /// Update from a run event received at `now`.
+    pub fn apply(&mut self, event: &RunEvent, now: Instant) {
+        match event {
+            RunEvent::Started {
+                run_id,
+                max_iterations,
+                ..
+            } => {
+                self.run_id = Some(run_id.clone());
+                self.max_iterations = *max_iterations;
+            }
+            RunEvent::IterationStarted { iteration, model } => {
+                self.iteration = *iteration;
+                self.model = Some(model.clone());
+                self.verifier_model = None;
+                self.verifiers.clear();
+                self.output.clear();
+                self.report = None;
+                self.review = None;
+            }
+            RunEvent::ModelCompleted { output_preview, .. } => {
+                self.output.clone_from(output_preview);
+            }
+            RunEvent::VerifierCompleted {
+                name,
+                passed,
+                duration_ms,
+                skipped,
+                ..
+            } => self.verifiers.push(VerifierOutcome {
+                name: name.clone(),
+                passed: *passed,
+                skipped: *skipped,
+                duration_ms: *duration_ms,
+            }),
+            RunEvent::VerificationStarted { model, .. } => {
+                self.verifier_model = Some(model.clone());
+            }
+            RunEvent::CooldownStarted {
+                model,
+                duration_secs,
+            } => {
+                self.cooldowns.retain(|(name, _)| name != model);
+                self.cooldowns
+                    .push((model.clone(), now + Duration::from_secs(*duration_secs)));
+            }
+            RunEvent::Paused { .. } => self.paused = true,
+            RunEvent::Resumed { .. } => {
+                self.paused = false;
+                self.pause_requested = false;
+            }
+            RunEvent::ReviewRequired {
+                reasons,
+                files_changed,
+                lines_changed,
+                ..
+            } => {
+                self.review = Some(PendingReview {
+                    reasons: reasons.clone(),
+                    files_changed: *files_changed,
+                    lines_changed: *lines_changed,
+                });
+            }
+            RunEvent::ReviewResolved { .. } => self.review = None,
+            RunEvent::QuestionAsked { question, .. } => self.question = Some(question.clone()),
+            RunEvent::QuestionAnswered { .. } => self.question = None,
+            RunEvent::CompletionReported { report, .. } => {
+                self.report = Some(report.clone());
+            }
+            RunEvent::Completed { reason, .. } => {
+                self.finish(RunOutcome::Completed(reason.clone()), now);
+            }
+            RunEvent::Failed { error, .. } => self.finish(RunOutcome::Failed(error.clone()), now),
+            RunEvent::Stuck { .. } => self.finish(RunOutcome::Stuck, now),
+            RunEvent::Cancelled { .. } => self.finish(RunOutcome::Cancelled, now),
+            RunEvent::SaveFailed { what, .. } => self.save_failures.push(what.clone()),
+            _ => {}
+        }
+    }
+
+    fn finish(&mut self, outcome: RunOutcome, now: Instant) {
+        self.outcome = Some(outcome);
+        self.finished_at = Some(now);
+        self.cooldowns.clear();
+        self.review = None;
+        self.question = None;
+    }
+
+    /// Whether the run is still going.
+    pub fn is_active(&self) -> bool {
+        self.outcome.is_none()
+    }
+
+    /// Time since the run was attached, up to when it ended.
+    pub fn elapsed(&self, now: Instant) -> Duration {
+        self.finished_at
+            .unwrap_or(now)
+            .saturating_duration_since(self.started_at)
+    }
+
+    /// Models still in cooldown at `now`, with the seconds left.
+    pub fn cooldowns(&self, now: Instant) -> Vec<(&str, u64)> {
+        self.cooldowns
+            .iter()
+            .filter(|(_, until)| *until > now)
+            // Round up so a cooldown never shows 0s
+            .map(|(model, until)| {
+                let left = until.saturating_duration_since(now);
+                (
+                    model.as_str(),
+                    left.as_secs() + u64::from(left.subsec_nanos() > 0),
+                )
+            })
+            .collect()
+    }
+
+    /// Status word: Running, Reviewing, Question, Pausing, Paused,
+    /// Cancelling, or the outcome.
+    pub fn status(&self) -> &'static str {
+        match &self.outcome {
+            Some(RunOutcome::Completed(_)) => "Completed",
+            Some(RunOutcome::Failed(_)) => "Failed",
+            Some(RunOutcome::Stuck) => "Stuck",
+            Some(RunOutcome::Cancelled) => "Cancelled",
+            None if self.cancel_requested => "Cancelling",
+            None if self.review.is_some() => "Review needed",
+            None if self.question.is_some() => "Question",
+            None if self.paused => "Paused",
+            None if self.pause_requested => "Pausing",
+            None if self.iteration == 0 => "Starting",
+            None => "Running",
+        }
+    }
+}
+
+/// Run dashboard widget.
+pub struct RunDashboard<'a> {
+    /// Run to render.
+    state: &'a RunDashboardState,
+    /// Theme for styling.
+    theme: &'a Theme,
+    /// Whether to use ASCII markers.
+    ascii_mode: bool,
+}
+
+impl<'a> RunDashboard<'a> {
+    /// Create a new run dashboard.
+    pub fn new(state: &'a RunDashboardState, theme: &'a Theme) -> Self {
+        Self {
+            state,
+            theme,
+            ascii_mode: false,
+        }
+    }
+
+    /// Set ASCII mode.
+    #[must_use]
+    pub fn ascii_mode(mut self, ascii: bool) -> Self {
+        self.ascii_mode = ascii;
+        self
+    }
+
+    /// Get the color for a model name.
+    fn model_color(&self, model: &str) -> ratatui::style::Color {
+        match model {
+            "claude" => self.theme.claude,
+            "gemini" => self.theme.gemini,
+            "codex" => self.theme.codex,
+            _ => self.theme.info,
+        }
+    }
+
+    /// Color of the status word: the outcome's, or a warning while the run
+    /// waits on the user.
+    fn status_color(&self) -> ratatui::style::Color {
+        let state = self.state;
+        match state.outcome {
+            Some(RunOutcome::Completed(_)) => self.theme.success,
+            Some(RunOutcome::Failed(_)) => self.theme.error,
+            Some(RunOutcome::Stuck | RunOutcome::Cancelled) => self.theme.warning,
+            None if state.paused
+                || state.pause_requested
+                || state.cancel_requested
+                || state.review.is_some()
+                || state.question.is_some() =>
+            {
+                self.theme.warning
+            }
+            None => self.theme.info,
+        }
+    }
+
+    /// A verifier's result: marker, name and duration.
+    fn verifier_line(&self, verifier: &VerifierOutcome) -> Line<'static> {
+        let (marker, color, detail) = match (verifier.skipped, verifier.passed) {
+            (true, _) => (("-", "-"), self.theme.muted, "skipped".to_string()),
+            (false, true) => (
+                ("✓", "+"),
+                self.theme.success,
+                format_duration_ms(verifier.duration_ms),
+            ),
+            (false, false) => (
+                ("✗", "x"),
+                self.theme.error,
+                format_duration_ms(verifier.duration_ms),
+            ),
+        };
+        let marker = if self.ascii_mode { marker.1 } else { marker.0 };
+        Line::from(vec![
+            Span::styled(format!("  {marker} "), Style::default().fg(color)),
+            Span::styled(verifier.name.clone(), Style::default().fg(self.theme.text)),
+            Span::styled(format!(" {detail}"), Style::default().fg(self.theme.muted)),
+        ])
+    }
+
+    /// The question the model is waiting on an answer to.
+    fn question_lines(&self, question: &str) -> Vec<Line<'static>> {
+        let text = Style::default().fg(self.theme.text);
+        let mut lines = vec![
+            Line::from(""),
+            Line::from(Span::styled(
+                "The model asks",
+                Style::default().fg(self.theme.warning),
+            )),
+        ];
+        lines.extend(
+            question
+                .lines()
+                .map(|line| Line::from(Span::styled(format!("  {line}"), text))),
+        );
+        lines
+    }
+
+    /// Changes held for review: their size and why they need review.
+    fn review_lines(&self, review: &PendingReview) -> Vec<Line<'static>> {
+        let text = Style::default().fg(self.theme.text);
+        let mut lines = vec![
+            Line::from(""),
+            Line::from(Span::styled(
+                format!(
+                    "Changed {} files, {} lines",
+                    review.files_changed, review.lines_changed
+                ),
+                Style::default().fg(self.theme.warning),
+            )),
+        ];
+        lines.extend(
+            review
+                .reasons
+                .iter()
+                .map(|reason| Line::from(Span::styled(format!("  - {reason}"), text))),
+        );
+        lines
+    }
+
+    /// The completion report: summary, files, the model's assessment of
+    /// each criterion and follow-up work.
+    fn report_lines(&self, report: &CompletionReport) -> Vec<Line<'static>> {
+        let muted = Style::default().fg(self.theme.muted);
+        let text = Style::default().fg(self.theme.text);
+        let mut lines = vec![Line::from(""), Line::from(Span::styled("Report", muted))];
+        if let Some(summary) = &report.summary {
+            lines.extend(
+                summary
+                    .lines()
+                    .map(|line| Line::from(Span::styled(line.to_string(), text))),
+            );
+        }
+        if !report.files_changed.is_empty() {
+            lines.push(Line::from(vec![
+                Span::styled("Files: ", muted),
+                Span::styled(report.files_changed.join(", "), text),
+            ]));
+        }
+        for criterion in &report.criteria {
+            let (marker, color) = match (criterion.met, self.ascii_mode) {
+                (true, false) => ("✓", self.theme.success),
+                (true, true) => ("+", self.theme.success),
+                (false, false) => ("✗", self.theme.error),
+                (false, true) => ("x", self.theme.error),
+            };
+            let mut spans = vec![
+                Span::styled(format!("  {marker} "), Style::default().fg(color)),
+                Span::styled(criterion.id.clone(), text),
+            ];
+            if let Some(notes) = &criterion.notes {
+                spans.push(Span::styled(format!(" {notes}"), muted));
+            }
+            lines.push(Line::from(spans));
+        }
+        if !report.follow_up.is_empty() {
+            lines.push(Line::from(Span::styled("Follow-up", muted)));
+            lines.extend(
+                report
+                    .follow_up
+                    .iter()
+                    .map(|item| Line::from(Span::styled(format!("  - {item}"), text))),
+            );
+        }
+        lines
+    }
+
+    /// Build the dashboard lines above the output preview.
+    fn build_lines(&self, now: Instant) -> Vec<Line<'static>> {
+        let state = self.state;
+        let muted = Style::default().fg(self.theme.muted);
+        let text = Style::default().fg(self.theme.text);
+
+        let status_color = self.status_color();
+        #[allow(clippy::cast_possible_wrap)] // Elapsed seconds fit in i64
+        let elapsed = format_elapsed(state.elapsed(now).as_secs() as i64);
+        let mut status = vec![
+            Span::styled(
+                state.status().to_string(),
+                Style::default()
+                    .fg(status_color)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(format!("  {elapsed}"), text),
+        ];
+        if let Some(run_id) = &state.run_id {
+            status.push(Span::styled(format!("  {run_id}"), muted));
+        }
+        let mut lines = vec![Line::from(status)];
+
+        if let Some(RunOutcome::Completed(detail) | RunOutcome::Failed(detail)) = &state.outcome {
+            lines.push(Line::from(Span::styled(
+                detail.clone(),
+                Style::default().fg(status_color),
+            )));
+        }
+
+        if !state.save_failures.is_empty() {
+            lines.push(Line::from(Span::styled(
+                format!(
+                    "History may be incomplete (failed to save {})",
+                    state.save_failures.join(", ")
+                ),
+                Style::default().fg(self.theme.warning),
+            )));
+        }
+
+        if state.iteration > 0 {
+            let iteration = if state.max_iterations > 0 {
+                format!("Iteration {}/{}", state.iteration, state.max_iterations)
+            } else {
+                format!("Iteration {}", state.iteration)
+            };
+            let mut spans = vec![Span::styled(iteration, text)];
+            if let Some(model) = &state.model {
+                spans.push(Span::styled(
+                    format!(" · {model}"),
+                    Style::default().fg(self.model_color(model)),
+                ));
+            }
+            if let Some(verifier) = &state.verifier_model {
+                spans.push(Span::styled(format!(" (verifying: {verifier})"), muted));
+            }
+            lines.push(Line::from(spans));
+        }
+
+        let cooldowns = state.cooldowns(now);
+        if !cooldowns.is_empty() {
+            let list = cooldowns
+                .iter()
+                .map(|(model, secs)| format!("{model} {}", format_countdown(*secs)))
+                .collect::<Vec<_>>()
+                .join(", ");
+            lines.push(Line::from(vec![
+                Span::styled("Cooldowns: ", muted),
+                Span::styled(list, Style::default().fg(self.theme.warning)),
+            ]));
+        }
+
+        if let Some(review) = &state.review {
+            lines.extend(self.review_lines(review));
+        }
+
+        if let Some(question) = &state.question {
+            lines.extend(self.question_lines(question));
+        }
+
+        if !state.verifiers.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled("Verifiers", muted)));
+            lines.extend(state.verifiers.iter().map(|v| self.verifier_line(v)));
+        }
+
+        if let (Some(RunOutcome::Completed(_)), Some(report)) = (&state.outcome, &state.report) {
+            lines.extend(self.report_lines(report));
+        }
+
+        if state.is_active() {
+            let pause = if state.review.is_some() {
+                "a Approve  r Reject"
+            } else if state.question.is_some() {
+                "Type your answer"
+            } else if state.pause_requested {
+                "p Resume"
+            } else {
+                "p Pause"
+            };
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                format!("{pause}  x Cancel"),
+                muted,
+            )));
+        }
+
+        if !state.output.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled("Output", muted)));
+        }
+        lines
+    }
+}
+
+/// "850ms", "1.2s".
+fn format_duration_ms(ms: u64) -> String {
+    if ms < 1000 {
+        format!("{ms}ms")
+    } else {
+        format!("{}.{}s", ms / 1000, ms % 1000 / 100)
+    }
+}
+
+impl Widget for RunDashboard<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let mut lines = self.build_lines(Instant::now());
+        // The output preview fills the rest of the pane, newest lines last
+        let room = usize::from(area.height).saturating_sub(lines.len());
+        let output: Vec<&str> = self.state.output.lines().collect();
+        let text = Style::default().fg(self.theme.text);
+        lines.extend(
+            output[output.len().saturating_sub(room)..]
+                .iter()
+                .map(|line| Line::from(Span::styled((*line).to_string(), text))),
+        );
+        Paragraph::new(lines).render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(lines: &[Line<'_>]) -> String {
+        lines
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_apply_events() {
+        let start = Instant::now();
+        let mut state = RunDashboardState::new(start);
+        assert_eq!(state.status(), "Starting");
+
+        state.apply(
+            &RunEvent::Started {
+                run_id: "run-1".into(),
+                max_iterations: 5,
+                branch: None,
+            },
+            start,
+        );
+        state.apply(
+            &RunEvent::IterationStarted {
+                iteration: 2,
+                model: "claude".into(),
+            },
+            start,
+        );
+        state.apply(
+            &RunEvent::CooldownStarted {
+                model: "gemini".into(),
+                duration_secs: 30,
+            },
+            start,
+        );
+        assert_eq!(state.status(), "Running");
+        assert_eq!(state.max_iterations, 5);
+        assert_eq!(
+            state.cooldowns(start + Duration::from_millis(10_500)),
+            vec![("gemini", 20)]
+        );
+        assert!(state.cooldowns(start + Duration::from_secs(30)).is_empty());
+
+        state.pause_requested = true;
+        assert_eq!(state.status(), "Pausing");
+        state.apply(&RunEvent::Paused { iteration: 2 }, start);
+        assert_eq!(state.status(), "Paused");
+        state.apply(&RunEvent::Resumed { iteration: 2 }, start);
+        assert_eq!(state.status(), "Running");
+
+        let end = start + Duration::from_secs(65);
+        state.apply(&RunEvent::Cancelled { iteration: 2 }, end);
+        assert_eq!(state.status(), "Cancelled");
+        assert!(!state.is_active());
+        assert_eq!(
+            state.elapsed(end + Duration::from_secs(100)),
+            Duration::from_secs(65)
+        );
+    }
+
+    #[test]
+    fn test_build_lines() {
+        let theme = Theme::default();
+        let start = Instant::now();
+        let mut state = RunDashboardState::new(start);
+        let events = [
+            RunEvent::Started {
+                run_id: "run-1".into(),
+                max_iterations: 5,
+                branch: None,
+            },
+            RunEvent::IterationStarted {
+                iteration: 1,
+                model: "codex".into(),
+            },
+            RunEvent::ModelCompleted {
+                iteration: 1,
+                model: "codex".into(),
+                duration_ms: 4000,
+                has_promise: false,
+                rate_limited: false,
+                output_preview: "Edited src/lib.rs".into(),
+            },
+            RunEvent::VerifierCompleted {
+                iteration: 1,
+                name: "test".into(),
+                passed: true,
+                duration_ms: 1250,
+                skipped: false,
+            },
+            RunEvent::VerifierCompleted {
+                iteration: 1,
+                name: "lint".into(),
+                passed: false,
+                duration_ms: 800,
+                skipped: false,
+            },
+        ];
+        for event in &events {
+            state.apply(event, start);
+        }
+
+        let now = start + Duration::from_secs(65);
+        let rendered = text(&RunDashboard::new(&state, &theme).build_lines(now));
+        assert_eq!(
+            rendered,
+            "Running  1m 05s  run-1\n\
+             Iteration 1/5 · codex\n\
+             \n\
+             Verifiers\n  \
+             ✓ test 1.2s\n  \
+             ✗ lint 800ms\n\
+             \n\
+             p Pause  x Cancel\n\
+             \n\
+             Output"
+        );
+
+        state.apply(
+            &RunEvent::Failed {
+                iteration: 1,
+                error: "lint failed".into(),
+            },
+            now,
+        );
+        let panel = RunDashboard::new(&state, &theme).ascii_mode(true);
+        let rendered = text(&panel.build_lines(now));
+        assert!(rendered.starts_with("Failed  1m 05s  run-1\nlint failed\n"));
+        assert!(rendered.contains("  x lint 800ms"));
+        assert!(!rendered.contains("Cancel"));
+
+        state.apply(
+            &RunEvent::SaveFailed {
+                what: "run state".into(),
+                error: "disk full".into(),
+            },
+            now,
+        );
+        let rendered = text(&RunDashboard::new(&state, &theme).build_lines(now));
+        assert!(rendered.starts_with(
+            "Failed  1m 05s  run-1\nlint failed\n\
+             History may be incomplete (failed to save run state)\n"
+        ));
+    }
+
+    #[test]
+    fn test_review_needed() {
+        let theme = Theme::default();
+        let start = Instant::now();
+        let mut state = RunDashboardState::new(start);
+        state.apply(
+            &RunEvent::IterationStarted {
+                iteration: 1,
+                model: "claude".into(),
+            },
+            start,
+        );
+        state.apply(
+            &RunEvent::ReviewRequired {
+                iteration: 1,
+                reasons: vec!["Touched protected paths: migrations/0002.sql".into()],
+                files_changed: 4,
+                lines_changed: 120,
+            },
+            start,
+        );
+        assert_eq!(state.status(), "Review needed");
+
+        let rendered = text(&RunDashboard::new(&state, &theme).build_lines(start));
+        assert!(rendered.contains(
+            "Changed 4 files, 120 lines
+  - Touched protected paths: migrations/0002.sql"
+        ));
+        assert!(rendered.contains("a Approve  r Reject  x Cancel"));
+
+        state.apply(
+            &RunEvent::ReviewResolved {
+                iteration: 1,
+                approved: true,
+            },
+            start,
+        );
+        assert_eq!(state.status(), "Running");
+        let rendered = text(&RunDashboard::new(&state, &theme).build_lines(start));
+        assert!(!rendered.contains("Changed 4 files"));
+        assert!(rendered.contains("p Pause  x Cancel"));
+    }
+
+    #[test]
+    fn test_question() {
+        let theme = Theme::default();
+        let start = Instant::now();
+        let mut state = RunDashboardState::new(start);
+        state.apply(
+            &RunEvent::QuestionAsked {
+                iteration: 1,
+                question: "Postgres or SQLite?".into(),
+            },
+            start,
+        );
+        assert_eq!(state.status(), "Question");
+        let rendered = text(&RunDashboard::new(&state, &theme).build_lines(start));
+        assert!(rendered.contains("The model asks\n  Postgres or SQLite?"));
+        assert!(rendered.contains("Type your answer  x Cancel"));
+
+        state.apply(
+            &RunEvent::QuestionAnswered {
+                iteration: 1,
+                answer: "SQLite".into(),
+            },
+            start,
+        );
+        assert_eq!(state.question, None);
+        assert_eq!(state.status(), "Starting");
+    }
+
+    #[test]
+    fn test_completion_report() {
+        let theme = Theme::default();
+        let start = Instant::now();
+        let mut state = RunDashboardState::new(start);
+        let report = CompletionReport {
+            summary: Some("Added a login page".into()),
+            files_changed: vec!["src/login.rs".into()],
+            criteria: vec![ralf_engine::CriterionAssessment {
+                id: "c1".into(),
+                met: true,
+                notes: Some("Tested".into()),
+            }],
+            follow_up: vec!["Rate-limit logins".into()],
+        };
+        state.apply(
+            &RunEvent::CompletionReported {
+                iteration: 1,
+                report,
+            },
+            start,
+        );
+        // Shown only once the run completes
+        let rendered = text(&RunDashboard::new(&state, &theme).build_lines(start));
+        assert!(!rendered.contains("Report"));
+
+        state.apply(
+            &RunEvent::Completed {
+                iteration: 1,
+                reason: "All criteria verified".into(),
+            },
+            start,
+        );
+        let rendered = text(&RunDashboard::new(&state, &theme).build_lines(start));
+        assert!(rendered.contains(
+            "Report\n\
+             Added a login page\n\
+             Files: src/login.rs\n  \
+             ✓ c1 Tested\n\
+             Follow-up\n  \
+             - Rate-limit logins"
+        ));
+    }
+}
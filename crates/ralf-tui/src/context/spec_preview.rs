@@ -1,16 +1,21 @@
 //! Spec preview widget for the context pane.
 //!
-//! Renders the spec draft with markdown styling and phase indicator.
+//! Renders the spec draft with markdown styling and phase indicator, with
+//! lint diagnostics above the draft and a scrollbar when it overflows.
 
 use ratatui::{
     buffer::Buffer,
     layout::Rect,
     style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Paragraph, Widget, Wrap},
+    widgets::{
+        Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, StatefulWidget, Widget, Wrap,
+    },
 };
 
-use crate::text::render_markdown;
+use ralf_engine::SpecDiagnostic;
+
+use crate::text::{render_markdown, wrap_lines};
 use crate::theme::Theme;
 
 /// Phase badge to display in the spec preview.
@@ -47,6 +52,8 @@ pub struct SpecPreview<'a> {
     scroll: u16,
     /// Whether this pane is focused.
     focused: bool,
+    /// Lint diagnostics for the content.
+    diagnostics: &'a [SpecDiagnostic],
 }
 
 impl<'a> SpecPreview<'a> {
@@ -58,6 +65,7 @@ impl<'a> SpecPreview<'a> {
             theme,
             scroll: 0,
             focused: false,
+            diagnostics: &[],
         }
     }
 
@@ -75,6 +83,13 @@ impl<'a> SpecPreview<'a> {
         self
     }
 
+    /// Set the lint diagnostics to show above the content.
+    #[must_use]
+    pub fn diagnostics(mut self, diagnostics: &'a [SpecDiagnostic]) -> Self {
+        self.diagnostics = diagnostics;
+        self
+    }
+
     /// Build styled lines from the spec content.
     fn build_lines(&self) -> Vec<Line<'static>> {
         let mut lines = Vec::new();
@@ -97,6 +112,11 @@ impl<'a> SpecPreview<'a> {
             return lines;
         }
 
+        if !self.diagnostics.is_empty() {
+            lines.extend(self.diagnostics.iter().map(|d| self.build_diagnostic(d)));
+            lines.push(Line::from(""));
+        }
+
         // Render markdown content using shared renderer
         let markdown_lines = render_markdown(self.content, 80, self.theme);
         lines.extend(markdown_lines);
@@ -104,6 +124,66 @@ impl<'a> SpecPreview<'a> {
         lines
     }
 
+    /// Lines wrapped to fit `area`, and whether they overflow it. Overflowing
+    /// lines leave the last column for the scrollbar.
+    fn wrapped_lines(&self, area: Rect) -> (Vec<Line<'static>>, bool) {
+        let lines = self.build_lines();
+        let wrapped = wrap_lines(lines.clone(), area.width as usize);
+        if wrapped.len() <= area.height as usize || area.width <= 1 {
+            return (wrapped, false);
+        }
+        (wrap_lines(lines, (area.width - 1) as usize), true)
+    }
+
+    /// The scroll offset, clamped so the last page stays full.
+    fn clamped_scroll(&self, total: usize, height: usize) -> usize {
+        usize::from(self.scroll).min(total.saturating_sub(height))
+    }
+
+    /// Visible lines for the pane border, e.g. "Lines 1-20 of 96", or None
+    /// when the spec fits in `area`.
+    pub fn position_label(&self, area: Rect) -> Option<String> {
+        let (lines, overflows) = self.wrapped_lines(area);
+        if !overflows {
+            return None;
+        }
+        let height = area.height as usize;
+        let first = self.clamped_scroll(lines.len(), height);
+        Some(format!(
+            "Lines {}-{} of {}",
+            first + 1,
+            first + height,
+            lines.len()
+        ))
+    }
+
+    /// Build a line for one diagnostic.
+    fn build_diagnostic(&self, diagnostic: &SpecDiagnostic) -> Line<'static> {
+        let (label, color) = if diagnostic.is_error() {
+            ("error", self.theme.error)
+        } else {
+            ("warning", self.theme.warning)
+        };
+        let mut spans = vec![
+            Span::styled(
+                label,
+                Style::default().fg(color).add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(": ", Style::default().fg(self.theme.muted)),
+            Span::styled(
+                diagnostic.message.clone(),
+                Style::default().fg(self.theme.text),
+            ),
+        ];
+        if let Some(line) = diagnostic.line {
+            spans.push(Span::styled(
+                format!(" (line {line})"),
+                Style::default().fg(self.theme.muted),
+            ));
+        }
+        Line::from(spans)
+    }
+
     /// Build the phase badge line.
     fn build_phase_badge(&self) -> Line<'static> {
         let badge_color = match self.phase {
@@ -125,13 +205,33 @@ impl<'a> SpecPreview<'a> {
 
 impl Widget for SpecPreview<'_> {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        let lines = self.build_lines();
-
+        if area.is_empty() {
+            return;
+        }
+        let (lines, overflows) = self.wrapped_lines(area);
+        let total = lines.len();
+        let height = area.height as usize;
+        let scroll = self.clamped_scroll(total, height);
+
+        let content = Rect {
+            width: area.width - u16::from(overflows),
+            ..area
+        };
         let paragraph = Paragraph::new(lines)
             .wrap(Wrap { trim: false })
-            .scroll((self.scroll, 0));
-
-        paragraph.render(area, buf);
+            .scroll((u16::try_from(scroll).unwrap_or(u16::MAX), 0));
+        paragraph.render(content, buf);
+
+        if overflows {
+            // One position per scroll offset, so the thumb reaches the
+            // bottom on the last page
+            let mut state = ScrollbarState::new(total - height + 1)
+                .position(scroll)
+                .viewport_content_length(height);
+            Scrollbar::new(ScrollbarOrientation::VerticalRight)
+                .style(Style::default().fg(self.theme.border))
+                .render(area, buf, &mut state);
+        }
     }
 }
 
@@ -150,6 +250,22 @@ mod tests {
         assert_eq!(SpecPhase::Ready.label(), "Ready");
     }
 
+    #[test]
+    fn test_diagnostics_rendering() {
+        let theme = test_theme();
+        let content = "# Task\n\n## Criteria\n- It works well";
+        let diagnostics = ralf_engine::lint(content);
+        let preview =
+            SpecPreview::new(content, SpecPhase::Drafting, &theme).diagnostics(&diagnostics);
+        let lines = preview.build_lines();
+
+        let text: Vec<String> = lines.iter().map(ToString::to_string).collect();
+        assert!(text[2].starts_with("error: Missing <promise>"));
+        assert!(text[3].starts_with("warning: Criterion 1 is hard to verify"));
+        assert!(text[3].ends_with("(line 4)"));
+        assert_eq!(text[4], "");
+    }
+
     #[test]
     fn test_empty_content() {
         let theme = test_theme();
@@ -210,6 +326,33 @@ mod tests {
         assert_eq!(preview.scroll, 5);
     }
 
+    #[test]
+    fn test_position_label_and_scrollbar() {
+        let theme = test_theme();
+        let content = (1..=30)
+            .map(|i| format!("Line {i}\n"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let area = Rect::new(0, 0, 30, 10);
+
+        let short = SpecPreview::new("Short", SpecPhase::Drafting, &theme);
+        assert_eq!(short.position_label(area), None);
+
+        let preview = SpecPreview::new(&content, SpecPhase::Drafting, &theme).scroll(5);
+        let label = preview.position_label(area).unwrap();
+        assert!(label.starts_with("Lines 6-15 of "), "{label}");
+
+        // Scrolling past the end keeps the last page in view
+        let preview = SpecPreview::new(&content, SpecPhase::Drafting, &theme).scroll(500);
+        let label = preview.position_label(area).unwrap();
+        let total = label.rsplit(' ').next().unwrap();
+        assert!(label.ends_with(&format!("-{total} of {total}")), "{label}");
+
+        let mut buf = Buffer::empty(area);
+        preview.render(area, &mut buf);
+        assert_eq!(buf[(29, 8)].symbol(), "█");
+    }
+
     #[test]
     fn test_focused_state() {
         let theme = test_theme();
@@ -50,6 +50,8 @@ pub struct ConversationPane<'a> {
     canvas_shows_spec: bool,
     /// Tick counter for animations.
     tick: usize,
+    /// Paths of the files attached to the chat.
+    attachments: &'a [String],
 }
 
 impl<'a> ConversationPane<'a> {
@@ -67,6 +69,7 @@ impl<'a> ConversationPane<'a> {
             focused: false,
             canvas_shows_spec: false,
             tick: 0,
+            attachments: &[],
         }
     }
 
@@ -80,6 +83,7 @@ impl<'a> ConversationPane<'a> {
             focused: false,
             canvas_shows_spec: false,
             tick: 0,
+            attachments: &[],
         }
     }
 
@@ -113,6 +117,13 @@ impl<'a> ConversationPane<'a> {
         self
     }
 
+    /// Set the paths of the attached files, listed along the bottom border.
+    #[must_use]
+    pub fn attachments(mut self, attachments: &'a [String]) -> Self {
+        self.attachments = attachments;
+        self
+    }
+
     /// Render the input area.
     fn render_input(&self, area: Rect, buf: &mut Buffer) {
         // Get the input state (should always be Some when this is called)
@@ -149,60 +160,33 @@ impl<'a> ConversationPane<'a> {
             let line = Line::from(spans);
             Paragraph::new(vec![line]).render(area, buf);
         } else {
-            // Show content with cursor
-            let content = input.content();
-            let cursor_pos = input.cursor;
-
-            let mut lines: Vec<Line<'_>> = Vec::new();
-            let mut current_line_spans: Vec<Span<'_>> = Vec::new();
-            let mut is_first_line = true;
-            let mut cursor_drawn = false;
-
-            // Add prompt to first line
-            current_line_spans.push(Span::styled(
-                prompt.to_string(),
-                Style::default().fg(self.theme.primary),
-            ));
-
-            for (char_count, ch) in content.chars().enumerate() {
-                // Insert cursor before this character if at position
-                if self.focused && char_count == cursor_pos && !cursor_drawn {
-                    current_line_spans.push(Span::styled(
-                        "|",
-                        Style::default().fg(self.theme.text),
-                    ));
-                    cursor_drawn = true;
-                }
-
-                if ch == '\n' {
-                    // End current line
-                    lines.push(Line::from(current_line_spans));
-                    current_line_spans = Vec::new();
-
+            // Show soft-wrapped content with cursor
+            let width = (area.width as usize).saturating_sub(prompt_len);
+            let (rows, (cursor_row, cursor_col)) = input.wrap(width);
+            let text_style = Style::default().fg(self.theme.text);
+
+            let mut lines: Vec<Line<'_>> = Vec::with_capacity(rows.len());
+            for (index, row) in rows.into_iter().enumerate() {
+                let mut spans = vec![if index == 0 {
+                    Span::styled(prompt.to_string(), Style::default().fg(self.theme.primary))
+                } else {
                     // Continuation lines get indentation
-                    if is_first_line {
-                        is_first_line = false;
-                    }
-                    current_line_spans.push(Span::raw(" ".repeat(prompt_len)));
+                    Span::raw(" ".repeat(prompt_len))
+                }];
+                if self.focused && index == cursor_row {
+                    let split = row
+                        .char_indices()
+                        .nth(cursor_col)
+                        .map_or(row.len(), |(at, _)| at);
+                    let (before, after) = row.split_at(split);
+                    let cursor = if after.is_empty() { "_" } else { "|" };
+                    spans.push(Span::styled(before.to_string(), text_style));
+                    spans.push(Span::styled(cursor, text_style));
+                    spans.push(Span::styled(after.to_string(), text_style));
                 } else {
-                    current_line_spans.push(Span::styled(
-                        ch.to_string(),
-                        Style::default().fg(self.theme.text),
-                    ));
+                    spans.push(Span::styled(row, text_style));
                 }
-            }
-
-            // Cursor at the end
-            if self.focused && !cursor_drawn {
-                current_line_spans.push(Span::styled(
-                    "_",
-                    Style::default().fg(self.theme.text),
-                ));
-            }
-
-            // Add remaining content
-            if !current_line_spans.is_empty() {
-                lines.push(Line::from(current_line_spans));
+                lines.push(Line::from(spans));
             }
 
             Paragraph::new(lines).render(area, buf);
@@ -243,12 +227,24 @@ impl Widget for ConversationPane<'_> {
             " Timeline "
         };
 
-        let block = Block::default()
+        let mut block = Block::default()
             .title(title)
             .title_style(Style::default().fg(self.theme.text))
             .borders(Borders::ALL)
             .border_style(border_style)
             .style(Style::default().bg(self.theme.base));
+        if !self.attachments.is_empty() {
+            block = block.title_bottom(Line::styled(
+                format!(" Attached: {} ", self.attachments.join(", ")),
+                Style::default().fg(self.theme.subtext),
+            ));
+        }
+        if let Some(label) = self.timeline.position_label() {
+            block = block.title_bottom(
+                Line::styled(format!(" {label} "), Style::default().fg(self.theme.muted))
+                    .right_aligned(),
+            );
+        }
 
         let inner = block.inner(area);
         block.render(area, buf);
@@ -0,0 +1,161 @@
+//! Running interactive commands with the TUI suspended.
+//!
+//! Some commands need the terminal to themselves: signing in to a model CLI,
+//! editing the draft in `$EDITOR`, `git commit`, `cargo fix`.
+//! [`run_suspended`] leaves the alternate screen and raw mode, runs the
+//! command attached to the terminal, and restores the TUI once it exits. The
+//! command's [`AfterExternal`] tells the shell what to refresh afterwards.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crossterm::event::{
+    DisableMouseCapture, EnableMouseCapture, KeyboardEnhancementFlags, PopKeyboardEnhancementFlags,
+    PushKeyboardEnhancementFlags,
+};
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use ratatui::{backend::Backend, Terminal};
+
+/// What the shell refreshes after an external command exits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AfterExternal {
+    /// Nothing beyond reporting how the command exited.
+    Nothing,
+    /// Re-probe the models (e.g., after signing in).
+    RefreshModels,
+    /// Load the edited file back into the draft.
+    ReloadDraft(PathBuf),
+}
+
+/// An interactive command to run with the TUI suspended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalCommand {
+    /// Program followed by its arguments.
+    pub argv: Vec<String>,
+    /// What to refresh once the command exits.
+    pub after: AfterExternal,
+}
+
+impl ExternalCommand {
+    /// Run `argv` directly.
+    pub fn new(argv: Vec<String>) -> Self {
+        Self {
+            argv,
+            after: AfterExternal::Nothing,
+        }
+    }
+
+    /// Run a command line through the system shell.
+    pub fn shell(line: &str) -> Self {
+        let (shell, flag) = if cfg!(windows) {
+            ("cmd", "/C")
+        } else {
+            ("sh", "-c")
+        };
+        Self::new(vec![shell.into(), flag.into(), line.into()])
+    }
+
+    /// Open `path` in the user's editor, reloading the draft afterwards.
+    pub fn editor(path: &Path) -> Self {
+        let visual = std::env::var("VISUAL").ok();
+        let editor = std::env::var("EDITOR").ok();
+        let mut argv = editor_argv(visual.as_deref(), editor.as_deref());
+        argv.push(path.display().to_string());
+        Self::new(argv).after(AfterExternal::ReloadDraft(path.to_path_buf()))
+    }
+
+    /// Set what to refresh once the command exits.
+    #[must_use]
+    pub fn after(mut self, after: AfterExternal) -> Self {
+        self.after = after;
+        self
+    }
+
+    /// The command line, for messages.
+    pub fn display(&self) -> String {
+        self.argv.join(" ")
+    }
+}
+
+/// The editor from `$VISUAL` or `$EDITOR`, split into program and arguments
+/// (e.g., `code --wait`), falling back to `vi`.
+fn editor_argv(visual: Option<&str>, editor: Option<&str>) -> Vec<String> {
+    [visual, editor]
+        .into_iter()
+        .flatten()
+        .map(|cmd| {
+            cmd.split_whitespace()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+        })
+        .find(|parts| !parts.is_empty())
+        .unwrap_or_else(|| vec!["vi".to_string()])
+}
+
+/// Leave the TUI, run `command` attached to the terminal, and restore the
+/// TUI once it exits.
+///
+/// # Errors
+///
+/// Returns an error if the terminal can't be switched, the command can't be
+/// started, or it exits unsuccessfully.
+pub fn run_suspended<B: Backend>(
+    terminal: &mut Terminal<B>,
+    command: &ExternalCommand,
+) -> io::Result<()> {
+    let Some((program, args)) = command.argv.split_first() else {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "empty command"));
+    };
+
+    let mut stdout = io::stdout();
+    // Unsupported on some terminals; ignore errors as in `run_shell_tui`
+    let _ = crossterm::execute!(stdout, PopKeyboardEnhancementFlags);
+    crossterm::execute!(stdout, DisableMouseCapture, LeaveAlternateScreen)?;
+    disable_raw_mode()?;
+
+    let status = std::process::Command::new(program).args(args).status();
+
+    enable_raw_mode()?;
+    crossterm::execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    let _ = crossterm::execute!(
+        stdout,
+        PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
+    );
+    terminal.clear()?;
+
+    let status = status.map_err(|e| io::Error::new(e.kind(), format!("{program}: {e}")))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("{program} exited with {status}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_editor_argv() {
+        assert_eq!(editor_argv(None, None), vec!["vi"]);
+        assert_eq!(editor_argv(None, Some("nano")), vec!["nano"]);
+        assert_eq!(
+            editor_argv(Some("code --wait"), Some("nano")),
+            vec!["code", "--wait"]
+        );
+        assert_eq!(editor_argv(Some(" "), Some("nano")), vec!["nano"]);
+    }
+
+    #[test]
+    fn test_external_command() {
+        let command = ExternalCommand::shell("git commit").after(AfterExternal::RefreshModels);
+        assert_eq!(command.argv.last().map(String::as_str), Some("git commit"));
+        assert_eq!(command.after, AfterExternal::RefreshModels);
+
+        let command = ExternalCommand::new(vec!["codex".into(), "login".into()]);
+        assert_eq!(command.display(), "codex login");
+        assert_eq!(command.after, AfterExternal::Nothing);
+    }
+}
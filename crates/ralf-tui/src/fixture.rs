@@ -0,0 +1,201 @@
+//! Shell state fixtures for rendering single frames.
+//!
+//! `ralf shell --render-once --state <fixture.json>` renders one frame of the
+//! shell from a fixture and prints it, for reproducing layout issues at a
+//! given terminal size and for golden-file comparisons. A fixture describes
+//! everything the frame shows, so the output doesn't depend on the `.ralf/`
+//! directory it's rendered in:
+//!
+//! ```json
+//! {
+//!   "messages": [{"role": "user", "content": "Add a login page",
+//!                 "timestamp": "2026-01-01T12:00:00Z"}],
+//!   "draft": "# Login page\n",
+//!   "models": [{"name": "claude", "state": "Ready", "version": "1.0.0",
+//!               "message": null}],
+//!   "input": "/run"
+//! }
+//! ```
+
+use std::io;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use ralf_engine::chat::{ChatMessage, Thread as ChatThread};
+use ralf_engine::events::EventRecord;
+use ralf_engine::thread::Thread;
+use ralf_engine::usage::Usage;
+
+use crate::models::ModelStatus;
+use crate::shell::ShellApp;
+use crate::theme::{user_themes_dir, BorderSet, IconMode, IconSet, Theme, BUILTIN_THEMES};
+use crate::thread_state::ThreadDisplay;
+use crate::timeline::{merge_history, TimelineState};
+use crate::ui::widgets::TextInputState;
+
+/// State to render the shell from. Every field is optional.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ShellFixture {
+    /// Current thread, as saved in `.ralf/threads/<id>.json`.
+    pub thread: Option<Thread>,
+    /// Spec conversation shown in the timeline.
+    pub messages: Vec<ChatMessage>,
+    /// Spec draft shown in the canvas.
+    pub draft: Option<String>,
+    /// Model chosen with `/model`.
+    pub model: Option<String>,
+    /// Run events shown in the timeline, as in `events.jsonl`.
+    pub events: Vec<EventRecord>,
+    /// Model statuses shown in the models panel.
+    pub models: Vec<ModelStatus>,
+    /// Token/cost totals shown in the status bar.
+    pub usage: Option<Usage>,
+    /// Text in the input box.
+    pub input: String,
+    /// Toast message.
+    pub toast: Option<String>,
+    /// Whether the help overlay is open.
+    pub show_help: bool,
+    /// Theme name (a built-in or user theme; default mocha).
+    pub theme: Option<String>,
+    /// Whether to use ASCII icons and borders.
+    pub ascii: bool,
+}
+
+impl ShellFixture {
+    /// Load a fixture from a JSON file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be read or isn't a valid fixture.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        std::fs::read_to_string(path)
+            .and_then(|content| Self::from_json(&content))
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {e}", path.display())))
+    }
+
+    /// Parse a fixture from JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `json` isn't a valid fixture.
+    pub fn from_json(json: &str) -> io::Result<Self> {
+        serde_json::from_str(json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Replace the state `app` loaded with the fixture's.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the fixture names an unknown theme.
+    pub fn apply(self, app: &mut ShellApp) -> io::Result<()> {
+        let theme_name = self.theme.unwrap_or_else(|| BUILTIN_THEMES[0].to_string());
+        app.theme = Theme::load(&theme_name, user_themes_dir().as_deref())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        app.theme_name = theme_name;
+        app.ui_config.icons = if self.ascii {
+            IconMode::Ascii
+        } else {
+            IconMode::Nerd
+        };
+        app.icons = IconSet::new(app.ui_config.icons);
+        app.borders = BorderSet::new(app.ui_config.icons);
+
+        app.models = self.models;
+        app.probe_complete = true;
+        app.run_usage = self.usage;
+        app.current_thread = self.thread.as_ref().map(ThreadDisplay::from_thread);
+
+        app.timeline = TimelineState::new();
+        app.timeline
+            .restore(merge_history(&self.messages, &self.events));
+        app.chat_thread = (!self.messages.is_empty()
+            || self.draft.is_some()
+            || self.model.is_some())
+        .then(|| {
+            let mut chat = ChatThread::new();
+            chat.messages = self.messages;
+            chat.draft = self.draft.unwrap_or_default();
+            chat.model = self.model;
+            chat
+        });
+
+        // As in the shell, the models panel gives way to a thread or chat
+        app.show_models_panel = app.current_thread.is_none() && app.chat_thread.is_none();
+
+        app.input = TextInputState::new();
+        app.input.insert_str(&self.input);
+        app.toast = None;
+        if let Some(message) = self.toast {
+            app.show_toast(message);
+        }
+        app.show_help = self.show_help;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_json() {
+        let fixture = ShellFixture::from_json(
+            r##"{
+                "messages": [{"role": "user", "content": "Add a login page",
+                              "timestamp": "2026-01-01T12:00:00Z"}],
+                "draft": "# Login page",
+                "models": [{"name": "claude", "state": "Ready", "version": "1.0.0",
+                            "message": null}],
+                "input": "/run",
+                "ascii": true
+            }"##,
+        )
+        .unwrap();
+        assert_eq!(fixture.messages.len(), 1);
+        assert_eq!(fixture.models[0].name, "claude");
+        assert!(fixture.ascii);
+
+        let empty = ShellFixture::from_json("{}").unwrap();
+        assert!(empty.thread.is_none() && empty.input.is_empty());
+
+        let err = ShellFixture::from_json(r#"{"colour": "red"}"#).unwrap_err();
+        assert!(err.to_string().contains("unknown field `colour`"));
+    }
+
+    #[test]
+    fn test_apply() {
+        let fixture = ShellFixture::from_json(
+            r##"{
+                "messages": [{"role": "user", "content": "Add a login page",
+                              "timestamp": "2026-01-01T12:00:00Z"}],
+                "draft": "# Login page",
+                "input": "/run",
+                "toast": "Saved",
+                "theme": "latte"
+            }"##,
+        )
+        .unwrap();
+        let mut app = ShellApp::new();
+        fixture.apply(&mut app).unwrap();
+
+        assert_eq!(app.timeline.events().len(), 1);
+        assert_eq!(
+            app.chat_thread.as_ref().map(|t| t.draft.as_str()),
+            Some("# Login page")
+        );
+        assert_eq!(app.input.content(), "/run");
+        assert_eq!(
+            app.toast.as_ref().map(|t| t.message.as_str()),
+            Some("Saved")
+        );
+        assert!(app.models.is_empty());
+        assert!(!app.is_ascii_mode());
+
+        let fixture = ShellFixture::from_json(r#"{"theme": "neon"}"#).unwrap();
+        let err = fixture.apply(&mut app).unwrap_err();
+        assert!(err.to_string().contains("Unknown theme: neon"));
+    }
+}
@@ -293,9 +293,9 @@ async fn run_headless_loop(
                 app.mark_probe_started(&name);
 
                 let name_clone = name.clone();
-                let handle = tokio::task::spawn_blocking(move || {
+                let handle = tokio::spawn(async move {
                     let timeout = std::time::Duration::from_secs(10);
-                    let result = ralf_engine::probe_model(&name_clone, timeout);
+                    let result = ralf_engine::probe_model_async(&name_clone, timeout).await;
                     (name_clone, result)
                 });
                 probe_handles.push(handle);
@@ -339,7 +339,7 @@ async fn run_headless_loop(
 }
 
 /// Convert a terminal buffer to a string representation.
-fn buffer_to_string(buffer: &Buffer) -> String {
+pub(crate) fn buffer_to_string(buffer: &Buffer) -> String {
     let area = buffer.area;
     let mut result = String::new();
 
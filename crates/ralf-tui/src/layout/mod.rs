@@ -9,4 +9,4 @@ mod screen_modes;
 mod shell;
 
 pub use screen_modes::{FocusedPane, ScreenMode};
-pub use shell::{render_shell, MIN_HEIGHT, MIN_WIDTH};
+pub use shell::{render_shell, split_widths, MIN_HEIGHT, MIN_WIDTH};
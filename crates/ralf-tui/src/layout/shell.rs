@@ -2,8 +2,8 @@
 //!
 //! Regions:
 //! 1. Status Bar (top, 1 line)
-//! 2. Timeline Pane (left, 40%)
-//! 3. Context Pane (right, 60%)
+//! 2. Timeline Pane (left, 40% by default)
+//! 3. Context Pane (right, the rest)
 //! 4. Footer Hints (bottom, 1 line)
 
 use ratatui::{
@@ -12,28 +12,56 @@ use ratatui::{
 };
 
 use ratatui::{
-    style::{Color, Style},
-    text::Span,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
     widgets::{Block, Borders, Clear, Paragraph},
 };
 
 use super::screen_modes::{FocusedPane, ScreenMode};
+use ralf_engine::thread::StuckDiagnosis;
+use ralf_engine::{PreflightResult, Usage};
+
 use crate::{
-    context::{ContextView, SpecPhase, SpecPreview},
+    context::{
+        ChecklistPanel, ChecklistState, ComparisonState, ContextView, CriteriaPanel, CriteriaState,
+        DraftHistoryState, LogViewState, PreflightPanel, RecoveryPanel, RunDashboard,
+        RunDashboardState, SpecPhase, SpecPreview,
+    },
     conversation::ConversationPane,
     models::ModelStatus,
     shell::{TimelinePaneBounds, Toast},
     theme::{BorderSet, Theme},
     thread_state::ThreadDisplay,
     timeline::TimelineState,
-    ui::widgets::TextInputState,
-    widgets::{FooterHints, InputBar, ModelsPanel, Pane, StatusBar, StatusBarContent},
+    ui::widgets::{LogViewer, TextInputState},
+    widgets::{
+        FooterHints, InputBar, ModelsPanel, Pane, StatusBar, StatusBarContent, ThreadPicker,
+        ThreadPickerState,
+    },
 };
 
 /// Minimum terminal width.
 pub const MIN_WIDTH: u16 = 40;
 /// Minimum terminal height.
 pub const MIN_HEIGHT: u16 = 12;
+/// Minimum timeline pane width in split mode (when the terminal fits both
+/// minimums).
+pub const MIN_TIMELINE_WIDTH: u16 = 30;
+/// Minimum context pane width in split mode.
+pub const MIN_CONTEXT_WIDTH: u16 = 30;
+
+/// Widths of the timeline and context panes for a split of `width` columns
+/// giving the timeline `split_ratio` percent, keeping each pane at its
+/// minimum width when the terminal is wide enough for both.
+pub fn split_widths(width: u16, split_ratio: u16) -> (u16, u16) {
+    // Fits in u16: split_ratio is at most 100
+    #[allow(clippy::cast_possible_truncation)]
+    let mut timeline = (u32::from(width) * u32::from(split_ratio.min(100)) / 100) as u16;
+    if width >= MIN_TIMELINE_WIDTH + MIN_CONTEXT_WIDTH {
+        timeline = timeline.clamp(MIN_TIMELINE_WIDTH, width - MIN_CONTEXT_WIDTH);
+    }
+    (timeline, width - timeline)
+}
 
 /// Render the main shell layout.
 #[allow(clippy::too_many_arguments, clippy::fn_params_excessive_bools)]
@@ -46,11 +74,20 @@ pub fn render_shell(
     models: &[ModelStatus],
     ascii_mode: bool,
     show_models_panel: bool,
-    timeline: &TimelineState,
+    timeline: &mut TimelineState,
     input: &TextInputState,
     timeline_bounds: &mut TimelinePaneBounds,
     toast: Option<&Toast>,
     thread: Option<&ThreadDisplay>,
+    usage: Option<&Usage>,
+    active_model: Option<&str>,
+    attachments: &[String],
+    thread_picker: Option<&ThreadPickerState>,
+    log_view: Option<&LogViewState>,
+    draft_history: Option<&DraftHistoryState>,
+    comparison: Option<&ComparisonState>,
+    criteria: Option<&CriteriaState>,
+    run_dashboard: Option<&RunDashboardState>,
     chat_loading: bool,
     loading_model: Option<&str>,
     spec_content: Option<&str>,
@@ -68,9 +105,10 @@ pub fn render_shell(
         return;
     }
 
-    // Calculate dynamic input bar height based on content
+    // Calculate dynamic input bar height based on wrapped content
+    // (borders and the "> " prefix take 4 columns)
     // Minimum 3 (1 line + 2 for border), maximum 10 (8 lines + 2 for border)
-    let input_lines = input.line_count();
+    let input_lines = input.visual_line_count(area.width.saturating_sub(4) as usize);
     #[allow(clippy::cast_possible_truncation)]
     let input_height = (input_lines as u16 + 2).clamp(3, 10); // Safe: clamped to 3-10
 
@@ -86,12 +124,17 @@ pub fn render_shell(
         .split(area);
 
     // Status bar with thread-driven content
-    let status_content = StatusBarContent::from_thread(thread);
+    let status_content = StatusBarContent::from_thread(thread)
+        .with_usage(usage)
+        .with_model(active_model);
     let status_bar = StatusBar::new(&status_content, models, theme).ascii_mode(ascii_mode);
     frame.render_widget(status_bar, chunks[0]);
 
     // Extract phase once for reuse
     let phase = thread.map(|t| t.phase_kind);
+    let stuck = thread.and_then(|t| t.stuck.as_ref());
+    let preflight = thread.and_then(|t| t.preflight.as_ref());
+    let checklist = thread.and_then(|t| t.checklist.as_ref());
 
     // Main pane area (timeline and/or canvas)
     render_main_area(
@@ -106,7 +149,17 @@ pub fn render_shell(
         show_models_panel,
         timeline,
         timeline_bounds,
+        attachments,
         phase,
+        stuck,
+        preflight,
+        checklist,
+        thread_picker,
+        log_view,
+        draft_history,
+        comparison,
+        criteria,
+        run_dashboard,
         spec_content,
         spec_scroll,
         split_ratio,
@@ -178,9 +231,19 @@ fn render_main_area(
     models: &[ModelStatus],
     ascii_mode: bool,
     show_models_panel: bool,
-    timeline: &TimelineState,
+    timeline: &mut TimelineState,
     timeline_bounds: &mut TimelinePaneBounds,
+    attachments: &[String],
     phase: Option<ralf_engine::thread::PhaseKind>,
+    stuck: Option<&StuckDiagnosis>,
+    preflight: Option<&PreflightResult>,
+    checklist: Option<&ChecklistState>,
+    thread_picker: Option<&ThreadPickerState>,
+    log_view: Option<&LogViewState>,
+    draft_history: Option<&DraftHistoryState>,
+    comparison: Option<&ComparisonState>,
+    criteria: Option<&CriteriaState>,
+    run_dashboard: Option<&RunDashboardState>,
     spec_content: Option<&str>,
     spec_scroll: u16,
     split_ratio: u16,
@@ -190,6 +253,10 @@ fn render_main_area(
     // Determine if canvas is showing spec (used to auto-collapse spec events in timeline)
     let canvas_shows_spec = show_canvas && spec_content.is_some();
 
+    // Only a split with both panes has a divider to drag
+    timeline_bounds.main_area = area;
+    timeline_bounds.divider_x = None;
+
     // If canvas is collapsed/hidden, show timeline full width regardless of screen mode
     if !show_canvas && screen_mode == ScreenMode::Split {
         render_timeline_pane(
@@ -199,6 +266,7 @@ fn render_main_area(
             theme,
             timeline,
             timeline_bounds,
+            attachments,
             false, // Canvas not visible
             tick,
         );
@@ -208,15 +276,15 @@ fn render_main_area(
     match screen_mode {
         ScreenMode::Split => {
             // Use configurable split ratio
-            let timeline_pct = split_ratio;
-            let canvas_pct = 100 - split_ratio;
+            let (timeline_width, canvas_width) = split_widths(area.width, split_ratio);
             let chunks = Layout::default()
                 .direction(Direction::Horizontal)
                 .constraints([
-                    Constraint::Percentage(timeline_pct),
-                    Constraint::Percentage(canvas_pct),
+                    Constraint::Length(timeline_width),
+                    Constraint::Length(canvas_width),
                 ])
                 .split(area);
+            timeline_bounds.divider_x = chunks[0].right().checked_sub(1);
 
             render_timeline_pane(
                 frame,
@@ -225,6 +293,7 @@ fn render_main_area(
                 theme,
                 timeline,
                 timeline_bounds,
+                attachments,
                 canvas_shows_spec,
                 tick,
             );
@@ -238,6 +307,15 @@ fn render_main_area(
                 ascii_mode,
                 show_models_panel,
                 phase,
+                stuck,
+                preflight,
+                checklist,
+                thread_picker,
+                log_view,
+                draft_history,
+                comparison,
+                criteria,
+                run_dashboard,
                 spec_content,
                 spec_scroll,
             );
@@ -251,6 +329,7 @@ fn render_main_area(
                 theme,
                 timeline,
                 timeline_bounds,
+                attachments,
                 false, // Canvas not visible in focus mode
                 tick,
             );
@@ -267,6 +346,15 @@ fn render_main_area(
                 ascii_mode,
                 show_models_panel,
                 phase,
+                stuck,
+                preflight,
+                checklist,
+                thread_picker,
+                log_view,
+                draft_history,
+                comparison,
+                criteria,
+                run_dashboard,
                 spec_content,
                 spec_scroll,
             );
@@ -281,8 +369,9 @@ fn render_timeline_pane(
     area: Rect,
     focused: bool,
     theme: &Theme,
-    timeline: &TimelineState,
+    timeline: &mut TimelineState,
     timeline_bounds: &mut TimelinePaneBounds,
+    attachments: &[String],
     canvas_shows_spec: bool,
     tick: usize,
 ) {
@@ -293,7 +382,13 @@ fn render_timeline_pane(
     timeline_bounds.inner_width = area.width.saturating_sub(2);
     timeline_bounds.inner_height = area.height.saturating_sub(2);
 
+    // Keep the latest event in view while following
+    if timeline.is_following() {
+        timeline.scroll_to_end(timeline_bounds.inner_height as usize);
+    }
+
     let widget = ConversationPane::from_timeline(timeline, theme)
+        .attachments(attachments)
         .focused(focused)
         .canvas_shows_spec(canvas_shows_spec)
         .tick(tick);
@@ -312,6 +407,15 @@ fn render_context_pane(
     ascii_mode: bool,
     show_models_panel: bool,
     phase: Option<ralf_engine::thread::PhaseKind>,
+    stuck: Option<&StuckDiagnosis>,
+    preflight: Option<&PreflightResult>,
+    checklist: Option<&ChecklistState>,
+    thread_picker: Option<&ThreadPickerState>,
+    log_view: Option<&LogViewState>,
+    draft_history: Option<&DraftHistoryState>,
+    comparison: Option<&ComparisonState>,
+    criteria: Option<&CriteriaState>,
+    run_dashboard: Option<&RunDashboardState>,
     spec_content: Option<&str>,
     spec_scroll: u16,
 ) {
@@ -320,8 +424,19 @@ fn render_context_pane(
     // Route to appropriate view based on phase
     let view = ContextView::from_phase(phase);
 
-    // NoThread view shows ModelsPanel when models panel is enabled
-    if matches!(view, ContextView::NoThread) && show_models_panel {
+    // Thread picker (from /threads) takes over the pane while open
+    if let Some(picker) = thread_picker {
+        let widget = ThreadPicker::new(picker, theme)
+            .ascii_mode(ascii_mode)
+            .focused(focused);
+        frame.render_widget(widget, area);
+    } else if let Some(state) = log_view {
+        render_log_pane(frame, area, focused, theme, borders, state);
+    } else if let Some(state) = draft_history {
+        render_draft_history_pane(frame, area, focused, theme, borders, state);
+    } else if let Some(state) = comparison {
+        render_comparison_pane(frame, area, focused, theme, borders, state);
+    } else if matches!(view, ContextView::NoThread) && show_models_panel {
         let models_panel = ModelsPanel::new(models, theme)
             .ascii_mode(ascii_mode)
             .focused(focused);
@@ -335,7 +450,26 @@ fn render_context_pane(
         };
 
         // Render spec preview inside a bordered pane
-        render_spec_pane(frame, area, focused, theme, borders, spec_content.unwrap_or(""), spec_phase, spec_scroll);
+        render_spec_pane(
+            frame,
+            area,
+            focused,
+            theme,
+            borders,
+            spec_content.unwrap_or(""),
+            spec_phase,
+            spec_scroll,
+        );
+    } else if let (ContextView::DecisionPrompt, Some(diagnosis)) = (view, stuck) {
+        render_recovery_pane(frame, area, focused, theme, borders, diagnosis);
+    } else if matches!(view, ContextView::PreflightResults) {
+        render_preflight_pane(frame, area, focused, theme, borders, ascii_mode, preflight);
+    } else if let (ContextView::RunOutput, Some(state)) = (view, run_dashboard) {
+        render_run_pane(frame, area, focused, theme, borders, ascii_mode, state);
+    } else if let (ContextView::Criteria, Some(state)) = (view, criteria) {
+        render_criteria_pane(frame, area, focused, theme, borders, ascii_mode, state);
+    } else if let (ContextView::DiffViewer, Some(state)) = (view, checklist) {
+        render_checklist_pane(frame, area, focused, theme, borders, ascii_mode, state);
     } else {
         // Render placeholder for all other views (real implementations in M5-B.4)
         render_context_placeholder(frame, view, area, focused, theme, borders);
@@ -360,21 +494,475 @@ fn render_spec_pane(
         (borders.normal(), theme.border)
     };
 
-    let block = Block::default()
+    let mut block = Block::default()
         .borders(Borders::ALL)
         .border_set(border_set)
         .border_style(Style::default().fg(border_color))
         .title(Span::styled(" Spec ", Style::default().fg(theme.text)));
-
     let inner = block.inner(area);
-    frame.render_widget(block, area);
 
+    let diagnostics = if content.trim().is_empty() {
+        Vec::new()
+    } else {
+        ralf_engine::lint(content)
+    };
     let preview = SpecPreview::new(content, phase, theme)
         .focused(focused)
-        .scroll(scroll);
+        .scroll(scroll)
+        .diagnostics(&diagnostics);
+    if let Some(label) = preview.position_label(inner) {
+        block = block.title_bottom(
+            Line::styled(format!(" {label} "), Style::default().fg(theme.muted)).right_aligned(),
+        );
+    }
+
+    frame.render_widget(block, area);
     frame.render_widget(preview, inner);
 }
 
+/// Render the stuck diagnosis and recovery suggestions inside a bordered pane.
+fn render_recovery_pane(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    focused: bool,
+    theme: &Theme,
+    borders: &BorderSet,
+    diagnosis: &StuckDiagnosis,
+) {
+    let (border_set, border_color) = if focused {
+        (borders.focused(), theme.border_focused)
+    } else {
+        (borders.normal(), theme.border)
+    };
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(border_set)
+        .border_style(Style::default().fg(border_color))
+        .title(Span::styled(" Recovery ", Style::default().fg(theme.text)));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    frame.render_widget(RecoveryPanel::new(diagnosis, theme), inner);
+}
+
+/// Render preflight results inside a bordered pane.
+fn render_preflight_pane(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    focused: bool,
+    theme: &Theme,
+    borders: &BorderSet,
+    ascii_mode: bool,
+    result: Option<&PreflightResult>,
+) {
+    let (border_set, border_color) = if focused {
+        (borders.focused(), theme.border_focused)
+    } else {
+        (borders.normal(), theme.border)
+    };
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(border_set)
+        .border_style(Style::default().fg(border_color))
+        .title(Span::styled(" Preflight ", Style::default().fg(theme.text)));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    frame.render_widget(
+        PreflightPanel::new(result, theme).ascii_mode(ascii_mode),
+        inner,
+    );
+}
+
+/// Render the run dashboard inside a bordered pane.
+fn render_run_pane(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    focused: bool,
+    theme: &Theme,
+    borders: &BorderSet,
+    ascii_mode: bool,
+    state: &RunDashboardState,
+) {
+    let (border_set, border_color) = if focused {
+        (borders.focused(), theme.border_focused)
+    } else {
+        (borders.normal(), theme.border)
+    };
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(border_set)
+        .border_style(Style::default().fg(border_color))
+        .title(Span::styled(" Run ", Style::default().fg(theme.text)));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    frame.render_widget(
+        RunDashboard::new(state, theme).ascii_mode(ascii_mode),
+        inner,
+    );
+}
+
+/// Render completion criteria progress inside a bordered pane.
+fn render_criteria_pane(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    focused: bool,
+    theme: &Theme,
+    borders: &BorderSet,
+    ascii_mode: bool,
+    state: &CriteriaState,
+) {
+    let (border_set, border_color) = if focused {
+        (borders.focused(), theme.border_focused)
+    } else {
+        (borders.normal(), theme.border)
+    };
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(border_set)
+        .border_style(Style::default().fg(border_color))
+        .title(Span::styled(" Criteria ", Style::default().fg(theme.text)));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    frame.render_widget(
+        CriteriaPanel::new(state, theme)
+            .ascii_mode(ascii_mode)
+            .focused(focused),
+        inner,
+    );
+}
+
+/// Render the review checklist inside a bordered pane.
+fn render_checklist_pane(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    focused: bool,
+    theme: &Theme,
+    borders: &BorderSet,
+    ascii_mode: bool,
+    state: &ChecklistState,
+) {
+    let (border_set, border_color) = if focused {
+        (borders.focused(), theme.border_focused)
+    } else {
+        (borders.normal(), theme.border)
+    };
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(border_set)
+        .border_style(Style::default().fg(border_color))
+        .title(Span::styled(" Review ", Style::default().fg(theme.text)));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    frame.render_widget(
+        ChecklistPanel::new(state, theme)
+            .ascii_mode(ascii_mode)
+            .focused(focused),
+        inner,
+    );
+}
+
+/// Render the log view (from /logs) inside a bordered pane, with a status
+/// line for the search prompt and key hints.
+fn render_log_pane(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    focused: bool,
+    theme: &Theme,
+    borders: &BorderSet,
+    state: &LogViewState,
+) {
+    let (border_set, border_color) = if focused {
+        (borders.focused(), theme.border_focused)
+    } else {
+        (borders.normal(), theme.border)
+    };
+
+    let title = state.current().map_or_else(
+        || " Logs ".to_string(),
+        |log| format!(" Logs · {} ", log.title()),
+    );
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(border_set)
+        .border_style(Style::default().fg(border_color))
+        .title(Span::styled(title, Style::default().fg(theme.text)));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(inner);
+
+    if state.lines.is_empty() {
+        let empty = Paragraph::new(Span::styled(
+            " (empty log)",
+            Style::default().fg(theme.muted),
+        ));
+        frame.render_widget(empty, chunks[0]);
+    } else {
+        let (start, end) = state.selection();
+        let lines: Vec<Line<'_>> = state
+            .lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                let style = if state.anchor.is_some() && (start..=end).contains(&i) {
+                    Style::default().bg(theme.overlay)
+                } else if focused && i == state.cursor {
+                    Style::default().bg(theme.surface)
+                } else {
+                    Style::default()
+                };
+                Line::from(line.as_str()).style(style)
+            })
+            .collect();
+        let viewer = LogViewer::new()
+            .lines(lines)
+            .scroll(state.scroll)
+            .auto_scroll(false)
+            .style(Style::default().fg(theme.text))
+            .highlight(
+                &state.query,
+                Style::default().fg(theme.base).bg(theme.warning),
+            );
+        frame.render_widget(viewer, chunks[0]);
+    }
+
+    let status = if state.searching {
+        Span::styled(format!("/{}", state.query), Style::default().fg(theme.text))
+    } else if !state.query.is_empty() {
+        let position = state
+            .match_position()
+            .map_or_else(String::new, |n| format!("{n}/"));
+        Span::styled(
+            format!(
+                "/{}  {position}{} matches  [n/N] next/prev",
+                state.query,
+                state.matches.len()
+            ),
+            Style::default().fg(theme.subtext),
+        )
+    } else {
+        Span::styled(
+            format!(
+                "{} · {}/{}  [/] search  [ ] iteration  h/l log  v select  y copy",
+                state.run_id,
+                state.selected + 1,
+                state.logs.len()
+            ),
+            Style::default().fg(theme.muted),
+        )
+    };
+    frame.render_widget(Paragraph::new(status), chunks[1]);
+}
+
+/// Render the replies being compared (from `/compare`) side by side, one
+/// column per model.
+fn render_comparison_pane(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    focused: bool,
+    theme: &Theme,
+    borders: &BorderSet,
+    state: &ComparisonState,
+) {
+    use crate::context::Reply;
+
+    let (border_set, border_color) = if focused {
+        (borders.focused(), theme.border_focused)
+    } else {
+        (borders.normal(), theme.border)
+    };
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(border_set)
+        .border_style(Style::default().fg(border_color))
+        .title(Span::styled(
+            " Compare replies ",
+            Style::default().fg(theme.text),
+        ));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(inner);
+
+    let count = u32::try_from(state.replies.len().max(1)).unwrap_or(u32::MAX);
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(state.replies.iter().map(|_| Constraint::Ratio(1, count)))
+        .split(chunks[0]);
+    let width = state.column_width(inner.width);
+
+    for (i, (compared, column)) in state.replies.iter().zip(columns.iter()).enumerate() {
+        let (marker, header_style) = if i == state.selected {
+            (
+                ">",
+                Style::default()
+                    .fg(theme.primary)
+                    .add_modifier(Modifier::BOLD),
+            )
+        } else {
+            (" ", Style::default().fg(theme.subtext))
+        };
+        let body_style = match compared.reply {
+            Reply::Pending => Style::default().fg(theme.muted),
+            Reply::Done(_) => Style::default().fg(theme.text),
+            Reply::Failed(_) => Style::default().fg(theme.error),
+        };
+        let mut lines = vec![Line::from(Span::styled(
+            format!("{marker}{} {}", i + 1, compared.model),
+            header_style,
+        ))];
+        lines.extend(
+            compared
+                .lines(width)
+                .into_iter()
+                .skip(state.scroll)
+                .map(|line| Line::from(Span::styled(line, body_style))),
+        );
+        // Leave a one-column gap before the next reply
+        let text_area = Rect {
+            width: column.width.saturating_sub(1),
+            ..*column
+        };
+        frame.render_widget(Paragraph::new(lines), text_area);
+    }
+
+    let hint = if state.is_complete() {
+        "1-3 pick  h/l select  j/k scroll  Enter pick  Esc dismiss"
+    } else {
+        "Waiting for replies...  h/l select  j/k scroll  Esc dismiss"
+    };
+    frame.render_widget(
+        Paragraph::new(Span::styled(hint, Style::default().fg(theme.muted))),
+        chunks[1],
+    );
+}
+
+/// Render the draft history (from `/history`): the snapshot list above a
+/// diff from the selected snapshot to the current draft.
+fn render_draft_history_pane(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    focused: bool,
+    theme: &Theme,
+    borders: &BorderSet,
+    state: &DraftHistoryState,
+) {
+    use ralf_engine::DiffLine;
+
+    let (border_set, border_color) = if focused {
+        (borders.focused(), theme.border_focused)
+    } else {
+        (borders.normal(), theme.border)
+    };
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(border_set)
+        .border_style(Style::default().fg(border_color))
+        .title(Span::styled(
+            " Draft history ",
+            Style::default().fg(theme.text),
+        ));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    // Up to a third of the pane for the list, the rest for the diff
+    let list_height = u16::try_from(state.revisions.len())
+        .unwrap_or(u16::MAX)
+        .min(inner.height / 3)
+        .max(1);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(list_height),
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+    // Keep the selected snapshot in view
+    let list_skip = state
+        .selected
+        .saturating_sub(usize::from(list_height).saturating_sub(1));
+    let revisions: Vec<Line<'_>> = state
+        .revisions
+        .iter()
+        .enumerate()
+        .skip(list_skip)
+        .map(|(i, revision)| {
+            let saved_at: chrono::DateTime<chrono::Local> = revision.saved_at.into();
+            let text = format!(" {}", saved_at.format("%Y-%m-%d %H:%M:%S"));
+            if i == state.selected {
+                Line::from(Span::styled(
+                    text,
+                    Style::default().fg(theme.text).bg(theme.surface),
+                ))
+            } else {
+                Line::from(Span::styled(text, Style::default().fg(theme.subtext)))
+            }
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(revisions), chunks[0]);
+
+    let diff: Vec<Line<'_>> = if state.unchanged() {
+        vec![Line::from(Span::styled(
+            " (same as the current draft)",
+            Style::default().fg(theme.muted),
+        ))]
+    } else {
+        state
+            .diff
+            .iter()
+            .skip(state.scroll)
+            .map(|line| match line {
+                DiffLine::Same(text) => Line::from(Span::styled(
+                    format!("  {text}"),
+                    Style::default().fg(theme.muted),
+                )),
+                DiffLine::Removed(text) => Line::from(Span::styled(
+                    format!("- {text}"),
+                    Style::default().fg(theme.error),
+                )),
+                DiffLine::Added(text) => Line::from(Span::styled(
+                    format!("+ {text}"),
+                    Style::default().fg(theme.success),
+                )),
+            })
+            .collect()
+    };
+    frame.render_widget(Paragraph::new(diff), chunks[2]);
+
+    let status = Span::styled(
+        format!(
+            "{}/{}  j/k snapshot  PgUp/PgDn scroll  Enter restore  Esc close",
+            state.selected + 1,
+            state.revisions.len()
+        ),
+        Style::default().fg(theme.muted),
+    );
+    frame.render_widget(Paragraph::new(status), chunks[3]);
+}
+
 /// Render placeholder content for context views.
 fn render_context_placeholder(
     frame: &mut Frame<'_>,
@@ -416,4 +1004,14 @@ mod tests {
         assert_eq!(MIN_WIDTH, 40);
         assert_eq!(MIN_HEIGHT, 12);
     }
+
+    #[test]
+    fn test_split_widths() {
+        assert_eq!(split_widths(100, 40), (40, 60));
+        // Each pane keeps its minimum on a wide enough terminal
+        assert_eq!(split_widths(100, 20), (MIN_TIMELINE_WIDTH, 70));
+        assert_eq!(split_widths(100, 80), (70, MIN_CONTEXT_WIDTH));
+        // Narrower terminals split proportionally
+        assert_eq!(split_widths(50, 20), (10, 40));
+    }
 }
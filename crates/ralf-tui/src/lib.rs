@@ -15,13 +15,17 @@
 //! - [`shell`] - Main app and run function
 
 mod app;
+pub mod attach;
 pub mod commands;
 pub mod context;
 pub mod conversation;
 mod event;
+pub mod external;
+pub mod fixture;
 pub mod headless;
 pub mod layout;
 pub mod models;
+pub mod notify;
 mod screens;
 pub mod shell;
 #[cfg(test)]
@@ -42,11 +46,12 @@ pub use ralf_engine;
 // Re-export M5-A shell components
 pub use context::{CompletionKind, ContextView};
 pub use conversation::{input_placeholder, ConversationPane};
+pub use fixture::ShellFixture;
 pub use layout::{FocusedPane, ScreenMode};
 pub use models::{ModelState, ModelStatus, ModelsSummary};
-pub use shell::{run_shell, ShellApp, UiConfig};
+pub use shell::{render_once, run_shell, ShellApp, UiConfig};
 pub use text::{render_markdown, MarkdownStyles};
-pub use theme::{BorderSet, IconMode, IconSet, Theme};
+pub use theme::{Background, BorderSet, IconMode, IconSet, Theme};
 pub use thread_state::ThreadDisplay;
 pub use timeline::{
     EventKind, ReviewEvent, ReviewResult, RunEvent, SpecEvent, SystemEvent, SystemLevel,
@@ -160,6 +165,14 @@ pub fn run_shell_tui() -> Result<(), Box<dyn std::error::Error>> {
     enable_raw_mode()?;
     let _guard = TerminalGuard;
 
+    // Query the background before anything reads events, so the terminal's
+    // reply isn't taken for keystrokes
+    let background = if UiConfig::from_env().background.is_some() {
+        None
+    } else {
+        theme::detect_background(theme::BACKGROUND_QUERY_TIMEOUT)
+    };
+
     // Enable keyboard enhancement for proper Shift+Enter detection.
     // Only use DISAMBIGUATE_ESCAPE_CODES - REPORT_EVENT_TYPES causes double input
     // by reporting both press and release events.
@@ -175,7 +188,7 @@ pub fn run_shell_tui() -> Result<(), Box<dyn std::error::Error>> {
     let mut terminal = Terminal::new(backend)?;
 
     // Run the shell with keyboard enhancement info
-    shell::run_shell(&mut terminal, keyboard_enhanced)?;
+    shell::run_shell(&mut terminal, keyboard_enhanced, background)?;
 
     // Restore cursor before guard drops
     terminal.show_cursor()?;
@@ -183,6 +196,24 @@ pub fn run_shell_tui() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Render a single frame of the shell at `width` x `height` and return it
+/// as text, without touching the terminal.
+///
+/// The frame shows the fixture at `state`, or an empty shell without one.
+pub fn render_shell_once(
+    width: u16,
+    height: u16,
+    state: Option<&Path>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let fixture = match state {
+        Some(path) => ShellFixture::load(path)?,
+        None => ShellFixture::default(),
+    };
+    let mut app = ShellApp::new();
+    fixture.apply(&mut app)?;
+    Ok(render_once(&mut app, width, height)?)
+}
+
 #[allow(clippy::too_many_lines)]
 async fn run_loop(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
@@ -253,10 +284,9 @@ async fn run_loop(
                 app.mark_probe_started(&name);
 
                 let name_clone = name.clone();
-                // Use spawn_blocking since probe_model does blocking I/O
-                let handle = tokio::task::spawn_blocking(move || {
+                let handle = tokio::spawn(async move {
                     let timeout = std::time::Duration::from_secs(10);
-                    let result = ralf_engine::probe_model(&name_clone, timeout);
+                    let result = ralf_engine::probe_model_async(&name_clone, timeout).await;
                     (name_clone, result)
                 });
                 probe_handles.push(handle);
@@ -338,6 +368,7 @@ async fn run_loop(
 
 /// Handle key input for `SpecStudio` text input.
 /// Returns true if the key was handled (should not be processed as action).
+#[allow(clippy::too_many_lines)]
 fn handle_spec_studio_key(
     app: &mut App,
     key: crossterm::event::KeyEvent,
@@ -356,6 +387,34 @@ fn handle_spec_studio_key(
         return true;
     }
 
+    // Word movement and kills
+    let by_word = key
+        .modifiers
+        .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+    match key.code {
+        KeyCode::Left if by_word => {
+            app.input_state.move_word_left();
+            return true;
+        }
+        KeyCode::Right if by_word => {
+            app.input_state.move_word_right();
+            return true;
+        }
+        KeyCode::Char('w') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.input_state.delete_word_before();
+            return true;
+        }
+        KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.input_state.kill_line_before();
+            return true;
+        }
+        KeyCode::Char('k') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.input_state.kill_line_after();
+            return true;
+        }
+        _ => {}
+    }
+
     // Don't handle if control key is pressed (except for certain keys)
     if key.modifiers.contains(KeyModifiers::CONTROL) {
         return false; // Let action handler deal with Ctrl+C, Ctrl+F, etc.
@@ -408,16 +467,18 @@ fn handle_spec_studio_key(
             true
         }
         KeyCode::Home => {
-            app.input_state.move_home();
+            app.input_state.move_line_start();
             true
         }
         KeyCode::End => {
-            app.input_state.move_end();
+            app.input_state.move_line_end();
             true
         }
         KeyCode::Up => {
-            // History navigation when input is empty
-            if app.input_state.is_empty() {
+            // Previous line, or history navigation when input is empty
+            if app.input_state.move_up() {
+                true
+            } else if app.input_state.is_empty() {
                 app.input_state.history_prev();
                 true
             } else {
@@ -425,7 +486,9 @@ fn handle_spec_studio_key(
             }
         }
         KeyCode::Down => {
-            if app.input_state.is_empty() {
+            if app.input_state.move_down() {
+                true
+            } else if app.input_state.is_empty() {
                 app.input_state.history_next();
                 true
             } else {
@@ -612,7 +675,7 @@ mod snapshot_tests {
         let theme = theme::Theme::default();
         let borders = theme::BorderSet::new(theme::IconMode::Unicode);
         let models: Vec<models::ModelStatus> = vec![];
-        let timeline_state = timeline::TimelineState::new();
+        let mut timeline_state = timeline::TimelineState::new();
         let input_state = ui::widgets::TextInputState::new();
         let mut timeline_bounds = shell::TimelinePaneBounds::default();
 
@@ -630,11 +693,20 @@ mod snapshot_tests {
                     &models,
                     false, // ascii_mode
                     false, // show_models_panel
-                    &timeline_state,
+                    &mut timeline_state,
                     &input_state,
                     &mut timeline_bounds,
                     None,  // toast
                     None,  // thread (no thread loaded)
+                    None,  // usage
+                    None,  // active_model
+                    &[],   // attachments
+                    None,  // thread_picker
+                    None,  // log_view
+                    None,  // draft_history
+                    None,  // comparison
+                    None,  // criteria
+                    None,  // run_dashboard
                     false, // chat_loading
                     None,  // loading_model
                     None,  // spec_content
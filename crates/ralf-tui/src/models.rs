@@ -8,6 +8,7 @@ use std::path::Path;
 
 use ralf_engine::discovery::{ModelInfo, ProbeResult};
 use ralf_engine::runner::RunnerError;
+use ralf_engine::state::{current_timestamp, Cooldowns};
 use serde::{Deserialize, Serialize};
 
 /// Install URLs for each model CLI.
@@ -26,7 +27,7 @@ pub enum ModelState {
     Ready,
     /// Model rate-limited during probe (with optional reset time).
     RateLimited(Option<String>),
-    /// Model rate-limited, seconds remaining (forward-looking, not used this phase).
+    /// Model cooling down until this Unix timestamp.
     Cooldown(u64),
     /// Model not found, auth error, or probe failed.
     Unavailable,
@@ -79,6 +80,9 @@ pub struct ModelStatus {
     pub version: Option<String>,
     /// User-friendly status or error message.
     pub message: Option<String>,
+    /// Command to sign in, set when the probe found the model needs auth.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_command: Option<Vec<String>>,
 }
 
 impl ModelStatus {
@@ -89,18 +93,21 @@ impl ModelStatus {
             state: ModelState::Probing,
             version: None,
             message: Some("Checking...".to_string()),
+            auth_command: None,
         }
     }
 
     /// Create from engine discovery and probe results.
     pub fn from_engine(info: &ModelInfo, probe: Option<&ProbeResult>) -> Self {
         let (state, message) = Self::determine_state_and_message(info, probe);
+        let needs_auth = info.found && info.callable && probe.is_some_and(|p| p.needs_auth);
 
         Self {
             name: info.name.clone(),
             state,
             version: info.version.clone(),
             message,
+            auth_command: info.auth_command.clone().filter(|_| needs_auth),
         }
     }
 
@@ -140,7 +147,7 @@ impl ModelStatus {
                 )
             }
             Some(p) if p.needs_auth => {
-                let message = format!("Needs auth. Run: `{} auth login`", info.name);
+                let message = format!("Needs auth. Run: `{}`", info.auth_hint());
                 (ModelState::Unavailable, Some(message))
             }
             Some(p) => {
@@ -203,7 +210,7 @@ impl ModelStatus {
                 let msg = e.to_string();
                 if msg.contains("429") || msg.to_lowercase().contains("rate limit") {
                     // Default 15 min cooldown
-                    self.state = ModelState::Cooldown(900);
+                    self.state = ModelState::Cooldown(current_timestamp() + 900);
                     self.message = Some("Rate limited".into());
                 } else if msg.contains("401")
                     || msg.contains("403")
@@ -218,6 +225,57 @@ impl ModelStatus {
             }
         }
     }
+
+    /// Seconds left of this model's cooldown at `now` (a Unix timestamp).
+    pub fn cooldown_remaining(&self, now: u64) -> Option<u64> {
+        match self.state {
+            ModelState::Cooldown(until) if until > now => Some(until - now),
+            _ => None,
+        }
+    }
+
+    /// Sync the cooldown state with the engine's `cooldowns` at `now`.
+    ///
+    /// A ready or rate-limited model with an active cooldown starts counting
+    /// down; a cooldown that has run out (or was cleared) makes the model
+    /// ready again.
+    pub fn apply_cooldowns(&mut self, cooldowns: &Cooldowns, now: u64) {
+        let entry = cooldowns
+            .entries
+            .get(&self.name)
+            .filter(|entry| entry.cooldown_until > now);
+        match (entry, &self.state) {
+            (
+                Some(entry),
+                ModelState::Ready | ModelState::RateLimited(_) | ModelState::Cooldown(_),
+            ) => {
+                self.state = ModelState::Cooldown(entry.cooldown_until);
+                let countdown = format_countdown(entry.cooldown_until - now);
+                self.message = Some(match &entry.reset_hint {
+                    Some(hint) => format!("Cooldown {countdown} ({hint})"),
+                    None => format!("Cooldown {countdown}"),
+                });
+            }
+            // Cooldowns from a failed chat aren't in the engine's file
+            (None, ModelState::Cooldown(until)) if *until > now => {
+                self.message = Some(format!("Cooldown {}", format_countdown(until - now)));
+            }
+            (None, ModelState::Cooldown(_)) => {
+                self.state = ModelState::Ready;
+                self.message = Some("Ready".into());
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Time left as "mm:ss", or "h:mm:ss" from an hour up.
+pub fn format_countdown(secs: u64) -> String {
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs % 3600 / 60, secs % 60)
+    } else {
+        format!("{:02}:{:02}", secs / 60, secs % 60)
+    }
 }
 
 /// Summary of model statuses for status bar display.
@@ -266,7 +324,7 @@ pub fn save_status_cache(models: &[ModelStatus], ralf_dir: &Path) -> io::Result<
     let path = ralf_dir.join("models.json");
     let json = serde_json::to_string_pretty(models)
         .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
-    fs::write(path, json)
+    ralf_engine::state::atomic_write(&path, json.as_bytes())
 }
 
 /// Load model status cache from `.ralf/models.json`.
@@ -300,6 +358,7 @@ mod tests {
             } else {
                 vec![]
             },
+            auth_command: None,
         }
     }
 
@@ -366,6 +425,25 @@ mod tests {
         assert!(status.message.as_ref().unwrap().contains("auth login"));
     }
 
+    #[test]
+    fn test_model_status_auth_command() {
+        let mut info = mock_model_info("codex", true, true);
+        info.auth_command = Some(vec!["codex".into(), "login".into()]);
+
+        let probe = mock_probe_result("codex", false, true);
+        let status = ModelStatus::from_engine(&info, Some(&probe));
+        assert_eq!(
+            status.message.as_deref(),
+            Some("Needs auth. Run: `codex login`")
+        );
+        assert_eq!(status.auth_command, info.auth_command);
+
+        // Only models that need auth offer the command
+        let probe = mock_probe_result("codex", true, false);
+        let status = ModelStatus::from_engine(&info, Some(&probe));
+        assert!(status.auth_command.is_none());
+    }
+
     #[test]
     fn test_model_status_probing() {
         let status = ModelStatus::probing("claude");
@@ -435,10 +513,48 @@ mod tests {
         ));
         status.update_from_result(Err(&err));
 
-        assert!(matches!(status.state, ModelState::Cooldown(900)));
+        let remaining = status.cooldown_remaining(current_timestamp());
+        assert!(remaining.is_some_and(|secs| secs > 890 && secs <= 900));
         assert_eq!(status.message, Some("Rate limited".to_string()));
     }
 
+    #[test]
+    fn test_apply_cooldowns() {
+        let now = 1_000_000;
+        let mut cooldowns = Cooldowns::default();
+        cooldowns.set_cooldown_until(
+            "claude",
+            now + 125,
+            "rate limited",
+            Some("resets at 3:00 PM".into()),
+        );
+        cooldowns.set_cooldown_until("gemini", now + 3725, "rate limited", None);
+
+        let mut claude = ModelStatus::probing("claude");
+        claude.state = ModelState::Ready;
+        claude.apply_cooldowns(&cooldowns, now);
+        assert_eq!(claude.state, ModelState::Cooldown(now + 125));
+        assert_eq!(
+            claude.message.as_deref(),
+            Some("Cooldown 02:05 (resets at 3:00 PM)")
+        );
+        assert_eq!(claude.cooldown_remaining(now + 5), Some(120));
+
+        // Unavailable models stay unavailable
+        let mut gemini = ModelStatus::probing("gemini");
+        gemini.state = ModelState::Unavailable;
+        gemini.apply_cooldowns(&cooldowns, now);
+        assert_eq!(gemini.state, ModelState::Unavailable);
+        gemini.state = ModelState::RateLimited(None);
+        gemini.apply_cooldowns(&cooldowns, now);
+        assert_eq!(gemini.message.as_deref(), Some("Cooldown 1:02:05"));
+
+        // Once the cooldown runs out the model is ready again
+        claude.apply_cooldowns(&cooldowns, now + 125);
+        assert_eq!(claude.state, ModelState::Ready);
+        assert_eq!(claude.message.as_deref(), Some("Ready"));
+    }
+
     #[test]
     fn test_status_cache_round_trip() {
         let models = vec![
@@ -447,12 +563,14 @@ mod tests {
                 state: ModelState::Ready,
                 version: Some("1.0.0".to_string()),
                 message: Some("Ready".to_string()),
+                auth_command: None,
             },
             ModelStatus {
                 name: "codex".to_string(),
                 state: ModelState::Cooldown(300),
                 version: None,
                 message: Some("Rate limited".to_string()),
+                auth_command: None,
             },
         ];
 
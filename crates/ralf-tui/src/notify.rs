@@ -0,0 +1,154 @@
+//! Desktop notifications for moments that need the user's attention.
+//!
+//! Notifications are sent with `notify-rust` when the crate is built with
+//! the `desktop-notifications` feature. Without it, [`notify`] is a no-op,
+//! so callers never need their own `cfg` checks.
+
+/// Events that can trigger a desktop notification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotifyEvent {
+    /// A run finished successfully.
+    RunCompleted,
+    /// A run failed.
+    RunFailed,
+    /// A thread is waiting for human review.
+    NeedsReview,
+    /// A running model asked the user a question.
+    NeedsInput,
+}
+
+impl NotifyEvent {
+    /// Notification title.
+    pub fn title(self) -> &'static str {
+        match self {
+            Self::RunCompleted => "ralf: run completed",
+            Self::RunFailed => "ralf: run failed",
+            Self::NeedsReview => "ralf: review needed",
+            Self::NeedsInput => "ralf: question from the model",
+        }
+    }
+}
+
+/// Which events send desktop notifications.
+///
+/// Read from `RALF_NOTIFY`: `off`, `all` (the default), or a comma-separated
+/// list of `complete`, `failure`, `review`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct NotificationConfig {
+    /// Notify when a run completes.
+    pub on_complete: bool,
+    /// Notify when a run fails.
+    pub on_failure: bool,
+    /// Notify when a thread needs review or the model asks a question.
+    pub on_review: bool,
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+impl NotificationConfig {
+    /// Notify on every event.
+    pub fn all() -> Self {
+        Self {
+            on_complete: true,
+            on_failure: true,
+            on_review: true,
+        }
+    }
+
+    /// Never notify.
+    pub fn off() -> Self {
+        Self {
+            on_complete: false,
+            on_failure: false,
+            on_review: false,
+        }
+    }
+
+    /// Create config from the `RALF_NOTIFY` environment variable.
+    pub fn from_env() -> Self {
+        std::env::var("RALF_NOTIFY").map_or_else(|_| Self::all(), |v| Self::parse(&v))
+    }
+
+    /// Parse a `RALF_NOTIFY` value. Unknown names are ignored.
+    pub fn parse(value: &str) -> Self {
+        let value = value.trim().to_ascii_lowercase();
+        match value.as_str() {
+            "" | "all" | "on" | "1" | "true" => return Self::all(),
+            "off" | "none" | "0" | "false" => return Self::off(),
+            _ => {}
+        }
+
+        let mut config = Self::off();
+        for name in value.split(',').map(str::trim) {
+            match name {
+                "complete" | "completed" => config.on_complete = true,
+                "failure" | "failed" => config.on_failure = true,
+                "review" => config.on_review = true,
+                _ => {}
+            }
+        }
+        config
+    }
+
+    /// Whether `event` should send a notification.
+    pub fn allows(&self, event: NotifyEvent) -> bool {
+        match event {
+            NotifyEvent::RunCompleted => self.on_complete,
+            NotifyEvent::RunFailed => self.on_failure,
+            NotifyEvent::NeedsReview | NotifyEvent::NeedsInput => self.on_review,
+        }
+    }
+}
+
+/// Send a desktop notification for `event` if the config allows it.
+///
+/// Delivery happens on a background thread so a slow notification daemon
+/// cannot stall the UI; failures are ignored.
+pub fn notify(config: &NotificationConfig, event: NotifyEvent, body: impl Into<String>) {
+    if config.allows(event) {
+        send_desktop(event.title(), body.into());
+    }
+}
+
+#[cfg(feature = "desktop-notifications")]
+fn send_desktop(summary: &'static str, body: String) {
+    std::thread::spawn(move || {
+        let _ = notify_rust::Notification::new()
+            .appname("ralf")
+            .summary(summary)
+            .body(&body)
+            .show();
+    });
+}
+
+#[cfg(not(feature = "desktop-notifications"))]
+fn send_desktop(_summary: &'static str, _body: String) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_notification_config() {
+        assert_eq!(NotificationConfig::parse("all"), NotificationConfig::all());
+        assert_eq!(
+            NotificationConfig::parse(" OFF "),
+            NotificationConfig::off()
+        );
+
+        let config = NotificationConfig::parse("failure, review");
+        assert!(!config.allows(NotifyEvent::RunCompleted));
+        assert!(config.allows(NotifyEvent::RunFailed));
+        assert!(config.allows(NotifyEvent::NeedsReview));
+
+        assert_eq!(
+            NotificationConfig::parse("bogus"),
+            NotificationConfig::off()
+        );
+    }
+}
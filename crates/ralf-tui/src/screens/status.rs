@@ -3,6 +3,7 @@
 //! Shows all panes simultaneously for real-time visibility into the run.
 
 use crate::app::{App, CriterionStatus, RunStatus};
+use crate::models::format_countdown;
 use crate::screens::Screen;
 use crate::ui::main_layout;
 use crate::ui::theme::Styles;
@@ -84,11 +85,17 @@ impl Screen for StatusScreen {
             RunStatus::Idle => "Ready",
         };
 
+        // Append run usage (tokens/cost) when the models report it
+        let status_text = match &app.run_state.usage {
+            Some(usage) => format!("{status_text} · {}", usage.summary()),
+            None => status_text.to_string(),
+        };
+
         let mut status_bar = StatusBar::new("Status").hints(hints);
         if let Some(notification) = &app.notification {
             status_bar = status_bar.right(notification);
         } else {
-            status_bar = status_bar.right(status_text);
+            status_bar = status_bar.right(&status_text);
         }
         status_bar.render(status_area, buf);
     }
@@ -172,15 +179,22 @@ fn render_header(app: &App, area: Rect, buf: &mut Buffer) {
         Span::styled(model_text, Style::default().fg(Color::Cyan)),
     ];
 
-    // Add cooldowns inline if any
-    if !app.run_state.cooldowns.is_empty() {
+    // Add cooldowns inline if any, counting down to when they end
+    let now = std::time::Instant::now();
+    let cooldown_text: Vec<String> = app
+        .run_state
+        .cooldowns
+        .iter()
+        .filter(|(_, until)| *until > now)
+        .map(|(m, until)| {
+            format!(
+                "{m} {}",
+                format_countdown(until.duration_since(now).as_secs())
+            )
+        })
+        .collect();
+    if !cooldown_text.is_empty() {
         line2_spans.push(Span::styled("  Cooldowns: ", Styles::dim()));
-        let cooldown_text: Vec<String> = app
-            .run_state
-            .cooldowns
-            .iter()
-            .map(|(m, s)| format!("{m}:{s}s"))
-            .collect();
         line2_spans.push(Span::styled(
             cooldown_text.join(", "),
             Style::default().fg(Color::Yellow),
@@ -369,10 +383,14 @@ fn render_criteria_pane(app: &App, area: Rect, buf: &mut Buffer) {
             CriterionStatus::Failed => ("☒", Color::Red, Color::Red),
         };
 
-        lines.push(Line::from(vec![
+        let mut spans = vec![
             Span::styled(format!("{symbol} "), Style::default().fg(symbol_color)),
-            Span::styled(criterion.as_str(), Style::default().fg(text_color)),
-        ]));
+            Span::styled(criterion.text.as_str(), Style::default().fg(text_color)),
+        ];
+        if !criterion.required {
+            spans.push(Span::styled(" (optional)", Styles::dim()));
+        }
+        lines.push(Line::from(spans));
     }
 
     let paragraph = Paragraph::new(lines)
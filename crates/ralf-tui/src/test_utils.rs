@@ -81,7 +81,11 @@ pub fn create_test_app_with_criteria(criteria: Vec<&str>, statuses: Vec<Criterio
     app.run_state.run_id = Some("test-run-123".to_string());
     app.run_state.current_iteration = 1;
     app.run_state.current_model = Some("claude".to_string());
-    app.run_state.criteria = criteria.into_iter().map(String::from).collect();
+    app.run_state.criteria = criteria
+        .into_iter()
+        .enumerate()
+        .map(|(i, line)| ralf_engine::Criterion::parse(i, line))
+        .collect();
     app.run_state.criteria_status = statuses;
     app.run_state.verifier_model = Some("gemini".to_string());
     app
@@ -0,0 +1,205 @@
+//! Terminal background detection.
+//!
+//! Picks Latte on light terminals and Mocha on dark ones when no theme is
+//! configured. The background comes from `RALF_BACKGROUND` if set, then
+//! `COLORFGBG` (set by rxvt, Konsole and others), then by asking the
+//! terminal for its background color with an OSC 11 query.
+
+use std::time::Duration;
+
+/// Environment variable overriding background detection (`light`, `dark`,
+/// or `auto`).
+pub const BACKGROUND_ENV: &str = "RALF_BACKGROUND";
+
+/// How long to wait for the terminal to answer the OSC 11 query.
+pub const BACKGROUND_QUERY_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Brightness of the terminal background.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Background {
+    /// Light background (Latte).
+    Light,
+    /// Dark background (Mocha).
+    Dark,
+}
+
+impl Background {
+    /// Parse a `RALF_BACKGROUND` value; `auto` (or anything else) means
+    /// detect.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "light" => Some(Self::Light),
+            "dark" => Some(Self::Dark),
+            _ => None,
+        }
+    }
+
+    /// The background forced by [`BACKGROUND_ENV`], if any.
+    pub fn from_env() -> Option<Self> {
+        std::env::var(BACKGROUND_ENV)
+            .ok()
+            .and_then(|value| Self::parse(&value))
+    }
+
+    /// Name of the built-in theme for this background.
+    pub fn theme_name(self) -> &'static str {
+        match self {
+            Self::Light => "latte",
+            Self::Dark => "mocha",
+        }
+    }
+
+    /// Parse `COLORFGBG` (`fg;bg` or `fg;default;bg`): background colors 7
+    /// and 9-15 are light, the rest of the 16 ANSI colors dark.
+    pub fn from_colorfgbg(value: &str) -> Option<Self> {
+        let bg: u8 = value.rsplit(';').next()?.trim().parse().ok()?;
+        match bg {
+            7 | 9..=15 => Some(Self::Light),
+            0..=6 | 8 => Some(Self::Dark),
+            _ => None,
+        }
+    }
+
+    /// Parse a terminal's reply to an OSC 11 query
+    /// (`ESC ] 11 ; rgb:RRRR/GGGG/BBBB` then BEL or `ESC \`), judging the
+    /// color by its perceived luminance.
+    pub fn from_osc11_reply(reply: &[u8]) -> Option<Self> {
+        let reply = String::from_utf8_lossy(reply);
+        let start = reply.find("]11;rgb:")? + "]11;rgb:".len();
+        let rgb = &reply[start..];
+        let end = rgb.find(['\x07', '\x1b']).unwrap_or(rgb.len());
+        let mut channels = rgb[..end].split('/').map(channel_fraction);
+        let (r, g, b) = (channels.next()??, channels.next()??, channels.next()??);
+        let luminance = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+        Some(if luminance > 0.5 {
+            Self::Light
+        } else {
+            Self::Dark
+        })
+    }
+}
+
+/// A hex color channel of 1-4 digits as a fraction of its maximum.
+fn channel_fraction(hex: &str) -> Option<f64> {
+    if hex.is_empty() || hex.len() > 4 {
+        return None;
+    }
+    let value = u32::from_str_radix(hex, 16).ok()?;
+    let max = (1u32 << (4 * hex.len())) - 1;
+    Some(f64::from(value) / f64::from(max))
+}
+
+/// Detect the terminal background from `COLORFGBG` or, failing that, an
+/// OSC 11 query.
+///
+/// The query needs raw mode and must run before anything reads terminal
+/// events, or the reply shows up as keystrokes.
+pub fn detect_background(timeout: Duration) -> Option<Background> {
+    if let Some(background) = std::env::var("COLORFGBG")
+        .ok()
+        .and_then(|value| Background::from_colorfgbg(&value))
+    {
+        return Some(background);
+    }
+    query_background(timeout)
+}
+
+/// Ask the terminal for its background color.
+///
+/// The OSC 11 query is followed by a primary device attributes query,
+/// which every terminal answers, so terminals without OSC 11 support don't
+/// cost the whole timeout.
+#[cfg(unix)]
+fn query_background(timeout: Duration) -> Option<Background> {
+    use nix::poll::{poll, PollFd, PollFlags};
+    use std::io::{IsTerminal, Write};
+    use std::os::fd::AsRawFd;
+    use std::time::Instant;
+
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout();
+    if !stdin.is_terminal() || !stdout.is_terminal() {
+        return None;
+    }
+    stdout.write_all(b"\x1b]11;?\x07\x1b[c").ok()?;
+    stdout.flush().ok()?;
+
+    let fd = stdin.as_raw_fd();
+    let deadline = Instant::now() + timeout;
+    let mut reply = Vec::new();
+    while !has_device_attributes(&reply) {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let millis = i32::try_from(remaining.as_millis()).unwrap_or(i32::MAX);
+        let mut fds = [PollFd::new(fd, PollFlags::POLLIN)];
+        if millis == 0 || poll(&mut fds, millis).ok()? == 0 {
+            break;
+        }
+        let mut buf = [0u8; 64];
+        let n = nix::unistd::read(fd, &mut buf).ok()?;
+        if n == 0 {
+            break;
+        }
+        reply.extend_from_slice(&buf[..n]);
+    }
+    Background::from_osc11_reply(&reply)
+}
+
+#[cfg(not(unix))]
+fn query_background(_timeout: Duration) -> Option<Background> {
+    None
+}
+
+/// Whether `reply` ends with a device attributes reply (`ESC [ ? ... c`).
+#[cfg(unix)]
+fn has_device_attributes(reply: &[u8]) -> bool {
+    reply.ends_with(b"c") && reply.windows(3).any(|window| window == b"\x1b[?")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Background::parse("Light"), Some(Background::Light));
+        assert_eq!(Background::parse(" dark "), Some(Background::Dark));
+        assert_eq!(Background::parse("auto"), None);
+    }
+
+    #[test]
+    fn test_from_colorfgbg() {
+        assert_eq!(Background::from_colorfgbg("0;15"), Some(Background::Light));
+        assert_eq!(
+            Background::from_colorfgbg("15;default;0"),
+            Some(Background::Dark)
+        );
+        assert_eq!(Background::from_colorfgbg("12;7"), Some(Background::Light));
+        assert_eq!(Background::from_colorfgbg("0;default"), None);
+        assert_eq!(Background::from_colorfgbg("0;42"), None);
+    }
+
+    #[test]
+    fn test_from_osc11_reply() {
+        let light = b"\x1b]11;rgb:efef/f1f1/f5f5\x07\x1b[?62;c";
+        assert_eq!(Background::from_osc11_reply(light), Some(Background::Light));
+        let dark = b"\x1b]11;rgb:1e1e/1e1e/2e2e\x1b\\";
+        assert_eq!(Background::from_osc11_reply(dark), Some(Background::Dark));
+        // Short channels scale to their own maximum
+        assert_eq!(
+            Background::from_osc11_reply(b"\x1b]11;rgb:f/f/f\x07"),
+            Some(Background::Light)
+        );
+        // Terminals without OSC 11 only answer the attributes query
+        assert_eq!(Background::from_osc11_reply(b"\x1b[?62;c"), None);
+        assert_eq!(
+            Background::from_osc11_reply(b"\x1b]11;rgb:zz/00/00\x07"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_theme_name() {
+        assert_eq!(Background::Light.theme_name(), "latte");
+        assert_eq!(Background::Dark.theme_name(), "mocha");
+    }
+}
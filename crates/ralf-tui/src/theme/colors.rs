@@ -4,6 +4,9 @@
 
 use ratatui::style::Color;
 
+/// Names of the built-in themes, as `/theme` lists them.
+pub const BUILTIN_THEMES: [&str; 3] = ["mocha", "latte", "high-contrast"];
+
 /// Theme color palette.
 #[derive(Debug, Clone)]
 pub struct Theme {
@@ -44,6 +47,17 @@ impl Default for Theme {
 }
 
 impl Theme {
+    /// Look up a theme by its config name (`mocha`, `latte`,
+    /// `high_contrast`).
+    pub fn by_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+            "mocha" => Some(Self::mocha()),
+            "latte" => Some(Self::latte()),
+            "high_contrast" => Some(Self::high_contrast()),
+            _ => None,
+        }
+    }
+
     /// Catppuccin Mocha theme (default dark theme).
     pub fn mocha() -> Self {
         Self {
@@ -169,6 +183,19 @@ mod tests {
         assert!(matches!(theme.base, Color::Black));
     }
 
+    #[test]
+    fn test_theme_by_name() {
+        assert!(matches!(
+            Theme::by_name("Latte").unwrap().base,
+            Color::Rgb(239, 241, 245)
+        ));
+        assert!(matches!(
+            Theme::by_name("high-contrast").unwrap().base,
+            Color::Black
+        ));
+        assert!(Theme::by_name("solarized").is_none());
+    }
+
     #[test]
     fn test_default_is_mocha() {
         let default = Theme::default();
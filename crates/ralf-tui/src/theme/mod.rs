@@ -1,14 +1,20 @@
 //! Theme components for the TUI.
 //!
 //! This module provides:
-//! - [`Theme`] - Color palette (Catppuccin Mocha/Latte/High Contrast)
+//! - [`Theme`] - Color palette (Catppuccin Mocha/Latte/High Contrast, or a
+//!   user theme from `~/.config/ralf/themes/`)
 //! - [`IconSet`] - Icons with Nerd/Unicode/ASCII modes
 //! - [`BorderSet`] - Border characters with Unicode/ASCII fallback
+//! - [`Background`] - Light/dark terminal detection for the default theme
 
+mod background;
 mod borders;
 mod colors;
 mod icons;
+mod user;
 
+pub use background::{detect_background, Background, BACKGROUND_ENV, BACKGROUND_QUERY_TIMEOUT};
 pub use borders::BorderSet;
-pub use colors::Theme;
+pub use colors::{Theme, BUILTIN_THEMES};
 pub use icons::{IconMode, IconSet};
+pub use user::user_themes_dir;
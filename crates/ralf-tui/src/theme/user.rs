@@ -0,0 +1,220 @@
+//! User-defined themes from `~/.config/ralf/themes/*.toml`.
+//!
+//! A theme file starts from a built-in palette and overrides any of its
+//! colors, so it only needs to list what changes:
+//!
+//! ```toml
+//! # ~/.config/ralf/themes/dusk.toml
+//! extends = "latte"
+//! primary = "#8839ef"
+//! border_focused = "#8839ef"
+//! claude = "208"
+//! ```
+//!
+//! Colors are `#rrggbb`, an ANSI color name (`red`, `lightblue`), or a
+//! 256-color index. The theme is named after its file.
+
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use ratatui::style::Color;
+
+use super::colors::{Theme, BUILTIN_THEMES};
+
+/// Directory for user themes: `$XDG_CONFIG_HOME/ralf/themes`, falling back
+/// to `~/.config/ralf/themes`.
+pub fn user_themes_dir() -> Option<PathBuf> {
+    ralf_engine::global_config_path().and_then(|path| Some(path.parent()?.join("themes")))
+}
+
+impl Theme {
+    /// Load a theme by name: a user theme in `user_dir`, or a built-in one.
+    /// User themes replace built-ins of the same name.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no such theme or its file is invalid.
+    pub fn load(name: &str, user_dir: Option<&Path>) -> Result<Self, String> {
+        let name = name.trim();
+        let file = user_dir
+            .filter(|_| !name.is_empty() && !name.contains(['/', '\\']))
+            .map(|dir| dir.join(format!("{name}.toml")));
+        if let Some(content) = file.as_ref().and_then(|f| std::fs::read_to_string(f).ok()) {
+            return Self::from_toml(&content).map_err(|e| format!("{name}.toml: {e}"));
+        }
+        Self::by_name(name).ok_or_else(|| {
+            format!(
+                "Unknown theme: {name} (available: {})",
+                Self::available(user_dir).join(", ")
+            )
+        })
+    }
+
+    /// Names of the built-in themes, then the user themes in `user_dir`.
+    pub fn available(user_dir: Option<&Path>) -> Vec<String> {
+        let mut names: Vec<String> = BUILTIN_THEMES.iter().map(ToString::to_string).collect();
+        let mut user: Vec<String> = user_dir
+            .and_then(|dir| std::fs::read_dir(dir).ok())
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.path();
+                if path.extension().and_then(|ext| ext.to_str()) != Some("toml") {
+                    return None;
+                }
+                Some(path.file_stem()?.to_str()?.to_string())
+            })
+            .filter(|name| !names.contains(name))
+            .collect();
+        user.sort();
+        names.extend(user);
+        names
+    }
+
+    /// Parse a theme file: `key = "value"` lines, `#` comments.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the first line that isn't a known color with
+    /// a valid value.
+    pub fn from_toml(content: &str) -> Result<Self, String> {
+        let mut entries = Vec::new();
+        for (index, line) in content.lines().enumerate() {
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            let parse = || {
+                let (key, value) = line
+                    .split_once('=')
+                    .ok_or_else(|| "expected `key = \"value\"`".to_string())?;
+                let value = value.trim();
+                let value = value
+                    .strip_prefix('"')
+                    .and_then(|v| v.strip_suffix('"'))
+                    .ok_or_else(|| format!("expected a quoted string, got {value}"))?;
+                Ok::<_, String>((key.trim(), value))
+            };
+            let (key, value) = parse().map_err(|e| format!("line {}: {e}", index + 1))?;
+            entries.push((index + 1, key, value));
+        }
+
+        let mut theme = match entries.iter().find(|(_, key, _)| *key == "extends") {
+            Some((line, _, base)) => Self::by_name(base)
+                .ok_or_else(|| format!("line {line}: unknown built-in theme '{base}'"))?,
+            None => Self::default(),
+        };
+        for (line, key, value) in entries.into_iter().filter(|(_, key, _)| *key != "extends") {
+            let color = theme
+                .color_mut(key)
+                .ok_or_else(|| format!("line {line}: unknown color '{key}'"))?;
+            *color = Color::from_str(value)
+                .map_err(|_| format!("line {line}: invalid color '{value}'"))?;
+        }
+        Ok(theme)
+    }
+
+    /// The color a theme file sets with `key`.
+    fn color_mut(&mut self, key: &str) -> Option<&mut Color> {
+        Some(match key {
+            "base" => &mut self.base,
+            "surface" => &mut self.surface,
+            "overlay" => &mut self.overlay,
+            "text" => &mut self.text,
+            "subtext" => &mut self.subtext,
+            "muted" => &mut self.muted,
+            "primary" => &mut self.primary,
+            "secondary" => &mut self.secondary,
+            "success" => &mut self.success,
+            "warning" => &mut self.warning,
+            "error" => &mut self.error,
+            "info" => &mut self.info,
+            "claude" => &mut self.claude,
+            "gemini" => &mut self.gemini,
+            "codex" => &mut self.codex,
+            "border" => &mut self.border,
+            "border_focused" => &mut self.border_focused,
+            _ => return None,
+        })
+    }
+}
+
+/// Drop a trailing `#` comment, leaving `#` inside quotes alone.
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_from_toml() {
+        let theme = Theme::from_toml(
+            "# Dusk\nextends = \"latte\"\nprimary = \"#8839ef\" # mauve\nclaude = \"208\"\nerror = \"red\"\n",
+        )
+        .unwrap();
+        assert_eq!(theme.primary, Color::Rgb(0x88, 0x39, 0xef));
+        assert_eq!(theme.claude, Color::Indexed(208));
+        assert_eq!(theme.error, Color::Red);
+        assert_eq!(theme.base, Theme::latte().base);
+
+        // Without `extends`, unset colors come from the default theme
+        let theme = Theme::from_toml("text = \"white\"").unwrap();
+        assert_eq!(theme.base, Theme::mocha().base);
+    }
+
+    #[test]
+    fn test_from_toml_errors() {
+        let err = |content| Theme::from_toml(content).unwrap_err();
+        assert_eq!(err("primary"), "line 1: expected `key = \"value\"`");
+        assert_eq!(
+            err("\nprimary = fff"),
+            "line 2: expected a quoted string, got fff"
+        );
+        assert_eq!(err("colour = \"red\""), "line 1: unknown color 'colour'");
+        assert_eq!(err("text = \"nope\""), "line 1: invalid color 'nope'");
+        assert_eq!(
+            err("extends = \"neon\""),
+            "line 1: unknown built-in theme 'neon'"
+        );
+    }
+
+    #[test]
+    fn test_load_and_available() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("dusk.toml"), "primary = \"#8839ef\"").unwrap();
+        fs::write(temp.path().join("broken.toml"), "primary = \"nope\"").unwrap();
+        fs::write(temp.path().join("notes.txt"), "not a theme").unwrap();
+        let dir = Some(temp.path());
+
+        let theme = Theme::load("dusk", dir).unwrap();
+        assert_eq!(theme.primary, Color::Rgb(0x88, 0x39, 0xef));
+        assert_eq!(Theme::load("latte", dir).unwrap().base, Theme::latte().base);
+        assert_eq!(
+            Theme::load("broken", dir).unwrap_err(),
+            "broken.toml: line 1: invalid color 'nope'"
+        );
+        assert_eq!(
+            Theme::load("neon", dir).unwrap_err(),
+            "Unknown theme: neon (available: mocha, latte, high-contrast, broken, dusk)"
+        );
+        assert!(Theme::load("../dusk", dir).is_err());
+
+        assert_eq!(
+            Theme::available(None),
+            vec!["mocha", "latte", "high-contrast"]
+        );
+    }
+}
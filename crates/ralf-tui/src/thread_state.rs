@@ -4,6 +4,9 @@
 //! display information from the engine's [`Thread`].
 
 use ralf_engine::thread::{PhaseKind, RunConfig, StuckDiagnosis, Thread, ThreadPhase};
+use ralf_engine::PreflightResult;
+
+use crate::context::ChecklistState;
 
 /// Thread state extracted for UI display.
 ///
@@ -25,6 +28,12 @@ pub struct ThreadDisplay {
     pub max_iterations: u32,
     /// Failure/status reason (if PreflightFailed/Abandoned/Stuck).
     pub failure_reason: Option<String>,
+    /// Stuck diagnosis and recovery suggestions (if Stuck).
+    pub stuck: Option<StuckDiagnosis>,
+    /// Results of the last `/preflight` (not persisted with the thread).
+    pub preflight: Option<PreflightResult>,
+    /// Review checklist (if generated for `PendingReview`).
+    pub checklist: Option<ChecklistState>,
 }
 
 impl ThreadDisplay {
@@ -57,15 +66,27 @@ impl ThreadDisplay {
             iteration,
             max_iterations,
             failure_reason,
+            stuck: if let ThreadPhase::Stuck { diagnosis } = &thread.phase {
+                Some(diagnosis.clone())
+            } else {
+                None
+            },
+            preflight: None,
+            checklist: thread.review_checklist.clone().map(ChecklistState::new),
         }
     }
 
     /// Format `StuckDiagnosis` for display.
     fn format_diagnosis(d: &StuckDiagnosis) -> String {
-        format!(
+        let summary = format!(
             "{}/{} criteria ({} iterations)",
             d.best_criteria_passed, d.total_criteria, d.iterations_attempted
-        )
+        );
+        if d.signals.is_empty() {
+            summary
+        } else {
+            format!("{summary}: {}", d.signals.join("; "))
+        }
     }
 }
 
@@ -109,6 +130,8 @@ mod tests {
                 iterations_attempted: 5,
                 models_tried: vec!["claude-sonnet".into()],
                 last_error: Some("Tests fail".into()),
+                signals: vec!["no new criteria passing".into()],
+                suggestions: vec![],
                 best_criteria_passed: 2,
                 total_criteria: 3,
             },
@@ -120,6 +143,8 @@ mod tests {
         let reason = display.failure_reason.unwrap();
         assert!(reason.contains("2/3"));
         assert!(reason.contains("5 iterations"));
+        assert!(reason.ends_with(": no new criteria passing"));
+        assert_eq!(display.stuck.unwrap().iterations_attempted, 5);
     }
 
     #[test]
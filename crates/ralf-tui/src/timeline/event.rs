@@ -25,6 +25,8 @@ pub struct TimelineEvent {
     pub kind: EventKind,
     /// Whether the event is collapsed (for multi-line content).
     pub collapsed: bool,
+    /// Run iteration the event belongs to (None outside iterations).
+    pub iteration: Option<IterationTag>,
 }
 
 impl TimelineEvent {
@@ -36,6 +38,7 @@ impl TimelineEvent {
             timestamp: Utc::now(),
             kind,
             collapsed,
+            iteration: None,
         }
     }
 
@@ -47,9 +50,17 @@ impl TimelineEvent {
             timestamp,
             kind,
             collapsed,
+            iteration: None,
         }
     }
 
+    /// Set the run iteration the event belongs to.
+    #[must_use]
+    pub fn in_iteration(mut self, iteration: Option<IterationTag>) -> Self {
+        self.iteration = iteration;
+        self
+    }
+
     /// Get the timestamp formatted for display (HH:MM in local time).
     pub fn time_str(&self) -> String {
         let local: DateTime<Local> = self.timestamp.into();
@@ -175,6 +186,15 @@ impl TimelineEvent {
     }
 }
 
+/// The run iteration an event belongs to, for grouping the timeline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IterationTag {
+    /// Iteration number (1-based).
+    pub number: u32,
+    /// Model running the iteration.
+    pub model: String,
+}
+
 /// Event type and content.
 #[derive(Debug, Clone)]
 pub enum EventKind {
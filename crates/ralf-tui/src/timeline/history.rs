@@ -0,0 +1,435 @@
+//! Rebuilding a thread's timeline from what the engine persisted.
+//!
+//! The spec conversation is saved to `.ralf/threads/<id>.jsonl` and every
+//! run event to `.ralf/runs/<run_id>/events.jsonl`. Loading both (merged by
+//! time) gives the same history after a restart that the shell showed
+//! live; live run events go through [`run_event_kind`] too, so the two
+//! never disagree.
+
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use ralf_engine::chat::{ChatMessage, Role, Thread};
+use ralf_engine::events::{events_path, read_events, EventRecord};
+use ralf_engine::runner::RunEvent as EngineEvent;
+use ralf_engine::safety::violation_summary;
+use ralf_engine::secrets::secrets_warning;
+use ralf_engine::sink::save_warning;
+use ralf_engine::stuck::no_op_warning;
+
+use super::event::{
+    EventKind, IterationTag, ReviewEvent, ReviewResult, RunEvent, SpecEvent, SystemEvent,
+    TimelineEvent,
+};
+
+/// Timeline entry for a chat message.
+pub fn chat_event_kind(message: &ChatMessage) -> EventKind {
+    let event = match (message.role, &message.model) {
+        (Role::User, _) => SpecEvent::user(&message.content),
+        (Role::Assistant, Some(model)) => SpecEvent::assistant(&message.content, model),
+        (Role::Assistant | Role::System, _) => SpecEvent::system(&message.content),
+    };
+    EventKind::Spec(event)
+}
+
+/// Timeline entry for a run event, or `None` for bookkeeping events (usage
+/// updates) that would only add noise.
+#[allow(clippy::too_many_lines)]
+#[allow(clippy::cast_possible_truncation)] // iterations fit in u32
+#[allow(clippy::cast_precision_loss)] // durations are display-only
+pub fn run_event_kind(event: &EngineEvent) -> Option<EventKind> {
+    let kind = match event {
+        EngineEvent::Started { run_id, branch, .. } => {
+            EventKind::System(SystemEvent::info(match branch {
+                Some(branch) => format!("Run {run_id} started on {branch}"),
+                None => format!("Run {run_id} started"),
+            }))
+        }
+        EngineEvent::IterationStarted { iteration, model } => EventKind::System(SystemEvent::info(
+            format!("Iteration {iteration} started ({model})"),
+        )),
+        EngineEvent::ModelCompleted {
+            iteration,
+            model,
+            duration_ms,
+            has_promise,
+            rate_limited,
+            output_preview,
+        } => {
+            let secs = *duration_ms as f64 / 1000.0;
+            let status = if *rate_limited {
+                format!("Rate limited after {secs:.1}s")
+            } else if *has_promise {
+                format!("Claimed completion in {secs:.1}s")
+            } else {
+                format!("Finished in {secs:.1}s")
+            };
+            let content = if output_preview.trim().is_empty() {
+                status
+            } else {
+                format!("{status}\n{}", output_preview.trim_end())
+            };
+            EventKind::Run(RunEvent::new(model, *iteration as u32, content))
+        }
+        EngineEvent::VerifierCompleted {
+            name,
+            passed,
+            skipped,
+            ..
+        } => {
+            let result = if *skipped {
+                ReviewResult::Skipped
+            } else if *passed {
+                ReviewResult::Passed
+            } else {
+                ReviewResult::Failed
+            };
+            EventKind::Review(ReviewEvent::new(format!("Verifier: {name}"), result))
+        }
+        EngineEvent::VerificationStarted {
+            model,
+            criteria_count,
+            ..
+        } => EventKind::System(SystemEvent::info(format!(
+            "Verifying {criteria_count} criteria with {model}"
+        ))),
+        EngineEvent::CriterionVerified {
+            index,
+            required,
+            passed,
+            reason,
+            cached,
+            ..
+        } => {
+            let result = if *passed {
+                ReviewResult::Passed
+            } else {
+                ReviewResult::Failed
+            };
+            let mut criterion = format!("Criterion {}", index + 1);
+            if !*required {
+                criterion.push_str(" (optional)");
+            }
+            if *cached {
+                criterion.push_str(" (cached)");
+            }
+            EventKind::Review(match reason {
+                Some(reason) => ReviewEvent::with_details(criterion, result, reason),
+                None => ReviewEvent::new(criterion, result),
+            })
+        }
+        EngineEvent::UsageUpdated { .. } => return None,
+        EngineEvent::CooldownStarted {
+            model,
+            duration_secs,
+        } => EventKind::System(SystemEvent::warning(format!(
+            "{model} cooling down for {duration_secs}s"
+        ))),
+        EngineEvent::IterationCompleted {
+            iteration,
+            all_verifiers_passed,
+        } => EventKind::System(if *all_verifiers_passed {
+            SystemEvent::info(format!("Iteration {iteration} complete"))
+        } else {
+            SystemEvent::warning(format!("Iteration {iteration} complete, verifiers failed"))
+        }),
+        EngineEvent::IterationCommitted {
+            iteration,
+            commit_sha,
+        } => {
+            let short = commit_sha.get(..8).unwrap_or(commit_sha);
+            EventKind::System(SystemEvent::info(format!(
+                "Iteration {iteration} committed ({short})"
+            )))
+        }
+        EngineEvent::CompletionReported { iteration, report } => {
+            EventKind::System(SystemEvent::info(match &report.summary {
+                Some(summary) => format!("Iteration {iteration} report: {summary}"),
+                None => format!("Iteration {iteration} sent a completion report"),
+            }))
+        }
+        EngineEvent::Completed { iteration, reason } => EventKind::System(SystemEvent::info(
+            format!("Run completed after iteration {iteration}: {reason}"),
+        )),
+        EngineEvent::Failed { iteration, error } => EventKind::System(SystemEvent::error(format!(
+            "Run failed at iteration {iteration}: {error}"
+        ))),
+        EngineEvent::Stuck {
+            iteration,
+            diagnosis,
+        } => EventKind::System(SystemEvent::warning(format!(
+            "Stuck after iteration {iteration}: {}",
+            diagnosis.signals.join("; ")
+        ))),
+        EngineEvent::Cancelled { iteration } => EventKind::System(SystemEvent::warning(format!(
+            "Run cancelled at iteration {iteration}"
+        ))),
+        EngineEvent::Paused { iteration } => EventKind::System(SystemEvent::info(format!(
+            "Run paused after iteration {iteration}"
+        ))),
+        EngineEvent::Resumed { iteration } => EventKind::System(SystemEvent::info(format!(
+            "Run resumed at iteration {iteration}"
+        ))),
+        EngineEvent::QuestionAsked {
+            iteration,
+            question,
+        } => EventKind::System(SystemEvent::warning(format!(
+            "Iteration {iteration} asks: {question} (reply with /answer)"
+        ))),
+        EngineEvent::QuestionAnswered { iteration, answer } => EventKind::System(
+            SystemEvent::info(format!("Answered iteration {iteration}: {answer}")),
+        ),
+        EngineEvent::ReviewRequired {
+            iteration, reasons, ..
+        } => EventKind::System(SystemEvent::warning(format!(
+            "Iteration {iteration} needs review: {}",
+            reasons.join("; ")
+        ))),
+        EngineEvent::ReviewResolved {
+            iteration,
+            approved: true,
+        } => EventKind::System(SystemEvent::info(format!(
+            "Iteration {iteration} changes approved"
+        ))),
+        EngineEvent::ReviewResolved {
+            iteration,
+            approved: false,
+        } => EventKind::System(SystemEvent::warning(format!(
+            "Iteration {iteration} changes rejected"
+        ))),
+        EngineEvent::SafetyViolation {
+            iteration,
+            reverted,
+            forbidden_commands,
+        } => EventKind::System(SystemEvent::warning(format!(
+            "Iteration {iteration} {}",
+            violation_summary(reverted, forbidden_commands)
+        ))),
+        EngineEvent::SecretsDetected {
+            iteration,
+            findings,
+        } => EventKind::System(SystemEvent::warning(secrets_warning(*iteration, findings))),
+        EngineEvent::NoOp {
+            iteration,
+            model,
+            consecutive,
+        } => EventKind::System(SystemEvent::warning(no_op_warning(
+            *iteration,
+            model,
+            *consecutive,
+        ))),
+        EngineEvent::SaveFailed { what, error } => {
+            EventKind::System(SystemEvent::warning(save_warning(what, error)))
+        }
+        EngineEvent::Status { message } => EventKind::System(SystemEvent::info(message)),
+    };
+    Some(kind)
+}
+
+/// Assigns run events to iterations as they arrive, in order: everything
+/// from an iteration's start to its completion belongs to it, as does its
+/// commit.
+#[derive(Debug, Clone, Default)]
+pub struct IterationTracker {
+    /// Iteration in progress.
+    current: Option<IterationTag>,
+    /// Iteration that completed last.
+    last: Option<IterationTag>,
+}
+
+impl IterationTracker {
+    /// The iteration `event` belongs to.
+    #[allow(clippy::cast_possible_truncation)] // iterations fit in u32
+    pub fn track(&mut self, event: &EngineEvent) -> Option<IterationTag> {
+        match event {
+            EngineEvent::IterationStarted { iteration, model } => {
+                self.current = Some(IterationTag {
+                    number: *iteration as u32,
+                    model: model.clone(),
+                });
+                self.current.clone()
+            }
+            EngineEvent::IterationCompleted { .. } => {
+                self.last = self.current.take();
+                self.last.clone()
+            }
+            EngineEvent::IterationCommitted { iteration, .. } => self
+                .last
+                .clone()
+                .filter(|tag| tag.number as usize == *iteration),
+            // Run-level events stand outside any iteration
+            EngineEvent::Started { .. }
+            | EngineEvent::Completed { .. }
+            | EngineEvent::Failed { .. }
+            | EngineEvent::Stuck { .. }
+            | EngineEvent::Cancelled { .. }
+            | EngineEvent::Paused { .. }
+            | EngineEvent::Resumed { .. } => {
+                self.current = None;
+                None
+            }
+            _ => self.current.clone(),
+        }
+    }
+}
+
+/// Load a thread's history: its spec conversation and, if it has one, the
+/// events of its current run, in time order.
+///
+/// Missing or unreadable files contribute nothing, so a thread that has
+/// never chatted or run simply has a shorter history.
+pub fn load_history(ralf_dir: &Path, thread_id: &str, run_id: Option<&str>) -> Vec<TimelineEvent> {
+    let messages = Thread::load(ralf_dir, thread_id)
+        .map(|thread| thread.messages)
+        .unwrap_or_default();
+    conversation_history(ralf_dir, &messages, run_id)
+}
+
+/// Like [`load_history`], for a conversation already in memory (such as a
+/// thread branch just switched to).
+pub fn conversation_history(
+    ralf_dir: &Path,
+    messages: &[ChatMessage],
+    run_id: Option<&str>,
+) -> Vec<TimelineEvent> {
+    let records = run_id
+        .and_then(|run_id| read_events(&events_path(ralf_dir, run_id)).ok())
+        .unwrap_or_default();
+    merge_history(messages, &records)
+}
+
+/// Merge chat messages and run event records into timeline events, in time
+/// order.
+pub fn merge_history(messages: &[ChatMessage], records: &[EventRecord]) -> Vec<TimelineEvent> {
+    let mut entries: Vec<(DateTime<Utc>, EventKind, Option<IterationTag>)> = messages
+        .iter()
+        .map(|message| (message.timestamp, chat_event_kind(message), None))
+        .collect();
+    let mut tracker = IterationTracker::default();
+    entries.extend(records.iter().filter_map(|record| {
+        let iteration = tracker.track(&record.event);
+        run_event_kind(&record.event).map(|kind| (record.timestamp, kind, iteration))
+    }));
+
+    // Stable, so events logged in the same instant keep their order
+    entries.sort_by_key(|(timestamp, ..)| *timestamp);
+    entries
+        .into_iter()
+        .zip(1..)
+        .map(|((timestamp, kind, iteration), id)| {
+            TimelineEvent::with_timestamp(id, timestamp, kind).in_iteration(iteration)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ralf_engine::events::EventLog;
+
+    #[test]
+    fn test_load_history_merges_chat_and_run() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let ralf_dir = temp.path();
+
+        let mut thread = Thread::with_id("thread-1");
+        thread.add_message(ChatMessage::user("Add a login page"));
+        thread.add_message(ChatMessage::assistant("Here's a spec", "claude"));
+        thread.save(ralf_dir).unwrap();
+
+        let run_dir = ralf_dir.join("runs").join("run-1");
+        std::fs::create_dir_all(&run_dir).unwrap();
+        let mut log = EventLog::open(&run_dir).unwrap();
+        for event in [
+            EngineEvent::IterationStarted {
+                iteration: 1,
+                model: "claude".into(),
+            },
+            EngineEvent::UsageUpdated {
+                iteration: 1,
+                model: "claude".into(),
+                usage: ralf_engine::Usage::default(),
+                run_total: ralf_engine::Usage::default(),
+            },
+            EngineEvent::CriterionVerified {
+                index: 0,
+                id: "c1".into(),
+                required: true,
+                passed: false,
+                reason: Some("tests fail".into()),
+                cached: false,
+            },
+        ] {
+            log.append(&event).unwrap();
+        }
+
+        let history = load_history(ralf_dir, "thread-1", Some("run-1"));
+        let summaries: Vec<String> = history.iter().map(TimelineEvent::summary).collect();
+        assert_eq!(
+            summaries,
+            vec![
+                "Add a login page",
+                "Here's a spec",
+                "Iteration 1 started (claude)",
+                "\u{2717} Criterion 1",
+            ]
+        );
+        assert_eq!(history[1].attribution(), "claude");
+        assert_eq!(history.last().unwrap().id, 4);
+
+        assert!(load_history(ralf_dir, "missing", Some("missing")).is_empty());
+    }
+
+    #[test]
+    fn test_iteration_tracker() {
+        let mut tracker = IterationTracker::default();
+        let started = |iteration: usize| EngineEvent::IterationStarted {
+            iteration,
+            model: "claude".into(),
+        };
+        let status = EngineEvent::Status {
+            message: "working".into(),
+        };
+
+        assert_eq!(tracker.track(&status), None);
+        assert_eq!(tracker.track(&started(1)).unwrap().number, 1);
+        assert_eq!(tracker.track(&status).unwrap().model, "claude");
+        let completed = EngineEvent::IterationCompleted {
+            iteration: 1,
+            all_verifiers_passed: true,
+        };
+        assert_eq!(tracker.track(&completed).unwrap().number, 1);
+        let committed = EngineEvent::IterationCommitted {
+            iteration: 1,
+            commit_sha: "abc".into(),
+        };
+        assert_eq!(tracker.track(&committed).unwrap().number, 1);
+        assert_eq!(tracker.track(&status), None);
+
+        tracker.track(&started(2));
+        assert_eq!(
+            tracker.track(&EngineEvent::Cancelled { iteration: 2 }),
+            None
+        );
+        assert_eq!(tracker.track(&status), None);
+    }
+
+    #[test]
+    fn test_run_event_kind() {
+        let kind = run_event_kind(&EngineEvent::ModelCompleted {
+            iteration: 2,
+            model: "codex".into(),
+            duration_ms: 1500,
+            has_promise: true,
+            rate_limited: false,
+            output_preview: "done\n".into(),
+        })
+        .unwrap();
+        let event = TimelineEvent::new(1, kind);
+        assert_eq!(event.attribution(), "codex #2");
+        assert_eq!(
+            event.content_lines(),
+            vec!["Claimed completion in 1.5s", "done"]
+        );
+    }
+}
@@ -4,14 +4,21 @@
 //! - [`TimelineEvent`] - Event data model with 4 types (Spec, Run, Review, System)
 //! - [`TimelineState`] - State management for events, selection, and scrolling
 //! - [`TimelineWidget`] - Widget for rendering the timeline pane
+//! - [`load_history`] - Rebuild a thread's timeline from persisted history
 
 mod event;
+mod history;
 mod state;
 mod widget;
 
 pub use event::{
-    EventKind, ReviewEvent, ReviewResult, RunEvent, SpecEvent, SystemEvent, SystemLevel,
-    TimelineEvent, COLLAPSED_HEIGHT, MAX_EXPANDED_LINES,
+    EventKind, IterationTag, ReviewEvent, ReviewResult, RunEvent, SpecEvent, SystemEvent,
+    SystemLevel, TimelineEvent, COLLAPSED_HEIGHT, MAX_EXPANDED_LINES,
+};
+pub use history::{
+    chat_event_kind, conversation_history, load_history, merge_history, run_event_kind,
+    IterationTracker,
 };
 pub use state::{TimelineState, SCROLL_SPEED};
+pub(crate) use widget::format_elapsed;
 pub use widget::TimelineWidget;
@@ -2,7 +2,9 @@
 //!
 //! Handles event storage, selection, scrolling, and follow mode.
 
-use super::event::{EventKind, TimelineEvent, COLLAPSED_HEIGHT};
+use chrono::TimeDelta;
+
+use super::event::{EventKind, IterationTag, SpecEvent, TimelineEvent, COLLAPSED_HEIGHT};
 
 /// Events scrolled per mouse wheel tick.
 pub const SCROLL_SPEED: usize = 3;
@@ -22,6 +24,8 @@ pub struct TimelineState {
     next_id: u64,
     /// Model name we're waiting for a response from (shows animated indicator).
     pending_response: Option<String>,
+    /// Index of the assistant message still being written.
+    streaming: Option<usize>,
 }
 
 impl TimelineState {
@@ -34,6 +38,7 @@ impl TimelineState {
             follow: true, // Start with follow enabled
             next_id: 1,
             pending_response: None,
+            streaming: None,
         }
     }
 
@@ -77,6 +82,41 @@ impl TimelineState {
         self.pending_response = None;
     }
 
+    /// Whether an assistant message is still being written.
+    pub fn is_streaming(&self) -> bool {
+        self.streaming.is_some()
+    }
+
+    /// Append `text` to the assistant message being written, starting one
+    /// from `model` if there isn't one.
+    pub fn stream_response(&mut self, model: &str, text: &str) {
+        let event = self.streaming.and_then(|i| self.events.get_mut(i));
+        if let Some(TimelineEvent {
+            kind: EventKind::Spec(spec),
+            ..
+        }) = event
+        {
+            spec.content.push_str(text);
+            return;
+        }
+        self.push(EventKind::Spec(SpecEvent::assistant(text, model)));
+        self.streaming = Some(self.events.len() - 1);
+    }
+
+    /// Stop streaming, replacing the message's text with `content` if
+    /// given. Returns whether a message was being streamed.
+    pub fn finish_stream(&mut self, content: Option<&str>) -> bool {
+        let Some(index) = self.streaming.take() else {
+            return false;
+        };
+        if let (Some(content), Some(EventKind::Spec(spec))) =
+            (content, self.events.get_mut(index).map(|e| &mut e.kind))
+        {
+            spec.content = content.to_string();
+        }
+        true
+    }
+
     /// Get the number of events.
     pub fn len(&self) -> usize {
         self.events.len()
@@ -86,7 +126,12 @@ impl TimelineState {
     ///
     /// If `follow` is true, auto-scrolls to show the new event.
     pub fn push(&mut self, kind: EventKind) {
-        let event = TimelineEvent::new(self.next_id, kind);
+        self.push_in_iteration(kind, None);
+    }
+
+    /// Add a new event belonging to a run iteration.
+    pub fn push_in_iteration(&mut self, kind: EventKind, iteration: Option<IterationTag>) {
+        let event = TimelineEvent::new(self.next_id, kind).in_iteration(iteration);
         self.next_id += 1;
         self.events.push(event);
 
@@ -106,11 +151,23 @@ impl TimelineState {
         }
     }
 
+    /// Replace all events with `events` (e.g., a thread's loaded history),
+    /// selecting the latest.
+    pub fn restore(&mut self, events: Vec<TimelineEvent>) {
+        self.clear();
+        for event in events {
+            self.push_event(event);
+        }
+        self.follow = true;
+        self.selected = self.events.len().checked_sub(1);
+    }
+
     /// Clear all events from the timeline.
     pub fn clear(&mut self) {
         self.events.clear();
         self.selected = None;
         self.scroll_offset = 0;
+        self.streaming = None;
         // Keep follow mode as-is
         // next_id not reset to avoid collisions if events are restored
     }
@@ -165,6 +222,30 @@ impl TimelineState {
         self.selected = Some(self.events.len() - 1);
     }
 
+    /// Collapse or expand every collapsible event.
+    pub fn set_all_collapsed(&mut self, collapsed: bool) {
+        for event in &mut self.events {
+            if event.is_collapsible() {
+                event.collapsed = collapsed;
+            }
+        }
+    }
+
+    /// Whether any collapsible event is expanded.
+    pub fn any_expanded(&self) -> bool {
+        self.events
+            .iter()
+            .any(|event| event.is_collapsible() && !event.collapsed)
+    }
+
+    /// Turn follow mode on (selecting the latest event) or off.
+    pub fn set_following(&mut self, follow: bool) {
+        self.follow = follow;
+        if follow {
+            self.selected = self.events.len().checked_sub(1);
+        }
+    }
+
     /// Move selection up by a page.
     pub fn page_up(&mut self, visible_count: usize) {
         if self.events.is_empty() {
@@ -251,6 +332,65 @@ impl TimelineState {
         }
     }
 
+    /// Lines of iteration header above event `index` when `first_visible`
+    /// or not: iterations get a header row above their first event, and
+    /// above the first visible event while scrolled partway through one.
+    fn header_lines(&self, index: usize, first_visible: bool) -> usize {
+        let Some(tag) = self.events.get(index).and_then(|e| e.iteration.as_ref()) else {
+            return 0;
+        };
+        let starts_iteration = index
+            .checked_sub(1)
+            .and_then(|prev| self.events[prev].iteration.as_ref())
+            .is_none_or(|prev| prev.number != tag.number);
+        usize::from(first_visible || starts_iteration)
+    }
+
+    /// Whether event `index` shows an iteration header row above it at the
+    /// current scroll position.
+    pub fn has_header(&self, index: usize) -> bool {
+        self.header_lines(index, index == self.scroll_offset) > 0
+    }
+
+    /// The iteration event `index` belongs to, and the time from its first
+    /// event to its last.
+    pub fn iteration_span(&self, index: usize) -> Option<(&IterationTag, TimeDelta)> {
+        let tag = self.events.get(index)?.iteration.as_ref()?;
+        let same = |event: &TimelineEvent| event.iteration.as_ref() == Some(tag);
+        let first = self.events[..=index]
+            .iter()
+            .rev()
+            .take_while(|event| same(event))
+            .last()?;
+        let last = self.events[index..]
+            .iter()
+            .take_while(|event| same(event))
+            .last()?;
+        Some((tag, last.timestamp - first.timestamp))
+    }
+
+    /// Scroll so the last event ends within `height` lines, showing as many
+    /// events before it as fit (with a blank line between events).
+    pub fn scroll_to_end(&mut self, height: usize) {
+        let mut used = 0;
+        let mut offset = self.events.len();
+        while let Some(index) = offset.checked_sub(1) {
+            let mut needed = self.header_lines(index, true) + self.events[index].display_height();
+            if index + 1 < self.events.len() {
+                // The event below gets a gap, and keeps its header only if
+                // it starts an iteration
+                needed += 1 + self.header_lines(index + 1, false);
+                needed -= self.header_lines(index + 1, true);
+            }
+            if used + needed > height && used > 0 {
+                break;
+            }
+            used += needed;
+            offset = index;
+        }
+        self.scroll_offset = offset;
+    }
+
     /// Calculate how many events fit in the given height.
     ///
     /// Uses fixed height: 2 lines for collapsed, up to 12 for expanded.
@@ -266,6 +406,16 @@ impl TimelineState {
             .map_or(0, TimelineEvent::display_height)
     }
 
+    /// Position for the pane border: "N of M events", where N is the
+    /// selected event (or the first visible one), marked while following.
+    pub fn position_label(&self) -> Option<String> {
+        let last = self.events.len().checked_sub(1)?;
+        let current = self.selected.unwrap_or(self.scroll_offset).min(last) + 1;
+        let noun = if last == 0 { "event" } else { "events" };
+        let follow = if self.follow { ", following" } else { "" };
+        Some(format!("{current} of {} {noun}{follow}", self.events.len()))
+    }
+
     /// Get visible events for current scroll position.
     ///
     /// Returns tuples of `(event_index, &event)`.
@@ -292,6 +442,7 @@ impl TimelineState {
         for idx in self.scroll_offset..self.events.len() {
             let event = &self.events[idx];
             let event_height = event.display_height();
+            current_y += self.header_lines(idx, idx == self.scroll_offset);
 
             // Check if y falls within this event's display area [current_y, current_y + event_height)
             if y >= current_y && y < current_y + event_height {
@@ -311,6 +462,36 @@ mod tests {
     use super::*;
     use crate::timeline::event::SpecEvent;
 
+    fn spec_content(state: &TimelineState, index: usize) -> &str {
+        match &state.events()[index].kind {
+            EventKind::Spec(spec) => &spec.content,
+            _ => panic!("expected a spec event"),
+        }
+    }
+
+    #[test]
+    fn test_stream_response() {
+        let mut state = create_test_timeline(1);
+        assert!(!state.finish_stream(None));
+
+        state.stream_response("claude", "Here is ");
+        state.stream_response("claude", "the draft");
+        assert!(state.is_streaming());
+        assert_eq!(state.len(), 2);
+        assert_eq!(spec_content(&state, 1), "Here is the draft");
+        assert_eq!(state.selected(), Some(1));
+
+        assert!(state.finish_stream(Some("Here is the final draft")));
+        assert!(!state.is_streaming());
+        assert_eq!(spec_content(&state, 1), "Here is the final draft");
+
+        // The next response starts a new message
+        state.stream_response("codex", "Partial");
+        assert_eq!(state.len(), 3);
+        assert!(state.finish_stream(None));
+        assert_eq!(spec_content(&state, 2), "Partial");
+    }
+
     fn create_test_timeline(count: usize) -> TimelineState {
         let mut state = TimelineState::new();
         for i in 0..count {
@@ -319,6 +500,119 @@ mod tests {
         state
     }
 
+    fn create_run_timeline() -> TimelineState {
+        let mut state = TimelineState::new();
+        state.push(EventKind::Spec(SpecEvent::user("Start")));
+        for number in 1..=2 {
+            let tag = IterationTag {
+                number,
+                model: "claude".into(),
+            };
+            for i in 0..3 {
+                state.push_in_iteration(
+                    EventKind::Spec(SpecEvent::system(format!("Step {i}"))),
+                    Some(tag.clone()),
+                );
+            }
+        }
+        state
+    }
+
+    #[test]
+    fn test_iteration_headers() {
+        let mut state = create_run_timeline();
+        let headers: Vec<usize> = (0..state.len()).filter(|&i| state.has_header(i)).collect();
+        assert_eq!(headers, vec![1, 4]);
+
+        // Scrolled into an iteration, its header sticks to the top
+        state.scroll_down(2);
+        let headers: Vec<usize> = (state.scroll_offset()..state.len())
+            .filter(|&i| state.has_header(i))
+            .collect();
+        assert_eq!(headers, vec![2, 4]);
+
+        // Header rows shift clicks down a line
+        assert_eq!(state.y_to_event_index(0), None);
+        assert_eq!(state.y_to_event_index(1), Some(2));
+
+        let (tag, _) = state.iteration_span(5).unwrap();
+        assert_eq!(tag.number, 2);
+        assert!(state.iteration_span(0).is_none());
+    }
+
+    #[test]
+    fn test_scroll_to_end_with_headers() {
+        // Last two events: header + 2 + gap + 2 lines
+        let mut state = create_run_timeline();
+        state.scroll_to_end(8);
+        assert_eq!(state.scroll_offset(), 5);
+        // Three events of iteration 2 take one header, not three
+        state.scroll_to_end(9);
+        assert_eq!(state.scroll_offset(), 4);
+    }
+
+    #[test]
+    fn test_set_all_collapsed() {
+        let mut state = TimelineState::new();
+        state.push(EventKind::Spec(SpecEvent::user("one\ntwo")));
+        state.push(EventKind::Spec(SpecEvent::user("single")));
+        assert!(state.any_expanded());
+
+        state.set_all_collapsed(true);
+        assert!(!state.any_expanded());
+        assert!(state.events()[0].collapsed);
+        assert!(!state.events()[1].collapsed);
+
+        state.set_all_collapsed(false);
+        assert!(state.any_expanded());
+    }
+
+    #[test]
+    fn test_set_following() {
+        let mut state = create_test_timeline(5);
+        state.scroll_up(1);
+        state.select(1);
+        assert!(!state.is_following());
+
+        state.set_following(true);
+        assert!(state.is_following());
+        assert_eq!(state.selected(), Some(4));
+        state.set_following(false);
+        assert!(!state.is_following());
+        assert_eq!(state.selected(), Some(4));
+    }
+
+    #[test]
+    fn test_scroll_to_end() {
+        // Collapsed events take 2 lines plus a gap
+        let mut state = create_test_timeline(30);
+        state.scroll_to_end(17);
+        assert_eq!(state.scroll_offset(), 24);
+        state.scroll_to_end(100);
+        assert_eq!(state.scroll_offset(), 0);
+        // The last event shows even when it doesn't fit
+        state.scroll_to_end(1);
+        assert_eq!(state.scroll_offset(), 29);
+    }
+
+    #[test]
+    fn test_position_label() {
+        assert_eq!(TimelineState::new().position_label(), None);
+        assert_eq!(
+            create_test_timeline(1).position_label().as_deref(),
+            Some("1 of 1 event, following")
+        );
+
+        let mut state = create_test_timeline(12);
+        assert_eq!(
+            state.position_label().as_deref(),
+            Some("12 of 12 events, following")
+        );
+        state.select_prev();
+        state.select_prev();
+        assert_eq!(state.position_label().as_deref(), Some("10 of 12 events"));
+    }
+
     #[test]
     fn test_new_timeline() {
         let state = TimelineState::new();
@@ -5,7 +5,10 @@ use ratatui::{
     layout::Rect,
     style::Style,
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph, Widget},
+    widgets::{
+        Block, Borders, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, StatefulWidget,
+        Widget,
+    },
 };
 
 use super::event::{EventKind, ReviewResult, SystemLevel, TimelineEvent, MAX_EXPANDED_LINES};
@@ -104,7 +107,9 @@ impl<'a> TimelineWidget<'a> {
         }
     }
 
-    /// Render the pending response indicator with animated spinner.
+    /// Render the pending response indicator with animated spinner: the
+    /// model is "thinking" until its reply starts to stream in, then
+    /// "typing".
     fn render_pending_indicator(&self, model: &str, y: u16, area: Rect, buf: &mut Buffer) {
         if y >= area.y + area.height {
             return;
@@ -119,7 +124,14 @@ impl<'a> TimelineWidget<'a> {
             Span::styled(frame, Style::default().fg(color)),
             Span::raw(" "),
             Span::styled(model, Style::default().fg(self.theme.subtext)),
-            Span::styled(" is thinking...", Style::default().fg(self.theme.muted)),
+            Span::styled(
+                if self.state.is_streaming() {
+                    " is typing... (Esc to cancel)"
+                } else {
+                    " is thinking... (Esc to cancel)"
+                },
+                Style::default().fg(self.theme.muted),
+            ),
         ]);
 
         Paragraph::new(line).render(Rect::new(area.x, y, area.width, 1), buf);
@@ -146,6 +158,42 @@ impl<'a> TimelineWidget<'a> {
         }
     }
 
+    /// Render the header row of the iteration event `index` belongs to:
+    /// number, model, and how long it has run.
+    fn render_iteration_header(&self, index: usize, y: u16, area: Rect, buf: &mut Buffer) {
+        let Some((tag, elapsed)) = self.state.iteration_span(index) else {
+            return;
+        };
+        let label = format!(
+            " Iteration {} \u{b7} {} \u{b7} {} ",
+            tag.number,
+            tag.model,
+            format_elapsed(elapsed.num_seconds())
+        );
+        let rule =
+            "\u{2500}".repeat((area.width as usize).saturating_sub(label.chars().count() + 2));
+        let line = Line::from(vec![
+            Span::styled("\u{2500}\u{2500}", Style::default().fg(self.theme.border)),
+            Span::styled(label, Style::default().fg(self.model_color(&tag.model))),
+            Span::styled(rule, Style::default().fg(self.theme.border)),
+        ]);
+        Paragraph::new(line).render(Rect::new(area.x, y, area.width, 1), buf);
+    }
+
+    /// Render the scrollbar in the right column of `area`.
+    fn render_scrollbar(&self, area: Rect, visible_count: usize, buf: &mut Buffer) {
+        // One position per scroll offset, so the thumb reaches the bottom
+        // once the last page shows
+        let offset = self.state.scroll_offset();
+        let positions = self.state.len().saturating_sub(visible_count).max(offset) + 1;
+        let mut state = ScrollbarState::new(positions)
+            .position(offset)
+            .viewport_content_length(visible_count);
+        Scrollbar::new(ScrollbarOrientation::VerticalRight)
+            .style(Style::default().fg(self.theme.border))
+            .render(area, buf, &mut state);
+    }
+
     /// Render a single event.
     #[allow(clippy::too_many_lines)]
     fn render_event(
@@ -328,12 +376,18 @@ impl Widget for TimelineWidget<'_> {
                 Style::default().fg(self.theme.border)
             };
 
-            let block = Block::default()
+            let mut block = Block::default()
                 .title(" Timeline ")
                 .title_style(Style::default().fg(self.theme.text))
                 .borders(Borders::ALL)
                 .border_style(border_style)
                 .style(Style::default().bg(self.theme.base));
+            if let Some(label) = self.state.position_label() {
+                block = block.title_bottom(
+                    Line::styled(format!(" {label} "), Style::default().fg(self.theme.muted))
+                        .right_aligned(),
+                );
+            }
 
             let inner = block.inner(area);
             block.render(area, buf);
@@ -370,6 +424,10 @@ impl Widget for TimelineWidget<'_> {
         let visible_count = self.state.events_per_page(inner.height as usize);
         let visible = self.state.visible_events(visible_count);
 
+        // Keep the last column for the scrollbar when events don't all fit
+        let has_scrollbar = self.state.len() > visible_count && inner.width > 1;
+        let content_width = inner.width - u16::from(has_scrollbar);
+
         // Render events
         let mut y = inner.y;
         for (idx, event) in visible {
@@ -377,9 +435,25 @@ impl Widget for TimelineWidget<'_> {
                 break;
             }
 
+            if self.state.has_header(idx) {
+                self.render_iteration_header(
+                    idx,
+                    y,
+                    Rect {
+                        width: content_width,
+                        ..inner
+                    },
+                    buf,
+                );
+                y += 1;
+                if y >= inner.y + inner.height {
+                    break;
+                }
+            }
+
             let is_selected = self.state.selected() == Some(idx);
             let remaining_height = (inner.y + inner.height).saturating_sub(y);
-            let event_area = Rect::new(inner.x, y, inner.width, remaining_height);
+            let event_area = Rect::new(inner.x, y, content_width, remaining_height);
 
             let lines_used = self.render_event(event, is_selected, event_area, buf);
             y += lines_used;
@@ -394,6 +468,22 @@ impl Widget for TimelineWidget<'_> {
         if let Some(model) = self.state.pending_response() {
             self.render_pending_indicator(model, y, inner, buf);
         }
+
+        if has_scrollbar {
+            self.render_scrollbar(inner, visible_count, buf);
+        }
+    }
+}
+
+/// "42s", "3m 05s", "1h 02m".
+pub(crate) fn format_elapsed(secs: i64) -> String {
+    let secs = secs.max(0);
+    if secs < 60 {
+        format!("{secs}s")
+    } else if secs < 3600 {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}h {:02}m", secs / 3600, secs % 3600 / 60)
     }
 }
 
@@ -408,4 +498,66 @@ mod tests {
         let widget = TimelineWidget::new(&state, &theme).focused(true);
         assert!(widget.focused);
     }
+
+    fn render_to_string(state: &TimelineState, width: u16, height: u16) -> String {
+        let theme = Theme::default();
+        let area = Rect::new(0, 0, width, height);
+        let mut buf = Buffer::empty(area);
+        TimelineWidget::new(state, &theme).render(area, &mut buf);
+        crate::headless::buffer_to_string(&buf)
+    }
+
+    #[test]
+    fn test_iteration_headers() {
+        use crate::timeline::event::{IterationTag, SpecEvent};
+
+        let mut state = TimelineState::new();
+        let tag = IterationTag {
+            number: 3,
+            model: "claude".into(),
+        };
+        for i in 0..2 {
+            state.push_in_iteration(
+                EventKind::Spec(SpecEvent::system(format!("Step {i}"))),
+                Some(tag.clone()),
+            );
+        }
+        let output = render_to_string(&state, 50, 12);
+        let header = output.lines().nth(1).unwrap();
+        assert!(
+            header.contains("Iteration 3 \u{b7} claude \u{b7} 0s"),
+            "{header}"
+        );
+        assert_eq!(output.matches("Iteration 3").count(), 1);
+    }
+
+    #[test]
+    fn test_format_elapsed() {
+        assert_eq!(format_elapsed(42), "42s");
+        assert_eq!(format_elapsed(185), "3m 05s");
+        assert_eq!(format_elapsed(3720), "1h 02m");
+    }
+
+    #[test]
+    fn test_scrollbar_and_position() {
+        use crate::timeline::event::SpecEvent;
+
+        let mut state = TimelineState::new();
+        for i in 0..3 {
+            state.push(EventKind::Spec(SpecEvent::user(format!("Event {i}"))));
+        }
+        let output = render_to_string(&state, 40, 20);
+        assert!(output.contains("3 of 3 events, following"));
+        assert!(!output.contains('█'));
+
+        for i in 3..20 {
+            state.push(EventKind::Spec(SpecEvent::user(format!("Event {i}"))));
+        }
+        state.jump_to_start();
+        let output = render_to_string(&state, 40, 20);
+        assert!(output.contains("1 of 20 events"));
+        // The thumb sits at the top of the track
+        let second_row = output.lines().nth(2).unwrap();
+        assert!(second_row.trim_end().ends_with("█│"));
+    }
 }
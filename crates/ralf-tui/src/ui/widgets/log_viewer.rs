@@ -5,7 +5,8 @@ use crate::ui::theme::Styles;
 use ratatui::{
     buffer::Buffer,
     layout::Rect,
-    text::{Line, Text},
+    style::Style,
+    text::{Line, Span, Text},
     widgets::{
         Block, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, StatefulWidget, Widget,
     },
@@ -18,6 +19,8 @@ pub struct LogViewer<'a> {
     scroll: usize,
     auto_scroll: bool,
     block: Option<Block<'a>>,
+    style: Style,
+    highlight: Option<(&'a str, Style)>,
 }
 
 impl<'a> LogViewer<'a> {
@@ -28,6 +31,8 @@ impl<'a> LogViewer<'a> {
             scroll: 0,
             auto_scroll: true,
             block: None,
+            style: Styles::default(),
+            highlight: None,
         }
     }
 
@@ -59,6 +64,20 @@ impl<'a> LogViewer<'a> {
         self
     }
 
+    /// Set the base style of the viewer.
+    #[must_use]
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Highlight case-insensitive occurrences of `query` (e.g., a search).
+    #[must_use]
+    pub fn highlight(mut self, query: &'a str, style: Style) -> Self {
+        self.highlight = (!query.is_empty()).then_some((query, style));
+        self
+    }
+
     /// Get the scroll state for external tracking.
     pub fn scroll_state(&self, viewport_height: usize) -> ScrollState {
         let total = self.lines.len();
@@ -101,11 +120,19 @@ impl Widget for LogViewer<'_> {
         let state = self.scroll_state(viewport_height);
 
         // Create paragraph with scroll
-        let text = Text::from(self.lines.clone());
+        let lines = match self.highlight {
+            Some((query, style)) => self
+                .lines
+                .iter()
+                .map(|line| highlight_line(line, query, style))
+                .collect(),
+            None => self.lines.clone(),
+        };
+        let text = Text::from(lines);
         #[allow(clippy::cast_possible_truncation)]
         let scroll_offset = state.offset as u16;
         let paragraph = Paragraph::new(text)
-            .style(Styles::default())
+            .style(self.style)
             .scroll((scroll_offset, 0));
 
         paragraph.render(area, buf);
@@ -127,6 +154,36 @@ impl Widget for LogViewer<'_> {
     }
 }
 
+/// Split `line` so occurrences of `query` get `style` on top of the line's
+/// own style. Lines without a match are returned unchanged.
+fn highlight_line<'a>(line: &Line<'a>, query: &str, style: Style) -> Line<'a> {
+    let content: String = line.spans.iter().map(|s| s.content.as_ref()).collect();
+    let haystack = content.to_lowercase();
+    let needle = query.to_lowercase();
+    // Lowercasing can change byte lengths outside ASCII; only highlight when
+    // offsets still line up.
+    if haystack.len() != content.len() || !haystack.contains(&needle) {
+        return line.clone();
+    }
+
+    let mut spans = Vec::new();
+    let mut rest = 0;
+    for (at, _) in haystack.match_indices(&needle) {
+        if at > rest {
+            spans.push(Span::raw(content[rest..at].to_string()));
+        }
+        spans.push(Span::styled(
+            content[at..at + needle.len()].to_string(),
+            style,
+        ));
+        rest = at + needle.len();
+    }
+    if rest < content.len() {
+        spans.push(Span::raw(content[rest..].to_string()));
+    }
+    Line::from(spans).style(line.style)
+}
+
 /// Scroll state for tracking position.
 #[derive(Debug, Clone, Copy)]
 pub struct ScrollState {
@@ -152,3 +209,34 @@ impl ScrollState {
         (self.offset + amount).min(max)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::{Color, Modifier};
+
+    #[test]
+    fn test_highlight_line() {
+        let style = Style::default().add_modifier(Modifier::REVERSED);
+        let line = Line::from("Error: error here").style(Style::default().fg(Color::Red));
+        let highlighted = highlight_line(&line, "error", style);
+        let parts: Vec<(&str, Style)> = highlighted
+            .spans
+            .iter()
+            .map(|s| (s.content.as_ref(), s.style))
+            .collect();
+        assert_eq!(
+            parts,
+            vec![
+                ("Error", style),
+                (": ", Style::default()),
+                ("error", style),
+                (" here", Style::default()),
+            ]
+        );
+        assert_eq!(highlighted.style, line.style);
+
+        let plain = Line::from("nothing");
+        assert_eq!(highlight_line(&plain, "error", style), plain);
+    }
+}
@@ -5,5 +5,6 @@ pub mod status_bar;
 mod tabs;
 pub mod text_input;
 
+pub use log_viewer::{LogViewer, ScrollState};
 pub use status_bar::{KeyHint, StatusBar};
 pub use text_input::TextInputState;
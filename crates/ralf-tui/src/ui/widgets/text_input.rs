@@ -1,4 +1,8 @@
 //! Multi-line text input widget.
+//!
+//! [`TextInputState`] is a small line editor: the cursor moves by character,
+//! word, and line, and Ctrl+W / Ctrl+U / Ctrl+K style kills are supported.
+//! Content is soft wrapped for display with [`wrap_input`].
 
 use crate::ui::theme::Styles;
 use ratatui::{
@@ -13,7 +17,7 @@ use ratatui::{
 pub struct TextInput<'a> {
     /// The text content.
     content: String,
-    /// Cursor position (character index).
+    /// Cursor position (byte offset).
     cursor: usize,
     /// Optional block for borders/title.
     block: Option<Block<'a>>,
@@ -93,43 +97,30 @@ impl Widget for TextInput<'_> {
             return;
         }
 
-        // Render content with cursor
-        let mut lines = Vec::new();
-        let mut current_line = self.prompt.to_string(); // Start first line with prompt
-        let mut cursor_drawn = false;
-        let mut is_first_line = true;
-
-        for (char_count, ch) in self.content.chars().enumerate() {
-            if ch == '\n' {
-                // Check if cursor is at end of this line
-                if self.focused && char_count == self.cursor && !cursor_drawn {
-                    current_line.push('_');
-                    cursor_drawn = true;
-                }
-                lines.push(Line::from(current_line.clone()));
-                current_line.clear();
-                // Continuation lines get indentation matching prompt length
-                if is_first_line {
-                    is_first_line = false;
-                }
-                current_line.push_str(&" ".repeat(prompt_len));
+        // Render soft-wrapped content with cursor
+        let width = (inner.width as usize).saturating_sub(prompt_len);
+        let (rows, (cursor_row, cursor_col)) = wrap_input(&self.content, self.cursor, width);
+        let mut lines = Vec::with_capacity(rows.len());
+        for (index, row) in rows.iter().enumerate() {
+            // Continuation lines get indentation matching prompt length
+            let mut line = if index == 0 {
+                self.prompt.to_string()
             } else {
-                // Insert cursor before this character if position matches
-                if self.focused && char_count == self.cursor && !cursor_drawn {
-                    current_line.push('|');
-                    cursor_drawn = true;
-                }
-                current_line.push(ch);
+                " ".repeat(prompt_len)
+            };
+            if self.focused && index == cursor_row {
+                let split = row
+                    .char_indices()
+                    .nth(cursor_col)
+                    .map_or(row.len(), |(at, _)| at);
+                line.push_str(&row[..split]);
+                // Cursor before a character, or at the end of the row
+                line.push(if split < row.len() { '|' } else { '_' });
+                line.push_str(&row[split..]);
+            } else {
+                line.push_str(row);
             }
-        }
-
-        // Cursor at the end
-        if self.focused && !cursor_drawn {
-            current_line.push('_');
-        }
-
-        if !current_line.is_empty() || lines.is_empty() {
-            lines.push(Line::from(current_line));
+            lines.push(Line::from(line));
         }
 
         let paragraph = Paragraph::new(lines).style(Styles::default());
@@ -137,12 +128,47 @@ impl Widget for TextInput<'_> {
     }
 }
 
+/// Soft-wrap `content` into rows of at most `width` characters, breaking
+/// after a space where possible.
+///
+/// Returns the rows and the cursor's (row, column) among them; `cursor` is
+/// a byte offset and the column counts characters.
+pub fn wrap_input(content: &str, cursor: usize, width: usize) -> (Vec<String>, (usize, usize)) {
+    let width = width.max(1);
+    let mut rows = Vec::new();
+    let mut position = (0, 0);
+    let mut line_start = 0;
+
+    for line in content.split('\n') {
+        let mut start = 0;
+        loop {
+            let rest = &line[start..];
+            let end = match rest.char_indices().nth(width) {
+                None => line.len(),
+                Some((limit, _)) => start + rest[..limit].rfind(' ').map_or(limit, |at| at + 1),
+            };
+            let at = cursor.wrapping_sub(line_start + start);
+            // A cursor on a break belongs to the next row, except at line end
+            if at < end - start || (at == end - start && end == line.len()) {
+                position = (rows.len(), line[start..start + at].chars().count());
+            }
+            rows.push(line[start..end].to_string());
+            if end == line.len() {
+                break;
+            }
+            start = end;
+        }
+        line_start += line.len() + 1;
+    }
+    (rows, position)
+}
+
 /// State for a text input, managing content and cursor position.
 #[derive(Debug, Clone, Default)]
 pub struct TextInputState {
     /// The text content.
     pub content: String,
-    /// Cursor position (character index).
+    /// Cursor position (byte offset, always on a character boundary).
     pub cursor: usize,
     /// Input history for up/down navigation.
     history: Vec<String>,
@@ -191,17 +217,28 @@ impl TextInputState {
         content
     }
 
+    /// Count the rows the content takes when soft wrapped to `width`.
+    pub fn visual_line_count(&self, width: usize) -> usize {
+        self.wrap(width).0.len()
+    }
+
+    /// Soft-wrap the content to `width`; see [`wrap_input`].
+    pub fn wrap(&self, width: usize) -> (Vec<String>, (usize, usize)) {
+        wrap_input(&self.content, self.cursor, width)
+    }
+
+    /// The cursor's line and column (in characters), ignoring soft wraps.
+    pub fn cursor_position(&self) -> (usize, usize) {
+        let before = &self.content[..self.cursor];
+        let row = before.matches('\n').count();
+        let col = before[self.line_start()..].chars().count();
+        (row, col)
+    }
+
     /// Insert a character at the cursor position.
     pub fn insert(&mut self, ch: char) {
-        // Handle newline
-        if ch == '\n' {
-            self.content.insert(self.cursor, ch);
-            self.cursor += 1;
-            return;
-        }
-
         self.content.insert(self.cursor, ch);
-        self.cursor += 1;
+        self.cursor += ch.len_utf8();
     }
 
     /// Insert a string at the cursor position.
@@ -212,8 +249,8 @@ impl TextInputState {
 
     /// Delete the character before the cursor (backspace).
     pub fn backspace(&mut self) {
-        if self.cursor > 0 {
-            self.cursor -= 1;
+        if let Some(ch) = self.content[..self.cursor].chars().next_back() {
+            self.cursor -= ch.len_utf8();
             self.content.remove(self.cursor);
         }
     }
@@ -227,18 +264,134 @@ impl TextInputState {
 
     /// Move cursor left.
     pub fn move_left(&mut self) {
-        if self.cursor > 0 {
-            self.cursor -= 1;
+        if let Some(ch) = self.content[..self.cursor].chars().next_back() {
+            self.cursor -= ch.len_utf8();
         }
     }
 
     /// Move cursor right.
     pub fn move_right(&mut self) {
-        if self.cursor < self.content.len() {
-            self.cursor += 1;
+        if let Some(ch) = self.content[self.cursor..].chars().next() {
+            self.cursor += ch.len_utf8();
+        }
+    }
+
+    /// Move the cursor to the start of the previous word.
+    pub fn move_word_left(&mut self) {
+        self.cursor = self.word_start();
+    }
+
+    /// Move the cursor to the end of the next word.
+    pub fn move_word_right(&mut self) {
+        let rest = &self.content[self.cursor..];
+        let word = rest.len() - rest.trim_start().len();
+        let end = rest[word..]
+            .find(char::is_whitespace)
+            .map_or(rest.len(), |at| word + at);
+        self.cursor += end;
+    }
+
+    /// Move the cursor up a line, keeping its column where the line is long
+    /// enough. Returns `false` on the first line.
+    pub fn move_up(&mut self) -> bool {
+        let (row, col) = self.cursor_position();
+        if row == 0 {
+            return false;
+        }
+        self.move_to(row - 1, col);
+        true
+    }
+
+    /// Move the cursor down a line, keeping its column where the line is
+    /// long enough. Returns `false` on the last line.
+    pub fn move_down(&mut self) -> bool {
+        let (row, col) = self.cursor_position();
+        if row + 1 >= self.line_count() {
+            return false;
+        }
+        self.move_to(row + 1, col);
+        true
+    }
+
+    /// Move cursor to the start of the current line.
+    pub fn move_line_start(&mut self) {
+        self.cursor = self.line_start();
+    }
+
+    /// Move cursor to the end of the current line.
+    pub fn move_line_end(&mut self) {
+        self.cursor = self.line_end();
+    }
+
+    /// Delete the word before the cursor (Ctrl+W).
+    pub fn delete_word_before(&mut self) {
+        let start = self.word_start();
+        self.content.replace_range(start..self.cursor, "");
+        self.cursor = start;
+    }
+
+    /// Delete from the start of the line to the cursor (Ctrl+U). At the
+    /// start of a line, joins it to the previous one.
+    pub fn kill_line_before(&mut self) {
+        let start = self.line_start();
+        if start == self.cursor {
+            self.backspace();
+        } else {
+            self.content.replace_range(start..self.cursor, "");
+            self.cursor = start;
+        }
+    }
+
+    /// Delete from the cursor to the end of the line (Ctrl+K). At the end
+    /// of a line, joins the next one to it.
+    pub fn kill_line_after(&mut self) {
+        let end = self.line_end();
+        if end == self.cursor {
+            self.delete();
+        } else {
+            self.content.replace_range(self.cursor..end, "");
         }
     }
 
+    /// Byte offset of the start of the cursor's line.
+    fn line_start(&self) -> usize {
+        self.content[..self.cursor]
+            .rfind('\n')
+            .map_or(0, |at| at + 1)
+    }
+
+    /// Byte offset of the end of the cursor's line.
+    fn line_end(&self) -> usize {
+        self.content[self.cursor..]
+            .find('\n')
+            .map_or(self.content.len(), |at| self.cursor + at)
+    }
+
+    /// Byte offset of the start of the word before the cursor, skipping
+    /// whitespace first.
+    fn word_start(&self) -> usize {
+        let before = self.content[..self.cursor].trim_end();
+        before.rfind(char::is_whitespace).map_or(0, |at| {
+            at + before[at..].chars().next().map_or(1, char::len_utf8)
+        })
+    }
+
+    /// Place the cursor on `row` at `col`, or at the end of a shorter line.
+    fn move_to(&mut self, row: usize, col: usize) {
+        let start: usize = self
+            .content
+            .split('\n')
+            .take(row)
+            .map(|line| line.len() + 1)
+            .sum();
+        let line = self.content[start..].split('\n').next().unwrap_or_default();
+        let offset = line
+            .char_indices()
+            .nth(col)
+            .map_or(line.len(), |(at, _)| at);
+        self.cursor = start + offset;
+    }
+
     /// Move cursor to start.
     pub fn move_home(&mut self) {
         self.cursor = 0;
@@ -373,4 +526,85 @@ mod tests {
         state.history_next();
         assert_eq!(state.content(), "second");
     }
+
+    #[test]
+    fn test_text_input_state_lines() {
+        let mut state = TextInputState::new();
+        state.insert_str("first line\nab\nthird");
+        assert_eq!(state.cursor_position(), (2, 5));
+
+        assert!(state.move_up());
+        assert_eq!(state.cursor_position(), (1, 2));
+        assert!(state.move_up());
+        assert_eq!(state.cursor_position(), (0, 2));
+        assert!(!state.move_up());
+
+        state.move_line_end();
+        assert!(state.move_down());
+        assert_eq!(state.cursor_position(), (1, 2));
+        state.move_line_start();
+        assert_eq!(state.cursor_position(), (1, 0));
+        assert!(state.move_down());
+        assert!(!state.move_down());
+    }
+
+    #[test]
+    fn test_text_input_state_words_and_kills() {
+        let mut state = TextInputState::new();
+        state.insert_str("add the  login\nflow");
+
+        state.move_word_left();
+        assert_eq!(state.cursor_position(), (1, 0));
+        state.move_word_left();
+        assert_eq!(&state.content()[state.cursor..], "login\nflow");
+        state.move_word_right();
+        assert_eq!(&state.content()[state.cursor..], "\nflow");
+
+        state.delete_word_before();
+        assert_eq!(state.content(), "add the  \nflow");
+        state.move_home();
+        state.move_word_right();
+        state.kill_line_after();
+        assert_eq!(state.content(), "add\nflow");
+
+        // Kills at a line boundary join the lines
+        state.kill_line_after();
+        assert_eq!(state.content(), "addflow");
+        state.kill_line_before();
+        assert_eq!(state.content(), "flow");
+        assert_eq!(state.cursor, 0);
+    }
+
+    #[test]
+    fn test_text_input_state_multibyte() {
+        let mut state = TextInputState::new();
+        state.insert_str("héllo");
+        state.move_left();
+        state.move_left();
+        state.move_left();
+        state.move_left();
+        assert_eq!(state.cursor_position(), (0, 1));
+        state.backspace();
+        assert_eq!(state.content(), "éllo");
+        state.delete();
+        assert_eq!(state.content(), "llo");
+    }
+
+    #[test]
+    fn test_wrap_input() {
+        let (rows, cursor) = wrap_input("the quick brown fox", 0, 10);
+        assert_eq!(rows, vec!["the quick ", "brown fox"]);
+        assert_eq!(cursor, (0, 0));
+
+        // A cursor on a wrap point starts the next row
+        assert_eq!(wrap_input("the quick brown fox", 10, 10).1, (1, 0));
+        assert_eq!(wrap_input("the quick brown fox", 19, 10).1, (1, 9));
+
+        // Words longer than the width are broken
+        let (rows, cursor) = wrap_input("abcdefgh\nxy", 11, 3);
+        assert_eq!(rows, vec!["abc", "def", "gh", "xy"]);
+        assert_eq!(cursor, (3, 2));
+
+        assert_eq!(wrap_input("", 0, 5), (vec![String::new()], (0, 0)));
+    }
 }
@@ -316,7 +316,10 @@ fn context_hints_for_phase(phase: Option<PhaseKind>) -> Vec<KeyHint> {
             KeyHint::new("/resume", "Resume"),
             KeyHint::new("/cancel", "Cancel"),
         ],
-        Some(PhaseKind::Stuck) => vec![KeyHint::new("Enter", "Provide input")],
+        Some(PhaseKind::Stuck) => vec![
+            KeyHint::new("1-3", "Apply fix"),
+            KeyHint::new("Enter", "Provide input"),
+        ],
         Some(PhaseKind::Implemented) => vec![KeyHint::new("Enter", "Review")],
         Some(PhaseKind::Polishing) => vec![KeyHint::new("Enter", "Finish")],
         Some(PhaseKind::PendingReview) => vec![
@@ -458,6 +461,7 @@ mod tests {
         assert!(hints
             .iter()
             .any(|h| h.key == "Enter" && h.action == "Provide input"));
+        assert!(hints.iter().any(|h| h.key == "1-3"));
     }
 
     #[test]
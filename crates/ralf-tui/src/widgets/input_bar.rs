@@ -50,41 +50,16 @@ impl<'a> InputBar<'a> {
         self
     }
 
-    /// Build Lines for multi-line input display.
+    /// Build Lines for multi-line input display, soft wrapped to `width`.
     /// Returns the lines to display and which line index contains the cursor.
-    fn build_input_lines(&self) -> (Vec<Line<'static>>, usize) {
-        let content = self.input.content();
-        let cursor_pos = self.input.cursor;
-
-        // Split content into lines
-        let text_lines: Vec<&str> = if content.is_empty() {
-            vec![""]
-        } else {
-            content.split('\n').collect()
-        };
-
-        // Find which line the cursor is on
-        let mut char_count = 0;
-        let mut cursor_line = 0;
-        let mut cursor_col = 0;
-
-        for (line_idx, line) in text_lines.iter().enumerate() {
-            let line_len = line.chars().count();
-            if cursor_pos <= char_count + line_len {
-                cursor_line = line_idx;
-                cursor_col = cursor_pos - char_count;
-                break;
-            }
-            // +1 for the newline character
-            char_count += line_len + 1;
-            cursor_line = line_idx;
-            cursor_col = 0; // Will be at start of next line
-        }
+    fn build_input_lines(&self, width: usize) -> (Vec<Line<'static>>, usize) {
+        // Leave room for the prefix
+        let (rows, (cursor_line, cursor_col)) = self.input.wrap(width.saturating_sub(2));
 
         // Build display lines
-        let mut lines = Vec::with_capacity(text_lines.len());
+        let mut lines = Vec::with_capacity(rows.len());
 
-        for (line_idx, line_text) in text_lines.iter().enumerate() {
+        for (line_idx, line_text) in rows.iter().enumerate() {
             let prefix = if line_idx == 0 { "> " } else { "  " };
 
             if self.focused && line_idx == cursor_line {
@@ -101,7 +76,7 @@ impl<'a> InputBar<'a> {
                     spans.push(Span::raw(after));
                 } else {
                     // Cursor at end of line
-                    spans.push(Span::raw(line_text.to_string()));
+                    spans.push(Span::raw(line_text.clone()));
                     spans.push(Span::raw("█"));
                 }
                 lines.push(Line::from(spans));
@@ -145,7 +120,8 @@ impl Widget for InputBar<'_> {
                 .block(block)
                 .style(Style::default().fg(self.theme.muted))
         } else {
-            let (lines, cursor_line) = self.build_input_lines();
+            let inner_width = area.width.saturating_sub(2) as usize;
+            let (lines, cursor_line) = self.build_input_lines(inner_width);
 
             // Calculate scroll offset to keep cursor visible
             let scroll_offset = if lines.len() <= inner_height {
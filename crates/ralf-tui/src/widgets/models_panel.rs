@@ -8,7 +8,7 @@
 //! ┃  gemini    ○ Not found     Install: gemini.google... ┃
 //! ┃  codex     ○ Auth needed   Run: codex auth login     ┃
 //! ┃                                                      ┃
-//! ┃  [r] Refresh                                         ┃
+//! ┃  [r] Refresh  [a] Sign in                            ┃
 //! ┃                                                      ┃
 //! ┗━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━┛
 //! ```
@@ -139,13 +139,22 @@ impl Widget for ModelsPanel<'_> {
         lines.push(Line::from(""));
 
         // Footer hint
-        let footer_spans = vec![
+        let mut footer_spans = vec![
             Span::raw("  "),
             Span::styled("[", Style::default().fg(self.theme.muted)),
             Span::styled("r", Style::default().fg(self.theme.primary)),
             Span::styled("] ", Style::default().fg(self.theme.muted)),
             Span::styled("Refresh", Style::default().fg(self.theme.subtext)),
         ];
+        if self.models.iter().any(|m| m.auth_command.is_some()) {
+            footer_spans.extend([
+                Span::raw("  "),
+                Span::styled("[", Style::default().fg(self.theme.muted)),
+                Span::styled("a", Style::default().fg(self.theme.primary)),
+                Span::styled("] ", Style::default().fg(self.theme.muted)),
+                Span::styled("Sign in", Style::default().fg(self.theme.subtext)),
+            ]);
+        }
         lines.push(Line::from(footer_spans));
 
         // Render
@@ -165,6 +174,7 @@ mod tests {
             state: ModelState::Ready,
             version: Some("1.0.0".to_string()),
             message: Some("Ready".to_string()),
+            auth_command: None,
         }
     }
 
@@ -174,6 +184,7 @@ mod tests {
             state: ModelState::Unavailable,
             version: None,
             message: Some(message.to_string()),
+            auth_command: None,
         }
     }
 
@@ -1,20 +1,25 @@
 //! Status bar widget for the top of the TUI.
 //!
-//! Format: `● Phase │ "Title" │ claude ● gemini ○ codex ○ │ file:line │ metric │ → hint`
+//! Format: `● Phase │ "Title" │ claude ● gemini ○ codex ○ │ file:line │ metric │ usage │ → hint`
+//!
+//! A model in cooldown shows the time left: `claude ◐ 04:59`.
 //!
 //! On narrow terminals (< 60 chars), model indicators collapse to: `2/3 models`
+//!
+//! The model chosen with `/model` is highlighted (or appended, when narrow).
 
 use ratatui::{
     buffer::Buffer,
     layout::Rect,
-    style::Style,
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Paragraph, Widget},
 };
 
 use ralf_engine::thread::PhaseKind;
+use ralf_engine::Usage;
 
-use crate::models::{ModelState, ModelStatus, ModelsSummary};
+use crate::models::{format_countdown, ModelState, ModelStatus, ModelsSummary};
 use crate::theme::Theme;
 use crate::thread_state::ThreadDisplay;
 
@@ -32,6 +37,10 @@ pub struct StatusBarContent {
     pub file: Option<String>,
     /// Progress metric (e.g., "2/5 criteria").
     pub metric: Option<String>,
+    /// Run token/cost usage (e.g., "12.3k tok · $0.42").
+    pub usage: Option<String>,
+    /// Model chosen for the thread with `/model`.
+    pub model: Option<String>,
     /// Next action hint (plain text, widget prepends "→ ").
     pub hint: Option<String>,
 }
@@ -44,6 +53,8 @@ impl StatusBarContent {
             title: "New Thread".into(),
             file: None,
             metric: None,
+            usage: None,
+            model: None,
             hint: None,
         }
     }
@@ -55,6 +66,8 @@ impl StatusBarContent {
             title: "Terminal too small".into(),
             file: None,
             metric: None,
+            usage: None,
+            model: None,
             hint: Some("Resize to at least 40x12".into()),
         }
     }
@@ -66,6 +79,8 @@ impl StatusBarContent {
             title: "Checking models...".into(),
             file: None,
             metric: None,
+            usage: None,
+            model: None,
             hint: None,
         }
     }
@@ -78,6 +93,8 @@ impl StatusBarContent {
                 title: "Select a thread to start".into(),
                 file: None,
                 metric: None,
+                usage: None,
+                model: None,
                 hint: None,
             },
             Some(t) => {
@@ -88,12 +105,28 @@ impl StatusBarContent {
                     title: t.title.clone(),
                     file: None,
                     metric,
+                    usage: None,
+                    model: None,
                     hint,
                 }
             }
         }
     }
 
+    /// Attach run usage totals, if any were recorded.
+    #[must_use]
+    pub fn with_usage(mut self, usage: Option<&Usage>) -> Self {
+        self.usage = usage.filter(|u| !u.is_empty()).map(Usage::summary);
+        self
+    }
+
+    /// Attach the model chosen for the thread, if any.
+    #[must_use]
+    pub fn with_model(mut self, model: Option<&str>) -> Self {
+        self.model = model.map(ToString::to_string);
+        self
+    }
+
     /// Get next action hint for a phase.
     #[must_use]
     pub fn next_action_hint(phase: PhaseKind) -> String {
@@ -158,6 +191,7 @@ impl<'a> StatusBar<'a> {
 
 impl Widget for StatusBar<'_> {
     fn render(self, area: Rect, buf: &mut Buffer) {
+        let now = ralf_engine::state::current_timestamp();
         let mut spans = vec![
             Span::styled("● ", Style::default().fg(self.theme.primary)),
             Span::styled(&self.content.phase, Style::default().fg(self.theme.text)),
@@ -179,20 +213,36 @@ impl Widget for StatusBar<'_> {
                     summary.narrow_format(),
                     Style::default().fg(self.theme.subtext),
                 ));
+                if let Some(ref model) = self.content.model {
+                    spans.push(Span::styled(
+                        format!(" · {model}"),
+                        Style::default().fg(self.theme.primary),
+                    ));
+                }
             } else {
                 // Show individual model indicators
                 for (i, model) in self.models.iter().enumerate() {
                     if i > 0 {
                         spans.push(Span::raw(" "));
                     }
-                    spans.push(Span::styled(
-                        &model.name,
-                        Style::default().fg(self.theme.subtext),
-                    ));
+                    let name_style = if self.content.model.as_ref() == Some(&model.name) {
+                        Style::default()
+                            .fg(self.theme.primary)
+                            .add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default().fg(self.theme.subtext)
+                    };
+                    spans.push(Span::styled(&model.name, name_style));
                     spans.push(Span::raw(" "));
                     let indicator = model.indicator(self.ascii_mode);
                     let color = self.state_color(&model.state);
                     spans.push(Span::styled(indicator, Style::default().fg(color)));
+                    if let Some(secs) = model.cooldown_remaining(now) {
+                        spans.push(Span::styled(
+                            format!(" {}", format_countdown(secs)),
+                            Style::default().fg(color),
+                        ));
+                    }
                 }
             }
         }
@@ -209,6 +259,12 @@ impl Widget for StatusBar<'_> {
             spans.push(Span::styled(metric, Style::default().fg(self.theme.info)));
         }
 
+        // Add optional usage
+        if let Some(ref usage) = self.content.usage {
+            spans.push(Span::styled(" │ ", Style::default().fg(self.theme.muted)));
+            spans.push(Span::styled(usage, Style::default().fg(self.theme.subtext)));
+        }
+
         // Add optional hint
         if let Some(ref hint) = self.content.hint {
             spans.push(Span::styled(" │ ", Style::default().fg(self.theme.muted)));
@@ -271,10 +327,7 @@ mod tests {
         // Unavailable should use muted color
         assert_eq!(bar.state_color(&ModelState::Unavailable), theme.muted);
         // RateLimited should use error color
-        assert_eq!(
-            bar.state_color(&ModelState::RateLimited(None)),
-            theme.error
-        );
+        assert_eq!(bar.state_color(&ModelState::RateLimited(None)), theme.error);
     }
 
     #[test]
@@ -295,6 +348,9 @@ mod tests {
             iteration: Some(2),
             max_iterations: 5,
             failure_reason: None,
+            stuck: None,
+            preflight: None,
+            checklist: None,
         };
 
         let content = StatusBarContent::from_thread(Some(&display));
@@ -304,6 +360,61 @@ mod tests {
         assert!(content.hint.is_some());
     }
 
+    #[test]
+    fn test_with_usage() {
+        let usage = Usage {
+            total_tokens: 2_500,
+            cost_usd: 0.1,
+            ..Usage::default()
+        };
+        let content = StatusBarContent::from_thread(None).with_usage(Some(&usage));
+        assert_eq!(content.usage, Some("2.5k tok · $0.10".into()));
+
+        // Empty usage is not shown
+        let content = StatusBarContent::from_thread(None).with_usage(Some(&Usage::default()));
+        assert!(content.usage.is_none());
+    }
+
+    #[test]
+    fn test_with_model_highlights_active() {
+        let content = StatusBarContent::from_thread(None).with_model(Some("codex"));
+        assert_eq!(content.model.as_deref(), Some("codex"));
+
+        let theme = Theme::default();
+        let mut models = vec![
+            ModelStatus::probing("claude"),
+            ModelStatus::probing("codex"),
+        ];
+        models[1].state = ModelState::Ready;
+        let area = Rect::new(0, 0, 100, 1);
+        let mut buf = Buffer::empty(area);
+        StatusBar::new(&content, &models, &theme).render(area, &mut buf);
+
+        let symbols: Vec<&str> = (0..area.width).map(|x| buf[(x, 0)].symbol()).collect();
+        let x = symbols
+            .windows(5)
+            .position(|w| w.concat() == "codex")
+            .unwrap();
+        let cell = &buf[(u16::try_from(x).unwrap(), 0)];
+        assert_eq!(cell.fg, theme.primary);
+        assert!(cell.modifier.contains(Modifier::BOLD));
+    }
+
+    #[test]
+    fn test_cooldown_countdown() {
+        let content = StatusBarContent::from_thread(None);
+        let theme = Theme::default();
+        let mut models = vec![ModelStatus::probing("claude")];
+        // Far enough out that the minutes can't change mid-test
+        models[0].state = ModelState::Cooldown(ralf_engine::state::current_timestamp() + 3000);
+        let area = Rect::new(0, 0, 100, 1);
+        let mut buf = Buffer::empty(area);
+        StatusBar::new(&content, &models, &theme).render(area, &mut buf);
+
+        let text: String = (0..area.width).map(|x| buf[(x, 0)].symbol()).collect();
+        assert!(text.contains("claude ◐ 49:") || text.contains("claude ◐ 50:00"));
+    }
+
     #[test]
     fn test_next_action_hint_all_phases() {
         // Test a few key phases
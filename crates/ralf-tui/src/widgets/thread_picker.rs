@@ -0,0 +1,293 @@
+//! Thread picker widget for switching between threads.
+//!
+//! Shown in the context pane by the `/threads` command:
+//! ```text
+//! ┏ Threads ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━┓
+//! ┃                                                      ┃
+//! ┃ ▸ ● Add OAuth login      Running        2025-01-14   ┃
+//! ┃     Fix flaky test       Drafting       2025-01-12   ┃
+//! ┃     Bump dependencies    Finalized #1   2025-01-11   ┃
+//! ┃                                                      ┃
+//! ┃  [Enter] Open  [a] Archive  [Esc] Close              ┃
+//! ┗━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━┛
+//! ```
+//!
+//! Threads waiting in `ralf queue` show their place in line after the phase.
+
+use std::collections::HashMap;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Widget},
+};
+
+use ralf_engine::{Queue, ThreadSummary};
+
+use crate::theme::Theme;
+
+/// Selection state for the thread picker.
+#[derive(Debug, Clone, Default)]
+pub struct ThreadPickerState {
+    /// Threads to choose from (most recent first).
+    pub threads: Vec<ThreadSummary>,
+    /// Index of the selected thread.
+    pub selected: usize,
+    /// Place in the queue of each queued thread, by thread ID.
+    pub queue_positions: HashMap<String, usize>,
+}
+
+impl ThreadPickerState {
+    /// Create picker state, selecting the active thread if present.
+    pub fn new(threads: Vec<ThreadSummary>) -> Self {
+        let selected = threads.iter().position(|t| t.is_active).unwrap_or(0);
+        Self {
+            threads,
+            selected,
+            queue_positions: HashMap::new(),
+        }
+    }
+
+    /// Show each thread's place in `queue`.
+    #[must_use]
+    pub fn with_queue(mut self, queue: &Queue) -> Self {
+        self.queue_positions = self
+            .threads
+            .iter()
+            .filter_map(|t| queue.position(&t.id).map(|p| (t.id.clone(), p)))
+            .collect();
+        self
+    }
+
+    /// Move selection down.
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.threads.len() {
+            self.selected += 1;
+        }
+    }
+
+    /// Move selection up.
+    pub fn select_prev(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Get the selected thread.
+    pub fn selected_thread(&self) -> Option<&ThreadSummary> {
+        self.threads.get(self.selected)
+    }
+
+    /// Remove the selected thread (e.g., after archiving it).
+    pub fn remove_selected(&mut self) -> Option<ThreadSummary> {
+        if self.selected >= self.threads.len() {
+            return None;
+        }
+        let removed = self.threads.remove(self.selected);
+        if self.selected >= self.threads.len() {
+            self.selected = self.threads.len().saturating_sub(1);
+        }
+        Some(removed)
+    }
+}
+
+/// Thread picker widget.
+pub struct ThreadPicker<'a> {
+    state: &'a ThreadPickerState,
+    theme: &'a Theme,
+    ascii_mode: bool,
+    focused: bool,
+}
+
+impl<'a> ThreadPicker<'a> {
+    /// Create a new thread picker.
+    pub fn new(state: &'a ThreadPickerState, theme: &'a Theme) -> Self {
+        Self {
+            state,
+            theme,
+            ascii_mode: false,
+            focused: false,
+        }
+    }
+
+    /// Set ASCII mode for `NO_COLOR` environments.
+    #[must_use]
+    pub fn ascii_mode(mut self, ascii: bool) -> Self {
+        self.ascii_mode = ascii;
+        self
+    }
+
+    /// Set whether this picker is focused.
+    #[must_use]
+    pub fn focused(mut self, focused: bool) -> Self {
+        self.focused = focused;
+        self
+    }
+
+    /// Footer hint span group: `[key] label`.
+    fn hint(&self, key: &'static str, label: &'static str) -> Vec<Span<'static>> {
+        vec![
+            Span::styled("[", Style::default().fg(self.theme.muted)),
+            Span::styled(key, Style::default().fg(self.theme.primary)),
+            Span::styled("] ", Style::default().fg(self.theme.muted)),
+            Span::styled(label, Style::default().fg(self.theme.subtext)),
+            Span::raw("  "),
+        ]
+    }
+}
+
+impl Widget for ThreadPicker<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let border_color = if self.focused {
+            self.theme.primary
+        } else {
+            self.theme.border
+        };
+        let block = Block::default()
+            .title(" Threads ")
+            .title_style(Style::default().fg(self.theme.text))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(border_color))
+            .style(Style::default().bg(self.theme.base));
+
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        if inner.height < 3 {
+            return;
+        }
+
+        let mut lines: Vec<Line<'_>> = vec![Line::from("")];
+
+        if self.state.threads.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "  No threads",
+                Style::default().fg(self.theme.muted),
+            )));
+        }
+
+        let (cursor, active) = if self.ascii_mode {
+            (">", "*")
+        } else {
+            ("▸", "●")
+        };
+        let title_width = inner.width.saturating_sub(34).max(10) as usize;
+
+        for (i, thread) in self.state.threads.iter().enumerate() {
+            let is_selected = i == self.state.selected;
+            let title_style = if is_selected {
+                Style::default()
+                    .fg(self.theme.text)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(self.theme.text)
+            };
+
+            let title: String = if thread.title.chars().count() > title_width {
+                let truncated: String = thread.title.chars().take(title_width - 3).collect();
+                format!("{truncated}...")
+            } else {
+                thread.title.clone()
+            };
+            let phase = match self.state.queue_positions.get(&thread.id) {
+                Some(position) => format!("{} #{position}", thread.phase),
+                None => thread.phase.clone(),
+            };
+
+            lines.push(Line::from(vec![
+                Span::styled(
+                    if is_selected {
+                        format!(" {cursor} ")
+                    } else {
+                        "   ".into()
+                    },
+                    Style::default().fg(self.theme.primary),
+                ),
+                Span::styled(
+                    if thread.is_active {
+                        format!("{active} ")
+                    } else {
+                        "  ".into()
+                    },
+                    Style::default().fg(self.theme.success),
+                ),
+                Span::styled(format!("{title:<title_width$} "), title_style),
+                Span::styled(
+                    format!("{phase:<16}"),
+                    Style::default().fg(self.theme.subtext),
+                ),
+                Span::styled(
+                    thread.updated_at.format("%Y-%m-%d").to_string(),
+                    Style::default().fg(self.theme.muted),
+                ),
+            ]));
+        }
+
+        lines.push(Line::from(""));
+
+        let mut footer = vec![Span::raw("  ")];
+        footer.extend(self.hint("Enter", "Open"));
+        footer.extend(self.hint("a", "Archive"));
+        footer.extend(self.hint("Esc", "Close"));
+        lines.push(Line::from(footer));
+
+        Paragraph::new(lines).render(inner, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(title: &str, is_active: bool) -> ThreadSummary {
+        ThreadSummary {
+            id: title.to_lowercase(),
+            title: title.to_string(),
+            phase: "Drafting".to_string(),
+            phase_category: 1,
+            updated_at: chrono::Utc::now(),
+            is_active,
+            is_archived: false,
+        }
+    }
+
+    #[test]
+    fn test_picker_selects_active_thread() {
+        let state = ThreadPickerState::new(vec![summary("One", false), summary("Two", true)]);
+        assert_eq!(state.selected, 1);
+        assert_eq!(state.selected_thread().unwrap().title, "Two");
+    }
+
+    #[test]
+    fn test_picker_navigation() {
+        let mut state = ThreadPickerState::new(vec![summary("One", false), summary("Two", false)]);
+        state.select_prev();
+        assert_eq!(state.selected, 0);
+        state.select_next();
+        state.select_next();
+        assert_eq!(state.selected, 1);
+    }
+
+    #[test]
+    fn test_picker_remove_selected() {
+        let mut state = ThreadPickerState::new(vec![summary("One", false), summary("Two", false)]);
+        state.select_next();
+        assert_eq!(state.remove_selected().unwrap().title, "Two");
+        assert_eq!(state.selected, 0);
+        assert_eq!(state.remove_selected().unwrap().title, "One");
+        assert!(state.remove_selected().is_none());
+        assert!(state.selected_thread().is_none());
+    }
+
+    #[test]
+    fn test_picker_queue_positions() {
+        let mut queue = Queue::default();
+        queue.add("two");
+        queue.add("elsewhere");
+        queue.add("one");
+        let state = ThreadPickerState::new(vec![summary("One", false), summary("Two", false)])
+            .with_queue(&queue);
+        assert_eq!(state.queue_positions.get("one"), Some(&3));
+        assert_eq!(state.queue_positions.get("two"), Some(&1));
+    }
+}